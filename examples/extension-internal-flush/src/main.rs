@@ -103,17 +103,14 @@ async fn main() -> Result<(), Error> {
 
     let handler = Arc::new(EventHandler::new(request_done_sender));
 
-    tokio::try_join!(
-        // always poll the handler function first before the flush extension,
-        // this results in a smaller future due to not needing to track which was polled first
-        // each time, and also a tiny latency savings
-        biased;
-        lambda_runtime::run(service_fn(|event| {
+    // The extension was registered above, so the runtime can start. The runtime is polled first,
+    // and the extension is dropped when the runtime stops.
+    extension
+        .run_with_runtime(lambda_runtime::run(service_fn(|event| {
             let handler = handler.clone();
             async move { handler.invoke(event).await }
-        })),
-        extension.run(),
-    )?;
+        })))
+        .await?;
 
     Ok(())
 }
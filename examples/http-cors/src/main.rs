@@ -1,7 +1,7 @@
 use lambda_http::{
-    http::Method, service_fn, tower::ServiceBuilder, tracing, Body, Error, IntoResponse, Request, RequestExt, Response,
+    http::Method, layers::CorsLayer, service_fn, tower::ServiceBuilder, tracing, Body, Error, IntoResponse, Request,
+    RequestExt, Response,
 };
-use tower_http::cors::{Any, CorsLayer};
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -11,7 +11,7 @@ async fn main() -> Result<(), Error> {
     // Define a layer to inject CORS headers
     let cors_layer = CorsLayer::new()
         .allow_methods(vec![Method::GET, Method::POST])
-        .allow_origin(Any);
+        .allow_any_origin();
 
     let handler = ServiceBuilder::new()
         // Add the CORS layer to the service
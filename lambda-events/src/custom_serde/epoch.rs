@@ -0,0 +1,217 @@
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{
+    de::{Deserializer, Error as DeError},
+    ser::Serializer,
+    Deserialize,
+};
+
+/// (De)serializes a `DateTime<Utc>` as milliseconds since the epoch, for use with
+/// `#[serde(with = "epoch_milliseconds")]`.
+///
+/// Timestamps are serialized as strings, and deserialized from either strings or numbers.
+pub mod epoch_milliseconds {
+    use super::*;
+
+    /// Serializes a `DateTime<Utc>` as a string of milliseconds since the epoch.
+    pub fn serialize<S>(date: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let ts_with_millis = date.timestamp_millis();
+        serializer.serialize_str(&ts_with_millis.to_string())
+    }
+
+    /// Deserializes a `DateTime<Utc>` from milliseconds since the epoch, given as a string or a number.
+    ///
+    /// Fractions of milliseconds are kept with nanosecond precision.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (millis, frac_nanos) = super::normalize_timestamp(deserializer)?;
+        let seconds = millis / 1000;
+        let nanos = (millis % 1000) * 1_000_000 + frac_nanos / 1_000;
+        Utc.timestamp_opt(seconds as i64, nanos as u32)
+            .latest()
+            .ok_or_else(|| D::Error::custom("invalid timestamp"))
+    }
+}
+
+/// (De)serializes a `DateTime<Utc>` as seconds since the epoch, with millisecond precision,
+/// for use with `#[serde(with = "epoch_seconds")]`.
+///
+/// Timestamps are serialized as strings, and deserialized from either strings or numbers.
+pub mod epoch_seconds {
+    use super::*;
+
+    /// Serializes a `DateTime<Utc>` as a string of seconds since the epoch, e.g. `"1507217624.302"`.
+    pub fn serialize<S>(date: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let seconds = date.timestamp();
+        let milliseconds = date.timestamp_subsec_millis();
+        let whole_seconds = seconds + (milliseconds as i64 / 1000);
+        let subsec_millis = milliseconds % 1000;
+        if milliseconds > 0 {
+            let combined = format!("{whole_seconds}.{subsec_millis:03}");
+            serializer.serialize_str(&combined)
+        } else {
+            serializer.serialize_str(&whole_seconds.to_string())
+        }
+    }
+
+    /// Deserializes a `DateTime<Utc>` from seconds since the epoch, given as a string or a number.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (seconds, frac_nanos) = super::normalize_timestamp(deserializer)?;
+        Utc.timestamp_opt(seconds as i64, frac_nanos as u32)
+            .latest()
+            .ok_or_else(|| D::Error::custom("invalid timestamp"))
+    }
+}
+
+/// Splits a timestamp into its whole units and the fraction of a unit, in billionths.
+fn normalize_timestamp<'de, D>(deserializer: D) -> Result<(u64, u64), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Int(u64),
+        Float(f64),
+    }
+
+    // Floats are formatted back to their shortest decimal representation,
+    // so the digits of the fraction are read as they were written.
+    let input = match StringOrNumber::deserialize(deserializer)? {
+        StringOrNumber::String(s) => s,
+        StringOrNumber::Int(i) => i.to_string(),
+        StringOrNumber::Float(f) => f.to_string(),
+    };
+
+    let (whole, frac) = input.split_once('.').unwrap_or((&input, ""));
+    if whole.is_empty() || !whole.bytes().all(|b| b.is_ascii_digit()) || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(D::Error::custom(format!("invalid timestamp: {input}")));
+    }
+    let whole = whole.parse::<u64>().map_err(D::Error::custom)?;
+    let frac_nanos = format!("{frac:0<9.9}").parse::<u64>().map_err(D::Error::custom)?;
+    Ok((whole, frac_nanos))
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod test {
+    use super::*;
+    use serde::Serialize;
+
+    #[test]
+    fn test_deserialize_milliseconds() {
+        #[derive(Deserialize)]
+        struct Test {
+            #[serde(deserialize_with = "epoch_milliseconds::deserialize")]
+            v: DateTime<Utc>,
+        }
+        let expected = Utc.ymd(2017, 10, 5).and_hms_nano(15, 33, 44, 302_000_000);
+
+        // Test parsing strings.
+        let data = serde_json::json!({
+            "v": "1507217624302",
+        });
+        let decoded: Test = serde_json::from_value(data).unwrap();
+        assert_eq!(expected, decoded.v,);
+        // Test parsing ints.
+        let decoded: Test = serde_json::from_slice(r#"{"v":1507217624302}"#.as_bytes()).unwrap();
+        assert_eq!(expected, decoded.v,);
+        // Test parsing floats.
+        let data = serde_json::json!({
+            "v": 1507217624302.0,
+        });
+        let decoded: Test = serde_json::from_value(data).unwrap();
+        assert_eq!(expected, decoded.v,);
+    }
+
+    #[test]
+    fn test_deserialize_fractional_milliseconds() {
+        #[derive(Deserialize)]
+        struct Test {
+            #[serde(deserialize_with = "epoch_milliseconds::deserialize")]
+            v: DateTime<Utc>,
+        }
+        let expected = Utc.ymd(2017, 10, 5).and_hms_nano(15, 33, 44, 302_500_000);
+
+        let decoded: Test = serde_json::from_slice(r#"{"v":1507217624302.5}"#.as_bytes()).unwrap();
+        assert_eq!(expected, decoded.v);
+        let decoded: Test = serde_json::from_slice(r#"{"v":"1507217624302.5"}"#.as_bytes()).unwrap();
+        assert_eq!(expected, decoded.v);
+
+        let err = serde_json::from_slice::<Test>(r#"{"v":"15072.1.7"}"#.as_bytes())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("invalid timestamp"));
+    }
+
+    #[test]
+    fn test_deserialize_seconds() {
+        #[derive(Deserialize)]
+        struct Test {
+            #[serde(deserialize_with = "epoch_seconds::deserialize")]
+            v: DateTime<Utc>,
+        }
+
+        let decoded: Test = serde_json::from_slice(r#"{"v":"1507217624.3"}"#.as_bytes()).unwrap();
+        assert_eq!(Utc.ymd(2017, 10, 5).and_hms_nano(15, 33, 44, 300_000_000), decoded.v);
+        let decoded: Test = serde_json::from_slice(r#"{"v":1507217624.302}"#.as_bytes()).unwrap();
+        assert_eq!(Utc.ymd(2017, 10, 5).and_hms_nano(15, 33, 44, 302_000_000), decoded.v);
+        let decoded: Test = serde_json::from_slice(r#"{"v":1507217624}"#.as_bytes()).unwrap();
+        assert_eq!(Utc.ymd(2017, 10, 5).and_hms(15, 33, 44), decoded.v);
+    }
+
+    #[test]
+    fn test_serialize_milliseconds() {
+        #[derive(Serialize)]
+        struct Test {
+            #[serde(serialize_with = "epoch_milliseconds::serialize")]
+            v: DateTime<Utc>,
+        }
+        let instance = Test {
+            v: Utc.ymd(1983, 7, 22).and_hms_nano(1, 0, 0, 99_888_777),
+        };
+        let encoded = serde_json::to_string(&instance).unwrap();
+        assert_eq!(encoded, String::from(r#"{"v":"427683600099"}"#));
+    }
+
+    #[test]
+    fn test_serialize_seconds() {
+        #[derive(Serialize)]
+        struct Test {
+            #[serde(serialize_with = "epoch_seconds::serialize")]
+            v: DateTime<Utc>,
+        }
+
+        // Make sure nanoseconds are chopped off.
+        let instance = Test {
+            v: Utc.ymd(1983, 7, 22).and_hms_nano(1, 0, 0, 99),
+        };
+        let encoded = serde_json::to_string(&instance).unwrap();
+        assert_eq!(encoded, String::from(r#"{"v":"427683600"}"#));
+
+        // Make sure milliseconds are included.
+        let instance = Test {
+            v: Utc.ymd(1983, 7, 22).and_hms_nano(1, 0, 0, 2_000_000),
+        };
+        let encoded = serde_json::to_string(&instance).unwrap();
+        assert_eq!(encoded, String::from(r#"{"v":"427683600.002"}"#));
+
+        // Make sure leap seconds are included.
+        let instance = Test {
+            v: Utc.ymd(1983, 7, 22).and_hms_nano(23, 59, 59, 1_999_999_999),
+        };
+        let encoded = serde_json::to_string(&instance).unwrap();
+        assert_eq!(encoded, String::from(r#"{"v":"427766400.999"}"#));
+    }
+}
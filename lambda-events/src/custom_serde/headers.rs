@@ -6,7 +6,7 @@ use serde::{
 use std::{borrow::Cow, fmt};
 
 /// Serialize a http::HeaderMap into a serde str => `Vec<str>` map
-pub(crate) fn serialize_multi_value_headers<S>(headers: &HeaderMap, serializer: S) -> Result<S::Ok, S::Error>
+pub fn serialize_multi_value_headers<S>(headers: &HeaderMap, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
@@ -22,7 +22,7 @@ where
 }
 
 /// Serialize a http::HeaderMap into a serde str => str map
-pub(crate) fn serialize_headers<S>(headers: &HeaderMap, serializer: S) -> Result<S::Ok, S::Error>
+pub fn serialize_headers<S>(headers: &HeaderMap, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
@@ -36,8 +36,7 @@ where
 
 /// Serialize a HeaderMap with multiple values per header combined as comma-separated strings.
 /// Used by VPC Lattice V1 which expects multi-value headers as "value1, value2".
-#[cfg(feature = "vpc_lattice")]
-pub(crate) fn serialize_comma_separated_headers<S>(headers: &HeaderMap, serializer: S) -> Result<S::Ok, S::Error>
+pub fn serialize_comma_separated_headers<S>(headers: &HeaderMap, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
@@ -172,8 +171,9 @@ where
     Ok(())
 }
 
-/// Implementation detail.
-pub(crate) fn deserialize_headers<'de, D>(de: D) -> Result<HeaderMap, D::Error>
+/// Deserialize a serde str => str or str => `Vec<str>` map into a http::HeaderMap,
+/// mapping JSON `null` to an empty map.
+pub fn deserialize_headers<'de, D>(de: D) -> Result<HeaderMap, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -186,8 +186,7 @@ where
 
 /// Deserialize headers, splitting comma-separated values into multiple header values.
 /// Used by VPC Lattice V1 which sends multi-value headers as "value1, value2".
-#[cfg(feature = "vpc_lattice")]
-pub(crate) fn deserialize_comma_separated_headers<'de, D>(de: D) -> Result<HeaderMap, D::Error>
+pub fn deserialize_comma_separated_headers<'de, D>(de: D) -> Result<HeaderMap, D::Error>
 where
     D: Deserializer<'de>,
 {
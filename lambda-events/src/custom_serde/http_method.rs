@@ -5,6 +5,7 @@ use serde::{
 };
 use std::fmt;
 
+/// Serializes a `Method` as its string representation.
 pub fn serialize<S: Serializer>(method: &Method, ser: S) -> Result<S::Ok, S::Error> {
     ser.serialize_str(method.as_str())
 }
@@ -27,6 +28,7 @@ impl Visitor<'_> for MethodVisitor {
     }
 }
 
+/// Deserializes a `Method` from its string representation.
 pub fn deserialize<'de, D>(de: D) -> Result<Method, D::Error>
 where
     D: Deserializer<'de>,
@@ -34,6 +36,7 @@ where
     de.deserialize_str(MethodVisitor)
 }
 
+/// Deserializes an optional `Method`, mapping JSON `null` to `None`.
 pub fn deserialize_optional<'de, D>(deserializer: D) -> Result<Option<Method>, D::Error>
 where
     D: Deserializer<'de>,
@@ -47,6 +50,7 @@ where
     Ok(None)
 }
 
+/// Serializes an optional `Method`, writing `null` for `None`.
 pub fn serialize_optional<S: Serializer>(method: &Option<Method>, ser: S) -> Result<S::Ok, S::Error> {
     if let Some(method) = method {
         return serialize(method, ser);
@@ -1,3 +1,26 @@
+//! Serde helpers used by the event definitions in this crate.
+//!
+//! They are public so custom event types, like the `detail` of an EventBridge
+//! event, can decode fields the same way AWS encodes them:
+//!
+//! ```
+//! use aws_lambda_events::custom_serde::{deserialize_base64, deserialize_lambda_map, serialize_base64};
+//! use serde::{Deserialize, Serialize};
+//! use std::collections::HashMap;
+//!
+//! #[derive(Deserialize, Serialize)]
+//! #[serde(rename_all = "camelCase")]
+//! struct OrderPlaced {
+//!     #[serde(deserialize_with = "deserialize_base64", serialize_with = "serialize_base64")]
+//!     payload: Vec<u8>,
+//!     #[serde(default, deserialize_with = "deserialize_lambda_map")]
+//!     tags: HashMap<String, String>,
+//! }
+//!
+//! let order: OrderPlaced = serde_json::from_str(r#"{"payload": "aGVsbG8=", "tags": null}"#).unwrap();
+//! assert_eq!(b"hello".as_slice(), order.payload.as_slice());
+//! assert!(order.tags.is_empty());
+//! ```
 use base64::Engine;
 use serde::{
     de::{Deserialize, Deserializer, Error as DeError},
@@ -11,37 +34,41 @@ pub(crate) mod codebuild_time;
 #[cfg_attr(docsrs, doc(cfg(feature = "codebuild")))]
 pub type CodeBuildNumber = f32;
 
-#[cfg(any(
-    feature = "alb",
-    feature = "apigw",
-    feature = "s3",
-    feature = "iot",
-    feature = "lambda_function_urls",
-    feature = "vpc_lattice"
-))]
-mod headers;
-#[cfg(any(
-    feature = "alb",
-    feature = "apigw",
-    feature = "s3",
-    feature = "iot",
-    feature = "lambda_function_urls",
-    feature = "vpc_lattice"
-))]
-pub(crate) use self::headers::*;
-
-#[cfg(feature = "dynamodb")]
-pub(crate) mod float_unix_epoch;
-
-#[cfg(any(feature = "alb", feature = "apigw", feature = "vpc_lattice"))]
-pub(crate) mod http_method;
+#[cfg(feature = "chrono")]
+mod epoch;
+#[cfg(feature = "chrono")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+pub use self::epoch::{epoch_milliseconds, epoch_seconds};
 
-#[cfg(feature = "alb")]
+#[cfg(feature = "http")]
+mod headers;
+#[cfg(feature = "http")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+pub use self::headers::*;
+
+/// (De)serializes a `DateTime<Utc>` as a float number of seconds since the epoch, like DynamoDB streams do.
+#[cfg(feature = "chrono")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+pub mod float_unix_epoch;
+
+/// (De)serializes an `http::Method`, for use with `#[serde(with = "http_method")]`.
+#[cfg(feature = "http")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+pub mod http_method;
+
+/// (De)serializes a number as a string, for use with `#[serde(with = "stringified_number")]`.
+///
+/// Numbers are serialized as strings, and deserialized from either strings or numbers.
+pub mod stringified_number;
+
+#[cfg(feature = "query_map")]
 mod query_string_parameters;
-#[cfg(feature = "alb")]
-pub(crate) use self::query_string_parameters::*;
+#[cfg(feature = "query_map")]
+#[cfg_attr(docsrs, doc(cfg(feature = "query_map")))]
+pub use self::query_string_parameters::*;
 
-pub(crate) fn deserialize_base64<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+/// Deserializes a base64 encoded string into its raw bytes.
+pub fn deserialize_base64<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -51,7 +78,8 @@ where
         .map_err(DeError::custom)
 }
 
-pub(crate) fn serialize_base64<S>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+/// Serializes raw bytes into a base64 encoded string.
+pub fn serialize_base64<S>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
@@ -59,7 +87,7 @@ where
 }
 
 /// Deserializes `HashMap<_>`, mapping JSON `null` to an empty map.
-pub(crate) fn deserialize_lambda_map<'de, D, K, V>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+pub fn deserialize_lambda_map<'de, D, K, V>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
 where
     D: Deserializer<'de>,
     K: serde::Deserialize<'de>,
@@ -72,9 +100,10 @@ where
     Ok(opt.unwrap_or_default())
 }
 
-#[cfg(feature = "dynamodb")]
 /// Deserializes `Item`, mapping JSON `null` to an empty item.
-pub(crate) fn deserialize_lambda_dynamodb_item<'de, D>(deserializer: D) -> Result<serde_dynamo::Item, D::Error>
+#[cfg(feature = "dynamodb")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dynamodb")))]
+pub fn deserialize_lambda_dynamodb_item<'de, D>(deserializer: D) -> Result<serde_dynamo::Item, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -83,18 +112,8 @@ where
     Ok(opt.unwrap_or_default())
 }
 
-/// Deserializes `HashMap<_>`, mapping JSON `null` to an empty map.
-#[cfg(any(
-    feature = "alb",
-    feature = "apigw",
-    feature = "cloudwatch_events",
-    feature = "code_commit",
-    feature = "cognito",
-    feature = "sns",
-    feature = "vpc_lattice",
-    test
-))]
-pub(crate) fn deserialize_nullish_boolean<'de, D>(deserializer: D) -> Result<bool, D::Error>
+/// Deserializes `bool`, mapping JSON `null` to `false`.
+pub fn deserialize_nullish_boolean<'de, D>(deserializer: D) -> Result<bool, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -0,0 +1,63 @@
+use serde::{
+    de::{Deserializer, Error as DeError},
+    ser::Serializer,
+    Deserialize,
+};
+use std::{fmt::Display, str::FromStr};
+
+/// Serializes a number as a string, the way AWS sends numbers in many string attributes.
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Display,
+    S: Serializer,
+{
+    serializer.collect_str(value)
+}
+
+/// Deserializes a number from a string, or from a JSON number.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr + Deserialize<'de>,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber<T> {
+        String(String),
+        Number(T),
+    }
+
+    match StringOrNumber::<T>::deserialize(deserializer)? {
+        StringOrNumber::String(s) => s.trim().parse::<T>().map_err(DeError::custom),
+        StringOrNumber::Number(n) => Ok(n),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Test {
+        #[serde(with = "super")]
+        count: u32,
+        #[serde(with = "super")]
+        ratio: f64,
+    }
+
+    #[test]
+    fn test_deserialize_stringified_numbers() {
+        let decoded: Test = serde_json::from_str(r#"{"count": "3", "ratio": "0.5"}"#).unwrap();
+        assert_eq!(Test { count: 3, ratio: 0.5 }, decoded);
+        let decoded: Test = serde_json::from_str(r#"{"count": 3, "ratio": 0.5}"#).unwrap();
+        assert_eq!(Test { count: 3, ratio: 0.5 }, decoded);
+        assert!(serde_json::from_str::<Test>(r#"{"count": "three", "ratio": 0.5}"#).is_err());
+    }
+
+    #[test]
+    fn test_serialize_stringified_numbers() {
+        let encoded = serde_json::to_string(&Test { count: 3, ratio: 0.5 }).unwrap();
+        assert_eq!(r#"{"count":"3","ratio":"0.5"}"#, encoded);
+    }
+}
@@ -0,0 +1,205 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, ops::Deref, str::FromStr};
+
+/// An Amazon Resource Name, parsed into its components.
+///
+/// The format is `arn:partition:service:region:account-id:resource`. The region and
+/// the account id are empty for global resources, like S3 buckets or IAM roles.
+/// The resource keeps any `:` or `/` separators it contains.
+///
+/// See <https://docs.aws.amazon.com/IAM/latest/UserGuide/reference-arns.html>
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Arn {
+    value: String,
+    // Byte offsets of the `:` separators after `arn`, partition, service, region and account id.
+    separators: [usize; 5],
+}
+
+impl Arn {
+    /// The partition the resource is in, e.g. `aws` or `aws-cn`.
+    pub fn partition(&self) -> &str {
+        self.component(0)
+    }
+
+    /// The service namespace that identifies the AWS product, e.g. `sqs`.
+    pub fn service(&self) -> &str {
+        self.component(1)
+    }
+
+    /// The region the resource resides in, `None` for global resources.
+    pub fn region(&self) -> Option<&str> {
+        Some(self.component(2)).filter(|region| !region.is_empty())
+    }
+
+    /// The id of the account that owns the resource, `None` for resources that don't require one.
+    pub fn account_id(&self) -> Option<&str> {
+        Some(self.component(3)).filter(|account_id| !account_id.is_empty())
+    }
+
+    /// The resource identifier, including its type prefix if it has one, e.g. `table/orders/stream/2024`.
+    pub fn resource(&self) -> &str {
+        &self.value[self.separators[4] + 1..]
+    }
+
+    /// The full ARN.
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    fn component(&self, index: usize) -> &str {
+        &self.value[self.separators[index] + 1..self.separators[index + 1]]
+    }
+}
+
+impl FromStr for Arn {
+    type Err = ArnError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.to_string().try_into()
+    }
+}
+
+impl TryFrom<String> for Arn {
+    type Error = ArnError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        if !value.starts_with("arn:") {
+            return Err(ArnError::new(value, "missing `arn:` prefix"));
+        }
+
+        let mut separators = [0; 5];
+        let mut indices = value.match_indices(':').map(|(i, _)| i);
+        for separator in separators.iter_mut() {
+            match indices.next() {
+                Some(i) => *separator = i,
+                None => return Err(ArnError::new(value, "expected six `:` separated components")),
+            }
+        }
+
+        let arn = Arn { value, separators };
+        if arn.partition().is_empty() {
+            return Err(ArnError::new(arn.value, "empty partition"));
+        }
+        if arn.service().is_empty() {
+            return Err(ArnError::new(arn.value, "empty service"));
+        }
+        if arn.resource().is_empty() {
+            return Err(ArnError::new(arn.value, "empty resource"));
+        }
+        Ok(arn)
+    }
+}
+
+impl From<Arn> for String {
+    fn from(arn: Arn) -> Self {
+        arn.value
+    }
+}
+
+impl Deref for Arn {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl AsRef<str> for Arn {
+    fn as_ref(&self) -> &str {
+        &self.value
+    }
+}
+
+impl fmt::Display for Arn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+/// Error returned when a string is not a valid [`Arn`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArnError {
+    value: String,
+    reason: &'static str,
+}
+
+impl ArnError {
+    fn new(value: String, reason: &'static str) -> Self {
+        ArnError { value, reason }
+    }
+}
+
+impl fmt::Display for ArnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid ARN `{}`: {}", self.value, self.reason)
+    }
+}
+
+impl std::error::Error for ArnError {}
+
+/// Parses an optional ARN field, keeping `None` when the field is not present.
+#[cfg(any(feature = "dynamodb", feature = "kinesis", feature = "s3", feature = "sqs"))]
+pub(crate) fn parse_optional(value: Option<&str>) -> Result<Option<Arn>, ArnError> {
+    value.map(str::parse).transpose()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_regional_arn() {
+        let arn: Arn = "arn:aws:sqs:us-east-2:123456789012:my-queue".parse().unwrap();
+        assert_eq!("aws", arn.partition());
+        assert_eq!("sqs", arn.service());
+        assert_eq!(Some("us-east-2"), arn.region());
+        assert_eq!(Some("123456789012"), arn.account_id());
+        assert_eq!("my-queue", arn.resource());
+        assert_eq!("arn:aws:sqs:us-east-2:123456789012:my-queue", arn.to_string());
+    }
+
+    #[test]
+    fn parse_global_arn() {
+        let arn: Arn = "arn:aws:s3:::my-bucket/path/to:object".parse().unwrap();
+        assert_eq!("s3", arn.service());
+        assert_eq!(None, arn.region());
+        assert_eq!(None, arn.account_id());
+        assert_eq!("my-bucket/path/to:object", arn.resource());
+    }
+
+    #[test]
+    fn parse_resource_with_separators() {
+        let arn: Arn = "arn:aws-cn:dynamodb:cn-north-1:123456789012:table/orders/stream/2024-01-01T00:00:00.000"
+            .parse()
+            .unwrap();
+        assert_eq!("aws-cn", arn.partition());
+        assert_eq!("table/orders/stream/2024-01-01T00:00:00.000", arn.resource());
+    }
+
+    #[test]
+    fn reject_invalid_arns() {
+        for value in [
+            "",
+            "arn",
+            "urn:aws:sqs:us-east-2:123456789012:my-queue",
+            "arn:aws:sqs:us-east-2:123456789012",
+            "arn::sqs:us-east-2:123456789012:my-queue",
+            "arn:aws::us-east-2:123456789012:my-queue",
+            "arn:aws:sqs:us-east-2:123456789012:",
+        ] {
+            assert!(value.parse::<Arn>().is_err(), "{value} should be rejected");
+        }
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let arn: Arn = serde_json::from_str(r#""arn:aws:sns:us-east-2:123456789012:my-topic""#).unwrap();
+        assert_eq!("sns", arn.service());
+        let output = serde_json::to_string(&arn).unwrap();
+        assert_eq!(r#""arn:aws:sns:us-east-2:123456789012:my-topic""#, output);
+
+        let err = serde_json::from_str::<Arn>(r#""my-topic""#).unwrap_err();
+        assert!(err.to_string().contains("missing `arn:` prefix"));
+    }
+}
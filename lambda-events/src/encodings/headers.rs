@@ -0,0 +1,76 @@
+use http::HeaderMap;
+
+/// HTTP events that carry their headers in both a `headers` and a `multiValueHeaders` field.
+///
+/// AWS fills those fields differently depending on the integration. API Gateway REST and
+/// WebSocket APIs send both, with only the last value of each header in `headers`, while
+/// ALB sends one or the other depending on whether multi-value headers are enabled on
+/// the target group. This trait gives a single view of the headers regardless of which
+/// fields were present, and fills the fields the way the integration expects them.
+pub trait MultiValueHeaders {
+    /// Returns all the headers of the event, merging `headers` into `multi_value_headers`.
+    fn merged_headers(&self) -> HeaderMap;
+
+    /// Replaces the headers of the event, filling `headers` and `multi_value_headers`
+    /// as the integration expects them.
+    fn set_merged_headers(&mut self, headers: HeaderMap);
+}
+
+/// Merges a `headers` map into a `multiValueHeaders` map.
+///
+/// Headers present in `multi_value_headers` keep all their values. Headers only present
+/// in `headers` are added, so no header is lost when only one of the fields was sent.
+pub fn merge_multi_value_headers(headers: &HeaderMap, multi_value_headers: &HeaderMap) -> HeaderMap {
+    let mut merged = multi_value_headers.clone();
+    for (key, value) in headers {
+        if !multi_value_headers.contains_key(key) {
+            merged.append(key, value.clone());
+        }
+    }
+    merged
+}
+
+/// Keeps only the last value of each header, which is what API Gateway sends in the
+/// `headers` field when a header has multiple values.
+pub fn last_header_values(headers: &HeaderMap) -> HeaderMap {
+    let mut last = HeaderMap::with_capacity(headers.keys_len());
+    for key in headers.keys() {
+        if let Some(value) = headers.get_all(key).iter().next_back() {
+            last.insert(key, value.clone());
+        }
+    }
+    last
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use http::HeaderValue;
+
+    #[test]
+    fn merge_keeps_all_multi_values() {
+        let mut headers = HeaderMap::new();
+        headers.insert("accept", HeaderValue::from_static("text/html"));
+        headers.insert("host", HeaderValue::from_static("example.com"));
+
+        let mut multi_value_headers = HeaderMap::new();
+        multi_value_headers.append("accept", HeaderValue::from_static("application/json"));
+        multi_value_headers.append("accept", HeaderValue::from_static("text/html"));
+
+        let merged = merge_multi_value_headers(&headers, &multi_value_headers);
+        let accept: Vec<_> = merged.get_all("accept").iter().collect();
+        assert_eq!(vec!["application/json", "text/html"], accept);
+        assert_eq!("example.com", merged["host"]);
+    }
+
+    #[test]
+    fn last_values() {
+        let mut headers = HeaderMap::new();
+        headers.append("accept", HeaderValue::from_static("application/json"));
+        headers.append("accept", HeaderValue::from_static("text/html"));
+
+        let last = last_header_values(&headers);
+        assert_eq!(1, last.get_all("accept").iter().count());
+        assert_eq!("text/html", last["accept"]);
+    }
+}
@@ -2,7 +2,7 @@ use base64::display::Base64Display;
 use bytes::Bytes;
 use http_body::{Body as HttpBody, SizeHint};
 use serde::{
-    de::{Deserialize, Deserializer, Error as DeError, Visitor},
+    de::{Deserialize, DeserializeOwned, Deserializer, Error as DeError, Visitor},
     ser::{Error as SerError, Serialize, Serializer},
 };
 use std::{borrow::Cow, mem::take, ops::Deref, pin::Pin, task::Poll};
@@ -35,7 +35,9 @@ use std::{borrow::Cow, mem::take, ops::Deref, pin::Pin, task::Poll};
 ///
 /// ## Binary
 ///
-/// Types like `Vec<u8>` and `&[u8]` whose types reflect raw bytes produce `Body::Binary` variants
+/// Types like `Vec<u8>`, `Bytes`, and `&[u8]` whose types reflect raw bytes produce `Body::Binary` variants.
+/// Binary data is backed by [`Bytes`], so converting from `Vec<u8>` or `Bytes`, and back into `Bytes`,
+/// doesn't copy the payload
 ///
 /// ```
 /// assert!(match aws_lambda_events::encodings::Body::from("text".as_bytes()) {
@@ -69,7 +71,7 @@ pub enum Body {
     /// A body containing string data
     Text(String),
     /// A body containing binary data
-    Binary(Vec<u8>),
+    Binary(Bytes),
 }
 
 impl Body {
@@ -92,6 +94,39 @@ impl Body {
             Body::from(body)
         }
     }
+
+    /// Returns the body as [`Bytes`].
+    ///
+    /// This is a cheap reference count increment for binary bodies, text bodies are copied.
+    pub fn to_bytes(&self) -> Bytes {
+        match self {
+            Body::Empty => Bytes::new(),
+            Body::Text(s) => Bytes::copy_from_slice(s.as_bytes()),
+            Body::Binary(b) => b.clone(),
+        }
+    }
+
+    /// Consumes the body and returns it as [`Bytes`], without copying.
+    pub fn into_bytes(self) -> Bytes {
+        match self {
+            Body::Empty => Bytes::new(),
+            Body::Text(s) => s.into(),
+            Body::Binary(b) => b,
+        }
+    }
+
+    /// Deserializes the body as JSON.
+    ///
+    /// ```
+    /// use aws_lambda_events::encodings::Body;
+    ///
+    /// let body = Body::from(r#"{"id": 5}"#);
+    /// let value: serde_json::Value = body.from_json().unwrap();
+    /// assert_eq!(5, value["id"]);
+    /// ```
+    pub fn from_json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(self.as_ref())
+    }
 }
 
 impl From<()> for Body {
@@ -126,7 +161,7 @@ impl From<Cow<'static, [u8]>> for Body {
     #[inline]
     fn from(cow: Cow<'static, [u8]>) -> Body {
         match cow {
-            Cow::Borrowed(b) => Body::from(b),
+            Cow::Borrowed(b) => Body::Binary(Bytes::from_static(b)),
             Cow::Owned(o) => Body::from(o),
         }
     }
@@ -134,13 +169,25 @@ impl From<Cow<'static, [u8]>> for Body {
 
 impl From<Vec<u8>> for Body {
     fn from(b: Vec<u8>) -> Self {
+        Body::Binary(b.into())
+    }
+}
+
+impl From<Bytes> for Body {
+    fn from(b: Bytes) -> Self {
         Body::Binary(b)
     }
 }
 
 impl<'a> From<&'a [u8]> for Body {
     fn from(b: &'a [u8]) -> Self {
-        Body::Binary(b.to_vec())
+        Body::Binary(Bytes::copy_from_slice(b))
+    }
+}
+
+impl From<Body> for Bytes {
+    fn from(body: Body) -> Self {
+        body.into_bytes()
     }
 }
 
@@ -241,7 +288,7 @@ impl HttpBody for Body {
         Poll::Ready(match body {
             Body::Empty => None,
             Body::Text(s) => Some(Ok(http_body::Frame::data(s.into()))),
-            Body::Binary(b) => Some(Ok(http_body::Frame::data(b.into()))),
+            Body::Binary(b) => Some(Ok(http_body::Frame::data(b))),
         })
     }
 }
@@ -301,6 +348,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn from_bytes_without_copy() {
+        let bytes = Bytes::from(vec![1, 2, 3]);
+        let body = Body::from(bytes.clone());
+        assert_eq!(bytes.as_ptr(), body.to_bytes().as_ptr());
+        assert_eq!(bytes.as_ptr(), Bytes::from(body).as_ptr());
+    }
+
+    #[test]
+    fn text_into_bytes() {
+        assert_eq!(Bytes::from_static(b"foo"), Body::from("foo").into_bytes());
+        assert_eq!(Bytes::new(), Body::Empty.to_bytes());
+    }
+
+    #[test]
+    fn from_json() {
+        #[derive(serde::Deserialize)]
+        struct Test {
+            id: u32,
+        }
+        let test: Test = Body::from(r#"{"id": 5}"#.as_bytes()).from_json().unwrap();
+        assert_eq!(5, test.id);
+        assert!(Body::from("not json").from_json::<Test>().is_err());
+    }
+
     #[test]
     fn serialize_text() {
         let mut map = HashMap::new();
@@ -330,7 +402,7 @@ mod tests {
         }
 
         match Body::from_maybe_encoded(true, "Zm9v") {
-            Body::Binary(b) => assert_eq!(&[102, 111, 111], b.as_slice()),
+            Body::Binary(b) => assert_eq!(&[102, 111, 111], b.as_ref()),
             not => panic!("expected Body::Text(...) got {not:?}"),
         }
     }
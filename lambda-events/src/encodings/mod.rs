@@ -1,18 +1,35 @@
 use serde::{Deserialize, Serialize};
 use std::ops::{Deref, DerefMut};
 
+#[cfg(feature = "arn")]
+mod arn;
+mod query;
 #[cfg(feature = "chrono")]
 mod time;
 use crate::custom_serde::{deserialize_base64, serialize_base64};
 
+#[cfg(all(
+    feature = "arn",
+    any(feature = "dynamodb", feature = "kinesis", feature = "s3", feature = "sqs")
+))]
+pub(crate) use self::arn::parse_optional as parse_optional_arn;
+#[cfg(feature = "arn")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arn")))]
+pub use self::arn::{Arn, ArnError};
 #[cfg(feature = "chrono")]
 #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
 pub use self::time::*;
 #[cfg(feature = "http")]
+mod headers;
+#[cfg(feature = "http")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+pub use self::headers::*;
+#[cfg(feature = "http")]
 mod http;
 #[cfg(feature = "http")]
 #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
 pub use self::http::*;
+pub use self::query::{QueryError, QueryMapExt};
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
 
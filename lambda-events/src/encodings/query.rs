@@ -0,0 +1,376 @@
+#[cfg(feature = "query_map")]
+use query_map::QueryMap;
+use serde::de::{self, value::SeqDeserializer, DeserializeOwned, Deserializer, IntoDeserializer, MapAccess, Visitor};
+use std::{collections::HashMap, fmt};
+
+/// Query string parameters of HTTP events, with lookups and typed extraction.
+///
+/// Implemented for the [`QueryMap`] used by API Gateway and ALB events,
+/// and for the `HashMap<String, String>` used by Lambda Function URL events.
+///
+/// [`QueryMap`]: query_map::QueryMap
+pub trait QueryMapExt {
+    /// Returns every key with all its values.
+    fn entries(&self) -> Vec<(&str, Vec<&str>)>;
+
+    /// Returns all the values of a key, or an empty list if the key is not present.
+    fn get_all(&self, key: &str) -> Vec<&str>;
+
+    /// Returns all the values of a key, comparing keys ASCII case-insensitively.
+    fn get_all_ignore_case(&self, key: &str) -> Vec<&str> {
+        self.entries()
+            .into_iter()
+            .filter(|(k, _)| k.eq_ignore_ascii_case(key))
+            .flat_map(|(_, values)| values)
+            .collect()
+    }
+
+    /// Returns the first value of a key, comparing keys ASCII case-insensitively.
+    fn get_ignore_case(&self, key: &str) -> Option<&str> {
+        self.get_all_ignore_case(key).into_iter().next()
+    }
+
+    /// Deserializes the parameters into `T`.
+    ///
+    /// Values are parsed into the field types, fields of sequence types receive
+    /// every value of their key, and other fields receive the first one.
+    ///
+    /// ```
+    /// use aws_lambda_events::encodings::QueryMapExt;
+    /// use std::collections::HashMap;
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Pagination {
+    ///     page: u32,
+    ///     size: Option<u32>,
+    /// }
+    ///
+    /// let params = HashMap::from([("page".to_string(), "2".to_string())]);
+    /// let pagination: Pagination = params.to_struct().unwrap();
+    /// assert_eq!(2, pagination.page);
+    /// assert_eq!(None, pagination.size);
+    /// ```
+    fn to_struct<T: DeserializeOwned>(&self) -> Result<T, QueryError> {
+        T::deserialize(EntriesDeserializer(self.entries()))
+    }
+}
+
+#[cfg(feature = "query_map")]
+#[cfg_attr(docsrs, doc(cfg(feature = "query_map")))]
+impl QueryMapExt for QueryMap {
+    fn entries(&self) -> Vec<(&str, Vec<&str>)> {
+        let mut entries: Vec<(&str, Vec<&str>)> = Vec::new();
+        for (key, value) in self.iter() {
+            match entries.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, values)) => values.push(value),
+                None => entries.push((key, vec![value])),
+            }
+        }
+        entries
+    }
+
+    fn get_all(&self, key: &str) -> Vec<&str> {
+        self.all(key).unwrap_or_default()
+    }
+}
+
+impl QueryMapExt for HashMap<String, String> {
+    fn entries(&self) -> Vec<(&str, Vec<&str>)> {
+        self.iter().map(|(k, v)| (k.as_str(), vec![v.as_str()])).collect()
+    }
+
+    fn get_all(&self, key: &str) -> Vec<&str> {
+        self.get(key).map(|v| vec![v.as_str()]).unwrap_or_default()
+    }
+}
+
+/// Error returned when query string parameters can't be deserialized into a type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QueryError(String);
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl de::Error for QueryError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        QueryError(msg.to_string())
+    }
+}
+
+struct EntriesDeserializer<'a>(Vec<(&'a str, Vec<&'a str>)>);
+
+impl<'de> Deserializer<'de> for EntriesDeserializer<'_> {
+    type Error = QueryError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(EntriesAccess {
+            entries: self.0.into_iter(),
+            values: None,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct EntriesAccess<'a> {
+    entries: std::vec::IntoIter<(&'a str, Vec<&'a str>)>,
+    values: Option<Vec<&'a str>>,
+}
+
+impl<'de> MapAccess<'de> for EntriesAccess<'_> {
+    type Error = QueryError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        match self.entries.next() {
+            Some((key, values)) => {
+                self.values = Some(values);
+                seed.deserialize(key.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        let values = self.values.take().unwrap_or_default();
+        seed.deserialize(ValuesDeserializer(values))
+    }
+}
+
+// All the values of a key. Sequences take every value, anything else takes the first one.
+struct ValuesDeserializer<'a>(Vec<&'a str>);
+
+impl<'a> ValuesDeserializer<'a> {
+    fn first(self) -> Result<ValueDeserializer<'a>, QueryError> {
+        self.0
+            .into_iter()
+            .next()
+            .map(ValueDeserializer)
+            .ok_or_else(|| QueryError("missing value".to_string()))
+    }
+}
+
+macro_rules! forward_to_first {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.first()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for ValuesDeserializer<'_> {
+    type Error = QueryError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.0.len() == 1 {
+            self.first()?.deserialize_any(visitor)
+        } else {
+            self.deserialize_seq(visitor)
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.0.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(SeqDeserializer::new(self.0.into_iter().map(ValueDeserializer)))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.first()?.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_first! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_unit deserialize_identifier
+    }
+
+    serde::forward_to_deserialize_any! {
+        unit_struct tuple_struct map struct
+    }
+}
+
+// A single value, parsed into the type the visitor asks for.
+struct ValueDeserializer<'a>(&'a str);
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self.0.parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(err) => Err(QueryError(format!("invalid value `{}`: {}", self.0, err))),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> IntoDeserializer<'de, QueryError> for ValueDeserializer<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'_> {
+    type Error = QueryError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.0)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    serde::forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Order {
+        Asc,
+        Desc,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Search {
+        q: String,
+        page: u32,
+        #[serde(default)]
+        exact: bool,
+        order: Option<Order>,
+        #[serde(default)]
+        tag: Vec<String>,
+    }
+
+    #[test]
+    #[cfg(feature = "query_map")]
+    fn query_map_to_struct() {
+        let params: QueryMap = "q=lambda&page=2&order=desc&tag=rust&tag=aws".parse().unwrap();
+        let search: Search = params.to_struct().unwrap();
+        assert_eq!(
+            Search {
+                q: "lambda".to_string(),
+                page: 2,
+                exact: false,
+                order: Some(Order::Desc),
+                tag: vec!["rust".to_string(), "aws".to_string()],
+            },
+            search
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "query_map")]
+    fn query_map_lookups() {
+        let params: QueryMap = "Page=2&tag=rust&tag=aws".parse().unwrap();
+        assert_eq!(vec!["rust", "aws"], params.get_all("tag"));
+        assert!(params.get_all("page").is_empty());
+        assert_eq!(Some("2"), params.get_ignore_case("page"));
+    }
+
+    #[test]
+    fn hash_map_to_struct() {
+        let params = HashMap::from([
+            ("q".to_string(), "lambda".to_string()),
+            ("page".to_string(), "1".to_string()),
+            ("exact".to_string(), "true".to_string()),
+        ]);
+        let search: Search = params.to_struct().unwrap();
+        assert!(search.exact);
+        assert_eq!(vec!["lambda"], params.get_all("q"));
+    }
+
+    #[test]
+    fn invalid_value() {
+        let params = HashMap::from([
+            ("q".to_string(), "lambda".to_string()),
+            ("page".to_string(), "first".to_string()),
+        ]);
+        let err = params.to_struct::<Search>().unwrap_err();
+        assert!(err.to_string().contains("invalid value `first`"), "{err}");
+    }
+}
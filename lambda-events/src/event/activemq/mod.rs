@@ -10,6 +10,10 @@ use crate::custom_serde::deserialize_lambda_map;
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ActiveMqEvent {
     #[serde(default)]
@@ -30,6 +34,10 @@ pub struct ActiveMqEvent {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ActiveMqMessage {
     #[serde(default)]
@@ -70,6 +78,10 @@ pub struct ActiveMqMessage {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ActiveMqDestination {
     #[serde(default)]
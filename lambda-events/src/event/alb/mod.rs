@@ -1,9 +1,11 @@
+#[cfg(feature = "validate")]
+use crate::validate::{Validate, ValidationErrors};
 use crate::{
     custom_serde::{
         deserialize_headers, deserialize_nullish_boolean, http_method, serialize_headers,
         serialize_multi_value_headers, serialize_query_string_parameters,
     },
-    encodings::Body,
+    encodings::{last_header_values, merge_multi_value_headers, Body, MultiValueHeaders},
 };
 #[cfg(feature = "builders")]
 use bon::Builder;
@@ -17,6 +19,10 @@ use serde_json::Value;
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct AlbTargetGroupRequest {
     #[serde(with = "http_method")]
@@ -48,10 +54,35 @@ pub struct AlbTargetGroupRequest {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+impl Validate for AlbTargetGroupRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_base64_body("body", self.is_base64_encoded, self.body.as_deref());
+        errors.into_result()
+    }
+}
+
+impl MultiValueHeaders for AlbTargetGroupRequest {
+    fn merged_headers(&self) -> HeaderMap {
+        merge_multi_value_headers(&self.headers, &self.multi_value_headers)
+    }
+
+    fn set_merged_headers(&mut self, headers: HeaderMap) {
+        self.headers = last_header_values(&headers);
+        self.multi_value_headers = headers;
+    }
+}
+
 /// `AlbTargetGroupRequestContext` contains the information to identify the load balancer invoking the lambda
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct AlbTargetGroupRequestContext {
     pub elb: ElbContext,
@@ -69,6 +100,10 @@ pub struct AlbTargetGroupRequestContext {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ElbContext {
     /// nolint: stylecheck
@@ -88,6 +123,10 @@ pub struct ElbContext {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct AlbTargetGroupResponse {
     pub status_code: i64,
@@ -113,6 +152,29 @@ pub struct AlbTargetGroupResponse {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+impl Validate for AlbTargetGroupResponse {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_body("body", self.is_base64_encoded, self.body.as_ref());
+        errors.into_result()
+    }
+}
+
+impl MultiValueHeaders for AlbTargetGroupResponse {
+    fn merged_headers(&self) -> HeaderMap {
+        merge_multi_value_headers(&self.headers, &self.multi_value_headers)
+    }
+
+    /// Fills both fields, as the target group reads either of them depending on
+    /// whether multi-value headers are enabled.
+    fn set_merged_headers(&mut self, headers: HeaderMap) {
+        self.headers = last_header_values(&headers);
+        self.multi_value_headers = headers;
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -138,6 +200,28 @@ mod test {
         assert_eq!(parsed, reparsed);
     }
 
+    #[test]
+    #[cfg(feature = "alb")]
+    fn example_alb_lambda_target_request_merged_headers() {
+        let data = include_bytes!("../../fixtures/example-alb-lambda-target-request-headers-only.json");
+        let headers_only: AlbTargetGroupRequest = serde_json::from_slice(data).unwrap();
+        assert_eq!("*/*", headers_only.merged_headers()["accept"]);
+
+        let data = include_bytes!("../../fixtures/example-alb-lambda-target-request-multivalue-headers.json");
+        let mut multi_value: AlbTargetGroupRequest = serde_json::from_slice(data).unwrap();
+        assert_eq!("*/*", multi_value.merged_headers()["accept"]);
+
+        let mut headers = multi_value.merged_headers();
+        headers.append("accept", "application/json".parse().unwrap());
+        multi_value.set_merged_headers(headers);
+        let output = serde_json::to_value(&multi_value).unwrap();
+        assert_eq!("application/json", output["headers"]["accept"]);
+        assert_eq!(
+            serde_json::json!(["*/*", "application/json"]),
+            output["multiValueHeaders"]["accept"]
+        );
+    }
+
     #[test]
     #[cfg(feature = "alb")]
     fn ensure_alb_lambda_target_request_query_string_parameter_value_is_string() {
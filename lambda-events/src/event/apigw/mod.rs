@@ -1,9 +1,11 @@
+#[cfg(feature = "validate")]
+use crate::validate::{Validate, ValidationErrors};
 use crate::{
     custom_serde::{
-        deserialize_headers, deserialize_lambda_map, deserialize_nullish_boolean, http_method, serialize_headers,
-        serialize_multi_value_headers,
+        deserialize_headers, deserialize_lambda_map, deserialize_nullish_boolean, http_method,
+        serialize_comma_separated_headers, serialize_headers, serialize_multi_value_headers,
     },
-    encodings::Body,
+    encodings::{last_header_values, merge_multi_value_headers, Body, MultiValueHeaders},
     iam::IamPolicyStatement,
 };
 #[cfg(feature = "builders")]
@@ -18,8 +20,15 @@ use std::collections::HashMap;
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayProxyRequest {
+    /// The payload format version, `"1.0"` when sent by an HTTP API or SAM local
+    #[serde(default)]
+    pub version: Option<String>,
     /// The resource path defined in API Gateway
     #[serde(default)]
     pub resource: Option<String>,
@@ -61,10 +70,35 @@ pub struct ApiGatewayProxyRequest {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+impl Validate for ApiGatewayProxyRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_base64_body("body", self.is_base64_encoded, self.body.as_deref());
+        errors.into_result()
+    }
+}
+
+impl MultiValueHeaders for ApiGatewayProxyRequest {
+    fn merged_headers(&self) -> HeaderMap {
+        merge_multi_value_headers(&self.headers, &self.multi_value_headers)
+    }
+
+    fn set_merged_headers(&mut self, headers: HeaderMap) {
+        self.headers = last_header_values(&headers);
+        self.multi_value_headers = headers;
+    }
+}
+
 /// `ApiGatewayProxyResponse` configures the response to be returned by API Gateway for the request
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayProxyResponse {
     pub status_code: i64,
@@ -88,11 +122,38 @@ pub struct ApiGatewayProxyResponse {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+impl Validate for ApiGatewayProxyResponse {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_body("body", self.is_base64_encoded, self.body.as_ref());
+        errors.into_result()
+    }
+}
+
+impl MultiValueHeaders for ApiGatewayProxyResponse {
+    fn merged_headers(&self) -> HeaderMap {
+        merge_multi_value_headers(&self.headers, &self.multi_value_headers)
+    }
+
+    /// Only fills `multi_value_headers`, as API Gateway merges both fields together,
+    /// which would duplicate the response headers.
+    fn set_merged_headers(&mut self, headers: HeaderMap) {
+        self.headers = HeaderMap::new();
+        self.multi_value_headers = headers;
+    }
+}
+
 /// `ApiGatewayProxyRequestContext` contains the information to identify the AWS account and resources invoking the
 /// Lambda function. It also includes Cognito identity information for the caller.
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayProxyRequestContext {
     #[serde(default)]
@@ -110,6 +171,8 @@ pub struct ApiGatewayProxyRequestContext {
     #[serde(default)]
     pub request_id: Option<String>,
     #[serde(default)]
+    pub extended_request_id: Option<String>,
+    #[serde(default)]
     pub protocol: Option<String>,
     #[serde(default)]
     pub identity: ApiGatewayRequestIdentity,
@@ -158,6 +221,10 @@ pub struct ApiGatewayProxyRequestContext {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayV2httpRequest {
     /// The type field from Custom Authorizer v1 requests.
@@ -260,10 +327,271 @@ pub struct ApiGatewayV2httpRequest {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+impl Validate for ApiGatewayV2httpRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_base64_body("body", self.is_base64_encoded, self.body.as_deref());
+        errors.into_result()
+    }
+}
+
+/// `ApiGatewayRequest` contains data coming from either payload format of the API Gateway proxy integration.
+///
+/// The format is detected from the `version` field during deserialization: `"2.0"` payloads,
+/// sent by HTTP APIs, are parsed as `V2`, everything else, sent by REST APIs or by HTTP APIs
+/// configured with the `1.0` format, is parsed as `V1`. Use [`ApiGatewayRequestParts`] to read
+/// the request regardless of its format.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum ApiGatewayRequest {
+    /// Payload format version `1.0`
+    V1(ApiGatewayProxyRequest),
+    /// Payload format version `2.0`
+    V2(ApiGatewayV2httpRequest),
+}
+
+impl<'de> Deserialize<'de> for ApiGatewayRequest {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        let request = match value.get("version").and_then(Value::as_str) {
+            Some("2.0") => serde_json::from_value(value).map(ApiGatewayRequest::V2),
+            _ => serde_json::from_value(value).map(ApiGatewayRequest::V1),
+        };
+        request.map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for ApiGatewayRequest {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            ApiGatewayRequest::V1(request) => request.serialize(serializer),
+            ApiGatewayRequest::V2(request) => request.serialize(serializer),
+        }
+    }
+}
+
+impl From<ApiGatewayProxyRequest> for ApiGatewayRequest {
+    fn from(request: ApiGatewayProxyRequest) -> Self {
+        ApiGatewayRequest::V1(request)
+    }
+}
+
+impl From<ApiGatewayV2httpRequest> for ApiGatewayRequest {
+    fn from(request: ApiGatewayV2httpRequest) -> Self {
+        ApiGatewayRequest::V2(request)
+    }
+}
+
+/// Accessors shared by both payload formats of the API Gateway proxy integration.
+pub trait ApiGatewayRequestParts {
+    /// The HTTP method of the request.
+    fn http_method(&self) -> &Method;
+
+    /// The path of the request, as sent by the client.
+    fn path(&self) -> Option<&str>;
+
+    /// The headers of the request. Version `1.0` headers are merged from `headers` and `multiValueHeaders`.
+    fn headers(&self) -> HeaderMap;
+
+    /// The query string parameters of the request, with all the values of each parameter.
+    fn query_string_parameters(&self) -> &QueryMap;
+
+    /// The path parameters matched by the route.
+    fn path_parameters(&self) -> &HashMap<String, String>;
+
+    /// The stage variables of the stage that received the request.
+    fn stage_variables(&self) -> &HashMap<String, String>;
+
+    /// The stage that received the request.
+    fn stage(&self) -> Option<&str>;
+
+    /// The id API Gateway assigned to the request.
+    fn request_id(&self) -> Option<&str>;
+
+    /// The raw body of the request, base64 encoded when [`is_base64_encoded`](Self::is_base64_encoded) is true.
+    fn body(&self) -> Option<&str>;
+
+    /// Whether the body is base64 encoded.
+    fn is_base64_encoded(&self) -> bool;
+}
+
+impl ApiGatewayRequestParts for ApiGatewayProxyRequest {
+    fn http_method(&self) -> &Method {
+        &self.http_method
+    }
+
+    fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    fn headers(&self) -> HeaderMap {
+        self.merged_headers()
+    }
+
+    fn query_string_parameters(&self) -> &QueryMap {
+        if self.multi_value_query_string_parameters.is_empty() {
+            &self.query_string_parameters
+        } else {
+            &self.multi_value_query_string_parameters
+        }
+    }
+
+    fn path_parameters(&self) -> &HashMap<String, String> {
+        &self.path_parameters
+    }
+
+    fn stage_variables(&self) -> &HashMap<String, String> {
+        &self.stage_variables
+    }
+
+    fn stage(&self) -> Option<&str> {
+        self.request_context.stage.as_deref()
+    }
+
+    fn request_id(&self) -> Option<&str> {
+        self.request_context.request_id.as_deref()
+    }
+
+    fn body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
+    fn is_base64_encoded(&self) -> bool {
+        self.is_base64_encoded
+    }
+}
+
+impl ApiGatewayRequestParts for ApiGatewayV2httpRequest {
+    fn http_method(&self) -> &Method {
+        &self.request_context.http.method
+    }
+
+    fn path(&self) -> Option<&str> {
+        self.raw_path.as_deref()
+    }
+
+    fn headers(&self) -> HeaderMap {
+        self.headers.clone()
+    }
+
+    fn query_string_parameters(&self) -> &QueryMap {
+        &self.query_string_parameters
+    }
+
+    fn path_parameters(&self) -> &HashMap<String, String> {
+        &self.path_parameters
+    }
+
+    fn stage_variables(&self) -> &HashMap<String, String> {
+        &self.stage_variables
+    }
+
+    fn stage(&self) -> Option<&str> {
+        self.request_context.stage.as_deref()
+    }
+
+    fn request_id(&self) -> Option<&str> {
+        self.request_context.request_id.as_deref()
+    }
+
+    fn body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
+    fn is_base64_encoded(&self) -> bool {
+        self.is_base64_encoded
+    }
+}
+
+impl ApiGatewayRequestParts for ApiGatewayRequest {
+    fn http_method(&self) -> &Method {
+        match self {
+            ApiGatewayRequest::V1(request) => request.http_method(),
+            ApiGatewayRequest::V2(request) => request.http_method(),
+        }
+    }
+
+    fn path(&self) -> Option<&str> {
+        match self {
+            ApiGatewayRequest::V1(request) => request.path(),
+            ApiGatewayRequest::V2(request) => request.path(),
+        }
+    }
+
+    fn headers(&self) -> HeaderMap {
+        match self {
+            ApiGatewayRequest::V1(request) => request.headers(),
+            ApiGatewayRequest::V2(request) => request.headers(),
+        }
+    }
+
+    fn query_string_parameters(&self) -> &QueryMap {
+        match self {
+            ApiGatewayRequest::V1(request) => request.query_string_parameters(),
+            ApiGatewayRequest::V2(request) => request.query_string_parameters(),
+        }
+    }
+
+    fn path_parameters(&self) -> &HashMap<String, String> {
+        match self {
+            ApiGatewayRequest::V1(request) => request.path_parameters(),
+            ApiGatewayRequest::V2(request) => request.path_parameters(),
+        }
+    }
+
+    fn stage_variables(&self) -> &HashMap<String, String> {
+        match self {
+            ApiGatewayRequest::V1(request) => request.stage_variables(),
+            ApiGatewayRequest::V2(request) => request.stage_variables(),
+        }
+    }
+
+    fn stage(&self) -> Option<&str> {
+        match self {
+            ApiGatewayRequest::V1(request) => request.stage(),
+            ApiGatewayRequest::V2(request) => request.stage(),
+        }
+    }
+
+    fn request_id(&self) -> Option<&str> {
+        match self {
+            ApiGatewayRequest::V1(request) => request.request_id(),
+            ApiGatewayRequest::V2(request) => request.request_id(),
+        }
+    }
+
+    fn body(&self) -> Option<&str> {
+        match self {
+            ApiGatewayRequest::V1(request) => request.body(),
+            ApiGatewayRequest::V2(request) => request.body(),
+        }
+    }
+
+    fn is_base64_encoded(&self) -> bool {
+        match self {
+            ApiGatewayRequest::V1(request) => request.is_base64_encoded(),
+            ApiGatewayRequest::V2(request) => request.is_base64_encoded(),
+        }
+    }
+}
+
 /// `ApiGatewayV2httpRequestContext` contains the information to identify the AWS account and resources invoking the Lambda function.
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayV2httpRequestContext {
     #[serde(default)]
@@ -292,6 +620,41 @@ pub struct ApiGatewayV2httpRequestContext {
     pub http: ApiGatewayV2httpRequestContextHttpDescription,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub authentication: Option<ApiGatewayV2httpRequestContextAuthentication>,
+    /// The path from the request context of Custom Authorizer v1 requests.
+    #[deprecated(
+        since = "1.1.0",
+        note = "This field is only present in Custom Authorizer v1 requests. Use `ApiGatewayV2CustomAuthorizerV1Request` for authorizer payloads."
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// The resource id from the request context of Custom Authorizer v1 requests.
+    #[deprecated(
+        since = "1.1.0",
+        note = "This field is only present in Custom Authorizer v1 requests. Use `ApiGatewayV2CustomAuthorizerV1Request` for authorizer payloads."
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_id: Option<String>,
+    /// The resource path from the request context of Custom Authorizer v1 requests.
+    #[deprecated(
+        since = "1.1.0",
+        note = "This field is only present in Custom Authorizer v1 requests. Use `ApiGatewayV2CustomAuthorizerV1Request` for authorizer payloads."
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_path: Option<String>,
+    /// The HTTP method from the request context of Custom Authorizer v1 requests.
+    #[deprecated(
+        since = "1.1.0",
+        note = "This field is only present in Custom Authorizer v1 requests. Use `ApiGatewayV2CustomAuthorizerV1Request` for authorizer payloads."
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_method: Option<String>,
+    /// The identity from the request context of Custom Authorizer v1 requests.
+    #[deprecated(
+        since = "1.1.0",
+        note = "This field is only present in Custom Authorizer v1 requests. Use `ApiGatewayV2CustomAuthorizerV1Request` for authorizer payloads."
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<ApiGatewayCustomAuthorizerRequestTypeRequestIdentity>,
     /// Catchall to catch any additional fields that were present but not explicitly defined by this struct.
     /// Enabled with Cargo feature `catch-all-fields`.
     /// If `catch-all-fields` is disabled, any additional fields that are present will be ignored.
@@ -306,6 +669,10 @@ pub struct ApiGatewayV2httpRequestContext {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 pub struct ApiGatewayRequestAuthorizer {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub jwt: Option<ApiGatewayRequestAuthorizerJwtDescription>,
@@ -333,6 +700,10 @@ pub struct ApiGatewayRequestAuthorizer {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayRequestAuthorizerJwtDescription {
     #[serde(deserialize_with = "deserialize_lambda_map")]
@@ -354,6 +725,10 @@ pub struct ApiGatewayRequestAuthorizerJwtDescription {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayRequestAuthorizerIamDescription {
     #[serde(default)]
@@ -384,6 +759,10 @@ pub struct ApiGatewayRequestAuthorizerIamDescription {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayRequestAuthorizerCognitoIdentity {
     pub amr: Vec<String>,
@@ -405,6 +784,10 @@ pub struct ApiGatewayRequestAuthorizerCognitoIdentity {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayV2httpRequestContextHttpDescription {
     #[serde(with = "http_method")]
@@ -431,11 +814,15 @@ pub struct ApiGatewayV2httpRequestContextHttpDescription {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayV2httpResponse {
     pub status_code: i64,
     #[serde(deserialize_with = "deserialize_headers", default)]
-    #[serde(serialize_with = "serialize_headers")]
+    #[serde(serialize_with = "serialize_comma_separated_headers")]
     pub headers: HeaderMap,
     #[serde(deserialize_with = "deserialize_headers", default)]
     #[serde(serialize_with = "serialize_multi_value_headers")]
@@ -455,10 +842,37 @@ pub struct ApiGatewayV2httpResponse {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+impl Validate for ApiGatewayV2httpResponse {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_body("body", self.is_base64_encoded, self.body.as_ref());
+        errors.into_result()
+    }
+}
+
+impl MultiValueHeaders for ApiGatewayV2httpResponse {
+    fn merged_headers(&self) -> HeaderMap {
+        merge_multi_value_headers(&self.headers, &self.multi_value_headers)
+    }
+
+    /// Only fills `headers`, as HTTP APIs don't support `multi_value_headers`.
+    /// Multiple values of a header are combined with commas.
+    fn set_merged_headers(&mut self, headers: HeaderMap) {
+        self.headers = headers;
+        self.multi_value_headers = HeaderMap::new();
+    }
+}
+
 /// `ApiGatewayRequestIdentity` contains identity information for the request caller.
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayRequestIdentity {
     #[serde(default)]
@@ -488,6 +902,8 @@ pub struct ApiGatewayRequestIdentity {
     pub user_agent: Option<String>,
     #[serde(default)]
     pub user: Option<String>,
+    #[serde(default)]
+    pub principal_org_id: Option<String>,
     /// Catchall to catch any additional fields that were present but not explicitly defined by this struct.
     /// Enabled with Cargo feature `catch-all-fields`.
     /// If `catch-all-fields` is disabled, any additional fields that are present will be ignored.
@@ -502,6 +918,10 @@ pub struct ApiGatewayRequestIdentity {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayWebsocketProxyRequest {
     /// The resource path defined in API Gateway
@@ -547,12 +967,37 @@ pub struct ApiGatewayWebsocketProxyRequest {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+impl Validate for ApiGatewayWebsocketProxyRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_base64_body("body", self.is_base64_encoded, self.body.as_deref());
+        errors.into_result()
+    }
+}
+
+impl MultiValueHeaders for ApiGatewayWebsocketProxyRequest {
+    fn merged_headers(&self) -> HeaderMap {
+        merge_multi_value_headers(&self.headers, &self.multi_value_headers)
+    }
+
+    fn set_merged_headers(&mut self, headers: HeaderMap) {
+        self.headers = last_header_values(&headers);
+        self.multi_value_headers = headers;
+    }
+}
+
 /// `ApiGatewayWebsocketProxyRequestContext` contains the information to identify
 /// the AWS account and resources invoking the Lambda function. It also includes
 /// Cognito identity information for the caller.
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayWebsocketProxyRequestContext {
     #[serde(default)]
@@ -625,6 +1070,10 @@ pub struct ApiGatewayWebsocketProxyRequestContext {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayCustomAuthorizerRequestTypeRequestIdentity {
     #[serde(default)]
@@ -633,6 +1082,10 @@ pub struct ApiGatewayCustomAuthorizerRequestTypeRequestIdentity {
     pub api_key: Option<String>,
     #[serde(default)]
     pub source_ip: Option<String>,
+    /// The user agent of the caller, sent to WebSocket API authorizers
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
     #[serde(default)]
     pub client_cert: Option<ApiGatewayCustomAuthorizerRequestTypeRequestIdentityClientCert>,
     /// Catchall to catch any additional fields that were present but not explicitly defined by this struct.
@@ -649,6 +1102,10 @@ pub struct ApiGatewayCustomAuthorizerRequestTypeRequestIdentity {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayCustomAuthorizerRequestTypeRequestIdentityClientCert {
     #[serde(default)]
@@ -676,6 +1133,10 @@ pub struct ApiGatewayCustomAuthorizerRequestTypeRequestIdentityClientCert {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayCustomAuthorizerRequestTypeRequestIdentityClientCertValidity {
     #[serde(default)]
@@ -696,6 +1157,10 @@ pub struct ApiGatewayCustomAuthorizerRequestTypeRequestIdentityClientCertValidit
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayV2httpRequestContextAuthentication {
     #[serde(default)]
@@ -714,6 +1179,10 @@ pub struct ApiGatewayV2httpRequestContextAuthentication {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayV2httpRequestContextAuthenticationClientCert {
     #[serde(default)]
@@ -741,6 +1210,10 @@ pub struct ApiGatewayV2httpRequestContextAuthenticationClientCert {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayV2httpRequestContextAuthenticationClientCertValidity {
     #[serde(default)]
@@ -760,6 +1233,10 @@ pub struct ApiGatewayV2httpRequestContextAuthenticationClientCertValidity {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayV2CustomAuthorizerV1RequestTypeRequestContext {
     #[serde(default)]
@@ -793,6 +1270,10 @@ pub struct ApiGatewayV2CustomAuthorizerV1RequestTypeRequestContext {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayV2CustomAuthorizerV1Request {
     #[serde(default)]
@@ -838,6 +1319,10 @@ pub struct ApiGatewayV2CustomAuthorizerV1Request {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayV2CustomAuthorizerV2Request {
     #[serde(default)]
@@ -885,6 +1370,10 @@ pub struct ApiGatewayV2CustomAuthorizerV2Request {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayCustomAuthorizerContext {
     pub principal_id: Option<String>,
@@ -906,6 +1395,10 @@ pub struct ApiGatewayCustomAuthorizerContext {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayCustomAuthorizerRequestTypeRequestContext {
     #[serde(default)]
@@ -930,6 +1423,34 @@ pub struct ApiGatewayCustomAuthorizerRequestTypeRequestContext {
     #[serde(default)]
     #[serde(rename = "apiId")]
     pub apiid: Option<String>,
+    // The fields below are only sent to WebSocket API authorizers
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connected_at: Option<i64>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connection_id: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain_name: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extended_request_id: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_direction: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_time: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_time_epoch: Option<i64>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route_key: Option<String>,
     /// Catchall to catch any additional fields that were present but not explicitly defined by this struct.
     /// Enabled with Cargo feature `catch-all-fields`.
     /// If `catch-all-fields` is disabled, any additional fields that are present will be ignored.
@@ -944,6 +1465,10 @@ pub struct ApiGatewayCustomAuthorizerRequestTypeRequestContext {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayCustomAuthorizerRequest {
     #[serde(default)]
@@ -967,8 +1492,16 @@ pub struct ApiGatewayCustomAuthorizerRequest {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayCustomAuthorizerRequestTypeRequest {
+    /// The version of the payload, `"$LATEST"` for WebSocket API authorizers
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
     #[serde(default)]
     pub type_: Option<String>,
     /// nolint: stylecheck
@@ -1010,10 +1543,25 @@ pub struct ApiGatewayCustomAuthorizerRequestTypeRequest {
     pub other: serde_json::Map<String, Value>,
 }
 
+impl MultiValueHeaders for ApiGatewayCustomAuthorizerRequestTypeRequest {
+    fn merged_headers(&self) -> HeaderMap {
+        merge_multi_value_headers(&self.headers, &self.multi_value_headers)
+    }
+
+    fn set_merged_headers(&mut self, headers: HeaderMap) {
+        self.headers = last_header_values(&headers);
+        self.multi_value_headers = headers;
+    }
+}
+
 /// `ApiGatewayCustomAuthorizerResponse` represents the expected format of an API Gateway authorization response.
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayCustomAuthorizerResponse<T1 = Value>
 where
@@ -1040,6 +1588,10 @@ where
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayV2CustomAuthorizerSimpleResponse<T1 = Value>
 where
@@ -1062,6 +1614,10 @@ where
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiGatewayV2CustomAuthorizerIamPolicyResponse<T1 = Value>
 where
@@ -1087,6 +1643,10 @@ where
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 pub struct ApiGatewayCustomAuthorizerPolicy {
     #[serde(default)]
@@ -1262,6 +1822,17 @@ mod test {
         );
     }
 
+    #[test]
+    #[cfg(feature = "apigw")]
+    fn example_apigw_sam_rest_request() {
+        let data = include_bytes!("../../fixtures/example-apigw-sam-rest-request.json");
+        let parsed: ApiGatewayProxyRequest = serde_json::from_slice(data).unwrap();
+        assert_eq!(Some("1.0"), parsed.version.as_deref());
+        let output: String = serde_json::to_string(&parsed).unwrap();
+        let reparsed: ApiGatewayProxyRequest = serde_json::from_slice(output.as_bytes()).unwrap();
+        assert_eq!(parsed, reparsed);
+    }
+
     #[test]
     #[cfg(feature = "apigw")]
     fn example_apigw_restapi_openapi_request() {
@@ -1325,6 +1896,30 @@ mod test {
         assert_eq!(parsed, reparsed);
     }
 
+    #[test]
+    #[cfg(feature = "apigw")]
+    fn example_apigw_request_detects_payload_format() {
+        let data = include_bytes!("../../fixtures/example-apigw-request.json");
+        let parsed: ApiGatewayRequest = serde_json::from_slice(data).unwrap();
+        assert!(matches!(parsed, ApiGatewayRequest::V1(_)));
+        assert_eq!(Method::POST, parsed.http_method());
+        assert_eq!(Some("/hello/world"), parsed.path());
+        assert_eq!(Some("testStage"), parsed.stage());
+        let output: String = serde_json::to_string(&parsed).unwrap();
+        let reparsed: ApiGatewayRequest = serde_json::from_slice(output.as_bytes()).unwrap();
+        assert_eq!(parsed, reparsed);
+
+        let data = include_bytes!("../../fixtures/example-apigw-v2-request-no-authorizer.json");
+        let parsed: ApiGatewayRequest = serde_json::from_slice(data).unwrap();
+        assert!(matches!(parsed, ApiGatewayRequest::V2(_)));
+        assert_eq!(Method::GET, parsed.http_method());
+        assert_eq!(Some("/"), parsed.path());
+        assert_eq!(Some("$default"), parsed.stage());
+        let output: String = serde_json::to_string(&parsed).unwrap();
+        let reparsed: ApiGatewayRequest = serde_json::from_slice(output.as_bytes()).unwrap();
+        assert_eq!(parsed, reparsed);
+    }
+
     #[test]
     #[cfg(feature = "apigw")]
     fn apigw_v2_correct_http_method_usage() {
@@ -1389,8 +1984,9 @@ mod test {
         let output: String = serde_json::to_string(&parsed).unwrap();
         let reparsed: ApiGatewayV2httpRequest = serde_json::from_slice(output.as_bytes()).unwrap();
         assert_eq!(parsed, reparsed);
-        assert_eq!("REQUEST", parsed.kind.unwrap());
-        assert_eq!(Method::GET, parsed.http_method);
+        let value = serde_json::to_value(&parsed).unwrap();
+        assert_eq!("REQUEST", value["type"]);
+        assert_eq!("GET", value["httpMethod"]);
     }
 
     #[test]
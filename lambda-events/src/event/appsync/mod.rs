@@ -10,6 +10,10 @@ use crate::custom_serde::deserialize_lambda_map;
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct AppSyncResolverTemplate<T1 = Value>
 where
@@ -102,6 +106,10 @@ pub type AppSyncOperation = String;
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct AppSyncLambdaAuthorizerRequest {
     #[serde(default)]
@@ -122,6 +130,10 @@ pub struct AppSyncLambdaAuthorizerRequest {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct AppSyncLambdaAuthorizerRequestContext<T1 = Value>
 where
@@ -157,6 +169,10 @@ where
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct AppSyncLambdaAuthorizerResponse<T1 = Value>
 where
@@ -195,6 +211,10 @@ where
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 pub struct AppSyncDirectResolverEvent<TArguments = Value, TSource = Value, TStash = Value>
 where
     TArguments: Serialize + DeserializeOwned,
@@ -227,6 +247,10 @@ where
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct AppSyncRequest {
     #[serde(deserialize_with = "deserialize_lambda_map")]
@@ -249,6 +273,10 @@ pub struct AppSyncRequest {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct AppSyncInfo<T = Value>
 where
@@ -276,6 +304,10 @@ where
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 pub struct AppSyncPrevResult<T = Value>
 where
     T: Serialize + DeserializeOwned,
@@ -294,6 +326,9 @@ where
 
 /// `AppSyncIdentity` represents the identity of the caller as determined by the
 /// configured AppSync authorization mechanism (IAM, Cognito, OIDC, or Lambda).
+///
+/// The variants don't deny unknown fields under the `strict` feature, since that
+/// would change which variant an identity resolves to.
 #[non_exhaustive]
 #[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
 #[serde(untagged, rename_all = "camelCase")]
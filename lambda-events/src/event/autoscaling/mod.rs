@@ -11,6 +11,10 @@ use crate::custom_serde::deserialize_lambda_map;
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct AutoScalingEvent<T1 = Value>
 where
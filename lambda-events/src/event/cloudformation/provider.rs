@@ -105,6 +105,10 @@ where
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Default, Debug, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 pub struct CloudFormationCustomResourceResponse<D = Value>
 where
@@ -15,6 +15,10 @@ use std::{fmt, io::BufReader};
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 pub struct LogsEvent {
     /// `aws_logs` is gzipped and base64 encoded, it needs a custom deserializer
     #[serde(rename = "awslogs")]
@@ -42,6 +46,10 @@ pub struct AwsLogs {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct LogData {
     /// Owner of the log event
@@ -70,6 +78,10 @@ pub struct LogData {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 pub struct LogEntry {
     /// Unique id for the entry
     pub id: String,
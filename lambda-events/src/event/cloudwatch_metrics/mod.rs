@@ -0,0 +1,437 @@
+#[cfg(feature = "builders")]
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// Maximum number of metrics in a metric directive.
+pub const MAX_METRICS: usize = 100;
+/// Maximum number of dimensions in a dimension set.
+pub const MAX_DIMENSIONS: usize = 30;
+/// Maximum number of values of a single metric.
+pub const MAX_METRIC_VALUES: usize = 100;
+/// Maximum length of a namespace.
+pub const MAX_NAMESPACE_LENGTH: usize = 255;
+
+/// `EmfLog` is a log line in the CloudWatch Embedded Metric Format.
+///
+/// Printing it to stdout from a Lambda function makes CloudWatch extract its metrics,
+/// without calling the CloudWatch API. Use [`EmfLogBuilder`] to build one that respects
+/// the format constraints.
+///
+/// See <https://docs.aws.amazon.com/AmazonCloudWatch/latest/monitoring/CloudWatch_Embedded_Metric_Format_Specification.html>
+#[non_exhaustive]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct EmfLog {
+    #[serde(rename = "_aws")]
+    pub aws: EmfMetadata,
+    /// Values of the dimensions, metrics, and properties referenced by the metadata.
+    #[serde(flatten)]
+    pub members: Map<String, Value>,
+}
+
+impl fmt::Display for EmfLog {
+    /// Formats the log as a single JSON line, ready to be printed to stdout.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        f.write_str(&json)
+    }
+}
+
+/// `EmfMetadata` is the `_aws` member of an EMF log, which tells CloudWatch which members are metrics.
+#[non_exhaustive]
+#[cfg_attr(feature = "builders", derive(Builder))]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
+#[serde(rename_all = "PascalCase")]
+pub struct EmfMetadata {
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub timestamp: DateTime<Utc>,
+    #[serde(rename = "CloudWatchMetrics")]
+    pub cloudwatch_metrics: Vec<MetricDirective>,
+    /// Catchall to catch any additional fields that were present but not explicitly defined by this struct.
+    /// Enabled with Cargo feature `catch-all-fields`.
+    /// If `catch-all-fields` is disabled, any additional fields that are present will be ignored.
+    #[cfg(feature = "catch-all-fields")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "catch-all-fields")))]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "builders", builder(default))]
+    pub other: Map<String, Value>,
+}
+
+/// `MetricDirective` tells CloudWatch which metrics to extract into a namespace, and their dimensions.
+#[non_exhaustive]
+#[cfg_attr(feature = "builders", derive(Builder))]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
+#[serde(rename_all = "PascalCase")]
+pub struct MetricDirective {
+    pub namespace: String,
+    /// Dimension sets, each a list of the names of members holding the dimension values.
+    pub dimensions: Vec<Vec<String>>,
+    pub metrics: Vec<MetricDefinition>,
+    /// Catchall to catch any additional fields that were present but not explicitly defined by this struct.
+    /// Enabled with Cargo feature `catch-all-fields`.
+    /// If `catch-all-fields` is disabled, any additional fields that are present will be ignored.
+    #[cfg(feature = "catch-all-fields")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "catch-all-fields")))]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "builders", builder(default))]
+    pub other: Map<String, Value>,
+}
+
+/// `MetricDefinition` names a member holding metric values.
+#[non_exhaustive]
+#[cfg_attr(feature = "builders", derive(Builder))]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
+#[serde(rename_all = "PascalCase")]
+pub struct MetricDefinition {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<MetricUnit>,
+    /// `1` for high-resolution metrics, `60` or absent for standard resolution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage_resolution: Option<u32>,
+    /// Catchall to catch any additional fields that were present but not explicitly defined by this struct.
+    /// Enabled with Cargo feature `catch-all-fields`.
+    /// If `catch-all-fields` is disabled, any additional fields that are present will be ignored.
+    #[cfg(feature = "catch-all-fields")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "catch-all-fields")))]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "builders", builder(default))]
+    pub other: Map<String, Value>,
+}
+
+/// Units supported by CloudWatch metrics.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MetricUnit {
+    Seconds,
+    Microseconds,
+    Milliseconds,
+    Bytes,
+    Kilobytes,
+    Megabytes,
+    Gigabytes,
+    Terabytes,
+    Bits,
+    Kilobits,
+    Megabits,
+    Gigabits,
+    Terabits,
+    Percent,
+    Count,
+    #[serde(rename = "Bytes/Second")]
+    BytesPerSecond,
+    #[serde(rename = "Kilobytes/Second")]
+    KilobytesPerSecond,
+    #[serde(rename = "Megabytes/Second")]
+    MegabytesPerSecond,
+    #[serde(rename = "Gigabytes/Second")]
+    GigabytesPerSecond,
+    #[serde(rename = "Terabytes/Second")]
+    TerabytesPerSecond,
+    #[serde(rename = "Bits/Second")]
+    BitsPerSecond,
+    #[serde(rename = "Kilobits/Second")]
+    KilobitsPerSecond,
+    #[serde(rename = "Megabits/Second")]
+    MegabitsPerSecond,
+    #[serde(rename = "Gigabits/Second")]
+    GigabitsPerSecond,
+    #[serde(rename = "Terabits/Second")]
+    TerabitsPerSecond,
+    #[serde(rename = "Count/Second")]
+    CountPerSecond,
+    None,
+}
+
+/// Builds an [`EmfLog`] with a single namespace and dimension set, checking the EMF constraints.
+///
+/// ```
+/// use aws_lambda_events::cloudwatch_metrics::{EmfLogBuilder, MetricUnit};
+///
+/// let log = EmfLogBuilder::new("orders")
+///     .dimension("service", "checkout")
+///     .metric("latency", 42.0, MetricUnit::Milliseconds)
+///     .property("requestId", "8f5b6a6e")
+///     .build()
+///     .unwrap();
+/// println!("{log}");
+/// ```
+#[derive(Clone, Debug)]
+pub struct EmfLogBuilder {
+    namespace: String,
+    timestamp: Option<DateTime<Utc>>,
+    dimensions: Vec<String>,
+    metrics: Vec<MetricDefinition>,
+    members: Map<String, Value>,
+    error: Option<EmfError>,
+}
+
+impl EmfLogBuilder {
+    /// Starts a log for metrics in `namespace`.
+    pub fn new(namespace: impl Into<String>) -> Self {
+        EmfLogBuilder {
+            namespace: namespace.into(),
+            timestamp: None,
+            dimensions: Vec::new(),
+            metrics: Vec::new(),
+            members: Map::new(),
+            error: None,
+        }
+    }
+
+    /// Sets the timestamp of the metrics, the time the log is built by default.
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Adds a dimension to the dimension set of the metrics.
+    pub fn dimension(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let name = name.into();
+        if self.insert_member(&name, Value::String(value.into())) {
+            self.dimensions.push(name);
+        }
+        self
+    }
+
+    /// Records a value of a standard-resolution metric. Recording the same metric again adds a value to it.
+    pub fn metric(self, name: impl Into<String>, value: f64, unit: MetricUnit) -> Self {
+        self.add_metric(name.into(), value, unit, None)
+    }
+
+    /// Records a value of a high-resolution metric, stored with a one second resolution.
+    pub fn high_resolution_metric(self, name: impl Into<String>, value: f64, unit: MetricUnit) -> Self {
+        self.add_metric(name.into(), value, unit, Some(1))
+    }
+
+    /// Adds a member that is logged but not extracted as a metric, like a request id.
+    pub fn property(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        let name = name.into();
+        self.insert_member(&name, value.into());
+        self
+    }
+
+    /// Builds the log, or returns the first constraint the metrics break.
+    pub fn build(self) -> Result<EmfLog, EmfError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        if self.namespace.is_empty() || self.namespace.len() > MAX_NAMESPACE_LENGTH {
+            return Err(EmfError::InvalidNamespace(self.namespace));
+        }
+        if self.metrics.len() > MAX_METRICS {
+            return Err(EmfError::TooManyMetrics(self.metrics.len()));
+        }
+        if self.dimensions.len() > MAX_DIMENSIONS {
+            return Err(EmfError::TooManyDimensions(self.dimensions.len()));
+        }
+
+        let dimensions = if self.dimensions.is_empty() {
+            Vec::new()
+        } else {
+            vec![self.dimensions]
+        };
+        Ok(EmfLog {
+            aws: EmfMetadata {
+                timestamp: self.timestamp.unwrap_or_else(Utc::now),
+                cloudwatch_metrics: vec![MetricDirective {
+                    namespace: self.namespace,
+                    dimensions,
+                    metrics: self.metrics,
+                    #[cfg(feature = "catch-all-fields")]
+                    other: Map::new(),
+                }],
+                #[cfg(feature = "catch-all-fields")]
+                other: Map::new(),
+            },
+            members: self.members,
+        })
+    }
+
+    fn add_metric(mut self, name: String, value: f64, unit: MetricUnit, storage_resolution: Option<u32>) -> Self {
+        if self.metrics.iter().any(|metric| metric.name == name) {
+            if let Some(Value::Array(values)) = self.members.get_mut(&name) {
+                if values.len() == MAX_METRIC_VALUES {
+                    self.fail(EmfError::TooManyValues(name));
+                } else {
+                    values.push(value.into());
+                }
+            }
+            return self;
+        }
+
+        if self.insert_member(&name, Value::Array(vec![value.into()])) {
+            self.metrics.push(MetricDefinition {
+                name,
+                unit: Some(unit),
+                storage_resolution,
+                #[cfg(feature = "catch-all-fields")]
+                other: Map::new(),
+            });
+        }
+        self
+    }
+
+    fn insert_member(&mut self, name: &str, value: Value) -> bool {
+        if name.is_empty() || name == "_aws" {
+            self.fail(EmfError::InvalidName(name.to_string()));
+            false
+        } else if self.members.contains_key(name) {
+            self.fail(EmfError::DuplicateName(name.to_string()));
+            false
+        } else {
+            self.members.insert(name.to_string(), value);
+            true
+        }
+    }
+
+    fn fail(&mut self, error: EmfError) {
+        self.error.get_or_insert(error);
+    }
+}
+
+/// Error returned when metrics break a constraint of the Embedded Metric Format.
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EmfError {
+    /// The namespace is empty or longer than [`MAX_NAMESPACE_LENGTH`].
+    InvalidNamespace(String),
+    /// A member name is empty or reserved.
+    InvalidName(String),
+    /// A name is used by more than one dimension, metric, or property.
+    DuplicateName(String),
+    /// There are more than [`MAX_METRICS`] metrics.
+    TooManyMetrics(usize),
+    /// There are more than [`MAX_DIMENSIONS`] dimensions.
+    TooManyDimensions(usize),
+    /// A metric has more than [`MAX_METRIC_VALUES`] values.
+    TooManyValues(String),
+}
+
+impl fmt::Display for EmfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmfError::InvalidNamespace(namespace) => write!(
+                f,
+                "namespace `{namespace}` must be between 1 and {MAX_NAMESPACE_LENGTH} characters"
+            ),
+            EmfError::InvalidName(name) => write!(f, "`{name}` is not a valid member name"),
+            EmfError::DuplicateName(name) => write!(f, "`{name}` is used by more than one member"),
+            EmfError::TooManyMetrics(count) => write!(f, "{count} metrics exceed the limit of {MAX_METRICS}"),
+            EmfError::TooManyDimensions(count) => {
+                write!(f, "{count} dimensions exceed the limit of {MAX_DIMENSIONS}")
+            }
+            EmfError::TooManyValues(name) => {
+                write!(f, "metric `{name}` exceeds the limit of {MAX_METRIC_VALUES} values")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EmfError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn example_cloudwatch_metrics_emf_log() {
+        let data = include_bytes!("../../fixtures/example-cloudwatch-metrics-emf-log.json");
+        let parsed: EmfLog = serde_json::from_slice(data).unwrap();
+        assert_eq!("lambda-function-metrics", parsed.aws.cloudwatch_metrics[0].namespace);
+        assert_eq!(
+            Some(MetricUnit::Milliseconds),
+            parsed.aws.cloudwatch_metrics[0].metrics[0].unit
+        );
+        let output: String = serde_json::to_string(&parsed).unwrap();
+        let reparsed: EmfLog = serde_json::from_slice(output.as_bytes()).unwrap();
+        assert_eq!(parsed, reparsed);
+    }
+
+    #[test]
+    fn build_emf_log() {
+        let timestamp = Utc.timestamp_millis_opt(1574109732004).unwrap();
+        let log = EmfLogBuilder::new("orders")
+            .timestamp(timestamp)
+            .dimension("service", "checkout")
+            .metric("latency", 42.0, MetricUnit::Milliseconds)
+            .metric("latency", 17.0, MetricUnit::Milliseconds)
+            .high_resolution_metric("throughput", 3.0, MetricUnit::CountPerSecond)
+            .property("requestId", "8f5b6a6e")
+            .build()
+            .unwrap();
+
+        let output: Value = serde_json::from_str(&log.to_string()).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "_aws": {
+                    "Timestamp": 1574109732004i64,
+                    "CloudWatchMetrics": [{
+                        "Namespace": "orders",
+                        "Dimensions": [["service"]],
+                        "Metrics": [
+                            {"Name": "latency", "Unit": "Milliseconds"},
+                            {"Name": "throughput", "Unit": "Count/Second", "StorageResolution": 1}
+                        ]
+                    }]
+                },
+                "service": "checkout",
+                "latency": [42.0, 17.0],
+                "throughput": [3.0],
+                "requestId": "8f5b6a6e"
+            }),
+            output
+        );
+    }
+
+    #[test]
+    fn enforce_emf_constraints() {
+        let builder = (0..=MAX_METRICS).fold(EmfLogBuilder::new("orders"), |builder, i| {
+            builder.metric(format!("metric{i}"), 1.0, MetricUnit::Count)
+        });
+        assert_eq!(EmfError::TooManyMetrics(MAX_METRICS + 1), builder.build().unwrap_err());
+
+        let builder = (0..=MAX_DIMENSIONS).fold(EmfLogBuilder::new("orders"), |builder, i| {
+            builder.dimension(format!("dimension{i}"), "value")
+        });
+        assert_eq!(
+            EmfError::TooManyDimensions(MAX_DIMENSIONS + 1),
+            builder.build().unwrap_err()
+        );
+
+        let builder = (0..=MAX_METRIC_VALUES).fold(EmfLogBuilder::new("orders"), |builder, _| {
+            builder.metric("latency", 1.0, MetricUnit::Milliseconds)
+        });
+        assert_eq!(
+            EmfError::TooManyValues("latency".to_string()),
+            builder.build().unwrap_err()
+        );
+
+        let error = EmfLogBuilder::new("orders")
+            .dimension("latency", "fast")
+            .metric("latency", 1.0, MetricUnit::Milliseconds)
+            .build()
+            .unwrap_err();
+        assert_eq!(EmfError::DuplicateName("latency".to_string()), error);
+
+        assert!(matches!(
+            EmfLogBuilder::new("").build(),
+            Err(EmfError::InvalidNamespace(_))
+        ));
+    }
+}
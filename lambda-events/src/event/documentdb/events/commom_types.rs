@@ -9,6 +9,10 @@ pub type AnyDocument = HashMap<String, Value>;
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct DatabaseCollection {
     db: String,
@@ -27,6 +31,10 @@ pub struct DatabaseCollection {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 pub struct DocumentId {
     #[serde(rename = "_data")]
     pub data: String,
@@ -43,6 +51,10 @@ pub struct DocumentId {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 pub struct DocumentKeyIdOid {
     #[serde(rename = "$oid")]
     pub oid: String,
@@ -59,6 +71,10 @@ pub struct DocumentKeyIdOid {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 pub struct DocumentKeyId {
     #[serde(rename = "_id")]
     pub id: DocumentKeyIdOid,
@@ -75,6 +91,10 @@ pub struct DocumentKeyId {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 pub struct InnerTimestamp {
     t: usize,
     i: usize,
@@ -91,6 +111,10 @@ pub struct InnerTimestamp {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 pub struct Timestamp {
     #[serde(rename = "$timestamp")]
     pub timestamp: InnerTimestamp,
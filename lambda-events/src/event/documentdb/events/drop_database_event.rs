@@ -9,6 +9,10 @@ use super::commom_types::{AnyDocument, DatabaseCollection, DocumentId, Timestamp
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ChangeDropDatabaseEvent {
     #[serde(rename = "_id")]
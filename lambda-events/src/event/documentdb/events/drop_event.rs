@@ -1,4 +1,4 @@
-use super::commom_types::{AnyDocument, DatabaseCollection, DocumentId, Timestamp};
+use super::commom_types::{AnyDocument, DatabaseCollection, DocumentId, DocumentKeyId, Timestamp};
 #[cfg(feature = "builders")]
 use bon::Builder;
 use serde::{Deserialize, Serialize};
@@ -8,6 +8,10 @@ use serde_json::Value;
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ChangeDropEvent {
     #[serde(rename = "_id")]
@@ -15,6 +19,8 @@ pub struct ChangeDropEvent {
     #[serde(default)]
     cluster_time: Option<Timestamp>,
     #[serde(default)]
+    document_key: Option<DocumentKeyId>,
+    #[serde(default)]
     #[serde(rename = "lsid")]
     ls_id: Option<AnyDocument>,
     ns: DatabaseCollection,
@@ -4,18 +4,23 @@ use serde::{Deserialize, Serialize};
 #[cfg(feature = "catch-all-fields")]
 use serde_json::Value;
 
-use super::commom_types::{AnyDocument, DatabaseCollection, DocumentId, Timestamp};
+use super::commom_types::{AnyDocument, DatabaseCollection, DocumentId, DocumentKeyId, Timestamp};
 
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct ChangeRenameEvent {
     #[serde(rename = "_id")]
     id: DocumentId,
     #[serde(default)]
     cluster_time: Option<Timestamp>,
-
+    #[serde(default)]
+    document_key: Option<DocumentKeyId>,
     #[serde(default)]
     #[serde(rename = "lsid")]
     ls_id: Option<AnyDocument>,
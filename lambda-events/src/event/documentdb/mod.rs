@@ -28,6 +28,10 @@ pub enum ChangeEvent {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 pub struct DocumentDbInnerEvent {
     pub event: ChangeEvent,
     /// Catchall to catch any additional fields that were present but not explicitly defined by this struct.
@@ -43,6 +47,10 @@ pub struct DocumentDbInnerEvent {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct DocumentDbEvent {
     #[serde(default)]
@@ -1,3 +1,7 @@
+#[cfg(feature = "arn")]
+use crate::encodings::{parse_optional_arn, Arn, ArnError};
+#[cfg(feature = "validate")]
+use crate::validate::{Validate, ValidationErrors};
 use crate::{
     custom_serde::{deserialize_lambda_dynamodb_item, float_unix_epoch},
     streams::DynamoDbBatchItemFailure,
@@ -141,6 +145,24 @@ pub struct Event {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+impl Validate for Event {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_not_empty("Records", &self.records);
+        for (i, record) in self.records.iter().enumerate() {
+            errors.check_region(format!("Records[{i}].awsRegion"), Some(record.aws_region.as_str()));
+            errors.check_event_source(
+                format!("Records[{i}].eventSource"),
+                record.event_source.as_deref(),
+                "aws:dynamodb",
+            );
+        }
+        errors.into_result()
+    }
+}
+
 /// `TimeWindowEvent` represents an Amazon Dynamodb event when using time windows
 /// ref. <https://docs.aws.amazon.com/lambda/latest/dg/with-ddb.html#services-ddb-windows>
 #[non_exhaustive]
@@ -188,6 +210,10 @@ pub struct TimeWindowEventResponse {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct EventRecord {
     /// The region in which the GetRecords request was received.
@@ -254,9 +280,22 @@ pub struct EventRecord {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "arn")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arn")))]
+impl EventRecord {
+    /// Parses `event_source_arn` into an [`Arn`], returning `None` when the event source ARN is not present.
+    pub fn parsed_event_source_arn(&self) -> Result<Option<Arn>, ArnError> {
+        parse_optional_arn(self.event_source_arn.as_deref())
+    }
+}
+
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct UserIdentity {
     #[serde(default)]
@@ -278,6 +317,10 @@ pub struct UserIdentity {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct StreamRecord {
     /// The approximate date and time when the stream record was created, in UNIX
@@ -0,0 +1,212 @@
+use serde::{
+    de::{self, DeserializeOwned, Deserializer, Visitor},
+    Serialize,
+};
+use std::{collections::BTreeMap, fmt};
+
+/// Input transformer of an EventBridge rule target, generated from the type the target receives.
+///
+/// Field names are taken from the `Deserialize` implementation of the type, so renames like
+/// `#[serde(rename_all = "camelCase")]` are applied, and the rule stays in sync with the type.
+/// It serializes with the `InputPathsMap` and `InputTemplate` keys used by the EventBridge API
+/// and by CloudFormation.
+///
+/// See <https://docs.aws.amazon.com/eventbridge/latest/userguide/eb-transform-target-input.html>
+///
+/// ```
+/// use aws_lambda_events::eventbridge::InputTransformer;
+///
+/// #[derive(serde::Deserialize)]
+/// #[serde(rename_all = "kebab-case")]
+/// struct Ec2StateChange {
+///     instance_id: String,
+///     state: String,
+/// }
+///
+/// let transformer = InputTransformer::from_detail::<Ec2StateChange>().unwrap();
+/// assert_eq!("$.detail.instance-id", transformer.input_paths_map["instance_id"]);
+/// assert_eq!(r#"{"instance-id": <instance_id>, "state": <state>}"#, transformer.input_template);
+/// ```
+#[non_exhaustive]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct InputTransformer {
+    /// Variables of the template, and the JSON path of the event they are read from.
+    pub input_paths_map: BTreeMap<String, String>,
+    /// JSON template of the input the target receives.
+    pub input_template: String,
+}
+
+impl InputTransformer {
+    /// Generates a transformer that sends the fields of `T` from the `detail` of the event,
+    /// for targets that receive `T` instead of the whole `EventBridgeEvent<T>`.
+    pub fn from_detail<T: DeserializeOwned>() -> Result<Self, InputTransformerError> {
+        Self::from_path::<T>("$.detail")
+    }
+
+    /// Generates a transformer that sends the fields of `T` from the object at `path` in the event.
+    pub fn from_path<T: DeserializeOwned>(path: &str) -> Result<Self, InputTransformerError> {
+        let fields = field_names::<T>()?;
+        let mut input_paths_map = BTreeMap::new();
+        let mut template = Vec::with_capacity(fields.len());
+        for field in fields {
+            let variable = variable_name(field);
+            input_paths_map.insert(variable.clone(), format!("{path}.{field}"));
+            template.push(format!("{}: <{variable}>", serde_json::Value::from(*field)));
+        }
+
+        Ok(InputTransformer {
+            input_paths_map,
+            input_template: format!("{{{}}}", template.join(", ")),
+        })
+    }
+}
+
+/// Error returned when an input transformer can't be generated from a type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InputTransformerError(String);
+
+impl fmt::Display for InputTransformerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for InputTransformerError {}
+
+// Variables may only contain alphanumeric characters and underscores.
+fn variable_name(field: &str) -> String {
+    field
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+// Derived `Deserialize` implementations of structs hand their field names to
+// `deserialize_struct`, so a deserializer that stops there can read them.
+fn field_names<T: DeserializeOwned>() -> Result<&'static [&'static str], InputTransformerError> {
+    match T::deserialize(FieldNames) {
+        Err(FieldNamesError::Found(fields)) => Ok(fields),
+        Err(FieldNamesError::Other(message)) => Err(InputTransformerError(message)),
+        Ok(_) => Err(InputTransformerError(NOT_A_STRUCT.to_string())),
+    }
+}
+
+const NOT_A_STRUCT: &str = "input transformers can only be generated from structs without flattened fields";
+
+#[derive(Debug)]
+enum FieldNamesError {
+    Found(&'static [&'static str]),
+    Other(String),
+}
+
+impl fmt::Display for FieldNamesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldNamesError::Found(fields) => write!(f, "found fields {fields:?}"),
+            FieldNamesError::Other(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for FieldNamesError {}
+
+impl de::Error for FieldNamesError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        FieldNamesError::Other(msg.to_string())
+    }
+}
+
+struct FieldNames;
+
+impl<'de> Deserializer<'de> for FieldNames {
+    type Error = FieldNamesError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(FieldNamesError::Other(NOT_A_STRUCT.to_string()))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(FieldNamesError::Found(fields))
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    #[allow(dead_code)]
+    struct OrderPlaced {
+        order_id: String,
+        total_amount: f64,
+        customer: Customer,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Customer {
+        id: String,
+    }
+
+    #[test]
+    fn from_detail() {
+        let transformer = InputTransformer::from_detail::<OrderPlaced>().unwrap();
+        assert_eq!(
+            BTreeMap::from([
+                ("orderId".to_string(), "$.detail.orderId".to_string()),
+                ("totalAmount".to_string(), "$.detail.totalAmount".to_string()),
+                ("customer".to_string(), "$.detail.customer".to_string()),
+            ]),
+            transformer.input_paths_map
+        );
+        assert_eq!(
+            r#"{"orderId": <orderId>, "totalAmount": <totalAmount>, "customer": <customer>}"#,
+            transformer.input_template
+        );
+
+        let output = serde_json::to_value(&transformer).unwrap();
+        assert_eq!("$.detail.orderId", output["InputPathsMap"]["orderId"]);
+        assert!(output["InputTemplate"].is_string());
+    }
+
+    #[test]
+    fn from_path() {
+        let transformer = InputTransformer::from_path::<Customer>("$.detail.customer").unwrap();
+        assert_eq!("$.detail.customer.id", transformer.input_paths_map["id"]);
+    }
+
+    #[test]
+    fn reject_non_structs() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Flattened {
+            #[serde(flatten)]
+            other: serde_json::Map<String, serde_json::Value>,
+        }
+
+        assert!(InputTransformer::from_detail::<String>().is_err());
+        assert!(InputTransformer::from_detail::<Flattened>().is_err());
+    }
+}
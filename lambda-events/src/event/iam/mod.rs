@@ -13,6 +13,10 @@ use serde::{
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 pub struct IamPolicyDocument {
     #[serde(default)]
@@ -32,6 +36,10 @@ pub struct IamPolicyDocument {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 pub struct IamPolicyStatement {
     #[serde(deserialize_with = "deserialize_string_or_slice")]
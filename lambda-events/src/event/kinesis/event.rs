@@ -1,3 +1,7 @@
+#[cfg(feature = "arn")]
+use crate::encodings::{parse_optional_arn, Arn, ArnError};
+#[cfg(feature = "validate")]
+use crate::validate::{Validate, ValidationErrors};
 use crate::{
     encodings::{Base64Data, SecondTimestamp},
     time_window::{TimeWindowEventResponseProperties, TimeWindowProperties},
@@ -25,6 +29,24 @@ pub struct KinesisEvent {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+impl Validate for KinesisEvent {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_not_empty("Records", &self.records);
+        for (i, record) in self.records.iter().enumerate() {
+            errors.check_region(format!("Records[{i}].awsRegion"), record.aws_region.as_deref());
+            errors.check_event_source(
+                format!("Records[{i}].eventSource"),
+                record.event_source.as_deref(),
+                "aws:kinesis",
+            );
+        }
+        errors.into_result()
+    }
+}
+
 /// `KinesisTimeWindowEvent` represents an Amazon Dynamodb event when using time windows
 /// ref. <https://docs.aws.amazon.com/lambda/latest/dg/with-kinesis.html#services-kinesis-windows>
 #[non_exhaustive]
@@ -71,6 +93,10 @@ pub struct KinesisTimeWindowEventResponse {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct KinesisEventRecord {
     /// nolint: stylecheck
@@ -103,9 +129,22 @@ pub struct KinesisEventRecord {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "arn")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arn")))]
+impl KinesisEventRecord {
+    /// Parses `event_source_arn` into an [`Arn`], returning `None` when the event source ARN is not present.
+    pub fn parsed_event_source_arn(&self) -> Result<Option<Arn>, ArnError> {
+        parse_optional_arn(self.event_source_arn.as_deref())
+    }
+}
+
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct KinesisRecord {
     pub approximate_arrival_timestamp: SecondTimestamp,
@@ -1,3 +1,5 @@
+#[cfg(feature = "validate")]
+use crate::validate::{Validate, ValidationErrors};
 #[cfg(feature = "builders")]
 use bon::Builder;
 use http::HeaderMap;
@@ -6,12 +8,16 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
-use crate::custom_serde::{deserialize_lambda_map, serialize_headers};
+use crate::custom_serde::{deserialize_lambda_map, serialize_comma_separated_headers, serialize_headers};
 
 /// `LambdaFunctionUrlRequest` contains data coming from the HTTP request to a Lambda Function URL.
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct LambdaFunctionUrlRequest {
     /// Version is expected to be `"2.0"`
@@ -41,10 +47,24 @@ pub struct LambdaFunctionUrlRequest {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+impl Validate for LambdaFunctionUrlRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_base64_body("body", self.is_base64_encoded, self.body.as_deref());
+        errors.into_result()
+    }
+}
+
 /// `LambdaFunctionUrlRequestContext` contains the information to identify the AWS account and resources invoking the Lambda function.
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct LambdaFunctionUrlRequestContext {
     #[serde(default)]
@@ -80,6 +100,10 @@ pub struct LambdaFunctionUrlRequestContext {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct LambdaFunctionUrlRequestContextAuthorizerDescription {
     pub iam: Option<LambdaFunctionUrlRequestContextAuthorizerIamDescription>,
@@ -97,6 +121,10 @@ pub struct LambdaFunctionUrlRequestContextAuthorizerDescription {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct LambdaFunctionUrlRequestContextAuthorizerIamDescription {
     #[serde(default)]
@@ -123,6 +151,10 @@ pub struct LambdaFunctionUrlRequestContextAuthorizerIamDescription {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct LambdaFunctionUrlRequestContextHttpDescription {
     #[serde(default)]
@@ -149,11 +181,15 @@ pub struct LambdaFunctionUrlRequestContextHttpDescription {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct LambdaFunctionUrlResponse {
     pub status_code: i64,
     #[serde(deserialize_with = "http_serde::header_map::deserialize", default)]
-    #[serde(serialize_with = "serialize_headers")]
+    #[serde(serialize_with = "serialize_comma_separated_headers")]
     pub headers: HeaderMap,
     #[serde(default)]
     pub body: Option<String>,
@@ -168,3 +204,13 @@ pub struct LambdaFunctionUrlResponse {
     #[cfg_attr(feature = "builders", builder(default))]
     pub other: serde_json::Map<String, Value>,
 }
+
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+impl Validate for LambdaFunctionUrlResponse {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_base64_body("body", self.is_base64_encoded, self.body.as_deref());
+        errors.into_result()
+    }
+}
@@ -57,6 +57,11 @@ pub mod cloudwatch_events;
 #[cfg_attr(docsrs, doc(cfg(feature = "cloudwatch_logs")))]
 pub mod cloudwatch_logs;
 
+/// CloudWatch Embedded Metric Format output types.
+#[cfg(feature = "cloudwatch_metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cloudwatch_metrics")))]
+pub mod cloudwatch_metrics;
+
 /// AWS Lambda event definitions for code_commit.
 #[cfg(feature = "code_commit")]
 #[cfg_attr(docsrs, doc(cfg(feature = "code_commit")))]
@@ -1,3 +1,5 @@
+#[cfg(feature = "validate")]
+use crate::validate::{Validate, ValidationErrors};
 #[cfg(feature = "builders")]
 use bon::Builder;
 use chrono::{DateTime, Utc};
@@ -7,11 +9,17 @@ use serde_json::Value;
 use std::collections::HashMap;
 
 use crate::custom_serde::deserialize_lambda_map;
+#[cfg(feature = "arn")]
+use crate::encodings::{parse_optional_arn, Arn, ArnError};
 
 /// `S3Event` which wrap an array of `S3Event`Record
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct S3Event {
     #[serde(rename = "Records")]
@@ -26,10 +34,32 @@ pub struct S3Event {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+impl Validate for S3Event {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_not_empty("Records", &self.records);
+        for (i, record) in self.records.iter().enumerate() {
+            errors.check_region(format!("Records[{i}].awsRegion"), record.aws_region.as_deref());
+            errors.check_event_source(
+                format!("Records[{i}].eventSource"),
+                record.event_source.as_deref(),
+                "aws:s3",
+            );
+        }
+        errors.into_result()
+    }
+}
+
 /// `S3EventRecord` which wrap record data
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct S3EventRecord {
     #[serde(default)]
@@ -61,6 +91,10 @@ pub struct S3EventRecord {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct S3UserIdentity {
     #[serde(default)]
@@ -78,6 +112,10 @@ pub struct S3UserIdentity {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct S3RequestParameters {
     #[serde(default)]
@@ -96,6 +134,10 @@ pub struct S3RequestParameters {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct S3Entity {
     #[serde(default)]
@@ -118,6 +160,10 @@ pub struct S3Entity {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct S3Bucket {
     #[serde(default)]
@@ -136,9 +182,22 @@ pub struct S3Bucket {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "arn")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arn")))]
+impl S3Bucket {
+    /// Parses `arn` into an [`Arn`], returning `None` when the bucket ARN is not present.
+    pub fn parsed_arn(&self) -> Result<Option<Arn>, ArnError> {
+        parse_optional_arn(self.arn.as_deref())
+    }
+}
+
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct S3Object {
     #[serde(default)]
@@ -1,3 +1,5 @@
+#[cfg(feature = "validate")]
+use crate::validate::{Validate, ValidationErrors};
 #[cfg(feature = "builders")]
 use bon::Builder;
 use chrono::{DateTime, Utc};
@@ -7,6 +9,8 @@ use serde_json::Value;
 use std::collections::HashMap;
 
 use crate::custom_serde::{deserialize_lambda_map, deserialize_nullish_boolean};
+#[cfg(feature = "arn")]
+use crate::encodings::{Arn, ArnError};
 
 /// The `Event` notification event handled by Lambda
 ///
@@ -14,6 +18,10 @@ use crate::custom_serde::{deserialize_lambda_map, deserialize_nullish_boolean};
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 pub struct SnsEvent {
     pub records: Vec<SnsRecord>,
@@ -28,10 +36,31 @@ pub struct SnsEvent {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+impl Validate for SnsEvent {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_not_empty("Records", &self.records);
+        for (i, record) in self.records.iter().enumerate() {
+            errors.check_event_source(
+                format!("Records[{i}].eventSource"),
+                Some(record.event_source.as_str()),
+                "aws:sns",
+            );
+        }
+        errors.into_result()
+    }
+}
+
 /// SnsRecord stores information about each record of a SNS event
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 pub struct SnsRecord {
     /// A string containing the event source.
@@ -56,6 +85,15 @@ pub struct SnsRecord {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "arn")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arn")))]
+impl SnsRecord {
+    /// Parses `event_subscription_arn` into an [`Arn`].
+    pub fn parsed_event_subscription_arn(&self) -> Result<Arn, ArnError> {
+        self.event_subscription_arn.parse()
+    }
+}
+
 /// SnsMessage stores information about SNS **Notification** type messages only.
 ///
 /// **Important**: This struct is designed specifically for handling SNS Notification messages
@@ -64,6 +102,10 @@ pub struct SnsRecord {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 pub struct SnsMessage {
     /// The type of SNS message. For a lambda event, this should always be **Notification**
@@ -119,6 +161,15 @@ pub struct SnsMessage {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "arn")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arn")))]
+impl SnsMessage {
+    /// Parses `topic_arn` into an [`Arn`].
+    pub fn parsed_topic_arn(&self) -> Result<Arn, ArnError> {
+        self.topic_arn.parse()
+    }
+}
+
 /// SnsSubscriptionMessage stores information about SNS SubscriptionConfirmation and
 /// UnsubscribeConfirmation type messages.
 ///
@@ -149,6 +200,10 @@ pub struct SnsMessage {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 pub struct SnsSubscriptionMessage {
     /// The type of SNS message. Will be "SubscriptionConfirmation" or "UnsubscribeConfirmation".
@@ -214,6 +269,10 @@ pub struct SnsSubscriptionMessage {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 #[serde(bound(deserialize = "T: DeserializeOwned"))]
 pub struct SnsEventObj<T: Serialize> {
@@ -233,6 +292,10 @@ pub struct SnsEventObj<T: Serialize> {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 #[serde(bound(deserialize = "T: DeserializeOwned"))]
 pub struct SnsRecordObj<T: Serialize> {
@@ -267,6 +330,10 @@ pub struct SnsRecordObj<T: Serialize> {
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[serde_with::serde_as]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 #[serde(bound(deserialize = "T: DeserializeOwned"))]
 pub struct SnsMessageObj<T: Serialize> {
@@ -333,6 +400,10 @@ pub struct SnsMessageObj<T: Serialize> {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 pub struct MessageAttribute {
     /// The data type of the attribute. Per the [SNS Developer Guide](https://docs.aws.amazon.com/sns/latest/dg/sns-message-attributes.html), lambda notifications, this will only be **String** or **Binary**.
     #[serde(rename = "Type")]
@@ -355,6 +426,10 @@ pub struct MessageAttribute {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 pub struct CloudWatchAlarmPayload {
     pub alarm_name: String,
@@ -381,6 +456,10 @@ pub struct CloudWatchAlarmPayload {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 pub struct CloudWatchAlarmTrigger {
     pub period: i64,
@@ -411,6 +490,10 @@ pub struct CloudWatchAlarmTrigger {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 pub struct CloudWatchMetricDataQuery {
     pub id: String,
@@ -433,6 +516,10 @@ pub struct CloudWatchMetricDataQuery {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 pub struct CloudWatchMetricStat {
     pub metric: CloudWatchMetric,
@@ -452,6 +539,10 @@ pub struct CloudWatchMetricStat {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 pub struct CloudWatchMetric {
     #[serde(default)]
@@ -471,6 +562,10 @@ pub struct CloudWatchMetric {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 pub struct CloudWatchDimension {
     pub name: String,
     pub value: String,
@@ -1,3 +1,7 @@
+#[cfg(feature = "arn")]
+use crate::encodings::{parse_optional_arn, Arn, ArnError};
+#[cfg(feature = "validate")]
+use crate::validate::{Validate, ValidationErrors};
 use crate::{custom_serde::deserialize_lambda_map, encodings::Base64Data};
 #[cfg(feature = "builders")]
 use bon::Builder;
@@ -10,6 +14,10 @@ use std::collections::HashMap;
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct SqsEvent {
     #[serde(rename = "Records")]
@@ -24,10 +32,32 @@ pub struct SqsEvent {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+impl Validate for SqsEvent {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_not_empty("Records", &self.records);
+        for (i, record) in self.records.iter().enumerate() {
+            errors.check_region(format!("Records[{i}].awsRegion"), record.aws_region.as_deref());
+            errors.check_event_source(
+                format!("Records[{i}].eventSource"),
+                record.event_source.as_deref(),
+                "aws:sqs",
+            );
+        }
+        errors.into_result()
+    }
+}
+
 /// An individual SQS Message, its metadata, and Message Attributes
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct SqsMessage {
     /// nolint: stylecheck
@@ -64,10 +94,23 @@ pub struct SqsMessage {
     pub other: serde_json::Map<String, Value>,
 }
 
+#[cfg(feature = "arn")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arn")))]
+impl SqsMessage {
+    /// Parses `event_source_arn` into an [`Arn`], returning `None` when the event source ARN is not present.
+    pub fn parsed_event_source_arn(&self) -> Result<Option<Arn>, ArnError> {
+        parse_optional_arn(self.event_source_arn.as_deref())
+    }
+}
+
 /// Alternative to `SqsEvent` to be used alongside `SqsMessageObj<T>` when you need to deserialize a nested object into a struct of type `T` within the SQS Message rather than just using the raw SQS Message string
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 #[serde(bound(deserialize = "T: DeserializeOwned"))]
 pub struct SqsEventObj<T: Serialize> {
@@ -89,6 +132,10 @@ pub struct SqsEventObj<T: Serialize> {
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[serde_with::serde_as]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(bound(deserialize = "T: DeserializeOwned"))]
 #[serde(rename_all = "camelCase")]
 pub struct SqsMessageObj<T: Serialize> {
@@ -132,6 +179,10 @@ pub struct SqsMessageObj<T: Serialize> {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct SqsMessageAttribute {
     pub string_value: Option<String>,
@@ -155,6 +206,10 @@ pub struct SqsMessageAttribute {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct SqsBatchResponse {
     pub batch_item_failures: Vec<BatchItemFailure>,
@@ -283,6 +338,10 @@ impl SqsBatchResponse {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct BatchItemFailure {
     pub item_identifier: String,
@@ -300,6 +359,10 @@ pub struct BatchItemFailure {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 #[serde(bound(deserialize = "T: DeserializeOwned"))]
 pub struct SqsApiEventObj<T: Serialize> {
@@ -319,6 +382,10 @@ pub struct SqsApiEventObj<T: Serialize> {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "camelCase")]
 pub struct SqsApiEvent {
     pub messages: Vec<SqsApiMessage>,
@@ -339,6 +406,10 @@ pub struct SqsApiEvent {
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[serde_with::serde_as]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(bound(deserialize = "T: DeserializeOwned"))]
 #[serde(rename_all = "PascalCase")]
 pub struct SqsApiMessageObj<T: Serialize> {
@@ -375,6 +446,10 @@ pub struct SqsApiMessageObj<T: Serialize> {
 #[non_exhaustive]
 #[cfg_attr(feature = "builders", derive(Builder))]
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(
+    all(feature = "strict", not(feature = "catch-all-fields")),
+    serde(deny_unknown_fields)
+)]
 #[serde(rename_all = "PascalCase")]
 pub struct SqsApiMessage {
     /// nolint: stylecheck
@@ -418,6 +493,32 @@ mod test {
         assert_eq!(parsed, reparsed);
     }
 
+    #[test]
+    #[cfg(all(feature = "sqs", feature = "arn"))]
+    fn example_sqs_event_source_arn() {
+        let data = include_bytes!("../../fixtures/example-sqs-event.json");
+        let parsed: SqsEvent = serde_json::from_slice(data).unwrap();
+        let arn = parsed.records[0].parsed_event_source_arn().unwrap().unwrap();
+        assert_eq!("sqs", arn.service());
+        assert_eq!(Some("us-west-2"), arn.region());
+        assert_eq!(Some("123456789012"), arn.account_id());
+        assert_eq!("SQSQueue", arn.resource());
+    }
+
+    #[test]
+    #[cfg(all(feature = "sqs", feature = "validate"))]
+    fn example_sqs_event_validate() {
+        let data = include_bytes!("../../fixtures/example-sqs-event.json");
+        let mut parsed: SqsEvent = serde_json::from_slice(data).unwrap();
+        assert_eq!(Ok(()), parsed.validate());
+
+        parsed.records[0].aws_region = Some("us_west_2".to_string());
+        parsed.records[0].event_source = Some("aws:sns".to_string());
+        let errors = parsed.validate().unwrap_err();
+        let paths: Vec<_> = errors.errors().iter().map(|e| e.path()).collect();
+        assert_eq!(vec!["Records[0].awsRegion", "Records[0].eventSource"], paths);
+    }
+
     #[test]
     #[cfg(feature = "sqs")]
     fn example_sqs_obj_event() {
@@ -7,12 +7,19 @@ pub use http;
 #[cfg_attr(docsrs, doc(cfg(feature = "query_map")))]
 pub use query_map;
 
-mod custom_serde;
+/// Serde helpers for the encodings used in AWS Lambda json event values.
+pub mod custom_serde;
 /// Encodings used in AWS Lambda json event values.
 pub mod encodings;
+#[cfg(feature = "strict")]
+#[cfg_attr(docsrs, doc(cfg(feature = "strict")))]
+pub mod strict;
 #[cfg(feature = "chrono")]
 #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
 pub mod time_window;
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+pub mod validate;
 
 /// AWS Lambda event definitions.
 pub mod event;
@@ -72,6 +79,11 @@ pub use event::cloudwatch_events;
 #[cfg_attr(docsrs, doc(cfg(feature = "cloudwatch_logs")))]
 pub use event::cloudwatch_logs;
 
+/// CloudWatch Embedded Metric Format output types.
+#[cfg(feature = "cloudwatch_metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cloudwatch_metrics")))]
+pub use event::cloudwatch_metrics;
+
 /// AWS Lambda event definitions for code_commit.
 #[cfg(feature = "code_commit")]
 #[cfg_attr(docsrs, doc(cfg(feature = "code_commit")))]
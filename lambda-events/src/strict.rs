@@ -0,0 +1,82 @@
+//! Helpers for deserializing events with the `strict` feature enabled.
+//!
+//! With `strict`, event structs reject any field they don't model, which lets
+//! test suites and contract-testing pipelines detect schema drift early. The
+//! functions in this module wrap `serde_json` with [`serde_path_to_error`], so
+//! a failure reports the exact path of the offending field, e.g.
+//! `Records[0].s3.object.unknownField`.
+//!
+//! `catch-all-fields` takes precedence over `strict`: when both are enabled,
+//! unknown fields are captured in the `other` maps rather than rejected.
+//!
+//! Structs that are `#[serde(flatten)]`-ed into another struct, and the structs
+//! that flatten them, can't deny unknown fields and stay permissive.
+use serde::de::DeserializeOwned;
+
+pub use serde_path_to_error::{Error, Path, Segment};
+
+/// Deserializes an event from a slice of JSON bytes, reporting the path of the field that failed.
+pub fn from_slice<T>(data: &[u8]) -> Result<T, Error<serde_json::Error>>
+where
+    T: DeserializeOwned,
+{
+    let mut de = serde_json::Deserializer::from_slice(data);
+    serde_path_to_error::deserialize(&mut de)
+}
+
+/// Deserializes an event from a JSON string, reporting the path of the field that failed.
+pub fn from_str<T>(data: &str) -> Result<T, Error<serde_json::Error>>
+where
+    T: DeserializeOwned,
+{
+    let mut de = serde_json::Deserializer::from_str(data);
+    serde_path_to_error::deserialize(&mut de)
+}
+
+/// Deserializes an event from a [`serde_json::Value`], reporting the path of the field that failed.
+pub fn from_value<T>(value: serde_json::Value) -> Result<T, Error<serde_json::Error>>
+where
+    T: DeserializeOwned,
+{
+    serde_path_to_error::deserialize(value)
+}
+
+#[cfg(test)]
+#[cfg(feature = "sqs")]
+mod test {
+    use super::*;
+    use crate::event::sqs::SqsEvent;
+
+    #[test]
+    fn accepts_known_fields() {
+        let data = include_bytes!("fixtures/example-sqs-event.json");
+        let parsed: SqsEvent = from_slice(data).unwrap();
+        assert_eq!(parsed.records.len(), 1);
+    }
+
+    #[test]
+    #[cfg(not(feature = "catch-all-fields"))]
+    fn rejects_unknown_fields_with_path() {
+        let mut value: serde_json::Value =
+            serde_json::from_slice(include_bytes!("fixtures/example-sqs-event.json")).unwrap();
+        value["Records"][0]["unknownField"] = serde_json::json!("drift");
+
+        let err = from_value::<SqsEvent>(value).unwrap_err();
+        assert_eq!(err.path().to_string(), "Records[0].unknownField");
+        assert!(err.inner().to_string().contains("unknown field `unknownField`"));
+    }
+
+    #[test]
+    #[cfg(feature = "catch-all-fields")]
+    fn captures_unknown_fields_with_catch_all_fields() {
+        let mut value: serde_json::Value =
+            serde_json::from_slice(include_bytes!("fixtures/example-sqs-event.json")).unwrap();
+        value["Records"][0]["unknownField"] = serde_json::json!("drift");
+
+        let parsed = from_value::<SqsEvent>(value).unwrap();
+        assert_eq!(
+            parsed.records[0].other.get("unknownField"),
+            Some(&serde_json::json!("drift"))
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Structural validation of events, beyond what serde checks.
+//!
+//! Events delivered by AWS always hold these invariants, but events synthesized by tests
+//! or third-party emulators may not. Enable the `validate` feature to check them:
+//!
+//! ```
+//! use aws_lambda_events::{sqs::SqsEvent, validate::Validate};
+//!
+//! let event = SqsEvent::default();
+//! let errors = event.validate().unwrap_err();
+//! assert_eq!("Records: must not be empty", errors.to_string());
+//! ```
+use std::fmt;
+
+/// Events that can check structural invariants that deserialization doesn't enforce.
+pub trait Validate {
+    /// Checks the event, returning every invariant that doesn't hold.
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+/// An invariant that doesn't hold, and the path of the field that breaks it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValidationError {
+    path: String,
+    message: String,
+}
+
+impl ValidationError {
+    /// The path of the field, using the names of the JSON payload, e.g. `Records[0].awsRegion`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// What is wrong with the field.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Every invariant that doesn't hold in an event.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ValidationErrors {
+    errors: Vec<ValidationError>,
+}
+
+impl ValidationErrors {
+    /// Creates an empty list of errors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an error for the field at `path`.
+    pub fn push(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(ValidationError {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    /// The errors, in the order they were found.
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.errors
+    }
+
+    /// Whether no error was found.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns `Ok` when no error was found, or the errors otherwise.
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+
+    #[cfg(any(
+        feature = "dynamodb",
+        feature = "kinesis",
+        feature = "s3",
+        feature = "sns",
+        feature = "sqs"
+    ))]
+    pub(crate) fn check_not_empty<T>(&mut self, path: &str, records: &[T]) {
+        if records.is_empty() {
+            self.push(path, "must not be empty");
+        }
+    }
+
+    #[cfg(any(feature = "dynamodb", feature = "kinesis", feature = "s3", feature = "sqs"))]
+    pub(crate) fn check_region(&mut self, path: String, region: Option<&str>) {
+        if let Some(region) = region {
+            if !is_region(region) {
+                self.push(path, format!("`{region}` is not a valid region"));
+            }
+        }
+    }
+
+    #[cfg(any(
+        feature = "dynamodb",
+        feature = "kinesis",
+        feature = "s3",
+        feature = "sns",
+        feature = "sqs"
+    ))]
+    pub(crate) fn check_event_source(&mut self, path: String, event_source: Option<&str>, expected: &str) {
+        if let Some(event_source) = event_source {
+            if event_source != expected {
+                self.push(path, format!("expected `{expected}`, got `{event_source}`"));
+            }
+        }
+    }
+
+    #[cfg(any(feature = "alb", feature = "apigw", feature = "lambda_function_urls"))]
+    pub(crate) fn check_base64_body(&mut self, path: &str, is_base64_encoded: bool, body: Option<&str>) {
+        use base64::Engine;
+
+        if let (true, Some(body)) = (is_base64_encoded, body) {
+            if base64::engine::general_purpose::STANDARD.decode(body).is_err() {
+                self.push(path, "is not valid base64 but isBase64Encoded is true");
+            }
+        }
+    }
+
+    #[cfg(any(feature = "alb", feature = "apigw"))]
+    pub(crate) fn check_body(&mut self, path: &str, is_base64_encoded: bool, body: Option<&crate::encodings::Body>) {
+        use crate::encodings::Body;
+
+        match body {
+            Some(Body::Binary(_)) if !is_base64_encoded => {
+                self.push(path, "is binary but isBase64Encoded is false");
+            }
+            Some(Body::Text(text)) => self.check_base64_body(path, is_base64_encoded, Some(text)),
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.errors.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+// Regions look like `us-east-1`, `us-gov-west-1`, or `ap-southeast-4`.
+#[cfg(any(feature = "dynamodb", feature = "kinesis", feature = "s3", feature = "sqs"))]
+fn is_region(region: &str) -> bool {
+    let parts: Vec<&str> = region.split('-').collect();
+    parts.len() >= 3
+        && parts[0].len() == 2
+        && parts[..parts.len() - 1]
+            .iter()
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_lowercase()))
+        && parts[parts.len() - 1].parse::<u8>().is_ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "sqs")]
+    fn regions() {
+        for region in [
+            "us-east-1",
+            "us-gov-west-1",
+            "ap-southeast-4",
+            "cn-north-1",
+            "il-central-1",
+        ] {
+            assert!(is_region(region), "{region} should be valid");
+        }
+        for region in [
+            "",
+            "us-east",
+            "useast1",
+            "US-EAST-1",
+            "us--1",
+            "usa-east-1",
+            "us-east-x",
+        ] {
+            assert!(!is_region(region), "{region} should be invalid");
+        }
+    }
+
+    #[test]
+    fn display_errors() {
+        let mut errors = ValidationErrors::new();
+        assert_eq!(Ok(()), errors.clone().into_result());
+
+        errors.push("Records[0].awsRegion", "`moon-1` is not a valid region");
+        errors.push("Records[1].eventSource", "expected `aws:sqs`, got `aws:sns`");
+        assert_eq!(
+            "Records[0].awsRegion: `moon-1` is not a valid region; Records[1].eventSource: expected `aws:sqs`, got `aws:sns`",
+            errors.to_string()
+        );
+        assert_eq!("Records[1].eventSource", errors.errors()[1].path());
+    }
+}
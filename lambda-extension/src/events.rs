@@ -1,7 +1,12 @@
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use std::{future::Future, time::Duration};
+use tokio::time::Instant;
+
+use crate::{Error, ExtensionError};
 
 /// Request tracing information
-#[derive(Debug, Default, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Tracing {
     /// The type of tracing exposed to the extension
@@ -10,7 +15,7 @@ pub struct Tracing {
     pub value: String,
 }
 /// Event received when there is a new Lambda invocation.
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct InvokeEvent {
     /// The time that the function times out
@@ -24,20 +29,94 @@ pub struct InvokeEvent {
     pub tracing: Tracing,
 }
 
+/// Time that [`ShutdownEvent::run_until_deadline`] leaves to the extension after its cleanup.
+pub const SHUTDOWN_MARGIN: Duration = Duration::from_millis(100);
+
 /// Event received when a Lambda function shuts down.
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShutdownEvent {
     /// The reason why the function terminates
-    /// It can be SPINDOWN, TIMEOUT, or FAILURE
-    pub shutdown_reason: String,
-    /// The time that the function times out
+    pub shutdown_reason: ShutdownReason,
+    /// The time that the extension must exit by, in milliseconds since the Unix epoch
     pub deadline_ms: u64,
 }
 
+impl ShutdownEvent {
+    /// The time that the extension must exit by.
+    pub fn deadline(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(self.deadline_ms as i64).unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+
+    /// Time left until the deadline, which is zero once the deadline passed.
+    pub fn remaining(&self) -> Duration {
+        (self.deadline() - Utc::now()).to_std().unwrap_or_default()
+    }
+
+    /// The deadline as an [`Instant`], for timers of the Tokio runtime.
+    pub fn deadline_instant(&self) -> Instant {
+        Instant::now() + self.remaining()
+    }
+
+    /// Run a cleanup future, like the flush of buffered records, until
+    /// [`SHUTDOWN_MARGIN`] before the deadline.
+    ///
+    /// Lambda stops the extension at the deadline, so a cleanup that can't finish in time is
+    /// dropped, and an error is returned, leaving the extension the margin to log it.
+    ///
+    /// ```rust,no_run
+    /// use lambda_extension::{Error, LambdaEvent, NextEvent, ShutdownReason};
+    ///
+    /// async fn events_processor(event: LambdaEvent) -> Result<(), Error> {
+    ///     if let NextEvent::Shutdown(shutdown) = event.next {
+    ///         if shutdown.shutdown_reason == ShutdownReason::Failure {
+    ///             eprintln!("the function failed");
+    ///         }
+    ///         shutdown.run_until_deadline(async { /* flush state */ }).await?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn run_until_deadline<F: Future>(&self, cleanup: F) -> Result<F::Output, Error> {
+        self.run_until_deadline_with_margin(SHUTDOWN_MARGIN, cleanup).await
+    }
+
+    /// Run a cleanup future until `margin` before the deadline, for extensions that need more
+    /// or less time than [`SHUTDOWN_MARGIN`] after their cleanup.
+    pub async fn run_until_deadline_with_margin<F: Future>(
+        &self,
+        margin: Duration,
+        cleanup: F,
+    ) -> Result<F::Output, Error> {
+        let timeout = self.remaining().saturating_sub(margin);
+        tokio::time::timeout(timeout, cleanup).await.map_err(|_| {
+            let err = format!("shutdown cleanup exceeded its time of {}ms", timeout.as_millis());
+            ExtensionError::boxed(err) as Error
+        })
+    }
+}
+
+/// Reason why a Lambda function shuts down.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ShutdownReason {
+    /// The execution environment is recycled, after it was idle or reached its maximum lifetime
+    #[serde(alias = "SPINDOWN")]
+    Spindown,
+    /// The function timed out
+    #[serde(alias = "TIMEOUT")]
+    Timeout,
+    /// The function or an extension failed, like when it ran out of memory
+    #[serde(alias = "FAILURE")]
+    Failure,
+    /// A reason that this version of the crate doesn't know about
+    #[serde(other)]
+    Other,
+}
+
 /// Event that the extension receives in
 /// either the INVOKE or SHUTDOWN phase
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "UPPERCASE", tag = "eventType")]
 pub enum NextEvent {
     /// Payload when the event happens in the INVOKE phase
@@ -51,10 +130,50 @@ impl NextEvent {
     pub fn is_invoke(&self) -> bool {
         matches!(self, NextEvent::Invoke(_))
     }
+
+    /// Type of the event
+    pub fn event_type(&self) -> EventType {
+        match self {
+            NextEvent::Invoke(_) => EventType::Invoke,
+            NextEvent::Shutdown(_) => EventType::Shutdown,
+        }
+    }
+}
+
+/// Type of the events that an extension registers for
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EventType {
+    /// Event sent when the function is invoked
+    Invoke,
+    /// Event sent when the execution environment shuts down, which internal extensions can't
+    /// register for
+    Shutdown,
+}
+
+impl EventType {
+    /// Name of the event type in the Extensions API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventType::Invoke => "INVOKE",
+            EventType::Shutdown => "SHUTDOWN",
+        }
+    }
+
+    pub(crate) fn parse(name: &str) -> Result<Self, Error> {
+        match name {
+            "INVOKE" => Ok(EventType::Invoke),
+            "SHUTDOWN" => Ok(EventType::Shutdown),
+            _ => {
+                let err = format!("unknown event type {name:?}, the only accepted events are INVOKE and SHUTDOWN");
+                Err(ExtensionError::boxed(err))
+            }
+        }
+    }
 }
 
 /// Wrapper with information about the next
 /// event that the Lambda Runtime is going to process
+#[derive(Clone, Debug)]
 pub struct LambdaEvent {
     /// Next incoming event
     pub next: NextEvent,
@@ -65,3 +184,58 @@ impl LambdaEvent {
         LambdaEvent { next }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_shutdown_events() {
+        let data = r#"{"eventType":"SHUTDOWN","shutdownReason":"spindown","deadlineMs":1665532800000}"#;
+        let NextEvent::Shutdown(event) = serde_json::from_str(data).unwrap() else {
+            panic!("expected a shutdown event");
+        };
+        assert_eq!(ShutdownReason::Spindown, event.shutdown_reason);
+        assert_eq!(
+            "2022-10-12T00:00:00Z",
+            event.deadline().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        );
+        assert_eq!(Duration::ZERO, event.remaining());
+
+        let data = r#"{"shutdownReason":"TIMEOUT","deadlineMs":0}"#;
+        let event: ShutdownEvent = serde_json::from_str(data).unwrap();
+        assert_eq!(ShutdownReason::Timeout, event.shutdown_reason);
+        let data = r#"{"shutdownReason":"eviction","deadlineMs":0}"#;
+        let event: ShutdownEvent = serde_json::from_str(data).unwrap();
+        assert_eq!(ShutdownReason::Other, event.shutdown_reason);
+    }
+
+    #[tokio::test]
+    async fn run_cleanup_until_the_deadline() {
+        let deadline_ms = (Utc::now() + chrono::TimeDelta::seconds(60)).timestamp_millis() as u64;
+        let event = ShutdownEvent {
+            shutdown_reason: ShutdownReason::Spindown,
+            deadline_ms,
+        };
+        assert_eq!(42, event.run_until_deadline(async { 42 }).await.unwrap());
+
+        let event = ShutdownEvent {
+            shutdown_reason: ShutdownReason::Timeout,
+            deadline_ms: 0,
+        };
+        let result = event.run_until_deadline(std::future::pending::<()>()).await;
+        assert!(result.is_err());
+
+        // The margin leaves a second, so a cleanup that sleeps longer than the margin times out.
+        let deadline_ms = (Utc::now() + chrono::TimeDelta::milliseconds(1100)).timestamp_millis() as u64;
+        let event = ShutdownEvent {
+            shutdown_reason: ShutdownReason::Spindown,
+            deadline_ms,
+        };
+        let margin = Duration::from_secs(1);
+        let start = Instant::now();
+        let cleanup = tokio::time::sleep(Duration::from_millis(500));
+        assert!(event.run_until_deadline_with_margin(margin, cleanup).await.is_err());
+        assert!(start.elapsed() < Duration::from_millis(500));
+    }
+}
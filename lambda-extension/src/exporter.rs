@@ -0,0 +1,554 @@
+use chrono::{DateTime, Utc};
+use http::{
+    header::{HeaderName, CONTENT_TYPE},
+    HeaderValue, Method, Uri,
+};
+use http_body_util::BodyExt;
+use lambda_runtime_api_client::{
+    body::Body,
+    connect::{Connection, DynConnector},
+    Client,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::{sleep_until, Instant},
+};
+use tower::Service;
+use tracing::error;
+
+use crate::{Error, ExtensionError};
+
+/// Destination of the records that an extension receives from the Logs or Telemetry API, like
+/// a log storage or an OpenTelemetry collector.
+///
+/// Exporters receive batches of records from a [`BatchExporter`], so an extension that ships
+/// logs only needs to implement this trait:
+///
+/// ```rust,no_run
+/// use lambda_extension::{BatchExporter, Error, Extension, LambdaTelemetry, SharedService, TelemetryExporter};
+/// use std::future::Future;
+///
+/// struct Stdout;
+///
+/// impl TelemetryExporter<LambdaTelemetry> for Stdout {
+///     fn export(&mut self, batch: Vec<LambdaTelemetry>) -> impl Future<Output = Result<(), Error>> + Send {
+///         async move {
+///             println!("{batch:?}");
+///             Ok(())
+///         }
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let exporter = BatchExporter::new(Stdout).start();
+///     Extension::new()
+///         .with_telemetry_processor(SharedService::new(exporter))
+///         .run()
+///         .await
+/// }
+/// ```
+pub trait TelemetryExporter<R>: Send + 'static {
+    /// Export a batch of records.
+    fn export(&mut self, batch: Vec<R>) -> impl Future<Output = Result<(), Error>> + Send;
+}
+
+/// Batching engine that buffers records and sends them to a [`TelemetryExporter`].
+///
+/// Records are exported once a batch reaches the [maximum size](Self::with_max_batch_size), or
+/// once the oldest buffered record waited for the [flush interval](Self::with_flush_interval),
+/// and when the [`ExportHandle`] flushes or shuts down. Errors of the exporter are logged, and
+/// the batch is dropped. Lambda freezes the execution environment between invocations, so
+/// extensions should [flush](ExportHandle::flush) before they ask for the next event, and
+/// [shut down](ExportHandle::shutdown) the exporter on the `SHUTDOWN` event, to not lose records.
+#[derive(Debug, Clone)]
+pub struct BatchExporter<X> {
+    exporter: X,
+    max_batch_size: usize,
+    flush_interval: Duration,
+}
+
+impl<X> BatchExporter<X> {
+    /// Create a batching engine for an exporter, with batches of up to 1,000 records, flushed
+    /// every second.
+    pub fn new(exporter: X) -> Self {
+        BatchExporter {
+            exporter,
+            max_batch_size: 1_000,
+            flush_interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Set the largest number of records in a batch.
+    pub fn with_max_batch_size(self, max_batch_size: usize) -> Self {
+        BatchExporter {
+            max_batch_size: max_batch_size.max(1),
+            ..self
+        }
+    }
+
+    /// Set the longest time that a record waits in the buffer before it's exported.
+    pub fn with_flush_interval(self, flush_interval: Duration) -> Self {
+        BatchExporter { flush_interval, ..self }
+    }
+
+    /// Start the engine in a task of the Tokio runtime.
+    ///
+    /// The returned handle is the [`Service`] that records are sent to, like the logs or
+    /// telemetry processor of an [`Extension`](crate::Extension).
+    pub fn start<R>(self) -> ExportHandle<R>
+    where
+        X: TelemetryExporter<R>,
+        R: Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel(64);
+        tokio::task::spawn(self.run(receiver));
+        ExportHandle { sender }
+    }
+
+    async fn run<R>(mut self, mut receiver: mpsc::Receiver<Command<R>>)
+    where
+        X: TelemetryExporter<R>,
+    {
+        let mut buffer = Vec::new();
+        let mut deadline = None;
+        loop {
+            let command = match deadline {
+                Some(deadline) => tokio::select! {
+                    command = receiver.recv() => command,
+                    _ = sleep_until(deadline) => Some(Command::Tick),
+                },
+                None => receiver.recv().await,
+            };
+            match command {
+                Some(Command::Records(records)) => {
+                    if buffer.is_empty() {
+                        deadline = Some(Instant::now() + self.flush_interval);
+                    }
+                    buffer.extend(records);
+                    while buffer.len() >= self.max_batch_size {
+                        let batch = buffer.drain(..self.max_batch_size).collect();
+                        log_error(self.exporter.export(batch).await);
+                    }
+                }
+                Some(Command::Tick) => log_error(self.export(&mut buffer).await),
+                Some(Command::Flush(reply)) => {
+                    let _ = reply.send(self.export(&mut buffer).await);
+                }
+                Some(Command::Shutdown(reply)) => {
+                    let _ = reply.send(self.export(&mut buffer).await);
+                    return;
+                }
+                None => {
+                    log_error(self.export(&mut buffer).await);
+                    return;
+                }
+            }
+            if buffer.is_empty() {
+                deadline = None;
+            }
+        }
+    }
+
+    async fn export<R>(&mut self, buffer: &mut Vec<R>) -> Result<(), Error>
+    where
+        X: TelemetryExporter<R>,
+    {
+        while !buffer.is_empty() {
+            let size = buffer.len().min(self.max_batch_size);
+            let batch = buffer.drain(..size).collect();
+            self.exporter.export(batch).await?;
+        }
+        Ok(())
+    }
+}
+
+fn log_error(result: Result<(), Error>) {
+    if let Err(err) = result {
+        error!("Error exporting records: {err}");
+    }
+}
+
+enum Command<R> {
+    Records(Vec<R>),
+    Tick,
+    Flush(oneshot::Sender<Result<(), Error>>),
+    Shutdown(oneshot::Sender<Result<(), Error>>),
+}
+
+/// Handle to a [`BatchExporter`] that was started.
+///
+/// The handle is a [`Service`] that buffers the batches of records it receives, so it can be
+/// the logs or telemetry processor of an extension, wrapped in a
+/// [`SharedService`](crate::SharedService). Clones of the handle send records to the same
+/// engine, which flushes its buffer when all of them are dropped.
+#[derive(Debug)]
+pub struct ExportHandle<R> {
+    sender: mpsc::Sender<Command<R>>,
+}
+
+impl<R> Clone for ExportHandle<R> {
+    fn clone(&self) -> Self {
+        ExportHandle {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<R: Send + 'static> ExportHandle<R> {
+    /// Export the buffered records, and wait until they're exported.
+    pub async fn flush(&self) -> Result<(), Error> {
+        let (reply, response) = oneshot::channel();
+        self.send(Command::Flush(reply)).await?;
+        response.await.map_err(|_| stopped())?
+    }
+
+    /// Export the buffered records, and stop the engine.
+    pub async fn shutdown(self) -> Result<(), Error> {
+        let (reply, response) = oneshot::channel();
+        self.send(Command::Shutdown(reply)).await?;
+        response.await.map_err(|_| stopped())?
+    }
+
+    async fn send(&self, command: Command<R>) -> Result<(), Error> {
+        self.sender.send(command).await.map_err(|_| stopped())
+    }
+}
+
+fn stopped() -> Error {
+    ExtensionError::boxed("the exporter was shut down")
+}
+
+impl<R: Send + 'static> Service<Vec<R>> for ExportHandle<R> {
+    type Response = ();
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, records: Vec<R>) -> Self::Future {
+        let handle = self.clone();
+        Box::pin(async move { handle.send(Command::Records(records)).await })
+    }
+}
+
+/// Client of the exporters, that connects to endpoints with hyper's `HttpConnector` unless
+/// another connector is set.
+#[derive(Debug)]
+struct Endpoint {
+    uri: Uri,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    client: Client<DynConnector>,
+}
+
+impl Endpoint {
+    fn new(uri: Uri) -> Result<Self, Error> {
+        Ok(Endpoint {
+            client: Self::client(uri.clone(), DynConnector::default())?,
+            uri,
+            headers: Vec::new(),
+        })
+    }
+
+    fn client(uri: Uri, connector: DynConnector) -> Result<Client<DynConnector>, Error> {
+        Ok(Client::builder().with_connector(connector).with_endpoint(uri).build()?)
+    }
+
+    fn with_connector<C>(mut self, connector: C) -> Result<Self, Error>
+    where
+        C: Service<Uri> + Clone + Send + Sync + 'static,
+        C::Response: hyper::rt::Read + hyper::rt::Write + Connection + Send + Unpin + 'static,
+        C::Future: Send + 'static,
+        C::Error: Into<Error>,
+    {
+        self.client = Self::client(self.uri.clone(), DynConnector::new(connector))?;
+        Ok(self)
+    }
+
+    async fn post(&self, body: Value) -> Result<(), Error> {
+        let mut request = lambda_runtime_api_client::build_request()
+            .method(Method::POST)
+            .uri(self.uri.clone())
+            .header(CONTENT_TYPE, "application/json");
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let request = request.body(Body::from(serde_json::to_vec(&body)?))?;
+        let response = self.client.client.request(request).await?;
+        let status = response.status();
+        // Read the body so the connection can be reused.
+        response.into_body().collect().await?;
+        if !status.is_success() {
+            let err = format!("unable to export records to {}: {status}", self.uri);
+            return Err(ExtensionError::boxed(err));
+        }
+        Ok(())
+    }
+}
+
+/// Exporter that posts batches of records to an HTTP endpoint, as JSON arrays in the format
+/// that Lambda sends them to extensions.
+///
+/// Connections are plain HTTP, like the ones to a collector that runs in another extension. Use
+/// [`with_connector`](Self::with_connector) to connect over TLS.
+#[derive(Debug)]
+pub struct HttpExporter {
+    endpoint: Endpoint,
+}
+
+impl HttpExporter {
+    /// Create an exporter that posts records to `uri`.
+    pub fn new(uri: Uri) -> Result<Self, Error> {
+        Ok(HttpExporter {
+            endpoint: Endpoint::new(uri)?,
+        })
+    }
+
+    /// Add a header to the requests of the exporter, like an API key.
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.endpoint.headers.push((name, value));
+        self
+    }
+
+    /// Open connections with another connector, like the ones of `hyper-rustls` or `hyper-tls`.
+    pub fn with_connector<C>(self, connector: C) -> Result<Self, Error>
+    where
+        C: Service<Uri> + Clone + Send + Sync + 'static,
+        C::Response: hyper::rt::Read + hyper::rt::Write + Connection + Send + Unpin + 'static,
+        C::Future: Send + 'static,
+        C::Error: Into<Error>,
+    {
+        Ok(HttpExporter {
+            endpoint: self.endpoint.with_connector(connector)?,
+        })
+    }
+}
+
+impl<R: Serialize + Send + 'static> TelemetryExporter<R> for HttpExporter {
+    fn export(&mut self, batch: Vec<R>) -> impl Future<Output = Result<(), Error>> + Send {
+        let body = serde_json::to_value(batch);
+        async move { self.endpoint.post(body?).await }
+    }
+}
+
+/// Exporter that sends records to an OpenTelemetry collector, as logs of the OTLP/HTTP protocol
+/// with JSON encoding.
+///
+/// Each record becomes a log record with the time of the record, its type as the
+/// `lambda.record.type` attribute, and the request id of the invocation as the
+/// `faas.invocation_id` attribute. Records of the `function` and `extension` types keep their
+/// text as the body, and platform records are encoded as JSON. The resource of the logs is the
+/// function, described by the environment variables of Lambda.
+///
+/// ```rust,no_run
+/// use lambda_extension::{BatchExporter, Error, Extension, OtlpExporter, SharedService};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let exporter = OtlpExporter::new("http://localhost:4318/v1/logs".parse()?)?;
+///     let exporter = BatchExporter::new(exporter).start();
+///     Extension::new()
+///         .with_logs_processor(SharedService::new(exporter))
+///         .run()
+///         .await
+/// }
+/// ```
+#[derive(Debug)]
+pub struct OtlpExporter {
+    endpoint: Endpoint,
+    resource: Vec<Value>,
+}
+
+impl OtlpExporter {
+    /// Create an exporter that sends logs to the `/v1/logs` endpoint of a collector.
+    pub fn new(uri: Uri) -> Result<Self, Error> {
+        let mut resource = vec![attribute("cloud.provider", "aws")];
+        for (key, variable) in [
+            ("service.name", "AWS_LAMBDA_FUNCTION_NAME"),
+            ("faas.name", "AWS_LAMBDA_FUNCTION_NAME"),
+            ("faas.version", "AWS_LAMBDA_FUNCTION_VERSION"),
+            ("cloud.region", "AWS_REGION"),
+        ] {
+            if let Ok(value) = std::env::var(variable) {
+                resource.push(attribute(key, &value));
+            }
+        }
+        Ok(OtlpExporter {
+            endpoint: Endpoint::new(uri)?,
+            resource,
+        })
+    }
+
+    /// Add a header to the requests of the exporter, like the API key of a vendor.
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.endpoint.headers.push((name, value));
+        self
+    }
+
+    /// Open connections with another connector, like the ones of `hyper-rustls` or `hyper-tls`.
+    pub fn with_connector<C>(self, connector: C) -> Result<Self, Error>
+    where
+        C: Service<Uri> + Clone + Send + Sync + 'static,
+        C::Response: hyper::rt::Read + hyper::rt::Write + Connection + Send + Unpin + 'static,
+        C::Future: Send + 'static,
+        C::Error: Into<Error>,
+    {
+        Ok(OtlpExporter {
+            endpoint: self.endpoint.with_connector(connector)?,
+            ..self
+        })
+    }
+
+    /// `ExportLogsServiceRequest` of a batch of records.
+    fn request<R: Serialize>(&self, batch: Vec<R>) -> Result<Value, Error> {
+        let records = batch
+            .into_iter()
+            .map(|record| serde_json::to_value(record).map(log_record))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(json!({
+            "resourceLogs": [{
+                "resource": { "attributes": self.resource },
+                "scopeLogs": [{
+                    "scope": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+                    "logRecords": records,
+                }],
+            }],
+        }))
+    }
+}
+
+impl<R: Serialize + Send + 'static> TelemetryExporter<R> for OtlpExporter {
+    fn export(&mut self, batch: Vec<R>) -> impl Future<Output = Result<(), Error>> + Send {
+        let request = self.request(batch);
+        async move { self.endpoint.post(request?).await }
+    }
+}
+
+/// OTLP log record of a record serialized like Lambda sends them, with `time`, `type`, and
+/// `record` fields.
+fn log_record(mut record: Value) -> Value {
+    let time = record["time"]
+        .as_str()
+        .and_then(|time| time.parse::<DateTime<Utc>>().ok())
+        .and_then(|time| time.timestamp_nanos_opt())
+        .unwrap_or_default();
+    let mut attributes = Vec::new();
+    if let Some(record_type) = record["type"].as_str() {
+        attributes.push(attribute("lambda.record.type", record_type));
+    }
+    if let Some(request_id) = record["record"]["requestId"].as_str() {
+        attributes.push(attribute("faas.invocation_id", request_id));
+    }
+    let body = match record["record"].take() {
+        Value::String(text) => text,
+        record => record.to_string(),
+    };
+    json!({
+        "timeUnixNano": time.to_string(),
+        "body": { "stringValue": body },
+        "attributes": attributes,
+    })
+}
+
+fn attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{listener, LambdaLog, LambdaLogRecord};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<Vec<u32>>>>);
+
+    impl TelemetryExporter<u32> for Recorder {
+        fn export(&mut self, batch: Vec<u32>) -> impl Future<Output = Result<(), Error>> + Send {
+            self.0.lock().unwrap().push(batch);
+            async { Ok(()) }
+        }
+    }
+
+    #[tokio::test]
+    async fn export_batches_by_size_and_on_shutdown() {
+        let recorder = Recorder::default();
+        let mut handle = BatchExporter::new(recorder.clone())
+            .with_max_batch_size(2)
+            .with_flush_interval(Duration::from_secs(3600))
+            .start();
+        handle.call(vec![1, 2, 3]).await.unwrap();
+        handle.call(vec![4]).await.unwrap();
+        handle.flush().await.unwrap();
+        handle.call(vec![5]).await.unwrap();
+        handle.clone().shutdown().await.unwrap();
+
+        assert_eq!(vec![vec![1, 2], vec![3, 4], vec![5]], *recorder.0.lock().unwrap());
+        assert!(handle.call(vec![6]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn export_batches_by_time() {
+        let recorder = Recorder::default();
+        let mut handle = BatchExporter::new(recorder.clone())
+            .with_flush_interval(Duration::from_millis(10))
+            .start();
+        handle.call(vec![1]).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(vec![vec![1]], *recorder.0.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn post_records_to_http_endpoints() {
+        let listener = listener::bind(0).await.unwrap();
+        let uri = format!("http://127.0.0.1:{}/logs", listener.local_addr().unwrap().port());
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        listener::serve(
+            listener,
+            tower::service_fn(move |logs: Vec<LambdaLog>| {
+                sender.send(logs).unwrap();
+                async { Ok::<_, Error>(()) }
+            }),
+        );
+
+        let log = LambdaLog {
+            time: Utc::now(),
+            record: LambdaLogRecord::Function("hello".to_string()),
+        };
+        let mut exporter = HttpExporter::new(uri.parse().unwrap()).unwrap();
+        exporter.export(vec![log.clone()]).await.unwrap();
+        assert_eq!(vec![log], receiver.recv().await.unwrap());
+    }
+
+    #[test]
+    fn encode_otlp_log_records() {
+        let record = json!({
+            "time": "2022-10-12T00:00:00.000Z",
+            "type": "platform.start",
+            "record": { "requestId": "6d68ca91-49c9-448d-89b8-7ca3e6dc66aa" },
+        });
+        let expected = json!({
+            "timeUnixNano": "1665532800000000000",
+            "body": { "stringValue": r#"{"requestId":"6d68ca91-49c9-448d-89b8-7ca3e6dc66aa"}"# },
+            "attributes": [
+                { "key": "lambda.record.type", "value": { "stringValue": "platform.start" } },
+                { "key": "faas.invocation_id", "value": { "stringValue": "6d68ca91-49c9-448d-89b8-7ca3e6dc66aa" } },
+            ],
+        });
+        assert_eq!(expected, log_record(record));
+
+        let record = json!({ "time": "2022-10-12T00:00:00.000Z", "type": "function", "record": "hello" });
+        assert_eq!(json!({ "stringValue": "hello" }), log_record(record)["body"]);
+    }
+}
@@ -1,28 +1,22 @@
-use http::Request;
 use http_body_util::BodyExt;
-use hyper::{body::Incoming, server::conn::http1, service::service_fn};
-
-use hyper_util::rt::tokio::TokioIo;
 use lambda_runtime_api_client::Client;
 use serde::Deserialize;
 use std::{
     convert::Infallible,
     fmt,
     future::{ready, Future},
-    net::SocketAddr,
     path::PathBuf,
     pin::Pin,
-    sync::Arc,
 };
-use tokio::{net::TcpListener, sync::Mutex};
 use tokio_stream::StreamExt;
-use tower::{MakeService, Service, ServiceExt};
+use tower::{Layer, MakeService, Service, ServiceExt};
 use tracing::trace;
 
 use crate::{
+    listener,
     logs::*,
     requests::{self, Api},
-    telemetry_wrapper, Error, ExtensionError, LambdaEvent, LambdaTelemetry, NextEvent,
+    Error, EventType, ExtensionError, LambdaEvent, LambdaTelemetry, NextEvent,
 };
 
 const DEFAULT_LOG_PORT_NUMBER: u16 = 9002;
@@ -109,6 +103,22 @@ where
         }
     }
 
+    /// Create a new [`Extension`] that only registers for the given types of events, like only
+    /// [`EventType::Shutdown`] for an extension that flushes state when the execution environment
+    /// shuts down. Lambda doesn't send the other events to the extension, so it doesn't wake up
+    /// on every invocation.
+    pub fn with_event_types(self, event_types: &[EventType]) -> Self {
+        let invoke = event_types.contains(&EventType::Invoke);
+        let shutdown = event_types.contains(&EventType::Shutdown);
+        let events: &'static [&'static str] = match (invoke, shutdown) {
+            (true, true) => &["INVOKE", "SHUTDOWN"],
+            (true, false) => &["INVOKE"],
+            (false, true) => &["SHUTDOWN"],
+            (false, false) => &[],
+        };
+        self.with_events(events)
+    }
+
     /// Create a new [`Extension`] with a service that receives Lambda events.
     pub fn with_events_processor<N>(self, ep: N) -> Extension<'a, N, L, T>
     where
@@ -131,6 +141,49 @@ where
         }
     }
 
+    /// Wrap the events processor with a [`Layer`], like the layers of `tower` that log, measure,
+    /// or time out services. For an incoming event, this layer runs before any layer that was
+    /// added prior.
+    ///
+    /// ```rust,no_run
+    /// use lambda_extension::{service_fn, tower::util::MapRequestLayer, Error, Extension, LambdaEvent};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     Extension::new()
+    ///         .with_events_processor(service_fn(|_event: LambdaEvent| async { Ok::<_, Error>(()) }))
+    ///         .layer(MapRequestLayer::new(|event: LambdaEvent| {
+    ///             println!("received {:?} event", event.next.event_type());
+    ///             event
+    ///         }))
+    ///         .run()
+    ///         .await
+    /// }
+    /// ```
+    pub fn layer<Y>(self, layer: Y) -> Extension<'a, Y::Service, L, T>
+    where
+        Y: Layer<E>,
+        Y::Service: Service<LambdaEvent>,
+        <Y::Service as Service<LambdaEvent>>::Future:
+            Future<Output = Result<(), <Y::Service as Service<LambdaEvent>>::Error>>,
+        <Y::Service as Service<LambdaEvent>>::Error: Into<Error> + fmt::Display,
+    {
+        let ep = layer.layer(self.events_processor);
+        Extension {
+            events_processor: ep,
+            extension_name: self.extension_name,
+            events: self.events,
+            log_types: self.log_types,
+            log_buffering: self.log_buffering,
+            logs_processor: self.logs_processor,
+            log_port_number: self.log_port_number,
+            telemetry_types: self.telemetry_types,
+            telemetry_buffering: self.telemetry_buffering,
+            telemetry_processor: self.telemetry_processor,
+            telemetry_port_number: self.telemetry_port_number,
+        }
+    }
+
     /// Create a new [`Extension`] with a service that receives Lambda logs.
     pub fn with_logs_processor<N, NS>(self, lp: N) -> Extension<'a, E, N, T>
     where
@@ -244,26 +297,9 @@ where
 
             validate_buffering_configuration(self.log_buffering)?;
 
-            let addr = SocketAddr::from(([0, 0, 0, 0], self.log_port_number));
-            let service = log_processor.make_service(());
-            let service = Arc::new(Mutex::new(service.await.unwrap()));
-            tokio::task::spawn(async move {
-                trace!("Creating new logs processor Service");
-
-                loop {
-                    let service: Arc<Mutex<_>> = service.clone();
-                    let make_service = service_fn(move |req: Request<Incoming>| log_wrapper(service.clone(), req));
-
-                    let listener = TcpListener::bind(addr).await.unwrap();
-                    let (tcp, _) = listener.accept().await.unwrap();
-                    let io = TokioIo::new(tcp);
-                    tokio::task::spawn(async move {
-                        if let Err(err) = http1::Builder::new().serve_connection(io, make_service).await {
-                            println!("Error serving connection: {err:?}");
-                        }
-                    });
-                }
-            });
+            let listener = listener::bind(self.log_port_number).await?;
+            let service = log_processor.make_service(()).await.map_err(Into::into)?;
+            listener::serve::<LambdaLog, _>(listener, service);
 
             trace!("Log processor started");
 
@@ -290,26 +326,9 @@ where
 
             validate_buffering_configuration(self.telemetry_buffering)?;
 
-            let addr = SocketAddr::from(([0, 0, 0, 0], self.telemetry_port_number));
-            let service = telemetry_processor.make_service(());
-            let service = Arc::new(Mutex::new(service.await.unwrap()));
-            tokio::task::spawn(async move {
-                trace!("Creating new telemetry processor Service");
-
-                loop {
-                    let service = service.clone();
-                    let make_service = service_fn(move |req| telemetry_wrapper(service.clone(), req));
-
-                    let listener = TcpListener::bind(addr).await.unwrap();
-                    let (tcp, _) = listener.accept().await.unwrap();
-                    let io = TokioIo::new(tcp);
-                    tokio::task::spawn(async move {
-                        if let Err(err) = http1::Builder::new().serve_connection(io, make_service).await {
-                            println!("Error serving connection: {err:?}");
-                        }
-                    });
-                }
-            });
+            let listener = listener::bind(self.telemetry_port_number).await?;
+            let service = telemetry_processor.make_service(()).await.map_err(Into::into)?;
+            listener::serve::<LambdaTelemetry, _>(listener, service);
 
             trace!("Telemetry processor started");
 
@@ -433,6 +452,89 @@ where
         // Unreachable.
         Ok(())
     }
+
+    /// Run an internal extension alongside the runtime of the function, in the same process.
+    ///
+    /// `runtime` is the future of the runtime, usually the one returned by `lambda_runtime::run`.
+    /// The extension must be [registered](Extension::register) before the runtime starts, because
+    /// the runtime ends the Init phase when it asks for its first event; taking a registered
+    /// extension guarantees that order. The runtime and the extension share the state of the
+    /// process, like an `Arc` captured by both the handler and the events processor.
+    ///
+    /// Lambda sends each `INVOKE` event to the runtime and to the extension at the same time, and
+    /// only finishes the invocation once the runtime sent its response and the extension asked for
+    /// the next event. An events processor can then wait for the handler to finish, and flush the
+    /// records that it buffered for the invocation, before the execution environment is frozen.
+    ///
+    /// The runtime is polled first. The function returns the result of the runtime when it stops,
+    /// or the error of the extension when the extension fails, after reporting the error to
+    /// Lambda. Either way, the other future is dropped. Internal extensions can't register for the
+    /// `SHUTDOWN` event: Lambda sends `SIGTERM` to the process instead, which functions can handle
+    /// with `lambda_runtime::spawn_graceful_shutdown_handler` or a signal listener of their own.
+    ///
+    /// ```rust,ignore
+    /// use lambda_extension::{service_fn, Error, Extension, LambdaEvent};
+    /// use std::sync::{
+    ///     atomic::{AtomicUsize, Ordering},
+    ///     Arc,
+    /// };
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let invocations = Arc::new(AtomicUsize::new(0));
+    ///
+    ///     let extension = Extension::new()
+    ///         // Internal extensions only support INVOKE events.
+    ///         .with_events(&["INVOKE"])
+    ///         .with_events_processor(service_fn({
+    ///             let invocations = invocations.clone();
+    ///             move |_event: LambdaEvent| {
+    ///                 let invocations = invocations.clone();
+    ///                 async move {
+    ///                     invocations.fetch_add(1, Ordering::Relaxed);
+    ///                     Ok::<_, Error>(())
+    ///                 }
+    ///             }
+    ///         }))
+    ///         // Internal extension names MUST be unique within a given Lambda function.
+    ///         .with_extension_name("internal-counter")
+    ///         .register()
+    ///         .await?;
+    ///
+    ///     extension
+    ///         .run_with_runtime(lambda_runtime::run(lambda_runtime::service_fn(
+    ///             move |_event: lambda_runtime::LambdaEvent<serde_json::Value>| {
+    ///                 let invocations = invocations.clone();
+    ///                 async move { Ok::<_, Error>(invocations.load(Ordering::Relaxed)) }
+    ///             },
+    ///         )))
+    ///         .await
+    /// }
+    /// ```
+    pub async fn run_with_runtime<F, T, R>(self, runtime: F) -> Result<T, Error>
+    where
+        F: Future<Output = Result<T, R>>,
+        R: Into<Error>,
+    {
+        run_alongside(self.run(), runtime).await
+    }
+}
+
+/// Drive the run loop of an extension and the runtime of the function until one of them stops.
+async fn run_alongside<X, F, T, R>(extension: X, runtime: F) -> Result<T, Error>
+where
+    X: Future<Output = Result<(), Error>>,
+    F: Future<Output = Result<T, R>>,
+    R: Into<Error>,
+{
+    tokio::select! {
+        biased;
+        result = runtime => result.map_err(Into::into),
+        result = extension => match result {
+            Ok(()) => Err(ExtensionError::boxed("the extension stopped before the runtime")),
+            Err(err) => Err(err),
+        },
+    }
 }
 
 /// A no-op generic processor
@@ -524,6 +626,10 @@ async fn register<'a>(
     };
 
     let events = events.unwrap_or(&["INVOKE", "SHUTDOWN"]);
+    // Unknown event types fail before the extension registers.
+    for name in events {
+        EventType::parse(name)?;
+    }
 
     let req = requests::register_request(&name, events)?;
     let res = client.call(req).await?;
@@ -551,3 +657,67 @@ async fn register<'a>(
         account_id: response.account_id,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        future::pending,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    #[tokio::test]
+    async fn run_extensions_alongside_the_runtime() {
+        let result = run_alongside(pending(), async { Ok::<_, Error>(42) }).await;
+        assert_eq!(42, result.unwrap());
+
+        let extension = async { Err(ExtensionError::boxed("unable to register") as Error) };
+        let result = run_alongside(extension, pending::<Result<(), Error>>()).await;
+        assert_eq!("unable to register", result.unwrap_err().to_string());
+    }
+
+    #[tokio::test]
+    async fn wrap_events_processors_with_layers() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let layer = tower::util::MapRequestLayer::new({
+            let calls = calls.clone();
+            move |event: LambdaEvent| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                event
+            }
+        });
+        let extension = Extension::new()
+            .with_events_processor(tower::service_fn(|event: LambdaEvent| async move {
+                assert!(event.next.is_invoke());
+                Ok::<_, Error>(())
+            }))
+            .layer(layer);
+
+        let event: NextEvent = serde_json::from_str(
+            r#"{"eventType":"INVOKE","deadlineMs":0,"requestId":"id","invokedFunctionArn":"arn"}"#,
+        )
+        .unwrap();
+        let mut processor = extension.events_processor;
+        processor
+            .ready()
+            .await
+            .unwrap()
+            .call(LambdaEvent::new(event))
+            .await
+            .unwrap();
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn register_for_event_types() {
+        let extension = Extension::new().with_event_types(&[EventType::Shutdown]);
+        assert_eq!(Some(&["SHUTDOWN"][..]), extension.events);
+        let extension = extension.with_event_types(&[EventType::Shutdown, EventType::Invoke]);
+        assert_eq!(Some(&["INVOKE", "SHUTDOWN"][..]), extension.events);
+
+        assert!(EventType::parse("RESTORE").is_err());
+    }
+}
@@ -16,6 +16,9 @@ mod extension;
 pub use extension::*;
 mod events;
 pub use events::*;
+mod exporter;
+pub use exporter::*;
+mod listener;
 mod logs;
 pub use logs::*;
 mod telemetry;
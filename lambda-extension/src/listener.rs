@@ -0,0 +1,149 @@
+use http::{Request, Response, StatusCode};
+use http_body_util::BodyExt;
+use hyper::{body::Incoming, server::conn::http1, service::service_fn};
+use hyper_util::rt::tokio::TokioIo;
+use lambda_runtime_api_client::body::Body;
+use serde::de::DeserializeOwned;
+use std::{convert::Infallible, fmt, net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{net::TcpListener, sync::Mutex};
+use tower::{Service, ServiceExt};
+use tracing::{error, trace};
+
+use crate::{Error, ExtensionError};
+
+/// How long the listener waits before accepting connections again after a failure.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Bind the HTTP listener where Lambda sends batches of records.
+///
+/// The listener must accept connections before the extension subscribes to the Logs or
+/// Telemetry API, or Lambda can't deliver the first batches.
+pub(crate) async fn bind(port_number: u16) -> Result<TcpListener, Error> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port_number));
+    TcpListener::bind(addr).await.map_err(|err| {
+        let err = format!("unable to listen on port {port_number}: {err}");
+        ExtensionError::boxed(err) as Error
+    })
+}
+
+/// Serve the batches of records that Lambda sends to the listener to a service, until the
+/// execution environment shuts down.
+pub(crate) fn serve<R, S>(listener: TcpListener, service: S)
+where
+    R: DeserializeOwned + Send + 'static,
+    S: Service<Vec<R>, Response = ()> + Send + 'static,
+    S::Error: fmt::Debug,
+    S::Future: Send,
+{
+    let service = Arc::new(Mutex::new(service));
+    tokio::task::spawn(async move {
+        loop {
+            let tcp = match listener.accept().await {
+                Ok((tcp, _)) => tcp,
+                Err(err) => {
+                    // Errors like running out of file descriptors persist for a while, so back off
+                    // instead of spinning on them.
+                    error!("Error accepting connection: {err}");
+                    tokio::time::sleep(ACCEPT_BACKOFF).await;
+                    continue;
+                }
+            };
+            let service = service.clone();
+            let service = service_fn(move |req| deliver(service.clone(), req));
+            tokio::task::spawn(async move {
+                if let Err(err) = http1::Builder::new().serve_connection(TokioIo::new(tcp), service).await {
+                    error!("Error serving connection: {err:?}");
+                }
+            });
+        }
+    });
+}
+
+/// Parse a batch of records and send it to the service.
+///
+/// Errors of the service are logged, and don't make Lambda send the batch again.
+async fn deliver<R, S>(service: Arc<Mutex<S>>, req: Request<Incoming>) -> Result<Response<Body>, Infallible>
+where
+    R: DeserializeOwned,
+    S: Service<Vec<R>, Response = ()>,
+    S::Error: fmt::Debug,
+{
+    trace!("Received batch of records");
+    let body = match req.into_body().collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(err) => {
+            error!("Error reading request body: {err}");
+            return Ok(status(StatusCode::BAD_REQUEST));
+        }
+    };
+    let records: Vec<R> = match serde_json::from_slice(&body) {
+        Ok(records) => records,
+        Err(err) => {
+            error!("Error parsing records: {err}");
+            return Ok(status(StatusCode::BAD_REQUEST));
+        }
+    };
+
+    let mut service = service.lock().await;
+    if let Err(err) = service.ready().await {
+        error!("Records processor is not ready: {err:?}");
+        return Ok(status(StatusCode::OK));
+    }
+    if let Err(err) = service.call(records).await {
+        error!("Error processing records: {err:?}");
+    }
+    Ok(status(StatusCode::OK))
+}
+
+fn status(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LambdaTelemetry, LambdaTelemetryRecord};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+        sync::mpsc,
+    };
+
+    #[tokio::test]
+    async fn deliver_typed_batches() {
+        let listener = bind(0).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        serve(
+            listener,
+            tower::service_fn(move |records: Vec<LambdaTelemetry>| {
+                sender.send(records).unwrap();
+                async { Ok::<_, Error>(()) }
+            }),
+        );
+
+        for (body, expected) in [
+            (
+                r#"[{"time":"2022-10-21T14:05:03.165Z","type":"function","record":"hello"}]"#,
+                "200 OK",
+            ),
+            ("not json", "400 Bad Request"),
+        ] {
+            let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+            let request = format!(
+                "POST / HTTP/1.1\r\nHost: sandbox.localdomain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            assert!(response.starts_with(&format!("HTTP/1.1 {expected}")), "{response}");
+        }
+
+        let records = receiver.recv().await.unwrap();
+        assert_eq!(LambdaTelemetryRecord::Function("hello".to_string()), records[0].record);
+        assert!(receiver.try_recv().is_err());
+    }
+}
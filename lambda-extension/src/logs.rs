@@ -1,19 +1,11 @@
 use chrono::{DateTime, Utc};
-use http::{Request, Response};
-use http_body_util::BodyExt;
-use hyper::body::Incoming;
-use lambda_runtime_api_client::body::Body;
 use serde::{Deserialize, Serialize};
-use std::{fmt, sync::Arc};
-use tokio::sync::Mutex;
-use tower::Service;
-use tracing::{error, trace};
 
-use crate::{Error, ExtensionError};
+use crate::{Error, ExtensionError, TraceContext};
 
 /// Payload received from the Lambda Logs API
 /// See: <https://docs.aws.amazon.com/lambda/latest/dg/runtimes-logs-api.html#runtimes-logs-api-msg>
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct LambdaLog {
     /// Time when the log was generated
     pub time: DateTime<Utc>,
@@ -23,8 +15,9 @@ pub struct LambdaLog {
 }
 
 /// Record in a LambdaLog entry
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(tag = "type", content = "record", rename_all = "lowercase")]
+#[non_exhaustive]
 pub enum LambdaLogRecord {
     /// Function log records
     Function(String),
@@ -37,6 +30,9 @@ pub enum LambdaLogRecord {
     PlatformStart {
         /// Request identifier
         request_id: String,
+        /// Version of the function
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        version: Option<String>,
     },
     /// Platform stop record
     #[serde(rename = "platform.end", rename_all = "camelCase")]
@@ -51,6 +47,9 @@ pub enum LambdaLogRecord {
         request_id: String,
         /// Request metrics
         metrics: LogPlatformReportMetrics,
+        /// Trace of the invocation, when tracing is active
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tracing: Option<TraceContext>,
     },
     /// Runtime or execution environment error record
     #[serde(rename = "platform.fault")]
@@ -96,7 +95,7 @@ pub enum LambdaLogRecord {
 }
 
 /// Platform report metrics
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct LogPlatformReportMetrics {
     /// Duration in milliseconds
@@ -186,50 +185,6 @@ pub(crate) fn validate_buffering_configuration(log_buffering: Option<LogBufferin
     }
 }
 
-/// Wrapper function that sends logs to the subscriber Service
-///
-/// This takes an `hyper::Request` and transforms it into `Vec<LambdaLog>` for the
-/// underlying `Service` to process.
-pub(crate) async fn log_wrapper<S>(service: Arc<Mutex<S>>, req: Request<Incoming>) -> Result<Response<Body>, Error>
-where
-    S: Service<Vec<LambdaLog>, Response = ()>,
-    S::Error: Into<Error> + fmt::Debug,
-    S::Future: Send,
-{
-    trace!("Received logs request");
-    // Parse the request body as a Vec<LambdaLog>
-    let body = match req.into_body().collect().await {
-        Ok(body) => body,
-        Err(e) => {
-            error!("Error reading logs request body: {}", e);
-            return Ok(hyper::Response::builder()
-                .status(hyper::StatusCode::BAD_REQUEST)
-                .body(Body::empty())
-                .unwrap());
-        }
-    };
-    let logs: Vec<LambdaLog> = match serde_json::from_slice(&body.to_bytes()) {
-        Ok(logs) => logs,
-        Err(e) => {
-            error!("Error parsing logs: {}", e);
-            return Ok(hyper::Response::builder()
-                .status(hyper::StatusCode::BAD_REQUEST)
-                .body(Body::empty())
-                .unwrap());
-        }
-    };
-
-    {
-        let mut service = service.lock().await;
-        match service.call(logs).await {
-            Ok(_) => (),
-            Err(err) => println!("{err:?}"),
-        }
-    }
-
-    Ok(hyper::Response::new(Body::empty()))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -252,6 +207,18 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn serialize_without_missing_fields() {
+        let record = LambdaLogRecord::PlatformStart {
+            request_id: "6f7f0961f83442118a7af6fe80b88d56".to_string(),
+            version: None,
+        };
+        assert_eq!(
+            r#"{"type":"platform.start","record":{"requestId":"6f7f0961f83442118a7af6fe80b88d56"}}"#,
+            serde_json::to_string(&record).unwrap()
+        );
+    }
+
     macro_rules! deserialize_tests {
         ($($name:ident: $value:expr,)*) => {
             $(
@@ -284,6 +251,15 @@ mod tests {
             r#"{"time": "2020-08-20T12:31:32.123Z","type": "platform.start","record": {"requestId": "6f7f0961f83442118a7af6fe80b88d56"}}"#,
             LambdaLogRecord::PlatformStart {
                 request_id: "6f7f0961f83442118a7af6fe80b88d56".to_string(),
+                version: None,
+            },
+        ),
+        // platform.start with the version of the function
+        platform_start_version: (
+            r#"{"time": "2020-08-20T12:31:32.123Z","type": "platform.start","record": {"requestId": "6f7f0961f83442118a7af6fe80b88d56","version": "$LATEST"}}"#,
+            LambdaLogRecord::PlatformStart {
+                request_id: "6f7f0961f83442118a7af6fe80b88d56".to_string(),
+                version: Some("$LATEST".to_string()),
             },
         ),
         // platform.end
@@ -305,6 +281,26 @@ mod tests {
                     max_memory_used_mb: 123,
                     init_duration_ms: Some(1.23),
                 },
+                tracing: None,
+            },
+        ),
+        // platform.report with tracing
+        platform_report_tracing: (
+            r#"{"time": "2020-08-20T12:31:32.123Z","type": "platform.report","record": {"requestId": "6f7f0961f83442118a7af6fe80b88d56","metrics": {"durationMs": 1.23,"billedDurationMs": 123,"memorySizeMB": 123,"maxMemoryUsedMB": 123},"tracing": {"type": "X-Amzn-Trace-Id","value": "Root=1-5f3e9e4f-0c3f2d5a6b7c8d9e0f1a2b3c;Parent=53995c3f42cd8ad8;Sampled=1"}}}"#,
+            LambdaLogRecord::PlatformReport {
+                request_id: "6f7f0961f83442118a7af6fe80b88d56".to_string(),
+                metrics: LogPlatformReportMetrics {
+                    duration_ms: 1.23,
+                    billed_duration_ms: 123,
+                    memory_size_mb: 123,
+                    max_memory_used_mb: 123,
+                    init_duration_ms: None,
+                },
+                tracing: Some(TraceContext {
+                    span_id: None,
+                    r#type: crate::TracingType::AmznTraceId,
+                    value: "Root=1-5f3e9e4f-0c3f2d5a6b7c8d9e0f1a2b3c;Parent=53995c3f42cd8ad8;Sampled=1".to_string(),
+                }),
             },
         ),
         // platform.fault
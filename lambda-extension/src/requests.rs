@@ -46,7 +46,7 @@ impl Api {
     pub(crate) fn schema_version(&self) -> &str {
         match *self {
             Api::LogsApi => "2021-03-18",
-            Api::TelemetryApi => "2022-07-01",
+            Api::TelemetryApi => "2022-12-13",
         }
     }
 
@@ -1,13 +1,5 @@
 use chrono::{DateTime, Utc};
-use http::{Request, Response};
-use http_body_util::BodyExt;
-use hyper::body::Incoming;
-use lambda_runtime_api_client::body::Body;
 use serde::{Deserialize, Serialize};
-use std::{boxed::Box, fmt, sync::Arc};
-use tokio::sync::Mutex;
-use tower::Service;
-use tracing::{error, trace};
 
 /// Payload received from the Telemetry API
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -22,6 +14,7 @@ pub struct LambdaTelemetry {
 /// Record in a LambdaTelemetry entry
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(tag = "type", content = "record", rename_all = "lowercase")]
+#[non_exhaustive]
 pub enum LambdaTelemetryRecord {
     /// Function log records
     Function(String),
@@ -60,13 +53,19 @@ pub enum LambdaTelemetryRecord {
         #[serde(default)]
         spans: Vec<Span>,
     },
-    /// Platform init start record
+    /// Platform init report record
     #[serde(rename = "platform.initReport", rename_all = "camelCase")]
     PlatformInitReport {
         /// Type of initialization
         initialization_type: InitType,
         /// Phase of initialisation
         phase: InitPhase,
+        /// Status of initialization
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        status: Option<Status>,
+        /// When the status = failure, the error_type describes what kind of error occurred
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error_type: Option<String>,
         /// Metrics
         metrics: InitReportMetrics,
         /// Spans
@@ -125,6 +124,43 @@ pub enum LambdaTelemetryRecord {
         tracing: Option<TraceContext>,
     },
 
+    /// Platform restore start record, when a SnapStart function restores a snapshot
+    #[serde(rename = "platform.restoreStart", rename_all = "camelCase")]
+    PlatformRestoreStart {
+        /// Lambda runtime version
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        runtime_version: Option<String>,
+        /// Lambda runtime version ARN
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        runtime_version_arn: Option<String>,
+    },
+    /// Platform restore runtime done record, when the runtime hooks of a snapshot complete
+    #[serde(rename = "platform.restoreRuntimeDone", rename_all = "camelCase")]
+    PlatformRestoreRuntimeDone {
+        /// Status of the restore
+        status: Status,
+        /// When unsuccessful, the error_type describes what kind of error occurred
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error_type: Option<String>,
+        /// Spans
+        #[serde(default)]
+        spans: Vec<Span>,
+    },
+    /// Platform restore report record
+    #[serde(rename = "platform.restoreReport", rename_all = "camelCase")]
+    PlatformRestoreReport {
+        /// Status of the restore
+        status: Status,
+        /// When unsuccessful, the error_type describes what kind of error occurred
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error_type: Option<String>,
+        /// Metrics
+        metrics: RestoreReportMetrics,
+        /// Spans
+        #[serde(default)]
+        spans: Vec<Span>,
+    },
+
     /// Extension-specific record
     #[serde(rename = "platform.extension", rename_all = "camelCase")]
     PlatformExtension {
@@ -134,6 +170,9 @@ pub enum LambdaTelemetryRecord {
         state: String,
         /// Events sent to the extension
         events: Vec<String>,
+        /// When the extension failed, the error_type describes what kind of error occurred
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error_type: Option<String>,
     },
     /// Telemetry processor-specific record
     #[serde(rename = "platform.telemetrySubscription", rename_all = "camelCase")]
@@ -157,6 +196,31 @@ pub enum LambdaTelemetryRecord {
     },
 }
 
+impl LambdaTelemetryRecord {
+    /// Request identifier of the invocation that the record is about, if any
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            LambdaTelemetryRecord::PlatformStart { request_id, .. }
+            | LambdaTelemetryRecord::PlatformRuntimeDone { request_id, .. }
+            | LambdaTelemetryRecord::PlatformReport { request_id, .. } => Some(request_id),
+            _ => None,
+        }
+    }
+
+    /// Spans of the phase that the record reports, like `responseLatency`
+    pub fn spans(&self) -> &[Span] {
+        match self {
+            LambdaTelemetryRecord::PlatformInitRuntimeDone { spans, .. }
+            | LambdaTelemetryRecord::PlatformInitReport { spans, .. }
+            | LambdaTelemetryRecord::PlatformRuntimeDone { spans, .. }
+            | LambdaTelemetryRecord::PlatformReport { spans, .. }
+            | LambdaTelemetryRecord::PlatformRestoreRuntimeDone { spans, .. }
+            | LambdaTelemetryRecord::PlatformRestoreReport { spans, .. } => spans,
+            _ => &[],
+        }
+    }
+}
+
 /// Type of Initialization
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
 #[serde(rename_all = "kebab-case")]
@@ -233,6 +297,14 @@ pub struct InitReportMetrics {
     pub duration_ms: f64,
 }
 
+/// Restore report metrics
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreReportMetrics {
+    /// Duration of the restore
+    pub duration_ms: f64,
+}
+
 /// Report metrics
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -265,54 +337,6 @@ pub struct RuntimeDoneMetrics {
     pub produced_bytes: Option<u64>,
 }
 
-/// Wrapper function that sends telemetry to the subscriber Service
-///
-/// This takes an `hyper::Request` and transforms it into `Vec<LambdaTelemetry>` for the
-/// underlying `Service` to process.
-pub(crate) async fn telemetry_wrapper<S>(
-    service: Arc<Mutex<S>>,
-    req: Request<Incoming>,
-) -> Result<Response<Body>, Box<dyn std::error::Error + Send + Sync>>
-where
-    S: Service<Vec<LambdaTelemetry>, Response = ()>,
-    S::Error: Into<Box<dyn std::error::Error + Send + Sync>> + fmt::Debug,
-    S::Future: Send,
-{
-    trace!("Received telemetry request");
-    // Parse the request body as a Vec<LambdaTelemetry>
-    let body = match req.into_body().collect().await {
-        Ok(body) => body,
-        Err(e) => {
-            error!("Error reading telemetry request body: {}", e);
-            return Ok(hyper::Response::builder()
-                .status(hyper::StatusCode::BAD_REQUEST)
-                .body(Body::empty())
-                .unwrap());
-        }
-    };
-
-    let telemetry: Vec<LambdaTelemetry> = match serde_json::from_slice(&body.to_bytes()) {
-        Ok(telemetry) => telemetry,
-        Err(e) => {
-            error!("Error parsing telemetry: {}", e);
-            return Ok(hyper::Response::builder()
-                .status(hyper::StatusCode::BAD_REQUEST)
-                .body(Body::empty())
-                .unwrap());
-        }
-    };
-
-    {
-        let mut service = service.lock().await;
-        match service.call(telemetry).await {
-            Ok(_) => (),
-            Err(err) => println!("{err:?}"),
-        }
-    }
-
-    Ok(hyper::Response::new(Body::empty()))
-}
-
 #[cfg(test)]
 mod deserialization_tests {
     use super::*;
@@ -456,6 +480,7 @@ mod deserialization_tests {
                 name: "my-extension".to_string(),
                 state: "Ready".to_string(),
                 events: vec!("SHUTDOWN".to_string(), "INVOKE".to_string()),
+                error_type: None,
              },
         ),
         // platform.initReport
@@ -464,10 +489,39 @@ mod deserialization_tests {
             LambdaTelemetryRecord::PlatformInitReport {
                 initialization_type: InitType::OnDemand,
                 phase: InitPhase::Init,
+                status: None,
+                error_type: None,
                 metrics: InitReportMetrics { duration_ms: 500.0 },
                 spans: Vec::new(),
             }
         ),
+        // platform.restoreStart
+        platform_restore_start: (
+            r#"{"time":"2022-10-19T13:52:15.636Z","type":"platform.restoreStart","record":{"runtimeVersion":"java:21.v1","runtimeVersionArn":"arn:aws:lambda:us-east-1::runtime:abc"}}"#,
+            LambdaTelemetryRecord::PlatformRestoreStart {
+                runtime_version: Some("java:21.v1".to_string()),
+                runtime_version_arn: Some("arn:aws:lambda:us-east-1::runtime:abc".to_string()),
+            },
+        ),
+        // platform.restoreRuntimeDone
+        platform_restore_runtime_done: (
+            r#"{"time":"2022-10-19T13:52:15.936Z","type":"platform.restoreRuntimeDone","record":{"status":"failure","errorType":"Runtime.ExitError"}}"#,
+            LambdaTelemetryRecord::PlatformRestoreRuntimeDone {
+                status: Status::Failure,
+                error_type: Some("Runtime.ExitError".to_string()),
+                spans: Vec::new(),
+            },
+        ),
+        // platform.restoreReport
+        platform_restore_report: (
+            r#"{"time":"2022-10-19T13:52:16.136Z","type":"platform.restoreReport","record":{"status":"success","metrics":{"durationMs":300.5}}}"#,
+            LambdaTelemetryRecord::PlatformRestoreReport {
+                status: Status::Success,
+                error_type: None,
+                metrics: RestoreReportMetrics { duration_ms: 300.5 },
+                spans: Vec::new(),
+            },
+        ),
     }
 }
 
@@ -640,6 +694,7 @@ mod serialization_tests {
                     name: "my-extension".to_string(),
                     state: "Ready".to_string(),
                     events: vec!("SHUTDOWN".to_string(), "INVOKE".to_string()),
+                    error_type: None,
                 },
             },
             r#"{"time":"2023-11-28T12:00:09Z","type":"platform.extension","record":{"name":"my-extension","state":"Ready","events":["SHUTDOWN","INVOKE"]}}"#,
@@ -651,6 +706,8 @@ mod serialization_tests {
                 record: LambdaTelemetryRecord::PlatformInitReport {
                     initialization_type: InitType::OnDemand,
                     phase: InitPhase::Init,
+                    status: None,
+                    error_type: None,
                     metrics: InitReportMetrics { duration_ms: 500.0 },
                     spans: Vec::new(),
                 },
@@ -0,0 +1,502 @@
+//! Static assets embedded in the binary of the function.
+//!
+//! [`Assets`](crate::assets::Assets) serves files embedded at compile time, like the build of a single-page
+//! application, with their content type, an `ETag` for conditional requests, and range
+//! requests. Compressed copies of the files, like `app.js.gz` or `app.js.br`, are served to
+//! clients that accept them, and binary files are returned encoded in base64.
+//!
+//! ```rust,ignore
+//! use lambda_http::{assets::Assets, embed_assets, Error};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Error> {
+//!     let assets = embed_assets!("$CARGO_MANIFEST_DIR/dist").spa_fallback("index.html");
+//!     lambda_http::run(assets).await
+//! }
+//! ```
+//!
+//! Files can also be listed one by one with [`embed_assets!`](crate::embed_assets), or come from
+//! anywhere else with [`Assets::new`](crate::assets::Assets::new).
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    future::{ready, Ready},
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use http::{
+    header::{ACCEPT_ENCODING, ALLOW, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_TYPE, VARY},
+    HeaderValue, Method, StatusCode,
+};
+use percent_encoding::percent_decode_str;
+
+use crate::{
+    conditional::{ETag, Validators},
+    range::ranged,
+    response::is_text_content_type,
+    tower::Service,
+    Body, Request, RequestExt, Response,
+};
+
+/// Embed files at compile time into [`Assets`](crate::assets::Assets).
+///
+/// With a single path, every file of the directory and of its subdirectories is embedded. The
+/// path is relative to the directory that `cargo` builds from, so it usually starts with
+/// `$CARGO_MANIFEST_DIR`, which is replaced by the directory of the crate, like other
+/// environment variables:
+///
+/// ```rust,ignore
+/// let assets = lambda_http::embed_assets!("$CARGO_MANIFEST_DIR/dist");
+/// ```
+///
+/// With a list of paths, only these files are embedded, from a directory relative to the current
+/// file, like with `include_bytes!`:
+///
+/// ```rust,ignore
+/// let assets = lambda_http::embed_assets!("../dist", ["index.html", "css/style.css"]);
+/// ```
+#[macro_export]
+#[cfg_attr(docsrs, doc(cfg(feature = "static-assets")))]
+macro_rules! embed_assets {
+    ($root:literal, [$($path:literal),* $(,)?]) => {
+        $crate::assets::Assets::new([
+            $(($path, &include_bytes!(concat!($root, "/", $path))[..])),*
+        ])
+    };
+    // A `tt` and not a `literal`, which `include_dir!` wouldn't see as a string literal.
+    ($root:tt) => {{
+        use $crate::assets::__private::include_dir;
+        static DIR: include_dir::Dir<'static> = include_dir::include_dir!($root);
+        $crate::assets::__private::from_dir(&DIR)
+    }};
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use include_dir;
+    use include_dir::{Dir, DirEntry};
+
+    use super::Assets;
+
+    /// Assets of the files of a directory embedded by [`embed_assets!`](crate::embed_assets), and
+    /// of its subdirectories.
+    pub fn from_dir(dir: &'static Dir<'static>) -> Assets {
+        fn collect(dir: &'static Dir<'static>, files: &mut Vec<(String, &'static [u8])>) {
+            for entry in dir.entries() {
+                match entry {
+                    DirEntry::Dir(dir) => collect(dir, files),
+                    DirEntry::File(file) => {
+                        files.push((file.path().to_string_lossy().into_owned(), file.contents()));
+                    }
+                }
+            }
+        }
+
+        let mut files = Vec::new();
+        collect(dir, &mut files);
+        Assets::new(files)
+    }
+}
+
+/// File served by [`Assets`].
+#[derive(Debug, Clone)]
+pub struct Asset {
+    bytes: &'static [u8],
+    content_type: &'static str,
+    etag: ETag,
+}
+
+impl Asset {
+    fn new(path: &str, bytes: &'static [u8]) -> Self {
+        Self {
+            bytes,
+            content_type: content_type(path),
+            etag: ETag::from_bytes(bytes),
+        }
+    }
+
+    /// Content of the file.
+    pub fn bytes(&self) -> &'static [u8] {
+        self.bytes
+    }
+
+    /// Content type of the file, from its extension.
+    pub fn content_type(&self) -> &'static str {
+        self.content_type
+    }
+
+    /// Entity tag of the file, computed from its content.
+    pub fn etag(&self) -> &ETag {
+        &self.etag
+    }
+}
+
+/// Service that serves static assets embedded in the binary of the function.
+///
+/// Paths are matched against the path of requests, after removing the [prefix](Assets::prefix).
+/// Paths of directories are served their `index.html`, and paths without assets get a `404 Not
+/// Found` response, unless they are sent to a [fallback](Assets::spa_fallback). The service only
+/// answers `GET` and `HEAD` requests.
+///
+/// Assets can be the [fallback](crate::router::Router::fallback) of a `Router` too, to serve an
+/// application and its API from the same function.
+#[derive(Debug, Clone)]
+pub struct Assets {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Clone)]
+struct Inner {
+    files: HashMap<String, Asset>,
+    prefix: String,
+    index: String,
+    fallback: Option<String>,
+    cache_control: HeaderValue,
+}
+
+impl Assets {
+    /// Assets from pairs of paths, relative to the root of the assets, and contents.
+    pub fn new<P: AsRef<str>>(files: impl IntoIterator<Item = (P, &'static [u8])>) -> Self {
+        let files = files
+            .into_iter()
+            .map(|(path, bytes)| {
+                let path = path.as_ref().trim_start_matches('/');
+                (path.to_string(), Asset::new(path, bytes))
+            })
+            .collect();
+        Self {
+            inner: Arc::new(Inner {
+                files,
+                prefix: String::new(),
+                index: "index.html".to_string(),
+                fallback: None,
+                cache_control: HeaderValue::from_static("public, max-age=0, must-revalidate"),
+            }),
+        }
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Arc::make_mut(&mut self.inner)
+    }
+
+    /// Serve the assets under a path, like `/static`.
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.inner_mut().prefix = prefix.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// File served for the paths of directories, `index.html` by default.
+    pub fn index(mut self, index: &str) -> Self {
+        self.inner_mut().index = index.trim_start_matches('/').to_string();
+        self
+    }
+
+    /// File served for the paths without an extension that have no asset, like the `index.html`
+    /// of single-page applications that route on the client.
+    pub fn spa_fallback(mut self, path: &str) -> Self {
+        self.inner_mut().fallback = Some(path.trim_start_matches('/').to_string());
+        self
+    }
+
+    /// `Cache-Control` header of the responses, `public, max-age=0, must-revalidate` by
+    /// default, so clients revalidate their copies with the `ETag` of the assets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value isn't a valid header value.
+    pub fn cache_control(mut self, value: &str) -> Self {
+        self.inner_mut().cache_control = HeaderValue::from_str(value).expect("invalid Cache-Control header");
+        self
+    }
+
+    /// Asset at a path, relative to the root of the assets.
+    pub fn get(&self, path: &str) -> Option<&Asset> {
+        self.inner.files.get(path.trim_start_matches('/'))
+    }
+
+    /// Serve the asset of a request.
+    pub fn respond(&self, request: &Request) -> Response<Body> {
+        if !matches!(*request.method(), Method::GET | Method::HEAD) {
+            let mut response = status(StatusCode::METHOD_NOT_ALLOWED);
+            response
+                .headers_mut()
+                .insert(ALLOW, HeaderValue::from_static("GET, HEAD"));
+            return response;
+        }
+        let Some(path) = self.resolve(request) else {
+            return status(StatusCode::NOT_FOUND);
+        };
+        let asset = &self.inner.files[&path];
+
+        let mut response = match self.encoded(request, &path) {
+            Some((encoding, encoded)) => {
+                let mut response = self.response(
+                    request,
+                    encoded,
+                    asset.content_type,
+                    Body::Binary(Bytes::from_static(encoded.bytes)),
+                );
+                response
+                    .headers_mut()
+                    .insert(CONTENT_ENCODING, HeaderValue::from_static(encoding));
+                response
+            }
+            None => {
+                let body = match std::str::from_utf8(asset.bytes) {
+                    Ok(text) if is_text_content_type(asset.content_type) => Body::from(text),
+                    _ => Body::Binary(Bytes::from_static(asset.bytes)),
+                };
+                let response = self.response(request, asset, asset.content_type, body);
+                ranged(request, response)
+            }
+        };
+        if self.has_encodings(&path) {
+            response
+                .headers_mut()
+                .append(VARY, HeaderValue::from_static("accept-encoding"));
+        }
+        if request.method() == Method::HEAD {
+            *response.body_mut() = Body::Empty;
+        }
+        response
+    }
+
+    fn response(&self, request: &Request, asset: &Asset, content_type: &'static str, body: Body) -> Response<Body> {
+        let validators = Validators::new().etag(asset.etag.clone());
+        let mut response = match validators.precondition_response(request) {
+            Some(response) => response,
+            None => {
+                let mut response = validators.apply(Response::new(body));
+                response
+                    .headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+                response
+            }
+        };
+        response
+            .headers_mut()
+            .insert(CACHE_CONTROL, self.inner.cache_control.clone());
+        response
+    }
+
+    /// Path of the asset of a request.
+    fn resolve(&self, request: &Request) -> Option<String> {
+        let path = match request.raw_http_path() {
+            "" => request.uri().path(),
+            path => path,
+        };
+        let path = percent_decode_str(path).decode_utf8().ok()?;
+        let path = path.strip_prefix(self.inner.prefix.as_str())?;
+        if !path.is_empty() && !path.starts_with('/') {
+            return None;
+        }
+        let mut path = path.trim_start_matches('/').to_string();
+        if path.is_empty() || path.ends_with('/') {
+            path.push_str(&self.inner.index);
+        }
+        if self.inner.files.contains_key(&path) {
+            return Some(path);
+        }
+        let has_extension = path.rsplit('/').next().is_some_and(|name| name.contains('.'));
+        self.inner
+            .fallback
+            .clone()
+            .filter(|fallback| !has_extension && self.inner.files.contains_key(fallback))
+    }
+
+    /// Compressed copy of an asset that the client accepts.
+    fn encoded(&self, request: &Request, path: &str) -> Option<(&'static str, &Asset)> {
+        let accept_encoding = request.headers().get(ACCEPT_ENCODING)?.to_str().ok()?;
+        let accepts = |coding: &str| {
+            accept_encoding.split(',').any(|item| {
+                let mut params = item.split(';');
+                let name = params.next().unwrap_or_default().trim();
+                let quality = params
+                    .filter_map(|param| param.trim().strip_prefix("q="))
+                    .find_map(|quality| quality.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (name.eq_ignore_ascii_case(coding) || name == "*") && quality > 0.0
+            })
+        };
+        [("br", "br"), ("gzip", "gz")]
+            .into_iter()
+            .filter(|(coding, _)| accepts(coding))
+            .find_map(|(coding, extension)| Some((coding, self.inner.files.get(&format!("{path}.{extension}"))?)))
+    }
+
+    fn has_encodings(&self, path: &str) -> bool {
+        ["br", "gz"]
+            .iter()
+            .any(|extension| self.inner.files.contains_key(&format!("{path}.{extension}")))
+    }
+}
+
+impl Service<Request> for Assets {
+    type Response = Response<Body>;
+    type Error = Infallible;
+    type Future = Ready<Result<Response<Body>, Infallible>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        ready(Ok(self.respond(&request)))
+    }
+}
+
+fn status(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::from(status.canonical_reason().unwrap_or_default()));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("text/plain; charset=utf-8"));
+    response
+}
+
+/// Content type of a file, from its extension.
+fn content_type(path: &str) -> &'static str {
+    let extension = path
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" | "map" => "application/json",
+        "webmanifest" => "application/manifest+json",
+        "txt" => "text/plain; charset=utf-8",
+        "csv" => "text/csv; charset=utf-8",
+        "md" => "text/markdown; charset=utf-8",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "wasm" => "application/wasm",
+        "pdf" => "application/pdf",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::{CONTENT_RANGE, ETAG, IF_NONE_MATCH, RANGE};
+
+    use super::*;
+    use crate::tower::ServiceExt;
+
+    fn assets() -> Assets {
+        Assets::new([
+            ("index.html", &b"<h1>home</h1>"[..]),
+            ("app.js", b"console.log('app')"),
+            ("app.js.gz", b"\x1f\x8bcompressed"),
+            ("images/logo.png", b"\x89PNG\r\n\x1a\n"),
+        ])
+    }
+
+    #[test]
+    fn embed_directories() {
+        let assets = crate::embed_assets!("$CARGO_MANIFEST_DIR/tests/data/assets");
+        assert_eq!(b"<h1>home</h1>\n", assets.get("index.html").unwrap().bytes());
+        let style = assets.get("css/style.css").unwrap();
+        assert_eq!("text/css; charset=utf-8", style.content_type());
+        assert_eq!(b"h1 { color: teal; }\n", style.bytes());
+    }
+
+    fn get(uri: &str) -> http::request::Builder {
+        http::Request::builder().uri(uri)
+    }
+
+    #[tokio::test]
+    async fn serve_assets() {
+        let response = assets()
+            .oneshot(get("/app.js").body(Body::Empty).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!("text/javascript; charset=utf-8", response.headers()[CONTENT_TYPE]);
+        assert_eq!("accept-encoding", response.headers()[VARY]);
+        assert_eq!(&Body::from("console.log('app')"), response.body());
+
+        let response = assets().respond(&get("/images/logo.png").body(Body::Empty).unwrap());
+        assert_eq!("image/png", response.headers()[CONTENT_TYPE]);
+        assert!(matches!(response.body(), Body::Binary(_)));
+
+        let response = assets().respond(&get("/").body(Body::Empty).unwrap());
+        assert_eq!(&Body::from("<h1>home</h1>"), response.body());
+
+        let response = assets().respond(&get("/missing.css").body(Body::Empty).unwrap());
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+        let request = get("/app.js").method(Method::POST).body(Body::Empty).unwrap();
+        assert_eq!(StatusCode::METHOD_NOT_ALLOWED, assets().respond(&request).status());
+    }
+
+    #[test]
+    fn compressed_copies() {
+        let request = get("/app.js")
+            .header(ACCEPT_ENCODING, "br;q=0, gzip")
+            .body(Body::Empty)
+            .unwrap();
+        let response = assets().respond(&request);
+        assert_eq!("gzip", response.headers()[CONTENT_ENCODING]);
+        assert_eq!("text/javascript; charset=utf-8", response.headers()[CONTENT_TYPE]);
+        assert_eq!(b"\x1f\x8bcompressed", response.body().as_ref());
+    }
+
+    #[test]
+    fn conditional_and_range_requests() {
+        let assets = assets();
+        let etag = assets.get("app.js").unwrap().etag().to_string();
+        let request = get("/app.js").header(IF_NONE_MATCH, &etag).body(Body::Empty).unwrap();
+        let response = assets.respond(&request);
+        assert_eq!(StatusCode::NOT_MODIFIED, response.status());
+        assert_eq!(etag.as_str(), response.headers()[ETAG]);
+
+        let request = get("/app.js").header(RANGE, "bytes=0-6").body(Body::Empty).unwrap();
+        let response = assets.respond(&request);
+        assert_eq!(StatusCode::PARTIAL_CONTENT, response.status());
+        assert_eq!("bytes 0-6/18", response.headers()[CONTENT_RANGE]);
+        assert_eq!(b"console", response.body().as_ref());
+    }
+
+    #[test]
+    fn prefix_and_fallback() {
+        let assets = assets().prefix("/static/").spa_fallback("index.html");
+        let response = assets.respond(&get("/static/users/42").body(Body::Empty).unwrap());
+        assert_eq!(&Body::from("<h1>home</h1>"), response.body());
+
+        let response = assets.respond(&get("/static/users/42.json").body(Body::Empty).unwrap());
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+        let response = assets.respond(&get("/staticfile").body(Body::Empty).unwrap());
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+        let response = assets.respond(&get("/static/app.js").method(Method::HEAD).body(Body::Empty).unwrap());
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(&Body::Empty, response.body());
+    }
+
+    #[test]
+    fn embed_files() {
+        let assets = crate::embed_assets!("../tests/data", ["apigw_proxy_request.json"]);
+        let asset = assets.get("/apigw_proxy_request.json").unwrap();
+        assert_eq!("application/json", asset.content_type());
+        assert_eq!(include_bytes!("../tests/data/apigw_proxy_request.json"), asset.bytes());
+    }
+}
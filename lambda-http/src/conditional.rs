@@ -0,0 +1,400 @@
+//! Conditional requests, with entity tags and modification dates.
+//!
+//! [`Validators`] evaluates the `If-Match`, `If-Unmodified-Since`, `If-None-Match`, and
+//! `If-Modified-Since` headers of a request in the order of
+//! [RFC 9110](https://www.rfc-editor.org/rfc/rfc9110#section-13.2.2), and builds the
+//! `304 Not Modified` and `412 Precondition Failed` responses.
+//!
+//! ```rust,no_run
+//! use lambda_http::{conditional::{ETag, Validators}, Body, Error, Request, Response};
+//!
+//! async fn handler(request: Request) -> Result<Response<Body>, Error> {
+//!     let document = "{\"name\":\"lambda\"}";
+//!     let validators = Validators::new().etag(ETag::from_bytes(document.as_bytes()));
+//!     if let Some(response) = validators.precondition_response(&request) {
+//!         return Ok(response);
+//!     }
+//!     Ok(validators.apply(Response::new(document.into())))
+//! }
+//! ```
+
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use http::{
+    header::{ETAG, IF_MATCH, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_UNMODIFIED_SINCE, LAST_MODIFIED},
+    HeaderMap, HeaderValue, Method, StatusCode,
+};
+
+use crate::{Body, Request, Response};
+
+/// Entity tag of a representation, sent in `ETag` headers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ETag {
+    tag: String,
+    weak: bool,
+}
+
+impl ETag {
+    /// Strong entity tag, for representations that are identical byte for byte.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tag contains characters that entity tags can't contain, like `"`.
+    pub fn strong(tag: impl Into<String>) -> Self {
+        Self::new(tag.into(), false)
+    }
+
+    /// Weak entity tag, for representations that are equivalent but not identical, like
+    /// compressed ones.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tag contains characters that entity tags can't contain, like `"`.
+    pub fn weak(tag: impl Into<String>) -> Self {
+        Self::new(tag.into(), true)
+    }
+
+    fn new(tag: String, weak: bool) -> Self {
+        assert!(
+            tag.bytes().all(is_etag_char),
+            "invalid characters in entity tag {tag:?}"
+        );
+        Self { tag, weak }
+    }
+
+    /// Strong entity tag computed from the bytes of a representation.
+    ///
+    /// The tag is a hash of the bytes that doesn't change between invocations or deployments, so
+    /// every instance of a function computes the same tag for the same representation.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        // 64-bit FNV-1a
+        let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        Self {
+            tag: format!("{:x}-{hash:016x}", bytes.len()),
+            weak: false,
+        }
+    }
+
+    /// Parse an entity tag like `"v1"` or `W/"v1"`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (weak, value) = match value.strip_prefix("W/") {
+            Some(value) => (true, value),
+            None => (false, value),
+        };
+        let tag = value.strip_prefix('"')?.strip_suffix('"')?;
+        tag.bytes().all(is_etag_char).then(|| Self {
+            tag: tag.to_string(),
+            weak,
+        })
+    }
+
+    /// Opaque tag, without quotes.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Whether the tag is weak.
+    pub fn is_weak(&self) -> bool {
+        self.weak
+    }
+
+    /// Strong comparison: both tags are strong and identical. Used by `If-Match`.
+    pub fn strong_eq(&self, other: &ETag) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// Weak comparison: both tags are identical, weak or not. Used by `If-None-Match`.
+    pub fn weak_eq(&self, other: &ETag) -> bool {
+        self.tag == other.tag
+    }
+}
+
+impl fmt::Display for ETag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.weak {
+            f.write_str("W/")?;
+        }
+        write!(f, "\"{}\"", self.tag)
+    }
+}
+
+impl From<&ETag> for HeaderValue {
+    fn from(etag: &ETag) -> Self {
+        HeaderValue::try_from(etag.to_string()).expect("entity tags are valid header values")
+    }
+}
+
+fn is_etag_char(byte: u8) -> bool {
+    byte == 0x21 || (0x23..=0x7e).contains(&byte) || byte >= 0x80
+}
+
+/// Outcome of the evaluation of the preconditions of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precondition {
+    /// The preconditions hold, or the request has none: the request should be handled.
+    Passed,
+    /// The client has the current representation: respond with `304 Not Modified`.
+    NotModified,
+    /// The preconditions don't hold: respond with `412 Precondition Failed`.
+    Failed,
+}
+
+/// Validators of the current representation of a resource: its entity tag and its
+/// modification date.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validators {
+    etag: Option<ETag>,
+    last_modified: Option<SystemTime>,
+}
+
+impl Validators {
+    /// Validators of a resource without a representation, or with neither an entity tag nor a
+    /// modification date yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the entity tag of the representation.
+    pub fn etag(mut self, etag: ETag) -> Self {
+        self.etag = Some(etag);
+        self
+    }
+
+    /// Set the modification date of the representation.
+    ///
+    /// HTTP dates have a precision of one second, so the date is truncated to the second.
+    pub fn last_modified(mut self, last_modified: SystemTime) -> Self {
+        self.last_modified = Some(truncate(last_modified));
+        self
+    }
+
+    /// Evaluate the preconditions of a request against the validators.
+    ///
+    /// `If-None-Match` takes precedence over `If-Modified-Since`, and `If-Match` over
+    /// `If-Unmodified-Since`, and dates are ignored when they can't be parsed. Requests that
+    /// aren't `GET` or `HEAD` fail instead of getting `304 Not Modified`.
+    pub fn evaluate(&self, request: &Request) -> Precondition {
+        let headers = request.headers();
+        let safe = matches!(*request.method(), Method::GET | Method::HEAD);
+
+        if let Some(if_match) = header(headers, IF_MATCH) {
+            if !self.matches(if_match, ETag::strong_eq) {
+                return Precondition::Failed;
+            }
+        } else if let Some(since) = header(headers, IF_UNMODIFIED_SINCE).and_then(parse_date) {
+            if self.last_modified.is_some_and(|modified| modified > since) {
+                return Precondition::Failed;
+            }
+        }
+
+        if let Some(if_none_match) = header(headers, IF_NONE_MATCH) {
+            if self.matches(if_none_match, ETag::weak_eq) {
+                return if safe {
+                    Precondition::NotModified
+                } else {
+                    Precondition::Failed
+                };
+            }
+        } else if let Some(since) = header(headers, IF_MODIFIED_SINCE).filter(|_| safe).and_then(parse_date) {
+            if self.last_modified.is_some_and(|modified| modified <= since) {
+                return Precondition::NotModified;
+            }
+        }
+
+        Precondition::Passed
+    }
+
+    /// Response to send instead of handling the request, when its preconditions don't pass.
+    pub fn precondition_response(&self, request: &Request) -> Option<Response<Body>> {
+        match self.evaluate(request) {
+            Precondition::Passed => None,
+            Precondition::NotModified => Some(self.not_modified()),
+            Precondition::Failed => {
+                let mut response = Response::new(Body::Empty);
+                *response.status_mut() = StatusCode::PRECONDITION_FAILED;
+                Some(response)
+            }
+        }
+    }
+
+    /// `304 Not Modified` response, with the `ETag` and `Last-Modified` headers of the
+    /// representation.
+    pub fn not_modified(&self) -> Response<Body> {
+        let mut response = Response::new(Body::Empty);
+        *response.status_mut() = StatusCode::NOT_MODIFIED;
+        self.apply(response)
+    }
+
+    /// Set the `ETag` and `Last-Modified` headers of a response to the validators.
+    pub fn apply<B>(&self, mut response: Response<B>) -> Response<B> {
+        let headers = response.headers_mut();
+        if let Some(etag) = &self.etag {
+            headers.insert(ETAG, etag.into());
+        }
+        if let Some(last_modified) = self.last_modified {
+            let date = httpdate::fmt_http_date(last_modified);
+            headers.insert(
+                LAST_MODIFIED,
+                HeaderValue::try_from(date).expect("HTTP dates are valid header values"),
+            );
+        }
+        response
+    }
+
+    /// Whether a list of entity tags like `"a", W/"b"`, or `*`, matches the entity tag of the
+    /// representation.
+    fn matches(&self, list: &str, eq: fn(&ETag, &ETag) -> bool) -> bool {
+        if list.trim() == "*" {
+            return self.etag.is_some() || self.last_modified.is_some();
+        }
+        let Some(current) = &self.etag else {
+            return false;
+        };
+        parse_list(list).any(|etag| eq(&etag, current))
+    }
+}
+
+fn header(headers: &HeaderMap, name: http::HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn parse_date(value: &str) -> Option<SystemTime> {
+    httpdate::parse_http_date(value).ok()
+}
+
+fn truncate(time: SystemTime) -> SystemTime {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => UNIX_EPOCH + Duration::from_secs(duration.as_secs()),
+        Err(_) => time,
+    }
+}
+
+/// Entity tags of a list, which can contain commas inside of the quotes of the tags.
+fn parse_list(list: &str) -> impl Iterator<Item = ETag> + '_ {
+    let mut rest = list;
+    std::iter::from_fn(move || loop {
+        rest = rest.trim_start_matches([',', ' ', '\t']);
+        if rest.is_empty() {
+            return None;
+        }
+        let start = if rest.starts_with("W/") { 2 } else { 0 };
+        let end = match rest[start..].strip_prefix('"') {
+            Some(quoted) => quoted.find('"').map(|end| start + end + 2),
+            None => None,
+        };
+        let Some(end) = end else {
+            // Skip the malformed tag.
+            rest = rest.find(',').map_or("", |comma| &rest[comma..]);
+            continue;
+        };
+        let (etag, remaining) = rest.split_at(end);
+        rest = remaining;
+        if let Some(etag) = ETag::parse(etag) {
+            return Some(etag);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: Method, headers: &[(&str, &str)]) -> Request {
+        let mut builder = http::Request::builder().method(method).uri("/documents/1");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(Body::Empty).unwrap()
+    }
+
+    #[test]
+    fn parse_and_compare_etags() {
+        let strong = ETag::parse("\"v1\"").unwrap();
+        let weak = ETag::parse("W/\"v1\"").unwrap();
+        assert_eq!("W/\"v1\"", weak.to_string());
+        assert!(strong.weak_eq(&weak));
+        assert!(!strong.strong_eq(&weak));
+        assert!(strong.strong_eq(&ETag::strong("v1")));
+        assert_eq!(None, ETag::parse("v1"));
+
+        let tags = parse_list("\"a,b\", W/\"c\", bad, \"d\"").collect::<Vec<_>>();
+        assert_eq!(vec![ETag::strong("a,b"), ETag::weak("c"), ETag::strong("d")], tags);
+
+        assert_eq!(ETag::from_bytes(b"hello"), ETag::from_bytes(b"hello"));
+        assert_ne!(ETag::from_bytes(b"hello"), ETag::from_bytes(b"world"));
+    }
+
+    #[test]
+    fn if_none_match() {
+        let validators = Validators::new().etag(ETag::strong("v2"));
+
+        let response = validators
+            .precondition_response(&request(Method::GET, &[("if-none-match", "\"v1\", W/\"v2\"")]))
+            .unwrap();
+        assert_eq!(StatusCode::NOT_MODIFIED, response.status());
+        assert_eq!("\"v2\"", response.headers()[ETAG]);
+
+        let request_v1 = request(Method::GET, &[("if-none-match", "\"v1\"")]);
+        assert_eq!(Precondition::Passed, validators.evaluate(&request_v1));
+
+        let put = request(Method::PUT, &[("if-none-match", "*")]);
+        assert_eq!(Precondition::Failed, validators.evaluate(&put));
+        assert_eq!(Precondition::Passed, Validators::new().evaluate(&put));
+    }
+
+    #[test]
+    fn if_match() {
+        let validators = Validators::new().etag(ETag::strong("v2"));
+        let put = request(Method::PUT, &[("if-match", "\"v2\"")]);
+        assert_eq!(Precondition::Passed, validators.evaluate(&put));
+
+        // If-Match uses the strong comparison
+        let put = request(Method::PUT, &[("if-match", "W/\"v2\"")]);
+        let response = validators.precondition_response(&put).unwrap();
+        assert_eq!(StatusCode::PRECONDITION_FAILED, response.status());
+    }
+
+    #[test]
+    fn modification_dates() {
+        let modified = UNIX_EPOCH + Duration::from_millis(1_700_000_000_500);
+        let validators = Validators::new().last_modified(modified);
+        let date = httpdate::fmt_http_date(modified);
+        let earlier = httpdate::fmt_http_date(modified - Duration::from_secs(60));
+
+        let response = validators
+            .precondition_response(&request(Method::GET, &[("if-modified-since", &date)]))
+            .unwrap();
+        assert_eq!(StatusCode::NOT_MODIFIED, response.status());
+        assert_eq!(date.as_str(), response.headers()[LAST_MODIFIED]);
+
+        let get = request(Method::GET, &[("if-modified-since", &earlier)]);
+        assert_eq!(Precondition::Passed, validators.evaluate(&get));
+        let get = request(Method::GET, &[("if-modified-since", "yesterday")]);
+        assert_eq!(Precondition::Passed, validators.evaluate(&get));
+
+        let delete = request(Method::DELETE, &[("if-unmodified-since", &earlier)]);
+        assert_eq!(Precondition::Failed, validators.evaluate(&delete));
+        let delete = request(Method::DELETE, &[("if-unmodified-since", &date)]);
+        assert_eq!(Precondition::Passed, validators.evaluate(&delete));
+    }
+
+    #[test]
+    fn if_none_match_takes_precedence() {
+        let modified = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let validators = Validators::new().etag(ETag::strong("v2")).last_modified(modified);
+        let get = request(
+            Method::GET,
+            &[
+                ("if-none-match", "\"v1\""),
+                ("if-modified-since", &httpdate::fmt_http_date(modified)),
+            ],
+        );
+        assert_eq!(Precondition::Passed, validators.evaluate(&get));
+    }
+}
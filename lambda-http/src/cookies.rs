@@ -0,0 +1,617 @@
+//! Cookies of requests and responses.
+//!
+//! A [`CookieJar`] holds the cookies that the client sent, and records the cookies that the
+//! handler adds or removes. [`CookieJar::apply`] writes the changes to the response as
+//! `Set-Cookie` headers, which are returned in the `cookies` array of API Gateway HTTP API
+//! responses, and in the `multiValueHeaders` of REST API and ALB responses.
+//!
+//! ```rust,no_run
+//! use lambda_http::{
+//!     cookies::{Cookie, CookieJar},
+//!     Body, Error, Request, Response,
+//! };
+//!
+//! async fn visit(request: Request) -> Result<Response<Body>, Error> {
+//!     let mut jar = CookieJar::from_headers(request.headers());
+//!     let visits = jar
+//!         .get("visits")
+//!         .and_then(|cookie| cookie.value().parse::<u32>().ok())
+//!         .unwrap_or_default();
+//!     jar.add(Cookie::new("visits", (visits + 1).to_string()).with_http_only(true));
+//!
+//!     let mut response = Response::new(Body::from(format!("{} previous visits", visits)));
+//!     jar.apply(&mut response)?;
+//!     Ok(response)
+//! }
+//! ```
+
+use std::{convert::Infallible, error::Error, fmt, time::Duration};
+
+use http::{
+    header::{COOKIE, SET_COOKIE},
+    HeaderMap, HeaderValue,
+};
+
+use crate::{ext::extract::FromRequest, Request, Response};
+
+/// Cookie with the attributes of a `Set-Cookie` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+    name: String,
+    value: String,
+    path: Option<String>,
+    domain: Option<String>,
+    max_age: Option<Duration>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<SameSite>,
+}
+
+impl Cookie {
+    /// Create a cookie without attributes.
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            path: None,
+            domain: None,
+            max_age: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        }
+    }
+
+    /// Create a cookie with an empty value, to remove it with [`CookieJar::remove`].
+    pub fn named(name: impl Into<String>) -> Self {
+        Self::new(name, "")
+    }
+
+    /// Name of the cookie.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Value of the cookie.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Path of the cookie, when it was set.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Domain of the cookie, when it was set.
+    pub fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+
+    /// Lifetime of the cookie, when it was set.
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
+    /// Set the path that the cookie is sent to.
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Set the domain that the cookie is sent to.
+    pub fn with_domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// Set the lifetime of the cookie, after which the client deletes it.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Only send the cookie over HTTPS.
+    pub fn with_secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// Hide the cookie from the JavaScript of pages.
+    pub fn with_http_only(mut self, http_only: bool) -> Self {
+        self.http_only = http_only;
+        self
+    }
+
+    /// Set when the cookie is sent with cross-site requests.
+    pub fn with_same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = Some(same_site);
+        self
+    }
+
+    fn with_value(mut self, value: String) -> Self {
+        self.value = value;
+        self
+    }
+
+    fn is_removal(&self) -> bool {
+        self.max_age == Some(Duration::ZERO)
+    }
+
+    /// Check that the cookie can be written in a `Set-Cookie` header, per
+    /// [RFC 6265](https://www.rfc-editor.org/rfc/rfc6265#section-4.1.1): the name is a token, the
+    /// value is made of cookie octets, optionally in double quotes, and the path and domain don't
+    /// contain control characters or `;`, which would let them add attributes to the cookie.
+    pub fn validate(&self) -> Result<(), InvalidCookie> {
+        let invalid = |part| InvalidCookie {
+            name: self.name.clone(),
+            part,
+        };
+        if self.name.is_empty() || !self.name.bytes().all(is_token_octet) {
+            return Err(invalid("name"));
+        }
+        let value = self
+            .value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(&self.value);
+        if !value.bytes().all(is_cookie_octet) {
+            return Err(invalid("value"));
+        }
+        if !self.path.as_deref().is_none_or(is_attribute_value) {
+            return Err(invalid("path"));
+        }
+        if !self.domain.as_deref().is_none_or(is_attribute_value) {
+            return Err(invalid("domain"));
+        }
+        Ok(())
+    }
+}
+
+fn is_token_octet(byte: u8) -> bool {
+    byte.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?={}".contains(&byte)
+}
+
+fn is_cookie_octet(byte: u8) -> bool {
+    byte.is_ascii_graphic() && !b"\",;\\".contains(&byte)
+}
+
+fn is_attribute_value(value: &str) -> bool {
+    value
+        .bytes()
+        .all(|byte| byte.is_ascii() && !byte.is_ascii_control() && byte != b';')
+}
+
+/// Error returned when a cookie can't be written in a `Set-Cookie` header, see
+/// [`Cookie::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidCookie {
+    name: String,
+    part: &'static str,
+}
+
+impl fmt::Display for InvalidCookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cookie {:?} has an invalid {}", self.name, self.part)
+    }
+}
+
+impl Error for InvalidCookie {}
+
+/// Formats the cookie as the value of a `Set-Cookie` header.
+///
+/// The name, value, and attributes are written as they are, check them with
+/// [`Cookie::validate`] first when they come from users.
+impl fmt::Display for Cookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.value)?;
+        if let Some(path) = &self.path {
+            write!(f, "; Path={path}")?;
+        }
+        if let Some(domain) = &self.domain {
+            write!(f, "; Domain={domain}")?;
+        }
+        if let Some(max_age) = self.max_age {
+            write!(f, "; Max-Age={}", max_age.as_secs())?;
+        }
+        if self.secure {
+            f.write_str("; Secure")?;
+        }
+        if self.http_only {
+            f.write_str("; HttpOnly")?;
+        }
+        if let Some(same_site) = self.same_site {
+            write!(f, "; SameSite={same_site}")?;
+        }
+        Ok(())
+    }
+}
+
+/// `SameSite` attribute of a cookie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    /// Only send the cookie with requests from the same site.
+    Strict,
+    /// Also send the cookie when users navigate to the site from other sites.
+    Lax,
+    /// Send the cookie with all requests. Clients require the cookie to be secure.
+    None,
+}
+
+impl fmt::Display for SameSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None",
+        })
+    }
+}
+
+/// Cookies of a request, and the changes to send back in the response.
+#[derive(Debug, Clone, Default)]
+pub struct CookieJar {
+    original: Vec<Cookie>,
+    delta: Vec<Cookie>,
+}
+
+impl CookieJar {
+    /// Create an empty jar.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse the cookies of the `Cookie` headers.
+    ///
+    /// API Gateway HTTP APIs send cookies in a separate `cookies` field of the event, which is
+    /// merged into the `Cookie` header of the request.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let original = headers
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|pair| {
+                let (name, value) = pair.trim().split_once('=')?;
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .unwrap_or(value);
+                Some(Cookie::new(name.trim(), value))
+            })
+            .filter(|cookie| !cookie.name.is_empty())
+            .collect();
+        Self {
+            original,
+            delta: Vec::new(),
+        }
+    }
+
+    /// Cookie with the given name, including the cookies added to the jar.
+    pub fn get(&self, name: &str) -> Option<&Cookie> {
+        match self.delta.iter().find(|cookie| cookie.name == name) {
+            Some(cookie) if cookie.is_removal() => None,
+            Some(cookie) => Some(cookie),
+            None => self.original.iter().find(|cookie| cookie.name == name),
+        }
+    }
+
+    /// Cookies of the jar, including the cookies added to it.
+    pub fn iter(&self) -> impl Iterator<Item = &Cookie> {
+        let original = self
+            .original
+            .iter()
+            .filter(|cookie| !self.delta.iter().any(|delta| delta.name == cookie.name));
+        self.delta.iter().filter(|cookie| !cookie.is_removal()).chain(original)
+    }
+
+    /// Add a cookie, which replaces the cookie with the same name.
+    pub fn add(&mut self, cookie: Cookie) {
+        self.delta.retain(|delta| delta.name != cookie.name);
+        self.delta.push(cookie);
+    }
+
+    /// Remove a cookie from the client. The path and domain of the cookie must match the ones
+    /// that it was set with.
+    pub fn remove(&mut self, cookie: Cookie) {
+        self.add(cookie.with_value(String::new()).with_max_age(Duration::ZERO));
+    }
+
+    /// Cookies added or removed since the jar was created.
+    pub fn delta(&self) -> impl Iterator<Item = &Cookie> {
+        self.delta.iter()
+    }
+
+    /// Append a `Set-Cookie` header for each cookie added or removed to the response.
+    ///
+    /// Fails without changing the response when a cookie isn't valid, see [`Cookie::validate`].
+    pub fn apply<B>(&self, response: &mut Response<B>) -> Result<(), InvalidCookie> {
+        let values = self
+            .delta
+            .iter()
+            .map(|cookie| {
+                cookie.validate()?;
+                Ok(HeaderValue::from_str(&cookie.to_string()).expect("valid cookies are valid header values"))
+            })
+            .collect::<Result<Vec<_>, InvalidCookie>>()?;
+        for value in values {
+            response.headers_mut().append(SET_COOKIE, value);
+        }
+        Ok(())
+    }
+
+    /// Verified cookie added with [`CookieJar::add_signed`], with its original value.
+    #[cfg(feature = "cookies-signed")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookies-signed")))]
+    pub fn get_signed(&self, name: &str, key: &Key) -> Option<Cookie> {
+        let cookie = self.get(name)?;
+        let value = key.verify(&cookie.name, &cookie.value)?;
+        Some(cookie.clone().with_value(value))
+    }
+
+    /// Add a cookie with a signature of its name and value, which clients can read but not
+    /// change.
+    #[cfg(feature = "cookies-signed")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookies-signed")))]
+    pub fn add_signed(&mut self, cookie: Cookie, key: &Key) {
+        let value = key.sign(&cookie.name, &cookie.value);
+        self.add(cookie.with_value(value));
+    }
+
+    /// Decrypted cookie added with [`CookieJar::add_private`].
+    #[cfg(feature = "cookies-signed")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookies-signed")))]
+    pub fn get_private(&self, name: &str, key: &Key) -> Option<Cookie> {
+        let cookie = self.get(name)?;
+        let value = key.decrypt(&cookie.name, &cookie.value)?;
+        Some(cookie.clone().with_value(value))
+    }
+
+    /// Add a cookie with an encrypted value, which clients can neither read nor change.
+    #[cfg(feature = "cookies-signed")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookies-signed")))]
+    pub fn add_private(&mut self, cookie: Cookie, key: &Key) {
+        let value = key.encrypt(&cookie.name, &cookie.value);
+        self.add(cookie.with_value(value));
+    }
+}
+
+impl FromRequest for CookieJar {
+    type Rejection = Infallible;
+
+    fn from_request(request: &Request) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(request.headers()))
+    }
+}
+
+#[cfg(feature = "cookies-signed")]
+pub use self::signed::{InvalidKeyLength, Key};
+
+#[cfg(feature = "cookies-signed")]
+mod signed {
+    use std::{error::Error, fmt};
+
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use ring::{
+        aead::{self, Aad, LessSafeKey, Nonce, UnboundKey, NONCE_LEN},
+        hmac,
+        rand::{SecureRandom, SystemRandom},
+    };
+
+    const KEY_LEN: usize = 64;
+    /// Length of an HMAC-SHA256 tag encoded in base64.
+    const TAG_LEN: usize = 43;
+
+    /// Secret key of signed and private cookies.
+    ///
+    /// The first half of the key signs cookies with HMAC-SHA256, and the second half encrypts
+    /// them with AES-256-GCM. Keep the key stable across invocations and deployments, for
+    /// example in AWS Secrets Manager, or clients lose their cookies.
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookies-signed")))]
+    #[derive(Clone)]
+    pub struct Key([u8; KEY_LEN]);
+
+    impl Key {
+        /// Create a key from the first 64 bytes of a master key.
+        pub fn from_bytes(master: &[u8]) -> Result<Self, InvalidKeyLength> {
+            let bytes = master
+                .get(..KEY_LEN)
+                .ok_or(InvalidKeyLength(master.len()))?
+                .try_into()
+                .expect("the slice has the length of the key");
+            Ok(Self(bytes))
+        }
+
+        /// Generate a random key.
+        pub fn generate() -> Self {
+            let mut bytes = [0; KEY_LEN];
+            SystemRandom::new()
+                .fill(&mut bytes)
+                .expect("the system random number generator is available");
+            Self(bytes)
+        }
+
+        fn signing_key(&self) -> hmac::Key {
+            hmac::Key::new(hmac::HMAC_SHA256, &self.0[..KEY_LEN / 2])
+        }
+
+        fn encryption_key(&self) -> LessSafeKey {
+            let key = UnboundKey::new(&aead::AES_256_GCM, &self.0[KEY_LEN / 2..]).expect("the key has 32 bytes");
+            LessSafeKey::new(key)
+        }
+
+        pub(super) fn sign(&self, name: &str, value: &str) -> String {
+            let tag = hmac::sign(&self.signing_key(), format!("{name}={value}").as_bytes());
+            format!("{}{value}", URL_SAFE_NO_PAD.encode(tag))
+        }
+
+        pub(super) fn verify(&self, name: &str, signed: &str) -> Option<String> {
+            if !signed.is_char_boundary(TAG_LEN) {
+                return None;
+            }
+            let (tag, value) = signed.split_at(TAG_LEN);
+            let tag = URL_SAFE_NO_PAD.decode(tag).ok()?;
+            hmac::verify(&self.signing_key(), format!("{name}={value}").as_bytes(), &tag).ok()?;
+            Some(value.to_string())
+        }
+
+        pub(super) fn encrypt(&self, name: &str, value: &str) -> String {
+            let mut nonce = [0; NONCE_LEN];
+            SystemRandom::new()
+                .fill(&mut nonce)
+                .expect("the system random number generator is available");
+            let mut sealed = value.as_bytes().to_vec();
+            self.encryption_key()
+                .seal_in_place_append_tag(
+                    Nonce::assume_unique_for_key(nonce),
+                    Aad::from(name.as_bytes()),
+                    &mut sealed,
+                )
+                .expect("the value fits in a cookie");
+            URL_SAFE_NO_PAD.encode([&nonce[..], &sealed].concat())
+        }
+
+        pub(super) fn decrypt(&self, name: &str, encrypted: &str) -> Option<String> {
+            let data = URL_SAFE_NO_PAD.decode(encrypted).ok()?;
+            if data.len() < NONCE_LEN {
+                return None;
+            }
+            let (nonce, sealed) = data.split_at(NONCE_LEN);
+            let nonce = Nonce::try_assume_unique_for_key(nonce).ok()?;
+            let mut sealed = sealed.to_vec();
+            let value = self
+                .encryption_key()
+                .open_in_place(nonce, Aad::from(name.as_bytes()), &mut sealed)
+                .ok()?;
+            String::from_utf8(value.to_vec()).ok()
+        }
+    }
+
+    impl fmt::Debug for Key {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Key").finish_non_exhaustive()
+        }
+    }
+
+    /// Error returned when a master key is shorter than 64 bytes.
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookies-signed")))]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct InvalidKeyLength(usize);
+
+    impl fmt::Display for InvalidKeyLength {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "cookie keys need at least {KEY_LEN} bytes, got {}", self.0)
+        }
+    }
+
+    impl Error for InvalidKeyLength {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, RequestExtractExt};
+
+    #[test]
+    fn parse_request_cookies() {
+        let request = http::Request::builder()
+            .header(COOKIE, "theme=dark;session=\"abc\"")
+            .header(COOKIE, "lang=en; empty=")
+            .body(Body::Empty)
+            .unwrap();
+        let jar = request.extract::<CookieJar>().unwrap();
+        assert_eq!(Some("dark"), jar.get("theme").map(Cookie::value));
+        assert_eq!(Some("abc"), jar.get("session").map(Cookie::value));
+        assert_eq!(Some("en"), jar.get("lang").map(Cookie::value));
+        assert_eq!(Some(""), jar.get("empty").map(Cookie::value));
+        assert_eq!(None, jar.get("missing"));
+        assert_eq!(0, jar.delta().count());
+    }
+
+    #[test]
+    fn set_response_cookies() {
+        let mut headers = HeaderMap::new();
+        headers.insert(COOKIE, HeaderValue::from_static("theme=dark; lang=en"));
+        let mut jar = CookieJar::from_headers(&headers);
+        jar.add(
+            Cookie::new("theme", "light")
+                .with_path("/")
+                .with_max_age(Duration::from_secs(3600))
+                .with_secure(true)
+                .with_http_only(true)
+                .with_same_site(SameSite::Lax),
+        );
+        jar.remove(Cookie::named("lang").with_path("/"));
+        assert_eq!(Some("light"), jar.get("theme").map(Cookie::value));
+        assert_eq!(None, jar.get("lang"));
+        assert_eq!(vec!["theme"], jar.iter().map(Cookie::name).collect::<Vec<_>>());
+
+        let mut response = Response::new(Body::Empty);
+        jar.apply(&mut response).unwrap();
+        let set_cookies: Vec<_> = response.headers().get_all(SET_COOKIE).iter().collect();
+        assert_eq!(
+            vec![
+                "theme=light; Path=/; Max-Age=3600; Secure; HttpOnly; SameSite=Lax",
+                "lang=; Path=/; Max-Age=0"
+            ],
+            set_cookies
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cookies-signed")]
+    fn sign_and_encrypt_cookies() {
+        let err = Key::from_bytes(&[0; 32]).unwrap_err();
+        assert_eq!("cookie keys need at least 64 bytes, got 32", err.to_string());
+        let key = Key::from_bytes(&[7; 64]).unwrap();
+        let mut jar = CookieJar::new();
+        jar.add_signed(Cookie::new("user", "ferris"), &key);
+        jar.add_private(Cookie::new("token", "secret"), &key);
+
+        let signed = jar.get("user").unwrap().value();
+        assert!(signed.ends_with("ferris"));
+        assert_eq!(Some("ferris"), jar.get_signed("user", &key).as_ref().map(Cookie::value));
+        assert!(!jar.get("token").unwrap().value().contains("secret"));
+        assert_eq!(
+            Some("secret"),
+            jar.get_private("token", &key).as_ref().map(Cookie::value)
+        );
+
+        // Tampered values, values moved to another cookie, and other keys are rejected.
+        let mut tampered = CookieJar::new();
+        tampered.add(Cookie::new("user", signed.replace("ferris", "admin")));
+        tampered.add(Cookie::new("other", signed));
+        tampered.add(Cookie::new("token", jar.get("token").unwrap().value()));
+        assert_eq!(None, tampered.get_signed("user", &key));
+        assert_eq!(None, tampered.get_signed("other", &key));
+        assert_eq!(None, tampered.get_private("token", &Key::generate()));
+        assert_eq!(None, jar.get_private("user", &key));
+    }
+
+    #[test]
+    fn reject_invalid_cookies() {
+        let invalid = [
+            Cookie::new("session", "abc; Domain=evil.example.com"),
+            Cookie::new("session", "a b"),
+            Cookie::new("session", "abc\r\nSet-Cookie: admin=1"),
+            Cookie::new("ses;sion", "abc"),
+            Cookie::new("", "abc"),
+            Cookie::new("session", "abc").with_path("/; Domain=evil.example.com"),
+            Cookie::new("session", "abc").with_domain("example.com; Secure"),
+        ];
+        for cookie in invalid {
+            let mut jar = CookieJar::new();
+            jar.add(Cookie::new("theme", "dark"));
+            jar.add(cookie.clone());
+            let mut response = Response::new(Body::Empty);
+            assert!(jar.apply(&mut response).is_err(), "{cookie}");
+            assert_eq!(0, response.headers().get_all(SET_COOKIE).iter().count());
+        }
+
+        assert_eq!(
+            "cookie \"session\" has an invalid value",
+            Cookie::new("session", "a;b").validate().unwrap_err().to_string()
+        );
+        assert_eq!(Ok(()), Cookie::new("session", "\"quoted\"").validate());
+    }
+}
@@ -0,0 +1,424 @@
+//! Errors of handlers that convert into HTTP responses.
+//!
+//! Errors returned by handlers fail the invocation, and API Gateway answers clients with a
+//! `502 Bad Gateway`. Handlers wrapped in an [`ErrorResponseLayer`](crate::layers::ErrorResponseLayer)
+//! turn their errors into responses instead, with [`IntoResponse`]: an [`ErrorResponse`] carries
+//! its status code, `application/problem+json` body, and headers, and other errors become
+//! `500 Internal Server Error` responses that don't leak their details.
+//!
+//! The bodies are [`Problem`] documents, which handlers can also return directly, with the
+//! `type`, `instance`, and extension members of their API.
+//!
+//! ```rust,no_run
+//! use lambda_http::{
+//!     error::ErrorResponse, http::StatusCode, layers::ErrorResponseLayer, service_fn,
+//!     tower::ServiceBuilder, Error, Request, RequestExt,
+//! };
+//!
+//! async fn handler(request: Request) -> Result<String, ErrorResponse> {
+//!     let name = request
+//!         .query_string_parameters_ref()
+//!         .and_then(|params| params.first("name"))
+//!         .ok_or_else(|| ErrorResponse::new(StatusCode::BAD_REQUEST).detail("missing name"))?;
+//!     Ok(format!("hello {name}"))
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Error> {
+//!     let handler = ServiceBuilder::new()
+//!         .layer(ErrorResponseLayer::new())
+//!         .service(service_fn(handler));
+//!     lambda_http::run(handler).await
+//! }
+//! ```
+
+use std::{error, fmt};
+
+use http::{
+    header::{IntoHeaderName, CONTENT_TYPE},
+    HeaderMap, HeaderValue, StatusCode,
+};
+use serde_json::{Map, Value};
+
+use crate::{ext::extract::ExtractError, response::ResponseFuture, Body, Error, IntoResponse, Response};
+
+/// [Problem details](https://www.rfc-editor.org/rfc/rfc9457) of an error, returned as an
+/// `application/problem+json` response.
+///
+/// The `type` is `about:blank` by default, whose `title` is the reason phrase of the status code.
+/// Extension members add details for clients, like the invalid fields of a request.
+///
+/// ```rust
+/// use lambda_http::{error::Problem, http::StatusCode};
+/// use serde_json::json;
+///
+/// let problem = Problem::new(StatusCode::FORBIDDEN)
+///     .problem_type("https://example.com/problems/out-of-credit")
+///     .title("You do not have enough credit.")
+///     .detail("Your current balance is 30, but that costs 50.")
+///     .instance("/account/12345/msgs/abc")
+///     .extension("balance", 30);
+/// assert_eq!(json!(30), problem.to_json()["balance"]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    status: StatusCode,
+    problem_type: Option<String>,
+    title: Option<String>,
+    detail: Option<String>,
+    instance: Option<String>,
+    extensions: Map<String, Value>,
+}
+
+impl Problem {
+    /// Members of the problem details defined by the RFC, which extensions can't replace.
+    const MEMBERS: [&'static str; 5] = ["type", "title", "status", "detail", "instance"];
+
+    /// Problem with a status code, and the `about:blank` type.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            status,
+            problem_type: None,
+            title: None,
+            detail: None,
+            instance: None,
+            extensions: Map::new(),
+        }
+    }
+
+    /// Set the URI that identifies the type of the problem.
+    pub fn problem_type(mut self, uri: impl Into<String>) -> Self {
+        self.problem_type = Some(uri.into());
+        self
+    }
+
+    /// Set the summary of the type of the problem, the same for all its occurrences.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the explanation of this occurrence of the problem.
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Set the URI that identifies this occurrence of the problem.
+    pub fn instance(mut self, uri: impl Into<String>) -> Self {
+        self.instance = Some(uri.into());
+        self
+    }
+
+    /// Add an extension member. Members named like the ones of the RFC are ignored.
+    pub fn extension(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        let name = name.into();
+        if !Self::MEMBERS.contains(&name.as_str()) {
+            self.extensions.insert(name, value.into());
+        }
+        self
+    }
+
+    /// Status code of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// URI of the type of the problem, `about:blank` by default.
+    pub fn type_ref(&self) -> &str {
+        self.problem_type.as_deref().unwrap_or("about:blank")
+    }
+
+    /// Summary of the type of the problem, the reason phrase of the status code by default.
+    pub fn title_ref(&self) -> &str {
+        self.title
+            .as_deref()
+            .unwrap_or_else(|| self.status.canonical_reason().unwrap_or_default())
+    }
+
+    /// Explanation of this occurrence of the problem.
+    pub fn detail_ref(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    /// URI of this occurrence of the problem.
+    pub fn instance_ref(&self) -> Option<&str> {
+        self.instance.as_deref()
+    }
+
+    /// Extension members of the problem.
+    pub fn extensions(&self) -> &Map<String, Value> {
+        &self.extensions
+    }
+
+    /// JSON document of the problem details.
+    pub fn to_json(&self) -> Value {
+        let mut members = Map::new();
+        members.insert("type".into(), self.type_ref().into());
+        members.insert("title".into(), self.title_ref().into());
+        members.insert("status".into(), self.status.as_u16().into());
+        if let Some(detail) = &self.detail {
+            members.insert("detail".into(), detail.clone().into());
+        }
+        if let Some(instance) = &self.instance {
+            members.insert("instance".into(), instance.clone().into());
+        }
+        members.extend(self.extensions.clone());
+        Value::Object(members)
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.status.as_u16(), self.title_ref())?;
+        if let Some(detail) = &self.detail {
+            write!(f, ": {detail}")?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for Problem {}
+
+impl From<Problem> for Response<Body> {
+    fn from(problem: Problem) -> Self {
+        let mut response = Response::new(Body::from(problem.to_json().to_string()));
+        *response.status_mut() = problem.status;
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/problem+json"));
+        response
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> ResponseFuture {
+        Response::<Body>::from(self).into_response()
+    }
+}
+
+/// Error that converts into an HTTP response, with a status code, an
+/// [`application/problem+json`](https://www.rfc-editor.org/rfc/rfc9457) body, and headers.
+///
+/// The body is a [`Problem`], with the `title` of the status code and the optional `detail` of
+/// the error by default. The [source](ErrorResponse::with_source) of the error is kept for logs,
+/// but never sent to clients.
+#[derive(Debug)]
+pub struct ErrorResponse {
+    problem: Problem,
+    headers: HeaderMap,
+    source: Option<Error>,
+}
+
+impl ErrorResponse {
+    /// Error with a status code, usually a client or server error.
+    pub fn new(status: StatusCode) -> Self {
+        Problem::new(status).into()
+    }
+
+    /// `400 Bad Request` error, with a detail for clients.
+    pub fn bad_request(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST).detail(detail)
+    }
+
+    /// `404 Not Found` error, with a detail for clients.
+    pub fn not_found(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND).detail(detail)
+    }
+
+    /// `500 Internal Server Error`, caused by another error that isn't sent to clients.
+    pub fn internal(source: impl Into<Error>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR).with_source(source)
+    }
+
+    /// Set the explanation of the error for clients.
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.problem = self.problem.detail(detail);
+        self
+    }
+
+    /// Add a header to the response, like `Retry-After` or `WWW-Authenticate`.
+    pub fn header(mut self, name: impl IntoHeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Set the error that caused this one.
+    pub fn with_source(mut self, source: impl Into<Error>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Status code of the response.
+    pub fn status(&self) -> StatusCode {
+        self.problem.status()
+    }
+
+    /// Explanation of the error for clients.
+    pub fn detail_ref(&self) -> Option<&str> {
+        self.problem.detail_ref()
+    }
+
+    /// Problem details of the body of the response.
+    pub fn problem_ref(&self) -> &Problem {
+        &self.problem
+    }
+
+    /// Headers of the response.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+}
+
+impl fmt::Display for ErrorResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.problem.status())?;
+        if let Some(detail) = self.problem.detail_ref() {
+            write!(f, ": {detail}")?;
+        }
+        if let Some(source) = &self.source {
+            write!(f, ": {source}")?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for ErrorResponse {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn error::Error + 'static))
+    }
+}
+
+impl From<ExtractError> for ErrorResponse {
+    fn from(err: ExtractError) -> Self {
+        Self::new(err.status()).detail(err.to_string()).with_source(err)
+    }
+}
+
+/// Error with the status code and the members of a problem.
+impl From<Problem> for ErrorResponse {
+    fn from(problem: Problem) -> Self {
+        Self {
+            problem,
+            headers: HeaderMap::new(),
+            source: None,
+        }
+    }
+}
+
+impl From<ErrorResponse> for Response<Body> {
+    fn from(err: ErrorResponse) -> Self {
+        let mut response = Response::from(err.problem);
+        let mut headers = err.headers;
+        headers.extend(response.headers_mut().drain());
+        *response.headers_mut() = headers;
+        response
+    }
+}
+
+impl IntoResponse for ErrorResponse {
+    fn into_response(self) -> ResponseFuture {
+        Response::<Body>::from(self).into_response()
+    }
+}
+
+/// Errors that aren't an [`ErrorResponse`], a [`Problem`], or an [`ExtractError`] become
+/// `500 Internal Server Error` responses, without their message.
+impl IntoResponse for Error {
+    fn into_response(self) -> ResponseFuture {
+        let err = match self.downcast::<ErrorResponse>() {
+            Ok(err) => return err.into_response(),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<Problem>() {
+            Ok(problem) => return problem.into_response(),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<ExtractError>() {
+            Ok(err) => return err.into_response(),
+            Err(err) => err,
+        };
+        #[cfg(feature = "tracing")]
+        crate::tracing::error!(error = %err, "handler failed");
+        ErrorResponse::internal(err).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::RETRY_AFTER;
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn problem_json_body() {
+        let err = ErrorResponse::new(StatusCode::SERVICE_UNAVAILABLE)
+            .detail("try again later")
+            .header(RETRY_AFTER, HeaderValue::from_static("30"));
+        let response = err.into_response().await;
+        assert_eq!(StatusCode::SERVICE_UNAVAILABLE, response.status());
+        assert_eq!("application/problem+json", response.headers()[CONTENT_TYPE]);
+        assert_eq!("30", response.headers()[RETRY_AFTER]);
+        let body: Value = serde_json::from_slice(response.body().as_ref()).unwrap();
+        assert_eq!(
+            json!({
+                "type": "about:blank",
+                "title": "Service Unavailable",
+                "status": 503,
+                "detail": "try again later",
+            }),
+            body
+        );
+    }
+
+    #[tokio::test]
+    async fn hide_internal_errors() {
+        let err: Error = "database password is hunter2".into();
+        let response = err.into_response().await;
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+        assert!(!String::from_utf8_lossy(response.body().as_ref()).contains("hunter2"));
+
+        let err: Error = ErrorResponse::not_found("no such user").into();
+        assert_eq!(StatusCode::NOT_FOUND, err.into_response().await.status());
+
+        let err: Error = ExtractError::PayloadTooLarge { limit: 1 }.into();
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, err.into_response().await.status());
+    }
+
+    #[tokio::test]
+    async fn problem_members() {
+        let problem = Problem::new(StatusCode::FORBIDDEN)
+            .problem_type("https://example.com/problems/out-of-credit")
+            .title("You do not have enough credit.")
+            .instance("/account/12345")
+            .extension("balance", 30)
+            .extension("status", 200);
+        let response = problem.clone().into_response().await;
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+        assert_eq!("application/problem+json", response.headers()[CONTENT_TYPE]);
+        let body: Value = serde_json::from_slice(response.body().as_ref()).unwrap();
+        assert_eq!(
+            json!({
+                "type": "https://example.com/problems/out-of-credit",
+                "title": "You do not have enough credit.",
+                "status": 403,
+                "instance": "/account/12345",
+                "balance": 30,
+            }),
+            body
+        );
+
+        let err: Error = ErrorResponse::from(problem.clone())
+            .header(RETRY_AFTER, HeaderValue::from_static("60"))
+            .into();
+        let response = err.into_response().await;
+        assert_eq!("60", response.headers()[RETRY_AFTER]);
+        assert_eq!(body, serde_json::from_slice::<Value>(response.body().as_ref()).unwrap());
+
+        let err: Error = problem.into();
+        assert_eq!(StatusCode::FORBIDDEN, err.into_response().await.status());
+    }
+}
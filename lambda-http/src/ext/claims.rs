@@ -0,0 +1,230 @@
+//! Claims of the authorizer of API Gateway requests
+
+use aws_lambda_events::apigw::ApiGatewayRequestAuthorizer;
+use serde::{
+    de::{
+        value::{MapDeserializer, SeqDeserializer},
+        DeserializeOwned, Deserializer, IntoDeserializer, Visitor,
+    },
+    forward_to_deserialize_any,
+};
+use serde_json::{Map, Value};
+
+use crate::request::RequestContext;
+
+/// Claims of the authorizer: the claims of JWT authorizers, the claims of Cognito user pool
+/// authorizers of REST APIs, or the context of Lambda authorizers.
+fn claims(authorizer: &ApiGatewayRequestAuthorizer) -> Option<Map<String, Value>> {
+    if let Some(jwt) = &authorizer.jwt {
+        return Some(
+            jwt.claims
+                .iter()
+                .map(|(name, value)| (name.clone(), Value::String(value.clone())))
+                .collect(),
+        );
+    }
+    if let Some(Value::Object(claims)) = authorizer.fields.get("claims") {
+        return Some(claims.clone());
+    }
+    if authorizer.fields.is_empty() {
+        return None;
+    }
+    Some(
+        authorizer
+            .fields
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect(),
+    )
+}
+
+fn claim<'a>(authorizer: &'a ApiGatewayRequestAuthorizer, name: &str) -> Option<&'a Value> {
+    match authorizer.fields.get("claims") {
+        Some(Value::Object(claims)) => claims.get(name),
+        _ => authorizer.fields.get(name),
+    }
+}
+
+fn authorizer(context: Option<&RequestContext>) -> Option<&ApiGatewayRequestAuthorizer> {
+    context?.authorizer()
+}
+
+pub(crate) fn deserialize<T: DeserializeOwned>(
+    context: Option<&RequestContext>,
+) -> Result<Option<T>, serde_json::Error> {
+    let Some(claims) = authorizer(context).and_then(claims) else {
+        return Ok(None);
+    };
+    T::deserialize(Coerce(Value::Object(claims))).map(Some)
+}
+
+/// Deserializer of claims that parses strings into the numbers, booleans, objects, and arrays
+/// that fields expect, since REST APIs turn the values of the context of Lambda authorizers into
+/// strings, and HTTP APIs the values of JWT claims. Fields that expect strings keep them as they
+/// are, so a zip code like `"02134"` stays a string.
+struct Coerce(Value);
+
+impl Coerce {
+    /// Parse a string into the JSON value that a field expects, when `expected` accepts it.
+    fn parse(self, expected: fn(&Value) -> bool) -> Value {
+        match self.0 {
+            Value::String(string) => match serde_json::from_str(&string) {
+                Ok(value) if expected(&value) => value,
+                _ => Value::String(string),
+            },
+            value => value,
+        }
+    }
+}
+
+macro_rules! deserialize_scalars {
+    ($expected:expr => $($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.parse($expected).$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for Coerce {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Object(map) => {
+                let mut map = MapDeserializer::new(map.into_iter().map(|(name, value)| (name, Coerce(value))));
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+            Value::Array(values) => {
+                let mut seq = SeqDeserializer::new(values.into_iter().map(Coerce));
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    deserialize_scalars!(Value::is_number => deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64);
+    deserialize_scalars!(Value::is_boolean => deserialize_bool);
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(Coerce(value)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        Coerce(self.parse(Value::is_array)).deserialize_any(visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        Coerce(self.parse(Value::is_object)).deserialize_any(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, serde_json::Error> for Coerce {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+pub(crate) fn sub(context: Option<&RequestContext>) -> Option<&str> {
+    let authorizer = authorizer(context)?;
+    match &authorizer.jwt {
+        Some(jwt) => jwt.claims.get("sub").map(String::as_str),
+        None => claim(authorizer, "sub")?.as_str(),
+    }
+}
+
+pub(crate) fn scopes(context: Option<&RequestContext>) -> Vec<String> {
+    let Some(authorizer) = authorizer(context) else {
+        return Vec::new();
+    };
+    if let Some(scopes) = authorizer.jwt.as_ref().and_then(|jwt| jwt.scopes.as_ref()) {
+        return scopes.clone();
+    }
+    let scope = match &authorizer.jwt {
+        Some(jwt) => jwt.claims.get("scope").map(String::as_str),
+        None => claim(authorizer, "scope").and_then(Value::as_str),
+    };
+    scope
+        .map(|scope| scope.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+pub(crate) fn cognito_groups(context: Option<&RequestContext>) -> Vec<String> {
+    let Some(authorizer) = authorizer(context) else {
+        return Vec::new();
+    };
+    let groups = match &authorizer.jwt {
+        Some(jwt) => jwt.claims.get("cognito:groups").cloned().map(Value::String),
+        None => claim(authorizer, "cognito:groups").cloned(),
+    };
+    match groups {
+        Some(Value::Array(groups)) => groups
+            .into_iter()
+            .filter_map(|group| group.as_str().map(str::to_string))
+            .collect(),
+        // Groups are sent as `[admin users]` by HTTP APIs, and as `admin,users` by REST APIs.
+        Some(Value::String(groups)) => groups
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split([',', ' '])
+            .filter(|group| !group.is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
@@ -1,8 +1,12 @@
 //! Extension methods for `http::Extensions` and `http::Request<T>` types
 
+#[cfg(feature = "apigw_http")]
+use aws_lambda_events::apigw::ApiGatewayRequestAuthorizerIamDescription;
 use aws_lambda_events::query_map::QueryMap;
 use http::request::Parts;
 use lambda_runtime::Context;
+#[cfg(any(feature = "apigw_rest", feature = "apigw_http", feature = "apigw_websockets"))]
+use serde::de::DeserializeOwned;
 
 use crate::request::RequestContext;
 
@@ -146,6 +150,89 @@ pub trait RequestExt {
 
     /// Configures instance with lambda context
     fn with_lambda_context(self, context: Context) -> Self;
+
+    /// Deserialize the claims of the API Gateway authorizer of the request
+    ///
+    /// The claims are the ones of JWT authorizers, or of Cognito user pool authorizers of
+    /// REST APIs, or else the context of Lambda authorizers. Values that API Gateway turned
+    /// into strings, like numbers or JSON objects, are parsed back when `T` expects them.
+    ///
+    /// Returns `Ok(None)` when the request has no authorizer claims.
+    ///
+    /// ```rust,no_run
+    /// use lambda_http::{Request, RequestExt};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Claims {
+    ///     sub: String,
+    ///     email: String,
+    /// }
+    ///
+    /// fn email(request: &Request) -> Option<String> {
+    ///     let claims: Claims = request.authorizer_claims().ok()??;
+    ///     Some(claims.email)
+    /// }
+    /// ```
+    #[cfg(any(feature = "apigw_rest", feature = "apigw_http", feature = "apigw_websockets"))]
+    fn authorizer_claims<T>(&self) -> Result<Option<T>, serde_json::Error>
+    where
+        T: DeserializeOwned,
+    {
+        super::claims::deserialize(self.request_context_ref())
+    }
+
+    /// Return the `sub` claim of the API Gateway authorizer of the request, the user that the
+    /// token was issued to
+    #[cfg(any(feature = "apigw_rest", feature = "apigw_http", feature = "apigw_websockets"))]
+    fn authorizer_sub(&self) -> Option<&str> {
+        super::claims::sub(self.request_context_ref())
+    }
+
+    /// Return the OAuth scopes granted to the token of the API Gateway authorizer of the request
+    #[cfg(any(feature = "apigw_rest", feature = "apigw_http", feature = "apigw_websockets"))]
+    fn authorizer_scopes(&self) -> Vec<String> {
+        super::claims::scopes(self.request_context_ref())
+    }
+
+    /// Return the Cognito user pool groups of the user of the request, from the
+    /// `cognito:groups` claim of the API Gateway authorizer
+    #[cfg(any(feature = "apigw_rest", feature = "apigw_http", feature = "apigw_websockets"))]
+    fn cognito_groups(&self) -> Vec<String> {
+        super::claims::cognito_groups(self.request_context_ref())
+    }
+
+    /// Return the IAM identity of the caller of a Function URL or HTTP API with `AWS_IAM`
+    /// authorization
+    #[cfg(feature = "apigw_http")]
+    fn iam_identity(&self) -> Option<&ApiGatewayRequestAuthorizerIamDescription> {
+        match self.request_context_ref()? {
+            RequestContext::ApiGatewayV2(context) => context.authorizer.as_ref()?.iam.as_ref(),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// Return the id of the IAM principal that signed the request, like
+    /// `AROAEXAMPLE:session-name` for roles, from its [IAM identity](RequestExt::iam_identity)
+    #[cfg(feature = "apigw_http")]
+    fn iam_caller_id(&self) -> Option<&str> {
+        self.iam_identity()?.caller_id.as_deref()
+    }
+
+    /// Return the ARN of the IAM principal that signed the request, from its
+    /// [IAM identity](RequestExt::iam_identity)
+    #[cfg(feature = "apigw_http")]
+    fn iam_user_arn(&self) -> Option<&str> {
+        self.iam_identity()?.user_arn.as_deref()
+    }
+
+    /// Return the AWS account of the IAM principal that signed the request, from its
+    /// [IAM identity](RequestExt::iam_identity)
+    #[cfg(feature = "apigw_http")]
+    fn iam_account_id(&self) -> Option<&str> {
+        self.iam_identity()?.account_id.as_deref()
+    }
 }
 
 impl RequestExt for http::Extensions {
@@ -628,4 +715,146 @@ mod tests {
         let request = Request::default().with_raw_http_path("/raw-path");
         assert_eq!("/raw-path", request.raw_http_path());
     }
+
+    #[test]
+    #[cfg(feature = "apigw_http")]
+    fn requests_have_jwt_authorizer_claims() {
+        use aws_lambda_events::apigw::{
+            ApiGatewayRequestAuthorizer, ApiGatewayRequestAuthorizerJwtDescription, ApiGatewayV2httpRequestContext,
+        };
+
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Claims {
+            sub: String,
+            email_verified: bool,
+        }
+
+        let mut jwt = ApiGatewayRequestAuthorizerJwtDescription::default();
+        jwt.claims = [
+            ("sub", "user-1"),
+            ("email_verified", "true"),
+            ("cognito:groups", "[admin users]"),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        jwt.scopes = Some(vec!["read".into(), "write".into()]);
+        let mut authorizer = ApiGatewayRequestAuthorizer::default();
+        authorizer.jwt = Some(jwt);
+        let mut context = ApiGatewayV2httpRequestContext::default();
+        context.authorizer = Some(authorizer);
+        let request = Request::default().with_request_context(crate::request::RequestContext::ApiGatewayV2(context));
+
+        assert_eq!(
+            Some(Claims {
+                sub: "user-1".into(),
+                email_verified: true
+            }),
+            request.authorizer_claims().unwrap()
+        );
+        assert_eq!(Some("user-1"), request.authorizer_sub());
+        assert_eq!(vec!["read", "write"], request.authorizer_scopes());
+        assert_eq!(vec!["admin", "users"], request.cognito_groups());
+    }
+
+    #[test]
+    #[cfg(feature = "apigw_rest")]
+    fn requests_have_rest_authorizer_claims() {
+        use aws_lambda_events::apigw::ApiGatewayProxyRequestContext;
+        use serde_json::json;
+
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Tenant {
+            id: u64,
+            plan: String,
+        }
+
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Context {
+            tenant: Tenant,
+            admin: bool,
+            zip: String,
+            coupon: String,
+            seats: Option<u32>,
+        }
+
+        let mut context = ApiGatewayProxyRequestContext::default();
+        context.authorizer.fields = [
+            ("principalId", json!("user-2")),
+            ("tenant", json!(r#"{"id":"7","plan":"pro"}"#)),
+            ("admin", json!("false")),
+            ("zip", json!("02134")),
+            ("coupon", json!("2024")),
+            ("seats", json!("25")),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+        let request =
+            Request::default().with_request_context(crate::request::RequestContext::ApiGatewayV1(context.clone()));
+        assert_eq!(
+            Some(Context {
+                tenant: Tenant {
+                    id: 7,
+                    plan: "pro".into()
+                },
+                admin: false,
+                zip: "02134".into(),
+                coupon: "2024".into(),
+                seats: Some(25),
+            }),
+            request.authorizer_claims().unwrap()
+        );
+        assert_eq!(None, request.authorizer_sub());
+
+        context.authorizer.fields = [(
+            "claims".to_string(),
+            json!({"sub": "user-3", "scope": "openid profile", "cognito:groups": "admin,users"}),
+        )]
+        .into_iter()
+        .collect();
+        let request = Request::default().with_request_context(crate::request::RequestContext::ApiGatewayV1(context));
+        assert_eq!(Some("user-3"), request.authorizer_sub());
+        assert_eq!(vec!["openid", "profile"], request.authorizer_scopes());
+        assert_eq!(vec!["admin", "users"], request.cognito_groups());
+        assert!(request.authorizer_claims::<Context>().is_err());
+        assert!(Request::default().authorizer_claims::<Context>().unwrap().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "apigw_http")]
+    fn function_url_requests_have_iam_identities() {
+        let request = crate::request::from_str(
+            r#"{
+                "version": "2.0",
+                "rawPath": "/admin",
+                "requestContext": {
+                    "accountId": "123456789012",
+                    "authorizer": {
+                        "iam": {
+                            "accessKey": "ASIAEXAMPLE",
+                            "accountId": "111122223333",
+                            "callerId": "AROAEXAMPLE:admin-session",
+                            "userArn": "arn:aws:sts::111122223333:assumed-role/admin/admin-session",
+                            "userId": "AROAEXAMPLE:admin-session"
+                        }
+                    },
+                    "domainName": "example.lambda-url.us-east-1.on.aws",
+                    "http": {"method": "GET", "path": "/admin"}
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(Some("AROAEXAMPLE:admin-session"), request.iam_caller_id());
+        assert_eq!(
+            Some("arn:aws:sts::111122223333:assumed-role/admin/admin-session"),
+            request.iam_user_arn()
+        );
+        assert_eq!(Some("111122223333"), request.iam_account_id());
+        assert_eq!(
+            Some("ASIAEXAMPLE"),
+            request.iam_identity().unwrap().access_key.as_deref()
+        );
+        assert!(Request::default().iam_identity().is_none());
+    }
 }
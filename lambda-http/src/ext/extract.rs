@@ -0,0 +1,799 @@
+//! Typed extractors for `Request` types
+
+use std::{error::Error, fmt, str::FromStr};
+
+use http::{header::CONTENT_TYPE, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    response::{IntoResponse, ResponseFuture},
+    Body, Request, RequestExt, Response,
+};
+
+/// Types that can be created from a request, with [`RequestExtractExt::extract`].
+pub trait FromRequest: Sized {
+    /// Error returned when the request doesn't contain a valid value.
+    type Rejection;
+
+    /// Extract the value from the request.
+    fn from_request(request: &Request) -> Result<Self, Self::Rejection>;
+}
+
+/// Extends `http::Request<Body>` with typed extractors.
+///
+/// ### Examples
+///
+/// ```rust,no_run
+/// use lambda_http::{
+///     ext::extract::{Form, Query},
+///     service_fn, Error, IntoResponse, Request, RequestExtractExt, Response, Body,
+/// };
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Pagination {
+///     page: u32,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Comment {
+///     author: String,
+///     text: String,
+/// }
+///
+/// async fn comment(request: Request) -> Result<Response<Body>, Error> {
+///     let (Query(pagination), Form(comment)) = match (
+///         request.extract::<Query<Pagination>>(),
+///         request.extract::<Form<Comment>>(),
+///     ) {
+///         (Ok(query), Ok(form)) => (query, form),
+///         (Err(rejection), _) | (_, Err(rejection)) => return Ok(rejection.into()),
+///     };
+///     Ok(Response::new(format!("{} on page {}: {}", comment.author, pagination.page, comment.text).into()))
+/// }
+/// ```
+pub trait RequestExtractExt {
+    /// Extract a typed value from the request, like a [`Query`], a [`Form`], a [`Json`] body, or a
+    /// [`Path`].
+    fn extract<E: FromRequest>(&self) -> Result<E, E::Rejection>;
+
+    /// Parse the path parameter `name` of the route, like the `id` of `/users/{id}`.
+    ///
+    /// ```rust,no_run
+    /// use lambda_http::{ext::extract::ExtractError, Request, RequestExtractExt};
+    ///
+    /// fn user_id(request: &Request) -> Result<u64, ExtractError> {
+    ///     request.path_param::<u64>("id")
+    /// }
+    /// ```
+    fn path_param<T>(&self, name: &str) -> Result<T, ExtractError>
+    where
+        T: FromStr,
+        T::Err: Error + Send + Sync + 'static;
+
+    /// URL that clients use to reach the root of the API, without a trailing slash.
+    ///
+    /// The URL includes the stage on the default domain of API Gateway, or the base path of a
+    /// custom domain. Its host is the domain name of the request context, and the host and
+    /// protocol of `X-Forwarded-Host` and `X-Forwarded-Proto` when the
+    /// [`Adapter`](crate::Adapter) trusts forwarded headers. Requests without a host, like
+    /// requests built in tests, get only the path.
+    ///
+    /// ```rust,no_run
+    /// use lambda_http::{Request, RequestExtractExt};
+    ///
+    /// fn login_url(request: &Request) -> String {
+    ///     format!("{}/login", request.base_url())
+    /// }
+    /// ```
+    fn base_url(&self) -> String;
+
+    /// Deserialize the body according to its content type: JSON, including `+json` types,
+    /// `application/x-www-form-urlencoded` forms, XML with the `xml` feature, and MessagePack
+    /// with the `msgpack` feature.
+    ///
+    /// Other content types are rejected with `415 Unsupported Media Type`, and bodies larger
+    /// than [`Json::DEFAULT_LIMIT`] with `413 Payload Too Large`.
+    ///
+    /// ```rust,no_run
+    /// use lambda_http::{ext::extract::ExtractError, Request, RequestExtractExt};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Order {
+    ///     item: String,
+    ///     quantity: u32,
+    /// }
+    ///
+    /// fn order(request: &Request) -> Result<Order, ExtractError> {
+    ///     request.body_as::<Order>()
+    /// }
+    /// ```
+    fn body_as<T: DeserializeOwned>(&self) -> Result<T, ExtractError>;
+}
+
+impl RequestExtractExt for Request {
+    fn extract<E: FromRequest>(&self) -> Result<E, E::Rejection> {
+        E::from_request(self)
+    }
+
+    fn path_param<T>(&self, name: &str) -> Result<T, ExtractError>
+    where
+        T: FromStr,
+        T::Err: Error + Send + Sync + 'static,
+    {
+        let value = self
+            .path_parameters_ref()
+            .and_then(|parameters| parameters.first(name))
+            .ok_or_else(|| ExtractError::MissingPathParam(name.to_string()))?;
+        parse_path_param(name, value)
+    }
+
+    fn base_url(&self) -> String {
+        crate::redirect::base_url(self)
+    }
+
+    fn body_as<T: DeserializeOwned>(&self) -> Result<T, ExtractError> {
+        let unsupported = ExtractError::UnsupportedMediaType {
+            expected: BODY_MEDIA_TYPES,
+        };
+        let mime = self
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .and_then(|content_type| content_type.parse::<mime::Mime>().ok())
+            .ok_or(unsupported)?;
+        let body = self.body().as_ref();
+        if body.len() > Json::<T>::DEFAULT_LIMIT {
+            return Err(ExtractError::PayloadTooLarge {
+                limit: Json::<T>::DEFAULT_LIMIT,
+            });
+        }
+
+        let (type_, subtype, suffix) = (mime.type_(), mime.subtype(), mime.suffix());
+        if type_ == mime::APPLICATION && (subtype == mime::JSON || suffix == Some(mime::JSON)) {
+            return serde_json::from_slice(body).map_err(ExtractError::InvalidJson);
+        }
+        if type_ == mime::APPLICATION && subtype == mime::WWW_FORM_URLENCODED {
+            return serde_urlencoded::from_bytes(body).map_err(ExtractError::InvalidForm);
+        }
+        #[cfg(feature = "xml")]
+        if (type_ == mime::APPLICATION || type_ == mime::TEXT) && subtype == mime::XML
+            || type_ == mime::APPLICATION && suffix == Some(mime::XML)
+        {
+            return quick_xml::de::from_reader(body).map_err(ExtractError::InvalidXml);
+        }
+        #[cfg(feature = "msgpack")]
+        if type_ == mime::APPLICATION && matches!(subtype.as_str(), "msgpack" | "x-msgpack" | "vnd.msgpack") {
+            return rmp_serde::from_slice(body).map_err(ExtractError::InvalidMsgPack);
+        }
+        Err(ExtractError::UnsupportedMediaType {
+            expected: BODY_MEDIA_TYPES,
+        })
+    }
+}
+
+/// Content types that [`RequestExtractExt::body_as`] deserializes, for its rejections.
+#[cfg(all(not(feature = "xml"), not(feature = "msgpack")))]
+const BODY_MEDIA_TYPES: &str = "application/json or application/x-www-form-urlencoded";
+#[cfg(all(feature = "xml", not(feature = "msgpack")))]
+const BODY_MEDIA_TYPES: &str = "application/json, application/x-www-form-urlencoded, or application/xml";
+#[cfg(all(not(feature = "xml"), feature = "msgpack"))]
+const BODY_MEDIA_TYPES: &str = "application/json, application/x-www-form-urlencoded, or application/msgpack";
+#[cfg(all(feature = "xml", feature = "msgpack"))]
+const BODY_MEDIA_TYPES: &str =
+    "application/json, application/x-www-form-urlencoded, application/xml, or application/msgpack";
+
+/// Query string parameters deserialized with `serde_urlencoded`.
+///
+/// The parameters are read from the query string parameters of the event, or from the URI of
+/// requests without them, like requests built in tests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Query<T>(pub T);
+
+impl<T: DeserializeOwned> FromRequest for Query<T> {
+    type Rejection = ExtractError;
+
+    fn from_request(request: &Request) -> Result<Self, Self::Rejection> {
+        let query = match request.query_string_parameters_ref() {
+            Some(parameters) => parameters.to_query_string(),
+            None => request.uri().query().unwrap_or_default().to_string(),
+        };
+        serde_urlencoded::from_str(&query)
+            .map(Query)
+            .map_err(ExtractError::InvalidQuery)
+    }
+}
+
+/// Path parameters of the route, parsed in the order of the route template.
+///
+/// `Path<(String, u64)>` extracts the parameters of `/users/{user}/posts/{id}`. The template
+/// is the one of the [`Router`](crate::router::Router) route that matched the request, or else
+/// the resource path of API Gateway REST APIs, or the route key of HTTP APIs. Requests
+/// without a template, like requests built in tests, can only have one path parameter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Path<T>(pub T);
+
+impl<T: FromPathParams> FromRequest for Path<T> {
+    type Rejection = ExtractError;
+
+    fn from_request(request: &Request) -> Result<Self, Self::Rejection> {
+        let parameters = request.path_parameters_ref();
+        let params = match route_template(request) {
+            Some(template) => template_params(template)
+                .map(|name| {
+                    parameters
+                        .and_then(|parameters| parameters.first(name))
+                        .map(|value| (name, value))
+                        .ok_or_else(|| ExtractError::MissingPathParam(name.to_string()))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => {
+                let params: Vec<_> = parameters
+                    .map(|parameters| parameters.iter().collect())
+                    .unwrap_or_default();
+                if params.len() > 1 {
+                    return Err(ExtractError::UnknownRouteTemplate);
+                }
+                params
+            }
+        };
+        T::from_path_params(&params).map(Path)
+    }
+}
+
+/// Tuples of path parameters that [`Path`] extracts, implemented for tuples of up to six
+/// values that implement [`FromStr`].
+pub trait FromPathParams: Sized {
+    /// Parse the names and values of the path parameters, in the order of the route template.
+    fn from_path_params(params: &[(&str, &str)]) -> Result<Self, ExtractError>;
+}
+
+macro_rules! impl_from_path_params {
+    ($len:literal: $($ty:ident),+) => {
+        impl<$($ty),+> FromPathParams for ($($ty,)+)
+        where
+            $($ty: FromStr, <$ty as FromStr>::Err: Error + Send + Sync + 'static,)+
+        {
+            fn from_path_params(params: &[(&str, &str)]) -> Result<Self, ExtractError> {
+                if params.len() != $len {
+                    return Err(ExtractError::PathParamCount {
+                        expected: $len,
+                        found: params.len(),
+                    });
+                }
+                let mut params = params.iter();
+                Ok(($({
+                    let (name, value) = params.next().expect("the number of parameters was checked");
+                    parse_path_param::<$ty>(name, value)?
+                },)+))
+            }
+        }
+    };
+}
+
+impl_from_path_params!(1: T1);
+impl_from_path_params!(2: T1, T2);
+impl_from_path_params!(3: T1, T2, T3);
+impl_from_path_params!(4: T1, T2, T3, T4);
+impl_from_path_params!(5: T1, T2, T3, T4, T5);
+impl_from_path_params!(6: T1, T2, T3, T4, T5, T6);
+
+fn parse_path_param<T>(name: &str, value: &str) -> Result<T, ExtractError>
+where
+    T: FromStr,
+    T::Err: Error + Send + Sync + 'static,
+{
+    value.parse().map_err(|err| ExtractError::InvalidPathParam {
+        name: name.to_string(),
+        source: Box::new(err),
+    })
+}
+
+pub(crate) fn route_template(request: &Request) -> Option<&str> {
+    #[cfg(feature = "router")]
+    if let Some(matched) = request.extensions().get::<crate::router::MatchedPath>() {
+        return Some(matched.as_str());
+    }
+    match request.request_context_ref()? {
+        #[cfg(feature = "apigw_rest")]
+        crate::request::RequestContext::ApiGatewayV1(context) => context.resource_path.as_deref(),
+        #[cfg(feature = "apigw_http")]
+        crate::request::RequestContext::ApiGatewayV2(context) => context.route_key.as_deref(),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
+/// Names of the parameters of a route template, like `id` and `proxy` in
+/// `GET /users/{id}/{proxy+}`.
+fn template_params(template: &str) -> impl Iterator<Item = &str> {
+    template.split('{').skip(1).filter_map(|segment| {
+        let (name, _) = segment.split_once('}')?;
+        Some(name.strip_suffix('+').unwrap_or(name))
+    })
+}
+
+/// `application/x-www-form-urlencoded` body deserialized with `serde_urlencoded`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Form<T>(pub T);
+
+impl<T: DeserializeOwned> FromRequest for Form<T> {
+    type Rejection = ExtractError;
+
+    fn from_request(request: &Request) -> Result<Self, Self::Rejection> {
+        let is_form = request
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("application/x-www-form-urlencoded"));
+        if !is_form {
+            return Err(ExtractError::UnsupportedMediaType {
+                expected: "application/x-www-form-urlencoded",
+            });
+        }
+        serde_urlencoded::from_bytes(request.body().as_ref())
+            .map(Form)
+            .map_err(ExtractError::InvalidForm)
+    }
+}
+
+/// JSON body, extracted from requests and returned in responses.
+///
+/// The extractor requires the `application/json` content type, or a `+json` one, and
+/// rejects bodies larger than [`Json::DEFAULT_LIMIT`]. Bodies with invalid JSON are rejected
+/// with `400 Bad Request`, and JSON that doesn't match `T` with `422 Unprocessable Entity`.
+///
+/// As a response, the value is serialized with the `application/json` content type. Return
+/// `(StatusCode, Json<T>)` to set the status code.
+///
+/// ```rust,no_run
+/// use http::StatusCode;
+/// use lambda_http::{ext::extract::Json, Body, Error, Request, RequestExtractExt, Response};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize)]
+/// struct NewUser {
+///     name: String,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     id: u64,
+///     name: String,
+/// }
+///
+/// async fn create_user(request: Request) -> Result<Response<Body>, Error> {
+///     let Json(user) = match request.extract::<Json<NewUser>>() {
+///         Ok(user) => user,
+///         Err(rejection) => return Ok(rejection.into()),
+///     };
+///     let mut response = Response::from(Json(User { id: 1, name: user.name }));
+///     *response.status_mut() = StatusCode::CREATED;
+///     Ok(response)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    /// Largest body that the extractor accepts, 1 MiB.
+    pub const DEFAULT_LIMIT: usize = 1024 * 1024;
+}
+
+impl<T: DeserializeOwned> Json<T> {
+    /// Extract the value from a request, with a body of at most `limit` bytes.
+    pub fn from_request_with_limit(request: &Request, limit: usize) -> Result<Self, ExtractError> {
+        let is_json = request
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .and_then(|content_type| content_type.parse::<mime::Mime>().ok())
+            .is_some_and(|mime| {
+                mime.type_() == mime::APPLICATION && (mime.subtype() == mime::JSON || mime.suffix() == Some(mime::JSON))
+            });
+        if !is_json {
+            return Err(ExtractError::UnsupportedMediaType {
+                expected: "application/json",
+            });
+        }
+        let body = request.body().as_ref();
+        if body.len() > limit {
+            return Err(ExtractError::PayloadTooLarge { limit });
+        }
+        serde_json::from_slice(body)
+            .map(Json)
+            .map_err(ExtractError::InvalidJson)
+    }
+}
+
+impl<T: DeserializeOwned> FromRequest for Json<T> {
+    type Rejection = ExtractError;
+
+    fn from_request(request: &Request) -> Result<Self, Self::Rejection> {
+        Self::from_request_with_limit(request, Self::DEFAULT_LIMIT)
+    }
+}
+
+impl<T: Serialize> From<Json<T>> for Response<Body> {
+    fn from(Json(value): Json<T>) -> Self {
+        match serde_json::to_string(&value) {
+            Ok(json) => Response::builder()
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(json))
+                .expect("unable to build http::Response"),
+            Err(err) => Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .header(CONTENT_TYPE, "text/plain")
+                .body(Body::from(format!("failed to serialize response: {err}")))
+                .expect("unable to build http::Response"),
+        }
+    }
+}
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> ResponseFuture {
+        Response::<Body>::from(self).into_response()
+    }
+}
+
+impl<T: Serialize> IntoResponse for (StatusCode, Json<T>) {
+    fn into_response(self) -> ResponseFuture {
+        let (status, json) = self;
+        let mut response = Response::<Body>::from(json);
+        if response.status().is_success() {
+            *response.status_mut() = status;
+        }
+        response.into_response()
+    }
+}
+
+/// Errors returned by the extractors when a request doesn't contain a valid value.
+///
+/// The error converts into a client error response, with the status code of
+/// [`ExtractError::status`] and the error message as body.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum ExtractError {
+    /// The content-type header of a request isn't the one of the extractor.
+    UnsupportedMediaType {
+        /// Content type of the extractor.
+        expected: &'static str,
+    },
+    /// The body is larger than the limit of the extractor.
+    PayloadTooLarge {
+        /// Largest body, in bytes, that the extractor accepts.
+        limit: usize,
+    },
+    /// The JSON payload can't be deserialized.
+    InvalidJson(serde_json::Error),
+    /// The query string parameters can't be deserialized.
+    InvalidQuery(serde_urlencoded::de::Error),
+    /// The form payload can't be deserialized.
+    InvalidForm(serde_urlencoded::de::Error),
+    /// The XML payload can't be deserialized.
+    #[cfg(feature = "xml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "xml")))]
+    InvalidXml(quick_xml::DeError),
+    /// The MessagePack payload can't be deserialized.
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    InvalidMsgPack(rmp_serde::decode::Error),
+    /// The route has no path parameter with this name.
+    MissingPathParam(String),
+    /// A path parameter can't be parsed.
+    InvalidPathParam {
+        /// Name of the path parameter.
+        name: String,
+        /// Error returned by the parser of the value.
+        source: Box<dyn Error + Send + Sync>,
+    },
+    /// The number of path parameters of the route isn't the one of the extractor.
+    PathParamCount {
+        /// Number of values of the extractor.
+        expected: usize,
+        /// Number of path parameters of the route.
+        found: usize,
+    },
+    /// The request has several path parameters, but no route template to order them.
+    UnknownRouteTemplate,
+}
+
+impl ExtractError {
+    /// Status code of the response to the request.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ExtractError::UnsupportedMediaType { .. } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ExtractError::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            // The payload is valid JSON, but it doesn't have the expected shape.
+            ExtractError::InvalidJson(err) if err.classify() == serde_json::error::Category::Data => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            ExtractError::InvalidJson(_) => StatusCode::BAD_REQUEST,
+            ExtractError::InvalidQuery(_) | ExtractError::InvalidForm(_) | ExtractError::InvalidPathParam { .. } => {
+                StatusCode::BAD_REQUEST
+            }
+            #[cfg(feature = "xml")]
+            ExtractError::InvalidXml(_) => StatusCode::BAD_REQUEST,
+            #[cfg(feature = "msgpack")]
+            ExtractError::InvalidMsgPack(_) => StatusCode::BAD_REQUEST,
+            // The route doesn't match the handler, which isn't the fault of the client.
+            ExtractError::MissingPathParam(_)
+            | ExtractError::PathParamCount { .. }
+            | ExtractError::UnknownRouteTemplate => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractError::UnsupportedMediaType { expected } => {
+                write!(f, "expected request with content type {expected}")
+            }
+            ExtractError::PayloadTooLarge { limit } => write!(f, "payload is larger than {limit} bytes"),
+            ExtractError::InvalidJson(err) => write!(f, "failed to deserialize json: {err}"),
+            ExtractError::InvalidQuery(err) => write!(f, "failed to deserialize query string: {err}"),
+            ExtractError::InvalidForm(err) => write!(f, "failed to deserialize form: {err}"),
+            #[cfg(feature = "xml")]
+            ExtractError::InvalidXml(err) => write!(f, "failed to deserialize xml: {err}"),
+            #[cfg(feature = "msgpack")]
+            ExtractError::InvalidMsgPack(err) => write!(f, "failed to deserialize msgpack: {err}"),
+            ExtractError::MissingPathParam(name) => write!(f, "missing path parameter {name}"),
+            ExtractError::InvalidPathParam { name, source } => {
+                write!(f, "failed to parse path parameter {name}: {source}")
+            }
+            ExtractError::PathParamCount { expected, found } => {
+                write!(f, "expected {expected} path parameters, but the route has {found}")
+            }
+            ExtractError::UnknownRouteTemplate => {
+                write!(f, "path parameters can't be ordered without the route template")
+            }
+        }
+    }
+}
+
+impl Error for ExtractError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExtractError::InvalidQuery(err) | ExtractError::InvalidForm(err) => Some(err),
+            ExtractError::InvalidJson(err) => Some(err),
+            #[cfg(feature = "xml")]
+            ExtractError::InvalidXml(err) => Some(err),
+            #[cfg(feature = "msgpack")]
+            ExtractError::InvalidMsgPack(err) => Some(err),
+            ExtractError::InvalidPathParam { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<ExtractError> for Response<Body> {
+    fn from(err: ExtractError) -> Self {
+        Response::builder()
+            .status(err.status())
+            .header(CONTENT_TYPE, "text/plain")
+            .body(Body::from(err.to_string()))
+            .expect("unable to build http::Response")
+    }
+}
+
+impl IntoResponse for ExtractError {
+    fn into_response(self) -> ResponseFuture {
+        Response::<Body>::from(self).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Search {
+        q: String,
+        page: Option<u32>,
+    }
+
+    #[test]
+    fn extract_query_from_parameters_or_uri() {
+        let request = http::Request::builder()
+            .uri("https://example.com/search?q=rust%20lambda&page=2")
+            .body(Body::Empty)
+            .unwrap();
+        let Query(search) = request.extract::<Query<Search>>().unwrap();
+        assert_eq!(
+            Search {
+                q: "rust lambda".into(),
+                page: Some(2)
+            },
+            search
+        );
+
+        let request = http::Request::builder()
+            .body(Body::Empty)
+            .unwrap()
+            .with_query_string_parameters(
+                [("q".to_string(), "lambda & rust".to_string())]
+                    .into_iter()
+                    .collect::<std::collections::HashMap<_, _>>(),
+            );
+        let Query(search) = request.extract::<Query<Search>>().unwrap();
+        assert_eq!("lambda & rust", search.q);
+
+        let err = http::Request::new(Body::Empty).extract::<Query<Search>>().unwrap_err();
+        assert_eq!(StatusCode::BAD_REQUEST, err.status());
+    }
+
+    #[test]
+    fn extract_forms() {
+        let form = |content_type: &str, body: &str| {
+            http::Request::builder()
+                .header(CONTENT_TYPE, content_type)
+                .body(Body::from(body))
+                .unwrap()
+                .extract::<Form<Search>>()
+        };
+        let Form(search) = form("application/x-www-form-urlencoded; charset=UTF-8", "q=rust").unwrap();
+        assert_eq!("rust", search.q);
+
+        let err = form("application/json", r#"{"q":"rust"}"#).unwrap_err();
+        assert_eq!(StatusCode::UNSUPPORTED_MEDIA_TYPE, err.status());
+
+        let err = form("application/x-www-form-urlencoded", "q=rust&page=first").unwrap_err();
+        let response = Response::<Body>::from(err);
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        assert!(matches!(response.body(), Body::Text(text) if text.starts_with("failed to deserialize form")));
+    }
+
+    #[test]
+    fn extract_path_params() {
+        let request = http::Request::new(Body::Empty).with_path_parameters(
+            [("id".to_string(), "42".to_string())]
+                .into_iter()
+                .collect::<std::collections::HashMap<_, _>>(),
+        );
+        assert_eq!(42, request.path_param::<u64>("id").unwrap());
+        let Path((id,)) = request.extract::<Path<(u64,)>>().unwrap();
+        assert_eq!(42, id);
+
+        let err = request.path_param::<u64>("user").unwrap_err();
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, err.status());
+        let err = request.path_param::<bool>("id").unwrap_err();
+        assert_eq!(StatusCode::BAD_REQUEST, err.status());
+        assert_eq!(
+            "failed to parse path parameter id: provided string was not `true` or `false`",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "apigw_http")]
+    fn extract_path_params_in_route_order() {
+        let mut context = aws_lambda_events::apigw::ApiGatewayV2httpRequestContext::default();
+        context.route_key = Some("GET /users/{user}/posts/{id}/{proxy+}".into());
+        let request = http::Request::new(Body::Empty)
+            .with_path_parameters(
+                [("id", "7"), ("proxy", "comments/1"), ("user", "ferris")]
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+            )
+            .with_request_context(crate::request::RequestContext::ApiGatewayV2(context));
+
+        let Path((user, id, rest)) = request.extract::<Path<(String, u32, String)>>().unwrap();
+        assert_eq!(("ferris", 7, "comments/1"), (user.as_str(), id, rest.as_str()));
+
+        let err = request.extract::<Path<(String, u32)>>().unwrap_err();
+        assert!(matches!(err, ExtractError::PathParamCount { expected: 2, found: 3 }));
+        let err = request.extract::<Path<(u32, u32, String)>>().unwrap_err();
+        assert!(matches!(err, ExtractError::InvalidPathParam { ref name, .. } if name == "user"));
+
+        let request = http::Request::new(Body::Empty).with_path_parameters(
+            [("id", "7"), ("user", "ferris")]
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<std::collections::HashMap<_, _>>(),
+        );
+        let err = request.extract::<Path<(String, u32)>>().unwrap_err();
+        assert!(matches!(err, ExtractError::UnknownRouteTemplate));
+    }
+
+    #[test]
+    fn extract_json() {
+        let json = |content_type: &str, body: &str| {
+            http::Request::builder()
+                .header(CONTENT_TYPE, content_type)
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let Json(search) = json("application/json", r#"{"q":"rust","page":3}"#)
+            .extract::<Json<Search>>()
+            .unwrap();
+        assert_eq!(
+            Search {
+                q: "rust".into(),
+                page: Some(3)
+            },
+            search
+        );
+        let Json(search) = json("application/vnd.api+json; charset=utf-8", r#"{"q":"rust"}"#)
+            .extract::<Json<Search>>()
+            .unwrap();
+        assert_eq!("rust", search.q);
+
+        let err = json("text/plain", r#"{"q":"rust"}"#)
+            .extract::<Json<Search>>()
+            .unwrap_err();
+        assert_eq!(StatusCode::UNSUPPORTED_MEDIA_TYPE, err.status());
+        assert_eq!("expected request with content type application/json", err.to_string());
+
+        let err = json("application/json", r#"{"q":"#)
+            .extract::<Json<Search>>()
+            .unwrap_err();
+        assert_eq!(StatusCode::BAD_REQUEST, err.status());
+        let err = json("application/json", r#"{"page":3}"#)
+            .extract::<Json<Search>>()
+            .unwrap_err();
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, err.status());
+
+        let err = Json::<Search>::from_request_with_limit(&json("application/json", r#"{"q":"rust"}"#), 8).unwrap_err();
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, err.status());
+    }
+
+    #[tokio::test]
+    async fn json_into_response() {
+        let response = (StatusCode::CREATED, Json(serde_json::json!({"id": 1})))
+            .into_response()
+            .await;
+        assert_eq!(StatusCode::CREATED, response.status());
+        assert_eq!("application/json", response.headers()[CONTENT_TYPE]);
+        assert_eq!(&Body::from(r#"{"id":1}"#), response.body());
+    }
+
+    #[test]
+    fn deserialize_bodies_by_content_type() {
+        let request = |content_type: &str, body: Body| {
+            http::Request::builder()
+                .header(CONTENT_TYPE, content_type)
+                .body(body)
+                .unwrap()
+        };
+        let search = Search {
+            q: "rust".into(),
+            page: Some(2),
+        };
+
+        let json = request("application/vnd.api+json", Body::from(r#"{"q":"rust","page":2}"#));
+        assert_eq!(search, json.body_as::<Search>().unwrap());
+        let form = request(
+            "application/x-www-form-urlencoded; charset=utf-8",
+            Body::from("q=rust&page=2"),
+        );
+        assert_eq!(search, form.body_as::<Search>().unwrap());
+
+        let err = request("text/csv", Body::from("rust,2"))
+            .body_as::<Search>()
+            .unwrap_err();
+        assert_eq!(StatusCode::UNSUPPORTED_MEDIA_TYPE, err.status());
+        let err = http::Request::new(Body::Empty).body_as::<Search>().unwrap_err();
+        assert_eq!(StatusCode::UNSUPPORTED_MEDIA_TYPE, err.status());
+        let err = request("application/json", Body::from("{"))
+            .body_as::<Search>()
+            .unwrap_err();
+        assert_eq!(StatusCode::BAD_REQUEST, err.status());
+
+        #[cfg(feature = "xml")]
+        {
+            let xml = request("text/xml", Body::from("<search><q>rust</q><page>2</page></search>"));
+            assert_eq!(search, xml.body_as::<Search>().unwrap());
+        }
+        #[cfg(feature = "msgpack")]
+        {
+            let bytes = rmp_serde::to_vec_named(&serde_json::json!({"q": "rust", "page": 2})).unwrap();
+            let msgpack = request("application/msgpack", Body::from(bytes));
+            assert_eq!(search, msgpack.body_as::<Search>().unwrap());
+        }
+    }
+}
@@ -1,7 +1,11 @@
 //! Extension methods for `Request` types
 
+#[cfg(any(feature = "apigw_rest", feature = "apigw_http", feature = "apigw_websockets"))]
+mod claims;
 pub mod extensions;
+pub mod extract;
 pub mod request;
 
 pub use extensions::RequestExt;
+pub use extract::RequestExtractExt;
 pub use request::{PayloadError, RequestPayloadExt};
@@ -7,6 +7,8 @@ use serde::{
     Deserialize,
 };
 
+#[cfg(feature = "multipart")]
+use crate::multipart::{Multipart, MultipartError};
 use crate::Body;
 
 /// Request payload deserialization errors
@@ -224,6 +226,17 @@ pub trait RequestPayloadExt {
     fn form_url_encoded<D>(&self) -> Result<Option<D>, FormUrlEncodedPayloadError>
     where
         D: DeserializeOwned;
+
+    /// Iterate over the parts of a `multipart/form-data` payload, like the fields and files of
+    /// an upload form. See [`Multipart`] for an example.
+    ///
+    /// ### Errors
+    ///
+    /// A [`MultipartError`] is returned when the content-type header isn't
+    /// `multipart/form-data` with a boundary, and by the iterator for malformed parts.
+    #[cfg(feature = "multipart")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+    fn multipart(&self) -> Result<Multipart<'_>, MultipartError>;
 }
 
 impl RequestPayloadExt for http::Request<Body> {
@@ -270,6 +283,11 @@ impl RequestPayloadExt for http::Request<Body> {
             .map(Some)
             .map_err(FormUrlEncodedPayloadError::Parsing)
     }
+
+    #[cfg(feature = "multipart")]
+    fn multipart(&self) -> Result<Multipart<'_>, MultipartError> {
+        Multipart::from_request(self)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,567 @@
+//! GraphQL endpoints backed by a schema.
+//!
+//! [`GraphQL`](crate::graphql::GraphQL) turns a
+//! [`GraphQLExecutor`](crate::graphql::GraphQLExecutor), like a schema of `async-graphql` or
+//! `juniper`, into a handler that answers `GET` and `POST` requests of the
+//! [GraphQL over HTTP](https://graphql.github.io/graphql-over-http/draft/) protocol, with
+//! [automatic persisted queries](https://www.apollographql.com/docs/apollo-server/performance/apq)
+//! and an optional GraphiQL playground.
+//!
+//! ```rust,ignore
+//! use async_graphql::{EmptyMutation, EmptySubscription, Schema};
+//! use lambda_http::{
+//!     graphql::{GraphQL, GraphQLExecutor, GraphQLRequest},
+//!     Error,
+//! };
+//!
+//! struct Executor(Schema<Query, EmptyMutation, EmptySubscription>);
+//!
+//! impl GraphQLExecutor for Executor {
+//!     async fn execute(&self, request: GraphQLRequest) -> serde_json::Value {
+//!         let mut query = async_graphql::Request::new(request.query);
+//!         query.operation_name = request.operation_name;
+//!         query.variables = async_graphql::Variables::from_json(request.variables);
+//!         serde_json::to_value(self.0.execute(query).await).unwrap()
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Error> {
+//!     let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+//!     lambda_http::run(GraphQL::new(Executor(schema)).playground(true)).await
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    fmt::Write,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use http::{
+    header::{ACCEPT, ALLOW, CONTENT_TYPE},
+    HeaderValue, Method, StatusCode,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{tower::Service, Body, Request, RequestExt, Response};
+
+/// GraphQL request, from the query string of `GET` requests or the body of `POST` requests.
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQLRequest {
+    /// Document of the query, which is resolved from the cache of persisted queries when the
+    /// client only sends its hash.
+    #[serde(default)]
+    pub query: String,
+    /// Name of the operation of the document to execute.
+    #[serde(default)]
+    pub operation_name: Option<String>,
+    /// Values of the variables of the operation.
+    #[serde(default)]
+    pub variables: Value,
+    /// Extensions of the request, like `persistedQuery`.
+    #[serde(default)]
+    pub extensions: Value,
+    /// Method of the HTTP request. [`GraphQL`] rejects `GET` requests of operations other than
+    /// queries before executing them, so that links can't change data.
+    #[serde(skip)]
+    pub method: Method,
+}
+
+/// Schemas that execute GraphQL requests, like the schemas of `async-graphql` or `juniper`
+/// wrapped in a type of the function.
+///
+/// Closures that take a [`GraphQLRequest`] and return a future of the response implement it.
+pub trait GraphQLExecutor: Send + Sync + 'static {
+    /// Execute a request, and return the GraphQL response, with its `data` and `errors`.
+    fn execute(&self, request: GraphQLRequest) -> impl Future<Output = Value> + Send;
+}
+
+impl<F, Fut> GraphQLExecutor for F
+where
+    F: Fn(GraphQLRequest) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Value> + Send,
+{
+    fn execute(&self, request: GraphQLRequest) -> impl Future<Output = Value> + Send {
+        self(request)
+    }
+}
+
+/// Handler of a GraphQL endpoint, that executes the requests with a [`GraphQLExecutor`].
+///
+/// Requests are read from the `query`, `operationName`, `variables`, and `extensions`
+/// parameters of `GET` requests, and from `application/json` or `application/graphql` bodies
+/// of `POST` requests. Queries sent with their SHA-256 hash in the `persistedQuery` extension
+/// are kept while the execution environment lives, so clients can send only the hash later.
+///
+/// `GET` requests of mutations and subscriptions are answered with
+/// `405 Method Not Allowed`, as the GraphQL over HTTP protocol requires.
+pub struct GraphQL<E> {
+    executor: Arc<E>,
+    persisted_queries: Arc<Mutex<HashMap<String, String>>>,
+    max_persisted_queries: usize,
+    playground: bool,
+}
+
+impl<E: GraphQLExecutor> GraphQL<E> {
+    /// Create a handler that executes requests with `executor`.
+    pub fn new(executor: E) -> Self {
+        Self {
+            executor: Arc::new(executor),
+            persisted_queries: Arc::default(),
+            max_persisted_queries: 1000,
+            playground: false,
+        }
+    }
+
+    /// Serve a GraphiQL playground to browsers that send `GET` requests without a query.
+    /// Disabled by default.
+    pub fn playground(mut self, enable: bool) -> Self {
+        self.playground = enable;
+        self
+    }
+
+    /// Set the number of persisted queries to keep, 1000 by default. Queries over the limit
+    /// are executed but not kept, and `0` disables persisted queries.
+    pub fn max_persisted_queries(mut self, max: usize) -> Self {
+        self.max_persisted_queries = max;
+        self
+    }
+
+    async fn respond(&self, request: Request) -> Response<Body> {
+        let graphql = match *request.method() {
+            Method::GET if self.playground && wants_html(&request) && !has_query(&request) => {
+                return playground(request.uri().path());
+            }
+            Method::GET => from_query(&request),
+            Method::POST => from_body(&request),
+            _ => {
+                let mut response = error(StatusCode::METHOD_NOT_ALLOWED, "GraphQL requests must be GET or POST");
+                response
+                    .headers_mut()
+                    .insert(ALLOW, HeaderValue::from_static("GET, POST"));
+                return response;
+            }
+        };
+        let mut graphql = match graphql {
+            Ok(graphql) => graphql,
+            Err(message) => return error(StatusCode::BAD_REQUEST, &message),
+        };
+        graphql.method = request.method().clone();
+
+        if let Err(err) = self.resolve_persisted_query(&mut graphql) {
+            return err.into_response();
+        }
+        if graphql.query.is_empty() {
+            return error(StatusCode::BAD_REQUEST, "missing query");
+        }
+        if graphql.method == Method::GET {
+            let kind = operation_kind(&graphql.query, graphql.operation_name.as_deref());
+            if kind.is_some_and(|kind| kind != "query") {
+                let mut response = error(StatusCode::METHOD_NOT_ALLOWED, "GET requests can only execute queries");
+                response.headers_mut().insert(ALLOW, HeaderValue::from_static("POST"));
+                return response;
+            }
+        }
+        json_response(StatusCode::OK, &self.executor.execute(graphql).await)
+    }
+
+    /// Fill the query of requests that only send the hash of a persisted query, and persist the
+    /// queries of requests that send both.
+    fn resolve_persisted_query(&self, request: &mut GraphQLRequest) -> Result<(), PersistedQueryError> {
+        let Some(hash) = request.extensions["persistedQuery"]["sha256Hash"].as_str() else {
+            return Ok(());
+        };
+        if self.max_persisted_queries == 0 {
+            return Err(PersistedQueryError::NotSupported);
+        }
+        let mut queries = self.persisted_queries.lock().expect("persisted queries lock poisoned");
+        if request.query.is_empty() {
+            return match queries.get(hash) {
+                Some(query) => {
+                    request.query = query.clone();
+                    Ok(())
+                }
+                None => Err(PersistedQueryError::NotFound),
+            };
+        }
+        if sha256_hex(&request.query) != hash {
+            return Err(PersistedQueryError::HashMismatch);
+        }
+        if queries.len() < self.max_persisted_queries {
+            queries.insert(hash.to_string(), request.query.clone());
+        }
+        Ok(())
+    }
+}
+
+impl<E> Clone for GraphQL<E> {
+    fn clone(&self) -> Self {
+        Self {
+            executor: self.executor.clone(),
+            persisted_queries: self.persisted_queries.clone(),
+            max_persisted_queries: self.max_persisted_queries,
+            playground: self.playground,
+        }
+    }
+}
+
+impl<E: GraphQLExecutor> Service<Request> for GraphQL<E> {
+    type Response = Response<Body>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response<Body>, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let graphql = self.clone();
+        Box::pin(async move { Ok(graphql.respond(request).await) })
+    }
+}
+
+fn from_query(request: &Request) -> Result<GraphQLRequest, String> {
+    let parameters = match request.query_string_parameters_ref() {
+        Some(parameters) => parameters.clone(),
+        None => request.uri().query().unwrap_or_default().parse().unwrap_or_default(),
+    };
+    let json = |name: &str| match parameters.first(name) {
+        Some(value) => serde_json::from_str(value).map_err(|err| format!("invalid {name}: {err}")),
+        None => Ok(Value::Null),
+    };
+    Ok(GraphQLRequest {
+        query: parameters.first("query").unwrap_or_default().to_string(),
+        operation_name: parameters.first("operationName").map(str::to_string),
+        variables: json("variables")?,
+        extensions: json("extensions")?,
+        method: Method::GET,
+    })
+}
+
+fn from_body(request: &Request) -> Result<GraphQLRequest, String> {
+    let content_type = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .unwrap_or_default();
+    let body = request.body().as_ref();
+    if content_type.starts_with("application/graphql") && !content_type.starts_with("application/graphql-response") {
+        let query = std::str::from_utf8(body).map_err(|err| format!("invalid query: {err}"))?;
+        return Ok(GraphQLRequest {
+            query: query.to_string(),
+            ..Default::default()
+        });
+    }
+    serde_json::from_slice(body).map_err(|err| format!("invalid request: {err}"))
+}
+
+/// Find the kind of the operation of a document that a request executes, `query`, `mutation`,
+/// or `subscription`, by scanning its top-level definitions. Documents whose operation can't
+/// be found are left to the executor, which rejects them.
+fn operation_kind<'a>(document: &'a str, operation_name: Option<&str>) -> Option<&'a str> {
+    let bytes = document.as_bytes();
+    let mut operations: Vec<(&str, Option<&str>)> = Vec::new();
+    let mut depth = 0usize;
+    let mut expecting_definition = true;
+    let mut naming = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'#' => {
+                while i < bytes.len() && bytes[i] != b'\n' && bytes[i] != b'\r' {
+                    i += 1;
+                }
+            }
+            b'"' if document[i..].starts_with("\"\"\"") => {
+                let mut end = i + 3;
+                i = loop {
+                    match document[end..].find("\"\"\"") {
+                        Some(offset) if bytes[end + offset - 1] == b'\\' => end += offset + 3,
+                        Some(offset) => break end + offset + 3,
+                        None => break bytes.len(),
+                    }
+                };
+                naming = false;
+            }
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' && bytes[i] != b'\n' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i += 1;
+                naming = false;
+            }
+            byte if byte.is_ascii_alphabetic() || byte == b'_' => {
+                let start = i;
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                let name = &document[start..i];
+                if depth > 0 {
+                    continue;
+                }
+                if naming {
+                    if let Some(operation) = operations.last_mut() {
+                        operation.1 = Some(name);
+                    }
+                    naming = false;
+                } else if expecting_definition {
+                    expecting_definition = false;
+                    if matches!(name, "query" | "mutation" | "subscription") {
+                        operations.push((name, None));
+                        naming = true;
+                    }
+                }
+            }
+            byte => {
+                match byte {
+                    b'{' if depth == 0 && expecting_definition => {
+                        operations.push(("query", None));
+                        expecting_definition = false;
+                        depth += 1;
+                    }
+                    b'{' | b'(' | b'[' => depth += 1,
+                    b'}' | b')' | b']' => {
+                        depth = depth.saturating_sub(1);
+                        expecting_definition |= byte == b'}' && depth == 0;
+                    }
+                    _ => {}
+                }
+                naming &= byte.is_ascii_whitespace() || byte == b',';
+                i += 1;
+            }
+        }
+    }
+    match operation_name {
+        Some(operation_name) => operations
+            .iter()
+            .find(|(_, name)| *name == Some(operation_name))
+            .map(|(kind, _)| *kind),
+        None if operations.len() == 1 => Some(operations[0].0),
+        None => None,
+    }
+}
+
+fn has_query(request: &Request) -> bool {
+    match request.query_string_parameters_ref() {
+        Some(parameters) => parameters.first("query").is_some(),
+        None => request
+            .uri()
+            .query()
+            .is_some_and(|query| query.split('&').any(|pair| pair.starts_with("query="))),
+    }
+}
+
+fn wants_html(request: &Request) -> bool {
+    request
+        .headers()
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"))
+}
+
+fn sha256_hex(query: &str) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, query.as_bytes());
+    digest.as_ref().iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+fn json_response(status: StatusCode, value: &Value) -> Response<Body> {
+    let mut response = Response::new(Body::from(value.to_string()));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+fn error(status: StatusCode, message: &str) -> Response<Body> {
+    json_response(status, &json!({ "errors": [{ "message": message }] }))
+}
+
+enum PersistedQueryError {
+    NotSupported,
+    NotFound,
+    HashMismatch,
+}
+
+impl PersistedQueryError {
+    fn into_response(self) -> Response<Body> {
+        let (message, code) = match self {
+            PersistedQueryError::NotSupported => ("PersistedQueryNotSupported", "PERSISTED_QUERY_NOT_SUPPORTED"),
+            PersistedQueryError::NotFound => ("PersistedQueryNotFound", "PERSISTED_QUERY_NOT_FOUND"),
+            PersistedQueryError::HashMismatch => {
+                return error(StatusCode::BAD_REQUEST, "provided sha does not match query");
+            }
+        };
+        // Errors of persisted queries are GraphQL errors, which clients handle by sending the
+        // query.
+        json_response(
+            StatusCode::OK,
+            &json!({ "errors": [{ "message": message, "extensions": { "code": code } }] }),
+        )
+    }
+}
+
+fn playground(endpoint: &str) -> Response<Body> {
+    let endpoint = serde_json::to_string(endpoint).unwrap_or_else(|_| "\"/\"".to_string());
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>GraphiQL</title>
+<link rel="stylesheet" href="https://unpkg.com/graphiql@3/graphiql.min.css">
+</head>
+<body style="margin: 0">
+<div id="graphiql" style="height: 100vh"></div>
+<script src="https://unpkg.com/react@18/umd/react.production.min.js"></script>
+<script src="https://unpkg.com/react-dom@18/umd/react-dom.production.min.js"></script>
+<script src="https://unpkg.com/graphiql@3/graphiql.min.js"></script>
+<script>
+const fetcher = GraphiQL.createFetcher({{ url: {endpoint} }});
+ReactDOM.createRoot(document.getElementById("graphiql")).render(React.createElement(GraphiQL, {{ fetcher }}));
+</script>
+</body>
+</html>
+"#
+    );
+    let mut response = Response::new(Body::from(html));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("text/html; charset=utf-8"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tower::ServiceExt;
+
+    fn graphql() -> GraphQL<impl GraphQLExecutor> {
+        GraphQL::new(|request: GraphQLRequest| async move {
+            json!({ "data": { "query": request.query, "method": request.method.as_str() } })
+        })
+    }
+
+    async fn call(graphql: &GraphQL<impl GraphQLExecutor>, request: http::request::Builder, body: &str) -> Value {
+        let request = request.body(Body::from(body)).unwrap();
+        let response = graphql.clone().oneshot(request).await.unwrap();
+        serde_json::from_slice(response.body().as_ref()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn execute_get_and_post_requests() {
+        let graphql = graphql();
+        let get = http::Request::builder().uri("/graphql?query=%7Bme%7D");
+        assert_eq!(
+            json!({ "data": { "query": "{me}", "method": "GET" } }),
+            call(&graphql, get, "").await
+        );
+
+        let post = http::Request::builder()
+            .method(Method::POST)
+            .header(CONTENT_TYPE, "application/json");
+        assert_eq!(
+            json!({ "data": { "query": "{me}", "method": "POST" } }),
+            call(&graphql, post, r#"{"query":"{me}"}"#).await
+        );
+
+        let post = http::Request::builder()
+            .method(Method::POST)
+            .header(CONTENT_TYPE, "application/graphql");
+        assert_eq!("{me}", call(&graphql, post, "{me}").await["data"]["query"]);
+
+        let request = http::Request::builder().uri("/graphql").header(ACCEPT, "text/html");
+        let response = graphql
+            .clone()
+            .playground(true)
+            .oneshot(request.body(Body::Empty).unwrap())
+            .await
+            .unwrap();
+        assert_eq!("text/html; charset=utf-8", response.headers()[CONTENT_TYPE]);
+    }
+
+    #[tokio::test]
+    async fn reject_mutations_in_get_requests() {
+        let graphql = graphql();
+        let get = |query: &str| {
+            let query = query.replace(' ', "%20").replace('{', "%7B").replace('}', "%7D");
+            http::Request::builder()
+                .uri(format!("/graphql?query={query}"))
+                .body(Body::Empty)
+                .unwrap()
+        };
+        let response = graphql.clone().oneshot(get("mutation { delete }")).await.unwrap();
+        assert_eq!(StatusCode::METHOD_NOT_ALLOWED, response.status());
+        assert_eq!("POST", response.headers()[ALLOW]);
+
+        let request = get("query Me { me } mutation Delete { delete }&operationName=Delete");
+        let response = graphql.clone().oneshot(request).await.unwrap();
+        assert_eq!(StatusCode::METHOD_NOT_ALLOWED, response.status());
+
+        let request = get("query Me { me } mutation Delete { delete }&operationName=Me");
+        let response = graphql.clone().oneshot(request).await.unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+
+        let post = http::Request::builder()
+            .method(Method::POST)
+            .header(CONTENT_TYPE, "application/graphql");
+        assert_eq!(
+            "POST",
+            call(&graphql, post, "mutation { delete }").await["data"]["method"]
+        );
+    }
+
+    #[test]
+    fn find_operation_kinds() {
+        assert_eq!(Some("query"), operation_kind("{ me }", None));
+        assert_eq!(Some("mutation"), operation_kind("# query\nmutation { delete }", None));
+        assert_eq!(
+            Some("mutation"),
+            operation_kind(
+                r#"mutation M($a: In = { b: "}" }) @live { delete(c: """{\"""}""") }"#,
+                None
+            )
+        );
+        assert_eq!(
+            Some("subscription"),
+            operation_kind("fragment F on User { id } subscription S { ...F }", Some("S"))
+        );
+        assert_eq!(None, operation_kind("query A { a } query B { b }", None));
+        assert_eq!(None, operation_kind("query A { a }", Some("B")));
+    }
+
+    #[tokio::test]
+    async fn persist_queries() {
+        let graphql = graphql();
+        let hash = sha256_hex("{me}");
+        let post = || {
+            http::Request::builder()
+                .method(Method::POST)
+                .header(CONTENT_TYPE, "application/json")
+        };
+        let extensions = format!(r#""extensions":{{"persistedQuery":{{"version":1,"sha256Hash":"{hash}"}}}}"#);
+
+        let response = call(&graphql, post(), &format!("{{{extensions}}}")).await;
+        assert_eq!("PERSISTED_QUERY_NOT_FOUND", response["errors"][0]["extensions"]["code"]);
+
+        let response = call(&graphql, post(), &format!(r#"{{"query":"{{me}}",{extensions}}}"#)).await;
+        assert_eq!("{me}", response["data"]["query"]);
+        let response = call(&graphql, post(), &format!("{{{extensions}}}")).await;
+        assert_eq!("{me}", response["data"]["query"]);
+
+        let response = call(&graphql, post(), &format!(r#"{{"query":"{{you}}",{extensions}}}"#)).await;
+        assert!(response["errors"][0]["message"].as_str().unwrap().contains("sha"));
+    }
+}
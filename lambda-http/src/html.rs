@@ -0,0 +1,260 @@
+//! HTML responses, rendered from strings or templates.
+//!
+//! [`Html`] sets the `Content-Type` of responses to `text/html; charset=utf-8`. Pages can be
+//! rendered at once, or, with the `html-stream` feature, streamed to clients while they are
+//! rendered, with [`run_with_streaming_response`](crate::run_with_streaming_response). The
+//! `askama` and `minijinja` features render the templates of these engines.
+//!
+//! ```rust,no_run
+//! use lambda_http::{html::Html, service_fn, Error, Request};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Error> {
+//!     lambda_http::run(service_fn(hello)).await
+//! }
+//!
+//! async fn hello(_request: Request) -> Result<Html<&'static str>, Error> {
+//!     Ok(Html("<h1>Hello, world!</h1>"))
+//! }
+//! ```
+
+#[cfg(feature = "html-stream")]
+use std::io;
+
+#[cfg(feature = "html-stream")]
+use bytes::Bytes;
+use http::{header::CONTENT_TYPE, HeaderValue, StatusCode};
+#[cfg(feature = "html-stream")]
+use tokio::sync::mpsc;
+#[cfg(feature = "html-stream")]
+use tokio_stream::wrappers::ReceiverStream;
+
+#[cfg(feature = "html-stream")]
+use crate::Error;
+use crate::{response::ResponseFuture, Body, IntoResponse, Response, StreamBody};
+
+const TEXT_HTML: &str = "text/html; charset=utf-8";
+
+/// HTML response, with a `text/html; charset=utf-8` content type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Html<T>(pub T);
+
+#[cfg(feature = "html-stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "html-stream")))]
+impl Html<StreamBody> {
+    /// Largest chunk of a streamed page that is buffered before it's sent to the client.
+    pub const CHUNK_SIZE: usize = 4096;
+
+    /// Stream a page to the client while `render` writes it, in chunks of up to
+    /// [`CHUNK_SIZE`](Self::CHUNK_SIZE) bytes.
+    ///
+    /// Template engines render pages synchronously, so `render` runs on a blocking thread of the
+    /// Tokio runtime, and waits for the client to read the page. An error of `render` aborts the
+    /// response.
+    ///
+    /// ```rust,no_run
+    /// use lambda_http::{html::Html, service_fn, Error, Request, Response, StreamBody};
+    /// use std::io::Write;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     lambda_http::run_with_streaming_response(service_fn(list)).await
+    /// }
+    ///
+    /// async fn list(_request: Request) -> Result<Response<StreamBody>, Error> {
+    ///     let page = Html::stream(|writer| {
+    ///         writer.write_all(b"<ul>")?;
+    ///         for item in 0..10_000 {
+    ///             write!(writer, "<li>{item}</li>")?;
+    ///         }
+    ///         writer.write_all(b"</ul>")
+    ///     });
+    ///     Ok(page.into())
+    /// }
+    /// ```
+    pub fn stream<F, E>(render: F) -> Self
+    where
+        F: FnOnce(&mut dyn io::Write) -> Result<(), E> + Send + 'static,
+        E: Into<Error>,
+    {
+        let (sender, receiver) = mpsc::channel(16);
+        tokio::task::spawn_blocking(move || {
+            let mut writer = ChunkWriter {
+                sender,
+                buffer: Vec::with_capacity(Self::CHUNK_SIZE),
+            };
+            let result = render(&mut writer)
+                .map_err(Into::into)
+                .and_then(|()| io::Write::flush(&mut writer).map_err(Into::into));
+            if let Err(err) = result {
+                // The client may be gone already, and then no one reads the error.
+                let _ = writer.sender.blocking_send(Err(err));
+            }
+        });
+        Html(StreamBody::from_stream(ReceiverStream::new(receiver)))
+    }
+}
+
+/// Writer that sends the chunks of a streamed page to its body.
+#[cfg(feature = "html-stream")]
+struct ChunkWriter {
+    sender: mpsc::Sender<Result<Bytes, Error>>,
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "html-stream")]
+impl io::Write for ChunkWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(data);
+        if self.buffer.len() >= Html::CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = Bytes::from(std::mem::replace(
+            &mut self.buffer,
+            Vec::with_capacity(Html::CHUNK_SIZE),
+        ));
+        self.sender
+            .blocking_send(Ok(chunk))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the response was dropped"))
+    }
+}
+
+#[cfg(feature = "askama")]
+#[cfg_attr(docsrs, doc(cfg(feature = "askama")))]
+impl Html<String> {
+    /// Render an askama template.
+    ///
+    /// ```rust,ignore
+    /// use askama::Template;
+    /// use lambda_http::{html::Html, Error, Request};
+    ///
+    /// #[derive(Template)]
+    /// #[template(path = "hello.html")]
+    /// struct Hello<'a> {
+    ///     name: &'a str,
+    /// }
+    ///
+    /// async fn hello(_request: Request) -> Result<Html<String>, Error> {
+    ///     Ok(Html::render(&Hello { name: "world" })?)
+    /// }
+    /// ```
+    pub fn render<T: askama::Template + ?Sized>(template: &T) -> Result<Self, askama::Error> {
+        template.render().map(Html)
+    }
+}
+
+#[cfg(feature = "minijinja")]
+#[cfg_attr(docsrs, doc(cfg(feature = "minijinja")))]
+impl Html<String> {
+    /// Render a minijinja template with a context.
+    ///
+    /// ```rust
+    /// use lambda_http::html::Html;
+    /// use minijinja::{context, Environment};
+    ///
+    /// let mut env = Environment::new();
+    /// env.add_template("hello", "<h1>Hello, {{ name }}!</h1>").unwrap();
+    /// let page = Html::render_template(&env.get_template("hello").unwrap(), context! { name => "world" });
+    /// assert_eq!(Html("<h1>Hello, world!</h1>".to_string()), page.unwrap());
+    /// ```
+    pub fn render_template<S: serde::Serialize>(
+        template: &minijinja::Template<'_, '_>,
+        context: S,
+    ) -> Result<Self, minijinja::Error> {
+        template.render(context).map(Html)
+    }
+}
+
+impl<T: Into<Body>> From<Html<T>> for Response<Body> {
+    fn from(Html(body): Html<T>) -> Self {
+        let mut response = Response::new(body.into());
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(TEXT_HTML));
+        response
+    }
+}
+
+impl From<Html<StreamBody>> for Response<StreamBody> {
+    fn from(Html(body): Html<StreamBody>) -> Self {
+        let mut response = Response::new(body);
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(TEXT_HTML));
+        response
+    }
+}
+
+impl<T: Into<Body>> IntoResponse for Html<T> {
+    fn into_response(self) -> ResponseFuture {
+        Response::<Body>::from(self).into_response()
+    }
+}
+
+impl<T: Into<Body>> IntoResponse for (StatusCode, Html<T>) {
+    fn into_response(self) -> ResponseFuture {
+        let (status, html) = self;
+        let mut response = Response::<Body>::from(html);
+        *response.status_mut() = status;
+        response.into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn html_responses() {
+        let response = (StatusCode::NOT_FOUND, Html("<h1>Not Found</h1>"))
+            .into_response()
+            .await;
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        assert_eq!(TEXT_HTML, response.headers()[CONTENT_TYPE]);
+        assert_eq!(&Body::from("<h1>Not Found</h1>"), response.body());
+    }
+
+    #[cfg(feature = "html-stream")]
+    #[tokio::test]
+    async fn stream_rendered_pages() {
+        use http_body_util::BodyExt;
+
+        let response = Response::from(Html::stream(|writer| {
+            for item in 0..1000 {
+                write!(writer, "<li>{item}</li>")?;
+            }
+            Ok::<_, io::Error>(())
+        }));
+        assert_eq!(TEXT_HTML, response.headers()[CONTENT_TYPE]);
+        let page = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(page.starts_with(b"<li>0</li><li>1</li>"));
+        assert!(page.ends_with(b"<li>999</li>"));
+
+        let page = Html::stream(|writer| {
+            writer.write_all(b"<h1>")?;
+            Err::<(), _>(io::Error::other("missing variable"))
+        });
+        let err = page.0.collect().await.unwrap_err();
+        assert_eq!("missing variable", err.to_string());
+    }
+
+    #[cfg(feature = "askama")]
+    #[test]
+    fn render_askama_templates() {
+        #[derive(askama::Template)]
+        #[template(source = "<h1>Hello, {{ name }}!</h1>", ext = "html")]
+        struct Hello<'a> {
+            name: &'a str,
+        }
+
+        let page = Html::render(&Hello { name: "<world>" }).unwrap();
+        assert_eq!("<h1>Hello, &#60;world&#62;!</h1>", page.0);
+    }
+}
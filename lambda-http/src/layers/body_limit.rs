@@ -0,0 +1,125 @@
+use std::task::{self, Poll};
+
+use crate::{
+    ext::extract::ExtractError,
+    layers::RejectionFuture,
+    tower::{Layer, Service},
+    Body, IntoResponse, Request, Response,
+};
+
+/// Size in bytes of the body of a request, after it's decoded from base64, added to the
+/// extensions of requests by [`BodyLimitLayer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BodySize(pub usize);
+
+/// Layer that rejects requests with bodies larger than a limit with a `413 Payload Too Large`
+/// response, before the handler deserializes them.
+///
+/// The limit applies to the decoded body of requests, so bodies that API Gateway, ALB, or
+/// Function URLs encode in base64 are measured by their size in bytes rather than the size of
+/// their encoding. Requests that pass the limit get their size in a [`BodySize`] extension.
+///
+/// ```rust,no_run
+/// use lambda_http::{layers::BodyLimitLayer, service_fn, tower::ServiceBuilder, Error, Request};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let handler = ServiceBuilder::new()
+///         .layer(BodyLimitLayer::new(64 * 1024))
+///         .service(service_fn(|_request: Request| async { Ok::<_, Error>("hello") }));
+///     lambda_http::run(handler).await
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLimitLayer {
+    limit: usize,
+}
+
+impl BodyLimitLayer {
+    /// Create a layer that rejects bodies larger than `limit` bytes.
+    pub fn new(limit: usize) -> Self {
+        Self { limit }
+    }
+}
+
+impl<S> Layer<S> for BodyLimitLayer {
+    type Service = BodyLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BodyLimitService {
+            inner,
+            limit: self.limit,
+        }
+    }
+}
+
+/// Service that rejects requests with bodies larger than a limit, created by
+/// [`BodyLimitLayer`].
+#[derive(Debug, Clone)]
+pub struct BodyLimitService<S> {
+    inner: S,
+    limit: usize,
+}
+
+impl<S> Service<Request> for BodyLimitService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BodyLimitFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request) -> Self::Future {
+        let size = request.body().as_ref().len();
+        if size > self.limit {
+            let response = ExtractError::PayloadTooLarge { limit: self.limit }.into();
+            return BodyLimitFuture::rejected(response);
+        }
+
+        request.extensions_mut().insert(BodySize(size));
+        BodyLimitFuture::inner(self.inner.call(request))
+    }
+}
+
+/// Future returned by [`BodyLimitService`].
+pub type BodyLimitFuture<F> = RejectionFuture<F>;
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{service_fn, tower::ServiceExt, Error};
+
+    async fn call(body: Body) -> Response<Body> {
+        let service = BodyLimitLayer::new(8).layer(service_fn(|request: Request| async move {
+            let BodySize(size) = *request.extensions().get::<BodySize>().unwrap();
+            Ok::<_, Error>(format!("{size} bytes"))
+        }));
+        service
+            .oneshot(http::Request::builder().body(body).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn accept_bodies_within_the_limit() {
+        let response = call(Body::from(vec![0u8; 8])).await;
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(&Body::from("8 bytes"), response.body());
+
+        let response = call(Body::Empty).await;
+        assert_eq!(&Body::from("0 bytes"), response.body());
+    }
+
+    #[tokio::test]
+    async fn reject_bodies_over_the_limit() {
+        let response = call(Body::from("123456789")).await;
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, response.status());
+    }
+}
@@ -0,0 +1,350 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{self, Poll},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use http::{
+    header::{CACHE_CONTROL, EXPIRES, VARY},
+    HeaderMap, HeaderName, HeaderValue,
+};
+
+use crate::{
+    response::ResponseFuture,
+    tower::{Layer, Service},
+    Body, IntoResponse, Request, Response,
+};
+
+/// Caching of responses by browsers and CDNs like CloudFront, with consistent `Cache-Control`,
+/// `Expires`, and `Vary` headers.
+///
+/// Policies are applied to responses by a [`CacheLayer`], by the
+/// [`Router`](crate::router::Router) for the routes they are [set on](crate::router::Router::cache),
+/// or by handlers with [`CachePolicy::apply`]. They don't replace the `Cache-Control` header of
+/// responses that already have one. Policies that let caches store responses only apply to
+/// successful and redirect responses, so errors aren't cached for as long, while
+/// [`CachePolicy::no_store`] applies to all responses.
+///
+/// ```rust
+/// use lambda_http::{http::header::ACCEPT_LANGUAGE, layers::CachePolicy};
+/// use std::time::Duration;
+///
+/// // Fingerprinted assets never change.
+/// let assets = CachePolicy::immutable();
+/// // Pages are fresh for a minute, and served stale for an hour while they're revalidated.
+/// let pages = CachePolicy::swr(Duration::from_secs(60), Duration::from_secs(3600)).vary(ACCEPT_LANGUAGE);
+/// // Personal data is never stored.
+/// let account = CachePolicy::no_store();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachePolicy {
+    storage: Storage,
+    private: bool,
+    vary: Vec<HeaderName>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Storage {
+    NoStore,
+    MaxAge {
+        max_age: Duration,
+        stale_while_revalidate: Option<Duration>,
+        immutable: bool,
+    },
+}
+
+impl CachePolicy {
+    /// Responses that never change, like fingerprinted assets, cached for a year.
+    pub fn immutable() -> Self {
+        Self::new(Storage::MaxAge {
+            max_age: Duration::from_secs(365 * 24 * 60 * 60),
+            stale_while_revalidate: None,
+            immutable: true,
+        })
+    }
+
+    /// Responses that caches never store, like personal data.
+    pub fn no_store() -> Self {
+        Self::new(Storage::NoStore)
+    }
+
+    /// Responses that are fresh for `max_age`.
+    pub fn max_age(max_age: Duration) -> Self {
+        Self::new(Storage::MaxAge {
+            max_age,
+            stale_while_revalidate: None,
+            immutable: false,
+        })
+    }
+
+    /// Responses that are fresh for `ttl`, then served stale for up to `stale` while caches
+    /// revalidate them in the background.
+    pub fn swr(ttl: Duration, stale: Duration) -> Self {
+        Self::new(Storage::MaxAge {
+            max_age: ttl,
+            stale_while_revalidate: Some(stale),
+            immutable: false,
+        })
+    }
+
+    fn new(storage: Storage) -> Self {
+        Self {
+            storage,
+            private: false,
+            vary: Vec::new(),
+        }
+    }
+
+    /// Only let the browser of the client cache responses, not shared caches like CloudFront.
+    pub fn private(mut self) -> Self {
+        self.private = true;
+        self
+    }
+
+    /// Add a request header that selects between representations of responses, like
+    /// `Accept-Language`, so caches store one response for each of its values.
+    pub fn vary(mut self, header: HeaderName) -> Self {
+        if !self.vary.contains(&header) {
+            self.vary.push(header);
+        }
+        self
+    }
+
+    /// Value of the `Cache-Control` header of the policy.
+    pub fn cache_control(&self) -> HeaderValue {
+        let visibility = if self.private { "private" } else { "public" };
+        let value = match self.storage {
+            Storage::NoStore => "no-store".to_string(),
+            Storage::MaxAge {
+                max_age,
+                stale_while_revalidate,
+                immutable,
+            } => {
+                let mut value = format!("{visibility}, max-age={}", max_age.as_secs());
+                if let Some(stale) = stale_while_revalidate {
+                    value.push_str(&format!(", stale-while-revalidate={}", stale.as_secs()));
+                }
+                if immutable {
+                    value.push_str(", immutable");
+                }
+                value
+            }
+        };
+        HeaderValue::try_from(value).expect("the Cache-Control header is valid")
+    }
+
+    /// Set the caching headers of a response.
+    pub fn apply<B>(&self, response: &mut Response<B>) {
+        let status = response.status();
+        let cacheable = status.is_success() || status.is_redirection();
+        if self.storage != Storage::NoStore && !cacheable {
+            return;
+        }
+        let headers = response.headers_mut();
+        if !headers.contains_key(CACHE_CONTROL) {
+            headers.insert(CACHE_CONTROL, self.cache_control());
+            headers.insert(EXPIRES, self.expires());
+        }
+        self.add_vary(headers);
+    }
+
+    /// `Expires` header for HTTP/1.0 caches, which ignore `Cache-Control`.
+    fn expires(&self) -> HeaderValue {
+        let expires = match self.storage {
+            Storage::NoStore => UNIX_EPOCH,
+            Storage::MaxAge { max_age, .. } => SystemTime::now() + max_age,
+        };
+        HeaderValue::try_from(httpdate::fmt_http_date(expires)).expect("HTTP dates are valid headers")
+    }
+
+    fn add_vary(&self, headers: &mut HeaderMap) {
+        let existing: Vec<String> = headers
+            .get_all(VARY)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|name| name.trim().to_ascii_lowercase())
+            .collect();
+        // Responses that vary on everything can't vary on more.
+        if existing.iter().any(|name| name == "*") {
+            return;
+        }
+        for header in &self.vary {
+            if !existing.iter().any(|name| name == header.as_str()) {
+                headers.append(VARY, HeaderValue::from_name(header.clone()));
+            }
+        }
+    }
+}
+
+/// Layer that applies a [`CachePolicy`] to the responses of a handler.
+///
+/// ```rust,no_run
+/// use lambda_http::{
+///     layers::{CacheLayer, CachePolicy},
+///     service_fn,
+///     tower::ServiceBuilder,
+///     Error, Request,
+/// };
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let handler = ServiceBuilder::new()
+///         .layer(CacheLayer::new(CachePolicy::immutable()))
+///         .service(service_fn(|_request: Request| async { Ok::<_, Error>("hello") }));
+///     lambda_http::run(handler).await
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CacheLayer {
+    policy: CachePolicy,
+}
+
+impl CacheLayer {
+    /// Create a layer that applies `policy`.
+    pub fn new(policy: CachePolicy) -> Self {
+        Self { policy }
+    }
+}
+
+impl<S> Layer<S> for CacheLayer {
+    type Service = CacheService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CacheService {
+            inner,
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+/// Service that applies a [`CachePolicy`] to the responses of the inner service, created by
+/// [`CacheLayer`].
+#[derive(Debug, Clone)]
+pub struct CacheService<S> {
+    inner: S,
+    policy: CachePolicy,
+}
+
+impl<S> Service<Request> for CacheService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = CacheFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        CacheFuture {
+            policy: Some(self.policy.clone()),
+            state: State::Inner {
+                future: self.inner.call(request),
+            },
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Future returned by [`CacheService`].
+    pub struct CacheFuture<F> {
+        policy: Option<CachePolicy>,
+        #[pin]
+        state: State<F>,
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[project = StateProj]
+    enum State<F> {
+        Inner {
+            #[pin]
+            future: F,
+        },
+        // Conversion of the response of the inner service.
+        Response {
+            response: ResponseFuture,
+        },
+    }
+}
+
+impl<F, R, E> Future for CacheFuture<F>
+where
+    F: Future<Output = Result<R, E>>,
+    R: IntoResponse,
+{
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        loop {
+            match this.state.as_mut().project() {
+                StateProj::Inner { future } => {
+                    let response = task::ready!(future.poll(cx))?.into_response();
+                    this.state.set(State::Response { response });
+                }
+                StateProj::Response { response } => {
+                    let mut response = task::ready!(response.as_mut().poll(cx));
+                    let policy = this.policy.take().expect("future polled after completion");
+                    policy.apply(&mut response);
+                    return Poll::Ready(Ok(response));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{header::ACCEPT_LANGUAGE, StatusCode};
+
+    use super::*;
+    use crate::{service_fn, tower::ServiceExt, Error};
+
+    #[test]
+    fn cache_control_of_presets() {
+        assert_eq!(
+            "public, max-age=31536000, immutable",
+            CachePolicy::immutable().cache_control()
+        );
+        assert_eq!("no-store", CachePolicy::no_store().cache_control());
+        assert_eq!(
+            "private, max-age=60, stale-while-revalidate=600",
+            CachePolicy::swr(Duration::from_secs(60), Duration::from_secs(600))
+                .private()
+                .cache_control()
+        );
+    }
+
+    #[tokio::test]
+    async fn apply_policies_to_responses() {
+        let policy = CachePolicy::max_age(Duration::from_secs(60)).vary(ACCEPT_LANGUAGE);
+        let service = CacheLayer::new(policy).layer(service_fn(|request: Request| async move {
+            let status = match request.uri().path() {
+                "/missing" => StatusCode::NOT_FOUND,
+                _ => StatusCode::OK,
+            };
+            let response = Response::builder()
+                .status(status)
+                .header(VARY, "Accept-Encoding")
+                .body(Body::Empty)?;
+            Ok::<_, Error>(response)
+        }));
+
+        let request = http::Request::builder().uri("/page").body(Body::Empty).unwrap();
+        let response = service.clone().oneshot(request).await.unwrap();
+        assert_eq!("public, max-age=60", response.headers()[CACHE_CONTROL]);
+        assert!(response.headers().contains_key(EXPIRES));
+        let vary: Vec<_> = response.headers().get_all(VARY).iter().collect();
+        assert_eq!(vec!["Accept-Encoding", "accept-language"], vary);
+
+        let request = http::Request::builder().uri("/missing").body(Body::Empty).unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        assert!(!response.headers().contains_key(CACHE_CONTROL));
+    }
+}
@@ -0,0 +1,466 @@
+use std::{
+    future::Future,
+    io::{self, Write},
+    pin::Pin,
+    sync::Arc,
+    task::{self, Poll},
+};
+
+use brotli::enc::BrotliEncoderParams;
+use flate2::{
+    write::{GzEncoder, ZlibEncoder},
+    Compression,
+};
+use http::{
+    header::{ACCEPT_ENCODING, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, VARY},
+    HeaderValue,
+};
+
+use crate::{
+    response::{is_text_content_type, ResponseFuture},
+    tower::{Layer, Service},
+    Body, IntoResponse, Request, Response,
+};
+
+/// Layer that compresses the text responses of a handler with the best encoding that the client
+/// accepts, according to its `Accept-Encoding` header: brotli (`br`), zstd, gzip, or deflate.
+///
+/// Compressed bodies are binary, so they are returned to API Gateway, ALB, and Function URLs
+/// encoded in base64, with the `isBase64Encoded` flag set. REST APIs only decode them when their
+/// binary media types include the content type of the response, or `*/*`.
+///
+/// Responses are left untouched when they already have a `Content-Encoding`, when their body
+/// isn't text, like images, when they are smaller than [`CompressionLayer::min_size`], or when
+/// their `Cache-Control` header has the `no-transform` directive.
+///
+/// ```rust,no_run
+/// use lambda_http::{layers::CompressionLayer, service_fn, tower::ServiceBuilder, Error, Request};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let handler = ServiceBuilder::new()
+///         .layer(CompressionLayer::new().min_size(512))
+///         .service(service_fn(|_request: Request| async { Ok::<_, Error>("hello ".repeat(1000)) }));
+///     lambda_http::run(handler).await
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompressionLayer {
+    brotli: bool,
+    zstd: bool,
+    gzip: bool,
+    deflate: bool,
+    min_size: usize,
+    level: u32,
+}
+
+impl CompressionLayer {
+    const DEFAULT_MIN_SIZE: usize = 1024;
+
+    /// Create a layer that compresses text responses of at least 1 KiB with brotli, zstd, gzip,
+    /// or deflate.
+    pub fn new() -> Self {
+        Self {
+            brotli: true,
+            zstd: true,
+            gzip: true,
+            deflate: true,
+            min_size: Self::DEFAULT_MIN_SIZE,
+            level: Compression::default().level(),
+        }
+    }
+
+    /// Enable or disable the brotli encoding, `br`.
+    pub fn brotli(mut self, enable: bool) -> Self {
+        self.brotli = enable;
+        self
+    }
+
+    /// Enable or disable the zstd encoding.
+    pub fn zstd(mut self, enable: bool) -> Self {
+        self.zstd = enable;
+        self
+    }
+
+    /// Enable or disable the gzip encoding.
+    pub fn gzip(mut self, enable: bool) -> Self {
+        self.gzip = enable;
+        self
+    }
+
+    /// Enable or disable the deflate encoding.
+    pub fn deflate(mut self, enable: bool) -> Self {
+        self.deflate = enable;
+        self
+    }
+
+    /// Only compress bodies of at least this number of bytes. Compressing small bodies costs more
+    /// than it saves, once they are encoded in base64.
+    pub fn min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Compression level, from 0 for the fastest to 9 for the smallest bodies. Brotli uses the
+    /// level as its quality, and zstd as its level, from 1.
+    pub fn level(mut self, level: u32) -> Self {
+        self.level = level.min(9);
+        self
+    }
+
+    /// Encoding with the highest quality value in the `Accept-Encoding` header, preferring brotli,
+    /// then zstd, gzip, and deflate, which compress text less and less.
+    fn negotiate(&self, accept_encoding: &str) -> Option<Encoding> {
+        let mut wildcard = None;
+        let mut qualities = Vec::new();
+        for item in accept_encoding.split(',') {
+            let mut params = item.split(';');
+            let coding = params.next().unwrap_or_default().trim();
+            let quality = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|quality| quality.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if coding == "*" {
+                wildcard = Some(quality);
+            } else {
+                qualities.push((coding.to_ascii_lowercase(), quality));
+            }
+        }
+
+        let enabled = [
+            (Encoding::Brotli, self.brotli),
+            (Encoding::Zstd, self.zstd),
+            (Encoding::Gzip, self.gzip),
+            (Encoding::Deflate, self.deflate),
+        ];
+        let mut best: Option<(Encoding, f32)> = None;
+        for (encoding, _) in enabled.into_iter().filter(|(_, enabled)| *enabled) {
+            let quality = qualities
+                .iter()
+                .find(|(coding, _)| encoding.matches(coding))
+                .map(|(_, quality)| *quality)
+                .or(wildcard)
+                .unwrap_or(0.0);
+            if quality > 0.0 && best.is_none_or(|(_, best)| quality > best) {
+                best = Some((encoding, quality));
+            }
+        }
+        best.map(|(encoding, _)| encoding)
+    }
+
+    fn compress(&self, encoding: Option<Encoding>, mut response: Response<Body>) -> Response<Body> {
+        let headers = response.headers();
+        let is_text = headers
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(is_text_content_type);
+        let no_transform = headers
+            .get_all(CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .any(|value| value.to_ascii_lowercase().contains("no-transform"));
+        if !is_text
+            || no_transform
+            || headers.contains_key(CONTENT_ENCODING)
+            || response.body().as_ref().len() < self.min_size
+        {
+            return response;
+        }
+
+        // The response depends on the Accept-Encoding header of requests, even when this one
+        // isn't compressed.
+        response
+            .headers_mut()
+            .append(VARY, HeaderValue::from_static("accept-encoding"));
+        let Some(encoding) = encoding else {
+            return response;
+        };
+        let compressed = match encoding.compress(response.body().as_ref(), self.level) {
+            Ok(compressed) if compressed.len() < response.body().as_ref().len() => compressed,
+            _ => return response,
+        };
+
+        let headers = response.headers_mut();
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding.name()));
+        headers.remove(CONTENT_LENGTH);
+        *response.body_mut() = Body::from(compressed);
+        response
+    }
+}
+
+impl Default for CompressionLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Brotli,
+    Zstd,
+    Gzip,
+    Deflate,
+}
+
+impl Encoding {
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Zstd => "zstd",
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+        }
+    }
+
+    fn matches(self, coding: &str) -> bool {
+        coding == self.name() || (self == Encoding::Gzip && coding == "x-gzip")
+    }
+
+    fn compress(self, data: &[u8], level: u32) -> io::Result<Vec<u8>> {
+        match self {
+            Encoding::Brotli => {
+                let mut compressed = Vec::new();
+                let params = BrotliEncoderParams {
+                    quality: level as i32,
+                    ..Default::default()
+                };
+                brotli::BrotliCompress(&mut &data[..], &mut compressed, &params)?;
+                Ok(compressed)
+            }
+            Encoding::Zstd => zstd::encode_all(data, level.max(1) as i32),
+            Encoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::new(level));
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            // The deflate content coding is the zlib format.
+            Encoding::Deflate => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::new(level));
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+impl<S> Layer<S> for CompressionLayer {
+    type Service = CompressionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CompressionService {
+            inner,
+            config: Arc::new(self.clone()),
+        }
+    }
+}
+
+/// Service that compresses the responses of the inner service, created by [`CompressionLayer`].
+#[derive(Debug, Clone)]
+pub struct CompressionService<S> {
+    inner: S,
+    config: Arc<CompressionLayer>,
+}
+
+impl<S> Service<Request> for CompressionService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = CompressionFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let encoding = request
+            .headers()
+            .get(ACCEPT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| self.config.negotiate(value));
+        CompressionFuture {
+            config: self.config.clone(),
+            encoding,
+            state: State::Inner {
+                future: self.inner.call(request),
+            },
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Future returned by [`CompressionService`].
+    pub struct CompressionFuture<F> {
+        config: Arc<CompressionLayer>,
+        encoding: Option<Encoding>,
+        #[pin]
+        state: State<F>,
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[project = StateProj]
+    enum State<F> {
+        Inner {
+            #[pin]
+            future: F,
+        },
+        // Conversion of the response of the inner service.
+        Response {
+            response: ResponseFuture,
+        },
+    }
+}
+
+impl<F, R, E> Future for CompressionFuture<F>
+where
+    F: Future<Output = Result<R, E>>,
+    R: IntoResponse,
+{
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut state = this.state;
+        loop {
+            match state.as_mut().project() {
+                StateProj::Inner { future } => {
+                    let response = task::ready!(future.poll(cx))?.into_response();
+                    state.set(State::Response { response });
+                }
+                StateProj::Response { response } => {
+                    let response = task::ready!(response.as_mut().poll(cx));
+                    return Poll::Ready(Ok(this.config.compress(*this.encoding, response)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::{GzDecoder, ZlibDecoder};
+
+    use super::*;
+    use crate::{request::RequestOrigin, response::LambdaResponse, service_fn, tower::ServiceExt, Error};
+
+    const TEXT: &str = "Lambda functions compress well when they repeat themselves. ";
+
+    async fn call(
+        layer: &CompressionLayer,
+        accept_encoding: Option<&'static str>,
+        response: Response<Body>,
+    ) -> Response<Body> {
+        let mut request = Request::default();
+        if let Some(accept_encoding) = accept_encoding {
+            request
+                .headers_mut()
+                .insert(ACCEPT_ENCODING, HeaderValue::from_static(accept_encoding));
+        }
+        let mut response = Some(response);
+        let service = layer.layer(service_fn(move |_request: Request| {
+            let response = response.take().expect("the service is called once");
+            async move { Ok::<_, Error>(response) }
+        }));
+        service.oneshot(request).await.unwrap()
+    }
+
+    fn text_response(content_type: &'static str, body: String) -> Response<Body> {
+        Response::builder()
+            .header(CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[test]
+    fn negotiate_encodings() {
+        let layer = CompressionLayer::new();
+        assert_eq!(Some(Encoding::Brotli), layer.negotiate("gzip, deflate, br, zstd"));
+        assert_eq!(Some(Encoding::Zstd), layer.negotiate("gzip, deflate, br;q=0.8, zstd"));
+        assert_eq!(
+            Some(Encoding::Gzip),
+            layer.clone().brotli(false).zstd(false).negotiate("gzip, deflate, br")
+        );
+        assert_eq!(Some(Encoding::Deflate), layer.negotiate("gzip;q=0.5, deflate"));
+        assert_eq!(
+            Some(Encoding::Deflate),
+            layer.negotiate("br;q=0, zstd;q=0, gzip;q=0, *;q=0.1")
+        );
+        assert_eq!(Some(Encoding::Brotli), layer.negotiate("*"));
+        assert_eq!(None, layer.negotiate("compress, identity"));
+        assert_eq!(None, layer.clone().gzip(false).negotiate("gzip"));
+    }
+
+    #[tokio::test]
+    async fn compress_text_responses() {
+        let layer = CompressionLayer::new();
+        let body = TEXT.repeat(50);
+
+        let response = call(&layer, Some("gzip"), text_response("application/json", body.clone())).await;
+        assert_eq!("gzip", response.headers()[CONTENT_ENCODING]);
+        assert_eq!("accept-encoding", response.headers()[VARY]);
+        let Body::Binary(compressed) = response.body() else {
+            panic!("compressed bodies are binary");
+        };
+        let mut decompressed = String::new();
+        GzDecoder::new(&compressed[..])
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(body, decompressed);
+
+        let response = call(&layer, Some("deflate"), text_response("text/html", body.clone())).await;
+        assert_eq!("deflate", response.headers()[CONTENT_ENCODING]);
+        let mut decompressed = String::new();
+        ZlibDecoder::new(response.body().as_ref())
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(body, decompressed);
+
+        let response = call(&layer, Some("br"), text_response("text/plain", body.clone())).await;
+        assert_eq!("br", response.headers()[CONTENT_ENCODING]);
+        let mut decompressed = String::new();
+        brotli_decompressor::Decompressor::new(response.body().as_ref(), 4096)
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(body, decompressed);
+
+        let response = call(&layer, Some("zstd"), text_response("text/plain", body.clone())).await;
+        assert_eq!("zstd", response.headers()[CONTENT_ENCODING]);
+        let decompressed = zstd::decode_all(response.body().as_ref()).unwrap();
+        assert_eq!(body.as_bytes(), decompressed);
+
+        let response = call(&layer, Some("deflate"), text_response("text/html", body.clone())).await;
+        // Compressed bodies are base64 encoded in the responses to API Gateway.
+        let json = serde_json::to_value(LambdaResponse::from_response(&RequestOrigin::ApiGatewayV2, response)).unwrap();
+        assert_eq!(true, json["isBase64Encoded"]);
+        assert_eq!("deflate", json["headers"]["content-encoding"]);
+    }
+
+    #[tokio::test]
+    async fn skip_responses_that_dont_need_compression() {
+        let layer = CompressionLayer::new();
+        let body = TEXT.repeat(50);
+
+        let response = call(&layer, None, text_response("text/plain", body.clone())).await;
+        assert!(!response.headers().contains_key(CONTENT_ENCODING));
+        assert_eq!("accept-encoding", response.headers()[VARY]);
+        assert!(matches!(response.body(), Body::Text(text) if *text == body));
+
+        let response = call(&layer, Some("gzip"), text_response("text/plain", TEXT.to_string())).await;
+        assert!(!response.headers().contains_key(CONTENT_ENCODING));
+
+        let response = call(&layer, Some("gzip"), text_response("image/png", body.clone())).await;
+        assert!(!response.headers().contains_key(CONTENT_ENCODING));
+
+        let mut response = text_response("text/plain", body.clone());
+        response
+            .headers_mut()
+            .insert(CACHE_CONTROL, HeaderValue::from_static("public, no-transform"));
+        let response = call(&layer, Some("gzip"), response).await;
+        assert!(!response.headers().contains_key(CONTENT_ENCODING));
+    }
+}
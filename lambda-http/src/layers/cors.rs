@@ -0,0 +1,473 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{self, Poll},
+    time::Duration,
+};
+
+use crate::{
+    response::ResponseFuture,
+    tower::{Layer, Service},
+    Body, IntoResponse, Request, Response,
+};
+use http::{
+    header::{
+        ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+        ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS, ACCESS_CONTROL_MAX_AGE,
+        ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD, ORIGIN, VARY,
+    },
+    HeaderMap, HeaderName, HeaderValue, Method, StatusCode,
+};
+
+/// Layer that adds the [CORS](https://developer.mozilla.org/en-US/docs/Web/HTTP/CORS) headers
+/// to the responses of a handler, and responds to preflight requests without calling it.
+///
+/// The layer works the same for API Gateway REST and HTTP APIs, ALB, and Function URLs. Disable
+/// the CORS configuration of API Gateway or of the Function URL when using it, since they would
+/// answer preflight requests before the handler is invoked.
+///
+/// Requests without an `Origin` header, like requests from other servers, are passed through
+/// untouched. Preflight requests from origins that aren't allowed are rejected with a `403`.
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+///
+/// use lambda_http::{
+///     http::{header::CONTENT_TYPE, Method},
+///     layers::CorsLayer,
+///     service_fn,
+///     tower::ServiceBuilder,
+///     Error, Request,
+/// };
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let cors = CorsLayer::new()
+///         .allow_origins(["https://example.com"])
+///         .allow_methods([Method::GET, Method::PUT])
+///         .allow_headers([CONTENT_TYPE])
+///         .allow_credentials(true)
+///         .max_age(Duration::from_secs(600));
+///     let handler = ServiceBuilder::new()
+///         .layer(cors)
+///         .service(service_fn(|_request: Request| async { Ok::<_, Error>("hello") }));
+///     lambda_http::run(handler).await
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CorsLayer {
+    origins: Allow<String>,
+    methods: Vec<Method>,
+    headers: Allow<HeaderName>,
+    expose_headers: Vec<HeaderName>,
+    credentials: bool,
+    max_age: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+enum Allow<T> {
+    Any,
+    List(Vec<T>),
+}
+
+impl CorsLayer {
+    /// Create a layer that allows `GET`, `HEAD`, and `POST` requests without extra headers from
+    /// no origin. Allow origins with [`CorsLayer::allow_origins`] or
+    /// [`CorsLayer::allow_any_origin`].
+    pub fn new() -> Self {
+        Self {
+            origins: Allow::List(Vec::new()),
+            methods: vec![Method::GET, Method::HEAD, Method::POST],
+            headers: Allow::List(Vec::new()),
+            expose_headers: Vec::new(),
+            credentials: false,
+            max_age: None,
+        }
+    }
+
+    /// Create a layer that allows requests with any method and header from any origin.
+    pub fn permissive() -> Self {
+        Self::new()
+            .allow_any_origin()
+            .allow_methods([
+                Method::GET,
+                Method::HEAD,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+            ])
+            .allow_any_header()
+    }
+
+    /// Allow requests from these origins, like `https://example.com`.
+    pub fn allow_origins<I, O>(mut self, origins: I) -> Self
+    where
+        I: IntoIterator<Item = O>,
+        O: Into<String>,
+    {
+        self.origins = Allow::List(origins.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Allow requests from any origin, with the `*` wildcard.
+    ///
+    /// It can't be combined with [`CorsLayer::allow_credentials`], which would let every site
+    /// read the responses to the requests of its visitors.
+    pub fn allow_any_origin(mut self) -> Self {
+        self.origins = Allow::Any;
+        self
+    }
+
+    /// Allow requests with these methods.
+    pub fn allow_methods<I: IntoIterator<Item = Method>>(mut self, methods: I) -> Self {
+        self.methods = methods.into_iter().collect();
+        self
+    }
+
+    /// Allow requests with these headers, in addition to the headers that are always allowed.
+    pub fn allow_headers<I: IntoIterator<Item = HeaderName>>(mut self, headers: I) -> Self {
+        self.headers = Allow::List(headers.into_iter().collect());
+        self
+    }
+
+    /// Allow requests with any header.
+    pub fn allow_any_header(mut self) -> Self {
+        self.headers = Allow::Any;
+        self
+    }
+
+    /// Let the scripts of the page read these headers of responses.
+    pub fn expose_headers<I: IntoIterator<Item = HeaderName>>(mut self, headers: I) -> Self {
+        self.expose_headers = headers.into_iter().collect();
+        self
+    }
+
+    /// Allow requests with cookies or authorization headers, from the origins of
+    /// [`CorsLayer::allow_origins`].
+    ///
+    /// # Panics
+    ///
+    /// Layers that allow credentials from any origin panic when they wrap a service.
+    pub fn allow_credentials(mut self, credentials: bool) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Let browsers cache the responses to preflight requests for this duration.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    fn allows(&self, origin: &HeaderValue) -> bool {
+        match &self.origins {
+            Allow::Any => true,
+            Allow::List(origins) => origins.iter().any(|allowed| allowed.as_bytes() == origin.as_bytes()),
+        }
+    }
+
+    /// Headers of all the responses to requests from an allowed origin.
+    fn response_headers(&self, origin: &HeaderValue) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if matches!(self.origins, Allow::Any) {
+            headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+        } else {
+            headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+            headers.append(VARY, HeaderValue::from_static("origin"));
+        }
+        if self.credentials {
+            headers.insert(ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+        }
+        headers
+    }
+
+    fn preflight(&self, request: &Request, origin: &HeaderValue) -> Response<Body> {
+        let mut response = Response::new(Body::Empty);
+        if !self.allows(origin) {
+            *response.status_mut() = StatusCode::FORBIDDEN;
+            response.headers_mut().insert(VARY, HeaderValue::from_static("origin"));
+            return response;
+        }
+        *response.status_mut() = StatusCode::NO_CONTENT;
+
+        let mut headers = self.response_headers(origin);
+        if let Some(methods) = join(self.methods.iter().map(Method::as_str)) {
+            headers.insert(ACCESS_CONTROL_ALLOW_METHODS, methods);
+        }
+        let allowed_headers = match &self.headers {
+            // Repeat the headers that the browser asks for.
+            Allow::Any => {
+                headers.append(VARY, HeaderValue::from_static("access-control-request-headers"));
+                request.headers().get(ACCESS_CONTROL_REQUEST_HEADERS).cloned()
+            }
+            Allow::List(allowed) => join(allowed.iter().map(HeaderName::as_str)),
+        };
+        if let Some(allowed_headers) = allowed_headers {
+            headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, allowed_headers);
+        }
+        if let Some(max_age) = self.max_age {
+            headers.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from(max_age.as_secs()));
+        }
+        *response.headers_mut() = headers;
+        response
+    }
+}
+
+impl Default for CorsLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for CorsLayer {
+    type Service = CorsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        assert!(
+            !(self.credentials && matches!(self.origins, Allow::Any)),
+            "invalid CORS configuration: credentials can't be allowed from any origin"
+        );
+        CorsService {
+            inner,
+            config: Arc::new(self.clone()),
+        }
+    }
+}
+
+/// Service that adds CORS headers to the responses of the inner service, created by
+/// [`CorsLayer`].
+#[derive(Debug, Clone)]
+pub struct CorsService<S> {
+    inner: S,
+    config: Arc<CorsLayer>,
+}
+
+impl<S> Service<Request> for CorsService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = CorsFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let Some(origin) = request.headers().get(ORIGIN).cloned() else {
+            return CorsFuture::new(State::Inner {
+                future: self.inner.call(request),
+                headers: None,
+            });
+        };
+        let is_preflight =
+            request.method() == Method::OPTIONS && request.headers().contains_key(ACCESS_CONTROL_REQUEST_METHOD);
+        if is_preflight {
+            return CorsFuture::new(State::Preflight {
+                response: Some(self.config.preflight(&request, &origin)),
+            });
+        }
+
+        let headers = if self.config.allows(&origin) {
+            let mut headers = self.config.response_headers(&origin);
+            if let Some(exposed) = join(self.config.expose_headers.iter().map(HeaderName::as_str)) {
+                headers.insert(ACCESS_CONTROL_EXPOSE_HEADERS, exposed);
+            }
+            headers
+        } else {
+            let mut headers = HeaderMap::new();
+            headers.insert(VARY, HeaderValue::from_static("origin"));
+            headers
+        };
+        CorsFuture::new(State::Inner {
+            future: self.inner.call(request),
+            headers: Some(headers),
+        })
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Future returned by [`CorsService`].
+    pub struct CorsFuture<F> {
+        #[pin]
+        state: State<F>,
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[project = StateProj]
+    enum State<F> {
+        // Response to a preflight request, which the inner service doesn't see.
+        Preflight { response: Option<Response<Body>> },
+        Inner {
+            #[pin]
+            future: F,
+            headers: Option<HeaderMap>,
+        },
+        // Conversion of the response of the inner service.
+        Response {
+            response: ResponseFuture,
+            headers: Option<HeaderMap>,
+        },
+    }
+}
+
+impl<F> CorsFuture<F> {
+    fn new(state: State<F>) -> Self {
+        Self { state }
+    }
+}
+
+impl<F, R, E> Future for CorsFuture<F>
+where
+    F: Future<Output = Result<R, E>>,
+    R: IntoResponse,
+{
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.project().state;
+        loop {
+            match state.as_mut().project() {
+                StateProj::Preflight { response } => {
+                    return Poll::Ready(Ok(response.take().expect("future polled after completion")));
+                }
+                StateProj::Inner { future, headers } => {
+                    let response = task::ready!(future.poll(cx))?.into_response();
+                    let headers = headers.take();
+                    state.set(State::Response { response, headers });
+                }
+                StateProj::Response { response, headers } => {
+                    let mut response = task::ready!(response.as_mut().poll(cx));
+                    for (name, value) in headers.take().iter().flatten() {
+                        if name == VARY {
+                            response.headers_mut().append(name, value.clone());
+                        } else {
+                            response.headers_mut().insert(name, value.clone());
+                        }
+                    }
+                    return Poll::Ready(Ok(response));
+                }
+            }
+        }
+    }
+}
+
+fn join<'a>(values: impl Iterator<Item = &'a str>) -> Option<HeaderValue> {
+    let joined = values.collect::<Vec<_>>().join(", ");
+    if joined.is_empty() {
+        return None;
+    }
+    HeaderValue::from_str(&joined).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{service_fn, tower::ServiceExt, Error};
+
+    fn request(method: Method, headers: &[(HeaderName, &'static str)]) -> Request {
+        let mut request = http::Request::builder().method(method).body(Body::Empty).unwrap();
+        for (name, value) in headers {
+            request
+                .headers_mut()
+                .insert(name.clone(), HeaderValue::from_static(value));
+        }
+        request
+    }
+
+    async fn call(layer: &CorsLayer, request: Request) -> Response<Body> {
+        let service = layer.layer(service_fn(|request: Request| async move {
+            assert_ne!(
+                Method::OPTIONS,
+                request.method(),
+                "preflight requests reach the handler"
+            );
+            Ok::<_, Error>("hello")
+        }));
+        service.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn respond_to_preflight_requests() {
+        let layer = CorsLayer::new()
+            .allow_origins(["https://example.com"])
+            .allow_methods([Method::GET, Method::PUT])
+            .allow_headers([http::header::CONTENT_TYPE])
+            .max_age(Duration::from_secs(600));
+        let preflight = |origin| {
+            request(
+                Method::OPTIONS,
+                &[(ORIGIN, origin), (ACCESS_CONTROL_REQUEST_METHOD, "PUT")],
+            )
+        };
+
+        let response = call(&layer, preflight("https://example.com")).await;
+        assert_eq!(StatusCode::NO_CONTENT, response.status());
+        let headers = response.headers();
+        assert_eq!("https://example.com", headers[ACCESS_CONTROL_ALLOW_ORIGIN]);
+        assert_eq!("GET, PUT", headers[ACCESS_CONTROL_ALLOW_METHODS]);
+        assert_eq!("content-type", headers[ACCESS_CONTROL_ALLOW_HEADERS]);
+        assert_eq!("600", headers[ACCESS_CONTROL_MAX_AGE]);
+        assert_eq!("origin", headers[VARY]);
+
+        let response = call(&layer, preflight("https://evil.example")).await;
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+        assert!(!response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+
+        let response = call(
+            &CorsLayer::permissive(),
+            request(
+                Method::OPTIONS,
+                &[
+                    (ORIGIN, "https://example.com"),
+                    (ACCESS_CONTROL_REQUEST_METHOD, "DELETE"),
+                    (ACCESS_CONTROL_REQUEST_HEADERS, "x-api-key"),
+                ],
+            ),
+        )
+        .await;
+        assert_eq!("*", response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN]);
+        assert_eq!("x-api-key", response.headers()[ACCESS_CONTROL_ALLOW_HEADERS]);
+    }
+
+    #[tokio::test]
+    async fn add_headers_to_responses() {
+        let layer = CorsLayer::new()
+            .allow_origins(["https://example.com"])
+            .allow_credentials(true)
+            .expose_headers([HeaderName::from_static("x-request-id")]);
+
+        let response = call(&layer, request(Method::GET, &[(ORIGIN, "https://example.com")])).await;
+        assert_eq!(StatusCode::OK, response.status());
+        let headers = response.headers();
+        assert_eq!("https://example.com", headers[ACCESS_CONTROL_ALLOW_ORIGIN]);
+        assert_eq!("true", headers[ACCESS_CONTROL_ALLOW_CREDENTIALS]);
+        assert_eq!("x-request-id", headers[ACCESS_CONTROL_EXPOSE_HEADERS]);
+        assert_eq!("origin", headers[VARY]);
+        assert!(matches!(response.body(), Body::Text(text) if text == "hello"));
+
+        let response = call(
+            &CorsLayer::new(),
+            request(Method::GET, &[(ORIGIN, "https://example.com")]),
+        )
+        .await;
+        assert!(!response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+        assert_eq!("origin", response.headers()[VARY]);
+
+        let response = call(&layer, request(Method::GET, &[])).await;
+        assert!(response.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "credentials can't be allowed from any origin")]
+    fn reject_credentials_from_any_origin() {
+        CorsLayer::permissive()
+            .allow_credentials(true)
+            .layer(service_fn(|_request: Request| async { Ok::<_, Error>("hello") }));
+    }
+}
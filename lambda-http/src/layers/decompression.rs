@@ -0,0 +1,254 @@
+use std::{
+    io::{self, Read},
+    task::{self, Poll},
+};
+
+use flate2::read::{DeflateDecoder, MultiGzDecoder, ZlibDecoder};
+use http::{
+    header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH},
+    HeaderValue, StatusCode,
+};
+
+use crate::{
+    error::ErrorResponse,
+    layers::RejectionFuture,
+    tower::{Layer, Service},
+    Body, IntoResponse, Request, Response,
+};
+
+/// Layer that decompresses the bodies of requests with a `Content-Encoding` header of `gzip`,
+/// `deflate`, or `br`, so handlers get the original body.
+///
+/// Bodies are decompressed after API Gateway, ALB, or Function URLs decode them from base64,
+/// and the `Content-Encoding` header is removed. To protect the function from compression
+/// bombs, bodies that decompress to more than [`RequestDecompressionLayer::max_size`] bytes, or
+/// that expand more than [`RequestDecompressionLayer::max_ratio`] times, are rejected with
+/// `413 Payload Too Large`. Unknown encodings are rejected with `415 Unsupported Media Type`,
+/// and corrupted bodies with `400 Bad Request`.
+///
+/// ```rust,no_run
+/// use lambda_http::{layers::RequestDecompressionLayer, service_fn, tower::ServiceBuilder, Error, Request};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let handler = ServiceBuilder::new()
+///         .layer(RequestDecompressionLayer::new().max_size(4 * 1024 * 1024))
+///         .service(service_fn(|request: Request| async move {
+///             Ok::<_, Error>(format!("{} bytes", request.body().len()))
+///         }));
+///     lambda_http::run(handler).await
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestDecompressionLayer {
+    max_size: usize,
+    max_ratio: usize,
+}
+
+impl RequestDecompressionLayer {
+    /// Create a layer that decompresses bodies of up to 10 MiB, that expand at most 100 times.
+    pub fn new() -> Self {
+        Self {
+            max_size: 10 * 1024 * 1024,
+            max_ratio: 100,
+        }
+    }
+
+    /// Largest decompressed body, in bytes.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Largest ratio between the size of the decompressed body and the size of the compressed
+    /// one. Compressed JSON is usually 5 to 20 times smaller.
+    pub fn max_ratio(mut self, max_ratio: usize) -> Self {
+        self.max_ratio = max_ratio.max(1);
+        self
+    }
+
+    fn decompress(&self, request: &mut Request) -> Result<(), DecompressionError> {
+        let Some(encodings) = request.headers().get(CONTENT_ENCODING) else {
+            return Ok(());
+        };
+        let encodings = encodings
+            .to_str()
+            .map_err(|_| DecompressionError::Unsupported("invalid Content-Encoding header".to_string()))?
+            .split(',')
+            .map(|encoding| encoding.trim().to_ascii_lowercase())
+            .filter(|encoding| !encoding.is_empty() && encoding != "identity")
+            .collect::<Vec<_>>();
+
+        let compressed = request.body().as_ref();
+        let limit = self.max_size.min(compressed.len().saturating_mul(self.max_ratio));
+        let mut body = compressed.to_vec();
+        // Encodings are listed in the order that they were applied.
+        for encoding in encodings.iter().rev() {
+            body = match encoding.as_str() {
+                "gzip" | "x-gzip" => read_limited(MultiGzDecoder::new(&body[..]), limit)?,
+                "deflate" => match read_limited(ZlibDecoder::new(&body[..]), limit) {
+                    // Some clients send raw deflate streams, without the zlib wrapper.
+                    Err(DecompressionError::Corrupted(_)) => read_limited(DeflateDecoder::new(&body[..]), limit)?,
+                    result => result?,
+                },
+                "br" => read_limited(brotli_decompressor::Decompressor::new(&body[..], 4096), limit)?,
+                encoding => {
+                    return Err(DecompressionError::Unsupported(format!(
+                        "unsupported content encoding {encoding}"
+                    )))
+                }
+            };
+        }
+
+        let headers = request.headers_mut();
+        headers.remove(CONTENT_ENCODING);
+        headers.insert(CONTENT_LENGTH, HeaderValue::from(body.len()));
+        *request.body_mut() = Body::from(body);
+        Ok(())
+    }
+}
+
+impl Default for RequestDecompressionLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Read a decompressed body, failing once it's larger than `limit` bytes.
+fn read_limited(decoder: impl Read, limit: usize) -> Result<Vec<u8>, DecompressionError> {
+    let mut body = Vec::new();
+    decoder
+        .take(limit as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(DecompressionError::Corrupted)?;
+    if body.len() > limit {
+        return Err(DecompressionError::TooLarge(limit));
+    }
+    Ok(body)
+}
+
+enum DecompressionError {
+    Corrupted(io::Error),
+    TooLarge(usize),
+    Unsupported(String),
+}
+
+impl From<DecompressionError> for ErrorResponse {
+    fn from(err: DecompressionError) -> Self {
+        match err {
+            DecompressionError::Corrupted(err) => {
+                ErrorResponse::bad_request("the body can't be decompressed").with_source(err)
+            }
+            DecompressionError::TooLarge(limit) => ErrorResponse::new(StatusCode::PAYLOAD_TOO_LARGE)
+                .detail(format!("the decompressed body is larger than {limit} bytes")),
+            // RFC 7694 lists the supported encodings of requests in the Accept-Encoding header.
+            DecompressionError::Unsupported(detail) => ErrorResponse::new(StatusCode::UNSUPPORTED_MEDIA_TYPE)
+                .detail(detail)
+                .header(ACCEPT_ENCODING, HeaderValue::from_static("gzip, deflate, br")),
+        }
+    }
+}
+
+impl<S> Layer<S> for RequestDecompressionLayer {
+    type Service = RequestDecompressionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestDecompressionService { inner, layer: *self }
+    }
+}
+
+/// Service that decompresses the bodies of requests before the inner service gets them,
+/// created by [`RequestDecompressionLayer`].
+#[derive(Debug, Clone)]
+pub struct RequestDecompressionService<S> {
+    inner: S,
+    layer: RequestDecompressionLayer,
+}
+
+impl<S> Service<Request> for RequestDecompressionService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = RequestDecompressionFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request) -> Self::Future {
+        if let Err(err) = self.layer.decompress(&mut request) {
+            return RequestDecompressionFuture::rejected(ErrorResponse::from(err).into());
+        }
+        RequestDecompressionFuture::inner(self.inner.call(request))
+    }
+}
+
+/// Future returned by [`RequestDecompressionService`].
+pub type RequestDecompressionFuture<F> = RejectionFuture<F>;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+    use crate::{service_fn, tower::ServiceExt, Error};
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    async fn call(layer: RequestDecompressionLayer, encoding: &str, body: Vec<u8>) -> Response<Body> {
+        let service = layer.layer(service_fn(|request: Request| async move {
+            assert!(!request.headers().contains_key(CONTENT_ENCODING));
+            Ok::<_, Error>(Response::new(request.into_body()))
+        }));
+        let request = http::Request::builder()
+            .header(CONTENT_ENCODING, encoding)
+            .body(Body::from(body))
+            .unwrap();
+        service.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn decompress_bodies() {
+        let body = br#"{"items":[1,2,3]}"#;
+        let response = call(RequestDecompressionLayer::new(), "gzip", gzip(body)).await;
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(&body[..], response.body().as_ref());
+
+        let brotli = [&b"\x0b\x08\x80"[..], &body[..], &b"\x03"[..]].concat();
+        let response = call(RequestDecompressionLayer::new(), "br", brotli).await;
+        assert_eq!(&body[..], response.body().as_ref());
+
+        let response = call(RequestDecompressionLayer::new(), "identity", body.to_vec()).await;
+        assert_eq!(&body[..], response.body().as_ref());
+    }
+
+    #[tokio::test]
+    async fn reject_bombs_and_unknown_encodings() {
+        let bomb = gzip(&vec![0; 1024 * 1024]);
+        let response = call(RequestDecompressionLayer::new(), "gzip", bomb.clone()).await;
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, response.status());
+        let response = call(
+            RequestDecompressionLayer::new().max_size(1024),
+            "gzip",
+            gzip(&[0; 2048]),
+        )
+        .await;
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, response.status());
+
+        let response = call(RequestDecompressionLayer::new(), "zstd", b"data".to_vec()).await;
+        assert_eq!(StatusCode::UNSUPPORTED_MEDIA_TYPE, response.status());
+        assert_eq!("gzip, deflate, br", response.headers()[ACCEPT_ENCODING]);
+
+        let response = call(RequestDecompressionLayer::new(), "gzip", b"not gzip".to_vec()).await;
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{self, Poll},
+};
+
+use crate::{
+    response::ResponseFuture,
+    tower::{Layer, Service},
+    Body, IntoResponse, Request, Response,
+};
+
+/// Layer that converts the errors of a handler into HTTP responses, with [`IntoResponse`],
+/// instead of failing the invocation.
+///
+/// Failed invocations are answered with a `502 Bad Gateway` by API Gateway, and their errors
+/// are only visible in logs. With this layer, handlers can return an
+/// [`ErrorResponse`](crate::error::ErrorResponse) with the status code and body that clients
+/// should get, or any [`Error`](crate::Error), which is answered with a
+/// `500 Internal Server Error`.
+///
+/// ```rust,no_run
+/// use lambda_http::{layers::ErrorResponseLayer, service_fn, tower::ServiceBuilder, Error, Request};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let handler = ServiceBuilder::new()
+///         .layer(ErrorResponseLayer::new())
+///         .service(service_fn(|_request: Request| async { Err::<String, Error>("failed".into()) }));
+///     lambda_http::run(handler).await
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ErrorResponseLayer {}
+
+impl ErrorResponseLayer {
+    /// Create a new error response layer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S: Service<Request>> Layer<S> for ErrorResponseLayer {
+    type Service = ErrorResponseService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ErrorResponseService {
+            inner,
+            ready_error: None,
+        }
+    }
+}
+
+/// Service that converts the errors of the inner service into responses, created by
+/// [`ErrorResponseLayer`].
+#[derive(Debug)]
+pub struct ErrorResponseService<S: Service<Request>> {
+    inner: S,
+    // Error of the inner service when it isn't ready, answered to the next request.
+    ready_error: Option<S::Error>,
+}
+
+impl<S> Clone for ErrorResponseService<S>
+where
+    S: Service<Request> + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            ready_error: None,
+        }
+    }
+}
+
+impl<S> Service<Request> for ErrorResponseService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    S::Error: IntoResponse,
+{
+    type Response = Response<Body>;
+    type Error = Infallible;
+    type Future = ErrorResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.inner.poll_ready(cx) {
+            Poll::Ready(Err(err)) => {
+                self.ready_error = Some(err);
+                Poll::Ready(Ok(()))
+            }
+            poll => poll.map(|_| Ok(())),
+        }
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let state = match self.ready_error.take() {
+            Some(err) => State::Response {
+                response: err.into_response(),
+            },
+            None => State::Inner {
+                future: self.inner.call(request),
+            },
+        };
+        ErrorResponseFuture { state }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Future returned by [`ErrorResponseService`].
+    pub struct ErrorResponseFuture<F> {
+        #[pin]
+        state: State<F>,
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[project = StateProj]
+    enum State<F> {
+        Inner {
+            #[pin]
+            future: F,
+        },
+        // Conversion of the response or the error of the inner service.
+        Response {
+            response: ResponseFuture,
+        },
+    }
+}
+
+impl<F, R, E> Future for ErrorResponseFuture<F>
+where
+    F: Future<Output = Result<R, E>>,
+    R: IntoResponse,
+    E: IntoResponse,
+{
+    type Output = Result<Response<Body>, Infallible>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.project().state;
+        loop {
+            match state.as_mut().project() {
+                StateProj::Inner { future } => {
+                    let response = match task::ready!(future.poll(cx)) {
+                        Ok(response) => response.into_response(),
+                        Err(err) => err.into_response(),
+                    };
+                    state.set(State::Response { response });
+                }
+                StateProj::Response { response } => {
+                    return Poll::Ready(Ok(task::ready!(response.as_mut().poll(cx))));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{error::ErrorResponse, service_fn, tower::ServiceExt, Error};
+
+    #[tokio::test]
+    async fn convert_errors_into_responses() {
+        let service = ErrorResponseLayer::new().layer(service_fn(|request: Request| async move {
+            match request.uri().path() {
+                "/missing" => Err(ErrorResponse::not_found("no such page")),
+                _ => Ok("hello"),
+            }
+        }));
+        let request = |path| http::Request::builder().uri(path).body(Body::Empty).unwrap();
+
+        let response = service.clone().oneshot(request("/missing")).await.unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        let response = service.oneshot(request("/")).await.unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+
+        let service = ErrorResponseLayer::new().layer(service_fn(|_request: Request| async {
+            Err::<String, Error>("failed".into())
+        }));
+        let response = service.oneshot(request("/")).await.unwrap();
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+    }
+}
@@ -0,0 +1,116 @@
+//! Tower middleware for HTTP handlers.
+//!
+//! Layers wrap a `Service<Request>`, and can be added to a handler with
+//! [`ServiceBuilder`](crate::tower::ServiceBuilder) before it's passed to [`run`](crate::run).
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{self, Poll},
+};
+
+use crate::{response::ResponseFuture, Body, IntoResponse, Response};
+
+mod body_limit;
+mod cache;
+mod cors;
+mod error_response;
+mod request_id;
+mod tenant;
+
+pub use body_limit::{BodyLimitFuture, BodyLimitLayer, BodyLimitService, BodySize};
+pub use cache::{CacheFuture, CacheLayer, CachePolicy, CacheService};
+pub use cors::{CorsFuture, CorsLayer, CorsService};
+pub use error_response::{ErrorResponseFuture, ErrorResponseLayer, ErrorResponseService};
+pub use request_id::{RequestId, RequestIdFuture, RequestIdLayer, RequestIdService};
+pub use tenant::{Tenant, TenantFallback, TenantFuture, TenantLayer, TenantService};
+
+#[cfg(feature = "compression")]
+mod compression;
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub use compression::{CompressionFuture, CompressionLayer, CompressionService};
+#[cfg(feature = "compression")]
+mod decompression;
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub use decompression::{RequestDecompressionFuture, RequestDecompressionLayer, RequestDecompressionService};
+
+#[cfg(feature = "tracing")]
+mod trace;
+#[cfg(feature = "tracing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tracing")))]
+pub use trace::{TraceFuture, TraceLayer, TraceService};
+
+#[cfg(feature = "validation")]
+mod validation;
+#[cfg(feature = "validation")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validation")))]
+pub use validation::{RequestSchema, SchemaError, ValidationFuture, ValidationLayer, ValidationService};
+
+pin_project_lite::pin_project! {
+    /// Future of layers that answer some requests themselves, like [`BodyLimitService`], and
+    /// pass the others to their inner service.
+    pub struct RejectionFuture<F> {
+        #[pin]
+        state: State<F>,
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[project = StateProj]
+    enum State<F> {
+        // Response of the layer, to a request that the inner service doesn't see.
+        Rejected { response: Option<Response<Body>> },
+        Inner {
+            #[pin]
+            future: F,
+        },
+        // Conversion of the response of the inner service.
+        Response {
+            response: ResponseFuture,
+        },
+    }
+}
+
+impl<F> RejectionFuture<F> {
+    pub(crate) fn rejected(response: Response<Body>) -> Self {
+        Self {
+            state: State::Rejected {
+                response: Some(response),
+            },
+        }
+    }
+
+    pub(crate) fn inner(future: F) -> Self {
+        Self {
+            state: State::Inner { future },
+        }
+    }
+}
+
+impl<F, R, E> Future for RejectionFuture<F>
+where
+    F: Future<Output = Result<R, E>>,
+    R: IntoResponse,
+{
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.project().state;
+        loop {
+            match state.as_mut().project() {
+                StateProj::Rejected { response } => {
+                    return Poll::Ready(Ok(response.take().expect("future polled after completion")));
+                }
+                StateProj::Inner { future } => {
+                    let response = task::ready!(future.poll(cx))?.into_response();
+                    state.set(State::Response { response });
+                }
+                StateProj::Response { response } => {
+                    return Poll::Ready(Ok(task::ready!(response.as_mut().poll(cx))));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{self, Poll},
+};
+
+use http::{HeaderName, HeaderValue};
+
+use crate::{
+    response::ResponseFuture,
+    tower::{Layer, Service},
+    Body, IntoResponse, Request, RequestExt, Response,
+};
+
+/// Correlation id of a request, added to the extensions of requests by [`RequestIdLayer`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// The correlation id.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Layer that gives each request a correlation id, so the logs of the services that a request
+/// goes through can be tied together.
+///
+/// The id is the one of the `X-Request-Id` header of the request, or the trace id of its W3C
+/// `traceparent` header. Requests without one get the request id of their Lambda invocation, and
+/// the requests that have no Lambda context either, outside of Lambda, get no id. The id is added to the extensions of the request as a [`RequestId`], set on
+/// the header of the request and of the response, and recorded as the `requestId` field of a
+/// tracing span around the inner service. Ids that aren't printable ASCII, or that are longer
+/// than 128 characters, are ignored, so clients can't forge log lines.
+///
+/// ```rust,no_run
+/// use lambda_http::{
+///     layers::{RequestId, RequestIdLayer},
+///     service_fn,
+///     tower::ServiceBuilder,
+///     Error, Request,
+/// };
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let handler = ServiceBuilder::new()
+///         .layer(RequestIdLayer::new())
+///         .service(service_fn(|request: Request| async move {
+///             let RequestId(id) = request.extensions().get::<RequestId>().cloned().unwrap();
+///             Ok::<_, Error>(format!("request {id}"))
+///         }));
+///     lambda_http::run(handler).await
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequestIdLayer {
+    header: HeaderName,
+}
+
+impl RequestIdLayer {
+    /// Longest id that is accepted from clients.
+    const MAX_LENGTH: usize = 128;
+
+    /// Create a layer that uses the `X-Request-Id` header.
+    pub fn new() -> Self {
+        Self {
+            header: HeaderName::from_static("x-request-id"),
+        }
+    }
+
+    /// Use another header for the id, like `X-Correlation-Id`.
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+
+    fn request_id(&self, request: &Request) -> Option<RequestId> {
+        let headers = request.headers();
+        let id = headers
+            .get(&self.header)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|id| is_valid(id))
+            .or_else(|| {
+                headers
+                    .get("traceparent")
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(trace_id)
+            })
+            .map(str::to_string)
+            .or_else(|| {
+                request
+                    .lambda_context_ref()
+                    .map(|context| context.request_id.clone())
+                    .filter(|id| !id.is_empty())
+            })?;
+        Some(RequestId(id))
+    }
+}
+
+impl Default for RequestIdLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_valid(id: &str) -> bool {
+    !id.is_empty() && id.len() <= RequestIdLayer::MAX_LENGTH && id.bytes().all(|byte| byte.is_ascii_graphic())
+}
+
+/// Trace id of a `traceparent` header, like `4bf92f3577b34da6a3ce929d0e0e4736` in
+/// `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01`.
+fn trace_id(traceparent: &str) -> Option<&str> {
+    let mut parts = traceparent.trim().split('-');
+    let (_version, trace_id) = (parts.next()?, parts.next()?);
+    let valid = trace_id.len() == 32
+        && trace_id.bytes().all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f'))
+        && trace_id.bytes().any(|byte| byte != b'0');
+    valid.then_some(trace_id)
+}
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Service that gives each request of the inner service a correlation id, created by
+/// [`RequestIdLayer`].
+#[derive(Debug, Clone)]
+pub struct RequestIdService<S> {
+    inner: S,
+    layer: RequestIdLayer,
+}
+
+impl<S> Service<Request> for RequestIdService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = RequestIdFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request) -> Self::Future {
+        let Some(request_id) = self.layer.request_id(&request) else {
+            #[cfg(feature = "tracing")]
+            let span = crate::tracing::Span::none();
+            #[cfg(not(feature = "tracing"))]
+            let span = ();
+            return RequestIdFuture {
+                span,
+                header: None,
+                state: State::Inner {
+                    future: self.inner.call(request),
+                },
+            };
+        };
+        let value = HeaderValue::from_str(request_id.as_str()).expect("request ids are valid headers");
+        request.headers_mut().insert(self.layer.header.clone(), value.clone());
+
+        #[cfg(feature = "tracing")]
+        let span = crate::tracing::info_span!("request", requestId = request_id.as_str());
+        #[cfg(not(feature = "tracing"))]
+        let span = ();
+        request.extensions_mut().insert(request_id);
+        let future = {
+            #[cfg(feature = "tracing")]
+            let _guard = span.enter();
+            self.inner.call(request)
+        };
+        RequestIdFuture {
+            span,
+            header: Some((self.layer.header.clone(), value)),
+            state: State::Inner { future },
+        }
+    }
+}
+
+// Span with the id of requests, entered while the inner service is polled.
+#[cfg(feature = "tracing")]
+type RequestSpan = crate::tracing::Span;
+#[cfg(not(feature = "tracing"))]
+type RequestSpan = ();
+
+pin_project_lite::pin_project! {
+    /// Future returned by [`RequestIdService`].
+    pub struct RequestIdFuture<F> {
+        span: RequestSpan,
+        header: Option<(HeaderName, HeaderValue)>,
+        #[pin]
+        state: State<F>,
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[project = StateProj]
+    enum State<F> {
+        Inner {
+            #[pin]
+            future: F,
+        },
+        // Conversion of the response of the inner service.
+        Response {
+            response: ResponseFuture,
+        },
+    }
+}
+
+impl<F, R, E> Future for RequestIdFuture<F>
+where
+    F: Future<Output = Result<R, E>>,
+    R: IntoResponse,
+{
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        #[cfg(feature = "tracing")]
+        let _guard = this.span.enter();
+        loop {
+            match this.state.as_mut().project() {
+                StateProj::Inner { future } => {
+                    let response = task::ready!(future.poll(cx))?.into_response();
+                    this.state.set(State::Response { response });
+                }
+                StateProj::Response { response } => {
+                    let mut response = task::ready!(response.as_mut().poll(cx));
+                    if let Some((name, value)) = this.header.take() {
+                        response.headers_mut().entry(name).or_insert(value);
+                    }
+                    return Poll::Ready(Ok(response));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{service_fn, tower::ServiceExt, Context, Error};
+
+    async fn call(layer: RequestIdLayer, request: Request) -> Response<Body> {
+        let service = layer.layer(service_fn(|request: Request| async move {
+            let id = request.extensions().get::<RequestId>().cloned();
+            Ok::<_, Error>(id.map(|RequestId(id)| id).unwrap_or_default())
+        }));
+        service.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn propagate_incoming_ids() {
+        let request = http::Request::builder()
+            .header("x-request-id", "abc-123")
+            .body(Body::Empty)
+            .unwrap();
+        let response = call(RequestIdLayer::new(), request).await;
+        assert_eq!(&Body::from("abc-123"), response.body());
+        assert_eq!("abc-123", response.headers()["x-request-id"]);
+
+        let request = http::Request::builder()
+            .header("x-correlation-id", "forged\tline")
+            .header("traceparent", "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+            .body(Body::Empty)
+            .unwrap();
+        let layer = RequestIdLayer::new().header(HeaderName::from_static("x-correlation-id"));
+        let response = call(layer, request).await;
+        assert_eq!(&Body::from("4bf92f3577b34da6a3ce929d0e0e4736"), response.body());
+        assert_eq!(
+            "4bf92f3577b34da6a3ce929d0e0e4736",
+            response.headers()["x-correlation-id"]
+        );
+
+        let mut context = Context::default();
+        context.request_id = "lambda-id".to_string();
+        let request = http::Request::builder()
+            .header("x-request-id", "a".repeat(129))
+            .body(Body::Empty)
+            .unwrap()
+            .with_lambda_context(context);
+        let response = call(RequestIdLayer::new(), request).await;
+        assert_eq!(&Body::from("lambda-id"), response.body());
+        assert_eq!("lambda-id", response.headers()["x-request-id"]);
+    }
+
+    #[tokio::test]
+    async fn fall_back_to_lambda_request_ids() {
+        let mut context = Context::default();
+        context.request_id = "lambda-id".to_string();
+        let request = http::Request::new(Body::Empty).with_lambda_context(context);
+        let response = call(RequestIdLayer::new(), request).await;
+        assert_eq!("lambda-id", response.headers()["x-request-id"]);
+
+        let response = call(RequestIdLayer::new(), http::Request::new(Body::Empty)).await;
+        assert_eq!(&Body::from(""), response.body());
+        assert!(!response.headers().contains_key("x-request-id"));
+    }
+}
@@ -0,0 +1,285 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    task::{self, Poll},
+};
+
+use http::{header::HOST, HeaderName, StatusCode};
+
+use crate::{
+    error::ErrorResponse,
+    layers::RejectionFuture,
+    tower::{Layer, Service},
+    Body, IntoResponse, Request, RequestExt, Response,
+};
+
+/// Tenant of a request, added to the extensions of requests by [`TenantLayer`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Tenant(pub String);
+
+impl Tenant {
+    /// Identifier of the tenant.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// What [`TenantLayer`] does with requests whose host doesn't belong to a tenant.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TenantFallback {
+    /// Reject the request with `404 Not Found`.
+    #[default]
+    Reject,
+    /// Use a default tenant.
+    Default(String),
+    /// Pass the request to the handler without a [`Tenant`].
+    Pass,
+}
+
+/// Layer that finds the [`Tenant`] of requests from their host, for multi-tenant APIs served by
+/// one function, and adds it to their extensions.
+///
+/// Tenants are the label of subdomains next to a base domain, like `acme` for
+/// `acme.example.com` or `api.acme.example.com`, or come from a mapping of custom domains,
+/// which takes precedence. The host is the domain name of the request context of API Gateway
+/// and Function URLs, or else the `Host` header. Requests of other hosts get the
+/// [`TenantFallback`] of the layer.
+///
+/// Clients can send any `X-Forwarded-Host` header, so it's only used with
+/// [`TenantLayer::trust_forwarded_host`], for functions behind proxies like CloudFront that set
+/// it.
+///
+/// ```rust,no_run
+/// use lambda_http::{
+///     layers::{Tenant, TenantLayer},
+///     service_fn,
+///     tower::ServiceBuilder,
+///     Error, Request,
+/// };
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let handler = ServiceBuilder::new()
+///         .layer(TenantLayer::subdomain("example.com").domain("api.acme.com", "acme"))
+///         .service(service_fn(|request: Request| async move {
+///             let Tenant(tenant) = request.extensions().get::<Tenant>().cloned().unwrap();
+///             Ok::<_, Error>(format!("hello {tenant}"))
+///         }));
+///     lambda_http::run(handler).await
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TenantLayer {
+    config: Arc<TenantConfig>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct TenantConfig {
+    base_domain: Option<String>,
+    domains: HashMap<String, String>,
+    fallback: TenantFallback,
+    trust_forwarded_host: bool,
+}
+
+impl TenantLayer {
+    /// Create a layer that only finds tenants in the mapping of custom domains.
+    pub fn new() -> Self {
+        Self { config: Arc::default() }
+    }
+
+    /// Create a layer that finds tenants in the subdomains of `base_domain`.
+    pub fn subdomain(base_domain: impl Into<String>) -> Self {
+        let mut layer = Self::new();
+        Arc::make_mut(&mut layer.config).base_domain = Some(normalize(&base_domain.into()));
+        layer
+    }
+
+    /// Map a custom domain to a tenant.
+    pub fn domain(mut self, host: impl Into<String>, tenant: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.config)
+            .domains
+            .insert(normalize(&host.into()), tenant.into());
+        self
+    }
+
+    /// Set what happens to requests whose host doesn't belong to a tenant, which are rejected by
+    /// default.
+    pub fn fallback(mut self, fallback: TenantFallback) -> Self {
+        Arc::make_mut(&mut self.config).fallback = fallback;
+        self
+    }
+
+    /// Find tenants from the `X-Forwarded-Host` header of requests. Disabled by default.
+    ///
+    /// Only enable it when every request goes through a proxy that replaces the header, like
+    /// a CloudFront distribution, since otherwise clients choose their tenant.
+    pub fn trust_forwarded_host(mut self, enable: bool) -> Self {
+        Arc::make_mut(&mut self.config).trust_forwarded_host = enable;
+        self
+    }
+}
+
+impl Default for TenantLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TenantConfig {
+    fn tenant(&self, request: &Request) -> Option<Tenant> {
+        let header = |name| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|host| host.to_str().ok())
+                .map(|host| host.split(',').next().unwrap_or_default())
+        };
+        let host = self
+            .trust_forwarded_host
+            .then(|| header(HeaderName::from_static("x-forwarded-host")))
+            .flatten()
+            .or_else(|| request.request_context_ref()?.domain_name())
+            .or_else(|| header(HOST))
+            .or_else(|| request.uri().host())?;
+        let host = normalize(host);
+
+        if let Some(tenant) = self.domains.get(&host) {
+            return Some(Tenant(tenant.clone()));
+        }
+        let base_domain = self.base_domain.as_deref()?;
+        let subdomain = host.strip_suffix(base_domain)?.strip_suffix('.')?;
+        // The tenant is the label closest to the base domain, like `acme` in
+        // `api.acme.example.com`.
+        let tenant = subdomain.rsplit('.').next().filter(|label| !label.is_empty())?;
+        Some(Tenant(tenant.to_string()))
+    }
+}
+
+/// Lowercase host without its port or trailing dot.
+fn normalize(host: &str) -> String {
+    let host = host.trim();
+    let host = match host.rsplit_once(':') {
+        Some((host, port)) if port.bytes().all(|byte| byte.is_ascii_digit()) => host,
+        _ => host,
+    };
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+impl<S> Layer<S> for TenantLayer {
+    type Service = TenantService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TenantService {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// Service that adds the [`Tenant`] of requests to their extensions, created by [`TenantLayer`].
+#[derive(Debug, Clone)]
+pub struct TenantService<S> {
+    inner: S,
+    config: Arc<TenantConfig>,
+}
+
+impl<S> Service<Request> for TenantService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = TenantFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request) -> Self::Future {
+        let tenant = match (self.config.tenant(&request), &self.config.fallback) {
+            (Some(tenant), _) => Some(tenant),
+            (None, TenantFallback::Default(tenant)) => Some(Tenant(tenant.clone())),
+            (None, TenantFallback::Pass) => None,
+            (None, TenantFallback::Reject) => {
+                let response = ErrorResponse::new(StatusCode::NOT_FOUND).detail("unknown tenant");
+                return TenantFuture::rejected(response.into());
+            }
+        };
+        if let Some(tenant) = tenant {
+            request.extensions_mut().insert(tenant);
+        }
+        TenantFuture::inner(self.inner.call(request))
+    }
+}
+
+/// Future returned by [`TenantService`].
+pub type TenantFuture<F> = RejectionFuture<F>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{service_fn, tower::ServiceExt, Error};
+
+    async fn call(layer: &TenantLayer, host: &str) -> Response<Body> {
+        let request = http::Request::builder().header(HOST, host).body(Body::Empty).unwrap();
+        call_with(layer, request).await
+    }
+
+    async fn call_with(layer: &TenantLayer, request: Request) -> Response<Body> {
+        let service = layer.layer(service_fn(|request: Request| async move {
+            let tenant = request
+                .extensions()
+                .get::<Tenant>()
+                .map(Tenant::as_str)
+                .unwrap_or("none");
+            Ok::<_, Error>(tenant.to_string())
+        }));
+        service.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn find_tenants_of_hosts() {
+        let layer = TenantLayer::subdomain("example.com").domain("API.Acme.com", "acme");
+        for (host, tenant) in [
+            ("globex.example.com", "globex"),
+            ("api.initech.example.com:443", "initech"),
+            ("api.acme.com", "acme"),
+        ] {
+            let response = call(&layer, host).await;
+            assert_eq!(&Body::from(tenant), response.body(), "{host}");
+        }
+
+        let response = call(&layer, "example.com").await;
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        let response = call(&layer, "evil-example.com").await;
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+    }
+
+    #[tokio::test]
+    async fn fall_back_to_default_tenants() {
+        let layer = TenantLayer::subdomain("example.com").fallback(TenantFallback::Default("public".into()));
+        assert_eq!(&Body::from("public"), call(&layer, "example.com").await.body());
+
+        let layer = TenantLayer::new().fallback(TenantFallback::Pass);
+        assert_eq!(&Body::from("none"), call(&layer, "globex.example.com").await.body());
+    }
+
+    #[cfg(feature = "apigw_http")]
+    #[tokio::test]
+    async fn ignore_forged_forwarded_hosts() {
+        let event = r#"{
+            "version": "2.0",
+            "rawPath": "/",
+            "headers": { "host": "globex.example.com", "x-forwarded-host": "acme.example.com" },
+            "requestContext": { "domainName": "globex.example.com", "http": { "method": "GET" } }
+        }"#;
+        let request = || crate::request::from_str(event).unwrap();
+        let layer = TenantLayer::subdomain("example.com");
+        assert_eq!(&Body::from("globex"), call_with(&layer, request()).await.body());
+
+        let layer = layer.trust_forwarded_host(true);
+        assert_eq!(&Body::from("acme"), call_with(&layer, request()).await.body());
+    }
+}
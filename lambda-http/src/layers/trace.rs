@@ -0,0 +1,257 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{self, Poll},
+    time::Instant,
+};
+
+use crate::{
+    ext::extract::route_template,
+    response::ResponseFuture,
+    tower::{Layer, Service},
+    tracing::{field, Span},
+    Body, IntoResponse, Request, RequestExt, Response,
+};
+
+/// Layer that creates a tracing span for each HTTP request, as a child of the span of the
+/// invocation that the runtime creates.
+///
+/// The span records the method of the request, the template of its route rather than its path,
+/// so that paths like `/users/42` don't create a new series of metrics for each user, the status
+/// code and latency of the response, and the request id of API Gateway. The route template is
+/// the resource of REST APIs, the route key of HTTP APIs, or the route matched by the
+/// [`Router`](crate::router::Router); requests from ALB and Function URLs have none unless they
+/// go through a router.
+///
+/// ```rust,no_run
+/// use lambda_http::{layers::TraceLayer, service_fn, tower::ServiceBuilder, Error, Request};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     lambda_http::tracing::init_default_subscriber();
+///     let handler = ServiceBuilder::new()
+///         .layer(TraceLayer::new())
+///         .service(service_fn(|_request: Request| async { Ok::<_, Error>("hello") }));
+///     lambda_http::run(handler).await
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraceLayer {}
+
+impl TraceLayer {
+    /// Create a new tracing layer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for TraceLayer {
+    type Service = TraceService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TraceService { inner }
+    }
+}
+
+/// Service that creates a tracing span for the requests of the inner service, created by
+/// [`TraceLayer`].
+#[derive(Debug, Clone)]
+pub struct TraceService<S> {
+    inner: S,
+}
+
+impl<S> Service<Request> for TraceService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = TraceFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let span = request_span(&request);
+        let future = {
+            // Routers called by the inner service record their route in the current span.
+            let _guard = span.enter();
+            self.inner.call(request)
+        };
+        TraceFuture {
+            span,
+            start: Instant::now(),
+            state: State::Inner { future },
+        }
+    }
+}
+
+/// Creates a tracing span for an HTTP request, with its method, route template, and the request
+/// id of API Gateway.
+fn request_span(request: &Request) -> Span {
+    let span = crate::tracing::info_span!(
+        "HTTP request",
+        httpMethod = request.method().as_str(),
+        httpRoute = field::Empty,
+        httpStatus = field::Empty,
+        latencyMs = field::Empty,
+        apiRequestId = field::Empty,
+    );
+    if let Some(template) = route_template(request) {
+        // Route keys of HTTP APIs start with the method, like `GET /users/{id}`.
+        let template = template.split_once(' ').map_or(template, |(_, path)| path);
+        span.record("httpRoute", template);
+    }
+    if let Some(request_id) = api_request_id(request) {
+        span.record("apiRequestId", request_id);
+    }
+    span
+}
+
+fn api_request_id(request: &Request) -> Option<&str> {
+    match request.request_context_ref()? {
+        #[cfg(feature = "apigw_rest")]
+        crate::request::RequestContext::ApiGatewayV1(context) => context.request_id.as_deref(),
+        #[cfg(feature = "apigw_http")]
+        crate::request::RequestContext::ApiGatewayV2(context) => context.request_id.as_deref(),
+        #[cfg(feature = "apigw_websockets")]
+        crate::request::RequestContext::WebSocket(context) => context.request_id.as_deref(),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Future returned by [`TraceService`].
+    pub struct TraceFuture<F> {
+        span: Span,
+        start: Instant,
+        #[pin]
+        state: State<F>,
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[project = StateProj]
+    enum State<F> {
+        Inner {
+            #[pin]
+            future: F,
+        },
+        // Conversion of the response of the inner service.
+        Response {
+            response: ResponseFuture,
+        },
+    }
+}
+
+impl<F, R, E> Future for TraceFuture<F>
+where
+    F: Future<Output = Result<R, E>>,
+    R: IntoResponse,
+{
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let _guard = this.span.enter();
+        let mut state = this.state;
+        loop {
+            match state.as_mut().project() {
+                StateProj::Inner { future } => {
+                    let response = match task::ready!(future.poll(cx)) {
+                        Ok(response) => response.into_response(),
+                        Err(err) => {
+                            this.span.record("latencyMs", this.start.elapsed().as_millis() as u64);
+                            return Poll::Ready(Err(err));
+                        }
+                    };
+                    state.set(State::Response { response });
+                }
+                StateProj::Response { response } => {
+                    let response = task::ready!(response.as_mut().poll(cx));
+                    this.span.record("httpStatus", response.status().as_u16());
+                    this.span.record("latencyMs", this.start.elapsed().as_millis() as u64);
+                    return Poll::Ready(Ok(response));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    use super::*;
+    use crate::{
+        service_fn,
+        tower::ServiceExt,
+        tracing::subscriber::{self, fmt::format::FmtSpan, util::SubscriberInitExt},
+        Error,
+    };
+
+    #[derive(Clone, Default)]
+    struct Logs(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Logs {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    async fn trace<S>(service: S, request: Request) -> String
+    where
+        S: Service<Request, Error = Error>,
+        S::Response: IntoResponse,
+    {
+        let logs = Logs::default();
+        let writer = logs.clone();
+        let subscriber = subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_span_events(FmtSpan::CLOSE)
+            .with_ansi(false)
+            .finish();
+        let _guard = subscriber.set_default();
+        TraceLayer::new().layer(service).oneshot(request).await.unwrap();
+        let logs = logs.0.lock().unwrap();
+        String::from_utf8(logs.clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn record_route_templates() {
+        let request = crate::request::from_str(include_str!("../../tests/data/apigw_v2_proxy_request.json")).unwrap();
+        let logs = trace(
+            service_fn(|_request: Request| async { Ok::<_, Error>("hello") }),
+            request,
+        )
+        .await;
+        assert!(logs.contains("httpMethod=\"POST\""), "{logs}");
+        assert!(logs.contains("httpRoute=\"$default\""), "{logs}");
+        assert!(logs.contains("httpStatus=200"), "{logs}");
+        assert!(logs.contains("apiRequestId=\"id\""), "{logs}");
+        assert!(logs.contains("latencyMs="), "{logs}");
+    }
+
+    #[cfg(feature = "router")]
+    #[tokio::test]
+    async fn record_routes_of_routers() {
+        let router = crate::router::Router::new().get(
+            "/users/{id}",
+            service_fn(|_request: Request| async { Ok::<_, Error>("user") }),
+        );
+        let request = http::Request::builder().uri("/users/42").body(Body::Empty).unwrap();
+        let logs = trace(router, request).await;
+        assert!(logs.contains("httpRoute=\"/users/{id}\""), "{logs}");
+        assert!(!logs.contains("/users/42"), "{logs}");
+    }
+}
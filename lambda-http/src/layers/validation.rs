@@ -0,0 +1,690 @@
+use std::{
+    error, fmt,
+    sync::Arc,
+    task::{self, Poll},
+};
+
+use http::{Method, StatusCode};
+use jsonschema::Validator;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    error::Problem,
+    ext::extract::route_template,
+    layers::RejectionFuture,
+    tower::{Layer, Service},
+    Body, IntoResponse, Request, RequestExt, Response,
+};
+
+/// JSON Schemas of the path parameters, query string parameters, and JSON body of requests,
+/// validated by a [`ValidationLayer`].
+///
+/// Path and query string parameters are validated as an object with a property for each
+/// parameter. Their values are strings, unless the schema of the property expects a number, a
+/// boolean, or an array, which parameters with several values are.
+#[derive(Debug, Clone, Default)]
+pub struct RequestSchema {
+    path: Option<Value>,
+    query: Option<Value>,
+    body: Option<Value>,
+    body_required: bool,
+}
+
+impl RequestSchema {
+    /// Create an empty schema, which accepts any request.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the schema of the object of the path parameters.
+    pub fn path(mut self, schema: Value) -> Self {
+        self.path = Some(schema);
+        self
+    }
+
+    /// Set the schema of the object of the query string parameters.
+    pub fn query(mut self, schema: Value) -> Self {
+        self.query = Some(schema);
+        self
+    }
+
+    /// Set the schema of the JSON body. Requests without a body are validated as `null`.
+    pub fn body(mut self, schema: Value) -> Self {
+        self.body = Some(schema);
+        self.body_required = true;
+        self
+    }
+
+    fn compile(self) -> Result<Schemas, SchemaError> {
+        let compile = |schema: Option<Value>| -> Result<_, SchemaError> {
+            match schema {
+                Some(schema) => {
+                    let validator = jsonschema::validator_for(&schema).map_err(|err| SchemaError(err.to_string()))?;
+                    Ok(Some((schema, validator)))
+                }
+                None => Ok(None),
+            }
+        };
+        Ok(Schemas {
+            path: compile(self.path)?,
+            query: compile(self.query)?,
+            body: compile(self.body)?,
+            body_required: self.body_required,
+        })
+    }
+}
+
+/// Error returned when a JSON Schema or an OpenAPI document can't be compiled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError(String);
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid schema: {}", self.0)
+    }
+}
+
+impl error::Error for SchemaError {}
+
+/// Layer that validates the path parameters, query string parameters, and JSON body of requests
+/// against JSON Schemas, and rejects invalid requests with a `400 Bad Request` response.
+///
+/// Schemas are compiled when the layer is created, during the initialization of the function,
+/// from a [`RequestSchema`] for every request, or from the operations of an OpenAPI 3 document.
+/// The response is an `application/problem+json` document whose `errors` member lists each
+/// violation, with the `location` of the value, a JSON `pointer` to it, and a `message`.
+///
+/// ```rust,no_run
+/// use lambda_http::{
+///     layers::{RequestSchema, ValidationLayer},
+///     service_fn,
+///     tower::ServiceBuilder,
+///     Error, Request,
+/// };
+/// use serde_json::json;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let schema = RequestSchema::new().body(json!({
+///         "type": "object",
+///         "required": ["name"],
+///         "properties": { "name": { "type": "string" } },
+///     }));
+///     let handler = ServiceBuilder::new()
+///         .layer(ValidationLayer::new(schema)?)
+///         .service(service_fn(|_request: Request| async { Ok::<_, Error>("created") }));
+///     lambda_http::run(handler).await
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ValidationLayer {
+    operations: Arc<Vec<Operation>>,
+}
+
+impl ValidationLayer {
+    /// Create a layer that validates every request against a schema.
+    pub fn new(schema: RequestSchema) -> Result<Self, SchemaError> {
+        let operation = Operation {
+            method: None,
+            template: None,
+            schemas: schema.compile()?,
+        };
+        Ok(Self {
+            operations: Arc::new(vec![operation]),
+        })
+    }
+
+    /// Create a layer that validates the requests of the operations of an OpenAPI 3 document,
+    /// with the schemas of their parameters and of their `application/json` request body.
+    ///
+    /// Operations are matched by the route template of requests, from the
+    /// [`Router`](crate::router::Router) or API Gateway, or else by their path. References to
+    /// `#/components` are resolved in the document, and requests of other operations aren't
+    /// validated.
+    ///
+    /// Schemas of OpenAPI 3.0 documents are translated to JSON Schema, which OpenAPI 3.1 uses:
+    /// `nullable` adds `null` to their types, and boolean `exclusiveMinimum` and
+    /// `exclusiveMaximum` apply to their `minimum` and `maximum`.
+    pub fn from_openapi(document: &Value) -> Result<Self, SchemaError> {
+        let translated;
+        let document = if document["openapi"]
+            .as_str()
+            .is_some_and(|version| version.starts_with("3.0"))
+        {
+            translated = translate_openapi_30(document.clone());
+            &translated
+        } else {
+            document
+        };
+        let paths = document["paths"]
+            .as_object()
+            .ok_or_else(|| SchemaError("the OpenAPI document has no paths".to_string()))?;
+        let components = &document["components"];
+        let mut operations = Vec::new();
+        for (template, item) in paths {
+            let Some(item) = item.as_object() else {
+                continue;
+            };
+            let shared = item.get("parameters");
+            for (method, operation) in item {
+                // Path items also have fields like `parameters` and `summary`.
+                const METHODS: [&str; 8] = ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+                if !METHODS.contains(&method.as_str()) || !operation.is_object() {
+                    continue;
+                }
+                let method = method
+                    .to_uppercase()
+                    .parse::<Method>()
+                    .expect("operation methods are valid");
+                let parameters = shared
+                    .into_iter()
+                    .chain(operation.get("parameters"))
+                    .filter_map(Value::as_array)
+                    .flatten();
+                let schema = openapi_schema(parameters, operation, components);
+                operations.push(Operation {
+                    method: Some(method),
+                    template: Some(template.clone()),
+                    schemas: schema.compile()?,
+                });
+            }
+        }
+        Ok(Self {
+            operations: Arc::new(operations),
+        })
+    }
+}
+
+impl fmt::Debug for ValidationLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValidationLayer")
+            .field("operations", &self.operations.len())
+            .finish()
+    }
+}
+
+impl<S> Layer<S> for ValidationLayer {
+    type Service = ValidationService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ValidationService {
+            inner,
+            operations: self.operations.clone(),
+        }
+    }
+}
+
+/// Service that validates requests against JSON Schemas before the inner service gets them,
+/// created by [`ValidationLayer`].
+#[derive(Clone)]
+pub struct ValidationService<S> {
+    inner: S,
+    operations: Arc<Vec<Operation>>,
+}
+
+impl<S: fmt::Debug> fmt::Debug for ValidationService<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValidationService").field("inner", &self.inner).finish()
+    }
+}
+
+impl<S> Service<Request> for ValidationService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = ValidationFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let violations = self
+            .operations
+            .iter()
+            .find_map(|operation| {
+                operation
+                    .matches(&request)
+                    .map(|params| operation.validate(&request, params))
+            })
+            .unwrap_or_default();
+        if !violations.is_empty() {
+            return ValidationFuture::rejected(rejection(violations));
+        }
+        ValidationFuture::inner(self.inner.call(request))
+    }
+}
+
+/// Future returned by [`ValidationService`].
+pub type ValidationFuture<F> = RejectionFuture<F>;
+
+/// Compiled schemas, with their source to coerce the types of parameters.
+struct Schemas {
+    path: Option<(Value, Validator)>,
+    query: Option<(Value, Validator)>,
+    body: Option<(Value, Validator)>,
+    body_required: bool,
+}
+
+struct Operation {
+    method: Option<Method>,
+    template: Option<String>,
+    schemas: Schemas,
+}
+
+/// Value of a request that breaks its schema.
+struct Violation {
+    location: &'static str,
+    pointer: String,
+    message: String,
+}
+
+impl Operation {
+    /// Whether the operation handles the request, with the path parameters matched by its
+    /// template.
+    fn matches(&self, request: &Request) -> Option<Vec<(String, String)>> {
+        if self.method.as_ref().is_some_and(|method| method != request.method()) {
+            return None;
+        }
+        let Some(template) = &self.template else {
+            return Some(Vec::new());
+        };
+        if let Some(route) = route_template(request) {
+            // Route keys of HTTP APIs start with the method, like `GET /users/{id}`.
+            let route = route.split_once(' ').map_or(route, |(_, path)| path);
+            return (route == template).then(Vec::new);
+        }
+        let path = match request.raw_http_path() {
+            "" => request.uri().path(),
+            path => path,
+        };
+        match_template(template, path)
+    }
+
+    fn validate(&self, request: &Request, matched: Vec<(String, String)>) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        if let Some((schema, validator)) = &self.schemas.path {
+            let params: Vec<(String, String)> = match request.path_parameters_ref() {
+                Some(parameters) => parameters
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+                None => matched,
+            };
+            let instance = coerce(schema, params);
+            check(validator, &instance, "path", &mut violations);
+        }
+        if let Some((schema, validator)) = &self.schemas.query {
+            let params: Vec<(String, String)> = match request.query_string_parameters_ref() {
+                Some(parameters) => parameters
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+                None => {
+                    let query = request.uri().query().unwrap_or_default();
+                    serde_urlencoded::from_str(query).unwrap_or_default()
+                }
+            };
+            let instance = coerce(schema, params);
+            check(validator, &instance, "query", &mut violations);
+        }
+        if let Some((_, validator)) = &self.schemas.body {
+            let body = request.body().as_ref();
+            if body.is_empty() && !self.schemas.body_required {
+                return violations;
+            }
+            let instance = if body.is_empty() {
+                Ok(Value::Null)
+            } else {
+                serde_json::from_slice(body)
+            };
+            match instance {
+                Ok(instance) => check(validator, &instance, "body", &mut violations),
+                Err(err) => violations.push(Violation {
+                    location: "body",
+                    pointer: String::new(),
+                    message: format!("invalid JSON: {err}"),
+                }),
+            }
+        }
+        violations
+    }
+}
+
+fn check(validator: &Validator, instance: &Value, location: &'static str, violations: &mut Vec<Violation>) {
+    violations.extend(validator.iter_errors(instance).map(|err| Violation {
+        location,
+        pointer: err.instance_path.to_string(),
+        message: err.to_string(),
+    }));
+}
+
+/// Object of parameters, with the types that the schema of each property expects.
+fn coerce(schema: &Value, params: Vec<(String, String)>) -> Value {
+    let mut object = Map::new();
+    for (name, value) in params {
+        let property = &schema["properties"][&name];
+        let types = |property: &Value| -> Vec<String> {
+            match &property["type"] {
+                Value::String(ty) => vec![ty.clone()],
+                Value::Array(types) => types.iter().filter_map(Value::as_str).map(str::to_string).collect(),
+                _ => Vec::new(),
+            }
+        };
+        if types(property).iter().any(|ty| ty == "array") {
+            let item = scalar(&types(&property["items"]), value);
+            match object.entry(name).or_insert_with(|| Value::Array(Vec::new())) {
+                Value::Array(items) => items.push(item),
+                _ => unreachable!("parameters with an array schema are arrays"),
+            }
+        } else {
+            // The first value of a parameter wins, like `QueryMap::first`.
+            let value = scalar(&types(property), value);
+            object.entry(name).or_insert(value);
+        }
+    }
+    Value::Object(object)
+}
+
+fn scalar(types: &[String], value: String) -> Value {
+    for ty in types {
+        let coerced = match ty.as_str() {
+            "integer" => value.parse::<i64>().ok().map(Value::from),
+            "number" => value.parse::<f64>().ok().map(Value::from),
+            "boolean" => value.parse::<bool>().ok().map(Value::from),
+            _ => None,
+        };
+        if let Some(coerced) = coerced {
+            return coerced;
+        }
+    }
+    Value::String(value)
+}
+
+/// Path parameters of a path that matches an OpenAPI template, like `/users/{id}`.
+fn match_template(template: &str, path: &str) -> Option<Vec<(String, String)>> {
+    let template: Vec<_> = template.trim_matches('/').split('/').collect();
+    let path: Vec<_> = path.trim_matches('/').split('/').collect();
+    if template.len() != path.len() {
+        return None;
+    }
+    let mut params = Vec::new();
+    for (segment, value) in template.into_iter().zip(path) {
+        match segment.strip_prefix('{').and_then(|name| name.strip_suffix('}')) {
+            Some(name) => {
+                let value = percent_encoding::percent_decode_str(value).decode_utf8_lossy();
+                params.push((name.to_string(), value.into_owned()));
+            }
+            None if segment == value => {}
+            None => return None,
+        }
+    }
+    Some(params)
+}
+
+/// Schemas of the parameters and the JSON body of an OpenAPI operation.
+fn openapi_schema<'a>(
+    parameters: impl Iterator<Item = &'a Value>,
+    operation: &Value,
+    components: &Value,
+) -> RequestSchema {
+    let mut path = (Map::new(), Vec::new());
+    let mut query = (Map::new(), Vec::new());
+    for parameter in parameters {
+        let (properties, required) = match parameter["in"].as_str() {
+            Some("path") => &mut path,
+            Some("query") => &mut query,
+            _ => continue,
+        };
+        let Some(name) = parameter["name"].as_str() else {
+            continue;
+        };
+        properties.insert(name.to_string(), parameter.get("schema").cloned().unwrap_or(json!({})));
+        if parameter["required"].as_bool().unwrap_or(false) {
+            required.push(Value::from(name));
+        }
+    }
+
+    // The schemas keep the components of the document, so that their references resolve.
+    let with_components = |mut schema: Value| {
+        if let (Value::Object(object), false) = (&mut schema, components.is_null()) {
+            object.insert("components".to_string(), components.clone());
+        }
+        schema
+    };
+    let object = |(properties, required): (Map<String, Value>, Vec<Value>)| {
+        (!properties.is_empty())
+            .then(|| with_components(json!({ "type": "object", "properties": properties, "required": required })))
+    };
+
+    let body = &operation["requestBody"];
+    RequestSchema {
+        path: object(path),
+        query: object(query),
+        body: body["content"]["application/json"]
+            .get("schema")
+            .cloned()
+            .map(with_components),
+        body_required: body["required"].as_bool().unwrap_or(false),
+    }
+}
+
+/// Translate the schemas of an OpenAPI 3.0 document to JSON Schema.
+fn translate_openapi_30(mut value: Value) -> Value {
+    match &mut value {
+        Value::Object(object) => {
+            if let Some(Value::Bool(nullable)) = object.remove("nullable") {
+                let nullable = nullable && object.contains_key("type");
+                match object.get_mut("type") {
+                    Some(Value::Array(types)) if nullable => types.push(Value::from("null")),
+                    Some(ty) if nullable => *ty = json!([ty.take(), "null"]),
+                    _ => {}
+                }
+                if let (true, Some(Value::Array(values))) = (nullable, object.get_mut("enum")) {
+                    values.push(Value::Null);
+                }
+            }
+            for (exclusive, bound) in [("exclusiveMinimum", "minimum"), ("exclusiveMaximum", "maximum")] {
+                if let Some(&Value::Bool(enabled)) = object.get(exclusive) {
+                    object.remove(exclusive);
+                    if let Some(limit) = enabled.then(|| object.remove(bound)).flatten() {
+                        object.insert(exclusive.to_string(), limit);
+                    }
+                }
+            }
+            for (key, child) in object.iter_mut() {
+                // Examples and values are data, not schemas.
+                if !matches!(key.as_str(), "example" | "examples" | "default" | "enum" | "const") {
+                    *child = translate_openapi_30(child.take());
+                }
+            }
+        }
+        Value::Array(values) => {
+            for child in values {
+                *child = translate_openapi_30(child.take());
+            }
+        }
+        _ => {}
+    }
+    value
+}
+
+fn rejection(violations: Vec<Violation>) -> Response<Body> {
+    let errors: Vec<_> = violations
+        .into_iter()
+        .map(|violation| {
+            json!({
+                "location": violation.location,
+                "pointer": violation.pointer,
+                "message": violation.message,
+            })
+        })
+        .collect();
+    Problem::new(StatusCode::BAD_REQUEST)
+        .detail("the request doesn't match its schema")
+        .extension("errors", errors)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::CONTENT_TYPE;
+
+    use super::*;
+    use crate::{service_fn, tower::ServiceExt, Error};
+
+    async fn call(layer: &ValidationLayer, request: Request) -> Response<Body> {
+        let service = layer.layer(service_fn(|_request: Request| async { Ok::<_, Error>("ok") }));
+        service.oneshot(request).await.unwrap()
+    }
+
+    fn errors(response: &Response<Body>) -> Vec<(String, String)> {
+        let problem: Value = serde_json::from_slice(response.body().as_ref()).unwrap();
+        problem["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|error| {
+                (
+                    error["location"].as_str().unwrap().to_string(),
+                    error["pointer"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn validate_query_and_body() {
+        let schema = RequestSchema::new()
+            .query(json!({
+                "type": "object",
+                "properties": { "page": { "type": "integer", "minimum": 1 } },
+            }))
+            .body(json!({
+                "type": "object",
+                "required": ["name"],
+                "properties": { "name": { "type": "string" } },
+            }));
+        let layer = ValidationLayer::new(schema).unwrap();
+        let request = |uri: &str, body: &str| http::Request::builder().uri(uri).body(Body::from(body)).unwrap();
+
+        let response = call(&layer, request("/users?page=2", r#"{"name":"ferris"}"#)).await;
+        assert_eq!(StatusCode::OK, response.status());
+
+        let response = call(&layer, request("/users?page=0", r#"{"name":1}"#)).await;
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        assert_eq!("application/problem+json", response.headers()[CONTENT_TYPE]);
+        assert_eq!(
+            vec![("query".into(), "/page".into()), ("body".into(), "/name".into())],
+            errors(&response)
+        );
+
+        let response = call(&layer, request("/users", "{")).await;
+        assert_eq!(vec![("body".to_string(), String::new())], errors(&response));
+        assert!(ValidationLayer::new(RequestSchema::new().body(json!({ "type": 1 }))).is_err());
+    }
+
+    #[tokio::test]
+    async fn validate_openapi_operations() {
+        let document = json!({
+            "openapi": "3.0.3",
+            "paths": {
+                "/users/{id}": {
+                    "parameters": [
+                        { "name": "id", "in": "path", "required": true, "schema": { "type": "integer" } },
+                    ],
+                    "put": {
+                        "parameters": [
+                            { "name": "tags", "in": "query", "schema": { "type": "array", "items": { "type": "string" }, "maxItems": 2 } },
+                        ],
+                        "requestBody": {
+                            "required": true,
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/User" } } },
+                        },
+                    },
+                },
+            },
+            "components": {
+                "schemas": {
+                    "User": { "type": "object", "required": ["name"] },
+                },
+            },
+        });
+        let layer = ValidationLayer::from_openapi(&document).unwrap();
+        let request = |method: Method, uri: &str, body: &str| {
+            http::Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let response = call(
+            &layer,
+            request(Method::PUT, "/users/42?tags=a&tags=b", r#"{"name":"ferris"}"#),
+        )
+        .await;
+        assert_eq!(StatusCode::OK, response.status());
+
+        let response = call(&layer, request(Method::PUT, "/users/me?tags=a&tags=b&tags=c", "{}")).await;
+        assert_eq!(
+            vec![
+                ("path".into(), "/id".into()),
+                ("query".into(), "/tags".into()),
+                ("body".into(), String::new())
+            ],
+            errors(&response)
+        );
+
+        // Other operations aren't validated.
+        let response = call(&layer, request(Method::GET, "/users/me", "")).await;
+        assert_eq!(StatusCode::OK, response.status());
+    }
+
+    #[tokio::test]
+    async fn translate_openapi_30_schemas() {
+        let document = json!({
+            "openapi": "3.0.3",
+            "paths": {
+                "/orders": {
+                    "post": {
+                        "requestBody": {
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Order" } } },
+                        },
+                    },
+                },
+            },
+            "components": {
+                "schemas": {
+                    "Order": {
+                        "type": "object",
+                        "properties": {
+                            "note": { "type": "string", "nullable": true },
+                            "quantity": { "type": "integer", "minimum": 0, "exclusiveMinimum": true },
+                        },
+                    },
+                },
+            },
+        });
+        let layer = ValidationLayer::from_openapi(&document).unwrap();
+        let post = |body: &str| {
+            http::Request::builder()
+                .method(Method::POST)
+                .uri("/orders")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let response = call(&layer, post(r#"{"note":null,"quantity":1}"#)).await;
+        assert_eq!(StatusCode::OK, response.status());
+
+        let response = call(&layer, post(r#"{"note":1,"quantity":0}"#)).await;
+        assert_eq!(
+            vec![("body".into(), "/note".into()), ("body".into(), "/quantity".into())],
+            errors(&response)
+        );
+    }
+}
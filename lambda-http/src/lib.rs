@@ -75,13 +75,47 @@ pub use lambda_runtime::{self, service_fn, tower, Context, Error, LambdaEvent, S
 use request::RequestFuture;
 use response::ResponseFuture;
 
+/// Static assets embedded in the binary of functions.
+#[cfg(feature = "static-assets")]
+#[cfg_attr(docsrs, doc(cfg(feature = "static-assets")))]
+pub mod assets;
+pub mod conditional;
+pub mod cookies;
 mod deserializer;
+pub mod error;
 pub mod ext;
+/// GraphQL endpoints backed by a schema.
+#[cfg(feature = "graphql")]
+#[cfg_attr(docsrs, doc(cfg(feature = "graphql")))]
+pub mod graphql;
+pub mod html;
+pub mod layers;
+/// Parsing of `multipart/form-data` request bodies.
+#[cfg(feature = "multipart")]
+#[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+pub mod multipart;
+pub mod negotiate;
+pub mod range;
+pub mod redirect;
 pub mod request;
 mod response;
+#[cfg(feature = "router")]
+#[cfg_attr(docsrs, doc(cfg(feature = "router")))]
+pub mod router;
+mod service;
+#[cfg(feature = "sse")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sse")))]
+pub mod sse;
+#[cfg(feature = "apigw_websockets")]
+#[cfg_attr(docsrs, doc(cfg(feature = "apigw_websockets")))]
+pub mod websocket;
+#[cfg(feature = "alb")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alb")))]
+pub use crate::response::AlbHeaderMode;
 pub use crate::{
-    ext::{RequestExt, RequestPayloadExt},
-    response::IntoResponse,
+    ext::{RequestExt, RequestExtractExt, RequestPayloadExt},
+    response::{BinaryPolicy, IntoResponse, ResponseFormat},
+    service::{run_service, run_service_with_streaming_response, IncomingBody, ServiceAdapter, ServiceAdapterFuture},
 };
 use crate::{
     request::{LambdaRequest, RequestOrigin},
@@ -94,17 +128,20 @@ use crate::{
 pub use aws_lambda_events;
 
 pub use aws_lambda_events::encodings::Body;
+use http::{header::CONTENT_LENGTH, Method, StatusCode};
 use std::{
     future::Future,
     marker::PhantomData,
     pin::Pin,
+    sync::Arc,
     task::{Context as TaskContext, Poll},
 };
 
 mod streaming;
+pub use lambda_runtime::streaming::Sender as StreamSender;
 #[cfg(feature = "concurrency-tokio")]
 pub use streaming::run_with_streaming_response_concurrent;
-pub use streaming::{run_with_streaming_response, StreamAdapter};
+pub use streaming::{run_with_streaming_response, StreamAdapter, StreamBody};
 
 /// Type alias for `http::Request`s with a fixed [`Body`](enum.Body.html) type
 pub type Request = http::Request<Body>;
@@ -115,10 +152,26 @@ pub type Request = http::Request<Body>;
 #[non_exhaustive]
 #[doc(hidden)]
 pub enum TransformResponse<'a, R, E> {
-    Request(RequestOrigin, RequestFuture<'a, R, E>),
+    Request(
+        RequestOrigin,
+        Arc<BinaryPolicy>,
+        ResponseOptions,
+        RequestFuture<'a, R, E>,
+    ),
     Response(RequestOrigin, ResponseFuture),
 }
 
+/// How [`TransformResponse`] converts the response of a request.
+#[derive(Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+#[doc(hidden)]
+pub struct ResponseOptions {
+    /// Strip the body of responses to `HEAD` requests handled as `GET` requests.
+    pub strip_body: bool,
+    /// Replace responses that don't fit their format with `500 Internal Server Error` responses.
+    pub strict_format: bool,
+}
+
 impl<R, E> Future for TransformResponse<'_, R, E>
 where
     R: IntoResponse,
@@ -127,14 +180,24 @@ where
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
         match *self {
-            TransformResponse::Request(ref mut origin, ref mut request) => match request.as_mut().poll(cx) {
-                Poll::Ready(Ok(resp)) => {
-                    *self = TransformResponse::Response(origin.clone(), resp.into_response());
-                    self.poll(cx)
+            TransformResponse::Request(ref mut origin, ref policy, options, ref mut request) => {
+                match request.as_mut().poll(cx) {
+                    Poll::Ready(Ok(resp)) => {
+                        let mut response = resp.into_response_with_policy(policy.clone());
+                        if options.strip_body {
+                            response = Box::pin(async move { strip_body(response.await) });
+                        }
+                        if options.strict_format {
+                            let origin = origin.clone();
+                            response = Box::pin(async move { check_response_format(&origin, response.await) });
+                        }
+                        *self = TransformResponse::Response(origin.clone(), response);
+                        self.poll(cx)
+                    }
+                    Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+                    Poll::Pending => Poll::Pending,
                 }
-                Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
-                Poll::Pending => Poll::Pending,
-            },
+            }
             TransformResponse::Response(ref mut origin, ref mut response) => match response.as_mut().poll(cx) {
                 Poll::Ready(resp) => Poll::Ready(Ok(LambdaResponse::from_response(origin, resp))),
                 Poll::Pending => Poll::Pending,
@@ -145,14 +208,86 @@ where
 
 /// Wraps a `Service<Request>` in a `Service<LambdaEvent<Request>>`
 ///
-/// This is completely internal to the `lambda_http::run` function.
+/// [`run`] wraps handlers in an adapter. Create one to configure how responses are converted,
+/// and run it with a [`Runtime`](lambda_runtime::Runtime).
 #[non_exhaustive]
-#[doc(hidden)]
 pub struct Adapter<'a, R, S> {
     service: S,
+    binary_policy: Arc<BinaryPolicy>,
+    #[cfg(feature = "alb")]
+    alb_header_mode: AlbHeaderMode,
+    response_format: ResponseFormat,
+    strict_response_format: bool,
+    method_override: bool,
+    head_as_get: bool,
+    trust_forwarded_headers: bool,
     _phantom_data: PhantomData<&'a R>,
 }
 
+impl<R, S> Adapter<'_, R, S> {
+    /// Set the policy that decides which response bodies are encoded in base64.
+    pub fn binary_policy(mut self, policy: BinaryPolicy) -> Self {
+        self.binary_policy = Arc::new(policy);
+        self
+    }
+
+    /// Set the header mode of responses to ALB target groups, instead of using the mode of
+    /// the requests.
+    #[cfg(feature = "alb")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alb")))]
+    pub fn alb_header_mode(mut self, mode: AlbHeaderMode) -> Self {
+        self.alb_header_mode = mode;
+        self
+    }
+
+    /// Return responses in a format, instead of the format of the payload of their request.
+    pub fn response_format(mut self, format: ResponseFormat) -> Self {
+        self.response_format = format;
+        self
+    }
+
+    /// Fail invocations whose responses use features that their format can't represent, like
+    /// multiple values of a header in ALB responses without multi-value headers, or cookies
+    /// that aren't valid strings in payload format 2.0, instead of dropping them. Disabled by
+    /// default.
+    ///
+    /// Responses that don't fit are logged and replaced with `500 Internal Server Error`
+    /// responses, since the errors of handlers don't have a variant for them.
+    pub fn strict_response_format(mut self, enable: bool) -> Self {
+        self.strict_response_format = enable;
+        self
+    }
+
+    /// Use the method of the `X-HTTP-Method-Override` header of `POST` requests, for clients
+    /// that can only send `GET` and `POST` requests. Disabled by default.
+    ///
+    /// The original method isn't kept, so only enable it for handlers that authorize requests
+    /// by the method that they get.
+    pub fn method_override(mut self, enable: bool) -> Self {
+        self.method_override = enable;
+        self
+    }
+
+    /// Handle `HEAD` requests as `GET` requests, and remove the body of their responses while
+    /// keeping its `Content-Length`, so handlers don't need routes for `HEAD` requests.
+    /// Disabled by default.
+    pub fn head_as_get(mut self, enable: bool) -> Self {
+        self.head_as_get = enable;
+        self
+    }
+
+    /// Build the [base URL](RequestExtractExt::base_url) of requests and the locations of
+    /// [redirects](redirect::Redirect) with the `X-Forwarded-Host` and `X-Forwarded-Proto`
+    /// headers. Disabled by default.
+    ///
+    /// Clients can send any value in these headers, so only enable it for functions that are
+    /// only reachable through a proxy that replaces them, like a CloudFront distribution.
+    pub fn trust_forwarded_headers(mut self, enable: bool) -> Self {
+        self.trust_forwarded_headers = enable;
+        self
+    }
+}
+
 impl<'a, R, S> Clone for Adapter<'a, R, S>
 where
     S: Clone,
@@ -160,6 +295,14 @@ where
     fn clone(&self) -> Self {
         Self {
             service: self.service.clone(),
+            binary_policy: self.binary_policy.clone(),
+            #[cfg(feature = "alb")]
+            alb_header_mode: self.alb_header_mode,
+            response_format: self.response_format,
+            strict_response_format: self.strict_response_format,
+            method_override: self.method_override,
+            head_as_get: self.head_as_get,
+            trust_forwarded_headers: self.trust_forwarded_headers,
             _phantom_data: PhantomData,
         }
     }
@@ -174,6 +317,14 @@ where
     fn from(service: S) -> Self {
         Adapter {
             service,
+            binary_policy: Arc::default(),
+            #[cfg(feature = "alb")]
+            alb_header_mode: AlbHeaderMode::default(),
+            response_format: ResponseFormat::default(),
+            strict_response_format: false,
+            method_override: false,
+            head_as_get: false,
+            trust_forwarded_headers: false,
             _phantom_data: PhantomData,
         }
     }
@@ -195,12 +346,28 @@ where
 
     fn call(&mut self, req: LambdaEvent<LambdaRequest>) -> Self::Future {
         let LambdaEvent { payload, context } = req;
-        let request_origin = payload.request_origin();
+        let request_origin = payload.request_origin().with_response_format(self.response_format);
+        #[cfg(feature = "alb")]
+        let request_origin = request_origin.with_alb_header_mode(self.alb_header_mode);
         let mut event: Request = payload.into();
         update_xray_trace_id_header(event.headers_mut(), &context);
+        if self.method_override {
+            override_method(&mut event);
+        }
+        if self.trust_forwarded_headers {
+            event.extensions_mut().insert(redirect::TrustForwardedHeaders);
+        }
+        let head = self.head_as_get && event.method() == Method::HEAD;
+        if head {
+            *event.method_mut() = Method::GET;
+        }
         let fut = Box::pin(self.service.call(event.with_lambda_context(context)));
 
-        TransformResponse::Request(request_origin, fut)
+        let options = ResponseOptions {
+            strip_body: head,
+            strict_format: self.strict_response_format,
+        };
+        TransformResponse::Request(request_origin, self.binary_policy.clone(), options, fut)
     }
 }
 
@@ -265,6 +432,43 @@ where
     lambda_runtime::run_concurrent(Adapter::from(handler)).await
 }
 
+fn override_method(request: &mut Request) {
+    if request.method() != Method::POST {
+        return;
+    }
+    let method = request
+        .headers()
+        .get("x-http-method-override")
+        .and_then(|value| Method::from_bytes(value.as_bytes()).ok());
+    // Overrides can't turn requests into tunnels or echo their headers.
+    if let Some(method) = method.filter(|method| *method != Method::CONNECT && *method != Method::TRACE) {
+        *request.method_mut() = method;
+    }
+}
+
+fn strip_body(mut response: Response<Body>) -> Response<Body> {
+    let length = response.body().as_ref().len();
+    if length > 0 && !response.headers().contains_key(CONTENT_LENGTH) {
+        response.headers_mut().insert(CONTENT_LENGTH, length.into());
+    }
+    *response.body_mut() = Body::Empty;
+    response
+}
+
+fn check_response_format(origin: &RequestOrigin, response: Response<Body>) -> Response<Body> {
+    let Some(feature) = origin.unsupported_feature(&response) else {
+        return response;
+    };
+    #[cfg(feature = "tracing")]
+    crate::tracing::error!(feature, "the response uses a feature that its format doesn't support");
+    // Without tracing, the error still reaches the logs of the function.
+    #[cfg(not(feature = "tracing"))]
+    eprintln!("the response uses {feature}, which its format doesn't support");
+    let mut response = Response::new(Body::Empty);
+    *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+    response
+}
+
 // In concurrent mode we must use the per-request context.
 fn update_xray_trace_id_header(headers: &mut http::HeaderMap, context: &Context) {
     if let Some(trace_id) = context.xray_trace_id.as_deref() {
@@ -328,4 +532,130 @@ mod test_adapter {
             .service_fn(|_event: Request| async move { Response::builder().status(StatusCode::OK).body(Body::Empty) })
             .boxed();
     }
+
+    #[cfg(feature = "apigw_http")]
+    #[tokio::test]
+    async fn method_override_and_head_requests() {
+        let event = |method: &str, headers: &str| {
+            let event = format!(
+                r#"{{"version":"2.0","rawPath":"/","headers":{{{headers}}},"requestContext":{{"http":{{"method":"{method}"}}}}}}"#
+            );
+            LambdaEvent::new(
+                serde_json::from_str::<LambdaRequest>(&event).unwrap(),
+                crate::Context::default(),
+            )
+        };
+        let handler = crate::service_fn(|request: Request| async move {
+            Response::builder().body(Body::from(request.method().to_string()))
+        });
+        let body = |response: LambdaResponse| match response {
+            LambdaResponse::ApiGatewayV2(response) => (response.body, response.headers),
+            _ => panic!("unexpected response"),
+        };
+
+        let mut adapter = Adapter::from(handler).method_override(true).head_as_get(true);
+        let response = adapter
+            .call(event("POST", r#""x-http-method-override":"DELETE""#))
+            .await
+            .unwrap();
+        assert_eq!(Some(Body::from("DELETE")), body(response).0);
+
+        let response = adapter
+            .call(event("POST", r#""x-http-method-override":"TRACE""#))
+            .await
+            .unwrap();
+        assert_eq!(Some(Body::from("POST")), body(response).0);
+
+        let response = adapter.call(event("HEAD", "")).await.unwrap();
+        let (body, headers) = body(response);
+        assert_eq!(None, body);
+        assert_eq!("3", headers[http::header::CONTENT_LENGTH]);
+
+        let mut adapter = Adapter::from(handler);
+        let response = adapter
+            .call(event("POST", r#""x-http-method-override":"DELETE""#))
+            .await
+            .unwrap();
+        assert!(
+            matches!(response, LambdaResponse::ApiGatewayV2(response) if response.body == Some(Body::from("POST")))
+        );
+    }
+
+    #[cfg(all(feature = "apigw_rest", feature = "apigw_http"))]
+    #[tokio::test]
+    async fn forced_and_strict_response_formats() {
+        let event = || {
+            let event = r#"{"version":"2.0","rawPath":"/","requestContext":{"http":{"method":"GET"}}}"#;
+            LambdaEvent::new(
+                serde_json::from_str::<LambdaRequest>(event).unwrap(),
+                crate::Context::default(),
+            )
+        };
+        let handler = crate::service_fn(|_request: Request| async {
+            Response::builder()
+                .header("vary", "accept")
+                .header("vary", "origin")
+                .body(Body::Empty)
+        });
+
+        let mut adapter = Adapter::from(handler).response_format(crate::ResponseFormat::ApiGatewayV1);
+        let response = adapter.call(event()).await.unwrap();
+        assert!(
+            matches!(response, LambdaResponse::ApiGatewayV1(response) if response.multi_value_headers.get_all("vary").iter().count() == 2)
+        );
+
+        // Payload format 2.0 joins the values of headers with commas.
+        let mut adapter = Adapter::from(handler).strict_response_format(true);
+        let response = adapter.call(event()).await.unwrap();
+        assert!(matches!(response, LambdaResponse::ApiGatewayV2(response) if response.status_code == 200));
+
+        let handler = crate::service_fn(|_request: Request| async {
+            Response::builder()
+                .header("set-cookie", http::HeaderValue::from_bytes(b"name=\xff").unwrap())
+                .body(Body::Empty)
+        });
+        let mut adapter = Adapter::from(handler).strict_response_format(true);
+        let response = adapter.call(event()).await.unwrap();
+        assert!(matches!(response, LambdaResponse::ApiGatewayV2(response) if response.status_code == 500));
+    }
+
+    #[cfg(all(feature = "apigw_http", feature = "compression"))]
+    #[tokio::test]
+    async fn strict_response_format_with_cors_and_compression() {
+        use crate::layers::{CompressionLayer, CorsLayer};
+
+        let event = |method: &str| {
+            let event = format!(
+                r#"{{"version":"2.0","rawPath":"/","headers":{{"origin":"https://example.com","accept-encoding":"gzip","access-control-request-method":"POST","access-control-request-headers":"x-custom"}},"requestContext":{{"http":{{"method":"{method}"}}}}}}"#
+            );
+            LambdaEvent::new(
+                serde_json::from_str::<LambdaRequest>(&event).unwrap(),
+                crate::Context::default(),
+            )
+        };
+        let handler = ServiceBuilder::new()
+            .layer(
+                CorsLayer::new()
+                    .allow_origins(["https://example.com"])
+                    .allow_any_header(),
+            )
+            .layer(CompressionLayer::new().min_size(0))
+            .service(crate::service_fn(|_request: Request| async {
+                Ok::<_, crate::Error>(
+                    Response::builder()
+                        .header("vary", "accept")
+                        .body(Body::from("hello"))
+                        .unwrap(),
+                )
+            }));
+        let mut adapter = Adapter::from(handler).strict_response_format(true);
+        for (method, status) in [("GET", 200), ("OPTIONS", 204)] {
+            let response = adapter.call(event(method)).await.unwrap();
+            let LambdaResponse::ApiGatewayV2(response) = response else {
+                panic!("expected a response of payload format 2.0");
+            };
+            assert_eq!(status, response.status_code, "{method}");
+            assert!(response.headers.get_all("vary").iter().count() > 1, "{method}");
+        }
+    }
 }
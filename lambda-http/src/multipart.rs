@@ -0,0 +1,680 @@
+use std::{
+    error::Error,
+    fmt,
+    future::poll_fn,
+    pin::Pin,
+    str::Utf8Error,
+    task::{ready, Context, Poll},
+};
+
+use bytes::{Buf, Bytes};
+use futures_util::{stream, Stream};
+use http::{header::CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue};
+use mime::Mime;
+
+use crate::{Body, Request};
+
+/// Iterator over the parts of a `multipart/form-data` request body, created with
+/// [`RequestPayloadExt::multipart`](crate::RequestPayloadExt::multipart).
+///
+/// Parts are parsed one at a time as the iterator advances, and borrow their data from the
+/// body of the request, so large uploads are not copied. Bodies that API Gateway or ALB
+/// encoded in base64 are decoded when the request is created, so parts always hold the bytes
+/// that the client sent.
+///
+/// ```rust,no_run
+/// use lambda_http::{multipart::MultipartError, Request, RequestPayloadExt};
+///
+/// fn upload_sizes(request: &Request) -> Result<Vec<(String, usize)>, MultipartError> {
+///     let mut sizes = Vec::new();
+///     for part in request.multipart()? {
+///         let part = part?;
+///         if let Some(file_name) = part.file_name() {
+///             sizes.push((file_name.to_string(), part.data().len()));
+///         }
+///     }
+///     Ok(sizes)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Multipart<'a> {
+    body: &'a [u8],
+    delimiter: Vec<u8>,
+    position: Option<usize>,
+    done: bool,
+}
+
+impl<'a> Multipart<'a> {
+    /// Parse `body` with the boundary of a `multipart/form-data` content type.
+    pub fn new(content_type: &str, body: &'a [u8]) -> Result<Self, MultipartError> {
+        let mime: Mime = content_type.parse().map_err(|_| MultipartError::InvalidContentType)?;
+        if mime.type_() != mime::MULTIPART || mime.subtype() != mime::FORM_DATA {
+            return Err(MultipartError::InvalidContentType);
+        }
+        let boundary = mime
+            .get_param(mime::BOUNDARY)
+            .ok_or(MultipartError::InvalidContentType)?;
+        Ok(Self::with_boundary(boundary.as_str(), body))
+    }
+
+    /// Parse `body` with the given boundary.
+    pub fn with_boundary(boundary: &str, body: &'a [u8]) -> Self {
+        Self {
+            body,
+            delimiter: [b"--", boundary.as_bytes()].concat(),
+            position: None,
+            done: false,
+        }
+    }
+
+    pub(crate) fn from_request(request: &'a Request) -> Result<Self, MultipartError> {
+        let content_type = request
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .ok_or(MultipartError::InvalidContentType)?;
+        Self::new(content_type, request.body().as_ref())
+    }
+
+    fn next_part(&mut self) -> Result<Option<Part<'a>>, MultipartError> {
+        let body = self.body;
+        let position = match self.position {
+            Some(position) => position,
+            // Skip the preamble before the first delimiter.
+            None => find(body, &self.delimiter).ok_or(MultipartError::Malformed("missing first boundary"))?,
+        };
+
+        let rest = &body[position + self.delimiter.len()..];
+        if rest.starts_with(b"--") {
+            return Ok(None);
+        }
+        let rest =
+            strip_line_break(rest).ok_or(MultipartError::Malformed("boundary isn't followed by a line break"))?;
+        let header_end = find(rest, b"\r\n\r\n").ok_or(MultipartError::Malformed("part headers aren't terminated"))?;
+        let headers = parse_headers(&rest[..header_end])?;
+        let data = &rest[header_end + 4..];
+
+        let mut closing = Vec::with_capacity(self.delimiter.len() + 2);
+        closing.extend_from_slice(b"\r\n");
+        closing.extend_from_slice(&self.delimiter);
+        let data_end = find(data, &closing).ok_or(MultipartError::Malformed("missing closing boundary"))?;
+        // The next part starts at the delimiter, after the line break that ends the data.
+        self.position = Some(body.len() - data.len() + data_end + 2);
+
+        let (name, file_name, content_type) = part_metadata(&headers)?;
+        Ok(Some(Part {
+            name,
+            file_name,
+            content_type,
+            headers,
+            data: &data[..data_end],
+        }))
+    }
+}
+
+impl<'a> Iterator for Multipart<'a> {
+    type Item = Result<Part<'a>, MultipartError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let part = self.next_part().transpose();
+        // Stop after the last part, and after an error, since the position of the next part is
+        // unknown.
+        self.done = !matches!(part, Some(Ok(_)));
+        part
+    }
+}
+
+/// Name, file name, and content type of a part.
+fn part_metadata(headers: &HeaderMap) -> Result<(String, Option<String>, Option<Mime>), MultipartError> {
+    let disposition = headers
+        .get(http::header::CONTENT_DISPOSITION)
+        .and_then(|value| value.to_str().ok())
+        .ok_or(MultipartError::Malformed("part has no content disposition"))?;
+    let (name, file_name) = parse_disposition(disposition)?;
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok());
+    Ok((name, file_name, content_type))
+}
+
+/// Field or file of a `multipart/form-data` body.
+#[derive(Debug, Clone)]
+pub struct Part<'a> {
+    name: String,
+    file_name: Option<String>,
+    content_type: Option<Mime>,
+    headers: HeaderMap,
+    data: &'a [u8],
+}
+
+impl<'a> Part<'a> {
+    /// Name of the form field.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Name of the uploaded file, when the part is a file.
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// Content type of the part, when the client sent a valid one.
+    pub fn content_type(&self) -> Option<&Mime> {
+        self.content_type.as_ref()
+    }
+
+    /// Headers of the part.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Content of the part.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Content of the part as UTF-8 text, like the value of a form field.
+    pub fn text(&self) -> Result<&'a str, Utf8Error> {
+        std::str::from_utf8(self.data)
+    }
+}
+
+/// Stream of the parts of a `multipart/form-data` body, whose data is read in chunks rather
+/// than as a whole, like uploads piped into an S3 multipart upload.
+///
+/// The chunks of a part are slices of the body, so they are not copied. Lambda functions receive
+/// the whole body of requests in the event, which limits uploads to the size of event payloads,
+/// but a handler that forwards the chunks of a part never holds a second copy of it. The stream
+/// also parses bodies that arrive in chunks from any [`Stream`] of [`Bytes`].
+///
+/// ```rust,no_run
+/// use lambda_http::{multipart::{MultipartError, MultipartStream}, Request};
+/// use futures_util::StreamExt;
+///
+/// async fn upload(request: Request) -> Result<usize, MultipartError> {
+///     let mut multipart = MultipartStream::from_request(request)?;
+///     let mut uploaded = 0;
+///     while let Some(mut part) = multipart.next_part().await? {
+///         if part.file_name().is_none() {
+///             continue;
+///         }
+///         while let Some(chunk) = part.next().await {
+///             // Send the chunk to S3.
+///             uploaded += chunk?.len();
+///         }
+///     }
+///     Ok(uploaded)
+/// }
+/// ```
+pub struct MultipartStream {
+    input: Pin<Box<dyn Stream<Item = Bytes> + Send>>,
+    // Data of the input that hasn't been parsed yet.
+    buffer: Bytes,
+    delimiter: Vec<u8>,
+    // Line break and delimiter that end the data of parts.
+    closing: Vec<u8>,
+    state: StreamState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamState {
+    Preamble,
+    // After a delimiter, before the headers of a part or the end of the body.
+    Boundary,
+    Data,
+    Done,
+}
+
+impl MultipartStream {
+    /// Parse a stream of chunks with the boundary of a `multipart/form-data` content type.
+    pub fn new<S>(content_type: &str, input: S) -> Result<Self, MultipartError>
+    where
+        S: Stream<Item = Bytes> + Send + 'static,
+    {
+        let mime: Mime = content_type.parse().map_err(|_| MultipartError::InvalidContentType)?;
+        if mime.type_() != mime::MULTIPART || mime.subtype() != mime::FORM_DATA {
+            return Err(MultipartError::InvalidContentType);
+        }
+        let boundary = mime
+            .get_param(mime::BOUNDARY)
+            .ok_or(MultipartError::InvalidContentType)?;
+        Ok(Self::with_boundary(boundary.as_str(), input))
+    }
+
+    /// Parse a stream of chunks with the given boundary.
+    pub fn with_boundary<S>(boundary: &str, input: S) -> Self
+    where
+        S: Stream<Item = Bytes> + Send + 'static,
+    {
+        let delimiter = [b"--", boundary.as_bytes()].concat();
+        Self {
+            input: Box::pin(input),
+            buffer: Bytes::new(),
+            closing: [b"\r\n", delimiter.as_slice()].concat(),
+            delimiter,
+            state: StreamState::Preamble,
+        }
+    }
+
+    /// Parse the body of a request, without copying it.
+    pub fn from_request(request: Request) -> Result<Self, MultipartError> {
+        let content_type = request
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .ok_or(MultipartError::InvalidContentType)?
+            .to_string();
+        let body = match request.into_body() {
+            Body::Text(text) => Bytes::from(text),
+            Body::Binary(bytes) => bytes,
+            body => Bytes::copy_from_slice(body.as_ref()),
+        };
+        Self::new(&content_type, stream::iter(Some(body)))
+    }
+
+    /// Next part of the body, or `None` after the last one. The data of the previous part that
+    /// wasn't read is skipped.
+    pub async fn next_part(&mut self) -> Result<Option<PartStream<'_>>, MultipartError> {
+        let Some(headers) = poll_fn(|cx| self.poll_next_part(cx)).await? else {
+            return Ok(None);
+        };
+        let (name, file_name, content_type) = part_metadata(&headers)?;
+        Ok(Some(PartStream {
+            name,
+            file_name,
+            content_type,
+            headers,
+            multipart: self,
+        }))
+    }
+
+    /// Pull a chunk of the input into the buffer, or return `false` at the end of the input.
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<bool> {
+        loop {
+            let Some(chunk) = ready!(self.input.as_mut().poll_next(cx)) else {
+                return Poll::Ready(false);
+            };
+            if chunk.is_empty() {
+                continue;
+            }
+            self.buffer = if self.buffer.is_empty() {
+                chunk
+            } else {
+                [self.buffer.as_ref(), chunk.as_ref()].concat().into()
+            };
+            return Poll::Ready(true);
+        }
+    }
+
+    fn fail(&mut self, reason: &'static str) -> Poll<Result<Option<HeaderMap>, MultipartError>> {
+        self.state = StreamState::Done;
+        Poll::Ready(Err(MultipartError::Malformed(reason)))
+    }
+
+    fn poll_next_part(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<HeaderMap>, MultipartError>> {
+        loop {
+            match self.state {
+                StreamState::Done => return Poll::Ready(Ok(None)),
+                // Skip the rest of the previous part.
+                StreamState::Data => {
+                    ready!(self.poll_chunk(cx))?;
+                }
+                StreamState::Preamble => match find(&self.buffer, &self.delimiter) {
+                    Some(position) => {
+                        self.buffer.advance(position + self.delimiter.len());
+                        self.state = StreamState::Boundary;
+                    }
+                    None => {
+                        // Only keep the end of the preamble, which can be the start of the
+                        // delimiter.
+                        let keep = self.delimiter.len() - 1;
+                        if self.buffer.len() > keep {
+                            self.buffer.advance(self.buffer.len() - keep);
+                        }
+                        if !ready!(self.poll_fill(cx)) {
+                            return self.fail("missing first boundary");
+                        }
+                    }
+                },
+                StreamState::Boundary => {
+                    if self.buffer.starts_with(b"--") {
+                        self.state = StreamState::Done;
+                        return Poll::Ready(Ok(None));
+                    }
+                    if self.buffer.len() >= 2 {
+                        if let Some(header_end) = find(&self.buffer, b"\r\n\r\n") {
+                            let Some(rest) = strip_line_break(&self.buffer) else {
+                                return self.fail("boundary isn't followed by a line break");
+                            };
+                            let start = self.buffer.len() - rest.len();
+                            let headers = match parse_headers(&self.buffer[start..header_end.max(start)]) {
+                                Ok(headers) => headers,
+                                Err(err) => {
+                                    self.state = StreamState::Done;
+                                    return Poll::Ready(Err(err));
+                                }
+                            };
+                            self.buffer.advance(header_end + 4);
+                            self.state = StreamState::Data;
+                            return Poll::Ready(Ok(Some(headers)));
+                        }
+                    }
+                    if !ready!(self.poll_fill(cx)) {
+                        return self.fail("part headers aren't terminated");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Next chunk of the data of the current part, or `None` at the end of the part.
+    fn poll_chunk(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<Bytes>, MultipartError>> {
+        loop {
+            if self.state != StreamState::Data {
+                return Poll::Ready(Ok(None));
+            }
+            if let Some(end) = find(&self.buffer, &self.closing) {
+                let chunk = self.buffer.split_to(end);
+                self.buffer.advance(self.closing.len());
+                self.state = StreamState::Boundary;
+                return Poll::Ready(Ok((!chunk.is_empty()).then_some(chunk)));
+            }
+            // The end of the buffer can be the start of the closing delimiter.
+            let available = self.buffer.len().saturating_sub(self.closing.len() - 1);
+            if available > 0 {
+                return Poll::Ready(Ok(Some(self.buffer.split_to(available))));
+            }
+            if !ready!(self.poll_fill(cx)) {
+                self.state = StreamState::Done;
+                return Poll::Ready(Err(MultipartError::Malformed("missing closing boundary")));
+            }
+        }
+    }
+}
+
+impl fmt::Debug for MultipartStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultipartStream")
+            .field("delimiter", &String::from_utf8_lossy(&self.delimiter))
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Field or file of a [`MultipartStream`], whose data is a [`Stream`] of chunks.
+#[derive(Debug)]
+pub struct PartStream<'a> {
+    name: String,
+    file_name: Option<String>,
+    content_type: Option<Mime>,
+    headers: HeaderMap,
+    multipart: &'a mut MultipartStream,
+}
+
+impl PartStream<'_> {
+    /// Name of the form field.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Name of the uploaded file, when the part is a file.
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// Content type of the part, when the client sent a valid one.
+    pub fn content_type(&self) -> Option<&Mime> {
+        self.content_type.as_ref()
+    }
+
+    /// Headers of the part.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Next chunk of the content of the part, or `None` at its end.
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, MultipartError> {
+        poll_fn(|cx| self.multipart.poll_chunk(cx)).await
+    }
+
+    /// Whole content of the part, like the value of a form field.
+    pub async fn bytes(mut self) -> Result<Bytes, MultipartError> {
+        let mut data = Vec::new();
+        while let Some(chunk) = self.chunk().await? {
+            // Parts that fit in one chunk aren't copied.
+            if data.is_empty() && self.multipart.state != StreamState::Data {
+                return Ok(chunk);
+            }
+            data.extend_from_slice(&chunk);
+        }
+        Ok(data.into())
+    }
+}
+
+impl Stream for PartStream<'_> {
+    type Item = Result<Bytes, MultipartError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().multipart.poll_chunk(cx).map(Result::transpose)
+    }
+}
+
+/// Errors returned when a `multipart/form-data` body can't be parsed.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MultipartError {
+    /// The content type of the request isn't `multipart/form-data`, or has no boundary.
+    InvalidContentType,
+    /// The body doesn't follow the multipart format.
+    Malformed(&'static str),
+}
+
+impl fmt::Display for MultipartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultipartError::InvalidContentType => {
+                write!(f, "request content type isn't multipart/form-data with a boundary")
+            }
+            MultipartError::Malformed(reason) => write!(f, "malformed multipart/form-data body: {reason}"),
+        }
+    }
+}
+
+impl Error for MultipartError {}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn strip_line_break(bytes: &[u8]) -> Option<&[u8]> {
+    // Clients may pad the delimiter line with whitespace.
+    let start = bytes.iter().position(|byte| *byte != b' ' && *byte != b'\t')?;
+    bytes[start..].strip_prefix(b"\r\n")
+}
+
+fn parse_headers(bytes: &[u8]) -> Result<HeaderMap, MultipartError> {
+    let mut headers = HeaderMap::new();
+    for line in bytes.split(|byte| *byte == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        let colon = line
+            .iter()
+            .position(|byte| *byte == b':')
+            .ok_or(MultipartError::Malformed("invalid part header"))?;
+        let name =
+            HeaderName::from_bytes(&line[..colon]).map_err(|_| MultipartError::Malformed("invalid part header"))?;
+        let value = HeaderValue::from_bytes(line[colon + 1..].trim_ascii())
+            .map_err(|_| MultipartError::Malformed("invalid part header"))?;
+        headers.append(name, value);
+    }
+    Ok(headers)
+}
+
+/// Name and file name of a `form-data` content disposition.
+fn parse_disposition(disposition: &str) -> Result<(String, Option<String>), MultipartError> {
+    let mut params = split_params(disposition);
+    if !params
+        .next()
+        .is_some_and(|kind| kind.trim().eq_ignore_ascii_case("form-data"))
+    {
+        return Err(MultipartError::Malformed("part disposition isn't form-data"));
+    }
+    let (mut name, mut file_name) = (None, None);
+    for param in params {
+        let Some((key, value)) = param.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value)
+            .replace("\\\"", "\"");
+        match key.trim().to_ascii_lowercase().as_str() {
+            "name" => name = Some(value),
+            "filename" => file_name = Some(value),
+            _ => {}
+        }
+    }
+    let name = name.ok_or(MultipartError::Malformed("part has no name"))?;
+    Ok((name, file_name))
+}
+
+/// Split the parameters of a header value on semicolons outside of quoted strings.
+fn split_params(value: &str) -> impl Iterator<Item = &str> {
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut start = 0;
+    let mut params = Vec::new();
+    for (index, char) in value.char_indices() {
+        match char {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                params.push(&value[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    params.push(&value[start..]);
+    params.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use super::*;
+    use crate::{Body, RequestPayloadExt};
+
+    const BODY: &[u8] = b"preamble\r\n\
+        --XyZ\r\n\
+        Content-Disposition: form-data; name=\"title\"\r\n\
+        \r\n\
+        Holiday; 2024\r\n\
+        --XyZ\r\n\
+        Content-Disposition: form-data; name=\"photo\"; filename=\"beach \\\"1\\\".jpg\"\r\n\
+        Content-Type: image/jpeg\r\n\
+        \r\n\
+        \xff\xd8\r\n--not-the-boundary\r\n\
+        --XyZ--\r\n";
+
+    fn request(body: Body) -> Request {
+        http::Request::builder()
+            .header("Content-Type", "multipart/form-data; boundary=XyZ")
+            .body(body)
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_fields_and_files() {
+        let request = request(Body::from(BODY.to_vec()));
+        let parts = request.multipart().unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(2, parts.len());
+
+        assert_eq!("title", parts[0].name());
+        assert_eq!(None, parts[0].file_name());
+        assert_eq!("Holiday; 2024", parts[0].text().unwrap());
+
+        assert_eq!("photo", parts[1].name());
+        assert_eq!(Some("beach \"1\".jpg"), parts[1].file_name());
+        assert_eq!(Some(&mime::IMAGE_JPEG), parts[1].content_type());
+        assert_eq!(b"\xff\xd8\r\n--not-the-boundary", parts[1].data());
+    }
+
+    #[test]
+    fn report_invalid_bodies() {
+        let request = http::Request::builder()
+            .header("Content-Type", "application/json")
+            .body(Body::from("{}"))
+            .unwrap();
+        assert_eq!(MultipartError::InvalidContentType, request.multipart().unwrap_err());
+
+        let truncated = &BODY[..BODY.len() - 20];
+        let mut parts = Multipart::with_boundary("XyZ", truncated);
+        assert!(parts.next().unwrap().is_ok());
+        assert_eq!(
+            MultipartError::Malformed("missing closing boundary"),
+            parts.next().unwrap().unwrap_err()
+        );
+        assert!(parts.next().is_none());
+    }
+
+    #[tokio::test]
+    async fn stream_parts_in_chunks() {
+        // Feed the body one byte at a time, so that delimiters are split between chunks.
+        let input = stream::iter(BODY.iter().map(|byte| Bytes::copy_from_slice(&[*byte])));
+        let mut multipart = MultipartStream::new("multipart/form-data; boundary=XyZ", input).unwrap();
+
+        let part = multipart.next_part().await.unwrap().unwrap();
+        assert_eq!("title", part.name());
+        assert_eq!(&b"Holiday; 2024"[..], part.bytes().await.unwrap());
+
+        let mut part = multipart.next_part().await.unwrap().unwrap();
+        assert_eq!(Some("beach \"1\".jpg"), part.file_name());
+        assert_eq!(Some(&mime::IMAGE_JPEG), part.content_type());
+        let mut data = Vec::new();
+        while let Some(chunk) = part.next().await {
+            data.extend_from_slice(&chunk.unwrap());
+        }
+        assert_eq!(b"\xff\xd8\r\n--not-the-boundary", data.as_slice());
+
+        assert!(multipart.next_part().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn stream_parts_of_requests() {
+        let mut multipart = MultipartStream::from_request(request(Body::from(BODY.to_vec()))).unwrap();
+        // The data of parts that aren't read is skipped.
+        assert_eq!("title", multipart.next_part().await.unwrap().unwrap().name());
+        let mut part = multipart.next_part().await.unwrap().unwrap();
+        assert_eq!("photo", part.name());
+        assert_eq!(
+            Some(Bytes::from_static(b"\xff\xd8\r\n--not-the-boundary")),
+            part.chunk().await.unwrap()
+        );
+        assert_eq!(None, part.chunk().await.unwrap());
+        assert!(multipart.next_part().await.unwrap().is_none());
+
+        let truncated = Bytes::from_static(&BODY[..BODY.len() - 20]);
+        let mut multipart = MultipartStream::with_boundary("XyZ", stream::iter(Some(truncated)));
+        assert!(multipart.next_part().await.unwrap().is_some());
+        let mut part = multipart.next_part().await.unwrap().unwrap();
+        let mut result = Ok(());
+        while let Some(chunk) = part.next().await {
+            if let Err(err) = chunk {
+                result = Err(err);
+            }
+        }
+        assert_eq!(Err(MultipartError::Malformed("missing closing boundary")), result);
+        assert!(multipart.next_part().await.unwrap().is_none());
+    }
+}
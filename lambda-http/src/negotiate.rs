@@ -0,0 +1,390 @@
+//! Content negotiation with the `Accept` header of requests.
+//!
+//! [`Accept`] parses the media ranges of the header with their quality values, and
+//! [`Negotiate`] serializes a value in the format that the client prefers among the formats
+//! that the handler supports, or answers `406 Not Acceptable` when the client accepts none of
+//! them.
+//!
+//! ```rust,no_run
+//! use lambda_http::{negotiate::Negotiate, Error, Request, Response, Body};
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Greeting {
+//!     name: String,
+//! }
+//!
+//! async fn greet(request: Request) -> Result<Response<Body>, Error> {
+//!     let greeting = Greeting { name: "Lambda".to_string() };
+//!     Negotiate::new(&greeting)
+//!         .json()
+//!         .html(|greeting| format!("<h1>Hello, {}!</h1>", greeting.name))
+//!         .plain(|greeting| format!("Hello, {}!", greeting.name))
+//!         .respond(&request)
+//! }
+//! ```
+
+use std::{cmp::Ordering, convert::Infallible, fmt};
+
+use http::{
+    header::{ACCEPT, CONTENT_TYPE, VARY},
+    HeaderMap, HeaderValue, StatusCode,
+};
+use serde::Serialize;
+
+use crate::{ext::extract::FromRequest, Body, Error, Request, Response};
+
+/// Media range of an `Accept` header, like `text/html`, `text/*` or `*/*`, with its quality.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaRange {
+    type_: String,
+    subtype: String,
+    quality: f32,
+}
+
+impl MediaRange {
+    /// Type of the range, `*` for any type.
+    pub fn type_(&self) -> &str {
+        &self.type_
+    }
+
+    /// Subtype of the range, `*` for any subtype.
+    pub fn subtype(&self) -> &str {
+        &self.subtype
+    }
+
+    /// Quality value of the range, between 0 and 1. A quality of 0 means that the client
+    /// doesn't accept the media types of the range.
+    pub fn quality(&self) -> f32 {
+        self.quality
+    }
+
+    /// Whether a media type, like `application/json`, is in the range. Parameters of the media
+    /// type are ignored.
+    pub fn matches(&self, media_type: &str) -> bool {
+        let essence = media_type.split(';').next().unwrap_or_default().trim();
+        let Some((type_, subtype)) = essence.split_once('/') else {
+            return false;
+        };
+        (self.type_ == "*" || self.type_.eq_ignore_ascii_case(type_))
+            && (self.subtype == "*" || self.subtype.eq_ignore_ascii_case(subtype))
+    }
+
+    /// Ranges with a specific type and subtype take precedence over wildcards.
+    pub(crate) fn specificity(&self) -> u8 {
+        match (self.type_.as_str(), self.subtype.as_str()) {
+            ("*", _) => 0,
+            (_, "*") => 1,
+            _ => 2,
+        }
+    }
+
+    pub(crate) fn parse(item: &str) -> Option<Self> {
+        let mut params = item.split(';');
+        let (type_, subtype) = params.next()?.trim().split_once('/')?;
+        if type_.is_empty() || subtype.is_empty() || (type_ == "*" && subtype != "*") {
+            return None;
+        }
+        let quality = params
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|quality| quality.trim().parse::<f32>().ok())
+            .unwrap_or(1.0)
+            .clamp(0.0, 1.0);
+        Some(Self {
+            type_: type_.to_ascii_lowercase(),
+            subtype: subtype.to_ascii_lowercase(),
+            quality,
+        })
+    }
+}
+
+impl fmt::Display for MediaRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.type_, self.subtype)?;
+        if self.quality < 1.0 {
+            write!(f, ";q={}", self.quality)?;
+        }
+        Ok(())
+    }
+}
+
+/// Media ranges of the `Accept` header of a request, ordered by preference.
+///
+/// A request without an `Accept` header accepts any media type. Media ranges that can't be
+/// parsed are ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Accept {
+    ranges: Vec<MediaRange>,
+}
+
+impl Accept {
+    /// Parse the value of an `Accept` header.
+    pub fn parse(accept: &str) -> Self {
+        let mut ranges: Vec<MediaRange> = accept.split(',').filter_map(MediaRange::parse).collect();
+        ranges.sort_by(|a, b| {
+            b.quality
+                .partial_cmp(&a.quality)
+                .unwrap_or(Ordering::Equal)
+                .then(b.specificity().cmp(&a.specificity()))
+        });
+        Self { ranges }
+    }
+
+    /// Parse the `Accept` headers of a request.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let values: Vec<&str> = headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect();
+        if values.is_empty() {
+            return Self::parse("*/*");
+        }
+        Self::parse(&values.join(","))
+    }
+
+    /// Media ranges of the header, from the most preferred to the least preferred.
+    pub fn ranges(&self) -> &[MediaRange] {
+        &self.ranges
+    }
+
+    /// Quality of a media type, from the most specific range that matches it. It's 0 when no
+    /// range matches the media type.
+    pub fn quality(&self, media_type: &str) -> f32 {
+        self.ranges
+            .iter()
+            .filter(|range| range.matches(media_type))
+            .max_by_key(|range| range.specificity())
+            .map_or(0.0, MediaRange::quality)
+    }
+
+    /// Whether the client accepts a media type.
+    pub fn accepts(&self, media_type: &str) -> bool {
+        self.quality(media_type) > 0.0
+    }
+
+    /// Media type with the highest quality among `available`. Media types with the same
+    /// quality are picked in the order of `available`.
+    ///
+    /// Returns `None` when the client accepts none of them.
+    pub fn preferred<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        let mut best: Option<(&'a str, f32)> = None;
+        for media_type in available {
+            let quality = self.quality(media_type);
+            if quality > 0.0 && best.is_none_or(|(_, best)| quality > best) {
+                best = Some((media_type, quality));
+            }
+        }
+        best.map(|(media_type, _)| media_type)
+    }
+}
+
+impl FromRequest for Accept {
+    type Rejection = Infallible;
+
+    fn from_request(request: &Request) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(request.headers()))
+    }
+}
+
+type Serializer<'a, T> = Box<dyn Fn(&T) -> Result<Body, Error> + 'a>;
+
+/// Response that serializes a value in the format that the client prefers.
+///
+/// Formats are registered in the order of preference of the handler, which breaks ties
+/// between formats that the client accepts with the same quality.
+#[must_use = "the response is only built with `respond`"]
+pub struct Negotiate<'a, T: ?Sized> {
+    value: &'a T,
+    formats: Vec<(String, Serializer<'a, T>)>,
+}
+
+impl<'a, T: ?Sized> Negotiate<'a, T> {
+    /// Negotiate the format of `value`.
+    pub fn new(value: &'a T) -> Self {
+        Self {
+            value,
+            formats: Vec::new(),
+        }
+    }
+
+    /// Serialize the value in JSON, with the `application/json` content type.
+    pub fn json(self) -> Self
+    where
+        T: Serialize,
+    {
+        self.format("application/json", |value| serde_json::to_string(value))
+    }
+
+    /// Render the value in HTML, with the `text/html; charset=utf-8` content type.
+    pub fn html(self, render: impl Fn(&T) -> String + 'a) -> Self {
+        self.format("text/html; charset=utf-8", move |value| {
+            Ok::<_, Infallible>(render(value))
+        })
+    }
+
+    /// Render the value in plain text, with the `text/plain; charset=utf-8` content type.
+    pub fn plain(self, render: impl Fn(&T) -> String + 'a) -> Self {
+        self.format("text/plain; charset=utf-8", move |value| {
+            Ok::<_, Infallible>(render(value))
+        })
+    }
+
+    /// Serialize the value with `serialize` for clients that accept `content_type`.
+    pub fn format<B, E>(mut self, content_type: impl Into<String>, serialize: impl Fn(&T) -> Result<B, E> + 'a) -> Self
+    where
+        B: Into<Body>,
+        E: Into<Error>,
+    {
+        let serialize = move |value: &T| serialize(value).map(Into::into).map_err(Into::into);
+        self.formats.push((content_type.into(), Box::new(serialize)));
+        self
+    }
+
+    /// Build the response in the format that the `Accept` header of `request` prefers.
+    ///
+    /// The response is `406 Not Acceptable` when the client accepts none of the formats, and
+    /// lists them in its body. Errors of the serializers are returned.
+    pub fn respond(self, request: &Request) -> Result<Response<Body>, Error> {
+        self.respond_to(&Accept::from_headers(request.headers()))
+    }
+
+    /// Build the response in the format that `accept` prefers.
+    pub fn respond_to(self, accept: &Accept) -> Result<Response<Body>, Error> {
+        let available: Vec<&str> = self
+            .formats
+            .iter()
+            .map(|(content_type, _)| content_type.as_str())
+            .collect();
+        let Some(content_type) = accept.preferred(&available) else {
+            let mut response = Response::new(Body::from(format!(
+                "Not Acceptable, available formats: {}",
+                available.join(", ")
+            )));
+            *response.status_mut() = StatusCode::NOT_ACCEPTABLE;
+            response.headers_mut().insert(VARY, HeaderValue::from_static("accept"));
+            return Ok(response);
+        };
+
+        let (_, serialize) = self
+            .formats
+            .iter()
+            .find(|(available, _)| available == content_type)
+            .expect("the preferred format is available");
+        let mut response = Response::new(serialize(self.value)?);
+        let headers = response.headers_mut();
+        headers.insert(CONTENT_TYPE, HeaderValue::try_from(content_type)?);
+        headers.insert(VARY, HeaderValue::from_static("accept"));
+        Ok(response)
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Negotiate<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let formats: Vec<&str> = self
+            .formats
+            .iter()
+            .map(|(content_type, _)| content_type.as_str())
+            .collect();
+        f.debug_struct("Negotiate")
+            .field("formats", &formats)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(accept: Option<&str>) -> Request {
+        let mut request = Request::default();
+        if let Some(accept) = accept {
+            request
+                .headers_mut()
+                .insert(ACCEPT, HeaderValue::from_str(accept).unwrap());
+        }
+        request
+    }
+
+    fn greeting(name: &str) -> Negotiate<'_, str> {
+        Negotiate::new(name)
+            .json()
+            .html(|name| format!("<h1>{name}</h1>"))
+            .plain(|name| name.to_string())
+    }
+
+    #[test]
+    fn parse_media_ranges() {
+        let accept = Accept::parse("text/*;q=0.5, text/html, */*;q=0.1, invalid, application/json;q=0.5");
+        let ranges: Vec<String> = accept.ranges().iter().map(ToString::to_string).collect();
+        assert_eq!(
+            vec!["text/html", "application/json;q=0.5", "text/*;q=0.5", "*/*;q=0.1"],
+            ranges
+        );
+
+        assert_eq!(1.0, accept.quality("text/html; charset=utf-8"));
+        assert_eq!(0.5, accept.quality("text/plain"));
+        assert_eq!(0.1, accept.quality("image/png"));
+        assert!(!Accept::parse("text/html").accepts("application/json"));
+    }
+
+    #[test]
+    fn prefer_specific_ranges() {
+        let accept = Accept::parse("*/*, application/json;q=0");
+        assert!(!accept.accepts("application/json"));
+        assert_eq!(
+            Some("text/plain"),
+            accept.preferred(&["application/json", "text/plain"])
+        );
+    }
+
+    #[test]
+    fn accept_anything_without_header() {
+        let accept = Accept::from_headers(&HeaderMap::new());
+        assert_eq!(
+            Some("application/json"),
+            accept.preferred(&["application/json", "text/html"])
+        );
+    }
+
+    #[test]
+    fn respond_in_preferred_format() {
+        let response = greeting("Lambda")
+            .respond(&request(Some("text/html;q=0.9, text/plain")))
+            .unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!("text/plain; charset=utf-8", response.headers()[CONTENT_TYPE]);
+        assert_eq!("accept", response.headers()[VARY]);
+        assert_eq!(&Body::from("Lambda"), response.body());
+
+        let response = greeting("Lambda").respond(&request(Some("text/*"))).unwrap();
+        assert_eq!("text/html; charset=utf-8", response.headers()[CONTENT_TYPE]);
+
+        let response = greeting("Lambda").respond(&request(None)).unwrap();
+        assert_eq!("application/json", response.headers()[CONTENT_TYPE]);
+        assert_eq!(&Body::from("\"Lambda\""), response.body());
+    }
+
+    #[test]
+    fn respond_not_acceptable() {
+        let response = greeting("Lambda").respond(&request(Some("image/png"))).unwrap();
+        assert_eq!(StatusCode::NOT_ACCEPTABLE, response.status());
+        assert_eq!(
+            &Body::from(
+                "Not Acceptable, available formats: application/json, text/html; charset=utf-8, text/plain; charset=utf-8"
+            ),
+            response.body()
+        );
+    }
+
+    #[test]
+    fn respond_with_custom_format() {
+        let response = Negotiate::new(&42)
+            .json()
+            .format("text/csv", |value| Ok::<_, Infallible>(format!("value\n{value}\n")))
+            .respond(&request(Some("text/csv, application/json;q=0.8")))
+            .unwrap();
+        assert_eq!("text/csv", response.headers()[CONTENT_TYPE]);
+        assert_eq!(&Body::from("value\n42\n"), response.body());
+    }
+}
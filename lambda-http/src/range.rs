@@ -0,0 +1,301 @@
+//! Range requests, to download parts of responses.
+//!
+//! [`ranged`] slices the body of a response according to the `Range` header of the request, like
+//! [RFC 9110](https://www.rfc-editor.org/rfc/rfc9110#section-14) describes it, so clients can
+//! resume downloads or seek in media files:
+//!
+//! ```rust,no_run
+//! use lambda_http::{range::ranged, Body, Error, Request, Response};
+//!
+//! async fn download(request: Request) -> Result<Response<Body>, Error> {
+//!     let file = Body::from(vec![0u8; 4096]);
+//!     let response = Response::builder().header("content-type", "application/octet-stream").body(file)?;
+//!     Ok(ranged(&request, response))
+//! }
+//! ```
+
+use std::{error::Error, fmt};
+
+use http::{
+    header::{ACCEPT_RANGES, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE},
+    HeaderMap, HeaderValue, Method, StatusCode,
+};
+
+use crate::{conditional::ETag, Body, Request, Response};
+
+/// Range of bytes of a body, with inclusive bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    /// Offset of the first byte.
+    pub start: u64,
+    /// Offset of the last byte.
+    pub end: u64,
+}
+
+impl ByteRange {
+    /// Value of the `Content-Range` header of the range, for a body of `length` bytes.
+    pub fn content_range(&self, length: u64) -> String {
+        format!("bytes {}-{}/{length}", self.start, self.end)
+    }
+}
+
+/// Error parsing a `Range` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeError {
+    /// The header isn't a valid range of bytes, and should be ignored.
+    Invalid,
+    /// None of the ranges overlap the body: respond with `416 Range Not Satisfiable`.
+    Unsatisfiable,
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeError::Invalid => f.write_str("invalid range"),
+            RangeError::Unsatisfiable => f.write_str("range not satisfiable"),
+        }
+    }
+}
+
+impl Error for RangeError {}
+
+/// Parse a `Range` header like `bytes=0-499, -500` for a body of `length` bytes.
+///
+/// The ranges are clamped to the body, sorted, and merged when they overlap or are adjacent.
+/// Ranges that start after the end of the body are dropped.
+pub fn parse_ranges(header: &str, length: u64) -> Result<Vec<ByteRange>, RangeError> {
+    let (unit, specs) = header.split_once('=').ok_or(RangeError::Invalid)?;
+    if !unit.trim().eq_ignore_ascii_case("bytes") {
+        return Err(RangeError::Invalid);
+    }
+
+    let mut ranges = Vec::new();
+    for spec in specs.split(',').map(str::trim).filter(|spec| !spec.is_empty()) {
+        let (start, end) = spec.split_once('-').ok_or(RangeError::Invalid)?;
+        let parse = |value: &str| value.trim().parse::<u64>().map_err(|_| RangeError::Invalid);
+        let range = match (start.trim(), end.trim()) {
+            ("", "") => return Err(RangeError::Invalid),
+            // The last bytes of the body
+            ("", suffix) => match parse(suffix)? {
+                0 => None,
+                suffix => (length > 0).then(|| ByteRange {
+                    start: length.saturating_sub(suffix),
+                    end: length - 1,
+                }),
+            },
+            (start, "") => {
+                let start = parse(start)?;
+                (start < length).then(|| ByteRange { start, end: length - 1 })
+            }
+            (start, end) => {
+                let (start, end) = (parse(start)?, parse(end)?);
+                if end < start {
+                    return Err(RangeError::Invalid);
+                }
+                (start < length).then(|| ByteRange {
+                    start,
+                    end: end.min(length - 1),
+                })
+            }
+        };
+        ranges.extend(range);
+    }
+    if ranges.is_empty() {
+        return Err(RangeError::Unsatisfiable);
+    }
+
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<ByteRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end.saturating_add(1) => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    Ok(merged)
+}
+
+/// Slice a response according to the `Range` header of a request.
+///
+/// Successful responses to `GET` requests with a satisfiable range become
+/// `206 Partial Content` responses with a `Content-Range` header, or a `multipart/byteranges`
+/// body for several ranges, and responses to unsatisfiable ranges become
+/// `416 Range Not Satisfiable` responses. The response is kept whole when the range is invalid,
+/// or when the `If-Range` header of the request doesn't match the `ETag` or `Last-Modified`
+/// headers of the response. `200 OK` responses get an `Accept-Ranges: bytes` header.
+pub fn ranged(request: &Request, mut response: Response<Body>) -> Response<Body> {
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+    response
+        .headers_mut()
+        .insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+
+    let header = match request.headers().get(RANGE).and_then(|value| value.to_str().ok()) {
+        Some(header) if request.method() == Method::GET => header,
+        _ => return response,
+    };
+    if !if_range_matches(request.headers(), response.headers()) {
+        return response;
+    }
+
+    let length = response.body().as_ref().len() as u64;
+    let ranges = match parse_ranges(header, length) {
+        Ok(ranges) => ranges,
+        Err(RangeError::Invalid) => return response,
+        Err(RangeError::Unsatisfiable) => {
+            let mut response = Response::new(Body::Empty);
+            *response.status_mut() = StatusCode::RANGE_NOT_SATISFIABLE;
+            response.headers_mut().insert(
+                CONTENT_RANGE,
+                HeaderValue::try_from(format!("bytes */{length}")).expect("valid header value"),
+            );
+            return response;
+        }
+    };
+
+    let (mut parts, body) = response.into_parts();
+    parts.status = StatusCode::PARTIAL_CONTENT;
+    parts.headers.remove(CONTENT_LENGTH);
+    let bytes = body.as_ref();
+    let slice = |range: &ByteRange| &bytes[range.start as usize..=range.end as usize];
+
+    let body = match ranges.as_slice() {
+        [range] => {
+            parts.headers.insert(
+                CONTENT_RANGE,
+                HeaderValue::try_from(range.content_range(length)).expect("valid header value"),
+            );
+            slice(range).to_vec()
+        }
+        ranges => {
+            let boundary = format!("lambda-byteranges-{}", ETag::from_bytes(bytes).tag());
+            let content_type = parts.headers.get(CONTENT_TYPE).and_then(|value| value.to_str().ok());
+            let mut body = Vec::new();
+            for range in ranges {
+                body.extend_from_slice(format!("\r\n--{boundary}\r\n").as_bytes());
+                if let Some(content_type) = content_type {
+                    body.extend_from_slice(format!("content-type: {content_type}\r\n").as_bytes());
+                }
+                body.extend_from_slice(format!("content-range: {}\r\n\r\n", range.content_range(length)).as_bytes());
+                body.extend_from_slice(slice(range));
+            }
+            body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+            parts.headers.insert(
+                CONTENT_TYPE,
+                HeaderValue::try_from(format!("multipart/byteranges; boundary={boundary}"))
+                    .expect("valid header value"),
+            );
+            body
+        }
+    };
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// Whether the `If-Range` header of a request, if any, matches the representation of the
+/// response: strong entity tags have to be identical, and dates have to be the modification
+/// date of the response.
+fn if_range_matches(request: &HeaderMap, response: &HeaderMap) -> bool {
+    let Some(if_range) = request.get(IF_RANGE).and_then(|value| value.to_str().ok()) else {
+        return true;
+    };
+    let header = |name| response.get(name).and_then(|value: &HeaderValue| value.to_str().ok());
+    match ETag::parse(if_range) {
+        Some(etag) => header(ETAG)
+            .and_then(ETag::parse)
+            .is_some_and(|current| current.strong_eq(&etag)),
+        None => header(LAST_MODIFIED).is_some_and(|modified| modified == if_range.trim()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(headers: &[(&str, &str)]) -> Request {
+        let mut builder = http::Request::builder().uri("/file");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(Body::Empty).unwrap()
+    }
+
+    fn file() -> Response<Body> {
+        Response::builder()
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .header(ETAG, "\"v1\"")
+            .body(Body::from((0..100u8).collect::<Vec<_>>()))
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_byte_ranges() {
+        let range = |start, end| ByteRange { start, end };
+        assert_eq!(Ok(vec![range(0, 9)]), parse_ranges("bytes=0-9", 100));
+        assert_eq!(Ok(vec![range(90, 99)]), parse_ranges("bytes=-10", 100));
+        assert_eq!(Ok(vec![range(0, 99)]), parse_ranges("bytes=-1000", 100));
+        assert_eq!(Ok(vec![range(50, 99)]), parse_ranges("bytes=50-", 100));
+        assert_eq!(Ok(vec![range(95, 99)]), parse_ranges("bytes=95-200", 100));
+        assert_eq!(
+            Ok(vec![range(0, 19), range(50, 59)]),
+            parse_ranges("bytes=50-59, 10-19, 0-10", 100)
+        );
+        assert_eq!(Err(RangeError::Unsatisfiable), parse_ranges("bytes=100-", 100));
+        assert_eq!(Err(RangeError::Unsatisfiable), parse_ranges("bytes=-5", 0));
+        assert_eq!(Err(RangeError::Invalid), parse_ranges("bytes=9-0", 100));
+        assert_eq!(Err(RangeError::Invalid), parse_ranges("items=0-9", 100));
+        assert_eq!(Err(RangeError::Invalid), parse_ranges("bytes=a-b", 100));
+    }
+
+    #[test]
+    fn single_range() {
+        let response = ranged(&request(&[("range", "bytes=10-19")]), file());
+        assert_eq!(StatusCode::PARTIAL_CONTENT, response.status());
+        assert_eq!("bytes 10-19/100", response.headers()[CONTENT_RANGE]);
+        assert_eq!("bytes", response.headers()[ACCEPT_RANGES]);
+        assert_eq!((10..20u8).collect::<Vec<_>>(), response.body().as_ref());
+
+        let response = ranged(&request(&[]), file());
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!("bytes", response.headers()[ACCEPT_RANGES]);
+    }
+
+    #[test]
+    fn multiple_ranges() {
+        let response = ranged(&request(&[("range", "bytes=0-1,98-")]), file());
+        assert_eq!(StatusCode::PARTIAL_CONTENT, response.status());
+        let content_type = response.headers()[CONTENT_TYPE].to_str().unwrap();
+        let boundary = content_type.strip_prefix("multipart/byteranges; boundary=").unwrap();
+        let mut expected = format!(
+            "\r\n--{boundary}\r\ncontent-type: application/octet-stream\r\ncontent-range: bytes 0-1/100\r\n\r\n"
+        )
+        .into_bytes();
+        expected.extend_from_slice(&[0, 1]);
+        expected.extend_from_slice(
+            format!(
+                "\r\n--{boundary}\r\ncontent-type: application/octet-stream\r\ncontent-range: bytes 98-99/100\r\n\r\n"
+            )
+            .as_bytes(),
+        );
+        expected.extend_from_slice(&[98, 99]);
+        expected.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+        assert_eq!(expected, response.body().as_ref());
+    }
+
+    #[test]
+    fn unsatisfiable_range() {
+        let response = ranged(&request(&[("range", "bytes=200-")]), file());
+        assert_eq!(StatusCode::RANGE_NOT_SATISFIABLE, response.status());
+        assert_eq!("bytes */100", response.headers()[CONTENT_RANGE]);
+    }
+
+    #[test]
+    fn if_range() {
+        let response = ranged(&request(&[("range", "bytes=0-9"), ("if-range", "\"v1\"")]), file());
+        assert_eq!(StatusCode::PARTIAL_CONTENT, response.status());
+
+        let response = ranged(&request(&[("range", "bytes=0-9"), ("if-range", "\"v0\"")]), file());
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(100, response.body().as_ref().len());
+    }
+}
@@ -0,0 +1,285 @@
+//! Absolute URLs and redirects that account for API Gateway stages.
+//!
+//! Clients reach a function through URLs that the request doesn't show as is: the stage of the
+//! API is the first segment of the path on the default domain of API Gateway, but not on custom
+//! domains, and proxies in front of the function forward the host and protocol of the client in
+//! `X-Forwarded-*` headers. [`RequestExtractExt::base_url`](crate::RequestExtractExt::base_url)
+//! and [`Redirect`] rebuild the URLs that clients see.
+//!
+//! Clients can send any `X-Forwarded-*` header, so they're only used when the
+//! [`Adapter`](crate::Adapter) is built with
+//! [`trust_forwarded_headers`](crate::Adapter::trust_forwarded_headers).
+
+use http::{header::LOCATION, HeaderValue, StatusCode};
+use url::Url;
+
+use crate::{response::ResponseFuture, Body, IntoResponse, Request, RequestExt, Response};
+
+/// Redirect response, to a location resolved against the URL that the client requested.
+///
+/// Absolute locations are kept as is. Locations that start with `/` are relative to the
+/// [base URL](crate::RequestExtractExt::base_url) of the API, so `/login` redirects to the
+/// `/login` route of the API, with the stage of the request. Other locations are relative to
+/// the URL of the request.
+///
+/// ```rust,no_run
+/// use lambda_http::{redirect::Redirect, Request};
+///
+/// async fn logout(request: Request) -> Result<Redirect, lambda_http::Error> {
+///     Ok(Redirect::to(&request, "/login"))
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    status: StatusCode,
+    location: String,
+}
+
+impl Redirect {
+    /// Redirect with `303 See Other`, which clients follow with a `GET` request.
+    pub fn to(request: &Request, location: &str) -> Self {
+        Self::with_status(request, location, StatusCode::SEE_OTHER)
+    }
+
+    /// Redirect with `307 Temporary Redirect`, which clients follow with the same method and
+    /// body.
+    pub fn temporary(request: &Request, location: &str) -> Self {
+        Self::with_status(request, location, StatusCode::TEMPORARY_REDIRECT)
+    }
+
+    /// Redirect with `308 Permanent Redirect`, which clients follow with the same method and
+    /// body, and remember.
+    pub fn permanent(request: &Request, location: &str) -> Self {
+        Self::with_status(request, location, StatusCode::PERMANENT_REDIRECT)
+    }
+
+    fn with_status(request: &Request, location: &str, status: StatusCode) -> Self {
+        Self {
+            status,
+            location: resolve(request, location),
+        }
+    }
+
+    /// Status code of the redirect.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Resolved location of the redirect.
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+}
+
+impl From<Redirect> for Response<Body> {
+    fn from(redirect: Redirect) -> Self {
+        let mut response = Response::new(Body::Empty);
+        *response.status_mut() = redirect.status;
+        if let Ok(location) = HeaderValue::try_from(redirect.location) {
+            response.headers_mut().insert(LOCATION, location);
+        }
+        response
+    }
+}
+
+impl IntoResponse for Redirect {
+    fn into_response(self) -> ResponseFuture {
+        Response::<Body>::from(self).into_response()
+    }
+}
+
+fn resolve(request: &Request, location: &str) -> String {
+    if Url::parse(location).is_ok() || location.starts_with("//") {
+        return location.to_string();
+    }
+    let base = base_url(request);
+    if location.starts_with('/') {
+        return format!("{base}{location}");
+    }
+
+    let current = format!("{base}{}", relative_path(request));
+    match Url::parse(&current) {
+        Ok(current) => current
+            .join(location)
+            .map_or_else(|_| location.to_string(), String::from),
+        // Requests without a host, like requests built in tests, get a relative location.
+        Err(_) => match Url::parse("http://localhost")
+            .and_then(|localhost| localhost.join(&current))
+            .and_then(|current| current.join(location))
+        {
+            Ok(url) => url[url::Position::BeforePath..].to_string(),
+            Err(_) => location.to_string(),
+        },
+    }
+}
+
+/// Extension of requests whose `X-Forwarded-*` headers are set by a proxy, added by the
+/// [`Adapter`](crate::Adapter).
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TrustForwardedHeaders;
+
+/// URL that clients use to reach the root of the API, without a trailing slash.
+pub(crate) fn base_url(request: &Request) -> String {
+    let prefix = path_prefix(request);
+    let header = |name: &str| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            // Proxies append their own value to the values of the proxies before them.
+            .and_then(|value| value.split(',').next())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    };
+    let forwarded = request.extensions().get::<TrustForwardedHeaders>().is_some();
+    let forwarded_host = forwarded.then(|| header("x-forwarded-host")).flatten();
+    let (host, scheme) = if let Some(host) = forwarded_host {
+        (host, header("x-forwarded-proto").unwrap_or("https"))
+    } else if let Some(domain_name) = request.request_context_ref().and_then(|context| context.domain_name()) {
+        // API Gateway and Function URLs only serve HTTPS.
+        (domain_name, "https")
+    } else if let Some(authority) = request.uri().authority() {
+        (authority.as_str(), request.uri().scheme_str().unwrap_or("https"))
+    } else {
+        return prefix;
+    };
+    format!("{scheme}://{host}{prefix}")
+}
+
+/// Path that clients see before the routes of the API, like the stage on the default domain of
+/// API Gateway, or the base path of a custom domain.
+fn path_prefix(request: &Request) -> String {
+    match request.request_context_ref() {
+        #[cfg(feature = "apigw_rest")]
+        Some(crate::request::RequestContext::ApiGatewayV1(context)) => {
+            // The path of the context is the one of the client, and the path of the event the
+            // one of the resource.
+            if let Some(prefix) = context
+                .path
+                .as_deref()
+                .and_then(|path| path.strip_suffix(request.raw_http_path()))
+            {
+                return prefix.to_string();
+            }
+            match context.stage.as_deref() {
+                Some(stage)
+                    if stage != "$default"
+                        && request.uri().host().is_some_and(|host| host.contains(".execute-api.")) =>
+                {
+                    format!("/{stage}")
+                }
+                _ => String::new(),
+            }
+        }
+        #[cfg(feature = "apigw_http")]
+        Some(crate::request::RequestContext::ApiGatewayV2(context)) => match context.stage.as_deref() {
+            // HTTP APIs keep the stage in the path of the event.
+            Some(stage) if stage != "$default" => {
+                let prefix = format!("/{stage}");
+                let path = request.raw_http_path();
+                if path == prefix || path.starts_with(&format!("{prefix}/")) {
+                    prefix
+                } else {
+                    String::new()
+                }
+            }
+            _ => String::new(),
+        },
+        _ => String::new(),
+    }
+}
+
+/// Path of the request below the base URL.
+fn relative_path(request: &Request) -> String {
+    let path = match request.raw_http_path() {
+        "" => request.uri().path(),
+        path => path,
+    };
+    match request.request_context_ref() {
+        // Only HTTP APIs keep the stage in the path of the event.
+        #[cfg(feature = "apigw_http")]
+        Some(crate::request::RequestContext::ApiGatewayV2(_)) => {
+            let prefix = path_prefix(request);
+            path.strip_prefix(prefix.as_str()).unwrap_or(path).to_string()
+        }
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RequestExtractExt;
+
+    #[test]
+    fn base_url_of_rest_apis() {
+        let request = crate::request::from_str(include_str!("../tests/data/apigw_proxy_request.json")).unwrap();
+        assert_eq!(
+            "https://wt6mne2s9k.execute-api.us-west-2.amazonaws.com/test",
+            request.base_url()
+        );
+        assert_eq!(
+            "https://wt6mne2s9k.execute-api.us-west-2.amazonaws.com/test/login",
+            Redirect::to(&request, "/login").location()
+        );
+        assert_eq!(
+            "https://wt6mne2s9k.execute-api.us-west-2.amazonaws.com/test/world",
+            Redirect::to(&request, "world").location()
+        );
+    }
+
+    #[test]
+    fn base_url_of_http_apis() {
+        let request = crate::request::from_str(include_str!(
+            "../tests/data/apigw_v2_proxy_request_with_stage_in_path.json"
+        ))
+        .unwrap();
+        assert_eq!(
+            "https://id.execute-api.us-east-1.amazonaws.com/Prod",
+            request.base_url()
+        );
+
+        let request = crate::request::from_str(include_str!("../tests/data/apigw_v2_proxy_request.json")).unwrap();
+        let redirect = Redirect::permanent(&request, "other");
+        assert_eq!(StatusCode::PERMANENT_REDIRECT, redirect.status());
+        assert_eq!(format!("{}/my/other", request.base_url()), redirect.location());
+    }
+
+    #[test]
+    fn base_url_behind_proxies() {
+        let request = http::Request::builder()
+            .uri("http://internal.example.com/users")
+            .header("x-forwarded-host", "api.example.com, cdn.example.com")
+            .header("x-forwarded-proto", "https")
+            .extension(TrustForwardedHeaders)
+            .body(Body::Empty)
+            .unwrap();
+        assert_eq!("https://api.example.com", request.base_url());
+
+        let response = Response::from(Redirect::temporary(&request, "https://example.org/elsewhere"));
+        assert_eq!(StatusCode::TEMPORARY_REDIRECT, response.status());
+        assert_eq!("https://example.org/elsewhere", response.headers()[LOCATION]);
+    }
+
+    #[test]
+    fn ignore_forged_forwarded_headers() {
+        let mut request = crate::request::from_str(include_str!("../tests/data/apigw_v2_proxy_request.json")).unwrap();
+        let base_url = request.base_url();
+        let headers = request.headers_mut();
+        headers.insert("x-forwarded-host", HeaderValue::from_static("evil.example.com"));
+        headers.insert("x-forwarded-proto", HeaderValue::from_static("http"));
+        assert_eq!(base_url, request.base_url());
+        assert!(Redirect::to(&request, "/login").location().starts_with(&base_url));
+
+        request.extensions_mut().insert(TrustForwardedHeaders);
+        assert_eq!("http://evil.example.com", request.base_url());
+    }
+
+    #[test]
+    fn relative_redirects_without_host() {
+        let request = http::Request::builder().uri("/users/42").body(Body::Empty).unwrap();
+        assert_eq!("", request.base_url());
+        assert_eq!("/login", Redirect::to(&request, "/login").location());
+        assert_eq!("/users/posts", Redirect::to(&request, "posts").location());
+    }
+}
@@ -25,6 +25,8 @@ use aws_lambda_events::apigw::{ApiGatewayProxyRequest, ApiGatewayProxyRequestCon
 use aws_lambda_events::apigw::{ApiGatewayV2httpRequest, ApiGatewayV2httpRequestContext};
 #[cfg(feature = "apigw_websockets")]
 use aws_lambda_events::apigw::{ApiGatewayWebsocketProxyRequest, ApiGatewayWebsocketProxyRequestContext};
+#[cfg(any(feature = "apigw_rest", feature = "alb", feature = "apigw_websockets"))]
+use aws_lambda_events::encodings::merge_multi_value_headers;
 use aws_lambda_events::{encodings::Body, query_map::QueryMap};
 use http::{header::HeaderName, HeaderMap, HeaderValue};
 
@@ -40,6 +42,7 @@ use url::Url;
 /// This is not intended to be a type consumed by crate users directly. The order
 /// of the variants are notable. Serde will try to deserialize in this order.
 #[non_exhaustive]
+#[allow(clippy::large_enum_variant)]
 #[doc(hidden)]
 #[derive(Debug)]
 pub enum LambdaRequest {
@@ -66,7 +69,12 @@ impl LambdaRequest {
             #[cfg(feature = "apigw_http")]
             LambdaRequest::ApiGatewayV2 { .. } => RequestOrigin::ApiGatewayV2,
             #[cfg(feature = "alb")]
-            LambdaRequest::Alb { .. } => RequestOrigin::Alb,
+            LambdaRequest::Alb(alb) => RequestOrigin::Alb {
+                // Target groups with multi-value headers send all headers and query string
+                // parameters in the multi-value fields, and read them from the response.
+                multi_value_headers: !alb.multi_value_headers.is_empty()
+                    || !alb.multi_value_query_string_parameters.is_empty(),
+            },
             #[cfg(feature = "apigw_websockets")]
             LambdaRequest::WebSocket { .. } => RequestOrigin::WebSocket,
             #[cfg(feature = "pass_through")]
@@ -98,7 +106,10 @@ pub enum RequestOrigin {
     ApiGatewayV2,
     /// ALB request origin
     #[cfg(feature = "alb")]
-    Alb,
+    Alb {
+        /// Whether the target group has multi-value headers enabled.
+        multi_value_headers: bool,
+    },
     /// API Gateway WebSocket
     #[cfg(feature = "apigw_websockets")]
     WebSocket,
@@ -204,8 +215,7 @@ fn into_proxy_request(ag: ApiGatewayProxyRequest) -> http::Request<Body> {
 
     // merge headers into multi_value_headers and make
     // multi-value_headers our cannoncial source of request headers
-    let mut headers = ag.multi_value_headers;
-    headers.extend(ag.headers);
+    let headers = merge_multi_value_headers(&ag.headers, &ag.multi_value_headers);
 
     let base64 = ag.is_base64_encoded;
     let mut req = builder
@@ -254,8 +264,7 @@ fn into_alb_request(alb: AlbTargetGroupRequest) -> http::Request<Body> {
 
     // merge headers into multi_value_headers and make
     // multi-value_headers our cannoncial source of request headers
-    let mut headers = alb.multi_value_headers;
-    headers.extend(alb.headers);
+    let headers = merge_multi_value_headers(&alb.headers, &alb.multi_value_headers);
 
     let base64 = alb.is_base64_encoded;
 
@@ -318,8 +327,7 @@ fn into_websocket_request(ag: ApiGatewayWebsocketProxyRequest) -> http::Request<
 
     // merge headers into multi_value_headers and make
     // multi-value_headers our canonical source of request headers
-    let mut headers = ag.multi_value_headers;
-    headers.extend(ag.headers);
+    let headers = merge_multi_value_headers(&ag.headers, &ag.multi_value_headers);
 
     let base64 = ag.is_base64_encoded;
     let mut req = builder
@@ -431,6 +439,21 @@ impl RequestContext {
             _ => None,
         }
     }
+
+    /// Domain name that the client requested, which API Gateway and Function URLs set from the
+    /// domains of the API rather than from the headers of the client.
+    pub(crate) fn domain_name(&self) -> Option<&str> {
+        match self {
+            #[cfg(feature = "apigw_rest")]
+            Self::ApiGatewayV1(ag) => ag.domain_name.as_deref(),
+            #[cfg(feature = "apigw_http")]
+            Self::ApiGatewayV2(ag) => ag.domain_name.as_deref(),
+            #[cfg(feature = "apigw_websockets")]
+            Self::WebSocket(ag) => ag.domain_name.as_deref(),
+            #[cfg(any(feature = "alb", feature = "pass_through"))]
+            _ => None,
+        }
+    }
 }
 
 /// Deserializes a `Request` from a `Read` impl providing JSON events.
@@ -944,4 +967,26 @@ mod tests {
         let authorizer = req_context.authorizer().expect("authorizer is missing");
         assert_eq!(Some("admin"), authorizer.fields.get("principalId").unwrap().as_str());
     }
+
+    #[test]
+    #[cfg(feature = "alb")]
+    fn detects_alb_multi_value_headers_mode() {
+        let origin = |input: &str| {
+            serde_json::from_str::<LambdaRequest>(input)
+                .expect("failed to parse request")
+                .request_origin()
+        };
+        assert!(matches!(
+            origin(include_str!("../tests/data/alb_request.json")),
+            RequestOrigin::Alb {
+                multi_value_headers: false
+            }
+        ));
+        assert!(matches!(
+            origin(include_str!("../tests/data/alb_multi_value_request.json")),
+            RequestOrigin::Alb {
+                multi_value_headers: true
+            }
+        ));
+    }
 }
@@ -1,6 +1,6 @@
 //! Response types
 
-use crate::request::RequestOrigin;
+use crate::{negotiate::MediaRange, request::RequestOrigin};
 #[cfg(feature = "alb")]
 use aws_lambda_events::alb::AlbTargetGroupResponse;
 #[cfg(any(feature = "apigw_rest", feature = "apigw_websockets"))]
@@ -8,6 +8,13 @@ use aws_lambda_events::apigw::ApiGatewayProxyResponse;
 #[cfg(feature = "apigw_http")]
 use aws_lambda_events::apigw::ApiGatewayV2httpResponse;
 use aws_lambda_events::encodings::Body;
+#[cfg(any(
+    feature = "apigw_rest",
+    feature = "apigw_http",
+    feature = "alb",
+    feature = "apigw_websockets"
+))]
+use aws_lambda_events::encodings::MultiValueHeaders;
 use encoding_rs::Encoding;
 use http::{
     header::{CONTENT_ENCODING, CONTENT_TYPE},
@@ -22,6 +29,7 @@ use std::{
     fmt,
     future::{ready, Future},
     pin::Pin,
+    sync::Arc,
 };
 
 const X_LAMBDA_HTTP_CONTENT_ENCODING: &str = "x-lambda-http-content-encoding";
@@ -77,10 +85,7 @@ impl LambdaResponse {
                 response.body = body;
                 response.is_base64_encoded = is_base64_encoded;
                 response.status_code = status_code as i64;
-                // Explicitly empty, as API gateway v1 will merge "headers" and
-                // "multi_value_headers" fields together resulting in duplicate response headers.
-                response.headers = HeaderMap::new();
-                response.multi_value_headers = headers;
+                response.set_merged_headers(headers);
                 // Today, this implementation doesn't provide any additional fields
                 #[cfg(feature = "catch-all-fields")]
                 {
@@ -107,10 +112,7 @@ impl LambdaResponse {
                     response.is_base64_encoded = is_base64_encoded;
                     response.status_code = status_code as i64;
                     response.cookies = cookies;
-                    // API gateway v2 doesn't have "multi_value_headers" field. Duplicate headers
-                    // are combined with commas and included in the headers field.
-                    response.headers = headers;
-                    response.multi_value_headers = HeaderMap::new();
+                    response.set_merged_headers(headers);
                     // Today, this implementation doesn't provide any additional fields
                     #[cfg(feature = "catch-all-fields")]
                     {
@@ -120,17 +122,19 @@ impl LambdaResponse {
                 })
             }
             #[cfg(feature = "alb")]
-            RequestOrigin::Alb => LambdaResponse::Alb({
+            RequestOrigin::Alb { multi_value_headers } => LambdaResponse::Alb({
                 let mut response = AlbTargetGroupResponse::default();
 
                 response.body = body;
                 response.is_base64_encoded = is_base64_encoded;
                 response.status_code = status_code as i64;
-                // ALB responses are used for ALB integration, which can be configured to use
-                // either "headers" or "multi_value_headers" field. We need to return both fields
-                // to ensure both configuration work correctly.
-                response.headers = headers.clone();
-                response.multi_value_headers = headers;
+                // The target group reads only the headers of its mode.
+                response.set_merged_headers(headers);
+                if *multi_value_headers {
+                    response.headers.clear();
+                } else {
+                    response.multi_value_headers.clear();
+                }
                 response.status_description = Some(format!(
                     "{} {}",
                     status_code,
@@ -149,10 +153,7 @@ impl LambdaResponse {
                 response.body = body;
                 response.is_base64_encoded = is_base64_encoded;
                 response.status_code = status_code as i64;
-                // Explicitly empty, as API gateway v1 will merge "headers" and
-                // "multi_value_headers" fields together resulting in duplicate response headers.
-                response.headers = HeaderMap::new();
-                response.multi_value_headers = headers;
+                response.set_merged_headers(headers);
                 // Today, this implementation doesn't provide any additional fields
                 #[cfg(feature = "catch-all-fields")]
                 {
@@ -180,12 +181,137 @@ impl LambdaResponse {
     }
 }
 
+/// Header mode of the responses to an ALB target group.
+///
+/// Target groups read the headers of responses from `multiValueHeaders` when multi-value
+/// headers are enabled on them, and from `headers` otherwise.
+#[cfg(feature = "alb")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alb")))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AlbHeaderMode {
+    /// Use the mode of the request, which has multi-value headers when the target group has
+    /// them enabled.
+    #[default]
+    Detect,
+    /// Return headers with a single value, the last one of each header.
+    Single,
+    /// Return headers with all their values.
+    MultiValue,
+}
+
+#[cfg(feature = "alb")]
+impl RequestOrigin {
+    /// Override the header mode of ALB requests.
+    pub(crate) fn with_alb_header_mode(self, mode: AlbHeaderMode) -> Self {
+        match (self, mode) {
+            (RequestOrigin::Alb { .. }, AlbHeaderMode::Single) => RequestOrigin::Alb {
+                multi_value_headers: false,
+            },
+            (RequestOrigin::Alb { .. }, AlbHeaderMode::MultiValue) => RequestOrigin::Alb {
+                multi_value_headers: true,
+            },
+            (origin, _) => origin,
+        }
+    }
+}
+
+/// Format of the responses that the [`Adapter`](crate::Adapter) returns.
+///
+/// By default, responses have the format of the payload of their request. Functions invoked by
+/// an integration that expects another format, like HTTP APIs configured for payload format
+/// 1.0, can force the format of their responses instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ResponseFormat {
+    /// Use the format of the request.
+    #[default]
+    Detect,
+    /// API Gateway payload format 1.0, with `multiValueHeaders`.
+    #[cfg(feature = "apigw_rest")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "apigw_rest")))]
+    ApiGatewayV1,
+    /// API Gateway payload format 2.0, with `cookies` and comma-separated headers.
+    #[cfg(feature = "apigw_http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "apigw_http")))]
+    ApiGatewayV2,
+    /// ALB target group format, with the [header mode](AlbHeaderMode) of the adapter, or
+    /// single-value headers if it detects the mode of requests that don't come from ALB.
+    #[cfg(feature = "alb")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alb")))]
+    Alb,
+}
+
+impl RequestOrigin {
+    /// Override the origin of requests with the origin of a response format.
+    pub(crate) fn with_response_format(self, format: ResponseFormat) -> Self {
+        match format {
+            ResponseFormat::Detect => self,
+            #[cfg(feature = "apigw_rest")]
+            ResponseFormat::ApiGatewayV1 => RequestOrigin::ApiGatewayV1,
+            #[cfg(feature = "apigw_http")]
+            ResponseFormat::ApiGatewayV2 => RequestOrigin::ApiGatewayV2,
+            #[cfg(feature = "alb")]
+            ResponseFormat::Alb => match self {
+                origin @ RequestOrigin::Alb { .. } => origin,
+                #[allow(unreachable_patterns)]
+                _ => RequestOrigin::Alb {
+                    multi_value_headers: false,
+                },
+            },
+        }
+    }
+
+    /// Describe what a response uses that the response format of this origin can't represent.
+    pub(crate) fn unsupported_feature<B>(&self, response: &Response<B>) -> Option<String> {
+        match self {
+            #[cfg(feature = "apigw_http")]
+            // Payload format 2.0 joins the values of other headers with commas.
+            RequestOrigin::ApiGatewayV2 => response
+                .headers()
+                .get_all(http::header::SET_COOKIE)
+                .iter()
+                .any(|cookie| cookie.to_str().is_err())
+                .then(|| "cookies that aren't valid strings in payload format 2.0".to_string()),
+            #[cfg(feature = "alb")]
+            RequestOrigin::Alb {
+                multi_value_headers: false,
+            } => repeated_header(response.headers())
+                .map(|name| format!("multiple values of the `{name}` header without multi-value headers")),
+            #[allow(unreachable_patterns)]
+            _ => {
+                let _ = response;
+                None
+            }
+        }
+    }
+}
+
+/// First header with multiple values.
+#[cfg(feature = "alb")]
+fn repeated_header(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .keys()
+        .find(|name| headers.get_all(*name).iter().nth(1).is_some())
+        .map(|name| name.as_str())
+}
+
 /// Trait for generating responses
 ///
 /// Types that implement this trait can be used as return types for handler functions.
 pub trait IntoResponse {
     /// Transform into a `Response<Body>` Future
     fn into_response(self) -> ResponseFuture;
+
+    /// Transform into a `Response<Body>` Future, with a body that `policy` encodes as text or
+    /// binary.
+    #[doc(hidden)]
+    fn into_response_with_policy(self, policy: Arc<BinaryPolicy>) -> ResponseFuture
+    where
+        Self: Sized,
+    {
+        let response = self.into_response();
+        Box::pin(async move { policy.apply(response.await) })
+    }
 }
 
 impl<B> IntoResponse for Response<B>
@@ -200,6 +326,13 @@ where
 
         Box::pin(fut)
     }
+
+    fn into_response_with_policy(self, policy: Arc<BinaryPolicy>) -> ResponseFuture {
+        let (parts, body) = self.into_parts();
+        let body = body.convert_with_policy(parts.headers.clone(), &policy);
+
+        Box::pin(async { Response::from_parts(parts, body.await) })
+    }
 }
 
 impl IntoResponse for String {
@@ -310,6 +443,8 @@ pub type ResponseFuture = Pin<Box<dyn Future<Output = Response<Body>> + Send>>;
 
 pub trait ConvertBody {
     fn convert(self, parts: HeaderMap) -> BodyFuture;
+
+    fn convert_with_policy(self, parts: HeaderMap, policy: &BinaryPolicy) -> BodyFuture;
 }
 
 impl<B> ConvertBody for B
@@ -330,18 +465,8 @@ where
             return convert_to_text(self, "utf-8");
         };
 
-        for prefix in TEXT_ENCODING_PREFIXES {
-            if content_type.starts_with(prefix) {
-                return convert_to_text(self, content_type);
-            }
-        }
-
-        for suffix in TEXT_ENCODING_SUFFIXES {
-            let mut parts = content_type.trim().split(';');
-            let mime_type = parts.next().unwrap_or_default();
-            if mime_type.ends_with(suffix) {
-                return convert_to_text(self, content_type);
-            }
+        if is_text_content_type(content_type) {
+            return convert_to_text(self, content_type);
         }
 
         if let Some(value) = headers.get(X_LAMBDA_HTTP_CONTENT_ENCODING) {
@@ -352,6 +477,158 @@ where
 
         convert_to_binary(self)
     }
+
+    fn convert_with_policy(self, headers: HeaderMap, policy: &BinaryPolicy) -> BodyFuture {
+        let content_type = headers.get(CONTENT_TYPE).and_then(|value| value.to_str().ok());
+        match policy.kind(&headers) {
+            Some(BodyKind::Text) => convert_to_text(self, content_type.unwrap_or("utf-8")),
+            Some(BodyKind::Binary) => convert_to_binary(self),
+            Some(BodyKind::Detect) => Box::pin(async move {
+                let bytes = self.collect().await.expect("unable to read bytes from body").to_bytes();
+                detect_text(bytes.into())
+            }),
+            None => self.convert(headers),
+        }
+    }
+}
+
+/// Policy that decides which response bodies are returned as text, and which ones are binary
+/// and encoded in base64.
+///
+/// By default, bodies with a `Content-Encoding` header are binary, bodies whose content type is
+/// text, JSON, JavaScript, XML or YAML are text, and other bodies are binary. A policy
+/// overrides this for media ranges, like `font/*` or `application/x-protobuf`, and can detect
+/// text from the bytes of bodies that no media range matches.
+///
+/// The policy is set on the [`Adapter`](crate::Adapter) of the handler:
+///
+/// ```rust,no_run
+/// use lambda_http::{lambda_runtime::Runtime, service_fn, Adapter, BinaryPolicy, Error, Request};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let policy = BinaryPolicy::new()
+///         .binary("application/x-protobuf")
+///         .text("application/graphql")
+///         .detect_from_bytes(true);
+///     let adapter = Adapter::from(service_fn(|_request: Request| async { Ok::<_, Error>("hello") }))
+///         .binary_policy(policy);
+///     Runtime::new(adapter).run().await
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BinaryPolicy {
+    text: Vec<MediaRange>,
+    binary: Vec<MediaRange>,
+    detect: bool,
+}
+
+impl BinaryPolicy {
+    /// Create a policy with the default rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return bodies whose content type is in `media_range` as text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `media_range` isn't a valid media range.
+    pub fn text(mut self, media_range: &str) -> Self {
+        self.text.push(parse_media_range(media_range));
+        self
+    }
+
+    /// Return bodies whose content type is in `media_range` as binary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `media_range` isn't a valid media range.
+    pub fn binary(mut self, media_range: &str) -> Self {
+        self.binary.push(parse_media_range(media_range));
+        self
+    }
+
+    /// Return bodies that no media range of the policy matches as text when they're valid
+    /// UTF-8 without control characters, and as binary otherwise, instead of deciding from
+    /// their content type.
+    pub fn detect_from_bytes(mut self, detect: bool) -> Self {
+        self.detect = detect;
+        self
+    }
+
+    /// Kind of a body with these headers, or `None` to use the default rules. The most
+    /// specific media range that matches the content type wins, and binary ranges win ties.
+    fn kind(&self, headers: &HeaderMap) -> Option<BodyKind> {
+        if headers.contains_key(CONTENT_ENCODING) {
+            return Some(BodyKind::Binary);
+        }
+        if let Some(content_type) = headers.get(CONTENT_TYPE).and_then(|value| value.to_str().ok()) {
+            let specificity = |ranges: &[MediaRange]| {
+                ranges
+                    .iter()
+                    .filter(|range| range.matches(content_type))
+                    .map(MediaRange::specificity)
+                    .max()
+            };
+            match (specificity(&self.text), specificity(&self.binary)) {
+                (Some(text), Some(binary)) if text > binary => return Some(BodyKind::Text),
+                (_, Some(_)) => return Some(BodyKind::Binary),
+                (Some(_), None) => return Some(BodyKind::Text),
+                (None, None) => {}
+            }
+        }
+        self.detect.then_some(BodyKind::Detect)
+    }
+
+    /// Apply the policy to a response whose body was already converted.
+    pub(crate) fn apply(&self, response: Response<Body>) -> Response<Body> {
+        let (parts, body) = response.into_parts();
+        let body = match (self.kind(&parts.headers), body) {
+            (Some(BodyKind::Binary), Body::Text(text)) => Body::from(text.into_bytes()),
+            (Some(BodyKind::Text), Body::Binary(bytes)) => match String::from_utf8(bytes.into()) {
+                Ok(text) => Body::Text(text),
+                Err(err) => Body::from(err.into_bytes()),
+            },
+            (Some(BodyKind::Detect), Body::Binary(bytes)) => detect_text(bytes.into()),
+            (_, body) => body,
+        };
+        Response::from_parts(parts, body)
+    }
+}
+
+fn parse_media_range(media_range: &str) -> MediaRange {
+    MediaRange::parse(media_range).unwrap_or_else(|| panic!("invalid media range: {media_range}"))
+}
+
+enum BodyKind {
+    Text,
+    Binary,
+    Detect,
+}
+
+fn detect_text(bytes: Vec<u8>) -> Body {
+    match String::from_utf8(bytes) {
+        Ok(text)
+            if !text
+                .chars()
+                .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r' | '\x0c')) =>
+        {
+            Body::Text(text)
+        }
+        Ok(text) => Body::from(text.into_bytes()),
+        Err(err) => Body::from(err.into_bytes()),
+    }
+}
+
+/// Whether bodies with this content type are text, which is returned as is rather than encoded
+/// in base64.
+pub(crate) fn is_text_content_type(content_type: &str) -> bool {
+    let mime_type = content_type.trim().split(';').next().unwrap_or_default();
+    TEXT_ENCODING_PREFIXES
+        .iter()
+        .any(|prefix| content_type.starts_with(prefix))
+        || TEXT_ENCODING_SUFFIXES.iter().any(|suffix| mime_type.ends_with(suffix))
 }
 
 fn convert_to_binary<B>(body: B) -> BodyFuture
@@ -360,15 +637,7 @@ where
     B::Data: Send,
     B::Error: fmt::Debug,
 {
-    Box::pin(async move {
-        Body::from(
-            body.collect()
-                .await
-                .expect("unable to read bytes from body")
-                .to_bytes()
-                .to_vec(),
-        )
-    })
+    Box::pin(async move { Body::from(body.collect().await.expect("unable to read bytes from body").to_bytes()) })
 }
 
 fn convert_to_text<B>(body: B, content_type: &str) -> BodyFuture
@@ -403,7 +672,11 @@ pub type BodyFuture = Pin<Box<dyn Future<Output = Body> + Send>>;
 
 #[cfg(test)]
 mod tests {
-    use super::{Body, IntoResponse, LambdaResponse, RequestOrigin, X_LAMBDA_HTTP_CONTENT_ENCODING};
+    use std::sync::Arc;
+
+    use super::{
+        AlbHeaderMode, BinaryPolicy, Body, IntoResponse, LambdaResponse, RequestOrigin, X_LAMBDA_HTTP_CONTENT_ENCODING,
+    };
     use http::{
         header::{CONTENT_ENCODING, CONTENT_TYPE},
         Response, StatusCode,
@@ -618,6 +891,22 @@ mod tests {
         )
     }
 
+    #[test]
+    fn serialize_cookie_jar_in_multi_value_headers() {
+        let mut jar = crate::cookies::CookieJar::new();
+        jar.add(crate::cookies::Cookie::new("cookie1", "a"));
+        jar.add(crate::cookies::Cookie::new("cookie2", "b"));
+        let mut response = Response::new(Body::from(()));
+        jar.apply(&mut response).unwrap();
+
+        let res = LambdaResponse::from_response(&RequestOrigin::ApiGatewayV1, response);
+        let json = serde_json::to_string(&res).expect("failed to serialize to json");
+        assert_eq!(
+            r#"{"statusCode":200,"headers":{},"multiValueHeaders":{"set-cookie":["cookie1=a","cookie2=b"]},"isBase64Encoded":false}"#,
+            json
+        )
+    }
+
     #[tokio::test]
     async fn content_type_xml_as_text() {
         // Drive the implementation by using `hyper::Body` instead of
@@ -696,4 +985,127 @@ foo: bar
             )
         }
     }
+
+    #[tokio::test]
+    async fn binary_policy_overrides_content_types() {
+        let policy = Arc::new(
+            BinaryPolicy::new()
+                .binary("application/*")
+                .text("application/x-custom")
+                .binary("text/csv"),
+        );
+
+        let response = Response::builder()
+            .header(CONTENT_TYPE, "application/x-custom")
+            .body(HyperBody::from("custom"))
+            .expect("unable to build http::Response");
+        let response = response.into_response_with_policy(policy.clone()).await;
+        assert_eq!(&Body::from("custom"), response.body());
+
+        let response = Response::builder()
+            .header(CONTENT_TYPE, "application/json")
+            .body(HyperBody::from("{}"))
+            .expect("unable to build http::Response");
+        let response = response.into_response_with_policy(policy.clone()).await;
+        assert_eq!(&Body::from(b"{}".to_vec()), response.body());
+
+        let response = Response::builder()
+            .header(CONTENT_TYPE, "text/csv")
+            .body(Body::from("a,b"))
+            .expect("unable to build http::Response");
+        let response = policy.apply(response);
+        assert_eq!(&Body::from(b"a,b".to_vec()), response.body());
+    }
+
+    #[tokio::test]
+    async fn binary_policy_detects_text_from_bytes() {
+        let policy = Arc::new(BinaryPolicy::new().detect_from_bytes(true));
+
+        let response = Response::new(HyperBody::from(vec![0x00, 0x9f, 0x92, 0x96]))
+            .into_response_with_policy(policy.clone())
+            .await;
+        assert_eq!(&Body::from(vec![0x00, 0x9f, 0x92, 0x96]), response.body());
+
+        let response = Response::builder()
+            .header(CONTENT_TYPE, "font/woff2")
+            .body(HyperBody::from("plain\ttext\n"))
+            .expect("unable to build http::Response")
+            .into_response_with_policy(policy.clone())
+            .await;
+        assert_eq!(&Body::from("plain\ttext\n"), response.body());
+
+        let response = (StatusCode::OK, b"\x01\x02".as_slice())
+            .into_response_with_policy(policy)
+            .await;
+        assert_eq!(&Body::from(b"\x01\x02".to_vec()), response.body());
+    }
+
+    #[tokio::test]
+    async fn default_binary_policy_keeps_conversions() {
+        let policy = Arc::new(BinaryPolicy::default());
+        let response = Response::builder()
+            .header(CONTENT_TYPE, "image/svg+xml")
+            .body(HyperBody::from(SVG_LOGO.as_bytes()))
+            .expect("unable to build http::Response")
+            .into_response_with_policy(policy.clone())
+            .await;
+        assert_eq!(&Body::from(SVG_LOGO), response.body());
+
+        let response = "text".into_response_with_policy(policy).await;
+        assert_eq!(&Body::from("text"), response.body());
+    }
+
+    #[test]
+    fn alb_response_headers_follow_request_mode() {
+        let response = || {
+            Response::builder()
+                .header("set-cookie", "a=1")
+                .header("set-cookie", "b=2")
+                .body(Body::Empty)
+                .expect("unable to build http::Response")
+        };
+
+        let single = LambdaResponse::from_response(
+            &RequestOrigin::Alb {
+                multi_value_headers: false,
+            },
+            response(),
+        );
+        let json = serde_json::to_value(&single).expect("failed to serialize to json");
+        assert_eq!(json!({"set-cookie": "b=2"}), json["headers"]);
+        assert_eq!(json!({}), json["multiValueHeaders"]);
+
+        let multi_value = LambdaResponse::from_response(
+            &RequestOrigin::Alb {
+                multi_value_headers: true,
+            },
+            response(),
+        );
+        let json = serde_json::to_value(&multi_value).expect("failed to serialize to json");
+        assert_eq!(json!({}), json["headers"]);
+        assert_eq!(json!({"set-cookie": ["a=1", "b=2"]}), json["multiValueHeaders"]);
+    }
+
+    #[test]
+    fn override_alb_header_mode() {
+        let origin = RequestOrigin::Alb {
+            multi_value_headers: false,
+        };
+        assert!(matches!(
+            origin.clone().with_alb_header_mode(AlbHeaderMode::MultiValue),
+            RequestOrigin::Alb {
+                multi_value_headers: true
+            }
+        ));
+        assert!(matches!(
+            origin.with_alb_header_mode(AlbHeaderMode::Detect),
+            RequestOrigin::Alb {
+                multi_value_headers: false
+            }
+        ));
+        assert!(matches!(
+            RequestOrigin::ApiGatewayV2.with_alb_header_mode(AlbHeaderMode::Single),
+            RequestOrigin::ApiGatewayV2
+        ));
+    }
 }
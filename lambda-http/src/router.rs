@@ -0,0 +1,574 @@
+//! Routing of requests to handlers by method and path.
+//!
+//! A [`Router`] matches the path of requests against path templates like `/users/{id}` or
+//! `/files/{path+}`, and calls the handler of the method of the request. Requests that match no
+//! route are answered with `404 Not Found`, or with the [fallback](Router::fallback) handler,
+//! and requests that match a path, but not its methods, with `405 Method Not Allowed`.
+//!
+//! The parameters of the template are set as the path parameters of the request, so handlers
+//! read them with [`path_param`](crate::RequestExtractExt::path_param) or the
+//! [`Path`](crate::ext::extract::Path) extractor. The router is a `Service<Request>`, so it runs
+//! with [`run`](crate::run) and composes with layers.
+//!
+//! ```rust,no_run
+//! use lambda_http::{router::Router, service_fn, Error, Request, RequestExtractExt};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Error> {
+//!     let router = Router::new()
+//!         .get("/users", service_fn(list_users))
+//!         .get("/users/{id}", service_fn(get_user));
+//!     lambda_http::run(router).await
+//! }
+//!
+//! async fn list_users(_request: Request) -> Result<&'static str, Error> {
+//!     Ok("ferris")
+//! }
+//!
+//! async fn get_user(request: Request) -> Result<String, Error> {
+//!     let id: u64 = request.path_param("id")?;
+//!     Ok(format!("user {id}"))
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    fmt,
+    future::{ready, Future},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures_util::FutureExt;
+use http::{
+    header::{ALLOW, CONTENT_TYPE},
+    HeaderValue, Method, StatusCode,
+};
+use percent_encoding::percent_decode_str;
+
+use crate::{
+    layers::CachePolicy,
+    tower::{util::BoxCloneSyncService, Service, ServiceExt},
+    Body, Error, IntoResponse, Request, RequestExt, Response,
+};
+
+type Handler = BoxCloneSyncService<Request, Response<Body>, Error>;
+
+/// Future returned by [`Router`].
+pub type RouterFuture = Pin<Box<dyn Future<Output = Result<Response<Body>, Error>> + Send>>;
+
+/// Path template of the route that matched a request, added to the extensions of the request
+/// by the [`Router`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedPath(Arc<str>);
+
+impl MatchedPath {
+    /// The path template, like `/users/{id}`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Router of requests to handlers, by method and path template.
+///
+/// When several templates match a path, the one with the most specific first segments wins:
+/// literal segments before `{param}` segments, and `{param}` segments before `{param+}`
+/// segments. So `/users/me` wins over `/users/{id}`, whatever the order of the routes.
+#[derive(Clone, Default)]
+pub struct Router {
+    routes: Vec<Route>,
+    fallback: Option<Handler>,
+}
+
+#[derive(Clone)]
+struct Route {
+    template: Template,
+    handlers: Vec<(Option<Method>, Handler)>,
+    cache: Option<CachePolicy>,
+}
+
+impl Router {
+    /// Create a router without routes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Route requests with `method` whose path matches `template` to `handler`.
+    ///
+    /// Templates start with `/`, and segments between braces are parameters: `{id}` matches
+    /// one segment of the path, and `{path+}` matches the rest of the path.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the template is invalid, or if the method already has a handler for it.
+    pub fn route<S, R, E>(self, method: Method, template: &str, handler: S) -> Self
+    where
+        S: Service<Request, Response = R, Error = E> + Clone + Send + Sync + 'static,
+        S::Future: Send + 'static,
+        R: IntoResponse + 'static,
+        E: Into<Error> + 'static,
+    {
+        self.add(Some(method), template, handler)
+    }
+
+    /// Route `GET` requests to `handler`.
+    pub fn get<S, R, E>(self, template: &str, handler: S) -> Self
+    where
+        S: Service<Request, Response = R, Error = E> + Clone + Send + Sync + 'static,
+        S::Future: Send + 'static,
+        R: IntoResponse + 'static,
+        E: Into<Error> + 'static,
+    {
+        self.route(Method::GET, template, handler)
+    }
+
+    /// Route `POST` requests to `handler`.
+    pub fn post<S, R, E>(self, template: &str, handler: S) -> Self
+    where
+        S: Service<Request, Response = R, Error = E> + Clone + Send + Sync + 'static,
+        S::Future: Send + 'static,
+        R: IntoResponse + 'static,
+        E: Into<Error> + 'static,
+    {
+        self.route(Method::POST, template, handler)
+    }
+
+    /// Route `PUT` requests to `handler`.
+    pub fn put<S, R, E>(self, template: &str, handler: S) -> Self
+    where
+        S: Service<Request, Response = R, Error = E> + Clone + Send + Sync + 'static,
+        S::Future: Send + 'static,
+        R: IntoResponse + 'static,
+        E: Into<Error> + 'static,
+    {
+        self.route(Method::PUT, template, handler)
+    }
+
+    /// Route `PATCH` requests to `handler`.
+    pub fn patch<S, R, E>(self, template: &str, handler: S) -> Self
+    where
+        S: Service<Request, Response = R, Error = E> + Clone + Send + Sync + 'static,
+        S::Future: Send + 'static,
+        R: IntoResponse + 'static,
+        E: Into<Error> + 'static,
+    {
+        self.route(Method::PATCH, template, handler)
+    }
+
+    /// Route `DELETE` requests to `handler`.
+    pub fn delete<S, R, E>(self, template: &str, handler: S) -> Self
+    where
+        S: Service<Request, Response = R, Error = E> + Clone + Send + Sync + 'static,
+        S::Future: Send + 'static,
+        R: IntoResponse + 'static,
+        E: Into<Error> + 'static,
+    {
+        self.route(Method::DELETE, template, handler)
+    }
+
+    /// Route requests with any method to `handler`, unless the template has a handler for the
+    /// method of the request.
+    pub fn any<S, R, E>(self, template: &str, handler: S) -> Self
+    where
+        S: Service<Request, Response = R, Error = E> + Clone + Send + Sync + 'static,
+        S::Future: Send + 'static,
+        R: IntoResponse + 'static,
+        E: Into<Error> + 'static,
+    {
+        self.add(None, template, handler)
+    }
+
+    /// Call `handler` for requests that match no route, instead of answering `404 Not Found`.
+    pub fn fallback<S, R, E>(mut self, handler: S) -> Self
+    where
+        S: Service<Request, Response = R, Error = E> + Clone + Send + Sync + 'static,
+        S::Future: Send + 'static,
+        R: IntoResponse + 'static,
+        E: Into<Error> + 'static,
+    {
+        self.fallback = Some(box_handler(handler));
+        self
+    }
+
+    /// Apply a [`CachePolicy`] to the responses of the handlers of `template`, whatever their
+    /// method.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the template has no handlers.
+    pub fn cache(mut self, template: &str, policy: CachePolicy) -> Self {
+        let template = Template::parse(template);
+        let Some(route) = self.routes.iter_mut().find(|route| route.template == template) else {
+            panic!("the route {} has no handlers", template.source);
+        };
+        route.cache = Some(policy);
+        self
+    }
+
+    fn add<S, R, E>(mut self, method: Option<Method>, template: &str, handler: S) -> Self
+    where
+        S: Service<Request, Response = R, Error = E> + Clone + Send + Sync + 'static,
+        S::Future: Send + 'static,
+        R: IntoResponse + 'static,
+        E: Into<Error> + 'static,
+    {
+        let template = Template::parse(template);
+        let route = match self.routes.iter_mut().find(|route| route.template == template) {
+            Some(route) => route,
+            None => {
+                self.routes.push(Route {
+                    template,
+                    handlers: Vec::new(),
+                    cache: None,
+                });
+                self.routes.last_mut().expect("the route was just added")
+            }
+        };
+        assert!(
+            route.handlers.iter().all(|(existing, _)| *existing != method),
+            "the route {} already has a handler for {}",
+            route.template.source,
+            method.as_ref().map_or("any method", Method::as_str),
+        );
+        route.handlers.push((method, box_handler(handler)));
+        self
+    }
+}
+
+impl fmt::Debug for Router {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let routes: Vec<&str> = self.routes.iter().map(|route| route.template.source.as_ref()).collect();
+        f.debug_struct("Router")
+            .field("routes", &routes)
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+fn box_handler<S, R, E>(handler: S) -> Handler
+where
+    S: Service<Request, Response = R, Error = E> + Clone + Send + Sync + 'static,
+    S::Future: Send + 'static,
+    R: IntoResponse + 'static,
+    E: Into<Error> + 'static,
+{
+    BoxCloneSyncService::new(
+        handler
+            .map_err(Into::into)
+            .and_then(|response: R| response.into_response().map(Ok)),
+    )
+}
+
+impl Service<Request> for Router {
+    type Response = Response<Body>;
+    type Error = Error;
+    type Future = RouterFuture;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Handlers are cloned for each request, and polled for readiness when they're called.
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let path = match request.raw_http_path() {
+            "" => request.uri().path().to_string(),
+            path => path.to_string(),
+        };
+
+        let mut best: Option<(&Route, HashMap<String, String>)> = None;
+        for route in &self.routes {
+            let Some(params) = route.template.matches(&path) else {
+                continue;
+            };
+            if best
+                .as_ref()
+                .is_none_or(|(best, _)| route.template.rank() > best.template.rank())
+            {
+                best = Some((route, params));
+            }
+        }
+
+        let Some((route, params)) = best else {
+            return match &self.fallback {
+                Some(fallback) => Box::pin(fallback.clone().oneshot(request)),
+                None => Box::pin(ready(Ok(text_response(StatusCode::NOT_FOUND, "Not Found")))),
+            };
+        };
+
+        let handler = route
+            .handlers
+            .iter()
+            .find(|(method, _)| method.as_ref() == Some(request.method()))
+            .or_else(|| route.handlers.iter().find(|(method, _)| method.is_none()));
+        let Some((_, handler)) = handler else {
+            let allow: Vec<&str> = route
+                .handlers
+                .iter()
+                .filter_map(|(method, _)| method.as_ref().map(Method::as_str))
+                .collect();
+            let mut response = text_response(StatusCode::METHOD_NOT_ALLOWED, "Method Not Allowed");
+            if let Ok(allow) = HeaderValue::from_str(&allow.join(", ")) {
+                response.headers_mut().insert(ALLOW, allow);
+            }
+            return Box::pin(ready(Ok(response)));
+        };
+
+        let mut request = request.with_path_parameters(params);
+        request
+            .extensions_mut()
+            .insert(MatchedPath(route.template.source.clone()));
+        #[cfg(feature = "tracing")]
+        crate::tracing::Span::current().record("httpRoute", &*route.template.source);
+        let future = handler.clone().oneshot(request);
+        match route.cache.clone() {
+            Some(policy) => Box::pin(future.map(move |result| {
+                result.map(|mut response| {
+                    policy.apply(&mut response);
+                    response
+                })
+            })),
+            None => Box::pin(future),
+        }
+    }
+}
+
+fn text_response(status: StatusCode, body: &'static str) -> Response<Body> {
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+    response
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Param(String),
+    Rest(String),
+}
+
+#[derive(Debug, Clone)]
+struct Template {
+    source: Arc<str>,
+    segments: Vec<Segment>,
+}
+
+impl PartialEq for Template {
+    fn eq(&self, other: &Self) -> bool {
+        self.segments == other.segments
+    }
+}
+
+impl Template {
+    fn parse(source: &str) -> Self {
+        let Some(path) = source.strip_prefix('/') else {
+            panic!("the route {source} doesn't start with /");
+        };
+        let parts: Vec<&str> = path.split('/').collect();
+        let segments = parts
+            .iter()
+            .enumerate()
+            .map(|(index, part)| {
+                let Some(name) = part.strip_prefix('{').and_then(|part| part.strip_suffix('}')) else {
+                    assert!(
+                        !part.contains(['{', '}']),
+                        "the route {source} has an invalid segment {part}"
+                    );
+                    return Segment::Literal(part.to_string());
+                };
+                match name.strip_suffix('+') {
+                    Some(name) => {
+                        assert!(
+                            index == parts.len() - 1,
+                            "the parameter {{{name}+}} of the route {source} isn't its last segment"
+                        );
+                        assert!(!name.is_empty(), "the route {source} has a parameter without name");
+                        Segment::Rest(name.to_string())
+                    }
+                    None => {
+                        assert!(!name.is_empty(), "the route {source} has a parameter without name");
+                        Segment::Param(name.to_string())
+                    }
+                }
+            })
+            .collect();
+        Self {
+            source: source.into(),
+            segments,
+        }
+    }
+
+    /// Parameters of the path, or `None` if it doesn't match the template.
+    fn matches(&self, path: &str) -> Option<HashMap<String, String>> {
+        let mut parts = path.strip_prefix('/').unwrap_or(path).split('/');
+        let mut params = HashMap::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => {
+                    if parts.next()? != literal {
+                        return None;
+                    }
+                }
+                Segment::Param(name) => {
+                    let part = parts.next().filter(|part| !part.is_empty())?;
+                    params.insert(name.clone(), decode(part));
+                }
+                Segment::Rest(name) => {
+                    let rest: Vec<&str> = parts.by_ref().collect();
+                    if rest.concat().is_empty() {
+                        return None;
+                    }
+                    params.insert(name.clone(), decode(&rest.join("/")));
+                }
+            }
+        }
+        parts.next().is_none().then_some(params)
+    }
+
+    /// Specificity of the template, compared segment by segment.
+    fn rank(&self) -> Vec<u8> {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(_) => 2,
+                Segment::Param(_) => 1,
+                Segment::Rest(_) => 0,
+            })
+            .collect()
+    }
+}
+
+fn decode(part: &str) -> String {
+    percent_decode_str(part).decode_utf8_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::CACHE_CONTROL;
+
+    use crate::{ext::extract::Path, service_fn, RequestExtractExt};
+
+    use super::*;
+
+    fn router() -> Router {
+        Router::new()
+            .get(
+                "/users/{id}",
+                service_fn(|request: Request| async move {
+                    let Path((id,)) = request.extract::<Path<(u64,)>>()?;
+                    Ok::<_, Error>(format!("user {id}"))
+                }),
+            )
+            .get("/users/me", service_fn(|_: Request| async { Ok::<_, Error>("me") }))
+            .delete(
+                "/users/{id}",
+                service_fn(|_: Request| async { Ok::<_, Error>((StatusCode::NO_CONTENT, "")) }),
+            )
+            .get(
+                "/teams/{team}/files/{path+}",
+                service_fn(|request: Request| async move {
+                    let Path((team, path)) = request.extract::<Path<(String, String)>>()?;
+                    let matched = request.extensions().get::<MatchedPath>().unwrap().as_str().to_string();
+                    Ok::<_, Error>(format!("{matched}: {team} {path}"))
+                }),
+            )
+    }
+
+    async fn call(router: &Router, method: Method, path: &str) -> Response<Body> {
+        let request = http::Request::builder()
+            .method(method)
+            .uri(format!("https://example.com{path}"))
+            .body(Body::Empty)
+            .unwrap();
+        router.clone().oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn route_by_method_and_path() {
+        let router = router();
+
+        let response = call(&router, Method::GET, "/users/42").await;
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(&Body::from("user 42"), response.body());
+
+        let response = call(&router, Method::GET, "/users/me").await;
+        assert_eq!(&Body::from("me"), response.body());
+
+        let response = call(&router, Method::DELETE, "/users/42").await;
+        assert_eq!(StatusCode::NO_CONTENT, response.status());
+
+        let response = call(&router, Method::GET, "/teams/rust%20lang/files/docs/README.md").await;
+        assert_eq!(
+            &Body::from("/teams/{team}/files/{path+}: rust lang docs/README.md"),
+            response.body()
+        );
+    }
+
+    #[tokio::test]
+    async fn answer_unknown_routes_and_methods() {
+        let router = router();
+
+        let response = call(&router, Method::GET, "/teams/rust/files").await;
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        let response = call(&router, Method::GET, "/users/42/posts").await;
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+        let response = call(&router, Method::POST, "/users/42").await;
+        assert_eq!(StatusCode::METHOD_NOT_ALLOWED, response.status());
+        assert_eq!("GET, DELETE", response.headers()[ALLOW]);
+
+        let router = router.fallback(service_fn(|request: Request| async move {
+            Ok::<_, Error>(format!("nothing at {}", request.uri().path()))
+        }));
+        let response = call(&router, Method::GET, "/nothing").await;
+        assert_eq!(&Body::from("nothing at /nothing"), response.body());
+    }
+
+    #[tokio::test]
+    async fn return_handler_errors() {
+        let router = router();
+        let request = http::Request::builder()
+            .uri("https://example.com/users/ferris")
+            .body(Body::Empty)
+            .unwrap();
+        let err = router.oneshot(request).await.unwrap_err();
+        assert_eq!(
+            "failed to parse path parameter id: invalid digit found in string",
+            err.to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn apply_cache_policies_of_routes() {
+        let router = router()
+            .cache("/users/{id}", CachePolicy::no_store())
+            .cache("/teams/{team}/files/{path+}", CachePolicy::immutable());
+
+        let response = call(&router, Method::GET, "/users/42").await;
+        assert_eq!("no-store", response.headers()[CACHE_CONTROL]);
+        let response = call(&router, Method::DELETE, "/users/42").await;
+        assert_eq!("no-store", response.headers()[CACHE_CONTROL]);
+        let response = call(&router, Method::GET, "/teams/rust/files/logo.png").await;
+        assert_eq!("public, max-age=31536000, immutable", response.headers()[CACHE_CONTROL]);
+        let response = call(&router, Method::GET, "/users/me").await;
+        assert!(!response.headers().contains_key(CACHE_CONTROL));
+    }
+
+    #[test]
+    #[should_panic(expected = "the route /users/{id} already has a handler for GET")]
+    fn reject_duplicate_routes() {
+        let handler = service_fn(|_: Request| async { Ok::<_, Error>("") });
+        let _ = Router::new().get("/users/{id}", handler).get("/users/{id}", handler);
+    }
+
+    #[test]
+    #[should_panic(expected = "the parameter {path+} of the route /files/{path+}/raw isn't its last segment")]
+    fn reject_invalid_templates() {
+        let _ = Router::new().any(
+            "/files/{path+}/raw",
+            service_fn(|_: Request| async { Ok::<_, Error>("") }),
+        );
+    }
+}
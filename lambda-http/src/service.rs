@@ -0,0 +1,209 @@
+use std::{
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    task::{self, Poll},
+};
+
+use bytes::Bytes;
+use http_body::Body as HttpBody;
+use http_body_util::Full;
+
+use crate::{
+    tower::{util::Oneshot, ServiceExt},
+    Body, Error, IntoResponse, Request, Response, Service,
+};
+
+/// Body of the requests that [`run_service`] sends to services, in place of hyper's `Incoming`.
+///
+/// hyper only creates `Incoming` bodies in its own server, so services that take a
+/// `Request<Incoming>` need this type instead. Services that are generic over the body of
+/// requests, like the routers of axum or the stacks of tower-http, take it unchanged.
+pub type IncomingBody = Full<Bytes>;
+
+/// Adapter that lifts a [`Service`] of `http::Request<IncomingBody>`, like the ones of hyper
+/// servers, into a [`Service<Request>`] that runs on Lambda.
+///
+/// The bodies of requests are passed as [`IncomingBody`], with the extensions of the request,
+/// so services can still use [`RequestExt`](crate::RequestExt). tower services may be busy
+/// until their [`poll_ready`](Service::poll_ready), which hyper never calls: the adapter is
+/// always ready, and each request waits on a clone of the service until it is ready, like
+/// hyper-util does. A service that fails to get ready fails its invocation, not the runtime.
+#[derive(Debug, Clone)]
+pub struct ServiceAdapter<S> {
+    service: S,
+}
+
+impl<S> ServiceAdapter<S> {
+    /// Create an adapter for a service.
+    pub fn new(service: S) -> Self {
+        Self { service }
+    }
+
+    /// Service of the adapter.
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+}
+
+impl<S, B> Service<Request> for ServiceAdapter<S>
+where
+    S: Service<http::Request<IncomingBody>, Response = Response<B>> + Clone,
+    S::Error: Into<Error>,
+{
+    type Response = Response<B>;
+    type Error = Error;
+    type Future = ServiceAdapterFuture<S>;
+
+    fn poll_ready(&mut self, _cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let request = request.map(|body| Full::new(into_bytes(body)));
+        ServiceAdapterFuture {
+            future: self.service.clone().oneshot(request),
+        }
+    }
+}
+
+fn into_bytes(body: Body) -> Bytes {
+    match body {
+        Body::Text(text) => text.into(),
+        Body::Binary(bytes) => bytes,
+        _ => Bytes::new(),
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Future returned by [`ServiceAdapter`].
+    pub struct ServiceAdapterFuture<S>
+    where
+        S: Service<http::Request<IncomingBody>>,
+    {
+        #[pin]
+        future: Oneshot<S, http::Request<IncomingBody>>,
+    }
+}
+
+impl<S> Future for ServiceAdapterFuture<S>
+where
+    S: Service<http::Request<IncomingBody>>,
+    S::Error: Into<Error>,
+{
+    type Output = Result<S::Response, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        self.project().future.poll(cx).map_err(Into::into)
+    }
+}
+
+/// Starts the Lambda Rust runtime with a tower [`Service`] of `http::Request<IncomingBody>`, so
+/// apps written for hyper run on Lambda unchanged.
+///
+/// The bodies of responses are buffered, like the ones of [`run`](crate::run). Use
+/// [`run_service_with_streaming_response`] to stream them.
+///
+/// ```rust,no_run
+/// use lambda_http::{http, tower::service_fn, Error, IncomingBody};
+/// use std::convert::Infallible;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     lambda_http::run_service(service_fn(hello)).await
+/// }
+///
+/// async fn hello(_request: http::Request<IncomingBody>) -> Result<http::Response<String>, Infallible> {
+///     Ok(http::Response::new("hello".to_string()))
+/// }
+/// ```
+///
+/// # Panics
+///
+/// This function panics if required Lambda environment variables are missing
+/// (`AWS_LAMBDA_FUNCTION_NAME`, `AWS_LAMBDA_FUNCTION_MEMORY_SIZE`,
+/// `AWS_LAMBDA_FUNCTION_VERSION`, `AWS_LAMBDA_RUNTIME_API`).
+pub async fn run_service<S, B>(service: S) -> Result<(), Error>
+where
+    S: Service<http::Request<IncomingBody>, Response = Response<B>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<Error>,
+    Response<B>: IntoResponse,
+{
+    crate::run(ServiceAdapter::new(service)).await
+}
+
+/// Starts the Lambda Rust runtime with a tower [`Service`] of `http::Request<IncomingBody>`,
+/// whose responses are **streamed** to clients, like the ones of
+/// [`run_with_streaming_response`](crate::run_with_streaming_response).
+///
+/// # Panics
+///
+/// This function panics if required Lambda environment variables are missing
+/// (`AWS_LAMBDA_FUNCTION_NAME`, `AWS_LAMBDA_FUNCTION_MEMORY_SIZE`,
+/// `AWS_LAMBDA_FUNCTION_VERSION`, `AWS_LAMBDA_RUNTIME_API`).
+pub async fn run_service_with_streaming_response<S, B>(service: S) -> Result<(), Error>
+where
+    S: Service<http::Request<IncomingBody>, Response = Response<B>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<Error>,
+    B: HttpBody + Unpin + Send + 'static,
+    B::Data: Into<Bytes> + Send,
+    B::Error: Into<Error> + Send + Debug,
+{
+    crate::run_with_streaming_response(ServiceAdapter::new(service)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use http_body_util::BodyExt;
+
+    use super::*;
+    use crate::{tower::service_fn, Context, RequestExt};
+
+    #[tokio::test]
+    async fn pass_requests_to_services() {
+        let service = service_fn(|request: http::Request<IncomingBody>| async move {
+            let request_id = request.lambda_context_ref().map(|context| context.request_id.clone());
+            let body = request.into_body().collect().await?.to_bytes();
+            let body = format!("{} {}", request_id.unwrap_or_default(), String::from_utf8_lossy(&body));
+            Ok::<_, Infallible>(http::Response::new(body))
+        });
+        let mut context = Context::default();
+        context.request_id = "id".to_string();
+        let request = http::Request::builder()
+            .body(Body::from("hello"))
+            .unwrap()
+            .with_lambda_context(context);
+
+        let response = ServiceAdapter::new(service).oneshot(request).await.unwrap();
+        assert_eq!(&Body::from("id hello"), response.into_response().await.body());
+    }
+
+    #[derive(Clone)]
+    struct Unavailable;
+
+    impl Service<http::Request<IncomingBody>> for Unavailable {
+        type Response = Response<String>;
+        type Error = Error;
+        type Future = std::future::Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Err("unavailable".into()))
+        }
+
+        fn call(&mut self, _request: http::Request<IncomingBody>) -> Self::Future {
+            unreachable!("the service isn't ready")
+        }
+    }
+
+    #[tokio::test]
+    async fn fail_invocations_of_services_that_are_not_ready() {
+        let mut adapter = ServiceAdapter::new(Unavailable);
+        let request = http::Request::new(Body::Empty);
+        let err = adapter.ready().await.unwrap().call(request).await.unwrap_err();
+        assert_eq!("unavailable", err.to_string());
+    }
+}
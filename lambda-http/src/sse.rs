@@ -0,0 +1,350 @@
+//! [Server-Sent Events](https://html.spec.whatwg.org/multipage/server-sent-events.html) over
+//! streaming responses.
+//!
+//! An [`SseResponse`] turns a stream of [`Event`]s into a `text/event-stream` response, which
+//! Function URLs stream to the client when their invoke mode is `RESPONSE_STREAM`. Run the
+//! handler with [`run_with_streaming_response`](crate::run_with_streaming_response).
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//!
+//! use futures_util::StreamExt;
+//! use lambda_http::{
+//!     service_fn,
+//!     sse::{Event, SseResponse},
+//!     Error, Request, Response, StreamBody,
+//! };
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Token {
+//!     text: String,
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Error> {
+//!     lambda_http::run_with_streaming_response(service_fn(tokens)).await
+//! }
+//!
+//! async fn tokens(_request: Request) -> Result<Response<StreamBody>, Error> {
+//!     let tokens = futures_util::stream::iter(["Hello", " from", " Lambda"]).map(|text| {
+//!         Event::default()
+//!             .event("token")
+//!             .json_data(&Token { text: text.to_string() })
+//!     });
+//!     Ok(SseResponse::new(tokens)
+//!         .keep_alive(Duration::from_secs(15))
+//!         .into_response())
+//! }
+//! ```
+
+use std::{
+    fmt::{self, Write},
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use futures_util::Stream;
+use http::{
+    header::{CACHE_CONTROL, CONTENT_TYPE},
+    HeaderValue,
+};
+use serde::Serialize;
+use tokio::time::{sleep, Instant, Sleep};
+
+use crate::{Error, Response, StreamBody};
+
+/// Event of a Server-Sent Events stream.
+///
+/// Fields that aren't set are left out of the event. Clients ignore events without data.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Event {
+    event: Option<String>,
+    data: Option<String>,
+    id: Option<String>,
+    retry: Option<Duration>,
+    comment: Option<String>,
+}
+
+impl Event {
+    /// Set the type of the event, which clients listen to with `addEventListener`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type contains a line break.
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(single_line("event", event.into()));
+        self
+    }
+
+    /// Set the data of the event. Data with several lines is sent in several `data` fields,
+    /// which clients join with line breaks.
+    pub fn data(mut self, data: impl Into<String>) -> Self {
+        self.data = Some(data.into());
+        self
+    }
+
+    /// Set the data of the event to the JSON serialization of `data`.
+    pub fn json_data<T: Serialize + ?Sized>(self, data: &T) -> Result<Self, serde_json::Error> {
+        Ok(self.data(serde_json::to_string(data)?))
+    }
+
+    /// Set the id of the event, which clients send back in the `Last-Event-ID` header when they
+    /// reconnect.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id contains a line break or a null character.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        let id = single_line("id", id.into());
+        assert!(!id.contains('\0'), "the id of an event can't contain null characters");
+        self.id = Some(id);
+        self
+    }
+
+    /// Set how long clients wait before they reconnect when the connection is lost.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Set a comment, which clients ignore. Comments with several lines are sent as several
+    /// comment lines.
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+}
+
+/// Formats the event as it's sent in the stream, ending with a blank line.
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(comment) = &self.comment {
+            for line in split_lines(comment) {
+                writeln!(f, ":{line}")?;
+            }
+        }
+        if let Some(event) = &self.event {
+            writeln!(f, "event: {event}")?;
+        }
+        if let Some(id) = &self.id {
+            writeln!(f, "id: {id}")?;
+        }
+        if let Some(retry) = self.retry {
+            writeln!(f, "retry: {}", retry.as_millis())?;
+        }
+        if let Some(data) = &self.data {
+            for line in split_lines(data) {
+                writeln!(f, "data: {line}")?;
+            }
+        }
+        f.write_char('\n')
+    }
+}
+
+/// Split `text` on the line breaks of event streams, `\r\n`, `\r`, and `\n`, keeping the empty
+/// line after a line break at the end, unlike [`str::lines`].
+fn split_lines(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = Some(text);
+    std::iter::from_fn(move || {
+        let text = rest?;
+        let Some(end) = text.find(['\r', '\n']) else {
+            rest = None;
+            return Some(text);
+        };
+        let next = if text[end..].starts_with("\r\n") {
+            end + 2
+        } else {
+            end + 1
+        };
+        rest = Some(&text[next..]);
+        Some(&text[..end])
+    })
+}
+
+fn single_line(field: &str, value: String) -> String {
+    assert!(
+        !value.contains(['\n', '\r']),
+        "the {field} of an event can't contain line breaks"
+    );
+    value
+}
+
+/// Builder of a `text/event-stream` response from a stream of events.
+///
+/// The response ends with the stream. An error in the stream aborts the response, since
+/// Server-Sent Events can't report errors after the response started.
+#[must_use = "the events are only sent when the response is returned"]
+pub struct SseResponse<S> {
+    events: S,
+    keep_alive: Option<Duration>,
+}
+
+impl<S, E> SseResponse<S>
+where
+    S: Stream<Item = Result<Event, E>> + Send + 'static,
+    E: Into<Error> + 'static,
+{
+    /// Create a response that sends the events of the stream.
+    pub fn new(events: S) -> Self {
+        Self {
+            events,
+            keep_alive: None,
+        }
+    }
+
+    /// Send a comment when no event was sent for this interval, so that clients and proxies
+    /// don't close idle connections.
+    pub fn keep_alive(mut self, interval: Duration) -> Self {
+        self.keep_alive = Some(interval);
+        self
+    }
+
+    /// Build the streaming response, with the `text/event-stream` content type.
+    pub fn into_response(self) -> Response<StreamBody> {
+        let events = EventStream {
+            events: Box::pin(self.events),
+            keep_alive: self.keep_alive.map(|interval| (interval, Box::pin(sleep(interval)))),
+        };
+        let mut response = Response::new(StreamBody::from_stream(events));
+        let headers = response.headers_mut();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        response
+    }
+}
+
+impl<S, E> From<SseResponse<S>> for Response<StreamBody>
+where
+    S: Stream<Item = Result<Event, E>> + Send + 'static,
+    E: Into<Error> + 'static,
+{
+    fn from(response: SseResponse<S>) -> Self {
+        response.into_response()
+    }
+}
+
+impl<S> fmt::Debug for SseResponse<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SseResponse")
+            .field("keep_alive", &self.keep_alive)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Frames the events of a stream, and interleaves keep-alive comments.
+struct EventStream<S> {
+    events: Pin<Box<S>>,
+    keep_alive: Option<(Duration, Pin<Box<Sleep>>)>,
+}
+
+impl<S, E> Stream for EventStream<S>
+where
+    S: Stream<Item = Result<Event, E>>,
+    E: Into<Error>,
+{
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        match this.events.as_mut().poll_next(cx) {
+            Poll::Ready(Some(Ok(event))) => {
+                if let Some((interval, sleep)) = &mut this.keep_alive {
+                    sleep.as_mut().reset(Instant::now() + *interval);
+                }
+                Poll::Ready(Some(Ok(Bytes::from(event.to_string()))))
+            }
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err.into()))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => {
+                let Some((interval, sleep)) = &mut this.keep_alive else {
+                    return Poll::Pending;
+                };
+                if sleep.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                sleep.as_mut().reset(Instant::now() + *interval);
+                Poll::Ready(Some(Ok(Bytes::from_static(b":\n\n"))))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use futures_util::StreamExt;
+    use http_body_util::BodyExt;
+
+    use super::*;
+
+    #[test]
+    fn format_events() {
+        let event = Event::default()
+            .event("token")
+            .id("42")
+            .retry(Duration::from_secs(3))
+            .data("first line\nsecond line\r\n");
+        assert_eq!(
+            "event: token\nid: 42\nretry: 3000\ndata: first line\ndata: second line\ndata: \n\n",
+            event.to_string()
+        );
+
+        let event = Event::default()
+            .comment("keep\nalive")
+            .json_data(&serde_json::json!({"text": "hi"}))
+            .unwrap();
+        assert_eq!(":keep\n:alive\ndata: {\"text\":\"hi\"}\n\n", event.to_string());
+    }
+
+    #[test]
+    fn split_data_on_carriage_returns() {
+        let event = Event::default()
+            .comment("keep\revent: injected")
+            .data("first\rid: injected\r\nlast");
+        assert_eq!(
+            ":keep\n:event: injected\ndata: first\ndata: id: injected\ndata: last\n\n",
+            event.to_string()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "the event of an event can't contain line breaks")]
+    fn reject_multiline_event_types() {
+        let _ = Event::default().event("token\ndata: injected");
+    }
+
+    #[tokio::test]
+    async fn stream_events() {
+        let events =
+            futures_util::stream::iter(["a", "b"]).map(|data| Ok::<_, Infallible>(Event::default().data(data)));
+        let response = SseResponse::new(events).into_response();
+        assert_eq!("text/event-stream", response.headers()[CONTENT_TYPE]);
+        assert_eq!("no-cache", response.headers()[CACHE_CONTROL]);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!("data: a\n\ndata: b\n\n", body);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn send_keep_alive_comments() {
+        let events = futures_util::stream::once(async {
+            sleep(Duration::from_secs(15)).await;
+            Ok::<_, Infallible>(Event::default().data("late"))
+        });
+        let mut body = SseResponse::new(events)
+            .keep_alive(Duration::from_secs(10))
+            .into_response()
+            .into_body();
+
+        let frame = body.frame().await.unwrap().unwrap();
+        assert_eq!(":\n\n", frame.into_data().unwrap());
+        let frame = body.frame().await.unwrap().unwrap();
+        assert_eq!("data: late\n\n", frame.into_data().unwrap());
+        assert!(body.frame().await.is_none());
+    }
+}
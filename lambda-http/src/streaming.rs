@@ -5,9 +5,10 @@ use core::{
     pin::Pin,
     task::{Context, Poll},
 };
-use futures_util::{Stream, TryFutureExt};
+use futures_util::{Stream, TryFutureExt, TryStreamExt};
 pub use http::{self, Response};
-use http_body::Body;
+use http_body::{Body, Frame, SizeHint};
+use lambda_runtime::streaming::{Body as RuntimeBody, Sender};
 use lambda_runtime::{
     tower::{
         util::{MapRequest, MapResponse},
@@ -18,6 +19,95 @@ use lambda_runtime::{
 pub use lambda_runtime::{Error, LambdaEvent, MetadataPrelude, Service, StreamResponse};
 use std::{future::Future, marker::PhantomData};
 
+/// Body of a streaming response, sent to the client chunk by chunk as the handler produces it.
+///
+/// Handlers that run with [`run_with_streaming_response`] return a `Response<StreamBody>`, with
+/// the same status code, headers, and cookies as buffered responses. They are sent to the client
+/// before the first chunk of the body, so server-side rendered pages and large downloads start
+/// as soon as possible. Function URLs stream responses when their invoke mode is
+/// `RESPONSE_STREAM`.
+///
+/// ```rust,no_run
+/// use lambda_http::{http::header::CONTENT_TYPE, service_fn, Error, Request, Response, StreamBody};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     lambda_http::run_with_streaming_response(service_fn(render)).await
+/// }
+///
+/// async fn render(_request: Request) -> Result<Response<StreamBody>, Error> {
+///     let (mut sender, body) = StreamBody::channel();
+///     tokio::spawn(async move {
+///         sender.send_data("<html><body>".into()).await?;
+///         for item in ["rendered", "while", "streaming"] {
+///             sender.send_data(format!("<p>{item}</p>").into()).await?;
+///         }
+///         sender.send_data("</body></html>".into()).await
+///     });
+///     Ok(Response::builder().header(CONTENT_TYPE, "text/html").body(body)?)
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct StreamBody(RuntimeBody);
+
+impl StreamBody {
+    /// Create a body whose chunks are sent with the returned [`StreamSender`](crate::StreamSender), usually from a
+    /// spawned task. The response ends when the sender is dropped.
+    pub fn channel() -> (Sender, Self) {
+        let (sender, body) = RuntimeBody::channel();
+        (sender, Self(body))
+    }
+
+    /// Create a body that sends the chunks of a stream. The response ends with the stream, or is
+    /// aborted at its first error.
+    pub fn from_stream<S, D, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<D, E>> + Send + 'static,
+        D: Into<Bytes>,
+        E: Into<Error> + 'static,
+    {
+        let frames = stream.map_ok(|data| Frame::data(data.into())).map_err(Into::into);
+        Self(RuntimeBody::new(http_body_util::StreamBody::new(frames)))
+    }
+}
+
+macro_rules! stream_body_from {
+    ($($ty:ty),+) => {
+        $(
+            impl From<$ty> for StreamBody {
+                fn from(body: $ty) -> Self {
+                    Self(RuntimeBody::from(body))
+                }
+            }
+        )+
+    };
+}
+
+stream_body_from!(&'static str, String, &'static [u8], Vec<u8>, Bytes);
+
+impl From<crate::Body> for StreamBody {
+    fn from(body: crate::Body) -> Self {
+        Self(RuntimeBody::new(body))
+    }
+}
+
+impl Body for StreamBody {
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Bytes>, Error>>> {
+        Pin::new(&mut self.0).poll_frame(cx).map_err(Into::into)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        Body::is_end_stream(&self.0)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        Body::size_hint(&self.0)
+    }
+}
+
 /// An adapter that lifts a standard [`Service<Request>`] into a
 /// [`Service<LambdaEvent<LambdaRequest>>`] which produces streaming Lambda HTTP
 /// responses.
@@ -261,6 +351,7 @@ where
 #[cfg(test)]
 mod test_stream_adapter {
     use super::*;
+    use futures_util::StreamExt;
 
     use crate::Body;
     use http::StatusCode;
@@ -300,4 +391,37 @@ mod test_stream_adapter {
         // Also works when the stack is boxed (type-erased)
         let _boxed_svc = svc.boxed();
     }
+
+    #[tokio::test]
+    async fn stream_response_chunks() {
+        let chunks = futures_util::stream::iter(["first ", "second ", "third"]).map(Ok::<_, Error>);
+        let response = http::Response::builder()
+            .status(StatusCode::CREATED)
+            .header("content-type", "text/plain")
+            .header(SET_COOKIE, "session=1")
+            .body(StreamBody::from_stream(chunks))
+            .unwrap();
+
+        let StreamResponse {
+            metadata_prelude,
+            stream,
+        } = into_stream_response(response);
+        assert_eq!(StatusCode::CREATED, metadata_prelude.status_code);
+        assert_eq!("text/plain", metadata_prelude.headers["content-type"]);
+        assert_eq!(vec!["session=1".to_string()], metadata_prelude.cookies);
+        let chunks: Vec<Bytes> = stream.map(Result::unwrap).collect().await;
+        assert_eq!(vec!["first ", "second ", "third"], chunks);
+    }
+
+    #[tokio::test]
+    async fn stream_chunks_from_a_channel() {
+        let (mut sender, body) = StreamBody::channel();
+        tokio::spawn(async move {
+            sender.send_data("hello ".into()).await.unwrap();
+            sender.send_data("world".into()).await.unwrap();
+        });
+        let StreamResponse { stream, .. } = into_stream_response(http::Response::new(body));
+        let chunks: Vec<Bytes> = stream.map(Result::unwrap).collect().await;
+        assert_eq!(vec!["hello ", "world"], chunks);
+    }
 }
@@ -0,0 +1,481 @@
+//! Handlers for [API Gateway WebSocket APIs](https://docs.aws.amazon.com/apigateway/latest/developerguide/apigateway-websocket-api.html).
+//!
+//! API Gateway invokes the function with the route that matched a message, and the
+//! connection that sent it. A [`WebSocketRequest`] exposes both of them, and a
+//! [`WebSocketResponse`] accepts or rejects the connection, or replies to the client when the
+//! route has a route response.
+//!
+//! ```rust,no_run
+//! use lambda_http::{
+//!     service_fn,
+//!     websocket::{Route, WebSocketRequest, WebSocketResponse},
+//!     Error, Request,
+//! };
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Error> {
+//!     lambda_http::run(service_fn(handler)).await
+//! }
+//!
+//! async fn handler(request: Request) -> Result<WebSocketResponse, Error> {
+//!     let request = WebSocketRequest::try_from(request)?;
+//!     Ok(match request.route() {
+//!         Route::Connect if request.request().headers().contains_key("x-api-key") => WebSocketResponse::ok(),
+//!         Route::Connect => WebSocketResponse::reject(http::StatusCode::UNAUTHORIZED),
+//!         Route::Custom(key) if key == "ping" => WebSocketResponse::reply("pong"),
+//!         _ => WebSocketResponse::ok(),
+//!     })
+//! }
+//! ```
+//!
+//! Messages are sent to clients outside of a route response through the
+//! [`@connections` management API](https://docs.aws.amazon.com/apigateway/latest/developerguide/apigateway-how-to-call-websocket-api-connections.html).
+//! Implement [`ManagementApi`] for the client that calls it, usually the `ApiGatewayManagement`
+//! client of the AWS SDK, created with the endpoint of [`Connection::management_endpoint`], or
+//! an HTTP client that signs the requests of [`Connection::post_request`] and
+//! [`Connection::delete_request`].
+
+use std::{
+    error::Error,
+    fmt,
+    future::Future,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use aws_lambda_events::apigw::ApiGatewayWebsocketProxyRequestContext;
+use bytes::Bytes;
+use http::{header::CONTENT_TYPE, HeaderValue, Method, StatusCode};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::de::DeserializeOwned;
+
+use crate::{request::RequestContext, response::ResponseFuture, Body, IntoResponse, Request, RequestExt, Response};
+
+/// Route that matched a message of a WebSocket API, from its route key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Route {
+    /// `$connect`, when a client opens a connection.
+    Connect,
+    /// `$disconnect`, after a connection was closed.
+    Disconnect,
+    /// `$default`, when no other route matched a message.
+    Default,
+    /// Route of the API, selected with its route selection expression.
+    Custom(String),
+}
+
+impl Route {
+    /// Route of a route key.
+    pub fn from_key(key: &str) -> Self {
+        match key {
+            "$connect" => Route::Connect,
+            "$disconnect" => Route::Disconnect,
+            "$default" => Route::Default,
+            key => Route::Custom(key.to_string()),
+        }
+    }
+
+    /// Route key of the route.
+    pub fn key(&self) -> &str {
+        match self {
+            Route::Connect => "$connect",
+            Route::Disconnect => "$disconnect",
+            Route::Default => "$default",
+            Route::Custom(key) => key,
+        }
+    }
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.key())
+    }
+}
+
+/// Connection of a client to a WebSocket API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connection {
+    id: String,
+    api_id: Option<String>,
+    domain_name: Option<String>,
+    stage: Option<String>,
+    connected_at: SystemTime,
+    message_id: Option<String>,
+    disconnect_status_code: Option<u16>,
+    disconnect_reason: Option<String>,
+}
+
+impl Connection {
+    /// Id of the connection, which identifies the client in the management API.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Id of the API.
+    pub fn api_id(&self) -> Option<&str> {
+        self.api_id.as_deref()
+    }
+
+    /// Domain name that the client connected to.
+    pub fn domain_name(&self) -> Option<&str> {
+        self.domain_name.as_deref()
+    }
+
+    /// Stage of the API that the client connected to.
+    pub fn stage(&self) -> Option<&str> {
+        self.stage.as_deref()
+    }
+
+    /// Time when the client connected.
+    pub fn connected_at(&self) -> SystemTime {
+        self.connected_at
+    }
+
+    /// Id of the message, for messages sent by the client.
+    pub fn message_id(&self) -> Option<&str> {
+        self.message_id.as_deref()
+    }
+
+    /// Close code of the connection, on the `$disconnect` route.
+    pub fn disconnect_status_code(&self) -> Option<u16> {
+        self.disconnect_status_code
+    }
+
+    /// Reason why the connection was closed, on the `$disconnect` route.
+    pub fn disconnect_reason(&self) -> Option<&str> {
+        self.disconnect_reason.as_deref()
+    }
+
+    /// Endpoint of the management API for the connection, `https://{domain name}/{stage}`.
+    ///
+    /// Returns `None` when the event doesn't include the domain name or the stage.
+    pub fn management_endpoint(&self) -> Option<String> {
+        Some(format!("https://{}/{}", self.domain_name()?, self.stage()?))
+    }
+
+    /// URL of the connection in the management API,
+    /// `https://{domain name}/{stage}/@connections/{id}`.
+    ///
+    /// Returns `None` when the event doesn't include the domain name or the stage.
+    pub fn management_url(&self) -> Option<String> {
+        let id = utf8_percent_encode(&self.id, CONNECTION_ID);
+        Some(format!("{}/@connections/{id}", self.management_endpoint()?))
+    }
+
+    /// Request of the management API that sends `data` to the client of the connection.
+    ///
+    /// The request isn't signed: sign it with SigV4 for the `execute-api` service before
+    /// sending it. Returns `None` when the event doesn't include the domain name or the stage.
+    pub fn post_request(&self, data: Bytes) -> Option<http::Request<Bytes>> {
+        let mut request = self.management_request(Method::POST, data)?;
+        request
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/octet-stream"));
+        Some(request)
+    }
+
+    /// Request of the management API that closes the connection, unsigned like
+    /// [`Connection::post_request`].
+    pub fn delete_request(&self) -> Option<http::Request<Bytes>> {
+        self.management_request(Method::DELETE, Bytes::new())
+    }
+
+    fn management_request(&self, method: Method, body: Bytes) -> Option<http::Request<Bytes>> {
+        http::Request::builder()
+            .method(method)
+            .uri(self.management_url()?)
+            .body(body)
+            .ok()
+    }
+}
+
+/// Characters that are encoded in connection ids, which are base64 and end with `=`.
+const CONNECTION_ID: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+/// Request of a WebSocket API, with its route and connection.
+///
+/// Created from a [`Request`] that API Gateway sent for a WebSocket API.
+#[derive(Debug)]
+pub struct WebSocketRequest {
+    route: Route,
+    connection: Connection,
+    request: Request,
+}
+
+impl WebSocketRequest {
+    /// Route that matched the message.
+    pub fn route(&self) -> &Route {
+        &self.route
+    }
+
+    /// Connection of the client.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Message sent by the client. It's empty on the `$connect` and `$disconnect` routes.
+    pub fn body(&self) -> &Body {
+        self.request.body()
+    }
+
+    /// Deserialize the JSON message sent by the client.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(self.body().as_ref())
+    }
+
+    /// The request that the WebSocket request was created from, with the headers and query
+    /// string parameters that the client sent on the `$connect` route.
+    pub fn request(&self) -> &Request {
+        &self.request
+    }
+
+    /// Take back the request that the WebSocket request was created from.
+    pub fn into_request(self) -> Request {
+        self.request
+    }
+}
+
+impl TryFrom<Request> for WebSocketRequest {
+    type Error = NotWebSocketRequest;
+
+    fn try_from(request: Request) -> Result<Self, Self::Error> {
+        let context = match request.request_context_ref() {
+            Some(RequestContext::WebSocket(context)) => context,
+            _ => return Err(NotWebSocketRequest(())),
+        };
+        let route = Route::from_key(context.route_key.as_deref().ok_or(NotWebSocketRequest(()))?);
+        let connection = connection(context).ok_or(NotWebSocketRequest(()))?;
+        Ok(Self {
+            route,
+            connection,
+            request,
+        })
+    }
+}
+
+fn connection(context: &ApiGatewayWebsocketProxyRequestContext) -> Option<Connection> {
+    Some(Connection {
+        id: context.connection_id.clone()?,
+        api_id: context.apiid.clone(),
+        domain_name: context.domain_name.clone(),
+        stage: context.stage.clone(),
+        connected_at: UNIX_EPOCH + Duration::from_millis(context.connected_at.try_into().unwrap_or_default()),
+        message_id: context.message_id.clone(),
+        disconnect_status_code: context.disconnect_status_code.and_then(|code| code.try_into().ok()),
+        disconnect_reason: context.disconnect_reason.clone(),
+    })
+}
+
+/// Error returned when a [`WebSocketRequest`] is created from a request that doesn't come from
+/// a WebSocket API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotWebSocketRequest(());
+
+impl fmt::Display for NotWebSocketRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the request doesn't come from a WebSocket API")
+    }
+}
+
+impl Error for NotWebSocketRequest {}
+
+/// Response of a WebSocket route.
+///
+/// On the `$connect` route, a successful status accepts the connection and any other status
+/// rejects it. On other routes, the body is sent to the client when the route has a route
+/// response.
+#[derive(Debug, PartialEq)]
+pub struct WebSocketResponse {
+    status: StatusCode,
+    body: Body,
+}
+
+impl WebSocketResponse {
+    /// Accept the connection or the message, without replying.
+    pub fn ok() -> Self {
+        Self {
+            status: StatusCode::OK,
+            body: Body::Empty,
+        }
+    }
+
+    /// Reject the connection with `status`.
+    pub fn reject(status: StatusCode) -> Self {
+        Self {
+            status,
+            body: Body::Empty,
+        }
+    }
+
+    /// Reply to the client with `body`.
+    pub fn reply(body: impl Into<Body>) -> Self {
+        Self {
+            status: StatusCode::OK,
+            body: body.into(),
+        }
+    }
+
+    /// Status of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Body of the response.
+    pub fn body(&self) -> &Body {
+        &self.body
+    }
+}
+
+impl From<WebSocketResponse> for Response<Body> {
+    fn from(response: WebSocketResponse) -> Self {
+        let mut http_response = Response::new(response.body);
+        *http_response.status_mut() = response.status;
+        http_response
+    }
+}
+
+impl IntoResponse for WebSocketResponse {
+    fn into_response(self) -> ResponseFuture {
+        Response::<Body>::from(self).into_response()
+    }
+}
+
+/// Client of the `@connections` management API of a WebSocket API.
+///
+/// lambda_http doesn't depend on the AWS SDK, so handlers implement this trait for the client
+/// they use, which lets them be tested with a fake client. Clients that aren't the AWS SDK can
+/// send the requests that [`Connection`] builds.
+pub trait ManagementApi {
+    /// Error returned by the client.
+    type Error;
+
+    /// Send `data` to the client of a connection.
+    fn post_to_connection(
+        &self,
+        connection_id: &str,
+        data: Bytes,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Close a connection.
+    fn delete_connection(&self, connection_id: &str) -> impl Future<Output = Result<(), Self::Error>> + Send;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    fn websocket_request(fixture: &str) -> WebSocketRequest {
+        WebSocketRequest::try_from(crate::request::from_str(fixture).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn parse_route_keys() {
+        assert_eq!(Route::Connect, Route::from_key("$connect"));
+        assert_eq!(Route::Disconnect, Route::from_key("$disconnect"));
+        assert_eq!(Route::Default, Route::from_key("$default"));
+        assert_eq!(Route::Custom("sendMessage".into()), Route::from_key("sendMessage"));
+        assert_eq!("sendMessage", Route::from_key("sendMessage").key());
+    }
+
+    #[test]
+    fn connect_request() {
+        let request = websocket_request(include_str!(
+            "../../lambda-events/src/fixtures/example-apigw-websocket-request-without-method.json"
+        ));
+        assert_eq!(&Route::Connect, request.route());
+
+        let connection = request.connection();
+        assert_eq!("asdfasdf=", connection.id());
+        assert_eq!(Some("asdfasdf"), connection.api_id());
+        assert_eq!(
+            UNIX_EPOCH + Duration::from_millis(1645556857902),
+            connection.connected_at()
+        );
+        assert_eq!(
+            Some("https://asdfasdf.execute-api.us-west-2.amazonaws.com/dev".to_string()),
+            connection.management_endpoint()
+        );
+        assert_eq!("13", request.request().headers()["sec-websocket-version"]);
+    }
+
+    #[test]
+    fn disconnect_request() {
+        let request = websocket_request(include_str!(
+            "../../lambda-events/src/fixtures/example-apigw-websocket-request-disconnect-route.json"
+        ));
+        assert_eq!(&Route::Disconnect, request.route());
+        assert_eq!(Some(1005), request.connection().disconnect_status_code());
+        assert_eq!(
+            Some("Client-side close frame status not set"),
+            request.connection().disconnect_reason()
+        );
+    }
+
+    #[test]
+    fn reject_other_requests() {
+        let request = crate::request::from_str(include_str!("../tests/data/apigw_v2_proxy_request.json")).unwrap();
+        assert_eq!(
+            NotWebSocketRequest(()),
+            WebSocketRequest::try_from(request).unwrap_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn convert_responses() {
+        let response = WebSocketResponse::reject(StatusCode::FORBIDDEN).into_response().await;
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+
+        let response = WebSocketResponse::reply("pong").into_response().await;
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(&Body::from("pong"), response.body());
+    }
+
+    // Client that sends the requests of the connection, like an HTTP client that signs them.
+    struct HttpManagementApi {
+        connection: Connection,
+        sent: Mutex<Vec<http::Request<Bytes>>>,
+    }
+
+    impl ManagementApi for HttpManagementApi {
+        type Error = NotWebSocketRequest;
+
+        async fn post_to_connection(&self, connection_id: &str, data: Bytes) -> Result<(), Self::Error> {
+            assert_eq!(self.connection.id(), connection_id);
+            let request = self.connection.post_request(data).ok_or(NotWebSocketRequest(()))?;
+            self.sent.lock().unwrap().push(request);
+            Ok(())
+        }
+
+        async fn delete_connection(&self, connection_id: &str) -> Result<(), Self::Error> {
+            assert_eq!(self.connection.id(), connection_id);
+            let request = self.connection.delete_request().ok_or(NotWebSocketRequest(()))?;
+            self.sent.lock().unwrap().push(request);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn post_through_management_api() {
+        let request = websocket_request(include_str!(
+            "../../lambda-events/src/fixtures/example-apigw-websocket-request-without-method.json"
+        ));
+        let api = HttpManagementApi {
+            connection: request.connection().clone(),
+            sent: Mutex::default(),
+        };
+        let id = request.connection().id();
+        api.post_to_connection(id, Bytes::from_static(b"hello")).await.unwrap();
+        api.delete_connection(id).await.unwrap();
+
+        let sent = api.sent.lock().unwrap();
+        let url = "https://asdfasdf.execute-api.us-west-2.amazonaws.com/dev/@connections/asdfasdf%3D";
+        assert_eq!(Method::POST, sent[0].method());
+        assert_eq!(url, sent[0].uri());
+        assert_eq!("application/octet-stream", sent[0].headers()[CONTENT_TYPE]);
+        assert_eq!(&Bytes::from_static(b"hello"), sent[0].body());
+        assert_eq!(Method::DELETE, sent[1].method());
+        assert_eq!(url, sent[1].uri());
+        assert!(sent[1].body().is_empty());
+    }
+}
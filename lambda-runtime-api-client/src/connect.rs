@@ -0,0 +1,173 @@
+//! Connectors that open the connections of a [Client](crate::Client) to the Runtime API.
+//!
+//! By default, the client opens plain TCP connections with hyper's [HttpConnector]. Use
+//! [ClientBuilder::with_connector](crate::ClientBuilder::with_connector) to connect over TLS,
+//! through the Unix socket of a local emulator with a `UnixConnector`, or with a recording
+//! connector in tests.
+use crate::BoxError;
+use futures_util::future::BoxFuture;
+use http::Uri;
+use hyper::rt::{Read, ReadBufCursor, Write};
+use std::{
+    fmt,
+    io::{self, IoSlice},
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tower::{util::BoxCloneSyncService, Service, ServiceExt};
+#[cfg(all(unix, feature = "unix-socket"))]
+use {
+    futures_util::FutureExt,
+    hyper_util::rt::TokioIo,
+    std::{path::Path, sync::Arc},
+    tokio::net::UnixStream,
+};
+
+pub use hyper_util::client::legacy::connect::{Connected, Connection, HttpConnector};
+
+trait Io: Read + Write + Connection + Send + Unpin + 'static {}
+
+impl<T: Read + Write + Connection + Send + Unpin + 'static> Io for T {}
+
+/// Delay before the default connector tries the addresses of the other IP family of a host.
+pub const DUAL_STACK_FALLBACK: Duration = Duration::from_millis(300);
+
+/// Connection opened by a [DynConnector].
+pub struct DynConnection(Box<dyn Io>);
+
+impl fmt::Debug for DynConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DynConnection").finish_non_exhaustive()
+    }
+}
+
+impl Read for DynConnection {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: ReadBufCursor<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *self.0).poll_read(cx, buf)
+    }
+}
+
+impl Write for DynConnection {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut *self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *self.0).poll_shutdown(cx)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.0.is_write_vectored()
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut *self.0).poll_write_vectored(cx, bufs)
+    }
+}
+
+impl Connection for DynConnection {
+    fn connected(&self) -> Connected {
+        self.0.connected()
+    }
+}
+
+/// Connector that erases the type of another connector, so that code which holds a [Client](crate::Client)
+/// doesn't depend on the connector that the client was built with.
+///
+/// Any [tower::Service] from [Uri] to a connection implementing hyper's [Read], [Write],
+/// and [Connection] traits can be wrapped, which includes the connectors of `hyper-rustls`,
+/// `hyper-tls`, and hyper's [HttpConnector].
+#[derive(Clone)]
+pub struct DynConnector(BoxCloneSyncService<Uri, DynConnection, BoxError>);
+
+impl DynConnector {
+    /// Wrap a connector.
+    pub fn new<C>(connector: C) -> Self
+    where
+        C: Service<Uri> + Clone + Send + Sync + 'static,
+        C::Response: Read + Write + Connection + Send + Unpin + 'static,
+        C::Future: Send + 'static,
+        C::Error: Into<BoxError>,
+    {
+        let connector = connector
+            .map_response(|io| DynConnection(Box::new(io)))
+            .map_err(Into::into);
+        Self(BoxCloneSyncService::new(connector))
+    }
+}
+
+impl Default for DynConnector {
+    /// Wrap hyper's [HttpConnector], which tries every address the host of the Runtime API
+    /// resolves to. When it resolves to both IPv6 and IPv4 addresses, the connector starts
+    /// connecting to the second family after [DUAL_STACK_FALLBACK] if the first hasn't connected
+    /// yet, so the client also works in IPv6-only and dual-stack networks.
+    fn default() -> Self {
+        let mut connector = HttpConnector::new();
+        connector.set_happy_eyeballs_timeout(Some(DUAL_STACK_FALLBACK));
+        Self::new(connector)
+    }
+}
+
+impl fmt::Debug for DynConnector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DynConnector").finish_non_exhaustive()
+    }
+}
+
+impl Service<Uri> for DynConnector {
+    type Response = DynConnection;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<DynConnection, BoxError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        self.0.call(uri)
+    }
+}
+
+/// Connector that opens every connection to a Unix socket, like the socket of a local emulator
+/// of the Runtime API or of a sidecar proxy, whatever the host of the request.
+#[cfg(all(unix, feature = "unix-socket"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix-socket"))))]
+#[derive(Clone, Debug)]
+pub struct UnixConnector {
+    path: Arc<Path>,
+}
+
+#[cfg(all(unix, feature = "unix-socket"))]
+impl UnixConnector {
+    /// Create a connector to the socket at `path`.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().into(),
+        }
+    }
+}
+
+#[cfg(all(unix, feature = "unix-socket"))]
+impl Service<Uri> for UnixConnector {
+    type Response = TokioIo<UnixStream>;
+    type Error = io::Error;
+    type Future = BoxFuture<'static, io::Result<TokioIo<UnixStream>>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _uri: Uri) -> Self::Future {
+        let path = self.path.clone();
+        async move { UnixStream::connect(path).await.map(TokioIo::new) }.boxed()
+    }
+}
@@ -11,8 +11,8 @@ use http::{
     Request, Response, Uri,
 };
 use hyper::body::Incoming;
-use hyper_util::client::legacy::connect::HttpConnector;
-use std::{convert::TryInto, fmt::Debug, future};
+use hyper_util::client::legacy::connect::Connect;
+use std::{fmt, future, net::Ipv6Addr};
 
 const USER_AGENT_HEADER: &str = "User-Agent";
 const DEFAULT_USER_AGENT: &str = concat!("aws-lambda-rust/", env!("CARGO_PKG_VERSION"));
@@ -21,32 +21,49 @@ const CUSTOM_USER_AGENT: Option<&str> = option_env!("LAMBDA_RUNTIME_USER_AGENT")
 mod error;
 pub use error::*;
 pub mod body;
+pub mod connect;
 
 #[cfg(feature = "tracing")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tracing")))]
 pub mod tracing;
 
 /// API client to interact with the AWS Lambda Runtime API.
-#[derive(Debug)]
-pub struct Client {
+///
+/// The client connects to the API with hyper's [connect::HttpConnector], behind a
+/// [connect::DynConnector], unless another connector is set with [ClientBuilder::with_connector].
+/// When the host of the API resolves to both IPv6 and IPv4 addresses, the default connector tries
+/// both families, see [connect::DUAL_STACK_FALLBACK].
+pub struct Client<C = connect::DynConnector> {
     /// The runtime API URI
     pub base: Uri,
     /// The client that manages the API connections
-    pub client: hyper_util::client::legacy::Client<HttpConnector, body::Body>,
+    pub client: hyper_util::client::legacy::Client<C, body::Body>,
+}
+
+impl<C> fmt::Debug for Client<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("base", &self.base)
+            .field("client", &self.client)
+            .finish()
+    }
 }
 
 impl Client {
     /// Create a builder struct to configure the client.
     pub fn builder() -> ClientBuilder {
         ClientBuilder {
-            connector: HttpConnector::new(),
+            connector: connect::DynConnector::default(),
             uri: None,
             pool_size: None,
         }
     }
 }
 
-impl Client {
+impl<C> Client<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
     /// Send a given request to the Runtime API.
     /// Use the client's base URI to ensure the API endpoint is correct.
     pub fn call(&self, req: Request<body::Body>) -> BoxFuture<'static, Result<Response<Incoming>, BoxError>> {
@@ -60,8 +77,8 @@ impl Client {
         self.client.request(req).map_err(Into::into).boxed()
     }
 
-    /// Create a new client with a given base URI, HTTP connector, and optional pool size hint.
-    fn with(base: Uri, connector: HttpConnector, pool_size: Option<usize>) -> Self {
+    /// Create a new client with a given base URI, connector, and optional pool size hint.
+    fn with(base: Uri, connector: C, pool_size: Option<usize>) -> Self {
         let mut builder = hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new());
         builder.http1_max_buf_size(1024 * 1024);
 
@@ -97,15 +114,19 @@ impl Client {
 }
 
 /// Builder implementation to construct any Runtime API clients.
-pub struct ClientBuilder {
-    connector: HttpConnector,
+pub struct ClientBuilder<C = connect::DynConnector> {
+    connector: C,
     uri: Option<http::Uri>,
     pool_size: Option<usize>,
 }
 
-impl ClientBuilder {
-    /// Create a new builder with a given HTTP connector.
-    pub fn with_connector(self, connector: HttpConnector) -> ClientBuilder {
+impl<C> ClientBuilder<C> {
+    /// Create a new builder with a given connector.
+    ///
+    /// The connector can be any [tower::Service] that hyper accepts as a connector, like the
+    /// connectors of `hyper-rustls` or `hyper-tls`, or a connector to the Unix socket of a
+    /// local emulator. See [connect::DynConnector] to erase its type.
+    pub fn with_connector<T>(self, connector: T) -> ClientBuilder<T> {
         ClientBuilder {
             connector,
             uri: self.uri,
@@ -132,18 +153,67 @@ impl ClientBuilder {
     }
 
     /// Create the new client to interact with the Runtime API.
-    pub fn build(self) -> Result<Client, Error> {
+    pub fn build(self) -> Result<Client<C>, Error>
+    where
+        C: Connect + Clone + Send + Sync + 'static,
+    {
         let uri = match self.uri {
             Some(uri) => uri,
             None => {
                 let uri = std::env::var("AWS_LAMBDA_RUNTIME_API").expect("Missing AWS_LAMBDA_RUNTIME_API env var");
-                uri.try_into().expect("Unable to convert to URL")
+                parse_endpoint(&uri).expect("Unable to convert to URL")
             }
         };
         Ok(Client::with(uri, self.connector, self.pool_size))
     }
 }
 
+/// Parse the endpoint of the Runtime API, like the value of `AWS_LAMBDA_RUNTIME_API`.
+///
+/// The endpoint is usually a `host:port` pair, like `127.0.0.1:9001`. It can also have an
+/// `http://` scheme and a base path, and its host can be an IPv6 literal, with or without
+/// brackets, like `[::1]:9001` or `::1:9001`. The last segment of an IPv6 literal without
+/// brackets is read as the port when it can be one.
+///
+/// Endpoints with an `https://` scheme are rejected, since the default connector only opens
+/// plain TCP connections. To reach the API over TLS, set a TLS connector with
+/// [ClientBuilder::with_connector], and the endpoint with [ClientBuilder::with_endpoint].
+pub fn parse_endpoint(endpoint: &str) -> Result<Uri, Error> {
+    let invalid = || Error::new(format!("invalid Runtime API endpoint: {endpoint:?}"));
+    let endpoint = endpoint.trim();
+    let (scheme, rest) = endpoint.split_once("://").unwrap_or(("http", endpoint));
+    match scheme {
+        "http" => {}
+        "https" => {
+            return Err(Error::new(format!(
+                "Runtime API endpoint {endpoint:?} uses https, which needs a TLS connector: set it with \
+                 ClientBuilder::with_connector, and the endpoint with ClientBuilder::with_endpoint"
+            )))
+        }
+        _ => return Err(invalid()),
+    }
+    let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+
+    let authority = if !authority.starts_with('[') && authority.matches(':').count() > 1 {
+        match authority.rsplit_once(':') {
+            Some((host, port)) if host.parse::<Ipv6Addr>().is_ok() && port.parse::<u16>().is_ok() => {
+                format!("[{host}]:{port}")
+            }
+            _ if authority.parse::<Ipv6Addr>().is_ok() => format!("[{authority}]"),
+            _ => return Err(invalid()),
+        }
+    } else {
+        authority.to_owned()
+    };
+
+    Uri::builder()
+        .scheme(scheme)
+        .authority(authority)
+        .path_and_query(if path.is_empty() { "/" } else { path })
+        .build()
+        .map_err(|_| invalid())
+}
+
 /// Create a request builder.
 /// This builder uses `aws-lambda-rust/CRATE_VERSION` as
 /// the default User-Agent.
@@ -160,6 +230,81 @@ pub fn build_request() -> http::request::Builder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn parse_runtime_api_endpoints() {
+        let endpoints = [
+            ("127.0.0.1:9001", "http://127.0.0.1:9001/"),
+            ("localhost:9001/foo", "http://localhost:9001/foo"),
+            ("http://runtime.local:9001", "http://runtime.local:9001/"),
+            ("[::1]:9001", "http://[::1]:9001/"),
+            ("::1:9001", "http://[::1]:9001/"),
+            ("fd00:ec2::23:9001", "http://[fd00:ec2::23]:9001/"),
+            ("http://[fd00:ec2::23]:9001/", "http://[fd00:ec2::23]:9001/"),
+            ("fd00::1", "http://[fd00::1]/"),
+        ];
+        for (endpoint, expected) in endpoints {
+            assert_eq!(expected, parse_endpoint(endpoint).unwrap().to_string(), "{endpoint}");
+        }
+        assert!(parse_endpoint("fd00:zz::1:9001").is_err());
+        assert!(parse_endpoint("").is_err());
+        assert!(parse_endpoint("ftp://127.0.0.1:9001").is_err());
+
+        let err = parse_endpoint("https://runtime.local:9001").unwrap_err();
+        assert!(err.to_string().contains("needs a TLS connector"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn connect_to_ipv6_endpoints() {
+        let Ok(listener) = tokio::net::TcpListener::bind("[::1]:0").await else {
+            // The loopback interface has no IPv6 address.
+            return;
+        };
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 1024];
+            let read = stream.read(&mut request).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&request[..read]).into_owned()
+        });
+
+        let client = Client::builder()
+            .with_endpoint(parse_endpoint(&format!("::1:{port}")).unwrap())
+            .build()
+            .unwrap();
+        let req = build_request()
+            .uri("/2018-06-01/runtime/invocation/next")
+            .body(body::Body::empty())
+            .unwrap();
+        let response = client.call(req).await.unwrap();
+        assert_eq!(http::StatusCode::OK, response.status());
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("GET /2018-06-01/runtime/invocation/next "));
+        assert!(request.contains(&format!("host: [::1]:{port}")), "{request}");
+    }
+
+    #[test]
+    fn test_set_origin_with_ipv6_host() {
+        let client = Client::builder()
+            .with_endpoint(parse_endpoint("::1:9001").unwrap())
+            .build()
+            .unwrap();
+        let req = build_request()
+            .uri("/2018-06-01/runtime/invocation/next")
+            .body(())
+            .unwrap();
+        let req = client.set_origin(req).unwrap();
+        assert_eq!(
+            "http://[::1]:9001/2018-06-01/runtime/invocation/next",
+            &req.uri().to_string()
+        );
+    }
 
     #[test]
     fn test_set_origin() {
@@ -215,4 +360,17 @@ mod tests {
 
         assert_eq!(client.base, expected);
     }
+
+    #[test]
+    fn builder_accepts_custom_connector() {
+        let base = "http://localhost:9001";
+        let expected: Uri = base.parse().unwrap();
+        let client: Client<connect::HttpConnector> = Client::builder()
+            .with_connector(connect::HttpConnector::new())
+            .with_endpoint(base.parse().unwrap())
+            .build()
+            .unwrap();
+
+        assert_eq!(client.base, expected);
+    }
 }
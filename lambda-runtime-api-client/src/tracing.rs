@@ -5,9 +5,18 @@
 //! so you don't have to include them as direct dependencies in
 //! your projects.
 
-use std::{env, str::FromStr};
+use std::{env, fmt, str::FromStr};
 
-use subscriber::filter::{EnvFilter, LevelFilter};
+use serde_json::{Map, Value as JsonValue};
+use subscriber::{
+    filter::{EnvFilter, LevelFilter},
+    fmt::{
+        format::{JsonFields, Writer},
+        time::{FormatTime, SystemTime},
+        FmtContext, FormatEvent, FormatFields, FormattedFields,
+    },
+    registry::LookupSpan,
+};
 /// Re-export the `tracing` crate to have access to tracing macros
 /// like `info!`, `debug!`, `trace!` and so on.
 pub use tracing::*;
@@ -32,8 +41,8 @@ const DEFAULT_LOG_LEVEL: &str = "INFO";
 ///     - if none of those two variables are set, use `INFO` as the logging level.
 ///
 /// The logging format can also be changed based on Lambda's advanced logging controls.
-/// If the `AWS_LAMBDA_LOG_FORMAT` environment variable is set to `JSON`, the log lines will be formatted as json objects,
-/// otherwise they will be formatted with the default tracing format.
+/// If the `AWS_LAMBDA_LOG_FORMAT` environment variable is set to `JSON`, the log lines will be formatted as json objects
+/// with the [LambdaJsonFormat], otherwise they will be formatted with the default tracing format.
 pub fn init_default_subscriber() {
     init_default_subscriber_with_writer(std::io::stdout);
 }
@@ -55,16 +64,15 @@ pub fn init_default_subscriber() {
 ///     - if none of those two variables are set, use `INFO` as the logging level.
 ///
 /// The logging format can also be changed based on Lambda's advanced logging controls.
-/// If the `AWS_LAMBDA_LOG_FORMAT` environment variable is set to `JSON`, the log lines will be formatted as json objects,
-/// otherwise they will be formatted with the default tracing format.
+/// If the `AWS_LAMBDA_LOG_FORMAT` environment variable is set to `JSON`, the log lines will be formatted as json objects
+/// with the [LambdaJsonFormat], otherwise they will be formatted with the default tracing format.
 pub fn init_default_subscriber_with_writer<Writer>(writer: Writer)
 where
     Writer: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
 {
     let log_format = env::var("AWS_LAMBDA_LOG_FORMAT").unwrap_or_default();
     let log_level_str = env::var("AWS_LAMBDA_LOG_LEVEL").or_else(|_| env::var("RUST_LOG"));
-    let log_level =
-        LevelFilter::from_str(log_level_str.as_deref().unwrap_or(DEFAULT_LOG_LEVEL)).unwrap_or(LevelFilter::INFO);
+    let log_level = log_level_filter(log_level_str.as_deref().unwrap_or(DEFAULT_LOG_LEVEL));
 
     let collector = tracing_subscriber::fmt()
         .with_target(false)
@@ -77,8 +85,184 @@ where
         .with_writer(writer);
 
     if log_format.eq_ignore_ascii_case("json") {
-        collector.json().init()
+        collector
+            .event_format(LambdaJsonFormat)
+            .fmt_fields(JsonFields::new())
+            .init()
     } else {
         collector.init()
     }
 }
+
+/// Level filter of an `AWS_LAMBDA_LOG_LEVEL` or `RUST_LOG` value. Lambda's `FATAL` level is
+/// mapped to `ERROR`, the most severe level of `tracing`.
+fn log_level_filter(level: &str) -> LevelFilter {
+    if level.eq_ignore_ascii_case("fatal") {
+        return LevelFilter::ERROR;
+    }
+    LevelFilter::from_str(level).unwrap_or(LevelFilter::INFO)
+}
+
+/// Event formatter that writes JSON log lines in the schema of [Lambda's advanced logging controls](https://docs.aws.amazon.com/lambda/latest/dg/monitoring-cloudwatchlogs-advanced.html),
+/// so that CloudWatch can filter them by level and request.
+///
+/// Every line has the reserved `timestamp`, `level`, and `message` fields, the `requestId` of
+/// the invocation when the event is recorded in the span of the runtime, and the other fields
+/// of the event:
+///
+/// ```json
+/// {"timestamp":"2024-11-05T10:15:30.123456Z","level":"INFO","message":"order placed","requestId":"8f5ab2c5-...","orderId":"1234"}
+/// ```
+///
+/// The fields of the spans must be formatted as JSON, with [JsonFields], for the `requestId`
+/// to be found:
+///
+/// ```no_run
+/// use lambda_runtime_api_client::tracing::{
+///     subscriber::fmt::format::JsonFields, LambdaJsonFormat,
+/// };
+///
+/// lambda_runtime_api_client::tracing::subscriber::fmt()
+///     .event_format(LambdaJsonFormat)
+///     .fmt_fields(JsonFields::new())
+///     .init();
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LambdaJsonFormat;
+
+impl<S, N> FormatEvent<S, N> for LambdaJsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let mut timestamp = String::new();
+        SystemTime.format_time(&mut Writer::new(&mut timestamp))?;
+
+        let mut line = Map::new();
+        line.insert("timestamp".into(), timestamp.into());
+        line.insert("level".into(), event.metadata().level().as_str().into());
+        line.insert("message".into(), JsonValue::Null);
+
+        // The closest span that has a request id, usually the span of the `TracingLayer`.
+        let request_id = ctx.event_scope().into_iter().flatten().find_map(|span| {
+            let extensions = span.extensions();
+            let fields = extensions.get::<FormattedFields<N>>()?;
+            let mut fields: Map<String, JsonValue> = serde_json::from_str(fields).ok()?;
+            fields.remove("requestId")
+        });
+        if let Some(request_id) = request_id {
+            line.insert("requestId".into(), request_id);
+        }
+
+        event.record(&mut JsonVisitor(&mut line));
+        if line["message"].is_null() {
+            line.remove("message");
+        }
+
+        let line = serde_json::to_string(&line).map_err(|_| fmt::Error)?;
+        writeln!(writer, "{line}")
+    }
+}
+
+struct JsonVisitor<'a>(&'a mut Map<String, JsonValue>);
+
+impl JsonVisitor<'_> {
+    fn insert(&mut self, field: &field::Field, value: JsonValue) {
+        self.0.insert(field.name().to_string(), value);
+    }
+}
+
+impl field::Visit for JsonVisitor<'_> {
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &field::Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_error(&mut self, field: &field::Field, value: &(dyn std::error::Error + 'static)) {
+        self.insert(field, value.to_string().into());
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn fmt::Debug) {
+        self.insert(field, format!("{value:?}").into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_lines_have_the_reserved_fields() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = subscriber::fmt()
+            .event_format(LambdaJsonFormat)
+            .fmt_fields(JsonFields::new())
+            .with_writer(move || writer.clone())
+            .finish();
+
+        let _guard = dispatcher::set_default(&subscriber.into());
+        warn!("before the invocation");
+        let span = info_span!(
+            "Lambda runtime invoke",
+            requestId = "8f5ab2c5",
+            xrayTraceId = "Root=1-5759e988"
+        );
+        span.in_scope(|| error!(order_id = 1234, retry = true, "order failed"));
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<JsonValue> = output.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(2, lines.len());
+
+        assert_eq!("WARN", lines[0]["level"]);
+        assert_eq!("before the invocation", lines[0]["message"]);
+        assert!(lines[0].get("requestId").is_none());
+
+        assert!(lines[1]["timestamp"].as_str().unwrap().ends_with('Z'));
+        assert_eq!("ERROR", lines[1]["level"]);
+        assert_eq!("order failed", lines[1]["message"]);
+        assert_eq!("8f5ab2c5", lines[1]["requestId"]);
+        assert_eq!(1234, lines[1]["order_id"]);
+        assert_eq!(true, lines[1]["retry"]);
+    }
+
+    #[test]
+    fn fatal_is_the_error_level() {
+        assert_eq!(LevelFilter::ERROR, log_level_filter("FATAL"));
+        assert_eq!(LevelFilter::DEBUG, log_level_filter("DEBUG"));
+        assert_eq!(LevelFilter::INFO, log_level_filter("verbose"));
+    }
+}
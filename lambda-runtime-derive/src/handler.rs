@@ -0,0 +1,95 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+use syn::{meta::ParseNestedMeta, FnArg, Ident, ItemFn, LitStr, Path};
+
+/// Arguments of the `main` attribute.
+#[derive(Default)]
+pub(crate) struct Arguments {
+    catch_panic: bool,
+    streaming: bool,
+    krate: Option<Path>,
+}
+
+impl Arguments {
+    pub(crate) fn parse(&mut self, meta: ParseNestedMeta<'_>) -> syn::Result<()> {
+        if meta.path.is_ident("catch_panic") {
+            self.catch_panic = true;
+        } else if meta.path.is_ident("streaming") {
+            self.streaming = true;
+        } else if meta.path.is_ident("crate") {
+            self.krate = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+        } else {
+            return Err(meta.error("unsupported main attribute, expected `catch_panic`, `streaming`, or `crate`"));
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn expand(arguments: Arguments, mut handler: ItemFn) -> syn::Result<TokenStream2> {
+    let signature = &handler.sig;
+    if signature.asyncness.is_none() {
+        return Err(syn::Error::new(
+            signature.fn_token.span,
+            "the handler must be an async fn",
+        ));
+    }
+    if !signature.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &signature.generics,
+            "the handler can't have generic parameters",
+        ));
+    }
+    let event = match signature.inputs.first() {
+        Some(FnArg::Typed(event)) if signature.inputs.len() <= 2 => event.ty.to_token_stream(),
+        Some(FnArg::Receiver(receiver)) => {
+            return Err(syn::Error::new_spanned(receiver, "the handler can't be a method"));
+        }
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &signature.inputs,
+                "the handler takes the event, and optionally the context of the invocation",
+            ));
+        }
+    };
+
+    // The generated `main` function replaces a handler named `main`.
+    if handler.sig.ident == "main" {
+        handler.sig.ident = Ident::new("__lambda_handler", Span::call_site());
+    }
+    let ident = &handler.sig.ident;
+    let call = match handler.sig.inputs.len() {
+        1 => quote! { #ident(event.payload) },
+        _ => quote! {
+            let (payload, context) = event.into_parts();
+            #ident(payload, context)
+        },
+    };
+
+    let krate = arguments.krate.unwrap_or_else(|| syn::parse_quote!(::lambda_runtime));
+    let catch_panic = arguments
+        .catch_panic
+        .then(|| quote! { .layer(#krate::layers::CatchPanicLayer::new()) });
+    let codec = arguments.streaming.then(|| quote! { .codec(#krate::codec::Streamed) });
+
+    Ok(quote! {
+        #handler
+
+        fn main() -> ::core::result::Result<(), #krate::Error> {
+            #krate::__private::tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()?
+                .block_on(async {
+                    #krate::__private::init_tracing();
+                    #krate::Runtime::builder()
+                        #catch_panic
+                        #codec
+                        .build(#krate::service_fn(|event: #krate::LambdaEvent<#event>| {
+                            #call
+                        }))
+                        .layer(#krate::layers::TracingLayer::new())
+                        .run()
+                        .await
+                })
+        }
+    })
+}
@@ -0,0 +1,118 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitStr, Path};
+
+#[derive(Default)]
+struct Attributes {
+    error_type: Option<LitStr>,
+    retryable: bool,
+    krate: Option<Path>,
+}
+
+impl Attributes {
+    fn parse(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut parsed = Attributes::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("lambda_error")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("error_type") {
+                    parsed.error_type = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("retryable") {
+                    parsed.retryable = true;
+                } else if meta.path.is_ident("crate") {
+                    parsed.krate = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                } else {
+                    return Err(meta.error("unsupported lambda_error attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(parsed)
+    }
+}
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "LambdaError can only be derived for enums",
+        ));
+    };
+
+    let attributes = Attributes::parse(&input.attrs)?;
+    if attributes.error_type.is_some() || attributes.retryable {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`error_type` and `retryable` only apply to variants",
+        ));
+    }
+    let krate = attributes.krate.unwrap_or_else(|| syn::parse_quote!(::lambda_runtime));
+
+    let ident = &input.ident;
+    let mut arms = Vec::new();
+    let mut retryable_patterns = Vec::new();
+    let mut retryable_types = Vec::new();
+    for variant in &data.variants {
+        let attributes = Attributes::parse(&variant.attrs)?;
+        if attributes.krate.is_some() {
+            return Err(syn::Error::new_spanned(&variant.ident, "`crate` only applies to enums"));
+        }
+        let name = &variant.ident;
+        let pattern = match variant.fields {
+            Fields::Named(_) => quote! { Self::#name { .. } },
+            Fields::Unnamed(_) => quote! { Self::#name(..) },
+            Fields::Unit => quote! { Self::#name },
+        };
+        let error_type = attributes
+            .error_type
+            .map(|error_type| error_type.value())
+            .unwrap_or_else(|| format!("{ident}.{name}"));
+        arms.push(quote! { #pattern => #error_type });
+        if attributes.retryable {
+            retryable_patterns.push(pattern);
+            retryable_types.push(error_type);
+        }
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let is_retryable = if retryable_patterns.is_empty() {
+        quote! { false }
+    } else {
+        quote! { ::core::matches!(self, #(#retryable_patterns)|*) }
+    };
+    // Enums without variants have no value to match on.
+    let error_type = if arms.is_empty() {
+        quote! { match *self {} }
+    } else {
+        quote! { match self { #(#arms),* } }
+    };
+    let predicates = where_clause
+        .into_iter()
+        .flat_map(|where_clause| where_clause.predicates.iter());
+
+    Ok(quote! {
+        impl #impl_generics #krate::LambdaError for #ident #ty_generics #where_clause {
+            const RETRYABLE_ERROR_TYPES: &'static [&'static str] = &[#(#retryable_types),*];
+
+            fn error_type(&self) -> &'static str {
+                #error_type
+            }
+
+            fn is_retryable(&self) -> bool {
+                #is_retryable
+            }
+        }
+
+        impl #impl_generics ::core::convert::From<#ident #ty_generics> for #krate::Diagnostic
+        where
+            #(#predicates,)*
+            #ident #ty_generics: ::core::fmt::Display,
+        {
+            fn from(value: #ident #ty_generics) -> Self {
+                #krate::Diagnostic {
+                    error_type: ::std::string::ToString::to_string(#krate::LambdaError::error_type(&value)),
+                    error_message: ::std::string::ToString::to_string(&value),
+                }
+            }
+        }
+    })
+}
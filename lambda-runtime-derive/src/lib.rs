@@ -0,0 +1,207 @@
+#![deny(clippy::all, clippy::cargo)]
+#![warn(missing_docs, nonstandard_style, rust_2018_idioms)]
+
+//! Derive and attribute macros for the `lambda_runtime` crate.
+//!
+//! Use the macros through the `derive` feature of `lambda_runtime`, which re-exports them.
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    ext::IdentExt, parse_macro_input, Data, DeriveInput, Fields, GenericArgument, ItemFn, LitStr, Path, PathArguments,
+    Type,
+};
+
+mod handler;
+mod lambda_error;
+
+/// Derive `lambda_runtime::FromLambdaEnv` for a struct with named fields, reading every field
+/// from an environment variable.
+///
+/// The variable of a field is named after the field in upper case, unless it's set with
+/// `#[lambda_env(name = "...")]`. Missing variables are errors, unless the field has a
+/// `#[lambda_env(default = "...")]` value, or its type is an `Option`. Values are parsed with
+/// [FromStr](std::str::FromStr).
+///
+/// The struct accepts `#[lambda_env(prefix = "...")]` to prefix the names of all its variables,
+/// and `#[lambda_env(crate = "...")]` to set the path of the `lambda_runtime` crate, like
+/// `lambda_http::lambda_runtime`.
+#[proc_macro_derive(FromLambdaEnv, attributes(lambda_env))]
+pub fn derive_from_lambda_env(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Generate the `main` function of a Lambda function that runs the annotated async handler.
+///
+/// The handler takes the payload of the event, and optionally the `Context` of the invocation.
+/// `main` starts a multi-threaded tokio runtime, initializes the default tracing subscriber
+/// when the `tracing` feature is enabled, and runs the handler with the `TracingLayer`:
+///
+/// ```ignore
+/// use lambda_runtime::{Context, Error};
+/// use serde_json::Value;
+///
+/// #[lambda_runtime::main]
+/// async fn handler(event: Value, context: Context) -> Result<Value, Error> {
+///     Ok(event)
+/// }
+/// ```
+///
+/// The attribute accepts `catch_panic` to report the backtrace of panics with the
+/// `CatchPanicLayer`, `streaming` to receive the event as an `EventBody` with the `Streamed`
+/// codec, and `crate = "..."` to set the path of the `lambda_runtime` crate.
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut arguments = handler::Arguments::default();
+    let parser = syn::meta::parser(|meta| arguments.parse(meta));
+    parse_macro_input!(args with parser);
+    let input = parse_macro_input!(input as ItemFn);
+    handler::expand(arguments, input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `lambda_runtime::LambdaError` for an error enum, and convert it into a
+/// `lambda_runtime::Diagnostic` with the `errorType` of the variant and the `Display` message
+/// of the error.
+///
+/// The `errorType` of a variant is the name of the enum and the variant separated by a dot,
+/// like `OrderError.OutOfStock`, unless it's set with `#[lambda_error(error_type = "...")]`.
+/// Variants marked with `#[lambda_error(retryable)]` are retryable.
+///
+/// The enum accepts `#[lambda_error(crate = "...")]` to set the path of the `lambda_runtime`
+/// crate.
+#[proc_macro_derive(LambdaError, attributes(lambda_error))]
+pub fn derive_lambda_error(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    lambda_error::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct Attributes {
+    name: Option<LitStr>,
+    default: Option<LitStr>,
+    prefix: Option<LitStr>,
+    krate: Option<Path>,
+}
+
+impl Attributes {
+    fn parse(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut parsed = Attributes::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("lambda_env")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    parsed.name = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("default") {
+                    parsed.default = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("prefix") {
+                    parsed.prefix = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("crate") {
+                    parsed.krate = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                } else {
+                    return Err(meta.error("unsupported lambda_env attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(parsed)
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "FromLambdaEnv can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "FromLambdaEnv can only be derived for structs with named fields",
+        ));
+    };
+
+    let attributes = Attributes::parse(&input.attrs)?;
+    if attributes.name.is_some() || attributes.default.is_some() {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`name` and `default` only apply to fields",
+        ));
+    }
+    let krate = attributes.krate.unwrap_or_else(|| syn::parse_quote!(::lambda_runtime));
+    let prefix = attributes.prefix.map(|prefix| prefix.value()).unwrap_or_default();
+
+    let mut reads = Vec::new();
+    let mut inits = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.as_ref().expect("named fields have an identifier");
+        let attributes = Attributes::parse(&field.attrs)?;
+        if attributes.prefix.is_some() || attributes.krate.is_some() {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "`prefix` and `crate` only apply to structs",
+            ));
+        }
+        let name = attributes
+            .name
+            .map(|name| name.value())
+            .unwrap_or_else(|| format!("{prefix}{}", ident.unraw().to_string().to_uppercase()));
+
+        let value = format_ident!("__{}", ident.unraw());
+        match (option_type(&field.ty), attributes.default) {
+            (Some(_), Some(default)) => {
+                return Err(syn::Error::new_spanned(default, "optional fields can't have a default"))
+            }
+            (Some(inner), None) => {
+                reads.push(quote! { let #value = reader.optional::<#inner>(#name); });
+                inits.push(quote! { #ident: #value });
+            }
+            (None, default) => {
+                let ty = &field.ty;
+                let default = match default {
+                    Some(default) => quote! { ::core::option::Option::Some(#default) },
+                    None => quote! { ::core::option::Option::None },
+                };
+                reads.push(quote! { let #value = reader.required::<#ty>(#name, #default); });
+                inits.push(quote! { #ident: #value.expect("variables are checked by EnvReader::finish") });
+            }
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #krate::FromLambdaEnv for #ident #ty_generics #where_clause {
+            fn from_lookup(
+                lookup: &dyn ::core::ops::Fn(&str) -> ::core::option::Option<::std::string::String>,
+            ) -> ::core::result::Result<Self, #krate::EnvConfigError> {
+                let mut reader = #krate::__private::EnvReader::new(lookup);
+                #(#reads)*
+                reader.finish()?;
+                ::core::result::Result::Ok(Self { #(#inits),* })
+            }
+        }
+    })
+}
+
+/// The type wrapped by an `Option` type, like `u32` for `Option<u32>`.
+fn option_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(inner) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
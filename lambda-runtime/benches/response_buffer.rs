@@ -0,0 +1,95 @@
+//! Allocations and time per invocation to serialize buffered responses, with and without the
+//! buffer that the runtime reuses across invocations.
+//!
+//! Run it with `cargo bench -p lambda_runtime --bench response_buffer`.
+
+use bytes::BytesMut;
+use lambda_runtime::codec::{Json, Serializer};
+use serde::Serialize;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[derive(Serialize)]
+struct Record {
+    id: u64,
+    name: String,
+    tags: Vec<String>,
+}
+
+const INVOCATIONS: usize = 1_000;
+
+fn measure(name: &str, size: usize, mut invoke: impl FnMut() -> usize) {
+    // Warm up, so that the reused buffer reaches the size of the response.
+    let len = invoke();
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..INVOCATIONS {
+        black_box(invoke());
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes;
+
+    println!(
+        "{name:<14} {size:>6} records {len:>9} B  {:>6.1} allocs/invoke  {:>10} B/invoke  {:>10.2?}/invoke",
+        allocations as f64 / INVOCATIONS as f64,
+        bytes / INVOCATIONS,
+        elapsed / INVOCATIONS as u32,
+    );
+}
+
+fn main() {
+    for size in [10, 1_000, 10_000] {
+        let records: Vec<Record> = (0..size)
+            .map(|id| Record {
+                id,
+                name: format!("record-{id}"),
+                tags: vec!["lambda".into(), "runtime".into()],
+            })
+            .collect();
+
+        measure("serialize", size as usize, || {
+            let response = Json.serialize(&records).unwrap();
+            response.len()
+        });
+
+        let mut buffer = BytesMut::new();
+        measure("serialize_into", size as usize, || {
+            // The response is dropped once it's sent, before the next invocation.
+            let response = Json.serialize_into(&records, &mut buffer).unwrap();
+            response.len()
+        });
+    }
+}
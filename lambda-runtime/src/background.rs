@@ -0,0 +1,179 @@
+use std::{
+    fmt,
+    future::Future,
+    mem,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+use tokio::task::JoinSet;
+use tracing::{error, warn};
+
+/// Work that a handler spawns in the background of an invocation, like flushing metrics or
+/// writing an audit record, without delaying the computation of its response.
+///
+/// Lambda freezes the execution environment as soon as the response of an invocation is posted,
+/// so a task started with `tokio::spawn` may not run until the next invocation, or never. The
+/// runtime waits for the tasks spawned here before posting the response of the invocation, up to
+/// a margin before the deadline of the invocation, and aborts the tasks still running then. The
+/// margin is [BackgroundTasks::DEFAULT_MARGIN] unless it is set with
+/// [RuntimeBuilder::background_tasks_margin](crate::RuntimeBuilder::background_tasks_margin).
+///
+/// ```no_run
+/// use lambda_runtime::{service_fn, Error, LambdaEvent};
+/// use serde_json::Value;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     lambda_runtime::run(service_fn(func)).await
+/// }
+///
+/// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+///     let request_id = event.context.request_id.clone();
+///     event.context.background_tasks().spawn(async move {
+///         tracing::info!(%request_id, "recording the invocation");
+///     });
+///     Ok(event.payload)
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct BackgroundTasks(Arc<Mutex<JoinSet<()>>>);
+
+impl BackgroundTasks {
+    /// Time left before the deadline of an invocation when the runtime stops waiting for its
+    /// background tasks, to leave time to post the response.
+    pub const DEFAULT_MARGIN: Duration = Duration::from_millis(100);
+
+    /// Spawn a task that the runtime awaits before posting the response of the invocation.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Tokio runtime.
+    pub fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.0.lock().expect("background tasks lock poisoned").spawn(task);
+    }
+
+    /// Number of tasks spawned for the invocation that haven't been awaited yet.
+    pub fn len(&self) -> usize {
+        self.0.lock().expect("background tasks lock poisoned").len()
+    }
+
+    /// Whether no task is waiting to be awaited.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn take(&self) -> JoinSet<()> {
+        mem::take(&mut *self.0.lock().expect("background tasks lock poisoned"))
+    }
+
+    /// Await the tasks, including the ones they spawn, until `until` and abort the tasks still
+    /// running then.
+    pub(crate) async fn join(self, until: Option<SystemTime>) {
+        let join_all = async {
+            loop {
+                let mut tasks = self.take();
+                if tasks.is_empty() {
+                    break;
+                }
+                while let Some(result) = tasks.join_next().await {
+                    if let Err(err) = result {
+                        error!(error = %err, "background task failed");
+                    }
+                }
+            }
+        };
+
+        let Some(until) = until else {
+            return join_all.await;
+        };
+        let remaining = until.duration_since(SystemTime::now()).unwrap_or_default();
+        if tokio::time::timeout(remaining, join_all).await.is_err() {
+            // Tasks dropped with the `JoinSet` of the timed out future were aborted too.
+            self.take().abort_all();
+            warn!("background tasks did not complete before the invocation deadline and were aborted");
+        }
+    }
+}
+
+impl fmt::Debug for BackgroundTasks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BackgroundTasks").field(&self.len()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{codec::Json, layers::RuntimeApiResponseService, Context, LambdaEvent, LambdaInvocation};
+    use bytes::Bytes;
+    use serde_json::Value;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tower::Service;
+
+    fn invocation(deadline: SystemTime) -> LambdaInvocation {
+        let (parts, _) = http::Response::new(()).into_parts();
+        LambdaInvocation {
+            parts,
+            body: Bytes::from_static(b"{}"),
+            context: Context {
+                deadline: deadline.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis() as u64,
+                ..Context::default()
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn response_waits_for_background_tasks() {
+        let done = Arc::new(AtomicBool::new(false));
+        let handler = tower::service_fn({
+            let done = done.clone();
+            move |event: LambdaEvent<Value>| {
+                let done = done.clone();
+                async move {
+                    let tasks = event.context.background_tasks().clone();
+                    event.context.background_tasks().spawn(async move {
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        // Tasks can spawn more tasks, which are awaited too.
+                        tasks.spawn(async move { done.store(true, Ordering::SeqCst) });
+                    });
+                    Ok::<_, crate::ErrorReport>(event.payload)
+                }
+            }
+        });
+        let mut service = RuntimeApiResponseService::new(
+            handler,
+            Json,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            None,
+        );
+
+        let deadline = SystemTime::now() + Duration::from_secs(30);
+        service.call(invocation(deadline)).await.unwrap();
+        assert!(done.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn background_tasks_are_aborted_at_the_margin() {
+        let done = Arc::new(AtomicBool::new(false));
+        let tasks = BackgroundTasks::default();
+        tasks.spawn({
+            let done = done.clone();
+            async move {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                done.store(true, Ordering::SeqCst);
+            }
+        });
+
+        let until = SystemTime::now() + Duration::from_millis(100);
+        tokio::time::timeout(Duration::from_secs(1), tasks.clone().join(Some(until)))
+            .await
+            .expect("background tasks weren't aborted at the margin");
+        assert!(!done.load(Ordering::SeqCst));
+        assert!(tasks.is_empty());
+    }
+}
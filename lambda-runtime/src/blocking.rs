@@ -0,0 +1,166 @@
+use crate::{Error, LambdaEvent};
+use futures::{future::BoxFuture, FutureExt};
+use std::{fmt, sync::Arc, task};
+use tokio::sync::Semaphore;
+use tower::Service;
+
+/// Return a new [BlockingServiceFn] with a synchronous closure that takes an event and returns
+/// a result, to run CPU-bound or blocking handlers without stalling the event loop.
+///
+/// Every call of the closure runs on the blocking thread pool of Tokio, with
+/// [spawn_blocking](tokio::task::spawn_blocking). The size of that pool is set when the Tokio
+/// runtime is built, with
+/// [max_blocking_threads](tokio::runtime::Builder::max_blocking_threads), and the number of
+/// events processed at the same time can be limited further with
+/// [BlockingServiceFn::with_max_threads]. Panics of the closure are propagated to the runtime,
+/// which reports them like the panics of asynchronous handlers, and the invocations whose task is
+/// cancelled, when the Tokio runtime shuts down, fail with an [Error].
+///
+/// ```no_run
+/// use lambda_runtime::{service_fn_blocking, Error, LambdaEvent};
+/// use serde_json::Value;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     lambda_runtime::run(service_fn_blocking(func).with_max_threads(4)).await
+/// }
+///
+/// fn func(event: LambdaEvent<Value>) -> Result<usize, Error> {
+///     let document = std::fs::read_to_string(event.payload["path"].as_str().unwrap_or_default())?;
+///     Ok(document.lines().count())
+/// }
+/// ```
+pub fn service_fn_blocking<T>(f: T) -> BlockingServiceFn<T> {
+    BlockingServiceFn {
+        f: Arc::new(f),
+        threads: None,
+    }
+}
+
+/// Service returned by [service_fn_blocking].
+pub struct BlockingServiceFn<T> {
+    f: Arc<T>,
+    threads: Option<Arc<Semaphore>>,
+}
+
+impl<T> BlockingServiceFn<T> {
+    /// Limit the number of events processed at the same time to `threads`, the others wait for a
+    /// thread to be available.
+    pub fn with_max_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(Arc::new(Semaphore::new(threads)));
+        self
+    }
+}
+
+impl<T> Clone for BlockingServiceFn<T> {
+    fn clone(&self) -> Self {
+        Self {
+            f: self.f.clone(),
+            threads: self.threads.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for BlockingServiceFn<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockingServiceFn")
+            .field("f", &std::any::type_name::<T>())
+            .field(
+                "available_threads",
+                &self.threads.as_ref().map(|threads| threads.available_permits()),
+            )
+            .finish()
+    }
+}
+
+impl<T, A, R, E> Service<LambdaEvent<A>> for BlockingServiceFn<T>
+where
+    T: Fn(LambdaEvent<A>) -> Result<R, E> + Send + Sync + 'static,
+    A: Send + 'static,
+    R: Send + 'static,
+    E: Into<Error> + Send + 'static,
+{
+    type Response = R;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<R, Error>>;
+
+    fn poll_ready(&mut self, _: &mut task::Context<'_>) -> task::Poll<Result<(), Error>> {
+        task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, event: LambdaEvent<A>) -> Self::Future {
+        let f = self.f.clone();
+        let threads = self.threads.clone();
+        async move {
+            let permit = match threads {
+                Some(threads) => Some(threads.acquire_owned().await.expect("semaphore is never closed")),
+                None => None,
+            };
+            let result = tokio::task::spawn_blocking(move || {
+                let _permit = permit;
+                f(event)
+            })
+            .await;
+            match result {
+                Ok(result) => result.map_err(Into::into),
+                // Resume the panic in the future, so that the runtime reports it.
+                Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+                Err(err) => Err(format!("blocking handler was cancelled: {err}").into()),
+            }
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Context;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+        time::Duration,
+    };
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn blocking_handlers_run_on_the_blocking_pool() {
+        let mut service = service_fn_blocking(|event: LambdaEvent<u64>| {
+            thread::sleep(Duration::from_millis(event.payload));
+            Ok::<_, Error>(thread::current().name().map(String::from))
+        });
+        let event = LambdaEvent::new(10, Context::default());
+        let thread_name = service.ready().await.unwrap().call(event).await.unwrap();
+        assert_ne!(thread::current().name().map(String::from), thread_name);
+    }
+
+    #[tokio::test]
+    async fn max_threads_limits_concurrent_events() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let service = service_fn_blocking({
+            let (running, peak) = (running.clone(), peak.clone());
+            move |_: LambdaEvent<()>| {
+                peak.fetch_max(running.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(20));
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok::<_, Error>(())
+            }
+        })
+        .with_max_threads(2);
+
+        let calls = (0..6).map(|_| service.clone().oneshot(LambdaEvent::new((), Context::default())));
+        for result in futures::future::join_all(calls).await {
+            result.unwrap();
+        }
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "blocking handler panicked")]
+    async fn panics_are_propagated() {
+        let service =
+            service_fn_blocking(|_: LambdaEvent<()>| -> Result<(), Error> { panic!("blocking handler panicked") });
+        let _ = service.oneshot(LambdaEvent::new((), Context::default())).await;
+    }
+}
@@ -0,0 +1,267 @@
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub use crate::compression::{decompress, DecompressError, Gzip};
+pub use crate::deserializer::DeserializeError;
+use crate::{event_body::PendingBody, Error, EventBody, IntoDiagnostic};
+use bytes::{Bytes, BytesMut};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+
+/// Decodes the payload of an invocation into the event type of the handler.
+///
+/// Select the codec of a runtime with [RuntimeBuilder::codec](crate::RuntimeBuilder::codec).
+/// [Json] is used by default.
+pub trait Deserializer<T> {
+    /// Error returned when the payload can't be decoded, reported as the invocation error.
+    type Error: IntoDiagnostic + Debug;
+
+    /// Decode the payload of an invocation.
+    fn deserialize(&self, payload: Bytes) -> Result<T, Self::Error>;
+
+    /// Whether the runtime passes the body of invocations to the codec without reading it
+    /// first, as an [EventBody] that [Deserializer::deserialize_invocation] takes from the
+    /// extensions of the response of the Runtime API.
+    ///
+    /// The default implementation returns `false`, only [Streamed] streams bodies.
+    fn streams_body(&self) -> bool {
+        false
+    }
+
+    /// Decode the payload of an invocation, with the parts of the response of the Runtime API
+    /// that delivered it.
+    ///
+    /// The default implementation calls [Deserializer::deserialize].
+    fn deserialize_invocation(&self, payload: Bytes, parts: &mut http::response::Parts) -> Result<T, Self::Error> {
+        let _ = parts;
+        self.deserialize(payload)
+    }
+}
+
+/// Encodes the buffered response of the handler into the payload sent to the Runtime API.
+pub trait Serializer<T> {
+    /// Encode the response of an invocation.
+    fn serialize(&self, response: T) -> Result<Bytes, Error>;
+
+    /// Encode the response of an invocation into `buffer`, which the runtime keeps across
+    /// invocations, so that the memory of a response is reused for the next one once the
+    /// Runtime API received it.
+    ///
+    /// The default implementation ignores the buffer and calls [Serializer::serialize].
+    fn serialize_into(&self, response: T, buffer: &mut BytesMut) -> Result<Bytes, Error> {
+        let _ = buffer;
+        self.serialize(response)
+    }
+}
+
+/// Codec that reads and writes JSON payloads with `serde_json`, the default of the runtime.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Json;
+
+impl<T: DeserializeOwned> Deserializer<T> for Json {
+    type Error = DeserializeError;
+
+    fn deserialize(&self, payload: Bytes) -> Result<T, Self::Error> {
+        crate::deserializer::deserialize_payload(&payload)
+    }
+}
+
+impl<T: Serialize> Serializer<T> for Json {
+    fn serialize(&self, response: T) -> Result<Bytes, Error> {
+        Ok(serde_json::to_vec(&response)?.into())
+    }
+
+    fn serialize_into(&self, response: T, buffer: &mut BytesMut) -> Result<Bytes, Error> {
+        // Once the previous response is dropped, the buffer owns its memory again and turns into
+        // a `Vec` without copying. `serde_json` writes into a `Vec` faster than into `BytesMut`.
+        let mut vec = Vec::from(std::mem::take(buffer));
+        vec.clear();
+        let result = serde_json::to_writer(&mut vec, &response);
+        *buffer = BytesMut::from(Bytes::from(vec));
+        result?;
+        Ok(buffer.split().freeze())
+    }
+}
+
+/// Codec that passes payloads through without parsing them.
+///
+/// Handlers receive the payload as [Bytes], `Vec<u8>`, or a UTF-8 `String`, and return
+/// their response in one of those types, which is sent as is. Use it to skip JSON parsing,
+/// or to decode payloads with another format, like CBOR, MessagePack, or protobuf:
+///
+/// ```no_run
+/// use bytes::Bytes;
+/// use lambda_runtime::{codec::Raw, service_fn, Error, LambdaEvent, Runtime};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     Runtime::builder()
+///         .codec(Raw)
+///         .build(service_fn(echo))
+///         .run()
+///         .await
+/// }
+///
+/// async fn echo(event: LambdaEvent<Bytes>) -> Result<Vec<u8>, Error> {
+///     Ok(event.payload.to_vec())
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Raw;
+
+impl Deserializer<Bytes> for Raw {
+    type Error = Error;
+
+    fn deserialize(&self, payload: Bytes) -> Result<Bytes, Self::Error> {
+        Ok(payload)
+    }
+}
+
+impl Deserializer<Vec<u8>> for Raw {
+    type Error = Error;
+
+    fn deserialize(&self, payload: Bytes) -> Result<Vec<u8>, Self::Error> {
+        Ok(payload.into())
+    }
+}
+
+impl Deserializer<String> for Raw {
+    type Error = Error;
+
+    fn deserialize(&self, payload: Bytes) -> Result<String, Self::Error> {
+        Ok(String::from_utf8(payload.into())?)
+    }
+}
+
+impl Serializer<Bytes> for Raw {
+    fn serialize(&self, response: Bytes) -> Result<Bytes, Error> {
+        Ok(response)
+    }
+}
+
+impl Serializer<Vec<u8>> for Raw {
+    fn serialize(&self, response: Vec<u8>) -> Result<Bytes, Error> {
+        Ok(response.into())
+    }
+}
+
+impl Serializer<String> for Raw {
+    fn serialize(&self, response: String) -> Result<Bytes, Error> {
+        Ok(response.into())
+    }
+}
+
+/// Codec that passes the body of invocations to the handler as an [EventBody], which is read
+/// from the Runtime API as the handler consumes it, and encodes responses like [Json].
+///
+/// The runtime buffers the whole payload of every invocation before the handler is called.
+/// Handlers that process very large events can parse them incrementally with this codec
+/// instead, from the [Stream](futures::Stream) of chunks or the
+/// [AsyncRead](tokio::io::AsyncRead) implementation of the body:
+///
+/// ```no_run
+/// use lambda_runtime::{codec::Streamed, service_fn, Error, EventBody, LambdaEvent, Runtime};
+/// use tokio::io::{AsyncBufReadExt, BufReader};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     Runtime::builder()
+///         .codec(Streamed)
+///         .build(service_fn(count_lines))
+///         .run()
+///         .await
+/// }
+///
+/// async fn count_lines(event: LambdaEvent<EventBody>) -> Result<usize, Error> {
+///     let mut lines = BufReader::new(event.payload).lines();
+///     let mut count = 0;
+///     while lines.next_line().await?.is_some() {
+///         count += 1;
+///     }
+///     Ok(count)
+/// }
+/// ```
+///
+/// The payload isn't available before the handler reads it, so [short circuits](crate::ShortCircuit),
+/// the trace log of raw events, and [failure destinations](crate::RuntimeBuilder::on_failure)
+/// receive an empty payload. The body must be read before the deadline of the invocation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Streamed;
+
+impl Deserializer<EventBody> for Streamed {
+    type Error = Error;
+
+    fn deserialize(&self, payload: Bytes) -> Result<EventBody, Self::Error> {
+        Ok(EventBody::from(payload))
+    }
+
+    fn streams_body(&self) -> bool {
+        true
+    }
+
+    fn deserialize_invocation(
+        &self,
+        payload: Bytes,
+        parts: &mut http::response::Parts,
+    ) -> Result<EventBody, Self::Error> {
+        // Invocations that weren't received from the Runtime API, like the ones of the test
+        // utilities, have a buffered payload.
+        let body = parts
+            .extensions
+            .remove::<PendingBody>()
+            .and_then(|pending| pending.take());
+        Ok(body.unwrap_or_else(|| EventBody::from(payload)))
+    }
+}
+
+impl<T: Serialize> Serializer<T> for Streamed {
+    fn serialize(&self, response: T) -> Result<Bytes, Error> {
+        Json.serialize(response)
+    }
+
+    fn serialize_into(&self, response: T, buffer: &mut BytesMut) -> Result<Bytes, Error> {
+        Json.serialize_into(response, buffer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn json_codec() {
+        let value: Value = Json.deserialize(Bytes::from_static(br#"{"hello":"world"}"#)).unwrap();
+        assert_eq!(json!({"hello": "world"}), value);
+        assert_eq!(Bytes::from_static(br#""hello""#), Json.serialize("hello").unwrap());
+
+        let mut buffer = BytesMut::new();
+        let response = Json.serialize_into(json!({"hello": "world"}), &mut buffer).unwrap();
+        assert_eq!(Bytes::from_static(br#"{"hello":"world"}"#), response);
+        assert!(buffer.is_empty());
+
+        let err = <Json as Deserializer<Value>>::deserialize(&Json, Bytes::from_static(b"\x81\xa5hello"));
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn raw_codec() {
+        let payload = Bytes::from_static(b"\x81\xa5hello");
+        let bytes: Bytes = Raw.deserialize(payload.clone()).unwrap();
+        assert_eq!(payload, bytes);
+        assert!(<Raw as Deserializer<String>>::deserialize(&Raw, payload).is_err());
+
+        let text: String = Raw.deserialize(Bytes::from_static(b"hello")).unwrap();
+        assert_eq!("hello", text);
+        assert_eq!(Bytes::from_static(b"hello"), Raw.serialize(text).unwrap());
+    }
+
+    #[tokio::test]
+    async fn streamed_codec() {
+        let (mut parts, ()) = http::Response::new(()).into_parts();
+        let body = Streamed
+            .deserialize_invocation(Bytes::from_static(b"buffered"), &mut parts)
+            .unwrap();
+        assert_eq!(Bytes::from_static(b"buffered"), body.collect().await.unwrap());
+        assert!(Streamed.streams_body() && !<Json as Deserializer<Value>>::streams_body(&Json));
+    }
+}
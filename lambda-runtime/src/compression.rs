@@ -0,0 +1,208 @@
+use crate::{
+    codec::{Deserializer, Json, Serializer},
+    Error, ErrorReport, IntoDiagnostic,
+};
+use base64::prelude::*;
+use bytes::{Bytes, BytesMut};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    io::{Read, Write},
+};
+
+/// Field that marks a payload compressed by [Gzip], always the first one of the envelope.
+const MARKER: &str = "_lambdaCompression";
+const ENCODING: &str = "gzip";
+
+/// Envelope of a compressed payload, which stays a JSON object so that it's a valid response
+/// for the `Invoke` API.
+#[derive(Deserialize, Serialize)]
+struct Envelope<'a> {
+    #[serde(rename = "_lambdaCompression")]
+    encoding: &'a str,
+    payload: &'a str,
+}
+
+/// Codec that gzip-compresses large responses of another codec, and decompresses the events it
+/// compressed, to reduce the size of payloads exchanged between functions.
+///
+/// Responses larger than the threshold are compressed and sent as a JSON object with a
+/// `_lambdaCompression` marker field and the base64-encoded payload, smaller responses are sent
+/// as is. Events with the marker are decompressed before the inner codec decodes them, so a
+/// function can receive the response of another one. Callers that invoke the function with an
+/// AWS SDK read responses with [decompress].
+///
+/// ```no_run
+/// use lambda_runtime::{codec::{Gzip, Json}, service_fn, Error, LambdaEvent, Runtime};
+/// use serde_json::Value;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     Runtime::builder()
+///         .codec(Gzip::new(Json).with_threshold(16 * 1024))
+///         .build(service_fn(func))
+///         .run()
+///         .await
+/// }
+///
+/// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+///     Ok(event.payload)
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Gzip<C = Json> {
+    inner: C,
+    threshold: usize,
+    level: u32,
+}
+
+impl<C> Gzip<C> {
+    /// Size above which [Gzip::new] compresses responses, in bytes.
+    pub const DEFAULT_THRESHOLD: usize = 32 * 1024;
+
+    /// Compress the responses of `inner` larger than [Gzip::DEFAULT_THRESHOLD].
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            threshold: Self::DEFAULT_THRESHOLD,
+            level: Compression::default().level(),
+        }
+    }
+
+    /// Set the size above which responses are compressed, in bytes.
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Set the compression level, from 0 to 9, trading CPU time for smaller payloads.
+    pub fn with_level(mut self, level: u32) -> Self {
+        self.level = level.min(9);
+        self
+    }
+
+    fn compress(&self, payload: Bytes) -> Result<Bytes, Error> {
+        if payload.len() <= self.threshold {
+            return Ok(payload);
+        }
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::new(self.level));
+        encoder.write_all(&payload)?;
+        let compressed = BASE64_STANDARD.encode(encoder.finish()?);
+        let envelope = Envelope {
+            encoding: ENCODING,
+            payload: &compressed,
+        };
+        Ok(serde_json::to_vec(&envelope)?.into())
+    }
+}
+
+impl Default for Gzip {
+    fn default() -> Self {
+        Self::new(Json)
+    }
+}
+
+impl<C: Deserializer<T>, T> Deserializer<T> for Gzip<C> {
+    type Error = ErrorReport;
+
+    fn deserialize(&self, payload: Bytes) -> Result<T, Self::Error> {
+        let payload = decompress(payload).map_err(|err| ErrorReport::new("DecompressionError", err.to_string()))?;
+        self.inner
+            .deserialize(payload)
+            .map_err(IntoDiagnostic::into_error_report)
+    }
+}
+
+impl<C: Serializer<T>, T> Serializer<T> for Gzip<C> {
+    fn serialize(&self, response: T) -> Result<Bytes, Error> {
+        self.compress(self.inner.serialize(response)?)
+    }
+
+    fn serialize_into(&self, response: T, buffer: &mut BytesMut) -> Result<Bytes, Error> {
+        self.compress(self.inner.serialize_into(response, buffer)?)
+    }
+}
+
+/// Decompress a payload compressed by the [Gzip] codec, like the response of a function read
+/// with an AWS SDK. Payloads without the compression marker are returned as is.
+pub fn decompress(payload: Bytes) -> Result<Bytes, DecompressError> {
+    if !is_compressed(&payload) {
+        return Ok(payload);
+    }
+    let envelope: Envelope<'_> = serde_json::from_slice(&payload).map_err(|err| DecompressError(err.into()))?;
+    if envelope.encoding != ENCODING {
+        return Err(DecompressError(
+            format!("unsupported encoding `{}`", envelope.encoding).into(),
+        ));
+    }
+    let compressed = BASE64_STANDARD
+        .decode(envelope.payload)
+        .map_err(|err| DecompressError(err.into()))?;
+    let mut decompressed = Vec::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_end(&mut decompressed)
+        .map_err(|err| DecompressError(err.into()))?;
+    Ok(decompressed.into())
+}
+
+/// Whether the payload starts with the marker field, without parsing the whole payload.
+fn is_compressed(payload: &[u8]) -> bool {
+    let start = payload.iter().position(|byte| !byte.is_ascii_whitespace()).unwrap_or(0);
+    payload[start..]
+        .strip_prefix(b"{\"")
+        .is_some_and(|rest| rest.starts_with(MARKER.as_bytes()))
+}
+
+/// Error returned when a payload with the compression marker can't be decompressed.
+#[derive(Debug)]
+pub struct DecompressError(Error);
+
+impl fmt::Display for DecompressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "compressed payload can't be decoded: {}", self.0)
+    }
+}
+
+impl std::error::Error for DecompressError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn compress_large_responses() {
+        let codec = Gzip::new(Json).with_threshold(64);
+        let small = json!({ "id": 1 });
+        assert_eq!(Bytes::from_static(br#"{"id":1}"#), codec.serialize(&small).unwrap());
+
+        let large = json!({ "items": vec!["repeated item"; 100] });
+        let compressed = codec.serialize(&large).unwrap();
+        assert!(compressed.starts_with(br#"{"_lambdaCompression":"gzip","payload":""#));
+        assert!(compressed.len() < serde_json::to_vec(&large).unwrap().len());
+
+        let value: Value = codec.deserialize(compressed.clone()).unwrap();
+        assert_eq!(large, value);
+        let value: Value = codec.deserialize(Bytes::from_static(br#"{"id":1}"#)).unwrap();
+        assert_eq!(small, value);
+        assert_eq!(serde_json::to_vec(&large).unwrap(), decompress(compressed).unwrap());
+    }
+
+    #[test]
+    fn report_invalid_compressed_payloads() {
+        let payload = Bytes::from_static(br#" {"_lambdaCompression":"zstd","payload":""}"#);
+        let err = decompress(payload.clone()).unwrap_err();
+        assert_eq!(
+            "compressed payload can't be decoded: unsupported encoding `zstd`",
+            err.to_string()
+        );
+
+        let err = <Gzip as Deserializer<Value>>::deserialize(&Gzip::default(), payload).unwrap_err();
+        assert_eq!("DecompressionError", err.error_type);
+    }
+}
@@ -2,6 +2,7 @@ use std::{error::Error, fmt};
 
 use serde::Deserialize;
 
+#[cfg(feature = "router")]
 use crate::{Context, LambdaEvent};
 
 const ERROR_CONTEXT: &str = "failed to deserialize the incoming data into the function's payload type";
@@ -10,7 +11,7 @@ const ERROR_CONTEXT: &str = "failed to deserialize the incoming data into the fu
 /// Returned when the data sent to the function cannot be deserialized
 /// into the type that the function receives.
 #[derive(Debug)]
-pub(crate) struct DeserializeError {
+pub struct DeserializeError {
     inner: serde_path_to_error::Error<serde_json::Error>,
 }
 
@@ -32,12 +33,24 @@ impl Error for DeserializeError {
 }
 
 /// Deserialize the data sent to the function into the type that the function receives.
-pub(crate) fn deserialize<T>(body: &[u8], context: Context) -> Result<LambdaEvent<T>, DeserializeError>
+pub(crate) fn deserialize_payload<T>(body: &[u8]) -> Result<T, DeserializeError>
 where
     T: for<'de> Deserialize<'de>,
 {
     let jd = &mut serde_json::Deserializer::from_slice(body);
-    serde_path_to_error::deserialize(jd)
+    serde_path_to_error::deserialize(jd).map_err(|inner| DeserializeError { inner })
+}
+
+/// Deserialize an already parsed payload into the type that a handler receives.
+#[cfg(feature = "router")]
+pub(crate) fn deserialize_value<T>(
+    payload: serde_json::Value,
+    context: Context,
+) -> Result<LambdaEvent<T>, DeserializeError>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    serde_path_to_error::deserialize(payload)
         .map(|payload| LambdaEvent::new(payload, context))
         .map_err(|inner| DeserializeError { inner })
 }
@@ -16,7 +16,8 @@ use crate::{deserializer::DeserializeError, Error};
 /// not be reliable for conditional error handling.
 ///
 /// To get more descriptive [`error_type`][`Diagnostic::error_type`] fields, you can implement `From` for your error type.
-/// That gives you full control on what the `error_type` is.
+/// That gives you full control on what the `error_type` is. To also report a stack trace, implement
+/// [`IntoDiagnostic`] instead.
 ///
 /// Example:
 /// ```
@@ -151,6 +152,145 @@ impl From<miette::Report> for Diagnostic {
     }
 }
 
+/// Error payload sent to the Lambda Runtime API when an invocation fails.
+///
+/// It's a [`Diagnostic`] with an optional stack trace, which Lambda returns to the caller,
+/// like a Step Functions state machine, in the `stackTrace` field of the error.
+#[non_exhaustive]
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorReport {
+    /// The type of the error, see [`Diagnostic::error_type`].
+    pub error_type: String,
+    /// The message of the error, see [`Diagnostic::error_message`].
+    pub error_message: String,
+    /// The stack trace of the error, one frame per line.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stack_trace: Vec<String>,
+}
+
+impl ErrorReport {
+    /// Create a report without stack trace.
+    pub fn new(error_type: impl Into<String>, error_message: impl Into<String>) -> Self {
+        Self {
+            error_type: error_type.into(),
+            error_message: error_message.into(),
+            stack_trace: Vec::new(),
+        }
+    }
+
+    /// Set the stack trace of the report.
+    pub fn with_stack_trace(mut self, stack_trace: Vec<String>) -> Self {
+        self.stack_trace = stack_trace;
+        self
+    }
+}
+
+impl std::fmt::Display for ErrorReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.error_type, self.error_message)
+    }
+}
+
+impl std::error::Error for ErrorReport {}
+
+impl From<Diagnostic> for ErrorReport {
+    fn from(value: Diagnostic) -> Self {
+        Self::new(value.error_type, value.error_message)
+    }
+}
+
+/// Errors that control how they are reported to the Lambda Runtime API.
+///
+/// Every type that converts into a [`Diagnostic`] implements this trait. Implement it directly,
+/// instead of `From<YourError> for Diagnostic`, to also report a stack trace, or to pick an
+/// `errorType` that Step Functions retry and catch policies can match on:
+///
+/// ```
+/// use lambda_runtime::{diagnostic::{ErrorReport, IntoDiagnostic}, LambdaEvent};
+///
+/// #[derive(Debug)]
+/// enum OrderError {
+///     OutOfStock(String),
+/// }
+///
+/// impl IntoDiagnostic for OrderError {
+///     fn into_error_report(self) -> ErrorReport {
+///         match self {
+///             OrderError::OutOfStock(item) => ErrorReport::new("OrderError.OutOfStock", format!("{item} is out of stock"))
+///                 .with_stack_trace(vec!["place_order (src/orders.rs:42)".to_string()]),
+///         }
+///     }
+/// }
+///
+/// async fn function_handler(_event: LambdaEvent<()>) -> Result<(), OrderError> {
+///     Err(OrderError::OutOfStock("book".to_string()))
+/// }
+/// ```
+pub trait IntoDiagnostic {
+    /// Convert the error into the payload sent to the Runtime API.
+    fn into_error_report(self) -> ErrorReport;
+}
+
+impl<T: Into<Diagnostic>> IntoDiagnostic for T {
+    fn into_error_report(self) -> ErrorReport {
+        ErrorReport::from(self.into())
+    }
+}
+
+impl IntoDiagnostic for ErrorReport {
+    fn into_error_report(self) -> ErrorReport {
+        self
+    }
+}
+
+/// Error enums that declare the `errorType` of every variant, and the variants that callers can
+/// retry, usually derived with [`#[derive(LambdaError)]`](macro@crate::LambdaError) and the
+/// `derive` feature.
+///
+/// The derive macro also converts the enum into a [`Diagnostic`], with the `errorType` of the
+/// variant and the [`Display`](std::fmt::Display) message of the error. The `errorType` of a
+/// variant is `Enum.Variant`, unless it's set with `#[lambda_error(error_type = "...")]`.
+/// Variants marked with `#[lambda_error(retryable)]` are listed in
+/// [`LambdaError::RETRYABLE_ERROR_TYPES`], to declare the retry policies of Step Functions
+/// states or EventBridge targets from the same contract:
+///
+/// ```ignore
+/// use lambda_runtime::{LambdaError, LambdaEvent};
+///
+/// #[derive(Debug, LambdaError)]
+/// enum OrderError {
+///     #[lambda_error(error_type = "OutOfStock")]
+///     OutOfStock(String),
+///     #[lambda_error(retryable)]
+///     Throttled,
+/// }
+///
+/// impl std::fmt::Display for OrderError {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         match self {
+///             OrderError::OutOfStock(item) => write!(f, "{item} is out of stock"),
+///             OrderError::Throttled => write!(f, "the inventory service throttled the request"),
+///         }
+///     }
+/// }
+///
+/// assert_eq!(&["OrderError.Throttled"], OrderError::RETRYABLE_ERROR_TYPES);
+/// ```
+pub trait LambdaError {
+    /// `errorType` of the retryable variants.
+    const RETRYABLE_ERROR_TYPES: &'static [&'static str];
+
+    /// `errorType` of the error, reported to the Runtime API.
+    fn error_type(&self) -> &'static str;
+
+    /// Whether the caller can retry the invocation that failed with this error.
+    fn is_retryable(&self) -> bool;
+}
+
+/// Function applied to every error report before it's sent to the Runtime API.
+pub(crate) type ErrorReportHook = std::sync::Arc<dyn Fn(ErrorReport) -> ErrorReport + Send + Sync>;
+
 pub(crate) fn type_name_of_val<T>(_: T) -> String {
     type_name::<T>().into()
 }
@@ -175,6 +315,60 @@ mod test {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn error_report_with_stack_trace() {
+        use serde_json::json;
+        let diagnostic = Diagnostic {
+            error_type: "InvalidEventDataError".into(),
+            error_message: "Error parsing event data.".into(),
+        };
+        let report = diagnostic.into_error_report();
+        assert_eq!(
+            json!({"errorType": "InvalidEventDataError", "errorMessage": "Error parsing event data."}),
+            serde_json::to_value(&report).unwrap()
+        );
+
+        let report = report.with_stack_trace(vec!["main (src/main.rs:1)".into()]);
+        assert_eq!(
+            json!(["main (src/main.rs:1)"]),
+            serde_json::to_value(&report).unwrap()["stackTrace"]
+        );
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derive_lambda_error() {
+        #[derive(Debug, crate::LambdaError)]
+        #[lambda_error(crate = "crate")]
+        enum OrderError {
+            #[lambda_error(error_type = "OutOfStock")]
+            OutOfStock(String),
+            #[lambda_error(retryable)]
+            Throttled {
+                retry_after: u32,
+            },
+            Cancelled,
+        }
+
+        impl std::fmt::Display for OrderError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    OrderError::OutOfStock(item) => write!(f, "{item} is out of stock"),
+                    OrderError::Throttled { retry_after } => write!(f, "throttled, retry after {retry_after}s"),
+                    OrderError::Cancelled => write!(f, "the order was cancelled"),
+                }
+            }
+        }
+
+        assert_eq!(&["OrderError.Throttled"], OrderError::RETRYABLE_ERROR_TYPES);
+        assert!(OrderError::Throttled { retry_after: 1 }.is_retryable());
+        assert!(!OrderError::Cancelled.is_retryable());
+        assert_eq!("OrderError.Cancelled", OrderError::Cancelled.error_type());
+
+        let report = OrderError::OutOfStock("book".into()).into_error_report();
+        assert_eq!(ErrorReport::new("OutOfStock", "book is out of stock"), report);
+    }
+
     #[cfg(feature = "anyhow")]
     #[test]
     fn test_anyhow_integration() {
@@ -0,0 +1,235 @@
+use crate::LambdaEnvError;
+use std::{env, fmt, str::FromStr};
+
+/// Configuration read from environment variables, usually derived with
+/// [`#[derive(FromLambdaEnv)]`](macro@crate::FromLambdaEnv).
+///
+/// Read the configuration once, when the function initializes. All the missing or invalid
+/// variables are reported together in the [EnvConfigError], so that a misconfigured function
+/// can be fixed in one deployment. Empty variables are treated like unset ones, so they fall
+/// back to their default, or are reported as missing when they have none:
+///
+/// ```no_run
+/// use lambda_runtime::{Error, FromLambdaEnv, LambdaEvent};
+/// use serde_json::Value;
+///
+/// #[derive(FromLambdaEnv)]
+/// #[lambda_env(prefix = "ORDERS_")]
+/// struct Settings {
+///     // Read from `ORDERS_TABLE_NAME`.
+///     table_name: String,
+///     #[lambda_env(default = "25")]
+///     page_size: u32,
+///     #[lambda_env(name = "FEATURE_DISCOUNTS")]
+///     discounts: Option<bool>,
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let settings = Settings::from_env()?;
+///     lambda_runtime::run_with_state(settings, func).await
+/// }
+///
+/// async fn func(event: LambdaEvent<Value>, settings: lambda_runtime::State<Settings>) -> Result<Value, Error> {
+///     Ok(serde_json::json!({ "table": settings.table_name, "pageSize": settings.page_size }))
+/// }
+/// ```
+pub trait FromLambdaEnv: Sized {
+    /// Read the configuration from the environment variables of the process.
+    fn from_env() -> Result<Self, EnvConfigError> {
+        Self::from_lookup(&|name| env::var(name).ok())
+    }
+
+    /// Read the configuration from the variables returned by `lookup`, like a map of variables
+    /// in tests.
+    fn from_lookup(lookup: &dyn Fn(&str) -> Option<String>) -> Result<Self, EnvConfigError>;
+}
+
+/// Error returned when environment variables of a [FromLambdaEnv] configuration are missing or
+/// invalid, with the error of every variable.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EnvConfigError {
+    errors: Vec<LambdaEnvError>,
+}
+
+impl EnvConfigError {
+    /// The errors of the variables, in the order of the fields of the configuration.
+    pub fn errors(&self) -> &[LambdaEnvError] {
+        &self.errors
+    }
+}
+
+impl fmt::Display for EnvConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid configuration: ")?;
+        for (index, error) in self.errors.iter().enumerate() {
+            if index > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for EnvConfigError {}
+
+/// Reader of the variables of a configuration, used by the code generated by
+/// [`#[derive(FromLambdaEnv)]`](macro@crate::FromLambdaEnv).
+#[doc(hidden)]
+pub struct EnvReader<'a> {
+    lookup: &'a dyn Fn(&str) -> Option<String>,
+    errors: Vec<LambdaEnvError>,
+}
+
+impl<'a> EnvReader<'a> {
+    pub fn new(lookup: &'a dyn Fn(&str) -> Option<String>) -> Self {
+        Self {
+            lookup,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Read a variable, or its default when it's unset or empty.
+    pub fn required<T>(&mut self, name: &'static str, default: Option<&str>) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        match self.lookup(name).or_else(|| default.map(String::from)) {
+            Some(value) => self.parse(name, value),
+            None => {
+                self.errors.push(LambdaEnvError::Missing(name));
+                None
+            }
+        }
+    }
+
+    /// Read a variable that can be unset or empty.
+    pub fn optional<T>(&mut self, name: &'static str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let value = self.lookup(name)?;
+        self.parse(name, value)
+    }
+
+    fn lookup(&self, name: &str) -> Option<String> {
+        (self.lookup)(name).filter(|value| !value.is_empty())
+    }
+
+    fn parse<T>(&mut self, name: &'static str, value: String) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        match value.parse() {
+            Ok(parsed) => Some(parsed),
+            Err(err) => {
+                self.errors.push(LambdaEnvError::Invalid {
+                    name,
+                    reason: err.to_string(),
+                    value,
+                });
+                None
+            }
+        }
+    }
+
+    /// Fail with the errors of all the variables read.
+    pub fn finish(self) -> Result<(), EnvConfigError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(EnvConfigError { errors: self.errors })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::FromLambdaEnv;
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, FromLambdaEnv)]
+    #[lambda_env(prefix = "ORDERS_", crate = "crate")]
+    struct Settings {
+        table_name: String,
+        #[lambda_env(default = "25")]
+        page_size: u32,
+        #[lambda_env(name = "FEATURE_DISCOUNTS")]
+        discounts: Option<bool>,
+    }
+
+    fn settings(vars: &[(&str, &str)]) -> Result<Settings, EnvConfigError> {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        Settings::from_lookup(&|name| vars.get(name).map(|value| value.to_string()))
+    }
+
+    #[test]
+    fn read_settings_from_the_env() {
+        let expected = Settings {
+            table_name: "orders".into(),
+            page_size: 25,
+            discounts: None,
+        };
+        assert_eq!(expected, settings(&[("ORDERS_TABLE_NAME", "orders")]).unwrap());
+
+        let vars = [
+            ("ORDERS_TABLE_NAME", "orders"),
+            ("ORDERS_PAGE_SIZE", "50"),
+            ("FEATURE_DISCOUNTS", "true"),
+        ];
+        let expected = Settings {
+            table_name: "orders".into(),
+            page_size: 50,
+            discounts: Some(true),
+        };
+        assert_eq!(expected, settings(&vars).unwrap());
+    }
+
+    #[test]
+    fn treat_empty_variables_as_unset() {
+        let vars = [
+            ("ORDERS_TABLE_NAME", "orders"),
+            ("ORDERS_PAGE_SIZE", ""),
+            ("FEATURE_DISCOUNTS", ""),
+        ];
+        let expected = Settings {
+            table_name: "orders".into(),
+            page_size: 25,
+            discounts: None,
+        };
+        assert_eq!(expected, settings(&vars).unwrap());
+
+        let err = settings(&[("ORDERS_TABLE_NAME", "")]).unwrap_err();
+        assert_eq!(&[LambdaEnvError::Missing("ORDERS_TABLE_NAME")], err.errors());
+    }
+
+    #[test]
+    fn report_all_the_invalid_variables() {
+        let err = settings(&[("ORDERS_PAGE_SIZE", "many"), ("FEATURE_DISCOUNTS", "maybe")]).unwrap_err();
+        assert_eq!(
+            &[
+                LambdaEnvError::Missing("ORDERS_TABLE_NAME"),
+                LambdaEnvError::Invalid {
+                    name: "ORDERS_PAGE_SIZE",
+                    value: "many".into(),
+                    reason: "invalid digit found in string".into(),
+                },
+                LambdaEnvError::Invalid {
+                    name: "FEATURE_DISCOUNTS",
+                    value: "maybe".into(),
+                    reason: "provided string was not `true` or `false`".into(),
+                },
+            ],
+            err.errors()
+        );
+        assert_eq!(
+            "invalid configuration: missing ORDERS_TABLE_NAME env var; invalid ORDERS_PAGE_SIZE env var `many`: invalid digit found in string; invalid FEATURE_DISCOUNTS env var `maybe`: provided string was not `true` or `false`",
+            err.to_string()
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use crate::Error;
+use bytes::{Buf, Bytes};
+use futures::Stream;
+use http_body_util::BodyExt;
+use hyper::body::{Body as _, Incoming};
+use std::{
+    fmt, io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{self, Poll},
+};
+use tokio::io::{AsyncRead, ReadBuf};
+
+/// Payload of an invocation that is read from the Runtime API as the handler consumes it,
+/// received by handlers that use the [Streamed](crate::codec::Streamed) codec.
+///
+/// The body is a [Stream] of chunks and implements [AsyncRead], so handlers that process very
+/// large events can parse them incrementally, without holding the whole payload in memory.
+#[must_use = "the body of the invocation must be read"]
+pub struct EventBody {
+    inner: Inner,
+    chunk: Bytes,
+}
+
+enum Inner {
+    Incoming(Incoming),
+    Buffered(Option<Bytes>),
+}
+
+impl EventBody {
+    pub(crate) fn incoming(incoming: Incoming) -> Self {
+        Self {
+            inner: Inner::Incoming(incoming),
+            chunk: Bytes::new(),
+        }
+    }
+
+    /// Read the rest of the body into memory.
+    pub async fn collect(self) -> Result<Bytes, Error> {
+        let Self { inner, chunk } = self;
+        let rest = match inner {
+            Inner::Incoming(incoming) => incoming.collect().await?.to_bytes(),
+            Inner::Buffered(bytes) => bytes.unwrap_or_default(),
+        };
+        if chunk.is_empty() {
+            return Ok(rest);
+        }
+        Ok([chunk, rest].concat().into())
+    }
+
+    /// Number of bytes left in the body, when the Runtime API sent its length.
+    pub fn size_hint(&self) -> Option<u64> {
+        let rest = match &self.inner {
+            Inner::Incoming(incoming) => incoming.size_hint().exact()?,
+            Inner::Buffered(bytes) => bytes.as_ref().map_or(0, |bytes| bytes.len() as u64),
+        };
+        Some(rest + self.chunk.len() as u64)
+    }
+
+    fn poll_chunk(&mut self, cx: &mut task::Context<'_>) -> Poll<Option<Result<Bytes, Error>>> {
+        if !self.chunk.is_empty() {
+            return Poll::Ready(Some(Ok(std::mem::take(&mut self.chunk))));
+        }
+        match &mut self.inner {
+            Inner::Buffered(bytes) => Poll::Ready(bytes.take().filter(|bytes| !bytes.is_empty()).map(Ok)),
+            Inner::Incoming(incoming) => loop {
+                let frame = match task::ready!(Pin::new(&mut *incoming).poll_frame(cx)) {
+                    Some(Ok(frame)) => frame,
+                    Some(Err(err)) => return Poll::Ready(Some(Err(err.into()))),
+                    None => return Poll::Ready(None),
+                };
+                // The Runtime API doesn't send trailers with events, only data frames matter.
+                if let Ok(data) = frame.into_data() {
+                    if !data.is_empty() {
+                        return Poll::Ready(Some(Ok(data)));
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Slot of the body in the extensions of the response of the Runtime API, which only hold
+/// values that can be cloned. The codec takes the body out of it.
+#[derive(Clone)]
+pub(crate) struct PendingBody(Arc<Mutex<Option<EventBody>>>);
+
+impl PendingBody {
+    pub(crate) fn new(incoming: Incoming) -> Self {
+        Self(Arc::new(Mutex::new(Some(EventBody::incoming(incoming)))))
+    }
+
+    pub(crate) fn take(&self) -> Option<EventBody> {
+        self.0.lock().expect("the body is never taken while panicking").take()
+    }
+}
+
+impl From<Bytes> for EventBody {
+    fn from(bytes: Bytes) -> Self {
+        Self {
+            inner: Inner::Buffered(Some(bytes)),
+            chunk: Bytes::new(),
+        }
+    }
+}
+
+impl fmt::Debug for EventBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBody")
+            .field("size_hint", &self.size_hint())
+            .finish()
+    }
+}
+
+impl Stream for EventBody {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_chunk(cx)
+    }
+}
+
+impl AsyncRead for EventBody {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut task::Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let mut chunk = match task::ready!(this.poll_chunk(cx)) {
+            Some(Ok(chunk)) => chunk,
+            Some(Err(err)) => return Poll::Ready(Err(io::Error::other(err))),
+            None => return Poll::Ready(Ok(())),
+        };
+        let len = chunk.len().min(buf.remaining());
+        buf.put_slice(&chunk[..len]);
+        chunk.advance(len);
+        this.chunk = chunk;
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::StreamExt;
+    use tokio::io::AsyncReadExt;
+
+    #[tokio::test]
+    async fn read_buffered_body() {
+        let mut body = EventBody::from(Bytes::from_static(b"hello world"));
+        let mut start = [0; 6];
+        body.read_exact(&mut start).await.unwrap();
+        assert_eq!(b"hello ", &start);
+        assert_eq!(Some(5), body.size_hint());
+        assert_eq!(Bytes::from_static(b"world"), body.collect().await.unwrap());
+
+        let mut body = EventBody::from(Bytes::new());
+        assert!(body.next().await.is_none());
+    }
+}
@@ -0,0 +1,224 @@
+use crate::InitializationType;
+use serde::{Deserialize, Serialize};
+use std::{env, fmt, sync::OnceLock, time::Duration};
+
+/// Configuration of the function, read from the [Lambda environment variables].
+///
+/// Use [LambdaEnv::current] to get the configuration of the running function, which is only
+/// parsed once, or [LambdaEnv::from_env] to parse the environment again:
+///
+/// ```no_run
+/// use lambda_runtime::LambdaEnv;
+///
+/// let env = LambdaEnv::current().expect("invalid Lambda environment");
+/// println!("{} runs with {} MB in {}", env.function_name, env.memory_size, env.region);
+/// ```
+///
+/// [Lambda environment variables]: https://docs.aws.amazon.com/lambda/latest/dg/configuration-envvars.html#configuration-envvars-runtime
+#[non_exhaustive]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LambdaEnv {
+    /// The name of the function, from `AWS_LAMBDA_FUNCTION_NAME`.
+    pub function_name: String,
+    /// The version of the function being executed, from `AWS_LAMBDA_FUNCTION_VERSION`.
+    pub function_version: String,
+    /// The amount of memory available to the function in MB, from `AWS_LAMBDA_FUNCTION_MEMORY_SIZE`.
+    pub memory_size: u32,
+    /// The AWS region of the function, from `AWS_REGION` or `AWS_DEFAULT_REGION`.
+    pub region: String,
+    /// The name of the Amazon CloudWatch Logs group of the function, from `AWS_LAMBDA_LOG_GROUP_NAME`.
+    pub log_group: Option<String>,
+    /// The name of the Amazon CloudWatch Logs stream of the function, from `AWS_LAMBDA_LOG_STREAM_NAME`.
+    pub log_stream: Option<String>,
+    /// The timeout of the function, from `AWS_LAMBDA_FUNCTION_TIMEOUT`.
+    ///
+    /// Lambda doesn't set this variable, only local emulators like the Runtime Interface
+    /// Emulator do. Use the deadline of the [Context](crate::Context) to know how long an
+    /// invocation can still run.
+    pub timeout: Option<Duration>,
+    /// The instruction set architecture the function runs on.
+    pub architecture: Architecture,
+    /// How the execution environment was initialized, from `AWS_LAMBDA_INITIALIZATION_TYPE`.
+    pub initialization_type: InitializationType,
+    /// The host and port of the Lambda Runtime API, from `AWS_LAMBDA_RUNTIME_API`.
+    pub runtime_api: String,
+    /// The handler location configured on the function, from `_HANDLER`.
+    pub handler: Option<String>,
+}
+
+/// Instruction set architecture of a function.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Architecture {
+    /// 64-bit x86 architecture.
+    #[default]
+    X86_64,
+    /// 64-bit ARM architecture, AWS Graviton.
+    Arm64,
+}
+
+impl Architecture {
+    /// The architecture this binary is compiled for.
+    pub const fn current() -> Self {
+        if cfg!(target_arch = "aarch64") {
+            Architecture::Arm64
+        } else {
+            Architecture::X86_64
+        }
+    }
+}
+
+impl fmt::Display for Architecture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Architecture::X86_64 => f.write_str("x86_64"),
+            Architecture::Arm64 => f.write_str("arm64"),
+        }
+    }
+}
+
+/// Error returned when the Lambda environment variables are missing or invalid.
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LambdaEnvError {
+    /// A required variable is not set.
+    Missing(&'static str),
+    /// A variable is set to a value that can't be parsed.
+    Invalid {
+        /// The name of the variable.
+        name: &'static str,
+        /// The value of the variable.
+        value: String,
+        /// Why the value is invalid.
+        reason: String,
+    },
+}
+
+impl fmt::Display for LambdaEnvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LambdaEnvError::Missing(name) => write!(f, "missing {name} env var"),
+            LambdaEnvError::Invalid { name, value, reason } => {
+                write!(f, "invalid {name} env var `{value}`: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LambdaEnvError {}
+
+impl LambdaEnv {
+    /// Parse the configuration from the environment variables of the process.
+    pub fn from_env() -> Result<Self, LambdaEnvError> {
+        Self::from_lookup(|name| env::var(name).ok())
+    }
+
+    /// The configuration of the running function, parsed from the environment variables
+    /// the first time this function is called.
+    pub fn current() -> Result<&'static Self, LambdaEnvError> {
+        static CURRENT: OnceLock<Result<LambdaEnv, LambdaEnvError>> = OnceLock::new();
+        CURRENT.get_or_init(Self::from_env).as_ref().map_err(Clone::clone)
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, LambdaEnvError> {
+        let required = |name: &'static str| lookup(name).ok_or(LambdaEnvError::Missing(name));
+        let optional = |name: &'static str| lookup(name).filter(|value| !value.is_empty());
+
+        let memory_size = parse_number(
+            "AWS_LAMBDA_FUNCTION_MEMORY_SIZE",
+            required("AWS_LAMBDA_FUNCTION_MEMORY_SIZE")?,
+        )?;
+        let timeout = optional("AWS_LAMBDA_FUNCTION_TIMEOUT")
+            .map(|value| parse_number("AWS_LAMBDA_FUNCTION_TIMEOUT", value))
+            .transpose()?
+            .map(|seconds| Duration::from_secs(seconds.into()));
+
+        Ok(LambdaEnv {
+            function_name: required("AWS_LAMBDA_FUNCTION_NAME")?,
+            function_version: required("AWS_LAMBDA_FUNCTION_VERSION")?,
+            memory_size,
+            region: optional("AWS_REGION")
+                .or_else(|| optional("AWS_DEFAULT_REGION"))
+                .ok_or(LambdaEnvError::Missing("AWS_REGION"))?,
+            log_group: optional("AWS_LAMBDA_LOG_GROUP_NAME"),
+            log_stream: optional("AWS_LAMBDA_LOG_STREAM_NAME"),
+            timeout,
+            architecture: Architecture::current(),
+            initialization_type: optional("AWS_LAMBDA_INITIALIZATION_TYPE")
+                .map(|value| InitializationType::from(value.as_str()))
+                .unwrap_or_default(),
+            runtime_api: required("AWS_LAMBDA_RUNTIME_API")?,
+            handler: optional("_HANDLER"),
+        })
+    }
+}
+
+fn parse_number(name: &'static str, value: String) -> Result<u32, LambdaEnvError> {
+    value
+        .parse()
+        .map_err(|err: std::num::ParseIntError| LambdaEnvError::Invalid {
+            name,
+            reason: err.to_string(),
+            value,
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn lambda_env(vars: &[(&str, &str)]) -> Result<LambdaEnv, LambdaEnvError> {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        LambdaEnv::from_lookup(|name| vars.get(name).map(|value| value.to_string()))
+    }
+
+    const VARS: &[(&str, &str)] = &[
+        ("AWS_LAMBDA_FUNCTION_NAME", "orders"),
+        ("AWS_LAMBDA_FUNCTION_VERSION", "$LATEST"),
+        ("AWS_LAMBDA_FUNCTION_MEMORY_SIZE", "512"),
+        ("AWS_REGION", "eu-west-1"),
+        ("AWS_LAMBDA_LOG_GROUP_NAME", "/aws/lambda/orders"),
+        ("AWS_LAMBDA_LOG_STREAM_NAME", "2024/01/01/[$LATEST]abc"),
+        ("AWS_LAMBDA_INITIALIZATION_TYPE", "snap-start"),
+        ("AWS_LAMBDA_RUNTIME_API", "127.0.0.1:9001"),
+    ];
+
+    #[test]
+    fn parse_lambda_env() {
+        let env = lambda_env(VARS).unwrap();
+        assert_eq!("orders", env.function_name);
+        assert_eq!("$LATEST", env.function_version);
+        assert_eq!(512, env.memory_size);
+        assert_eq!("eu-west-1", env.region);
+        assert_eq!(Some("/aws/lambda/orders"), env.log_group.as_deref());
+        assert_eq!(None, env.timeout);
+        assert_eq!(Architecture::current(), env.architecture);
+        assert_eq!(InitializationType::SnapStart, env.initialization_type);
+        assert_eq!("127.0.0.1:9001", env.runtime_api);
+        assert_eq!(None, env.handler);
+
+        let env = lambda_env(&[VARS, &[("AWS_LAMBDA_FUNCTION_TIMEOUT", "30")]].concat()).unwrap();
+        assert_eq!(Some(Duration::from_secs(30)), env.timeout);
+    }
+
+    #[test]
+    fn lambda_env_errors() {
+        let vars: Vec<_> = VARS
+            .iter()
+            .copied()
+            .filter(|(name, _)| *name != "AWS_LAMBDA_FUNCTION_NAME")
+            .collect();
+        assert_eq!(
+            "missing AWS_LAMBDA_FUNCTION_NAME env var",
+            lambda_env(&vars).unwrap_err().to_string()
+        );
+
+        let vars = [VARS, &[("AWS_LAMBDA_FUNCTION_MEMORY_SIZE", "512MB")]].concat();
+        assert_eq!(
+            "invalid AWS_LAMBDA_FUNCTION_MEMORY_SIZE env var `512MB`: invalid digit found in string",
+            lambda_env(&vars).unwrap_err().to_string()
+        );
+    }
+}
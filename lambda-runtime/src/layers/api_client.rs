@@ -1,12 +1,40 @@
-use crate::LambdaInvocation;
+use crate::{observer::Observers, runtime::ApiClient, Context, LambdaInvocation, RetryPolicy};
+use bytes::Bytes;
 use futures::{future::BoxFuture, ready, FutureExt, TryFutureExt};
-use hyper::body::Incoming;
-use lambda_runtime_api_client::{body::Body, BoxError, Client};
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Body as _, Incoming};
+use lambda_runtime_api_client::{body::Body, BoxError};
 use pin_project::pin_project;
-use std::{future::Future, pin::Pin, sync::Arc, task};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task,
+};
+use tokio::sync::oneshot;
 use tower::Service;
 use tracing::error;
 
+/// Invocation extension that is notified once the response of the invocation was handed over to
+/// the connection to the Runtime API, before the Runtime API acknowledges it.
+///
+/// The sender is dropped when the body of the response is dropped, which the HTTP client only
+/// does after writing its last frame, or when the invocation fails before sending a response.
+/// When the response is retried, the sender is kept until an attempt writes the whole body, so
+/// attempts that fail before sending the response don't notify.
+#[derive(Clone, Debug)]
+pub struct ResponseSent(Arc<Mutex<Option<oneshot::Sender<()>>>>);
+
+impl ResponseSent {
+    pub(crate) fn new(sender: oneshot::Sender<()>) -> Self {
+        Self(Arc::new(Mutex::new(Some(sender))))
+    }
+
+    fn take(&self) -> Option<oneshot::Sender<()>> {
+        self.0.lock().unwrap().take()
+    }
+}
+
 /// Tower service that sends a Lambda Runtime API response to the Lambda Runtime HTTP API using
 /// a previously initialized client.
 ///
@@ -15,12 +43,19 @@ use tracing::error;
 /// response `()` as the Lambda request has been completed.
 pub struct RuntimeApiClientService<S> {
     inner: S,
-    client: Arc<Client>,
+    client: Arc<ApiClient>,
+    observers: Observers,
+    retry: RetryPolicy,
 }
 
 impl<S> RuntimeApiClientService<S> {
-    pub fn new(inner: S, client: Arc<Client>) -> Self {
-        Self { inner, client }
+    pub fn new(inner: S, client: Arc<ApiClient>, observers: Observers, retry: RetryPolicy) -> Self {
+        Self {
+            inner,
+            client,
+            observers,
+            retry,
+        }
     }
 }
 
@@ -37,10 +72,12 @@ where
         self.inner.poll_ready(cx)
     }
 
-    fn call(&mut self, req: LambdaInvocation) -> Self::Future {
+    fn call(&mut self, mut req: LambdaInvocation) -> Self::Future {
+        let sent = req.parts.extensions.remove::<ResponseSent>();
+        let observed = (!self.observers.is_empty()).then(|| Box::new((self.observers.clone(), req.context.clone())));
         let request_fut = self.inner.call(req);
         let client = self.client.clone();
-        RuntimeApiClientFuture::First(request_fut, client)
+        RuntimeApiClientFuture::First(request_fut, client, self.retry.clone(), sent, observed)
     }
 }
 
@@ -52,13 +89,22 @@ where
         Self {
             inner: self.inner.clone(),
             client: self.client.clone(),
+            observers: self.observers.clone(),
+            retry: self.retry.clone(),
         }
     }
 }
 
 #[pin_project(project = RuntimeApiClientFutureProj)]
 pub enum RuntimeApiClientFuture<F> {
-    First(#[pin] F, Arc<Client>),
+    First(
+        #[pin] F,
+        Arc<ApiClient>,
+        RetryPolicy,
+        Option<ResponseSent>,
+        // Boxed, like the observed invocations of `RuntimeApiResponseFuture`.
+        Option<Box<(Observers, Context)>>,
+    ),
     Second(#[pin] BoxFuture<'static, Result<http::Response<Incoming>, BoxError>>),
 }
 
@@ -72,12 +118,18 @@ where
         // NOTE: We loop here to directly poll the second future once the first has finished.
         task::Poll::Ready(loop {
             match self.as_mut().project() {
-                RuntimeApiClientFutureProj::First(fut, client) => match ready!(fut.poll(cx)) {
+                RuntimeApiClientFutureProj::First(fut, client, retry, sent, observed) => match ready!(fut.poll(cx)) {
                     Ok(ok) => {
-                        // NOTE: We use 'client.call_boxed' here to obtain a future with static
-                        // lifetime. Otherwise, this future would need to be self-referential...
-                        let next_fut = client
-                            .call(ok)
+                        // NOTE: We move a clone of the client into the future to obtain a future
+                        // with static lifetime. Otherwise, this future would need to be
+                        // self-referential...
+                        let observed = observed.take();
+                        let next_fut = post(client.clone(), retry.clone(), ok, sent.take())
+                            .inspect_ok(move |_| {
+                                if let Some((observers, context)) = observed.map(|observed| *observed) {
+                                    observers.response_posted(&context);
+                                }
+                            })
                             .map_err(|err| {
                                 error!(error = ?err, "failed to send request to Lambda Runtime API");
                                 err
@@ -92,3 +144,218 @@ where
         })
     }
 }
+
+/// Post the request to the Runtime API, and retry it after transport errors when its body is
+/// buffered, so that it can be sent again.
+async fn post(
+    client: Arc<ApiClient>,
+    retry: RetryPolicy,
+    request: http::Request<Body>,
+    sent: Option<ResponseSent>,
+) -> Result<http::Response<Incoming>, BoxError> {
+    if !retry.retries() || request.body().size_hint().exact().is_none() {
+        return client
+            .call(notify_sent(request, sent.and_then(|sent| sent.take())))
+            .await;
+    }
+    let (parts, body) = request.into_parts();
+    let body = body.collect().await?.to_bytes();
+    let response = retry
+        .call("response", || {
+            let mut request = http::Request::new(notify_written(body.clone(), sent.clone()));
+            *request.method_mut() = parts.method.clone();
+            *request.uri_mut() = parts.uri.clone();
+            *request.version_mut() = parts.version;
+            *request.headers_mut() = parts.headers.clone();
+            client.call(request)
+        })
+        .await;
+    // Empty bodies have no frame to notify with.
+    drop(sent.and_then(|sent| sent.take()));
+    response
+}
+
+/// Drop the sender once every byte of `body` was written to the connection, so that an attempt
+/// that fails before sending its body leaves the sender to the next attempt.
+fn notify_written(body: Bytes, sent: Option<ResponseSent>) -> Body {
+    let Some(sent) = sent else {
+        return Body::from(body);
+    };
+    let mut remaining = body.len();
+    Body::new(Full::new(body).map_frame(move |frame| {
+        if let Some(data) = frame.data_ref() {
+            remaining = remaining.saturating_sub(data.len());
+            if remaining == 0 {
+                drop(sent.take());
+            }
+        }
+        frame
+    }))
+}
+
+/// Drop the sender once the body of the request was written to the connection.
+fn notify_sent(request: http::Request<Body>, sent: Option<oneshot::Sender<()>>) -> http::Request<Body> {
+    let Some(sent) = sent else {
+        return request;
+    };
+    request.map(|body| {
+        // The closure owns the sender, so it's dropped with the body.
+        Body::new(body.map_frame(move |frame| {
+            let _ = &sent;
+            frame
+        }))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Context;
+    use httpmock::prelude::*;
+    use lambda_runtime_api_client::{
+        connect::{DynConnector, HttpConnector},
+        Client,
+    };
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    #[tokio::test]
+    async fn notify_when_the_response_is_sent() {
+        let server = MockServer::start();
+        let response = server.mock(|when, then| {
+            when.method(POST).path("/2018-06-01/runtime/invocation/id/response");
+            then.status(202).delay(Duration::from_millis(500));
+        });
+        let base = server.base_url().parse().unwrap();
+        let client = Arc::new(Client::builder().with_endpoint(base).build().unwrap());
+
+        let inner = tower::service_fn(|_: LambdaInvocation| async {
+            let req = http::Request::post("/2018-06-01/runtime/invocation/id/response")
+                .body(Body::from("{}"))
+                .unwrap();
+            Ok::<_, BoxError>(req)
+        });
+        let mut service = RuntimeApiClientService::new(inner, client, Default::default(), RetryPolicy::none());
+
+        let (sender, sent) = oneshot::channel();
+        let (mut parts, _) = http::Response::new(()).into_parts();
+        parts.extensions.insert(ResponseSent::new(sender));
+        let invocation = LambdaInvocation {
+            parts,
+            body: Default::default(),
+            context: Context::default(),
+        };
+        let call = tokio::spawn(service.call(invocation));
+
+        tokio::time::timeout(Duration::from_millis(250), sent)
+            .await
+            .expect("response wasn't sent before the Runtime API acknowledged it")
+            .unwrap_err();
+        assert!(!call.is_finished());
+        call.await.unwrap().unwrap();
+        response.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn notify_when_a_retried_response_is_sent() {
+        let server = MockServer::start();
+        let response = server.mock(|when, then| {
+            when.method(POST).path("/2018-06-01/runtime/invocation/id/response");
+            then.status(202).delay(Duration::from_millis(500));
+        });
+        // The first connection fails before the response is sent.
+        let connections = Arc::new(AtomicUsize::new(0));
+        let connector = {
+            let connections = connections.clone();
+            let http = HttpConnector::new();
+            tower::service_fn(move |uri: http::Uri| {
+                let mut http = http.clone();
+                let connection = connections.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if connection == 0 {
+                        return Err::<_, BoxError>("connection refused".into());
+                    }
+                    http.call(uri).await.map_err(Into::into)
+                }
+            })
+        };
+        let client = Client::builder()
+            .with_connector(DynConnector::new(connector))
+            .with_endpoint(server.base_url().parse().unwrap())
+            .build()
+            .unwrap();
+
+        let inner = tower::service_fn(|_: LambdaInvocation| async {
+            let req = http::Request::post("/2018-06-01/runtime/invocation/id/response")
+                .body(Body::from("{}"))
+                .unwrap();
+            Ok::<_, BoxError>(req)
+        });
+        let retry = RetryPolicy::new().with_base_delay(Duration::from_millis(1));
+        let mut service = RuntimeApiClientService::new(inner, Arc::new(client), Default::default(), retry);
+
+        let (sender, sent) = oneshot::channel();
+        let (mut parts, _) = http::Response::new(()).into_parts();
+        parts.extensions.insert(ResponseSent::new(sender));
+        let invocation = LambdaInvocation {
+            parts,
+            body: Default::default(),
+            context: Context::default(),
+        };
+        let call = tokio::spawn(service.call(invocation));
+
+        tokio::time::timeout(Duration::from_millis(250), sent)
+            .await
+            .expect("response wasn't sent before the Runtime API acknowledged it")
+            .unwrap_err();
+        assert_eq!(2, connections.load(Ordering::SeqCst));
+        assert!(!call.is_finished());
+        call.await.unwrap().unwrap();
+        response.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn retry_responses_after_transport_errors() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap()).parse().unwrap();
+        let server = tokio::spawn(async move {
+            // The first connection is closed before the Runtime API responds.
+            let (stream, _) = listener.accept().await.unwrap();
+            drop(stream);
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 1024];
+            let read = stream.read(&mut request).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 202 Accepted\r\ncontent-length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&request[..read]).into_owned()
+        });
+        let client = Arc::new(Client::builder().with_endpoint(base).build().unwrap());
+
+        let inner = tower::service_fn(|_: LambdaInvocation| async {
+            let req = http::Request::post("/2018-06-01/runtime/invocation/id/response")
+                .body(Body::from("{}"))
+                .unwrap();
+            Ok::<_, BoxError>(req)
+        });
+        let retry = RetryPolicy::new().with_base_delay(Duration::from_millis(1));
+        let mut service = RuntimeApiClientService::new(inner, client, Default::default(), retry);
+        let (parts, _) = http::Response::new(()).into_parts();
+        let invocation = LambdaInvocation {
+            parts,
+            body: Default::default(),
+            context: Context::default(),
+        };
+        service.call(invocation).await.unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /2018-06-01/runtime/invocation/id/response "));
+    }
+}
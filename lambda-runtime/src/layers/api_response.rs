@@ -1,14 +1,29 @@
 use crate::{
-    deserializer,
+    codec::{Deserializer, Json, Serializer},
+    diagnostic::ErrorReportHook,
+    layers::{Failure, FailureDestination},
+    observer::Observers,
     requests::{EventCompletionRequest, IntoRequest},
     runtime::LambdaInvocation,
-    Diagnostic, EventErrorRequest, IntoFunctionResponse, LambdaEvent,
+    short_circuit::ShortCircuit,
+    BackgroundTasks, Context, ErrorReport, EventErrorRequest, FunctionResponse, IntoDiagnostic, IntoFunctionResponse,
+    LambdaEvent,
 };
-use futures::{ready, Stream};
+use bytes::{Bytes, BytesMut};
+use futures::{future::BoxFuture, ready, FutureExt, Stream};
 use lambda_runtime_api_client::{body::Body, BoxError};
 use pin_project::pin_project;
-use serde::{Deserialize, Serialize};
-use std::{fmt::Debug, future::Future, marker::PhantomData, pin::Pin, task};
+use std::{
+    fmt::Debug,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task,
+    time::{Duration, Instant, SystemTime},
+};
+use tokio::time::{sleep, Sleep};
+use tokio_util::sync::CancellationToken;
 use tower::Service;
 use tracing::{error, trace};
 
@@ -28,8 +43,17 @@ pub struct RuntimeApiResponseService<
     StreamingResponse,
     StreamItem,
     StreamError,
+    C = Json,
 > {
     inner: S,
+    codec: C,
+    // Serialization buffer of the buffered responses, reused across invocations.
+    buffer: Arc<Mutex<BytesMut>>,
+    observers: Observers,
+    margins: DeadlineMargins,
+    short_circuits: Arc<[ShortCircuit]>,
+    failure_destination: Option<FailureDestination>,
+    error_report_hook: Option<ErrorReportHook>,
     _phantom: PhantomData<(
         EventPayload,
         Response,
@@ -40,18 +64,48 @@ pub struct RuntimeApiResponseService<
     )>,
 }
 
-impl<S, EventPayload, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError>
-    RuntimeApiResponseService<S, EventPayload, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError>
+impl<S, EventPayload, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError, C>
+    RuntimeApiResponseService<
+        S,
+        EventPayload,
+        Response,
+        BufferedResponse,
+        StreamingResponse,
+        StreamItem,
+        StreamError,
+        C,
+    >
 {
-    pub fn new(inner: S) -> Self {
+    pub fn new(
+        inner: S,
+        codec: C,
+        observers: Observers,
+        margins: DeadlineMargins,
+        short_circuits: Arc<[ShortCircuit]>,
+        failure_destination: Option<FailureDestination>,
+    ) -> Self {
         Self {
             inner,
+            codec,
+            buffer: Default::default(),
+            observers,
+            margins,
+            short_circuits,
+            failure_destination,
+            error_report_hook: None,
             _phantom: PhantomData,
         }
     }
+
+    /// Rewrite the [ErrorReport] of the payloads that can't be deserialized with `hook`, like the
+    /// `CatchPanicService` does for the errors of the handler.
+    pub(crate) fn with_error_report_hook(mut self, hook: Option<ErrorReportHook>) -> Self {
+        self.error_report_hook = hook;
+        self
+    }
 }
 
-impl<S, EventPayload, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError> Clone
+impl<S, EventPayload, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError, C> Clone
     for RuntimeApiResponseService<
         S,
         EventPayload,
@@ -60,19 +114,30 @@ impl<S, EventPayload, Response, BufferedResponse, StreamingResponse, StreamItem,
         StreamingResponse,
         StreamItem,
         StreamError,
+        C,
     >
 where
     S: Clone,
+    C: Clone,
 {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            codec: self.codec.clone(),
+            // Clones run invocations concurrently, so they don't share the buffer.
+            buffer: Default::default(),
+            observers: self.observers.clone(),
+            margins: self.margins,
+            short_circuits: self.short_circuits.clone(),
+            failure_destination: self.failure_destination.clone(),
+            error_report_hook: self.error_report_hook.clone(),
             _phantom: PhantomData,
         }
     }
 }
 
-impl<S, EventPayload, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError> Service<LambdaInvocation>
+impl<S, EventPayload, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError, C>
+    Service<LambdaInvocation>
     for RuntimeApiResponseService<
         S,
         EventPayload,
@@ -81,12 +146,12 @@ impl<S, EventPayload, Response, BufferedResponse, StreamingResponse, StreamItem,
         StreamingResponse,
         StreamItem,
         StreamError,
+        C,
     >
 where
-    S: Service<LambdaEvent<EventPayload>, Response = Response, Error = Diagnostic>,
-    EventPayload: for<'de> Deserialize<'de>,
+    S: Service<LambdaEvent<EventPayload>, Response = Response, Error = ErrorReport>,
+    C: Deserializer<EventPayload> + Serializer<BufferedResponse> + Clone,
     Response: IntoFunctionResponse<BufferedResponse, StreamingResponse>,
-    BufferedResponse: Serialize,
     StreamingResponse: Stream<Item = Result<StreamItem, StreamError>> + Unpin + Send + 'static,
     StreamItem: Into<bytes::Bytes> + Send,
     StreamError: Into<BoxError> + Send + Debug,
@@ -94,7 +159,7 @@ where
     type Response = http::Request<Body>;
     type Error = BoxError;
     type Future =
-        RuntimeApiResponseFuture<S::Future, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError>;
+        RuntimeApiResponseFuture<S::Future, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError, C>;
 
     fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
         self.inner
@@ -122,11 +187,48 @@ where
             return RuntimeApiResponseFuture::Ready(Box::new(Some(Err(err))));
         };
 
+        if let Some(response) = self
+            .short_circuits
+            .iter()
+            .find_map(|short_circuit| short_circuit.respond(&req.body, &req.context))
+        {
+            trace!("invocation short-circuited, the handler will not be called");
+            let response = FunctionResponse::<_, Body>::BufferedResponse(response);
+            let request = EventCompletionRequest::new(&req.context.request_id, response).into_req_with(Ok);
+            return RuntimeApiResponseFuture::Ready(Box::new(Some(request)));
+        }
+
+        let observed = (!self.observers.is_empty()).then(|| {
+            self.observers.event_received(&req.context);
+            Box::new(Observed {
+                observers: self.observers.clone(),
+                context: req.context.clone(),
+                start: Instant::now(),
+            })
+        });
+
         let request_id = req.context.request_id.clone();
-        let lambda_event = match deserializer::deserialize::<EventPayload>(&req.body, req.context) {
-            Ok(lambda_event) => lambda_event,
-            Err(err) => match build_event_error_request(&request_id, err) {
-                Ok(request) => return RuntimeApiResponseFuture::Ready(Box::new(Some(Ok(request)))),
+        let deadline = InvocationDeadline::new(&req.context, self.margins);
+        // Bytes are reference counted, so keeping the payload for the destination doesn't copy it.
+        let failed = self
+            .failure_destination
+            .clone()
+            .map(|destination| Box::new((destination, req.body.clone(), req.context.clone())));
+        let LambdaInvocation {
+            mut parts,
+            body,
+            context,
+        } = req;
+        let lambda_event = match self.codec.deserialize_invocation(body, &mut parts) {
+            Ok(payload) => LambdaEvent::new(payload, context),
+            Err(err) => match build_event_error_request(&request_id, err, self.error_report_hook.as_ref(), observed) {
+                Ok((request, report)) => {
+                    let Some(failed) = failed else {
+                        return RuntimeApiResponseFuture::Ready(Box::new(Some(Ok(request))));
+                    };
+                    let send = send_failure(failed, report);
+                    return RuntimeApiResponseFuture::Draining(send, Box::new(Some(Ok(request))));
+                }
                 Err(err) => {
                     error!(error = ?err, "failed to build error response for Lambda Runtime API");
                     return RuntimeApiResponseFuture::Ready(Box::new(Some(Err(err))));
@@ -137,23 +239,142 @@ where
         // Once the handler input has been generated successfully, pass it through to inner services
         // allowing processing both before reaching the handler function and after the handler completes.
         let fut = self.inner.call(lambda_event);
-        RuntimeApiResponseFuture::Future(fut, request_id, PhantomData)
+        RuntimeApiResponseFuture::Future(
+            fut,
+            request_id,
+            self.codec.clone(),
+            self.buffer.clone(),
+            observed,
+            Some(deadline),
+            failed,
+            PhantomData,
+        )
     }
 }
 
-fn build_event_error_request<T>(request_id: &str, err: T) -> Result<http::Request<Body>, BoxError>
+fn build_event_error_request<T>(
+    request_id: &str,
+    err: T,
+    hook: Option<&ErrorReportHook>,
+    observed: Option<Box<Observed>>,
+) -> Result<(http::Request<Body>, ErrorReport), BoxError>
 where
-    T: Into<Diagnostic> + Debug,
+    T: IntoDiagnostic + Debug,
 {
     error!(error = ?err, "Request payload deserialization into LambdaEvent<T> failed. The handler will not be called. Log at TRACE level to see the payload.");
-    EventErrorRequest::new(request_id, err).into_req()
+    let mut report = err.into_error_report();
+    if let Some(hook) = hook {
+        report = hook(report);
+    }
+    if let Some(observed) = observed {
+        observed.observers.error(&observed.context, &report);
+    }
+    let request = EventErrorRequest::new(request_id, report.clone()).into_req()?;
+    Ok((request, report))
+}
+
+/// Failed invocation that is waiting for its error to be handed to the [FailureDestination].
+type Failed = Box<(FailureDestination, Bytes, Context)>;
+
+fn send_failure(failed: Failed, error: ErrorReport) -> BoxFuture<'static, ()> {
+    let (destination, payload, context) = *failed;
+    destination.send(Failure {
+        payload,
+        context,
+        error,
+    })
+}
+
+/// How long before the deadline of an invocation the runtime cancels the
+/// [cancellation token](Context::cancellation_token) of the invocation, and stops waiting for
+/// its [BackgroundTasks].
+#[derive(Clone, Copy, Debug)]
+pub struct DeadlineMargins {
+    pub(crate) cancellation: Duration,
+    pub(crate) background_tasks: Duration,
+}
+
+impl DeadlineMargins {
+    pub(crate) const DEFAULT_CANCELLATION: Duration = Duration::from_millis(500);
+}
+
+impl Default for DeadlineMargins {
+    fn default() -> Self {
+        Self {
+            cancellation: Self::DEFAULT_CANCELLATION,
+            background_tasks: BackgroundTasks::DEFAULT_MARGIN,
+        }
+    }
+}
+
+/// Deadline of an invocation, which cancels the token of its context and bounds the time spent
+/// waiting for its background tasks.
+pub struct InvocationDeadline {
+    // Boxed so that the deadline can move to the future that drains the background tasks.
+    cancel: Option<Pin<Box<Sleep>>>,
+    token: CancellationToken,
+    tasks: BackgroundTasks,
+    until: Option<SystemTime>,
+}
+
+impl InvocationDeadline {
+    fn new(context: &Context, margins: DeadlineMargins) -> Self {
+        let remaining = context.remaining_time();
+        let cancel = remaining.map(|remaining| Box::pin(sleep(remaining.saturating_sub(margins.cancellation))));
+        Self {
+            cancel,
+            token: context.cancellation_token().clone(),
+            tasks: context.background_tasks().clone(),
+            until: remaining.map(|_| context.deadline() - margins.background_tasks),
+        }
+    }
+
+    /// Cancel the token of the invocation once its margin before the deadline is reached.
+    fn poll_cancel(&mut self, cx: &mut task::Context<'_>) {
+        if let Some(cancel) = &mut self.cancel {
+            if cancel.as_mut().poll(cx).is_ready() {
+                self.token.cancel();
+                self.cancel = None;
+            }
+        }
+    }
+
+    /// Await the background tasks, which can still observe the cancellation of the token.
+    async fn drain(mut self) {
+        let join = self.tasks.join(self.until);
+        match self.cancel.take() {
+            Some(cancel) => {
+                tokio::pin!(join);
+                tokio::select! {
+                    _ = &mut join => return,
+                    _ = cancel => self.token.cancel(),
+                }
+                join.await
+            }
+            None => join.await,
+        }
+    }
+}
+
+/// Invocation followed by the [Observers] of the runtime.
+pub struct Observed {
+    observers: Observers,
+    context: Context,
+    start: Instant,
 }
 
 #[pin_project(project = RuntimeApiResponseFutureProj)]
-pub enum RuntimeApiResponseFuture<F, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError> {
+pub enum RuntimeApiResponseFuture<F, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError, C> {
     Future(
         #[pin] F,
         String,
+        C,
+        Arc<Mutex<BytesMut>>,
+        // Boxed to keep the future small when no observer is registered.
+        Option<Box<Observed>>,
+        Option<InvocationDeadline>,
+        // Boxed to keep the future small when no failure destination is registered.
+        Option<Failed>,
         PhantomData<(
             (),
             Response,
@@ -167,14 +388,21 @@ pub enum RuntimeApiResponseFuture<F, Response, BufferedResponse, StreamingRespon
     /// We box it to avoid bloating the size of the more likely variant, which is
     /// the future that drives event processing.
     Ready(Box<Option<Result<http::Request<Body>, BoxError>>>),
+    /// This variant waits for the background tasks of the invocation, or for its failure
+    /// destination, before returning the request, since Lambda freezes the execution environment
+    /// once the response is posted.
+    Draining(
+        BoxFuture<'static, ()>,
+        Box<Option<Result<http::Request<Body>, BoxError>>>,
+    ),
 }
 
-impl<F, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError> Future
-    for RuntimeApiResponseFuture<F, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError>
+impl<F, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError, C> Future
+    for RuntimeApiResponseFuture<F, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError, C>
 where
-    F: Future<Output = Result<Response, Diagnostic>>,
+    F: Future<Output = Result<Response, ErrorReport>>,
+    C: Serializer<BufferedResponse>,
     Response: IntoFunctionResponse<BufferedResponse, StreamingResponse>,
-    BufferedResponse: Serialize,
     StreamingResponse: Stream<Item = Result<StreamItem, StreamError>> + Unpin + Send + 'static,
     StreamItem: Into<bytes::Bytes> + Send,
     StreamError: Into<BoxError> + Send + Debug,
@@ -182,12 +410,129 @@ where
     type Output = Result<http::Request<Body>, BoxError>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
-        task::Poll::Ready(match self.as_mut().project() {
-            RuntimeApiResponseFutureProj::Future(fut, request_id, _) => match ready!(fut.poll(cx)) {
-                Ok(ok) => EventCompletionRequest::new(request_id, ok).into_req(),
-                Err(err) => EventErrorRequest::new(request_id, err).into_req(),
-            },
-            RuntimeApiResponseFutureProj::Ready(ready) => ready.take().expect("future polled after completion"),
+        // NOTE: We loop here to directly poll the background tasks once the handler has finished.
+        task::Poll::Ready(loop {
+            match self.as_mut().project() {
+                RuntimeApiResponseFutureProj::Future(fut, request_id, codec, buffer, observed, deadline, failed, _) => {
+                    if let Some(deadline) = deadline {
+                        deadline.poll_cancel(cx);
+                    }
+                    let result = ready!(fut.poll(cx));
+                    if let Some(observed) = observed.take() {
+                        let Observed {
+                            observers,
+                            context,
+                            start,
+                        } = *observed;
+                        observers.handler_complete(&context, start.elapsed());
+                        if let Err(err) = &result {
+                            observers.error(&context, err);
+                        }
+                    }
+                    let (request, send) = match result {
+                        Ok(ok) => {
+                            let request = EventCompletionRequest::new(request_id, ok).into_req_with(|body| {
+                                let mut buffer = buffer.lock().expect("response buffer lock poisoned");
+                                codec.serialize_into(body, &mut buffer)
+                            });
+                            (request, None)
+                        }
+                        Err(err) => {
+                            let send = failed.take().map(|failed| send_failure(failed, err.clone()));
+                            (EventErrorRequest::new(request_id, err).into_req(), send)
+                        }
+                    };
+                    let deadline = deadline.take().expect("future polled after completion");
+                    let join = match send {
+                        None if deadline.tasks.is_empty() => break request,
+                        None => deadline.drain().boxed(),
+                        Some(send) => async move {
+                            send.await;
+                            deadline.drain().await
+                        }
+                        .boxed(),
+                    };
+                    self.set(RuntimeApiResponseFuture::Draining(join, Box::new(Some(request))));
+                }
+                RuntimeApiResponseFutureProj::Draining(join, request) => {
+                    ready!(join.as_mut().poll(cx));
+                    break request.take().expect("future polled after completion");
+                }
+                RuntimeApiResponseFutureProj::Ready(ready) => {
+                    break ready.take().expect("future polled after completion")
+                }
+            }
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+    use serde_json::Value;
+
+    #[tokio::test]
+    async fn cancel_the_invocation_before_its_deadline() {
+        let deadline = SystemTime::now() + Duration::from_millis(700);
+        let (parts, _) = http::Response::new(()).into_parts();
+        let invocation = LambdaInvocation {
+            parts,
+            body: Bytes::from_static(b"{}"),
+            context: Context {
+                deadline: deadline.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis() as u64,
+                ..Context::default()
+            },
+        };
+
+        let handler = tower::service_fn(|event: LambdaEvent<Value>| async move {
+            event.context.cancellation_token().cancelled().await;
+            Ok::<_, ErrorReport>(Value::from("partial"))
+        });
+        let mut service = RuntimeApiResponseService::new(
+            handler,
+            Json,
+            Default::default(),
+            DeadlineMargins::default(),
+            Default::default(),
+            None,
+        );
+
+        let request = tokio::time::timeout(Duration::from_millis(600), service.call(invocation))
+            .await
+            .expect("invocation wasn't cancelled before the margin")
+            .unwrap();
+        let body = request.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(Bytes::from_static(b"\"partial\""), body);
+    }
+
+    #[tokio::test]
+    async fn apply_the_error_report_hook_to_deserialization_errors() {
+        let (parts, _) = http::Response::new(()).into_parts();
+        let invocation = LambdaInvocation {
+            parts,
+            body: Bytes::from_static(b"not json"),
+            context: Context::default(),
+        };
+
+        let handler = tower::service_fn(|event: LambdaEvent<Value>| async move { Ok::<_, ErrorReport>(event.payload) });
+        let hook: ErrorReportHook = Arc::new(|mut report: ErrorReport| {
+            report.error_type = format!("Orders.{}", report.error_type);
+            report
+        });
+        let mut service = RuntimeApiResponseService::new(
+            handler,
+            Json,
+            Default::default(),
+            DeadlineMargins::default(),
+            Default::default(),
+            None,
+        )
+        .with_error_report_hook(Some(hook));
+
+        let request = service.call(invocation).await.unwrap();
+        let body = request.into_body().collect().await.unwrap().to_bytes();
+        let report: Value = serde_json::from_slice(&body).unwrap();
+        assert!(report["errorType"].as_str().unwrap().starts_with("Orders."));
+    }
+}
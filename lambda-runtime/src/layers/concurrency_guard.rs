@@ -0,0 +1,273 @@
+use crate::{Context, Diagnostic, ErrorReport, IntoDiagnostic, LambdaEvent};
+use futures::future::BoxFuture;
+use std::{fmt, future::Future, sync::Arc, task, time::Duration};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tower::{Layer, Service};
+use tracing::warn;
+
+/// Semaphore shared by all the invocations of an execution environment, to protect clients or
+/// resources that can't be used by several invocations at the same time.
+///
+/// Execution environments that run several invocations concurrently, with
+/// [Runtime::run_concurrent](crate::Runtime::run_concurrent), run the handler from several
+/// tasks. Sections of the handler that use a resource that isn't thread-safe, or that only
+/// supports a few connections, run under a permit of the guard. Unlike tower's
+/// `ConcurrencyLimitLayer`, invocations only wait for a permit until their deadline, and then
+/// fail with [GuardTimeout], instead of being stopped by Lambda without an error.
+///
+/// ```no_run
+/// use lambda_runtime::{layers::ConcurrencyGuard, service_fn, Error, LambdaEvent, Runtime};
+/// use serde_json::Value;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let guard = ConcurrencyGuard::new(1);
+///     Runtime::new(service_fn(move |event: LambdaEvent<Value>| {
+///         let guard = guard.clone();
+///         async move {
+///             let _permit = guard.acquire(&event.context).await?;
+///             // Only one invocation uses the legacy client at a time.
+///             Ok::<_, Error>(event.payload)
+///         }
+///     }))
+///     .run()
+///     .await
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ConcurrencyGuard {
+    semaphore: Arc<Semaphore>,
+    permits: usize,
+}
+
+impl ConcurrencyGuard {
+    /// Create a guard that lets `permits` invocations in at the same time.
+    pub fn new(permits: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(permits)),
+            permits,
+        }
+    }
+
+    /// Wait for a permit, until the deadline of the invocation. The permit is released when
+    /// it's dropped.
+    pub async fn acquire(&self, context: &Context) -> Result<ConcurrencyPermit, GuardTimeout> {
+        let acquire = self.semaphore.clone().acquire_owned();
+        let permit = match context.remaining_time() {
+            None => acquire.await,
+            Some(remaining) => tokio::time::timeout(remaining, acquire)
+                .await
+                .map_err(|_| GuardTimeout {
+                    permits: self.permits,
+                    waited: remaining,
+                })?,
+        };
+        Ok(ConcurrencyPermit {
+            _permit: permit.expect("semaphore is never closed"),
+        })
+    }
+
+    /// Run `section` under a permit of the guard.
+    pub async fn run<F: Future>(&self, context: &Context, section: F) -> Result<F::Output, GuardTimeout> {
+        let _permit = self.acquire(context).await?;
+        Ok(section.await)
+    }
+
+    /// Number of permits available right now.
+    pub fn available_permits(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    /// Create a layer that runs the whole handler under a permit of this guard.
+    pub fn layer(&self) -> ConcurrencyGuardLayer {
+        ConcurrencyGuardLayer { guard: self.clone() }
+    }
+}
+
+impl fmt::Debug for ConcurrencyGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConcurrencyGuard")
+            .field("permits", &self.permits)
+            .field("available_permits", &self.available_permits())
+            .finish()
+    }
+}
+
+/// Permit of a [ConcurrencyGuard], released when it's dropped.
+#[derive(Debug)]
+pub struct ConcurrencyPermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Error returned when no permit of a [ConcurrencyGuard] is available before the deadline of
+/// the invocation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuardTimeout {
+    permits: usize,
+    waited: Duration,
+}
+
+impl GuardTimeout {
+    /// How long the invocation waited for a permit.
+    pub fn waited(&self) -> Duration {
+        self.waited
+    }
+}
+
+impl fmt::Display for GuardTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "none of the {} permits of the concurrency guard was released within {}ms, before the invocation deadline",
+            self.permits,
+            self.waited.as_millis()
+        )
+    }
+}
+
+impl std::error::Error for GuardTimeout {}
+
+impl From<GuardTimeout> for Diagnostic {
+    fn from(value: GuardTimeout) -> Self {
+        Diagnostic {
+            error_type: "GuardTimeout".into(),
+            error_message: value.to_string(),
+        }
+    }
+}
+
+/// Tower layer that runs the handler under a permit of a [ConcurrencyGuard], created with
+/// [ConcurrencyGuard::layer].
+#[derive(Clone, Debug)]
+pub struct ConcurrencyGuardLayer {
+    guard: ConcurrencyGuard,
+}
+
+impl<S> Layer<S> for ConcurrencyGuardLayer {
+    type Service = ConcurrencyGuardService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConcurrencyGuardService {
+            inner,
+            guard: self.guard.clone(),
+        }
+    }
+}
+
+/// Tower service returned by [ConcurrencyGuardLayer].
+#[derive(Clone, Debug)]
+pub struct ConcurrencyGuardService<S> {
+    inner: S,
+    guard: ConcurrencyGuard,
+}
+
+impl<S, Payload> Service<LambdaEvent<Payload>> for ConcurrencyGuardService<S>
+where
+    S: Service<LambdaEvent<Payload>> + Clone + Send + 'static,
+    S::Error: IntoDiagnostic,
+    S::Future: Send,
+    Payload: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = ErrorReport;
+    type Future = BoxFuture<'static, Result<S::Response, ErrorReport>>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(IntoDiagnostic::into_error_report)
+    }
+
+    fn call(&mut self, req: LambdaEvent<Payload>) -> Self::Future {
+        // The permit is acquired in the future, with the deadline of the invocation, so the
+        // ready service is taken and replaced with a clone.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let guard = self.guard.clone();
+        Box::pin(async move {
+            let _permit = match guard.acquire(&req.context).await {
+                Ok(permit) => permit,
+                Err(error) => {
+                    warn!(request_id = %req.context.request_id, "{error}");
+                    return Err(error.into_error_report());
+                }
+            };
+            inner.call(req).await.map_err(IntoDiagnostic::into_error_report)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{service_fn, Error};
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::SystemTime,
+    };
+    use tower::ServiceExt;
+
+    fn event_with_deadline(remaining: Duration) -> LambdaEvent<()> {
+        let deadline = SystemTime::now() + remaining;
+        let context = Context {
+            deadline: deadline.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis() as u64,
+            ..Default::default()
+        };
+        LambdaEvent::new((), context)
+    }
+
+    #[tokio::test]
+    async fn guard_limits_concurrent_sections() {
+        let guard = ConcurrencyGuard::new(2);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let sections = (0..6).map(|_| {
+            let (running, peak) = (running.clone(), peak.clone());
+            let guard = guard.clone();
+            async move {
+                guard
+                    .run(&Context::default(), async {
+                        peak.fetch_max(running.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(10)).await;
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    })
+                    .await
+            }
+        });
+        for result in futures::future::join_all(sections).await {
+            result.unwrap();
+        }
+        assert_eq!(2, peak.load(Ordering::SeqCst));
+        assert_eq!(2, guard.available_permits());
+    }
+
+    #[tokio::test]
+    async fn waiting_stops_at_the_deadline() {
+        let guard = ConcurrencyGuard::new(1);
+        let _held = guard.acquire(&Context::default()).await.unwrap();
+
+        let service = guard
+            .layer()
+            .layer(service_fn(|_: LambdaEvent<()>| async { Ok::<_, Error>("done") }));
+        let report = service
+            .oneshot(event_with_deadline(Duration::from_millis(20)))
+            .await
+            .unwrap_err();
+        assert_eq!("GuardTimeout", report.error_type);
+    }
+
+    #[tokio::test]
+    async fn layer_runs_the_handler_under_a_permit() {
+        let guard = ConcurrencyGuard::new(1);
+        let service = guard.layer().layer(service_fn({
+            let guard = guard.clone();
+            move |_: LambdaEvent<()>| {
+                let available = guard.available_permits();
+                async move { Ok::<_, Error>(available) }
+            }
+        }));
+        let available = service
+            .oneshot(event_with_deadline(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        assert_eq!(0, available);
+    }
+}
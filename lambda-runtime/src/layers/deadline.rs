@@ -0,0 +1,237 @@
+use crate::{Diagnostic, ErrorReport, IntoDiagnostic, LambdaEvent};
+use pin_project::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task,
+    time::{Duration, SystemTime},
+};
+use tokio::time::{sleep, Sleep};
+use tower::{Layer, Service};
+use tracing::error;
+
+/// Tower layer that fails invocations shortly before their deadline.
+///
+/// When a function reaches its timeout, Lambda stops the execution environment without
+/// recording any error for the invocation. This layer races the handler against the deadline
+/// in [Context::deadline](crate::Context::deadline), minus a safety margin, and reports a
+/// [DeadlineExceeded] error to the Runtime API when the handler doesn't finish in time.
+///
+/// ```no_run
+/// use lambda_runtime::{layers::DeadlineLayer, Error, LambdaEvent, Runtime};
+/// use serde_json::Value;
+/// use std::time::Duration;
+/// use tower::service_fn;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     Runtime::builder()
+///         .layer(DeadlineLayer::new().with_margin(Duration::from_millis(250)))
+///         .build(service_fn(func))
+///         .run()
+///         .await
+/// }
+///
+/// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+///     Ok(event.payload)
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct DeadlineLayer {
+    margin: Duration,
+}
+
+impl DeadlineLayer {
+    /// Margin used by [DeadlineLayer::new].
+    pub const DEFAULT_MARGIN: Duration = Duration::from_millis(100);
+
+    /// Create a layer that fails invocations [DeadlineLayer::DEFAULT_MARGIN] before their deadline.
+    pub fn new() -> Self {
+        Self {
+            margin: Self::DEFAULT_MARGIN,
+        }
+    }
+
+    /// Set how long before the deadline invocations fail, to leave time to report the error.
+    pub fn with_margin(mut self, margin: Duration) -> Self {
+        self.margin = margin;
+        self
+    }
+}
+
+impl Default for DeadlineLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for DeadlineLayer {
+    type Service = DeadlineService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DeadlineService {
+            inner,
+            margin: self.margin,
+        }
+    }
+}
+
+/// Tower service returned by [DeadlineLayer].
+#[derive(Clone, Debug)]
+pub struct DeadlineService<S> {
+    inner: S,
+    margin: Duration,
+}
+
+impl<S, Payload> Service<LambdaEvent<Payload>> for DeadlineService<S>
+where
+    S: Service<LambdaEvent<Payload>>,
+    S::Error: IntoDiagnostic,
+{
+    type Response = S::Response;
+    type Error = ErrorReport;
+    type Future = DeadlineFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(IntoDiagnostic::into_error_report)
+    }
+
+    fn call(&mut self, req: LambdaEvent<Payload>) -> Self::Future {
+        let (sleep, deadline) = match req.context.remaining_time() {
+            None => (None, None),
+            Some(remaining) => (
+                Some(sleep(remaining.saturating_sub(self.margin))),
+                Some(req.context.deadline()),
+            ),
+        };
+
+        DeadlineFuture {
+            inner: self.inner.call(req),
+            sleep,
+            deadline,
+            margin: self.margin,
+        }
+    }
+}
+
+/// Future returned by [DeadlineService].
+#[pin_project]
+pub struct DeadlineFuture<F> {
+    #[pin]
+    inner: F,
+    #[pin]
+    sleep: Option<Sleep>,
+    deadline: Option<SystemTime>,
+    margin: Duration,
+}
+
+impl<F, T, E> Future for DeadlineFuture<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: IntoDiagnostic,
+{
+    type Output = Result<T, ErrorReport>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = self.project();
+        if let task::Poll::Ready(result) = this.inner.poll(cx) {
+            return task::Poll::Ready(result.map_err(IntoDiagnostic::into_error_report));
+        }
+
+        if let Some(sleep) = this.sleep.as_pin_mut() {
+            if sleep.poll(cx).is_ready() {
+                let error = DeadlineExceeded { margin: *this.margin };
+                error!(deadline = ?this.deadline, "{error}");
+                return task::Poll::Ready(Err(error.into_error_report()));
+            }
+        }
+        task::Poll::Pending
+    }
+}
+
+/// Error reported when the handler doesn't finish before the deadline of the invocation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeadlineExceeded {
+    margin: Duration,
+}
+
+impl DeadlineExceeded {
+    /// How long before the deadline the invocation was stopped.
+    pub fn margin(&self) -> Duration {
+        self.margin
+    }
+}
+
+impl fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "handler did not complete within {}ms of the invocation deadline",
+            self.margin.as_millis()
+        )
+    }
+}
+
+impl std::error::Error for DeadlineExceeded {}
+
+impl From<DeadlineExceeded> for Diagnostic {
+    fn from(value: DeadlineExceeded) -> Self {
+        Diagnostic {
+            error_type: "DeadlineExceeded".into(),
+            error_message: value.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{service_fn, Context, Error};
+
+    fn event_with_deadline(remaining: Duration) -> LambdaEvent<()> {
+        let deadline = SystemTime::now() + remaining;
+        let context = Context {
+            deadline: deadline.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis() as u64,
+            ..Default::default()
+        };
+        LambdaEvent::new((), context)
+    }
+
+    async fn slow(_event: LambdaEvent<()>) -> Result<&'static str, Error> {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        Ok("done")
+    }
+
+    async fn fast(_event: LambdaEvent<()>) -> Result<&'static str, Error> {
+        Ok("done")
+    }
+
+    #[tokio::test]
+    async fn deadline_exceeded() {
+        let mut service = DeadlineLayer::new()
+            .with_margin(Duration::from_millis(50))
+            .layer(service_fn(slow));
+
+        let diagnostic = service
+            .call(event_with_deadline(Duration::from_millis(100)))
+            .await
+            .unwrap_err();
+        assert_eq!("DeadlineExceeded", diagnostic.error_type);
+        assert_eq!(
+            "handler did not complete within 50ms of the invocation deadline",
+            diagnostic.error_message
+        );
+    }
+
+    #[tokio::test]
+    async fn deadline_not_reached() {
+        let mut service = DeadlineLayer::new().layer(service_fn(fast));
+        let response = service.call(event_with_deadline(Duration::from_secs(5))).await.unwrap();
+        assert_eq!("done", response);
+
+        // Contexts without a deadline never time out.
+        let response = service.call(LambdaEvent::new((), Context::default())).await.unwrap();
+        assert_eq!("done", response);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{Context, Error, ErrorReport};
+use bytes::Bytes;
+use futures::{future::BoxFuture, FutureExt};
+use std::{fmt, future::Future, sync::Arc, time::Duration};
+use tracing::{error, warn};
+
+/// Failed invocation handed to a [FailureDestination].
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub struct Failure {
+    /// Raw payload of the invocation, as received from the Runtime API.
+    pub payload: Bytes,
+    /// Context of the invocation.
+    pub context: Context,
+    /// Error reported to the Runtime API for the invocation.
+    pub error: ErrorReport,
+}
+
+/// Callback that receives the failed invocations before their error is reported to the Runtime
+/// API, to push them to a dead-letter queue or raise an alert.
+///
+/// Register the destination with
+/// [RuntimeBuilder::on_failure](crate::RuntimeBuilder::on_failure). It's called when the payload
+/// of an invocation can't be decoded, or when the handler returns an error or panics. The
+/// callback runs within a budget that ends [FailureDestination::DEFAULT_MARGIN] before the
+/// deadline of the invocation, and is abandoned when it doesn't complete in time, so that the
+/// error is still reported.
+///
+/// ```no_run
+/// use lambda_runtime::{layers::{Failure, FailureDestination}, service_fn, Error, LambdaEvent, Runtime};
+/// use serde_json::Value;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     Runtime::builder()
+///         .on_failure(FailureDestination::new(|failure: Failure| async move {
+///             tracing::error!(request_id = %failure.context.request_id, error = %failure.error, "sending to the DLQ");
+///             Ok(())
+///         }))
+///         .build(service_fn(func))
+///         .run()
+///         .await
+/// }
+///
+/// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+///     Ok(event.payload)
+/// }
+/// ```
+#[derive(Clone)]
+pub struct FailureDestination {
+    send: Arc<SendFailure>,
+    margin: Duration,
+    timeout: Option<Duration>,
+}
+
+type SendFailure = dyn Fn(Failure) -> BoxFuture<'static, Result<(), Error>> + Send + Sync;
+
+impl FailureDestination {
+    /// Time left before the deadline of an invocation when the runtime stops waiting for the
+    /// destination, to leave time to report the error.
+    pub const DEFAULT_MARGIN: Duration = Duration::from_millis(200);
+
+    /// Create a destination that hands the failed invocations to `send`.
+    pub fn new<F, Fut>(send: F) -> Self
+    where
+        F: Fn(Failure) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        Self {
+            send: Arc::new(move |failure| send(failure).boxed()),
+            margin: Self::DEFAULT_MARGIN,
+            timeout: None,
+        }
+    }
+
+    /// Set how long before the deadline of the invocation the runtime stops waiting for the
+    /// destination.
+    pub fn with_margin(mut self, margin: Duration) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Limit the time spent waiting for the destination, even when the deadline of the
+    /// invocation is further away.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Hand the failure to the destination, within the budget of the invocation.
+    pub(crate) fn send(&self, failure: Failure) -> BoxFuture<'static, ()> {
+        let until_deadline = failure
+            .context
+            .remaining_time()
+            .map(|remaining| remaining.saturating_sub(self.margin));
+        let budget = match (until_deadline, self.timeout) {
+            (Some(deadline), Some(timeout)) => Some(deadline.min(timeout)),
+            (deadline, timeout) => deadline.or(timeout),
+        };
+
+        let send = (self.send)(failure);
+        async move {
+            let result = match budget {
+                Some(budget) => match tokio::time::timeout(budget, send).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        warn!("failure destination did not complete before the invocation deadline");
+                        return;
+                    }
+                },
+                None => send.await,
+            };
+            if let Err(err) = result {
+                error!(error = %err, "failure destination returned an error");
+            }
+        }
+        .boxed()
+    }
+}
+
+impl fmt::Debug for FailureDestination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FailureDestination")
+            .field("margin", &self.margin)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{codec::Json, layers::RuntimeApiResponseService, LambdaEvent, LambdaInvocation};
+    use serde_json::Value;
+    use std::sync::Mutex;
+    use tower::Service;
+
+    fn invocation(body: &'static str) -> LambdaInvocation {
+        let (parts, _) = http::Response::new(()).into_parts();
+        LambdaInvocation {
+            parts,
+            body: Bytes::from_static(body.as_bytes()),
+            context: Context {
+                request_id: "id".into(),
+                ..Context::default()
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn failures_are_sent_before_the_error_is_reported() {
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        let destination = FailureDestination::new({
+            let failures = failures.clone();
+            move |failure: Failure| {
+                let failures = failures.clone();
+                async move {
+                    failures.lock().unwrap().push(failure);
+                    Ok(())
+                }
+            }
+        });
+        let handler = tower::service_fn(|event: LambdaEvent<Value>| async move {
+            Err::<Value, _>(ErrorReport::new("OrderRejected", event.payload.to_string()))
+        });
+        let mut service = RuntimeApiResponseService::new(
+            handler,
+            Json,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Some(destination),
+        );
+
+        let req = service.call(invocation(r#"{"orderId":42}"#)).await.unwrap();
+        assert_eq!("/2018-06-01/runtime/invocation/id/error", req.uri().path());
+        service.call(invocation("not json")).await.unwrap();
+
+        let failures = failures.lock().unwrap();
+        assert_eq!(2, failures.len());
+        assert_eq!(Bytes::from_static(br#"{"orderId":42}"#), failures[0].payload);
+        assert_eq!("OrderRejected", failures[0].error.error_type);
+        assert_eq!("id", failures[0].context.request_id);
+        assert_eq!(Bytes::from_static(b"not json"), failures[1].payload);
+    }
+
+    #[tokio::test]
+    async fn failure_destination_is_abandoned_after_its_timeout() {
+        let destination = FailureDestination::new(|_| async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        })
+        .with_timeout(Duration::from_millis(50));
+        let failure = Failure {
+            payload: Bytes::new(),
+            context: Context::default(),
+            error: ErrorReport::new("Error", "failed"),
+        };
+
+        tokio::time::timeout(Duration::from_secs(1), destination.send(failure))
+            .await
+            .expect("failure destination wasn't abandoned after its timeout");
+    }
+}
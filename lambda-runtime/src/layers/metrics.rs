@@ -0,0 +1,280 @@
+use crate::{Context, ErrorReport, IntoDiagnostic, LambdaEvent};
+pub use aws_lambda_events::cloudwatch_metrics::MetricUnit;
+use aws_lambda_events::cloudwatch_metrics::{EmfError, EmfLog, EmfLogBuilder};
+use pin_project::pin_project;
+use serde_json::{Map, Value};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task,
+    time::Instant,
+};
+use tower::{Layer, Service};
+use tracing::warn;
+
+/// Tower layer that prints metrics of each invocation to stdout in the CloudWatch
+/// [Embedded Metric Format](https://docs.aws.amazon.com/AmazonCloudWatch/latest/monitoring/CloudWatch_Embedded_Metric_Format_Specification.html),
+/// so CloudWatch records them without calls to its API.
+///
+/// Every invocation records `Invocations`, `Duration`, `Errors`, and `ColdStart` in the namespace of
+/// the layer, with the `FunctionName` dimension. Handlers add their own metrics to the same log
+/// with [MetricsContextExt::metrics].
+///
+/// ```no_run
+/// use lambda_runtime::{
+///     layers::{MetricUnit, MetricsContextExt, MetricsLayer},
+///     Error, LambdaEvent, Runtime,
+/// };
+/// use serde_json::Value;
+/// use tower::service_fn;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     Runtime::builder()
+///         .layer(MetricsLayer::new("orders"))
+///         .build(service_fn(func))
+///         .run()
+///         .await
+/// }
+///
+/// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+///     if let Some(metrics) = event.context.metrics() {
+///         metrics.add_metric("ItemsOrdered", 3.0, MetricUnit::Count);
+///     }
+///     Ok(event.payload)
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MetricsLayer {
+    namespace: String,
+    dimensions: Vec<(String, String)>,
+}
+
+impl MetricsLayer {
+    /// Create a layer that records metrics in `namespace`.
+    pub fn new(namespace: impl Into<String>) -> Self {
+        Self {
+            namespace: namespace.into(),
+            dimensions: Vec::new(),
+        }
+    }
+
+    /// Add a dimension to the metrics, besides `FunctionName`.
+    pub fn with_dimension(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.dimensions.push((name.into(), value.into()));
+        self
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetricsService {
+            inner,
+            config: Arc::new(self.clone()),
+        }
+    }
+}
+
+/// Tower service returned by [MetricsLayer].
+#[derive(Clone, Debug)]
+pub struct MetricsService<S> {
+    inner: S,
+    config: Arc<MetricsLayer>,
+}
+
+impl<S, Payload> Service<LambdaEvent<Payload>> for MetricsService<S>
+where
+    S: Service<LambdaEvent<Payload>>,
+    S::Error: IntoDiagnostic,
+{
+    type Response = S::Response;
+    type Error = ErrorReport;
+    type Future = MetricsFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(IntoDiagnostic::into_error_report)
+    }
+
+    fn call(&mut self, mut req: LambdaEvent<Payload>) -> Self::Future {
+        let metrics = Metrics::default();
+        req.context.handles.metrics = Some(metrics.clone());
+        let context = req.context.clone();
+
+        MetricsFuture {
+            inner: self.inner.call(req),
+            config: self.config.clone(),
+            context,
+            metrics,
+            start: Instant::now(),
+        }
+    }
+}
+
+/// Future returned by [MetricsService].
+#[pin_project]
+pub struct MetricsFuture<F> {
+    #[pin]
+    inner: F,
+    config: Arc<MetricsLayer>,
+    context: Context,
+    metrics: Metrics,
+    start: Instant,
+}
+
+impl<F, T, E> Future for MetricsFuture<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: IntoDiagnostic,
+{
+    type Output = Result<T, ErrorReport>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = self.project();
+        let result = task::ready!(this.inner.poll(cx)).map_err(IntoDiagnostic::into_error_report);
+
+        let invocation = Invocation {
+            context: this.context,
+            duration_ms: this.start.elapsed().as_secs_f64() * 1000.0,
+            failed: result.is_err(),
+        };
+        match emf_log(this.config, &invocation, this.metrics) {
+            Ok(log) => println!("{log}"),
+            Err(err) => warn!(error = %err, "invocation metrics can't be recorded"),
+        }
+        task::Poll::Ready(result)
+    }
+}
+
+/// Custom metrics of an invocation, added to the log of [MetricsLayer].
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    inner: Arc<Mutex<MetricsState>>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    metrics: Vec<(String, f64, MetricUnit)>,
+    properties: Map<String, Value>,
+}
+
+impl Metrics {
+    /// Record a value of a metric. Recording the same metric again adds a value to it.
+    pub fn add_metric(&self, name: impl Into<String>, value: f64, unit: MetricUnit) {
+        self.inner.lock().unwrap().metrics.push((name.into(), value, unit));
+    }
+
+    /// Add a value that is logged but not extracted as a metric, like an order id.
+    pub fn add_property(&self, name: impl Into<String>, value: impl Into<Value>) {
+        self.inner.lock().unwrap().properties.insert(name.into(), value.into());
+    }
+}
+
+/// Extension of [Context] to access the [Metrics] of an invocation.
+pub trait MetricsContextExt {
+    /// The metrics of the invocation, when the handler is wrapped with a [MetricsLayer].
+    fn metrics(&self) -> Option<Metrics>;
+}
+
+impl MetricsContextExt for Context {
+    fn metrics(&self) -> Option<Metrics> {
+        self.handles.metrics.clone()
+    }
+}
+
+struct Invocation<'a> {
+    context: &'a Context,
+    duration_ms: f64,
+    failed: bool,
+}
+
+fn emf_log(config: &MetricsLayer, invocation: &Invocation<'_>, metrics: &Metrics) -> Result<EmfLog, EmfError> {
+    let mut builder = EmfLogBuilder::new(config.namespace.as_str())
+        .dimension("FunctionName", invocation.context.env_config.function_name.as_str());
+    for (name, value) in &config.dimensions {
+        builder = builder.dimension(name.as_str(), value.as_str());
+    }
+    builder = builder
+        .metric("Invocations", 1.0, MetricUnit::Count)
+        .metric("Duration", invocation.duration_ms, MetricUnit::Milliseconds)
+        .metric("Errors", f64::from(u8::from(invocation.failed)), MetricUnit::Count)
+        .metric(
+            "ColdStart",
+            f64::from(u8::from(invocation.context.cold_start)),
+            MetricUnit::Count,
+        )
+        .property("requestId", invocation.context.request_id.as_str());
+
+    let state = std::mem::take(&mut *metrics.inner.lock().unwrap());
+    for (name, value, unit) in state.metrics {
+        builder = builder.metric(name, value, unit);
+    }
+    for (name, value) in state.properties {
+        builder = builder.property(name, value);
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{service_fn, Config, Error};
+
+    fn context(request_id: &str) -> Context {
+        Context {
+            request_id: request_id.to_string(),
+            cold_start: true,
+            env_config: Arc::new(Config {
+                function_name: "orders".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn invocation_emf_log() {
+        let context = context("request-1");
+        let metrics = Metrics::default();
+        metrics.add_metric("ItemsOrdered", 3.0, MetricUnit::Count);
+        metrics.add_property("orderId", "o-42");
+        let invocation = Invocation {
+            context: &context,
+            duration_ms: 12.5,
+            failed: true,
+        };
+
+        let log = emf_log(
+            &MetricsLayer::new("shop").with_dimension("Stage", "prod"),
+            &invocation,
+            &metrics,
+        )
+        .unwrap();
+        let log = serde_json::to_value(&log).unwrap();
+        let directive = &log["_aws"]["CloudWatchMetrics"][0];
+        assert_eq!("shop", directive["Namespace"]);
+        assert_eq!(serde_json::json!([["FunctionName", "Stage"]]), directive["Dimensions"]);
+        assert_eq!("orders", log["FunctionName"]);
+        assert_eq!(serde_json::json!([1.0]), log["Invocations"]);
+        assert_eq!(serde_json::json!([12.5]), log["Duration"]);
+        assert_eq!(serde_json::json!([1.0]), log["Errors"]);
+        assert_eq!(serde_json::json!([1.0]), log["ColdStart"]);
+        assert_eq!(serde_json::json!([3.0]), log["ItemsOrdered"]);
+        assert_eq!("o-42", log["orderId"]);
+        assert_eq!("request-1", log["requestId"]);
+    }
+
+    #[tokio::test]
+    async fn handlers_find_metrics_in_context() {
+        async fn func(event: LambdaEvent<()>) -> Result<bool, Error> {
+            Ok(event.context.metrics().is_some())
+        }
+        let mut service = MetricsLayer::new("shop").layer(service_fn(func));
+
+        let found = service.call(LambdaEvent::new((), context("request-2"))).await.unwrap();
+        assert!(found);
+        assert!(context("request-2").metrics().is_none());
+    }
+}
@@ -1,19 +1,41 @@
 // Internally used services.
 mod api_client;
 mod api_response;
+mod failure_destination;
 mod panic;
+mod response_size;
 
 // Publicly available services.
+mod concurrency_guard;
+mod deadline;
+mod resource_usage;
 /// Tracing utilities for Lambda runtime.
 pub mod trace;
+mod xray;
 
-pub(crate) use api_client::RuntimeApiClientService;
-pub(crate) use api_response::RuntimeApiResponseService;
+pub(crate) use api_client::{ResponseSent, RuntimeApiClientService};
+pub(crate) use api_response::{DeadlineMargins, RuntimeApiResponseService};
+pub use concurrency_guard::{
+    ConcurrencyGuard, ConcurrencyGuardLayer, ConcurrencyGuardService, ConcurrencyPermit, GuardTimeout,
+};
+pub use deadline::{DeadlineExceeded, DeadlineFuture, DeadlineLayer, DeadlineService};
+pub use failure_destination::{Failure, FailureDestination};
 pub(crate) use panic::CatchPanicService;
+pub use panic::{CatchPanic, CatchPanicLayer, CatchPanicResponseFuture};
+pub use resource_usage::{ResourceUsage, ResourceUsageFuture, ResourceUsageLayer, ResourceUsageService};
+pub(crate) use response_size::ResponseSizeService;
+pub use response_size::{ResponseSizeLimit, ResponseTooLarge, SpillResponse};
 pub use trace::TracingLayer;
+pub use xray::{XRayTraceLayer, XRayTraceService};
 
 #[cfg(feature = "opentelemetry")]
 mod otel;
 #[cfg(feature = "opentelemetry")]
 #[cfg_attr(docsrs, doc(cfg(feature = "opentelemetry")))]
 pub use otel::{OpenTelemetryFaasTrigger, OpenTelemetryLayer};
+
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub use metrics::{MetricUnit, Metrics, MetricsContextExt, MetricsFuture, MetricsLayer, MetricsService};
@@ -10,7 +10,7 @@ use tracing::{field, instrument::Instrumented, Instrument};
 /// a function to flush OpenTelemetry after the end of the invocation.
 pub struct OpenTelemetryLayer<F> {
     flush_fn: F,
-    otel_attribute_trigger: OpenTelemetryFaasTrigger,
+    otel_attribute_trigger: Option<OpenTelemetryFaasTrigger>,
 }
 
 impl<F> OpenTelemetryLayer<F>
@@ -21,14 +21,23 @@ where
     pub fn new(flush_fn: F) -> Self {
         Self {
             flush_fn,
-            otel_attribute_trigger: Default::default(),
+            otel_attribute_trigger: Some(Default::default()),
         }
     }
 
     /// Configure the `faas.trigger` attribute of the OpenTelemetry span.
     pub fn with_trigger(self, trigger: OpenTelemetryFaasTrigger) -> Self {
         Self {
-            otel_attribute_trigger: trigger,
+            otel_attribute_trigger: Some(trigger),
+            ..self
+        }
+    }
+
+    /// Derive the `faas.trigger` attribute of the OpenTelemetry span from the payload of each
+    /// invocation, see [OpenTelemetryFaasTrigger::detect].
+    pub fn with_detected_trigger(self) -> Self {
+        Self {
+            otel_attribute_trigger: None,
             ..self
         }
     }
@@ -45,7 +54,7 @@ where
             inner,
             flush_fn: self.flush_fn.clone(),
             coldstart: true,
-            otel_attribute_trigger: self.otel_attribute_trigger.to_string(),
+            otel_attribute_trigger: self.otel_attribute_trigger,
         }
     }
 }
@@ -55,7 +64,7 @@ pub struct OpenTelemetryService<S, F> {
     inner: S,
     flush_fn: F,
     coldstart: bool,
-    otel_attribute_trigger: String,
+    otel_attribute_trigger: Option<OpenTelemetryFaasTrigger>,
 }
 
 impl<S, F> Service<LambdaInvocation> for OpenTelemetryService<S, F>
@@ -72,26 +81,36 @@ where
     }
 
     fn call(&mut self, req: LambdaInvocation) -> Self::Future {
-        let span = if let Some(tenant_id) = &req.context.tenant_id {
-            tracing::info_span!(
-                "Lambda function invocation",
-                "otel.name" = req.context.env_config.function_name,
-                "otel.kind" = field::Empty,
-                { attribute::FAAS_TRIGGER } = &self.otel_attribute_trigger,
-                { attribute::FAAS_INVOCATION_ID } = req.context.request_id,
-                { attribute::FAAS_COLDSTART } = self.coldstart,
-                "tenant_id" = tenant_id
-            )
-        } else {
-            tracing::info_span!(
-                "Lambda function invocation",
-                "otel.name" = req.context.env_config.function_name,
-                "otel.kind" = field::Empty,
-                { attribute::FAAS_TRIGGER } = &self.otel_attribute_trigger,
-                { attribute::FAAS_INVOCATION_ID } = req.context.request_id,
-                { attribute::FAAS_COLDSTART } = self.coldstart
-            )
-        };
+        let trigger = self
+            .otel_attribute_trigger
+            .unwrap_or_else(|| OpenTelemetryFaasTrigger::detect(&req.body));
+        let span = tracing::info_span!(
+            "Lambda function invocation",
+            "otel.name" = req.context.env_config.function_name,
+            "otel.kind" = field::Empty,
+            { attribute::FAAS_TRIGGER } = trigger.to_string(),
+            { attribute::FAAS_INVOCATION_ID } = req.context.request_id,
+            { attribute::FAAS_COLDSTART } = self.coldstart,
+            { attribute::CLOUD_ACCOUNT_ID } = field::Empty,
+            { attribute::AWS_LAMBDA_INVOKED_ARN } = req.context.invoked_function_arn,
+            "aws.xray.trace_id" = field::Empty,
+            "aws.xray.parent_id" = field::Empty,
+            "tenant_id" = field::Empty,
+        );
+        // arn:partition:lambda:region:account-id:function:name
+        if let Some(account_id) = req.context.invoked_function_arn.split(':').nth(4) {
+            span.record(attribute::CLOUD_ACCOUNT_ID, account_id);
+        }
+        // The X-Ray ids let exporters link the span to the trace of the caller.
+        if let Some(trace) = req.context.xray_trace() {
+            span.record("aws.xray.trace_id", trace.root.as_str());
+            if let Some(parent) = &trace.parent {
+                span.record("aws.xray.parent_id", parent.as_str());
+            }
+        }
+        if let Some(tenant_id) = &req.context.tenant_id {
+            span.record("tenant_id", tenant_id.as_str());
+        }
 
         // After the first execution, we can set 'coldstart' to false
         self.coldstart = false;
@@ -160,6 +179,58 @@ pub enum OpenTelemetryFaasTrigger {
     Other,
 }
 
+impl OpenTelemetryFaasTrigger {
+    /// Guess the trigger of an invocation from its JSON payload:
+    /// - API Gateway, ALB, and function URL requests are [Http](Self::Http)
+    /// - SQS, SNS, Kinesis, and MQ records are [PubSub](Self::PubSub)
+    /// - S3 and DynamoDB records are [Datasource](Self::Datasource)
+    /// - EventBridge scheduled events are [Timer](Self::Timer)
+    /// - any other payload is [Other](Self::Other)
+    pub fn detect(payload: &[u8]) -> Self {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Probe {
+            // Kafka events have a map of records, grouped by topic.
+            #[serde(rename = "Records", alias = "records", default)]
+            records: Option<serde_json::Value>,
+            #[serde(default)]
+            request_context: Option<serde::de::IgnoredAny>,
+            #[serde(default)]
+            http_method: Option<serde::de::IgnoredAny>,
+            #[serde(default)]
+            source: Option<String>,
+            #[serde(rename = "detail-type", default)]
+            detail_type: Option<String>,
+            #[serde(default)]
+            event_source: Option<String>,
+        }
+
+        let Ok(probe) = serde_json::from_slice::<Probe>(payload) else {
+            return OpenTelemetryFaasTrigger::Other;
+        };
+        if probe.request_context.is_some() || probe.http_method.is_some() {
+            return OpenTelemetryFaasTrigger::Http;
+        }
+        if probe.source.as_deref() == Some("aws.events") && probe.detail_type.as_deref() == Some("Scheduled Event") {
+            return OpenTelemetryFaasTrigger::Timer;
+        }
+        let record_event_source = probe
+            .records
+            .as_ref()
+            .and_then(|records| records.get(0))
+            .and_then(|record| record.get("eventSource").or_else(|| record.get("EventSource")))
+            .and_then(|event_source| event_source.as_str());
+        let event_source = record_event_source.or(probe.event_source.as_deref());
+        match event_source {
+            Some("aws:sqs" | "aws:sns" | "aws:kinesis" | "aws:mq" | "aws:kafka" | "aws:rmq") => {
+                OpenTelemetryFaasTrigger::PubSub
+            }
+            Some("aws:s3" | "aws:dynamodb") => OpenTelemetryFaasTrigger::Datasource,
+            _ => OpenTelemetryFaasTrigger::Other,
+        }
+    }
+}
+
 impl Display for OpenTelemetryFaasTrigger {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -171,3 +242,30 @@ impl Display for OpenTelemetryFaasTrigger {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detect_trigger() {
+        let cases = [
+            (r#"{"httpMethod":"GET","path":"/"}"#, "http"),
+            (r#"{"version":"2.0","requestContext":{"http":{}}}"#, "http"),
+            (r#"{"Records":[{"eventSource":"aws:sqs"}]}"#, "pubsub"),
+            (r#"{"Records":[{"EventSource":"aws:sns"}]}"#, "pubsub"),
+            (r#"{"Records":[{"eventSource":"aws:s3"}]}"#, "datasource"),
+            (r#"{"eventSource":"aws:kafka","records":{"topic-0":[]}}"#, "pubsub"),
+            (r#"{"source":"aws.events","detail-type":"Scheduled Event"}"#, "timer"),
+            (r#"{"hello":"world"}"#, "other"),
+            ("not json", "other"),
+        ];
+        for (payload, expected) in cases {
+            assert_eq!(
+                expected,
+                OpenTelemetryFaasTrigger::detect(payload.as_bytes()).to_string(),
+                "{payload}"
+            );
+        }
+    }
+}
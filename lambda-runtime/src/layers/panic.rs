@@ -1,8 +1,14 @@
-use crate::{diagnostic::type_name_of_val, Diagnostic, LambdaEvent};
+use crate::{
+    diagnostic::{type_name_of_val, ErrorReportHook},
+    ErrorReport, IntoDiagnostic, LambdaEvent,
+};
 use futures::{future::CatchUnwind, FutureExt};
 use pin_project::pin_project;
-use std::{any::Any, fmt::Debug, future::Future, marker::PhantomData, panic::AssertUnwindSafe, pin::Pin, task};
-use tower::Service;
+use std::{
+    any::Any, backtrace::Backtrace, cell::Cell, fmt::Debug, future::Future, marker::PhantomData,
+    panic::AssertUnwindSafe, pin::Pin, sync::Once, task,
+};
+use tower::{Layer, Service};
 use tracing::error;
 
 /// Tower service that transforms panics into an error. Panics are converted to errors both when
@@ -11,17 +17,24 @@ use tracing::error;
 ///
 /// This type is only meant for internal use in the Lambda runtime crate. It neither augments the
 /// inner service's request type, nor its response type. It merely transforms the error type
-/// from `Into<Diagnostic<'_> + Debug` into `Diagnostic<'a>` to turn panics into diagnostics.
+/// from `IntoDiagnostic + Debug` into [ErrorReport] to turn panics into error reports, which
+/// the error report hook of [RuntimeBuilder](crate::RuntimeBuilder) is applied to.
 #[derive(Clone)]
 pub struct CatchPanicService<'a, S> {
     inner: S,
+    hook: Option<ErrorReportHook>,
     _phantom: PhantomData<&'a ()>,
 }
 
 impl<S> CatchPanicService<'_, S> {
     pub fn new(inner: S) -> Self {
+        Self::with_hook(inner, None)
+    }
+
+    pub(crate) fn with_hook(inner: S, hook: Option<ErrorReportHook>) -> Self {
         Self {
             inner,
+            hook,
             _phantom: PhantomData,
         }
     }
@@ -31,14 +44,17 @@ impl<'a, S, Payload> Service<LambdaEvent<Payload>> for CatchPanicService<'a, S>
 where
     S: Service<LambdaEvent<Payload>>,
     S::Future: 'a,
-    S::Error: Into<Diagnostic> + Debug,
+    S::Error: IntoDiagnostic + Debug,
 {
-    type Error = Diagnostic;
+    type Error = ErrorReport;
     type Response = S::Response;
     type Future = CatchPanicFuture<'a, S::Future>;
 
     fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
-        self.inner.poll_ready(cx).map_err(|err| err.into())
+        let hook = self.hook.clone();
+        self.inner
+            .poll_ready(cx)
+            .map_err(|err| apply_hook(&hook, err.into_error_report()))
     }
 
     fn call(&mut self, req: LambdaEvent<Payload>) -> Self::Future {
@@ -49,11 +65,11 @@ where
         match task {
             Ok(task) => {
                 let fut = AssertUnwindSafe(task).catch_unwind();
-                CatchPanicFuture::Future(fut, PhantomData)
+                CatchPanicFuture::Future(fut, self.hook.clone(), PhantomData)
             }
             Err(error) => {
                 error!(?error, "user handler panicked");
-                CatchPanicFuture::Error(error)
+                CatchPanicFuture::Error(error, self.hook.clone())
             }
         }
     }
@@ -62,51 +78,285 @@ where
 /// Future returned by [CatchPanicService].
 #[pin_project(project = CatchPanicFutureProj)]
 pub enum CatchPanicFuture<'a, F> {
-    Future(#[pin] CatchUnwind<AssertUnwindSafe<F>>, PhantomData<&'a ()>),
-    Error(Box<dyn Any + Send + 'static>),
+    Future(
+        #[pin] CatchUnwind<AssertUnwindSafe<F>>,
+        Option<ErrorReportHook>,
+        PhantomData<&'a ()>,
+    ),
+    Error(Box<dyn Any + Send + 'static>, Option<ErrorReportHook>),
 }
 
 impl<F, T, E> Future for CatchPanicFuture<'_, F>
 where
     F: Future<Output = Result<T, E>>,
-    E: Into<Diagnostic> + Debug,
+    E: IntoDiagnostic + Debug,
 {
-    type Output = Result<T, Diagnostic>;
+    type Output = Result<T, ErrorReport>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
         use task::Poll;
         match self.project() {
-            CatchPanicFutureProj::Future(fut, _) => match fut.poll(cx) {
+            CatchPanicFutureProj::Future(fut, hook, _) => match fut.poll(cx) {
                 Poll::Ready(ready) => match ready {
                     Ok(Ok(success)) => Poll::Ready(Ok(success)),
                     Ok(Err(error)) => {
-                        error!("{error:?}");
-                        Poll::Ready(Err(error.into()))
+                        let report = apply_hook(hook, error.into_error_report());
+                        error!(
+                            error_type = report.error_type.as_str(),
+                            error_message = report.error_message.as_str(),
+                            "handler returned an error"
+                        );
+                        Poll::Ready(Err(report))
                     }
                     Err(error) => {
                         error!(?error, "user handler panicked");
-                        Poll::Ready(Err(Self::build_panic_diagnostic(&error)))
+                        Poll::Ready(Err(apply_hook(hook, panic_report(&error, None))))
                     }
                 },
                 Poll::Pending => Poll::Pending,
             },
-            CatchPanicFutureProj::Error(error) => Poll::Ready(Err(Self::build_panic_diagnostic(error))),
+            CatchPanicFutureProj::Error(error, hook) => Poll::Ready(Err(apply_hook(hook, panic_report(error, None)))),
+        }
+    }
+}
+
+fn apply_hook(hook: &Option<ErrorReportHook>, report: ErrorReport) -> ErrorReport {
+    match hook {
+        Some(hook) => hook(report),
+        None => report,
+    }
+}
+
+fn panic_report(err: &Box<dyn Any + Send>, backtrace: Option<Backtrace>) -> ErrorReport {
+    let error_message = if let Some(msg) = err.downcast_ref::<&str>() {
+        format!("Lambda panicked: {msg}")
+    } else if let Some(msg) = err.downcast_ref::<String>() {
+        format!("Lambda panicked: {msg}")
+    } else {
+        "Lambda panicked".to_string()
+    };
+    let report = ErrorReport::new(type_name_of_val(err), error_message);
+    match backtrace {
+        Some(backtrace) => report.with_stack_trace(backtrace.to_string().lines().map(str::to_owned).collect()),
+        None => report,
+    }
+}
+
+/// Tower layer that reports panics of the handler as invocation errors, including the panic
+/// message and the backtrace as stack trace.
+///
+/// The runtime always catches panics so that it keeps processing the next events, but only
+/// reports the panic message. Add this layer with [Runtime::builder](crate::Runtime::builder)
+/// to also report where the handler panicked:
+///
+/// ```no_run
+/// use lambda_runtime::{layers::CatchPanicLayer, Error, LambdaEvent, Runtime};
+/// use serde_json::Value;
+/// use tower::service_fn;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     Runtime::builder()
+///         .layer(CatchPanicLayer::new())
+///         .build(service_fn(func))
+///         .run()
+///         .await
+/// }
+///
+/// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+///     Ok(event.payload)
+/// }
+/// ```
+///
+/// Capturing backtraces installs a panic hook that runs before the previously installed one.
+#[derive(Clone, Debug)]
+pub struct CatchPanicLayer {
+    backtrace: bool,
+}
+
+impl CatchPanicLayer {
+    /// Create a layer that reports panics with their backtrace.
+    pub fn new() -> Self {
+        Self { backtrace: true }
+    }
+
+    /// Whether to capture and report the backtrace of panics, `true` by default.
+    pub fn with_backtrace(mut self, backtrace: bool) -> Self {
+        self.backtrace = backtrace;
+        self
+    }
+}
+
+impl Default for CatchPanicLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for CatchPanicLayer {
+    type Service = CatchPanic<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        if self.backtrace {
+            install_backtrace_hook();
+        }
+        CatchPanic {
+            inner,
+            backtrace: self.backtrace,
+        }
+    }
+}
+
+/// Tower service that reports panics of the inner service as an [ErrorReport], created by [CatchPanicLayer].
+#[derive(Clone, Debug)]
+pub struct CatchPanic<S> {
+    inner: S,
+    backtrace: bool,
+}
+
+impl<S, Payload> Service<LambdaEvent<Payload>> for CatchPanic<S>
+where
+    S: Service<LambdaEvent<Payload>>,
+    S::Error: IntoDiagnostic,
+{
+    type Error = ErrorReport;
+    type Response = S::Response;
+    type Future = CatchPanicResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(IntoDiagnostic::into_error_report)
+    }
+
+    fn call(&mut self, req: LambdaEvent<Payload>) -> Self::Future {
+        let backtrace = self.backtrace;
+        match std::panic::catch_unwind(AssertUnwindSafe(|| self.inner.call(req))) {
+            Ok(task) => CatchPanicResponseFuture::Future {
+                future: AssertUnwindSafe(task).catch_unwind(),
+                backtrace,
+            },
+            Err(error) => CatchPanicResponseFuture::Panicked(Some(report_panic(&error, backtrace))),
+        }
+    }
+}
+
+/// Future returned by [CatchPanic].
+#[pin_project(project = CatchPanicResponseFutureProj)]
+pub enum CatchPanicResponseFuture<F> {
+    #[doc(hidden)]
+    Future {
+        #[pin]
+        future: CatchUnwind<AssertUnwindSafe<F>>,
+        backtrace: bool,
+    },
+    #[doc(hidden)]
+    Panicked(Option<ErrorReport>),
+}
+
+impl<F, T, E> Future for CatchPanicResponseFuture<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: IntoDiagnostic,
+{
+    type Output = Result<T, ErrorReport>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        use task::Poll;
+        match self.project() {
+            CatchPanicResponseFutureProj::Future { future, backtrace } => match future.poll(cx) {
+                Poll::Ready(Ok(result)) => Poll::Ready(result.map_err(IntoDiagnostic::into_error_report)),
+                Poll::Ready(Err(error)) => Poll::Ready(Err(report_panic(&error, *backtrace))),
+                Poll::Pending => Poll::Pending,
+            },
+            CatchPanicResponseFutureProj::Panicked(report) => {
+                Poll::Ready(Err(report.take().expect("future polled after completion")))
+            }
         }
     }
 }
 
-impl<F> CatchPanicFuture<'_, F> {
-    fn build_panic_diagnostic(err: &Box<dyn Any + Send>) -> Diagnostic {
-        let error_message = if let Some(msg) = err.downcast_ref::<&str>() {
-            format!("Lambda panicked: {msg}")
-        } else if let Some(msg) = err.downcast_ref::<String>() {
-            format!("Lambda panicked: {msg}")
-        } else {
-            "Lambda panicked".to_string()
-        };
-        Diagnostic {
-            error_type: type_name_of_val(err),
-            error_message,
+fn report_panic(err: &Box<dyn Any + Send>, backtrace: bool) -> ErrorReport {
+    let backtrace = if backtrace {
+        PANIC_BACKTRACE.with(Cell::take)
+    } else {
+        None
+    };
+    let report = panic_report(err, backtrace);
+    error!(error = %report.error_message, "user handler panicked");
+    report
+}
+
+thread_local! {
+    // Backtrace of the last panic on this thread, read where the panic is caught,
+    // which is always the thread that panicked.
+    static PANIC_BACKTRACE: Cell<Option<Backtrace>> = const { Cell::new(None) };
+}
+
+fn install_backtrace_hook() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            PANIC_BACKTRACE.with(|backtrace| backtrace.set(Some(Backtrace::force_capture())));
+            previous(info);
+        }));
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{service_fn, Context, Error};
+
+    #[tokio::test]
+    async fn catch_panic_service_applies_error_report_hook() {
+        async fn func(_event: LambdaEvent<()>) -> Result<(), crate::Diagnostic> {
+            Err(crate::Diagnostic {
+                error_type: "OutOfStock".into(),
+                error_message: "out of stock".into(),
+            })
+        }
+        let hook: ErrorReportHook = std::sync::Arc::new(|report: ErrorReport| {
+            ErrorReport::new(format!("Orders.{}", report.error_type), report.error_message)
+        });
+        let mut service = CatchPanicService::with_hook(service_fn(func), Some(hook));
+
+        let report = service
+            .call(LambdaEvent::new((), Context::default()))
+            .await
+            .unwrap_err();
+        assert_eq!("Orders.OutOfStock", report.error_type);
+        assert_eq!("out of stock", report.error_message);
+    }
+
+    #[tokio::test]
+    async fn catch_panic_layer_reports_backtrace() {
+        async fn func(_event: LambdaEvent<()>) -> Result<(), Error> {
+            panic!("handler failed");
         }
+        let mut service = CatchPanicLayer::new().layer(service_fn(func));
+
+        let report = service
+            .call(LambdaEvent::new((), Context::default()))
+            .await
+            .unwrap_err();
+        assert_eq!("Lambda panicked: handler failed", report.error_message);
+        assert!(report
+            .stack_trace
+            .iter()
+            .any(|frame| frame.contains("catch_panic_layer_reports_backtrace")));
+    }
+
+    #[tokio::test]
+    async fn catch_panic_layer_without_backtrace() {
+        let mut service = CatchPanicLayer::new().with_backtrace(false).layer(service_fn(
+            |_event: LambdaEvent<()>| -> futures::future::Ready<Result<(), Error>> { panic!("handler failed") },
+        ));
+
+        let report = service
+            .call(LambdaEvent::new((), Context::default()))
+            .await
+            .unwrap_err();
+        assert_eq!("Lambda panicked: handler failed", report.error_message);
+        assert!(report.stack_trace.is_empty());
     }
 }
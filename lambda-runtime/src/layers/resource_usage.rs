@@ -0,0 +1,330 @@
+use crate::{Context, LambdaEvent};
+use pin_project::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task,
+    time::{Duration, Instant},
+};
+use tower::{Layer, Service};
+use tracing::debug;
+
+/// Memory and CPU used by the process while an invocation was processed, sampled by the
+/// [ResourceUsageLayer].
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResourceUsage {
+    /// Resident memory of the process when the invocation started, in bytes.
+    pub rss_before: u64,
+    /// Resident memory of the process when the invocation completed, in bytes.
+    pub rss_after: u64,
+    /// Highest resident memory of the process since it started, in bytes.
+    pub peak_rss: u64,
+    /// CPU time spent by all the threads of the process during the invocation, in user and
+    /// kernel mode.
+    pub cpu_time: Duration,
+    /// Wall-clock time of the invocation.
+    pub duration: Duration,
+    /// Memory configured for the function, in MB.
+    pub memory_limit_mb: i32,
+}
+
+impl ResourceUsage {
+    /// Share of the configured memory used at the peak, between 0 and 1.
+    pub fn memory_utilization(&self) -> Option<f64> {
+        let limit = u64::try_from(self.memory_limit_mb).ok().filter(|limit| *limit > 0)? * 1024 * 1024;
+        Some(self.peak_rss as f64 / limit as f64)
+    }
+
+    /// Share of the wall-clock time the process spent on a CPU. Values above 1 mean that several
+    /// cores were busy, which only functions with more than 1769 MB of memory get.
+    pub fn cpu_utilization(&self) -> Option<f64> {
+        (!self.duration.is_zero()).then(|| self.cpu_time.as_secs_f64() / self.duration.as_secs_f64())
+    }
+}
+
+type UsageHook = dyn Fn(&Context, &ResourceUsage) + Send + Sync;
+
+/// Tower layer that samples the memory and CPU time of the process around every invocation,
+/// to right-size the memory of a function without external profilers.
+///
+/// The figures are read from `/proc/self`, so they're only available on Linux, like in the
+/// Lambda execution environment; elsewhere the layer only runs the handler. The usage is
+/// logged at the debug level and passed to the hook set with [ResourceUsageLayer::on_usage].
+/// With the `metrics` feature, it's also recorded as `MemoryUsed`, `PeakMemoryUsed`, and
+/// `CpuTime` in the log of the [MetricsLayer](crate::layers::MetricsLayer), when this layer is
+/// added after it.
+///
+/// ```no_run
+/// use lambda_runtime::{layers::ResourceUsageLayer, service_fn, Error, LambdaEvent, Runtime};
+/// use serde_json::Value;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let usage = ResourceUsageLayer::new().on_usage(|context, usage| {
+///         if usage.memory_utilization().is_some_and(|utilization| utilization > 0.9) {
+///             tracing::warn!(request_id = %context.request_id, peak_rss = usage.peak_rss, "memory is almost exhausted");
+///         }
+///     });
+///     Runtime::builder().layer(usage).build(service_fn(func)).run().await
+/// }
+///
+/// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+///     Ok(event.payload)
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct ResourceUsageLayer {
+    hook: Option<Arc<UsageHook>>,
+}
+
+impl ResourceUsageLayer {
+    /// Create a layer that logs the usage of every invocation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `hook` with the usage of every invocation, to feed custom metrics.
+    pub fn on_usage<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&Context, &ResourceUsage) + Send + Sync + 'static,
+    {
+        self.hook = Some(Arc::new(hook));
+        self
+    }
+}
+
+impl fmt::Debug for ResourceUsageLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResourceUsageLayer")
+            .field("hook", &self.hook.is_some())
+            .finish()
+    }
+}
+
+impl<S> Layer<S> for ResourceUsageLayer {
+    type Service = ResourceUsageService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ResourceUsageService {
+            inner,
+            hook: self.hook.clone(),
+        }
+    }
+}
+
+/// Tower service returned by [ResourceUsageLayer].
+#[derive(Clone)]
+pub struct ResourceUsageService<S> {
+    inner: S,
+    hook: Option<Arc<UsageHook>>,
+}
+
+impl<S: fmt::Debug> fmt::Debug for ResourceUsageService<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResourceUsageService")
+            .field("inner", &self.inner)
+            .field("hook", &self.hook.is_some())
+            .finish()
+    }
+}
+
+impl<S, Payload> Service<LambdaEvent<Payload>> for ResourceUsageService<S>
+where
+    S: Service<LambdaEvent<Payload>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResourceUsageFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: LambdaEvent<Payload>) -> Self::Future {
+        let context = req.context.clone();
+        ResourceUsageFuture {
+            before: Sample::read(),
+            start: Instant::now(),
+            inner: self.inner.call(req),
+            hook: self.hook.clone(),
+            context,
+        }
+    }
+}
+
+/// Future returned by [ResourceUsageService].
+#[pin_project]
+pub struct ResourceUsageFuture<F> {
+    #[pin]
+    inner: F,
+    hook: Option<Arc<UsageHook>>,
+    context: Context,
+    before: Option<Sample>,
+    start: Instant,
+}
+
+impl<F: Future> Future for ResourceUsageFuture<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = self.project();
+        let output = task::ready!(this.inner.poll(cx));
+
+        if let (Some(before), Some(after)) = (this.before.take(), Sample::read()) {
+            let usage = ResourceUsage {
+                rss_before: before.rss,
+                rss_after: after.rss,
+                peak_rss: after.peak_rss,
+                cpu_time: after.cpu_time.saturating_sub(before.cpu_time),
+                duration: this.start.elapsed(),
+                memory_limit_mb: this.context.env_config.memory,
+            };
+            debug!(
+                request_id = %this.context.request_id,
+                rss = usage.rss_after,
+                peak_rss = usage.peak_rss,
+                cpu_time = ?usage.cpu_time,
+                "invocation resource usage"
+            );
+            #[cfg(feature = "metrics")]
+            record_metrics(this.context, &usage);
+            if let Some(hook) = this.hook {
+                hook(this.context, &usage);
+            }
+        }
+        task::Poll::Ready(output)
+    }
+}
+
+#[cfg(feature = "metrics")]
+fn record_metrics(context: &Context, usage: &ResourceUsage) {
+    use crate::layers::{MetricUnit, MetricsContextExt};
+
+    if let Some(metrics) = context.metrics() {
+        const MB: f64 = 1024.0 * 1024.0;
+        metrics.add_metric("MemoryUsed", usage.rss_after as f64 / MB, MetricUnit::Megabytes);
+        metrics.add_metric("PeakMemoryUsed", usage.peak_rss as f64 / MB, MetricUnit::Megabytes);
+        metrics.add_metric(
+            "CpuTime",
+            usage.cpu_time.as_secs_f64() * 1000.0,
+            MetricUnit::Milliseconds,
+        );
+    }
+}
+
+/// Memory and CPU time of the process at one point in time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Sample {
+    rss: u64,
+    peak_rss: u64,
+    cpu_time: Duration,
+}
+
+impl Sample {
+    fn read() -> Option<Self> {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+        Self::parse(&status, &stat)
+    }
+
+    fn parse(status: &str, stat: &str) -> Option<Self> {
+        let (rss, peak_rss) = parse_status(status)?;
+        Some(Self {
+            rss,
+            peak_rss,
+            cpu_time: parse_cpu_time(stat)?,
+        })
+    }
+}
+
+/// Current and peak resident memory in `/proc/self/status`, in bytes.
+fn parse_status(status: &str) -> Option<(u64, u64)> {
+    let kilobytes = |name: &str| -> Option<u64> {
+        let line = status.lines().find_map(|line| line.strip_prefix(name))?;
+        let value = line.trim().strip_suffix("kB")?;
+        Some(value.trim().parse::<u64>().ok()? * 1024)
+    };
+    let rss = kilobytes("VmRSS:")?;
+    Some((rss, kilobytes("VmHWM:").unwrap_or(rss)))
+}
+
+/// User and system CPU time in `/proc/self/stat`.
+fn parse_cpu_time(stat: &str) -> Option<Duration> {
+    // Clock ticks per second of `/proc` times, which Linux exposes as 100 on every architecture
+    // Lambda runs on.
+    const TICKS_PER_SECOND: u64 = 100;
+
+    // The name of the executable is in parentheses and can contain spaces, the fields are
+    // counted after it: `utime` and `stime` are the 14th and 15th fields of the line.
+    let fields = stat.get(stat.rfind(')')? + 1..)?;
+    let mut fields = fields.split_whitespace().skip(11);
+    let utime: u64 = fields.next()?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    let ticks = utime + stime;
+    Some(Duration::from_millis(ticks * 1000 / TICKS_PER_SECOND))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{service_fn, Config, Error};
+    use std::sync::Mutex;
+
+    #[test]
+    fn parse_proc_files() {
+        let status = "Name:\tbootstrap\nVmHWM:\t   20480 kB\nVmRSS:\t   10240 kB\nThreads:\t3\n";
+        let stat = "42 (my bootstrap) S 1 42 42 0 -1 4194560 1000 0 0 0 150 25 0 0 20 0 3 0 100 0 0";
+        assert_eq!(
+            Some(Sample {
+                rss: 10 * 1024 * 1024,
+                peak_rss: 20 * 1024 * 1024,
+                cpu_time: Duration::from_millis(1750),
+            }),
+            Sample::parse(status, stat)
+        );
+        assert_eq!(None, Sample::parse("Name:\tbootstrap\n", stat));
+    }
+
+    #[test]
+    fn utilization() {
+        let usage = ResourceUsage {
+            rss_before: 0,
+            rss_after: 0,
+            peak_rss: 64 * 1024 * 1024,
+            cpu_time: Duration::from_millis(50),
+            duration: Duration::from_millis(100),
+            memory_limit_mb: 128,
+        };
+        assert_eq!(Some(0.5), usage.memory_utilization());
+        assert_eq!(Some(0.5), usage.cpu_utilization());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn usage_is_passed_to_the_hook() {
+        let usages = Arc::new(Mutex::new(Vec::new()));
+        let layer = ResourceUsageLayer::new().on_usage({
+            let usages = usages.clone();
+            move |_, usage| usages.lock().unwrap().push(usage.clone())
+        });
+        let mut service = layer.layer(service_fn(|_: LambdaEvent<()>| async { Ok::<_, Error>(()) }));
+
+        let context = Context {
+            env_config: Arc::new(Config {
+                memory: 128,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        service.call(LambdaEvent::new((), context)).await.unwrap();
+
+        let usages = usages.lock().unwrap();
+        assert_eq!(1, usages.len());
+        assert!(usages[0].rss_after > 0);
+        assert!(usages[0].peak_rss >= usages[0].rss_after);
+        assert_eq!(128, usages[0].memory_limit_mb);
+    }
+}
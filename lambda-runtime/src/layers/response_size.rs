@@ -0,0 +1,457 @@
+use crate::{
+    requests::{EventErrorRequest, IntoRequest},
+    types::ToStreamErrorTrailer,
+    Context, Diagnostic, Error, IntoDiagnostic, LambdaInvocation,
+};
+use bytes::Bytes;
+use futures::{future::BoxFuture, ready, FutureExt};
+use hyper::body::{Body as _, Frame, SizeHint};
+use lambda_runtime_api_client::{body::Body, BoxError};
+use pin_project::pin_project;
+use std::{fmt, future::Future, pin::Pin, sync::Arc, task};
+use tower::Service;
+use tracing::error;
+
+/// Store for the responses that are too large to be returned by Lambda, like an S3 bucket,
+/// used by [ResponseSizeLimit::spill_with].
+///
+/// The store returns the response posted instead of the original one, like a JSON pointer to
+/// the S3 object that holds it:
+///
+/// ```no_run
+/// use bytes::Bytes;
+/// use futures::{future::BoxFuture, FutureExt};
+/// use lambda_runtime::{layers::SpillResponse, Context, Error};
+///
+/// struct S3Spill {
+///     client: aws_sdk_s3::Client,
+///     bucket: String,
+/// }
+///
+/// impl SpillResponse for S3Spill {
+///     fn spill(&self, context: &Context, response: Bytes) -> BoxFuture<'static, Result<Bytes, Error>> {
+///         let (client, bucket, key) = (self.client.clone(), self.bucket.clone(), context.request_id.clone());
+///         async move {
+///             client.put_object().bucket(&bucket).key(&key).body(response.into()).send().await?;
+///             Ok(serde_json::to_vec(&serde_json::json!({ "bucket": bucket, "key": key }))?.into())
+///         }
+///         .boxed()
+///     }
+/// }
+/// # mod aws_sdk_s3 {
+/// #     #[derive(Clone)]
+/// #     pub struct Client;
+/// #     pub struct PutObject;
+/// #     impl Client {
+/// #         pub fn put_object(&self) -> PutObject { PutObject }
+/// #     }
+/// #     impl PutObject {
+/// #         pub fn bucket(self, _: &str) -> Self { self }
+/// #         pub fn key(self, _: &str) -> Self { self }
+/// #         pub fn body(self, _: Vec<u8>) -> Self { self }
+/// #         pub async fn send(self) -> Result<(), lambda_runtime::Error> { Ok(()) }
+/// #     }
+/// # }
+/// ```
+pub trait SpillResponse: Send + Sync + 'static {
+    /// Store the serialized `response` of the invocation, and return the response to post instead.
+    fn spill(&self, context: &Context, response: Bytes) -> BoxFuture<'static, Result<Bytes, Error>>;
+}
+
+/// Guard against responses that Lambda would reject because they're too large.
+///
+/// Lambda rejects buffered responses over 6MB, and streaming responses over 20MB, with a platform
+/// error that doesn't say which invocation produced them. With this guard, the runtime measures
+/// the serialized response before posting it, and reports a [ResponseTooLarge] error for the
+/// invocation instead, or hands the response to a [SpillResponse] store and posts what the store
+/// returns.
+///
+/// Streaming responses are measured while they're posted: the stream ends with a
+/// [ResponseTooLarge] error in its trailers at the chunk that goes over the limit. They're never
+/// spilled, since the client has already received the beginning of the response.
+///
+/// ```no_run
+/// use lambda_runtime::{layers::ResponseSizeLimit, service_fn, Error, LambdaEvent, Runtime};
+/// use serde_json::Value;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     Runtime::builder()
+///         .response_size_limit(ResponseSizeLimit::new())
+///         .build(service_fn(func))
+///         .run()
+///         .await
+/// }
+///
+/// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+///     Ok(event.payload)
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ResponseSizeLimit {
+    limit: usize,
+    streaming_limit: usize,
+    spill: Option<Arc<dyn SpillResponse>>,
+}
+
+impl ResponseSizeLimit {
+    /// Size of the largest buffered response that Lambda accepts, in bytes.
+    pub const BUFFERED_RESPONSE_LIMIT: usize = 6 * 1024 * 1024;
+
+    /// Size of the largest streaming response that Lambda accepts, in bytes.
+    pub const STREAMING_RESPONSE_LIMIT: usize = 20 * 1024 * 1024;
+
+    /// Create a guard for buffered responses over [ResponseSizeLimit::BUFFERED_RESPONSE_LIMIT],
+    /// and streaming responses over [ResponseSizeLimit::STREAMING_RESPONSE_LIMIT].
+    pub fn new() -> Self {
+        Self {
+            limit: Self::BUFFERED_RESPONSE_LIMIT,
+            streaming_limit: Self::STREAMING_RESPONSE_LIMIT,
+            spill: None,
+        }
+    }
+
+    /// Set the size of the largest buffered response posted to the Runtime API, in bytes.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Set the size of the largest streaming response posted to the Runtime API, in bytes,
+    /// including its metadata prelude.
+    pub fn with_streaming_limit(mut self, limit: usize) -> Self {
+        self.streaming_limit = limit;
+        self
+    }
+
+    /// Hand the responses that are too large to `spill`, instead of failing the invocation.
+    pub fn spill_with(mut self, spill: impl SpillResponse) -> Self {
+        self.spill = Some(Arc::new(spill));
+        self
+    }
+}
+
+impl Default for ResponseSizeLimit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ResponseSizeLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseSizeLimit")
+            .field("limit", &self.limit)
+            .field("streaming_limit", &self.streaming_limit)
+            .field("spill", &self.spill.is_some())
+            .finish()
+    }
+}
+
+/// Error reported when the serialized response of the handler is larger than the
+/// [ResponseSizeLimit].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResponseTooLarge {
+    size: usize,
+    limit: usize,
+}
+
+impl ResponseTooLarge {
+    /// Size of the serialized response, in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Size of the largest response allowed, in bytes.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl fmt::Display for ResponseTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "response of {} bytes exceeds the limit of {} bytes",
+            self.size, self.limit
+        )
+    }
+}
+
+impl std::error::Error for ResponseTooLarge {}
+
+impl From<ResponseTooLarge> for Diagnostic {
+    fn from(value: ResponseTooLarge) -> Self {
+        Diagnostic {
+            error_type: "ResponseTooLarge".into(),
+            error_message: value.to_string(),
+        }
+    }
+}
+
+/// Tower service that checks the size of the responses built by the inner service before they're
+/// posted to the Runtime API, see [ResponseSizeLimit].
+///
+/// This type is only meant for internal use in the Lambda runtime crate.
+pub struct ResponseSizeService<S> {
+    inner: S,
+    limit: Option<ResponseSizeLimit>,
+}
+
+impl<S> ResponseSizeService<S> {
+    pub fn new(inner: S, limit: Option<ResponseSizeLimit>) -> Self {
+        Self { inner, limit }
+    }
+}
+
+impl<S: Clone> Clone for ResponseSizeService<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            limit: self.limit.clone(),
+        }
+    }
+}
+
+impl<S> Service<LambdaInvocation> for ResponseSizeService<S>
+where
+    S: Service<LambdaInvocation, Response = http::Request<Body>, Error = BoxError>,
+{
+    type Response = http::Request<Body>;
+    type Error = BoxError;
+    type Future = ResponseSizeFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: LambdaInvocation) -> Self::Future {
+        // Boxed to keep the future small when no limit is set.
+        let checked = self.limit.clone().map(|limit| Box::new((limit, req.context.clone())));
+        ResponseSizeFuture::Inner(self.inner.call(req), checked)
+    }
+}
+
+#[pin_project(project = ResponseSizeFutureProj)]
+pub enum ResponseSizeFuture<F> {
+    Inner(#[pin] F, Option<Box<(ResponseSizeLimit, Context)>>),
+    Spilling(BoxFuture<'static, Result<http::Request<Body>, BoxError>>),
+}
+
+impl<F> Future for ResponseSizeFuture<F>
+where
+    F: Future<Output = Result<http::Request<Body>, BoxError>>,
+{
+    type Output = Result<http::Request<Body>, BoxError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        // NOTE: We loop here to directly poll the spilling future once the response is built.
+        task::Poll::Ready(loop {
+            match self.as_mut().project() {
+                ResponseSizeFutureProj::Inner(fut, checked) => {
+                    let request = ready!(fut.poll(cx))?;
+                    let Some((limit, context)) = checked.take().map(|checked| *checked) else {
+                        break Ok(request);
+                    };
+                    if !request.uri().path().ends_with("/response") {
+                        break Ok(request);
+                    }
+                    if is_streaming(&request) {
+                        let (parts, body) = request.into_parts();
+                        let body = Body::new(StreamingLimitBody {
+                            inner: Some(body),
+                            sent: 0,
+                            limit: limit.streaming_limit,
+                        });
+                        break Ok(http::Request::from_parts(parts, body));
+                    }
+                    let Some(size) = request.body().size_hint().exact() else {
+                        break Ok(request);
+                    };
+                    let size = size as usize;
+                    if size <= limit.limit {
+                        break Ok(request);
+                    }
+
+                    let too_large = ResponseTooLarge {
+                        size,
+                        limit: limit.limit,
+                    };
+                    let Some(spill) = limit.spill else {
+                        error!(error = %too_large, "response is too large to be returned by Lambda");
+                        break EventErrorRequest::new(&context.request_id, too_large).into_req();
+                    };
+                    self.set(ResponseSizeFuture::Spilling(
+                        spill_response(spill, context, request).boxed(),
+                    ));
+                }
+                ResponseSizeFutureProj::Spilling(fut) => break ready!(fut.as_mut().poll(cx)),
+            }
+        })
+    }
+}
+
+fn is_streaming(request: &http::Request<Body>) -> bool {
+    request
+        .headers()
+        .get("Lambda-Runtime-Function-Response-Mode")
+        .is_some_and(|mode| mode == "streaming")
+}
+
+/// Body of a streaming response that counts the bytes sent, and ends the stream with a
+/// [ResponseTooLarge] error in its trailers before the chunk that goes over the limit.
+struct StreamingLimitBody {
+    inner: Option<Body>,
+    sent: usize,
+    limit: usize,
+}
+
+impl hyper::body::Body for StreamingLimitBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let Some(inner) = self.inner.as_mut() else {
+            return task::Poll::Ready(None);
+        };
+        let frame = match ready!(Pin::new(inner).poll_frame(cx)) {
+            Some(Ok(frame)) => frame,
+            other => return task::Poll::Ready(other.map(|result| result.map_err(Into::into))),
+        };
+        let Some(data) = frame.data_ref() else {
+            return task::Poll::Ready(Some(Ok(frame)));
+        };
+        let size = self.sent + data.len();
+        if size <= self.limit {
+            self.sent = size;
+            return task::Poll::Ready(Some(Ok(frame)));
+        }
+
+        // Dropping the body closes the channel that the handler's stream is written to.
+        self.inner = None;
+        let too_large = ResponseTooLarge {
+            size,
+            limit: self.limit,
+        };
+        error!(error = %too_large, "streaming response is too large to be returned by Lambda");
+        let trailers = Diagnostic::from(too_large).to_trailers();
+        task::Poll::Ready(Some(Ok(Frame::trailers(trailers))))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.as_ref().is_none_or(|inner| inner.is_end_stream())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.as_ref().map(|inner| inner.size_hint()).unwrap_or_default()
+    }
+}
+
+async fn spill_response(
+    spill: Arc<dyn SpillResponse>,
+    context: Context,
+    request: http::Request<Body>,
+) -> Result<http::Request<Body>, BoxError> {
+    let (parts, body) = request.into_parts();
+    let response = body.collect().await?.to_bytes();
+    match spill.spill(&context, response).await {
+        Ok(pointer) => Ok(http::Request::from_parts(parts, Body::from(pointer))),
+        Err(err) => {
+            error!(error = %err, "response is too large to be returned by Lambda and couldn't be spilled");
+            EventErrorRequest::new(&context.request_id, err.into_error_report()).into_req()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn invocation() -> LambdaInvocation {
+        let (parts, _) = http::Response::new(()).into_parts();
+        LambdaInvocation {
+            parts,
+            body: Bytes::new(),
+            context: Context {
+                request_id: "id".into(),
+                ..Context::default()
+            },
+        }
+    }
+
+    fn respond(body: &'static str) -> impl Service<LambdaInvocation, Response = http::Request<Body>, Error = BoxError> {
+        tower::service_fn(move |_: LambdaInvocation| async move {
+            let req = http::Request::post("/2018-06-01/runtime/invocation/id/response")
+                .body(Body::from(body))
+                .unwrap();
+            Ok::<_, BoxError>(req)
+        })
+    }
+
+    #[tokio::test]
+    async fn report_responses_over_the_limit() {
+        let limit = ResponseSizeLimit::new().with_limit(4);
+        let mut service = ResponseSizeService::new(respond("\"ok\""), Some(limit.clone()));
+        let req = service.call(invocation()).await.unwrap();
+        assert_eq!("/2018-06-01/runtime/invocation/id/response", req.uri().path());
+
+        let mut service = ResponseSizeService::new(respond("\"too large\""), Some(limit));
+        let req = service.call(invocation()).await.unwrap();
+        assert_eq!("/2018-06-01/runtime/invocation/id/error", req.uri().path());
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!("ResponseTooLarge", report["errorType"]);
+        assert_eq!(
+            "response of 11 bytes exceeds the limit of 4 bytes",
+            report["errorMessage"]
+        );
+    }
+
+    #[tokio::test]
+    async fn spill_responses_over_the_limit() {
+        struct Spill;
+
+        impl SpillResponse for Spill {
+            fn spill(&self, context: &Context, response: Bytes) -> BoxFuture<'static, Result<Bytes, Error>> {
+                let pointer = format!("{{\"key\":\"{}\",\"size\":{}}}", context.request_id, response.len());
+                async move { Ok(pointer.into()) }.boxed()
+            }
+        }
+
+        let limit = ResponseSizeLimit::new().with_limit(4).spill_with(Spill);
+        let mut service = ResponseSizeService::new(respond("\"too large\""), Some(limit));
+        let req = service.call(invocation()).await.unwrap();
+        assert_eq!("/2018-06-01/runtime/invocation/id/response", req.uri().path());
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(Bytes::from_static(b"{\"key\":\"id\",\"size\":11}"), body);
+    }
+
+    #[tokio::test]
+    async fn end_streaming_responses_over_the_limit() {
+        let respond = tower::service_fn(|_: LambdaInvocation| async {
+            let (mut tx, rx) = Body::channel();
+            tokio::spawn(async move {
+                for chunk in ["abc", "def", "ghi"] {
+                    if tx.send_data(Bytes::from_static(chunk.as_bytes())).await.is_err() {
+                        return;
+                    }
+                }
+            });
+            let req = http::Request::post("/2018-06-01/runtime/invocation/id/response")
+                .header("Lambda-Runtime-Function-Response-Mode", "streaming")
+                .body(rx)
+                .unwrap();
+            Ok::<_, BoxError>(req)
+        });
+
+        let limit = ResponseSizeLimit::new().with_streaming_limit(7);
+        let mut service = ResponseSizeService::new(respond, Some(limit));
+        let req = service.call(invocation()).await.unwrap();
+        let body = req.into_body().collect().await.unwrap();
+        let trailers = body.trailers().cloned().expect("missing error trailers");
+        assert_eq!("ResponseTooLarge", trailers["lambda-runtime-function-error-type"]);
+        assert_eq!(Bytes::from_static(b"abcdef"), body.to_bytes());
+    }
+}
@@ -1,11 +1,15 @@
 use tower::{Layer, Service};
-use tracing::{instrument::Instrumented, Instrument};
+use tracing::{field, instrument::Instrumented, Instrument};
 
 use crate::{Context, LambdaInvocation};
 use lambda_runtime_api_client::BoxError;
-use std::task;
+use std::{task, time::SystemTime};
 
 /// Tower middleware to create a tracing span for invocations of the Lambda function.
+///
+/// The span carries the request id, X-Ray trace ids, function ARN, and remaining time of the
+/// invocation, see [request_span]. The events of the handler, and the `error` events that the
+/// runtime emits when the handler fails, are recorded in this span.
 #[derive(Default)]
 pub struct TracingLayer {}
 
@@ -59,33 +63,115 @@ where
 /// Creates a tracing span for a Lambda request with context information.
 ///
 /// This function creates a span that includes the request ID and optionally
-/// the X-Ray trace ID and tenant ID if they are available in the context.
+/// the X-Ray trace ID, its root trace and parent segment ids, the function ARN,
+/// the milliseconds remaining before the deadline when the invocation starts,
+/// and the tenant ID if they are available in the context.
 pub fn request_span(ctx: &Context) -> tracing::Span {
-    match (&ctx.xray_trace_id, &ctx.tenant_id) {
-        (Some(trace_id), Some(tenant_id)) => {
-            tracing::info_span!(
-                "Lambda runtime invoke",
-                requestId = &ctx.request_id,
-                xrayTraceId = trace_id,
-                tenantId = tenant_id
-            )
-        }
-        (Some(trace_id), None) => {
-            tracing::info_span!(
-                "Lambda runtime invoke",
-                requestId = &ctx.request_id,
-                xrayTraceId = trace_id
-            )
-        }
-        (None, Some(tenant_id)) => {
-            tracing::info_span!(
-                "Lambda runtime invoke",
-                requestId = &ctx.request_id,
-                tenantId = tenant_id
-            )
-        }
-        (None, None) => {
-            tracing::info_span!("Lambda runtime invoke", requestId = &ctx.request_id)
+    let span = tracing::info_span!(
+        "Lambda runtime invoke",
+        requestId = &ctx.request_id,
+        functionArn = field::Empty,
+        remainingTimeMs = field::Empty,
+        xrayTraceId = field::Empty,
+        xrayRootId = field::Empty,
+        xraySegmentId = field::Empty,
+        tenantId = field::Empty,
+    );
+    if !ctx.invoked_function_arn.is_empty() {
+        span.record("functionArn", ctx.invoked_function_arn.as_str());
+    }
+    if ctx.deadline > 0 {
+        let remaining = ctx.deadline().duration_since(SystemTime::now()).unwrap_or_default();
+        span.record("remainingTimeMs", remaining.as_millis() as u64);
+    }
+    if let Some(trace_id) = &ctx.xray_trace_id {
+        span.record("xrayTraceId", trace_id.as_str());
+    }
+    if let Some(trace) = ctx.xray_trace() {
+        span.record("xrayRootId", trace.root.as_str());
+        if let Some(parent) = &trace.parent {
+            span.record("xraySegmentId", parent.as_str());
         }
     }
+    if let Some(tenant_id) = &ctx.tenant_id {
+        span.record("tenantId", tenant_id.as_str());
+    }
+    span
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        codec::Json,
+        layers::{CatchPanicService, RuntimeApiResponseService},
+        ErrorReport, LambdaEvent,
+    };
+    use bytes::Bytes;
+    use serde_json::Value;
+    use std::time::{Duration, UNIX_EPOCH};
+    use tracing_capture::{CaptureLayer, SharedStorage};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn span_records_the_invocation_and_handler_errors() {
+        let storage = SharedStorage::default();
+        let subscriber = tracing_subscriber::registry().with(CaptureLayer::new(&storage));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let deadline = SystemTime::now() + Duration::from_secs(30);
+        let context = Context {
+            request_id: "request-1".into(),
+            invoked_function_arn: "arn:aws:lambda:us-east-1:123456789012:function:orders".into(),
+            deadline: deadline.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
+            ..Context::default()
+        };
+        let (parts, _) = http::Response::new(()).into_parts();
+        let invocation = LambdaInvocation {
+            parts,
+            body: Bytes::from_static(b"{}"),
+            context,
+        };
+
+        let handler = tower::service_fn(|_: LambdaEvent<Value>| async {
+            Err::<Value, _>(ErrorReport::new("OutOfStock", "no more items"))
+        });
+        let mut service = TracingLayer::new().layer(tower::service_fn({
+            let mut inner = RuntimeApiResponseService::new(
+                CatchPanicService::new(handler),
+                Json,
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                None,
+            );
+            move |invocation| {
+                let request = inner.call(invocation);
+                async move { request.await.map(|_| ()) }
+            }
+        }));
+        service.call(invocation).await.unwrap();
+
+        let storage = storage.lock();
+        let span = storage
+            .all_spans()
+            .find(|span| span.metadata().name() == "Lambda runtime invoke")
+            .expect("missing invocation span");
+        assert_eq!(Some("request-1"), span["requestId"].as_str());
+        assert_eq!(
+            Some("arn:aws:lambda:us-east-1:123456789012:function:orders"),
+            span["functionArn"].as_str()
+        );
+        let remaining = span["remainingTimeMs"].as_uint().expect("missing remaining time");
+        assert!(remaining > 25_000 && remaining <= 30_000, "{remaining}");
+
+        let errors: Vec<_> = span
+            .events()
+            .filter(|event| event.metadata().level() == &tracing::Level::ERROR)
+            .collect();
+        assert_eq!(1, errors.len(), "the handler error is logged once");
+        let error = errors[0];
+        assert_eq!(Some("OutOfStock"), error["error_type"].as_str());
+        assert_eq!(Some("no more items"), error["error_message"].as_str());
+    }
 }
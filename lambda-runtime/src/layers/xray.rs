@@ -0,0 +1,60 @@
+use crate::LambdaInvocation;
+use lambda_runtime_api_client::BoxError;
+use std::{env, task};
+use tower::{Layer, Service};
+
+/// Tower middleware that exposes the X-Ray trace header of each invocation in the
+/// `_X_AMZN_TRACE_ID` environment variable, where the AWS SDKs and X-Ray clients read it.
+///
+/// [Runtime::run](crate::Runtime::run) already sets the variable. Add this layer to runtimes that
+/// don't, like `Runtime::run_concurrent` when
+/// `AWS_LAMBDA_MAX_CONCURRENCY` is `1` or unset. The variable is shared by the whole process, so with
+/// concurrent invocations it only holds the header of the last one; read
+/// [Context::xray_trace](crate::Context::xray_trace) instead in that case.
+///
+/// The trace id and segment id of the header are recorded on the span created by
+/// [TracingLayer](super::TracingLayer).
+#[derive(Clone, Debug, Default)]
+pub struct XRayTraceLayer {}
+
+impl XRayTraceLayer {
+    /// Create a new X-Ray trace layer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for XRayTraceLayer {
+    type Service = XRayTraceService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        XRayTraceService { inner }
+    }
+}
+
+/// Tower service returned by [XRayTraceLayer].
+#[derive(Clone, Debug)]
+pub struct XRayTraceService<S> {
+    inner: S,
+}
+
+impl<S> Service<LambdaInvocation> for XRayTraceService<S>
+where
+    S: Service<LambdaInvocation, Response = (), Error = BoxError>,
+{
+    type Response = ();
+    type Error = BoxError;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: LambdaInvocation) -> Self::Future {
+        match req.context.xray_trace() {
+            Some(trace) => env::set_var("_X_AMZN_TRACE_ID", trace.to_string()),
+            None => env::remove_var("_X_AMZN_TRACE_ID"),
+        }
+        self.inner.call(req)
+    }
+}
@@ -19,17 +19,44 @@ use tokio_stream::Stream;
 use tower::util::ServiceFn;
 pub use tower::{self, service_fn, Service};
 
+/// Codecs that decode invocation payloads and encode handler responses.
+pub mod codec;
 /// Diagnostic utilities to convert Rust types into Lambda Error types.
 pub mod diagnostic;
-pub use diagnostic::Diagnostic;
+pub use diagnostic::{Diagnostic, ErrorReport, IntoDiagnostic, LambdaError};
 
+mod background;
+mod blocking;
+#[cfg(feature = "compression")]
+mod compression;
 mod deserializer;
+#[cfg(feature = "derive")]
+mod env_config;
+mod event_body;
+mod lambda_env;
 /// Tower middleware to be applied to runtime invocations.
 pub mod layers;
+/// In-process mock of the Runtime API to test the run loop of a function.
+#[cfg(feature = "mock-server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mock-server")))]
+pub mod mock;
+mod observer;
 mod requests;
+mod retry;
+/// Dispatch events from several triggers to typed handlers.
+#[cfg(feature = "router")]
+#[cfg_attr(docsrs, doc(cfg(feature = "router")))]
+pub mod router;
 mod runtime;
+mod short_circuit;
+mod snap_start;
+mod state;
 /// Utilities for Lambda Streaming functions.
 pub mod streaming;
+#[cfg(feature = "test-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+pub mod test;
+mod warmup;
 
 /// Utilities to initialize and use `tracing` and `tracing-subscriber` in Lambda Functions.
 #[cfg(feature = "tracing")]
@@ -39,9 +66,39 @@ pub use lambda_runtime_api_client::tracing;
 /// Types available to a Lambda function.
 mod types;
 
+pub use background::BackgroundTasks;
+pub use blocking::{service_fn_blocking, BlockingServiceFn};
+#[cfg(feature = "derive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
+pub use env_config::{EnvConfigError, FromLambdaEnv};
+pub use event_body::EventBody;
+pub use lambda_env::{Architecture, LambdaEnv, LambdaEnvError};
+#[cfg(feature = "derive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
+pub use lambda_runtime_derive::{main, main as lambda_handler, FromLambdaEnv, LambdaError};
+pub use observer::InvocationObserver;
 use requests::EventErrorRequest;
-pub use runtime::{LambdaInvocation, Runtime};
-pub use types::{Context, FunctionResponse, IntoFunctionResponse, LambdaEvent, MetadataPrelude, StreamResponse};
+pub use retry::{RetryAttempt, RetryPolicy};
+pub use runtime::{LambdaInvocation, Runtime, RuntimeBuilder};
+pub use short_circuit::ShortCircuit;
+pub use state::{with_state, State};
+pub use types::{
+    Context, FunctionResponse, InfallibleStream, InitializationType, IntoFunctionResponse, LambdaEvent,
+    MetadataPrelude, StreamResponse,
+};
+
+/// Items used by the code generated by the derive macros, not part of the public API.
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::env_config::EnvReader;
+    pub use tokio;
+
+    pub fn init_tracing() {
+        #[cfg(feature = "tracing")]
+        crate::tracing::init_default_subscriber();
+    }
+}
 
 /// Error type that lambdas may result in
 pub type Error = lambda_runtime_api_client::BoxError;
@@ -127,7 +184,7 @@ pub async fn run<A, F, R, B, S, D, E>(handler: F) -> Result<(), Error>
 where
     F: Service<LambdaEvent<A>, Response = R>,
     F::Future: Future<Output = Result<R, F::Error>>,
-    F::Error: Into<Diagnostic> + fmt::Debug,
+    F::Error: IntoDiagnostic + fmt::Debug,
     A: for<'de> Deserialize<'de>,
     R: IntoFunctionResponse<B, S>,
     B: Serialize,
@@ -139,6 +196,31 @@ where
     runtime.run().await
 }
 
+/// Starts the Lambda Rust runtime with a handler that receives the [State] shared by all the
+/// invocations with every event, instead of capturing the state in a closure.
+///
+/// The state is created once, before the runtime starts polling for events, and each invocation
+/// gets a clone of its [Arc]. See [State] for an example, and [with_state] to create the
+/// handler of a [Runtime].
+///
+/// # Panics
+///
+/// This function panics if required Lambda environment variables are missing, see [run].
+pub async fn run_with_state<T, A, F, Fut, R, B, S, D, E, Er>(state: T, handler: F) -> Result<(), Error>
+where
+    F: Fn(LambdaEvent<A>, State<T>) -> Fut,
+    Fut: Future<Output = Result<R, Er>>,
+    Er: IntoDiagnostic + fmt::Debug,
+    A: for<'de> Deserialize<'de>,
+    R: IntoFunctionResponse<B, S>,
+    B: Serialize,
+    S: Stream<Item = Result<D, E>> + Unpin + Send + 'static,
+    D: Into<bytes::Bytes> + Send,
+    E: Into<Error> + Send + Debug,
+{
+    run(with_state(Arc::new(state), handler)).await
+}
+
 /// Starts the Lambda Rust runtime in a mode that is compatible with
 /// Lambda Managed Instances (concurrent invocations).
 ///
@@ -183,7 +265,7 @@ pub async fn run_concurrent<A, F, R, B, S, D, E>(handler: F) -> Result<(), Error
 where
     F: Service<LambdaEvent<A>, Response = R> + Clone + Send + 'static,
     F::Future: Future<Output = Result<R, F::Error>> + Send + 'static,
-    F::Error: Into<Diagnostic> + fmt::Debug,
+    F::Error: IntoDiagnostic + fmt::Debug,
     A: for<'de> Deserialize<'de> + Send + 'static,
     R: IntoFunctionResponse<B, S> + Send + 'static,
     B: Serialize + Send + 'static,
@@ -195,9 +277,17 @@ where
     runtime.run_concurrent().await
 }
 
+/// Time given to graceful shutdown hooks before the process exits.
+///
+/// Lambda waits 500ms after sending `SIGTERM` to functions with internal extensions before
+/// sending `SIGKILL`, this leaves some of that window to flush stdout and exit.
+#[cfg(all(unix, feature = "graceful-shutdown"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "graceful-shutdown"))))]
+pub const GRACEFUL_SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(450);
+
 /// Spawns a task that will be execute a provided async closure when the process
-/// receives unix graceful shutdown signals. If the closure takes longer than 500ms
-/// to execute, an unhandled `SIGKILL` signal might be received.
+/// receives unix graceful shutdown signals. If the closure takes longer than
+/// [GRACEFUL_SHUTDOWN_TIMEOUT] to execute, the process exits without waiting for it.
 ///
 /// You can use this future to execute cleanup or flush related logic prior to runtime shutdown.
 ///
@@ -277,19 +367,21 @@ where
             tokio::select! {
                 _sigint = sigint.recv() => {
                     eprintln!("[runtime] SIGINT received");
-                    eprintln!("[runtime] Graceful shutdown in progress ...");
-                    shutdown_hook().await;
-                    eprintln!("[runtime] Graceful shutdown completed");
-                    std::process::exit(0);
                 },
                 _sigterm = sigterm.recv()=> {
                     eprintln!("[runtime] SIGTERM received");
-                    eprintln!("[runtime] Graceful shutdown in progress ...");
-                    shutdown_hook().await;
-                    eprintln!("[runtime] Graceful shutdown completed");
-                    std::process::exit(0);
                 },
             }
+            eprintln!("[runtime] Graceful shutdown in progress ...");
+            // Exit on our own terms before Lambda sends SIGKILL, even if the hook is still running.
+            match tokio::time::timeout(GRACEFUL_SHUTDOWN_TIMEOUT, shutdown_hook()).await {
+                Ok(()) => eprintln!("[runtime] Graceful shutdown completed"),
+                Err(_) => eprintln!(
+                    "[runtime] Graceful shutdown hook timed out after {}ms",
+                    GRACEFUL_SHUTDOWN_TIMEOUT.as_millis()
+                ),
+            }
+            std::process::exit(0);
         };
 
         let _: (_, ()) = tokio::join!(
@@ -303,3 +395,45 @@ where
         });
     });
 }
+
+/// Runs a future, usually the one returned by [run], with graceful shutdown handling.
+///
+/// This registers the graceful shutdown handler described in [spawn_graceful_shutdown_handler]
+/// before polling `handler_future`, so the extension is always registered before the runtime ends
+/// the Init phase. When the process receives `SIGTERM` or `SIGINT`, `on_shutdown` runs for at most
+/// [GRACEFUL_SHUTDOWN_TIMEOUT] before the process exits.
+///
+/// # Panics
+///
+/// This function panics in the same cases as [spawn_graceful_shutdown_handler].
+///
+/// # Example
+/// ```no_run
+/// use lambda_runtime::{Error, service_fn, LambdaEvent};
+/// use serde_json::Value;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let on_shutdown = || async move {
+///         // flush metrics, close database pools, ...
+///     };
+///     lambda_runtime::spawn_graceful_shutdown(lambda_runtime::run(service_fn(func)), on_shutdown).await
+/// }
+///
+/// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+///     Ok(event.payload)
+/// }
+/// ```
+#[cfg(all(unix, feature = "graceful-shutdown"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "graceful-shutdown"))))]
+pub async fn spawn_graceful_shutdown<F, Fut>(
+    handler_future: F,
+    on_shutdown: impl FnOnce() -> Fut + Send + 'static,
+) -> F::Output
+where
+    F: Future,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    spawn_graceful_shutdown_handler(on_shutdown).await;
+    handler_future.await
+}
@@ -0,0 +1,524 @@
+use crate::{
+    types::{STREAM_ERROR_BODY_TRAILER, STREAM_ERROR_TYPE_TRAILER},
+    Config, ErrorReport,
+};
+use base64::prelude::*;
+use bytes::Bytes;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, Request, Response, StatusCode};
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Incoming, server::conn::http1, service::service_fn};
+use hyper_util::rt::TokioIo;
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+use tokio::{net::TcpListener, sync::watch, task::JoinHandle};
+
+/// How long the server waits before accepting connections again after a failure.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Runtime API served in the process from an in-memory queue of invocations, to test the
+/// complete run loop of a function in `cargo test`.
+///
+/// Invocations pushed to the queue are returned by `/invocation/next` in order, and the
+/// responses, errors, and initialization errors posted by the runtime are recorded as
+/// [MockOutcome]s. Point the runtime to the server with [RuntimeBuilder::endpoint], give it a
+/// test [configuration](MockRuntimeApi::config) with [RuntimeBuilder::config], and stop it once
+/// every invocation is answered with [Runtime::run_until]:
+///
+/// ```
+/// use lambda_runtime::{mock::MockRuntimeApi, service_fn, Error, LambdaEvent, Runtime};
+/// use serde_json::{json, Value};
+///
+/// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+///     Ok(event.payload)
+/// }
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let api = MockRuntimeApi::start().await.unwrap();
+/// let request_id = api.push_event(json!({ "hello": "world" }));
+///
+/// Runtime::builder()
+///     .endpoint(api.endpoint())
+///     .config(api.config())
+///     .build(service_fn(func))
+///     .run_until(api.idle())
+///     .await
+///     .unwrap();
+///
+/// let outcome = api.next_outcome().await;
+/// assert_eq!(request_id, outcome.request_id);
+/// assert_eq!(br#"{"hello":"world"}"#, &outcome.result.unwrap()[..]);
+/// # });
+/// ```
+///
+/// [RuntimeBuilder::endpoint]: crate::RuntimeBuilder::endpoint
+/// [RuntimeBuilder::config]: crate::RuntimeBuilder::config
+/// [Runtime::run_until]: crate::Runtime::run_until
+#[derive(Debug)]
+pub struct MockRuntimeApi {
+    address: SocketAddr,
+    shared: Arc<Shared>,
+    server: JoinHandle<()>,
+}
+
+impl MockRuntimeApi {
+    /// Start a server on a random port of the loopback interface.
+    pub async fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, 0)).await?;
+        let address = listener.local_addr()?;
+        let shared = Arc::new(Shared::default());
+        let server = tokio::spawn(serve(listener, shared.clone()));
+        Ok(Self {
+            address,
+            shared,
+            server,
+        })
+    }
+
+    /// Address of the server, the value of `AWS_LAMBDA_RUNTIME_API` for functions that read
+    /// the endpoint from the environment.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Endpoint of the server, for [RuntimeBuilder::endpoint](crate::RuntimeBuilder::endpoint).
+    pub fn endpoint(&self) -> http::Uri {
+        format!("http://{}", self.address)
+            .parse()
+            .expect("socket addresses are valid authorities")
+    }
+
+    /// Configuration of a test function, for [RuntimeBuilder::config](crate::RuntimeBuilder::config),
+    /// so the runtime doesn't read it from the `AWS_LAMBDA_FUNCTION_*` environment variables.
+    pub fn config(&self) -> Config {
+        Config {
+            function_name: "test".to_string(),
+            memory: 128,
+            version: "$LATEST".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Queue an invocation, and return its request id.
+    pub fn push(&self, invocation: MockInvocation) -> String {
+        let mut state = self.shared.state.lock().unwrap();
+        state.pushed += 1;
+        let request_id = invocation
+            .request_id
+            .clone()
+            .unwrap_or_else(|| format!("mock-request-{}", state.pushed));
+        state.queue.push_back(MockInvocation {
+            request_id: Some(request_id.clone()),
+            ..invocation
+        });
+        state.pending += 1;
+        drop(state);
+        self.shared.notify();
+        request_id
+    }
+
+    /// Queue an invocation with `event` encoded as JSON, and return its request id.
+    pub fn push_event(&self, event: impl Serialize) -> String {
+        self.push(MockInvocation::json(event))
+    }
+
+    /// Wait for the next response or error posted by the runtime, in the order they're posted.
+    pub async fn next_outcome(&self) -> MockOutcome {
+        self.shared.wait(|state| state.outcomes.pop_front()).await
+    }
+
+    /// Wait for the next initialization error posted by the runtime.
+    pub async fn next_init_error(&self) -> ErrorReport {
+        self.shared.wait(|state| state.init_errors.pop_front()).await
+    }
+
+    /// Wait until every queued invocation has been answered by the runtime.
+    ///
+    /// Pass this future to [Runtime::run_until](crate::Runtime::run_until) to stop the runtime
+    /// once the queue is processed.
+    pub async fn idle(&self) {
+        self.shared.wait(|state| (state.pending == 0).then_some(())).await
+    }
+}
+
+impl Drop for MockRuntimeApi {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+/// Invocation queued in a [MockRuntimeApi].
+#[derive(Clone, Debug)]
+pub struct MockInvocation {
+    request_id: Option<String>,
+    body: Bytes,
+    timeout: Duration,
+    invoked_function_arn: String,
+    headers: HeaderMap,
+}
+
+impl MockInvocation {
+    /// Create an invocation with a raw event, a generated request id, and a deadline 3 seconds
+    /// after the runtime receives it.
+    pub fn new(body: impl Into<Bytes>) -> Self {
+        Self {
+            request_id: None,
+            body: body.into(),
+            timeout: Duration::from_secs(3),
+            invoked_function_arn: "arn:aws:lambda:us-east-1:123456789012:function:test".into(),
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Create an invocation with `event` encoded as JSON.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the event can't be encoded.
+    pub fn json(event: impl Serialize) -> Self {
+        Self::new(serde_json::to_vec(&event).expect("event can't be encoded"))
+    }
+
+    /// Set the request id of the invocation.
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    /// Set the time between the moment the runtime receives the invocation and its deadline.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the ARN of the invoked function.
+    pub fn with_invoked_function_arn(mut self, arn: impl Into<String>) -> Self {
+        self.invoked_function_arn = arn.into();
+        self
+    }
+
+    /// Add a header to the `/invocation/next` response, like `lambda-runtime-trace-id` or
+    /// `lambda-runtime-client-context`.
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    fn into_response(self) -> Response<Full<Bytes>> {
+        let deadline = (SystemTime::now() + self.timeout)
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("deadline is after the epoch")
+            .as_millis();
+        let mut response = Response::builder()
+            .header("content-type", "application/json")
+            .header(
+                "lambda-runtime-aws-request-id",
+                self.request_id
+                    .expect("request ids are set when invocations are queued"),
+            )
+            .header("lambda-runtime-deadline-ms", deadline.to_string())
+            .header("lambda-runtime-invoked-function-arn", self.invoked_function_arn)
+            .body(Full::new(self.body))
+            .expect("invocation headers are valid");
+        response.headers_mut().extend(self.headers);
+        response
+    }
+}
+
+/// Response or error posted by the runtime for an invocation of a [MockRuntimeApi].
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub struct MockOutcome {
+    /// Request id of the invocation.
+    pub request_id: String,
+    /// Body of the response, without the metadata prelude of streaming responses, or the error
+    /// reported for the invocation, including errors raised in the middle of a stream.
+    pub result: Result<Bytes, ErrorReport>,
+    /// Whether the response was streamed.
+    pub streaming: bool,
+    /// Metadata prelude of a streaming response, as JSON.
+    pub metadata_prelude: Option<Bytes>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    queue: VecDeque<MockInvocation>,
+    pushed: usize,
+    pending: usize,
+    outcomes: VecDeque<MockOutcome>,
+    init_errors: VecDeque<ErrorReport>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    changed: watch::Sender<()>,
+}
+
+impl Default for Shared {
+    fn default() -> Self {
+        Self {
+            state: Mutex::default(),
+            changed: watch::Sender::new(()),
+        }
+    }
+}
+
+impl Shared {
+    fn notify(&self) {
+        self.changed.send_replace(());
+    }
+
+    /// Wait until `f` returns a value, checking the state every time it changes.
+    async fn wait<T>(&self, mut f: impl FnMut(&mut State) -> Option<T>) -> T {
+        // Subscribing before the check makes sure that no change is missed.
+        let mut changed = self.changed.subscribe();
+        loop {
+            if let Some(value) = f(&mut self.state.lock().unwrap()) {
+                return value;
+            }
+            changed.changed().await.expect("the sender lives as long as the state");
+        }
+    }
+
+    fn complete(&self, outcome: MockOutcome) {
+        let mut state = self.state.lock().unwrap();
+        state.pending = state.pending.saturating_sub(1);
+        state.outcomes.push_back(outcome);
+        drop(state);
+        self.notify();
+    }
+}
+
+async fn serve(listener: TcpListener, shared: Arc<Shared>) {
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                // Errors like running out of file descriptors persist for a while, so back off
+                // instead of spinning on them.
+                tracing::debug!(error = %err, "mock Runtime API failed to accept a connection");
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+                continue;
+            }
+        };
+        let shared = shared.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |request| handle(shared.clone(), request));
+            if let Err(err) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!(error = %err, "mock Runtime API connection closed");
+            }
+        });
+    }
+}
+
+async fn handle(shared: Arc<Shared>, request: Request<Incoming>) -> Result<Response<Full<Bytes>>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let path = parts
+        .uri
+        .path()
+        .strip_prefix("/2018-06-01/runtime/")
+        .unwrap_or_default();
+    let segments: Vec<&str> = path.split('/').collect();
+
+    let response = match (parts.method, segments.as_slice()) {
+        (Method::GET, ["invocation", "next"]) => {
+            let invocation = shared.wait(|state| state.queue.pop_front()).await;
+            return Ok(invocation.into_response());
+        }
+        (Method::POST, ["invocation", request_id, "response"]) => {
+            let streaming = parts
+                .headers
+                .get("lambda-runtime-function-response-mode")
+                .is_some_and(|mode| mode == "streaming");
+            let request_id = request_id.to_string();
+            match body.collect().await {
+                Ok(collected) => {
+                    let trailers = collected.trailers().cloned();
+                    let body = collected.to_bytes();
+                    let (metadata_prelude, body) = match streaming {
+                        true => split_metadata_prelude(body),
+                        false => (None, body),
+                    };
+                    let result = match trailers.as_ref().and_then(stream_error) {
+                        Some(report) => Err(report),
+                        None => Ok(body),
+                    };
+                    shared.complete(MockOutcome {
+                        request_id,
+                        result,
+                        streaming,
+                        metadata_prelude,
+                    });
+                    accepted()
+                }
+                Err(err) => bad_request(err),
+            }
+        }
+        (Method::POST, ["invocation", request_id, "error"]) => match read_report(body).await {
+            Ok(report) => {
+                shared.complete(MockOutcome {
+                    request_id: request_id.to_string(),
+                    result: Err(report),
+                    streaming: false,
+                    metadata_prelude: None,
+                });
+                accepted()
+            }
+            Err(err) => bad_request(err),
+        },
+        (Method::POST, ["init", "error"]) => match read_report(body).await {
+            Ok(report) => {
+                shared.state.lock().unwrap().init_errors.push_back(report);
+                shared.notify();
+                accepted()
+            }
+            Err(err) => bad_request(err),
+        },
+        _ => status(StatusCode::NOT_FOUND, Bytes::new()),
+    };
+    Ok(response)
+}
+
+async fn read_report(body: Incoming) -> Result<ErrorReport, crate::Error> {
+    let body = body.collect().await?.to_bytes();
+    Ok(serde_json::from_slice(&body)?)
+}
+
+/// Split a streaming body into the metadata prelude and the streamed bytes, which are separated
+/// by 8 null bytes.
+fn split_metadata_prelude(body: Bytes) -> (Option<Bytes>, Bytes) {
+    const DELIMITER: [u8; 8] = [0; 8];
+    match body.windows(DELIMITER.len()).position(|window| window == DELIMITER) {
+        Some(index) => (Some(body.slice(..index)), body.slice(index + DELIMITER.len()..)),
+        None => (None, body),
+    }
+}
+
+/// Error reported in the trailers of a streaming response.
+fn stream_error(trailers: &HeaderMap) -> Option<ErrorReport> {
+    let error_type = trailers.get(STREAM_ERROR_TYPE_TRAILER)?.to_str().ok()?;
+    let report = trailers
+        .get(STREAM_ERROR_BODY_TRAILER)
+        .and_then(|body| BASE64_STANDARD.decode(body.as_bytes()).ok())
+        .and_then(|body| serde_json::from_slice(&body).ok());
+    Some(report.unwrap_or_else(|| ErrorReport::new(error_type, "")))
+}
+
+fn accepted() -> Response<Full<Bytes>> {
+    status(StatusCode::ACCEPTED, Bytes::from_static(br#"{"status":"OK"}"#))
+}
+
+fn bad_request(err: impl std::fmt::Display) -> Response<Full<Bytes>> {
+    status(StatusCode::BAD_REQUEST, Bytes::from(err.to_string()))
+}
+
+fn status(status: StatusCode, body: Bytes) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(body));
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{runtime::report_init_error, service_fn, Diagnostic, Error, LambdaEvent, Runtime, StreamResponse};
+    use lambda_runtime_api_client::{body::Body, Client};
+    use serde_json::{json, Value};
+
+    #[tokio::test]
+    async fn run_the_runtime_against_the_mock() {
+        let api = MockRuntimeApi::start().await.unwrap();
+        let ok = api.push_event(json!({ "value": 1 }));
+        let rejected = api.push(MockInvocation::json(json!({ "reject": true })).with_request_id("rejected"));
+        assert_eq!("rejected", rejected);
+
+        async fn func(event: LambdaEvent<Value>) -> Result<Value, Diagnostic> {
+            if event.payload["reject"] == json!(true) {
+                return Err(Diagnostic {
+                    error_type: "Rejected".into(),
+                    error_message: event.context.request_id,
+                });
+            }
+            Ok(event.payload)
+        }
+        Runtime::builder()
+            .endpoint(api.endpoint())
+            .config(api.config())
+            .build(service_fn(func))
+            .run_until(api.idle())
+            .await
+            .unwrap();
+
+        let outcome = api.next_outcome().await;
+        assert_eq!(ok, outcome.request_id);
+        assert_eq!(br#"{"value":1}"#, &outcome.result.unwrap()[..]);
+
+        let outcome = api.next_outcome().await;
+        assert_eq!("rejected", outcome.request_id);
+        let report = outcome.result.unwrap_err();
+        assert_eq!("Rejected", report.error_type);
+        assert_eq!("rejected", report.error_message);
+    }
+
+    #[tokio::test]
+    async fn record_streaming_responses_and_their_errors() {
+        let api = MockRuntimeApi::start().await.unwrap();
+        api.push_event(json!({ "fail": false }));
+        api.push_event(json!({ "fail": true }));
+
+        async fn func(
+            event: LambdaEvent<Value>,
+        ) -> Result<StreamResponse<impl futures::Stream<Item = Result<Bytes, Error>>>, Error> {
+            let mut chunks = vec![Ok(Bytes::from_static(b"hello "))];
+            if event.payload["fail"] == json!(true) {
+                chunks.push(Err(Error::from("stream broke")));
+            }
+            chunks.push(Ok(Bytes::from_static(b"world")));
+            Ok(StreamResponse::new(tokio_stream::iter(chunks)))
+        }
+        Runtime::builder()
+            .endpoint(api.endpoint())
+            .config(api.config())
+            .build(service_fn(func))
+            .run_until(api.idle())
+            .await
+            .unwrap();
+
+        let outcome = api.next_outcome().await;
+        assert!(outcome.streaming);
+        assert!(outcome.metadata_prelude.is_some());
+        assert_eq!(b"hello world", &outcome.result.unwrap()[..]);
+
+        let outcome = api.next_outcome().await;
+        let report = outcome.result.unwrap_err();
+        assert_eq!("Runtime.StreamError", report.error_type);
+        assert_eq!("stream broke", report.error_message);
+    }
+
+    #[tokio::test]
+    async fn record_init_errors() {
+        let api = MockRuntimeApi::start().await.unwrap();
+        let client = Client::builder().with_endpoint(api.endpoint()).build().unwrap();
+        report_init_error(&client, ErrorReport::new("ConfigError", "missing TABLE_NAME")).await;
+
+        let report = api.next_init_error().await;
+        assert_eq!("ConfigError", report.error_type);
+        assert_eq!("missing TABLE_NAME", report.error_message);
+
+        let request = http::Request::get("/2018-06-01/runtime/unknown")
+            .body(Body::empty())
+            .unwrap();
+        let response = client.call(request).await.unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{Context, ErrorReport};
+use std::{fmt, sync::Arc, time::Duration};
+
+/// Callbacks on the lifecycle of every invocation, to feed custom metrics or audit sinks
+/// without re-implementing the event loop of the runtime.
+///
+/// Register observers with [RuntimeBuilder::observe](crate::RuntimeBuilder::observe). Every
+/// callback has an empty default implementation, implement the ones you need. Callbacks run
+/// inline in the event loop, so they should return quickly and hand slow work to a task.
+///
+/// ```no_run
+/// use lambda_runtime::{service_fn, Context, Error, ErrorReport, InvocationObserver, LambdaEvent, Runtime};
+/// use serde_json::Value;
+/// use std::time::Duration;
+///
+/// struct Audit;
+///
+/// impl InvocationObserver for Audit {
+///     fn on_handler_complete(&self, context: &Context, duration: Duration) {
+///         tracing::info!(request_id = %context.request_id, ?duration, "handler completed");
+///     }
+///
+///     fn on_error(&self, context: &Context, error: &ErrorReport) {
+///         tracing::warn!(request_id = %context.request_id, %error, "invocation failed");
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     Runtime::builder()
+///         .observe(Audit)
+///         .build(service_fn(func))
+///         .run()
+///         .await
+/// }
+///
+/// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+///     Ok(event.payload)
+/// }
+/// ```
+pub trait InvocationObserver: Send + Sync + 'static {
+    /// Called when an invocation is received, before its payload is decoded.
+    fn on_event_received(&self, context: &Context) {
+        let _ = context;
+    }
+
+    /// Called when the handler completes, successfully or not, with the time it took to process
+    /// the event.
+    fn on_handler_complete(&self, context: &Context, duration: Duration) {
+        let _ = (context, duration);
+    }
+
+    /// Called when the Runtime API acknowledged the response or the error of the invocation.
+    fn on_response_posted(&self, context: &Context) {
+        let _ = context;
+    }
+
+    /// Called when the invocation fails, because its payload can't be decoded, or the handler
+    /// returns an error or panics, with the error reported to the Runtime API.
+    fn on_error(&self, context: &Context, error: &ErrorReport) {
+        let _ = (context, error);
+    }
+}
+
+/// Observers registered on a runtime, shared by all its workers.
+#[derive(Clone, Default)]
+pub struct Observers(Arc<Vec<Arc<dyn InvocationObserver>>>);
+
+impl Observers {
+    pub(crate) fn push(&mut self, observer: impl InvocationObserver) {
+        let mut observers = self.0.as_ref().clone();
+        observers.push(Arc::new(observer));
+        self.0 = Arc::new(observers);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn event_received(&self, context: &Context) {
+        self.0.iter().for_each(|observer| observer.on_event_received(context));
+    }
+
+    pub(crate) fn handler_complete(&self, context: &Context, duration: Duration) {
+        self.0
+            .iter()
+            .for_each(|observer| observer.on_handler_complete(context, duration));
+    }
+
+    pub(crate) fn response_posted(&self, context: &Context) {
+        self.0.iter().for_each(|observer| observer.on_response_posted(context));
+    }
+
+    pub(crate) fn error(&self, context: &Context, error: &ErrorReport) {
+        self.0.iter().for_each(|observer| observer.on_error(context, error));
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Observers").field(&self.0.len()).finish()
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{types::ToStreamErrorTrailer, Diagnostic, Error, FunctionResponse, IntoFunctionResponse};
+use crate::{
+    types::{ToStreamErrorTrailer, STREAM_ERROR_BODY_TRAILER, STREAM_ERROR_TYPE_TRAILER},
+    Error, ErrorReport, FunctionResponse, IntoDiagnostic, IntoFunctionResponse,
+};
 use bytes::Bytes;
 use http::{header::CONTENT_TYPE, Method, Request, Uri};
 use lambda_runtime_api_client::{body::Body, build_request};
@@ -28,7 +31,6 @@ impl IntoRequest for NextEventRequest {
 pub(crate) struct EventCompletionRequest<'a, R, B, S, D, E>
 where
     R: IntoFunctionResponse<B, S>,
-    B: Serialize,
     S: Stream<Item = Result<D, E>> + Unpin + Send + 'static,
     D: Into<Bytes> + Send,
     E: Into<Error> + Send + Debug,
@@ -42,7 +44,6 @@ where
 impl<'a, R, B, D, E, S> EventCompletionRequest<'a, R, B, S, D, E>
 where
     R: IntoFunctionResponse<B, S>,
-    B: Serialize,
     S: Stream<Item = Result<D, E>> + Unpin + Send + 'static,
     D: Into<Bytes> + Send,
     E: Into<Error> + Send + Debug,
@@ -67,13 +68,28 @@ where
     E: Into<Error> + Send + Debug,
 {
     fn into_req(self) -> Result<Request<Body>, Error> {
+        self.into_req_with(|body| Ok(serde_json::to_vec(&body)?.into()))
+    }
+}
+
+impl<R, B, S, D, E> EventCompletionRequest<'_, R, B, S, D, E>
+where
+    R: IntoFunctionResponse<B, S>,
+    S: Stream<Item = Result<D, E>> + Unpin + Send + 'static,
+    D: Into<Bytes> + Send,
+    E: Into<Error> + Send + Debug,
+{
+    /// Build the request, encoding a buffered response with `serialize`.
+    pub(crate) fn into_req_with(
+        self,
+        serialize: impl FnOnce(B) -> Result<Bytes, Error>,
+    ) -> Result<Request<Body>, Error> {
         match self.body.into_response() {
             FunctionResponse::BufferedResponse(body) => {
                 let uri = format!("/2018-06-01/runtime/invocation/{}/response", self.request_id);
                 let uri = Uri::from_str(&uri)?;
 
-                let body = serde_json::to_vec(&body)?;
-                let body = Body::from(body);
+                let body = Body::from(serialize(body)?);
 
                 let req = build_request().method(Method::POST).uri(uri).body(body)?;
                 Ok(req)
@@ -89,8 +105,8 @@ where
                 req_headers.insert("Lambda-Runtime-Function-Response-Mode", "streaming".parse()?);
                 // Report midstream errors using error trailers.
                 // See the details in Lambda Developer Doc: https://docs.aws.amazon.com/lambda/latest/dg/runtimes-custom.html#runtimes-custom-response-streaming
-                req_headers.append("Trailer", "Lambda-Runtime-Function-Error-Type".parse()?);
-                req_headers.append("Trailer", "Lambda-Runtime-Function-Error-Body".parse()?);
+                req_headers.append("Trailer", STREAM_ERROR_TYPE_TRAILER.parse()?);
+                req_headers.append("Trailer", STREAM_ERROR_BODY_TRAILER.parse()?);
                 req_headers.insert(
                     "Content-Type",
                     "application/vnd.awslambda.http-integration-response".parse()?,
@@ -114,20 +130,28 @@ where
                         return;
                     }
 
-                    if tx.send_data("\u{0}".repeat(8).into()).await.is_err() {
+                    if tx.send_data(Bytes::from_static(&[0; 8])).await.is_err() {
                         tracing::error!("Error sending metadata prelude delimiter, response channel closed");
                         return;
                     }
 
                     while let Some(chunk) = response.stream.next().await {
-                        let chunk = match chunk {
-                            Ok(chunk) => chunk.into(),
-                            Err(err) => err.into().to_tailer().into(),
-                        };
-
-                        if tx.send_data(chunk).await.is_err() {
-                            tracing::error!("Error sending response body chunk, response channel closed");
-                            return;
+                        match chunk {
+                            Ok(chunk) => {
+                                if tx.send_data(chunk.into()).await.is_err() {
+                                    tracing::error!("Error sending response body chunk, response channel closed");
+                                    return;
+                                }
+                            }
+                            Err(err) => {
+                                // The error ends the response, chunks after it are never sent.
+                                let err = err.into();
+                                tracing::error!(error = %err, "Streaming response failed, reporting it in trailers");
+                                if tx.send_trailers(err.to_trailers()).await.is_err() {
+                                    tracing::error!("Error sending response error trailers, response channel closed");
+                                }
+                                return;
+                            }
                         }
                     }
                 });
@@ -155,14 +179,14 @@ fn test_event_completion_request() {
 // /runtime/invocation/{AwsRequestId}/error
 pub(crate) struct EventErrorRequest<'a> {
     pub(crate) request_id: &'a str,
-    pub(crate) diagnostic: Diagnostic,
+    pub(crate) report: ErrorReport,
 }
 
 impl<'a> EventErrorRequest<'a> {
-    pub(crate) fn new(request_id: &'a str, diagnostic: impl Into<Diagnostic>) -> EventErrorRequest<'a> {
+    pub(crate) fn new(request_id: &'a str, error: impl IntoDiagnostic) -> EventErrorRequest<'a> {
         EventErrorRequest {
             request_id,
-            diagnostic: diagnostic.into(),
+            report: error.into_error_report(),
         }
     }
 }
@@ -171,7 +195,7 @@ impl IntoRequest for EventErrorRequest<'_> {
     fn into_req(self) -> Result<Request<Body>, Error> {
         let uri = format!("/2018-06-01/runtime/invocation/{}/error", self.request_id);
         let uri = Uri::from_str(&uri)?;
-        let body = serde_json::to_vec(&self.diagnostic)?;
+        let body = serde_json::to_vec(&self.report)?;
         let body = Body::from(body);
 
         let req = build_request()
@@ -183,6 +207,53 @@ impl IntoRequest for EventErrorRequest<'_> {
     }
 }
 
+// /runtime/init/error
+pub(crate) struct InitErrorRequest {
+    pub(crate) report: ErrorReport,
+}
+
+impl IntoRequest for InitErrorRequest {
+    fn into_req(self) -> Result<Request<Body>, Error> {
+        let body = serde_json::to_vec(&self.report)?;
+        let req = build_request()
+            .method(Method::POST)
+            .uri(Uri::from_static("/2018-06-01/runtime/init/error"))
+            .header("lambda-runtime-function-error-type", self.report.error_type.as_str())
+            .body(Body::from(body))?;
+        Ok(req)
+    }
+}
+
+// /runtime/restore/next
+pub(crate) struct RestoreNextRequest;
+
+impl IntoRequest for RestoreNextRequest {
+    fn into_req(self) -> Result<Request<Body>, Error> {
+        let req = build_request()
+            .method(Method::GET)
+            .uri(Uri::from_static("/2018-06-01/runtime/restore/next"))
+            .body(Default::default())?;
+        Ok(req)
+    }
+}
+
+// /runtime/restore/error
+pub(crate) struct RestoreErrorRequest {
+    pub(crate) report: ErrorReport,
+}
+
+impl IntoRequest for RestoreErrorRequest {
+    fn into_req(self) -> Result<Request<Body>, Error> {
+        let body = serde_json::to_vec(&self.report)?;
+        let req = build_request()
+            .method(Method::POST)
+            .uri(Uri::from_static("/2018-06-01/runtime/restore/error"))
+            .header("lambda-runtime-function-error-type", self.report.error_type.as_str())
+            .body(Body::from(body))?;
+        Ok(req)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -203,10 +274,7 @@ mod tests {
     fn test_event_error_request() {
         let req = EventErrorRequest {
             request_id: "id",
-            diagnostic: Diagnostic {
-                error_type: "InvalidEventDataError".into(),
-                error_message: "Error parsing event data".into(),
-            },
+            report: ErrorReport::new("InvalidEventDataError", "Error parsing event data"),
         };
         let req = req.into_req().unwrap();
         let expected = Uri::from_static("/2018-06-01/runtime/invocation/id/error");
@@ -218,6 +286,17 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_init_error_request() {
+        let req = InitErrorRequest {
+            report: ErrorReport::new("ConfigError", "missing table name"),
+        };
+        let req = req.into_req().unwrap();
+        assert_eq!(req.method(), Method::POST);
+        assert_eq!(req.uri(), &Uri::from_static("/2018-06-01/runtime/init/error"));
+        assert_eq!(req.headers()["lambda-runtime-function-error-type"], "ConfigError");
+    }
+
     #[test]
     #[cfg(tokio_unstable)]
     fn streaming_send_data_error_is_ignored() {
@@ -0,0 +1,230 @@
+use lambda_runtime_api_client::BoxError;
+use std::{
+    collections::hash_map::RandomState,
+    fmt,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    sync::Arc,
+    time::Duration,
+};
+use tracing::warn;
+
+/// Retry of a request to the Runtime API, passed to the hook set with
+/// [RetryPolicy::on_retry].
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct RetryAttempt<'a> {
+    /// Request that failed, `next` when polling for the next invocation, or `response` when
+    /// posting the response or the error of an invocation.
+    pub operation: &'static str,
+    /// Number of the attempt that failed, starting at 1.
+    pub attempt: u32,
+    /// Time the runtime waits before the next attempt.
+    pub delay: Duration,
+    /// Transport error of the failed attempt.
+    pub error: &'a BoxError,
+}
+
+/// Retries of the requests to the Runtime API that fail with a transport error, like a
+/// connection reset while the execution environment is recycled.
+///
+/// Failed requests are retried up to [RetryPolicy::DEFAULT_MAX_ATTEMPTS] times in total, after
+/// a random delay that doubles with every attempt, up to a maximum. The error of the last
+/// attempt stops the runtime, like without retries. Streaming responses aren't retried, since
+/// their body can't be replayed.
+///
+/// ```no_run
+/// use lambda_runtime::{service_fn, Error, LambdaEvent, RetryPolicy, Runtime};
+/// use serde_json::Value;
+/// use std::time::Duration;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let retry_policy = RetryPolicy::new()
+///         .with_max_attempts(5)
+///         .with_max_delay(Duration::from_millis(500))
+///         .on_retry(|attempt| tracing::info!(operation = attempt.operation, attempt = attempt.attempt, "retrying"));
+///     Runtime::builder()
+///         .retry_policy(retry_policy)
+///         .build(service_fn(func))
+///         .run()
+///         .await
+/// }
+///
+/// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+///     Ok(event.payload)
+/// }
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    on_retry: Option<Arc<OnRetry>>,
+}
+
+type OnRetry = dyn Fn(&RetryAttempt<'_>) + Send + Sync;
+
+impl RetryPolicy {
+    /// Attempts of a request used by [RetryPolicy::new], including the first one.
+    pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+    /// Delay before the first retry used by [RetryPolicy::new], before jitter.
+    pub const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(50);
+    /// Longest delay between two attempts used by [RetryPolicy::new], before jitter.
+    pub const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(1);
+
+    /// Create the default policy, which the runtime uses unless another one is set.
+    pub fn new() -> Self {
+        Self {
+            max_attempts: Self::DEFAULT_MAX_ATTEMPTS,
+            base_delay: Self::DEFAULT_BASE_DELAY,
+            max_delay: Self::DEFAULT_MAX_DELAY,
+            on_retry: None,
+        }
+    }
+
+    /// Create a policy that never retries, so that the first transport error stops the runtime.
+    pub fn none() -> Self {
+        Self::new().with_max_attempts(1)
+    }
+
+    /// Set the number of attempts of a request, including the first one.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the delay before the first retry, which doubles with every attempt.
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Set the longest delay between two attempts.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Call `hook` before every retry, to count the retries in metrics.
+    pub fn on_retry<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&RetryAttempt<'_>) + Send + Sync + 'static,
+    {
+        self.on_retry = Some(Arc::new(hook));
+        self
+    }
+
+    /// Whether a failed request is attempted again.
+    pub(crate) fn retries(&self) -> bool {
+        self.max_attempts > 1
+    }
+
+    /// Random delay before the attempt that follows `attempt`, between zero and the exponential
+    /// backoff, so that the retries of several execution environments don't synchronize.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        // `RandomState` is seeded randomly, which is enough for jitter without a RNG dependency.
+        let random = RandomState::new().build_hasher().finish();
+        ceiling.mul_f64((random as f64) / (u64::MAX as f64))
+    }
+
+    /// Call `request` until it succeeds or the attempts are exhausted.
+    pub(crate) async fn call<F, Fut, T>(&self, operation: &'static str, mut request: F) -> Result<T, BoxError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, BoxError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let error = match request().await {
+                Ok(response) => return Ok(response),
+                Err(error) if attempt >= self.max_attempts => return Err(error),
+                Err(error) => error,
+            };
+            let delay = self.backoff(attempt);
+            warn!(operation, attempt, ?delay, error = %error, "request to the Runtime API failed, retrying");
+            if let Some(on_retry) = &self.on_retry {
+                on_retry(&RetryAttempt {
+                    operation,
+                    attempt,
+                    delay,
+                    error: &error,
+                });
+            }
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("on_retry", &self.on_retry.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn backoff_is_bounded() {
+        let policy = RetryPolicy::new()
+            .with_base_delay(Duration::from_millis(10))
+            .with_max_delay(Duration::from_millis(30));
+        for attempt in 1..10 {
+            let ceiling = Duration::from_millis(10 * 2u64.pow(attempt - 1)).min(Duration::from_millis(30));
+            assert!(policy.backoff(attempt) <= ceiling);
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_transport_errors() {
+        let retries = Arc::new(AtomicU32::new(0));
+        let policy = RetryPolicy::new().with_base_delay(Duration::from_millis(1)).on_retry({
+            let retries = retries.clone();
+            move |attempt| {
+                assert_eq!("next", attempt.operation);
+                retries.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        let calls = AtomicU32::new(0);
+        let result = policy
+            .call("next", || async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => Err(BoxError::from("connection reset")),
+                    _ => Ok("event"),
+                }
+            })
+            .await;
+        assert_eq!("event", result.unwrap());
+        assert_eq!(2, retries.load(Ordering::SeqCst));
+
+        let calls = AtomicU32::new(0);
+        let result = policy
+            .call("next", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(BoxError::from("connection reset"))
+            })
+            .await;
+        assert_eq!("connection reset", result.unwrap_err().to_string());
+        assert_eq!(RetryPolicy::DEFAULT_MAX_ATTEMPTS, calls.load(Ordering::SeqCst));
+    }
+}
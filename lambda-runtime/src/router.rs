@@ -0,0 +1,304 @@
+use crate::{deserializer, Context, ErrorReport, IntoDiagnostic, LambdaEvent};
+use aws_lambda_events::{eventbridge::EventBridgeEvent, s3::S3Event, sns::SnsEvent, sqs::SqsEvent};
+use futures::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{fmt, future::Future, sync::Arc, task};
+use tower::Service;
+
+/// Source of an event, detected from the shape of its payload.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventSource {
+    /// Messages from an SQS queue, see [SqsEvent].
+    Sqs,
+    /// Notifications from an SNS topic, see [SnsEvent].
+    Sns,
+    /// Object notifications from an S3 bucket, see [S3Event].
+    S3,
+    /// Events from an EventBridge bus or scheduler, see [EventBridgeEvent].
+    EventBridge,
+}
+
+impl EventSource {
+    /// Detect the source of an event payload, `None` if it's not one of the known sources.
+    pub fn detect(payload: &Value) -> Option<Self> {
+        if let Some(record) = payload.get("Records").and_then(|records| records.get(0)) {
+            // SNS records use `EventSource`, every other service uses `eventSource`.
+            let source = record
+                .get("eventSource")
+                .or_else(|| record.get("EventSource"))
+                .and_then(Value::as_str);
+            return match source {
+                Some("aws:sqs") => Some(Self::Sqs),
+                Some("aws:sns") => Some(Self::Sns),
+                Some("aws:s3") => Some(Self::S3),
+                _ => None,
+            };
+        }
+
+        let is_eventbridge = ["detail-type", "source", "detail"]
+            .iter()
+            .all(|field| payload.get(field).is_some());
+        is_eventbridge.then_some(Self::EventBridge)
+    }
+}
+
+type BoxHandler<R> = Box<dyn Fn(Value, Context) -> BoxFuture<'static, Result<R, ErrorReport>> + Send + Sync>;
+
+struct Route<R> {
+    matches: Box<dyn Fn(&Value) -> bool + Send + Sync>,
+    handler: BoxHandler<R>,
+}
+
+/// Handler for functions wired to several triggers, that dispatches every event to the typed
+/// handler registered for its source.
+///
+/// Routes are checked in the order they are registered, events that no route matches are sent
+/// to the [fallback](EventRouter::fallback) handler, or fail with an `UnhandledEvent` error:
+///
+/// ```no_run
+/// use aws_lambda_events::{event::eventbridge::EventBridgeEvent, sqs::SqsEvent};
+/// use lambda_runtime::{router::EventRouter, Error, LambdaEvent};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize, Serialize)]
+/// struct OrderPlaced {
+///     order_id: String,
+/// }
+///
+/// async fn process_messages(event: LambdaEvent<SqsEvent>) -> Result<(), Error> {
+///     tracing::info!(messages = event.payload.records.len(), "received messages");
+///     Ok(())
+/// }
+///
+/// async fn place_order(event: LambdaEvent<EventBridgeEvent<OrderPlaced>>) -> Result<(), Error> {
+///     tracing::info!(order_id = %event.payload.detail.order_id, "order placed");
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let router = EventRouter::new()
+///         .on_sqs(process_messages)
+///         .on_eventbridge(place_order);
+///     lambda_runtime::run(router).await
+/// }
+/// ```
+pub struct EventRouter<R> {
+    routes: Vec<Arc<Route<R>>>,
+    fallback: Option<Arc<BoxHandler<R>>>,
+}
+
+impl<R: Send + 'static> EventRouter<R> {
+    /// Create a router without routes.
+    pub fn new() -> Self {
+        Self {
+            routes: Vec::new(),
+            fallback: None,
+        }
+    }
+
+    /// Handle the events that `matches` returns `true` for, deserialized into `T`.
+    pub fn on<T, F, Fut, E>(mut self, matches: impl Fn(&Value) -> bool + Send + Sync + 'static, handler: F) -> Self
+    where
+        T: DeserializeOwned + Send + 'static,
+        F: Fn(LambdaEvent<T>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, E>> + Send + 'static,
+        E: IntoDiagnostic,
+    {
+        let route = Route {
+            matches: Box::new(matches),
+            handler: boxed(handler),
+        };
+        self.routes.push(Arc::new(route));
+        self
+    }
+
+    /// Handle messages from SQS queues.
+    pub fn on_sqs<F, Fut, E>(self, handler: F) -> Self
+    where
+        F: Fn(LambdaEvent<SqsEvent>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, E>> + Send + 'static,
+        E: IntoDiagnostic,
+    {
+        self.on(source_is(EventSource::Sqs), handler)
+    }
+
+    /// Handle notifications from SNS topics.
+    pub fn on_sns<F, Fut, E>(self, handler: F) -> Self
+    where
+        F: Fn(LambdaEvent<SnsEvent>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, E>> + Send + 'static,
+        E: IntoDiagnostic,
+    {
+        self.on(source_is(EventSource::Sns), handler)
+    }
+
+    /// Handle object notifications from S3 buckets.
+    pub fn on_s3<F, Fut, E>(self, handler: F) -> Self
+    where
+        F: Fn(LambdaEvent<S3Event>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, E>> + Send + 'static,
+        E: IntoDiagnostic,
+    {
+        self.on(source_is(EventSource::S3), handler)
+    }
+
+    /// Handle EventBridge events, with their `detail` deserialized into `T`.
+    ///
+    /// To route events with different detail types to different handlers, use [EventRouter::on]
+    /// with a check of the `detail-type` field.
+    pub fn on_eventbridge<T, F, Fut, E>(self, handler: F) -> Self
+    where
+        T: serde::Serialize + DeserializeOwned + Send + 'static,
+        F: Fn(LambdaEvent<EventBridgeEvent<T>>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, E>> + Send + 'static,
+        E: IntoDiagnostic,
+    {
+        self.on(source_is(EventSource::EventBridge), handler)
+    }
+
+    /// Handle the events that no route matches.
+    pub fn fallback<F, Fut, E>(mut self, handler: F) -> Self
+    where
+        F: Fn(LambdaEvent<Value>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, E>> + Send + 'static,
+        E: IntoDiagnostic,
+    {
+        self.fallback = Some(Arc::new(boxed(handler)));
+        self
+    }
+}
+
+impl<R: Send + 'static> Default for EventRouter<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R> Clone for EventRouter<R> {
+    fn clone(&self) -> Self {
+        Self {
+            routes: self.routes.clone(),
+            fallback: self.fallback.clone(),
+        }
+    }
+}
+
+impl<R> fmt::Debug for EventRouter<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventRouter")
+            .field("routes", &self.routes.len())
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+impl<R: Send + 'static> Service<LambdaEvent<Value>> for EventRouter<R> {
+    type Response = R;
+    type Error = ErrorReport;
+    type Future = BoxFuture<'static, Result<R, ErrorReport>>;
+
+    fn poll_ready(&mut self, _cx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, event: LambdaEvent<Value>) -> Self::Future {
+        let (payload, context) = event.into_parts();
+        if let Some(route) = self.routes.iter().find(|route| (route.matches)(&payload)) {
+            return (route.handler)(payload, context);
+        }
+        match &self.fallback {
+            Some(fallback) => fallback(payload, context),
+            None => {
+                let report = ErrorReport::new("UnhandledEvent", "no handler is registered for this event");
+                Box::pin(futures::future::ready(Err(report)))
+            }
+        }
+    }
+}
+
+fn source_is(source: EventSource) -> impl Fn(&Value) -> bool + Send + Sync + 'static {
+    move |payload| EventSource::detect(payload) == Some(source)
+}
+
+fn boxed<T, R, F, Fut, E>(handler: F) -> BoxHandler<R>
+where
+    T: DeserializeOwned + Send + 'static,
+    R: Send + 'static,
+    F: Fn(LambdaEvent<T>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<R, E>> + Send + 'static,
+    E: IntoDiagnostic,
+{
+    Box::new(
+        move |payload, context| match deserializer::deserialize_value::<T>(payload, context) {
+            Ok(event) => {
+                let fut = handler(event);
+                Box::pin(async move { fut.await.map_err(IntoDiagnostic::into_error_report) })
+            }
+            Err(err) => Box::pin(futures::future::ready(Err(err.into_error_report()))),
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Error;
+    use serde::{Deserialize, Serialize};
+
+    fn event(fixture: &str) -> LambdaEvent<Value> {
+        let path = format!("{}/../lambda-events/src/fixtures/{fixture}", env!("CARGO_MANIFEST_DIR"));
+        let payload = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        LambdaEvent::new(payload, Context::default())
+    }
+
+    #[test]
+    fn detect_event_source() {
+        let cases = [
+            ("example-sqs-event.json", Some(EventSource::Sqs)),
+            ("example-sns-event.json", Some(EventSource::Sns)),
+            ("example-s3-event.json", Some(EventSource::S3)),
+            ("example-eventbridge-event-obj.json", Some(EventSource::EventBridge)),
+            ("example-eventbridge-schedule.json", Some(EventSource::EventBridge)),
+            ("example-dynamodb-event.json", None),
+        ];
+        for (fixture, source) in cases {
+            assert_eq!(source, EventSource::detect(&event(fixture).payload), "{fixture}");
+        }
+    }
+
+    #[tokio::test]
+    async fn dispatch_to_typed_handlers() {
+        #[derive(Deserialize, Serialize)]
+        struct Detail {
+            #[serde(rename = "instance-id")]
+            instance_id: String,
+        }
+
+        let mut router = EventRouter::new()
+            .on_sqs(|event: LambdaEvent<SqsEvent>| async move {
+                Ok::<_, Error>(format!("sqs:{}", event.payload.records.len()))
+            })
+            .on_s3(|event: LambdaEvent<S3Event>| async move {
+                Ok::<_, Error>(format!("s3:{}", event.payload.records.len()))
+            })
+            .on_eventbridge(|event: LambdaEvent<EventBridgeEvent<Detail>>| async move {
+                Ok::<_, Error>(format!("eventbridge:{}", event.payload.detail.instance_id))
+            });
+
+        assert_eq!("sqs:1", router.call(event("example-sqs-event.json")).await.unwrap());
+        assert_eq!("s3:1", router.call(event("example-s3-event.json")).await.unwrap());
+        assert_eq!(
+            "eventbridge:i-abcd1111",
+            router.call(event("example-eventbridge-event-obj.json")).await.unwrap()
+        );
+
+        let report = router.call(event("example-sns-event.json")).await.unwrap_err();
+        assert_eq!("UnhandledEvent", report.error_type);
+
+        let mut router = router.fallback(|_event: LambdaEvent<Value>| async { Ok::<_, Error>("fallback".into()) });
+        assert_eq!("fallback", router.call(event("example-sns-event.json")).await.unwrap());
+    }
+}
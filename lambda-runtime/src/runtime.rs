@@ -1,22 +1,53 @@
 use crate::{
-    layers::{CatchPanicService, RuntimeApiClientService, RuntimeApiResponseService},
-    requests::{IntoRequest, NextEventRequest},
-    types::{invoke_request_id, IntoFunctionResponse, LambdaEvent},
-    Config, Context, Diagnostic,
+    codec::{Deserializer, Json, Serializer},
+    diagnostic::ErrorReportHook,
+    event_body::PendingBody,
+    layers::{
+        CatchPanicService, DeadlineMargins, FailureDestination, ResponseSent, ResponseSizeLimit, ResponseSizeService,
+        RuntimeApiClientService, RuntimeApiResponseService,
+    },
+    observer::Observers,
+    requests::{InitErrorRequest, IntoRequest, NextEventRequest},
+    retry::RetryPolicy,
+    short_circuit::ShortCircuit,
+    snap_start::SnapStartHooks,
+    types::{invoke_request_id, InitializationType, IntoFunctionResponse, LambdaEvent},
+    warmup::WarmupHooks,
+    Config, Context, ErrorReport, IntoDiagnostic, InvocationObserver,
 };
+use bytes::Bytes;
 #[cfg(feature = "concurrency-tokio")]
 use futures::stream::FuturesUnordered;
 use http_body_util::BodyExt;
-use lambda_runtime_api_client::{BoxError, Client as ApiClient};
+
+#[cfg(all(unix, feature = "unix-socket"))]
+use lambda_runtime_api_client::connect::UnixConnector;
+use lambda_runtime_api_client::{
+    connect::{Connection, DynConnector},
+    BoxError, Client,
+};
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "concurrency-tokio")]
 use std::fmt;
-use std::{env, fmt::Debug, future::Future, sync::Arc};
+use std::{
+    env,
+    fmt::Debug,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, OnceLock,
+    },
+    time::Duration,
+};
+use tokio::sync::oneshot;
 use tokio_stream::{Stream, StreamExt};
-use tower::{Layer, Service, ServiceExt};
-use tracing::trace;
+use tower::{
+    layer::util::{Identity, Stack},
+    Layer, Service, ServiceBuilder, ServiceExt,
+};
 #[cfg(feature = "concurrency-tokio")]
-use tracing::{debug, error, info_span, warn, Instrument};
+use tracing::{debug, info_span, Instrument};
+use tracing::{error, trace};
 
 /* ----------------------------------------- INVOCATION ---------------------------------------- */
 
@@ -32,11 +63,15 @@ pub struct LambdaInvocation {
 
 /* ------------------------------------------ RUNTIME ------------------------------------------ */
 
+/// Client of the Runtime API, with the connector set on the [RuntimeBuilder].
+pub(crate) type ApiClient = Client<DynConnector>;
+
 /// Lambda runtime executing a handler function on incoming requests.
 ///
 /// Middleware can be added to a runtime using the [Runtime::layer] method in order to execute
 /// logic prior to processing the incoming request and/or after the response has been sent back
-/// to the Lambda Runtime API.
+/// to the Lambda Runtime API. To wrap only the handler with standard [tower] layers, create the
+/// runtime with [Runtime::builder].
 ///
 /// # Example
 /// ```no_run
@@ -60,26 +95,39 @@ pub struct Runtime<S> {
     config: Arc<Config>,
     client: Arc<ApiClient>,
     concurrency_limit: u32,
+    options: RunOptions,
+}
+
+/// Options of the run loops, set with the methods of [Runtime] and the [RuntimeBuilder].
+#[derive(Default)]
+struct RunOptions {
+    snap_start: SnapStartHooks,
+    warmup: WarmupHooks,
+    prefetch: bool,
+    retry: RetryPolicy,
+    stream_body: bool,
 }
 
 impl<F, EventPayload, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError>
     Runtime<
         RuntimeApiClientService<
-            RuntimeApiResponseService<
-                CatchPanicService<'_, F>,
-                EventPayload,
-                Response,
-                BufferedResponse,
-                StreamingResponse,
-                StreamItem,
-                StreamError,
+            ResponseSizeService<
+                RuntimeApiResponseService<
+                    CatchPanicService<'_, F>,
+                    EventPayload,
+                    Response,
+                    BufferedResponse,
+                    StreamingResponse,
+                    StreamItem,
+                    StreamError,
+                >,
             >,
         >,
     >
 where
     F: Service<LambdaEvent<EventPayload>, Response = Response>,
     F::Future: Future<Output = Result<Response, F::Error>>,
-    F::Error: Into<Diagnostic> + Debug,
+    F::Error: IntoDiagnostic + Debug,
     EventPayload: for<'de> Deserialize<'de>,
     Response: IntoFunctionResponse<BufferedResponse, StreamingResponse>,
     BufferedResponse: Serialize,
@@ -104,22 +152,37 @@ where
     /// (`AWS_LAMBDA_FUNCTION_NAME`, `AWS_LAMBDA_FUNCTION_MEMORY_SIZE`,
     /// `AWS_LAMBDA_FUNCTION_VERSION`, `AWS_LAMBDA_RUNTIME_API`).
     pub fn new(handler: F) -> Self {
-        trace!("Loading config from env");
-        let config = Arc::new(Config::from_env());
+        Runtime::from_service(DynConnector::default(), None, None, |client| {
+            wrap_handler(handler, client)
+        })
+    }
+}
+
+impl<S> Runtime<S> {
+    fn from_service(
+        connector: DynConnector,
+        endpoint: Option<http::Uri>,
+        config: Option<Config>,
+        make_service: impl FnOnce(Arc<ApiClient>) -> S,
+    ) -> Self {
+        let config = Arc::new(config.unwrap_or_else(|| {
+            trace!("Loading config from env");
+            Config::from_env()
+        }));
         let concurrency_limit = max_concurrency_from_env().unwrap_or(1).max(1);
         // Strategy: allocate all worker tasks up-front, so size the client pool to match.
         let pool_size = concurrency_limit as usize;
-        let client = Arc::new(
-            ApiClient::builder()
-                .with_pool_size(pool_size)
-                .build()
-                .expect("Unable to create a runtime client"),
-        );
+        let mut builder = Client::builder().with_connector(connector).with_pool_size(pool_size);
+        if let Some(endpoint) = endpoint {
+            builder = builder.with_endpoint(endpoint);
+        }
+        let client = Arc::new(builder.build().expect("Unable to create a runtime client"));
         Self {
-            service: wrap_handler(handler, client.clone()),
+            service: make_service(client.clone()),
             config,
             client,
             concurrency_limit,
+            options: RunOptions::default(),
         }
     }
 }
@@ -157,8 +220,508 @@ impl<S> Runtime<S> {
             config: self.config,
             service: layer.layer(self.service),
             concurrency_limit: self.concurrency_limit,
+            options: self.options,
+        }
+    }
+
+    /// Poll the Runtime API for the next invocation while the response of the previous
+    /// invocation is still being acknowledged, instead of waiting for the acknowledgement.
+    ///
+    /// The poll for the next invocation only starts once the response has been entirely
+    /// written to the connection, so the Runtime API always receives the response of an
+    /// invocation before the request for the next one, and the handler still processes
+    /// invocations one at a time. If posting the response fails, the runtime stops with that
+    /// error like it does without prefetching.
+    ///
+    /// Prefetching is disabled by default. It only applies to [Runtime::run],
+    /// [Runtime::run_concurrent] already polls for invocations from several workers.
+    pub fn prefetch_next_invocation(mut self, enabled: bool) -> Self {
+        self.options.prefetch = enabled;
+        self
+    }
+
+    /// Register a hook that runs before Lambda takes the SnapStart snapshot of the execution
+    /// environment, like closing connections that can't survive the snapshot.
+    ///
+    /// Hooks only run when the function uses SnapStart. When a hook fails, the error is
+    /// reported as an initialization error and the snapshot isn't taken. Checkpoint hooks run
+    /// in the reverse order they were registered.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use lambda_runtime::{service_fn, Error, LambdaEvent, Runtime};
+    /// use serde_json::Value;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     Runtime::new(service_fn(func))
+    ///         .on_checkpoint(|| async {
+    ///             tracing::info!("closing connections before the snapshot");
+    ///             Ok::<_, Error>(())
+    ///         })
+    ///         .on_restore(|| async {
+    ///             tracing::info!("refreshing credentials after the restore");
+    ///             Ok::<_, Error>(())
+    ///         })
+    ///         .run()
+    ///         .await
+    /// }
+    ///
+    /// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+    ///     Ok(event.payload)
+    /// }
+    /// ```
+    pub fn on_checkpoint<F, Fut, E>(mut self, hook: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: IntoDiagnostic,
+    {
+        self.options.snap_start.on_checkpoint(hook);
+        self
+    }
+
+    /// Register a hook that runs after the execution environment is restored from the SnapStart
+    /// snapshot, before the first invocation, like re-seeding random number generators,
+    /// refreshing credentials, or re-establishing connections.
+    ///
+    /// Hooks only run when the function uses SnapStart. When a hook fails, the error is
+    /// reported as a restore error. Restore hooks run in the order they were registered.
+    pub fn on_restore<F, Fut, E>(mut self, hook: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: IntoDiagnostic,
+    {
+        self.options.snap_start.on_restore(hook);
+        self
+    }
+
+    /// Register a hook that runs once before the runtime polls for the first invocation, like
+    /// opening the connections of a pool or loading a cache, so the first invocation doesn't
+    /// pay for them.
+    ///
+    /// Warmup hooks run concurrently, after the SnapStart restore hooks, so connections opened
+    /// by a hook aren't captured in the snapshot. Failed hooks are logged and don't stop the
+    /// runtime, since the connections are opened again by the first invocation that needs them.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use lambda_runtime::{service_fn, Error, LambdaEvent, Runtime};
+    /// use serde_json::Value;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     Runtime::new(service_fn(func))
+    ///         .on_warmup(|| async {
+    ///             tracing::info!("opening database connections");
+    ///             Ok::<_, Error>(())
+    ///         })
+    ///         .run()
+    ///         .await
+    /// }
+    ///
+    /// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+    ///     Ok(event.payload)
+    /// }
+    /// ```
+    pub fn on_warmup<F, Fut, E>(mut self, hook: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: IntoDiagnostic,
+    {
+        self.options.warmup.push(hook);
+        self
+    }
+
+    /// Register a hook that runs once before the first invocation, only when the execution
+    /// environment was initialized for provisioned concurrency, like pre-loading a cache.
+    ///
+    /// Provisioned environments are initialized ahead of the invocations, so work done there
+    /// doesn't add latency to any request, while on-demand environments would delay their first
+    /// invocation. The hooks run with the [warmup hooks](Runtime::on_warmup), and like them,
+    /// failures are logged without stopping the runtime. The initialization type of the
+    /// environment is also available in [Context::init_type](crate::Context::init_type).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use lambda_runtime::{service_fn, Error, LambdaEvent, Runtime};
+    /// use serde_json::Value;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     Runtime::new(service_fn(func))
+    ///         .on_provisioned_init(|| async {
+    ///             tracing::info!("loading the product catalog");
+    ///             Ok::<_, Error>(())
+    ///         })
+    ///         .run()
+    ///         .await
+    /// }
+    ///
+    /// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+    ///     Ok(event.payload)
+    /// }
+    /// ```
+    pub fn on_provisioned_init<F, Fut, E>(mut self, hook: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: IntoDiagnostic,
+    {
+        self.options.warmup.push_provisioned(hook);
+        self
+    }
+
+    async fn run_snap_start_hooks(snap_start: SnapStartHooks, client: &ApiClient) -> Result<(), BoxError> {
+        if snap_start.is_empty() || InitializationType::from_env() != InitializationType::SnapStart {
+            return Ok(());
+        }
+        snap_start.run(client).await
+    }
+}
+
+impl Runtime<()> {
+    /// Create a [RuntimeBuilder] to wrap the handler with [tower] layers, like timeouts,
+    /// concurrency limits, or retries, before creating the runtime.
+    ///
+    /// Layers added to the builder wrap the handler itself, so they receive the deserialized
+    /// [LambdaEvent] and the handler's response. Layers added with [Runtime::layer] wrap the
+    /// whole invocation instead.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use lambda_runtime::{Error, LambdaEvent, Runtime};
+    /// use serde_json::Value;
+    /// use std::time::Duration;
+    /// use tower::{limit::ConcurrencyLimitLayer, service_fn, timeout::TimeoutLayer};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     Runtime::builder()
+    ///         .layer(TimeoutLayer::new(Duration::from_secs(5)))
+    ///         .layer(ConcurrencyLimitLayer::new(1))
+    ///         .build(service_fn(func))
+    ///         .run()
+    ///         .await
+    /// }
+    ///
+    /// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+    ///     Ok(event.payload)
+    /// }
+    /// ```
+    pub fn builder() -> RuntimeBuilder<Identity> {
+        RuntimeBuilder {
+            layers: ServiceBuilder::new(),
+            codec: Json,
+            prefetch: false,
+            connector: DynConnector::default(),
+            endpoint: None,
+            config: None,
+            options: InvocationOptions::default(),
+        }
+    }
+
+    /// Run the initialization of the function, like opening database connections or fetching
+    /// configuration, before the runtime starts polling for events.
+    ///
+    /// When the initialization fails, the error is reported to the Runtime API as an
+    /// initialization error, with the `errorType` of the error, and returned so that `main`
+    /// can exit. Lambda then shows the error in the logs and the response of the invocation,
+    /// instead of an opaque `Runtime.ExitError`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use lambda_runtime::{service_fn, Error, LambdaEvent, Runtime};
+    /// use serde_json::Value;
+    ///
+    /// struct Settings {
+    ///     table_name: String,
+    /// }
+    ///
+    /// async fn load_settings() -> Result<Settings, Error> {
+    ///     let table_name = std::env::var("TABLE_NAME")?;
+    ///     Ok(Settings { table_name })
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let settings = Runtime::init(load_settings).await?;
+    ///     let settings = &settings;
+    ///     Runtime::new(service_fn(move |event: LambdaEvent<Value>| async move {
+    ///         Ok::<_, Error>(format!("{}: {}", settings.table_name, event.payload))
+    ///     }))
+    ///     .run()
+    ///     .await
+    /// }
+    /// ```
+    pub async fn init<F, Fut, T, E>(init: F) -> Result<T, ErrorReport>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: IntoDiagnostic + Debug,
+    {
+        let error = match init().await {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+        error!(?error, "function initialization failed");
+        let report = error.into_error_report();
+        match Client::builder().build() {
+            Ok(client) => report_init_error(&client, report.clone()).await,
+            Err(err) => error!(error = %err, "initialization error can't be reported to the Runtime API"),
+        }
+        Err(report)
+    }
+}
+
+/// Builder that wraps a handler with [tower] layers before creating a [Runtime].
+///
+/// See [Runtime::builder].
+#[derive(Clone)]
+pub struct RuntimeBuilder<L, C = Json> {
+    layers: ServiceBuilder<L>,
+    codec: C,
+    prefetch: bool,
+    connector: DynConnector,
+    endpoint: Option<http::Uri>,
+    config: Option<Config>,
+    options: InvocationOptions,
+}
+
+impl<L: Debug, C: Debug> Debug for RuntimeBuilder<L, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RuntimeBuilder")
+            .field("layers", &self.layers)
+            .field("codec", &self.codec)
+            .field("prefetch", &self.prefetch)
+            .field("connector", &self.connector)
+            .field("endpoint", &self.endpoint)
+            .field("config", &self.config)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+impl<L, C> RuntimeBuilder<L, C> {
+    /// Add a layer around the handler. For an incoming event, this layer will be executed
+    /// before any layer that is added after it.
+    pub fn layer<T>(self, layer: T) -> RuntimeBuilder<Stack<T, L>, C> {
+        RuntimeBuilder {
+            layers: self.layers.layer(layer),
+            codec: self.codec,
+            prefetch: self.prefetch,
+            connector: self.connector,
+            endpoint: self.endpoint,
+            config: self.config,
+            options: self.options,
+        }
+    }
+
+    /// Set the codec that decodes the payload of invocations into the handler's event type,
+    /// and encodes its buffered responses, [Json] by default.
+    ///
+    /// See [Raw](crate::codec::Raw) to receive payloads without parsing them, or implement
+    /// [Deserializer] and [Serializer] for other formats.
+    pub fn codec<T>(self, codec: T) -> RuntimeBuilder<L, T> {
+        RuntimeBuilder {
+            layers: self.layers,
+            codec,
+            prefetch: self.prefetch,
+            connector: self.connector,
+            endpoint: self.endpoint,
+            config: self.config,
+            options: self.options,
         }
     }
+
+    /// Poll for the next invocation while the response of the previous one is acknowledged,
+    /// see [Runtime::prefetch_next_invocation].
+    pub fn prefetch_next_invocation(mut self, enabled: bool) -> Self {
+        self.prefetch = enabled;
+        self
+    }
+
+    /// Set the endpoint of the Runtime API, instead of the `AWS_LAMBDA_RUNTIME_API` environment
+    /// variable, to run behind a local emulator, a sidecar proxy, or in an integration test
+    /// harness.
+    ///
+    /// The endpoint is a URI with the host and port of the API, and an optional base path that
+    /// prefixes the paths of the Runtime API, like `http://127.0.0.1:9001/proxy`.
+    pub fn endpoint(mut self, endpoint: http::Uri) -> Self {
+        self.endpoint = Some(endpoint);
+        self
+    }
+
+    /// Set the configuration of the function, instead of reading it from the
+    /// `AWS_LAMBDA_FUNCTION_*` environment variables, to run the runtime in tests, like against a
+    /// [MockRuntimeApi](crate::mock::MockRuntimeApi).
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Connect to the Runtime API through the Unix socket at `path`.
+    ///
+    /// The host of the [endpoint](RuntimeBuilder::endpoint) is only sent in the `Host` header,
+    /// it defaults to `http://localhost` when no endpoint is set.
+    #[cfg(all(unix, feature = "unix-socket"))]
+    #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix-socket"))))]
+    pub fn unix_socket(mut self, path: impl AsRef<std::path::Path>) -> Self {
+        self.connector = DynConnector::new(UnixConnector::new(path));
+        self.endpoint
+            .get_or_insert_with(|| http::Uri::from_static("http://localhost"));
+        self
+    }
+
+    /// Register an observer notified of the lifecycle of every invocation, see
+    /// [InvocationObserver]. Observers are notified in the order they were registered.
+    pub fn observe(mut self, observer: impl InvocationObserver) -> Self {
+        self.options.observers.push(observer);
+        self
+    }
+
+    /// Set how long before the deadline of an invocation the runtime stops waiting for its
+    /// [background tasks](crate::BackgroundTasks) and posts the response,
+    /// [BackgroundTasks::DEFAULT_MARGIN](crate::BackgroundTasks::DEFAULT_MARGIN) by default.
+    pub fn background_tasks_margin(mut self, margin: Duration) -> Self {
+        self.options.margins.background_tasks = margin;
+        self
+    }
+
+    /// Set how long before the deadline of an invocation the runtime cancels the
+    /// [cancellation token](Context::cancellation_token) of the invocation, 500ms by default.
+    pub fn cancellation_margin(mut self, margin: Duration) -> Self {
+        self.options.margins.cancellation = margin;
+        self
+    }
+
+    /// Check the size of the buffered responses before they're posted, see [ResponseSizeLimit].
+    pub fn response_size_limit(mut self, limit: ResponseSizeLimit) -> Self {
+        self.options.size_limit = Some(limit);
+        self
+    }
+
+    /// Respond to synthetic invocations, like warmup pings or health checks, without decoding
+    /// their payload or calling the handler, see [ShortCircuit].
+    pub fn short_circuit(mut self, short_circuit: ShortCircuit) -> Self {
+        self.options.short_circuits.push(short_circuit);
+        self
+    }
+
+    /// Set the connector that opens the connections to the Runtime API, hyper's plain TCP
+    /// `HttpConnector` by default.
+    ///
+    /// Use it to connect over TLS with the connectors of `hyper-rustls` or `hyper-tls`, through
+    /// the Unix socket of a local emulator, or with a connector that records the traffic in tests.
+    pub fn connector<T>(mut self, connector: T) -> Self
+    where
+        T: Service<http::Uri> + Clone + Send + Sync + 'static,
+        T::Response: hyper::rt::Read + hyper::rt::Write + Connection + Send + Unpin + 'static,
+        T::Future: Send + 'static,
+        T::Error: Into<BoxError>,
+    {
+        self.connector = DynConnector::new(connector);
+        self
+    }
+
+    /// Rewrite every [ErrorReport] of the handler, including panics, before it's sent to the
+    /// Runtime API.
+    ///
+    /// Use it to report `errorType` values that Step Functions retry and catch policies can
+    /// match on, without implementing [IntoDiagnostic] for every error type:
+    ///
+    /// ```no_run
+    /// use lambda_runtime::{service_fn, Error, LambdaEvent, Runtime};
+    /// use serde_json::Value;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     Runtime::builder()
+    ///         .map_error_report(|mut report| {
+    ///             report.error_type = format!("Orders.{}", report.error_type);
+    ///             report
+    ///         })
+    ///         .build(service_fn(func))
+    ///         .run()
+    ///         .await
+    /// }
+    ///
+    /// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+    ///     Ok(event.payload)
+    /// }
+    /// ```
+    pub fn map_error_report<H>(mut self, hook: H) -> Self
+    where
+        H: Fn(ErrorReport) -> ErrorReport + Send + Sync + 'static,
+    {
+        self.options.error_report_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Set how the requests to the Runtime API that fail with a transport error are retried, see
+    /// [RetryPolicy]. Use [RetryPolicy::none] to stop the runtime on the first error.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.options.retry = policy;
+        self
+    }
+
+    /// Hand the failed invocations, with their payload and error, to a [FailureDestination]
+    /// before their error is reported to the Runtime API.
+    pub fn on_failure(mut self, destination: FailureDestination) -> Self {
+        self.options.failure_destination = Some(destination);
+        self
+    }
+
+    /// Create a runtime that executes the handler, wrapped with the layers, for incoming requests.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if required Lambda environment variables are missing, see [Runtime::new].
+    #[allow(clippy::type_complexity)]
+    pub fn build<'a, F, EventPayload, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError>(
+        self,
+        handler: F,
+    ) -> Runtime<
+        RuntimeApiClientService<
+            ResponseSizeService<
+                RuntimeApiResponseService<
+                    CatchPanicService<'a, L::Service>,
+                    EventPayload,
+                    Response,
+                    BufferedResponse,
+                    StreamingResponse,
+                    StreamItem,
+                    StreamError,
+                    C,
+                >,
+            >,
+        >,
+    >
+    where
+        L: Layer<F>,
+        L::Service: Service<LambdaEvent<EventPayload>, Response = Response>,
+        <L::Service as Service<LambdaEvent<EventPayload>>>::Future:
+            Future<Output = Result<Response, <L::Service as Service<LambdaEvent<EventPayload>>>::Error>>,
+        <L::Service as Service<LambdaEvent<EventPayload>>>::Error: IntoDiagnostic + Debug,
+        C: Deserializer<EventPayload> + Serializer<BufferedResponse> + Clone,
+        Response: IntoFunctionResponse<BufferedResponse, StreamingResponse>,
+        StreamingResponse: Stream<Item = Result<StreamItem, StreamError>> + Unpin + Send + 'static,
+        StreamItem: Into<bytes::Bytes> + Send,
+        StreamError: Into<BoxError> + Send + Debug,
+    {
+        let handler = self.layers.service(handler);
+        let retry = self.options.retry.clone();
+        let stream_body = Deserializer::<EventPayload>::streams_body(&self.codec);
+        let mut runtime = Runtime::from_service(self.connector, self.endpoint, self.config, |client| {
+            wrap_handler_with(handler, client, self.codec, self.options)
+        })
+        .prefetch_next_invocation(self.prefetch);
+        runtime.options.retry = retry;
+        runtime.options.stream_body = stream_body;
+        runtime
+    }
 }
 
 #[cfg(feature = "concurrency-tokio")]
@@ -184,16 +747,36 @@ where
         if tokio::runtime::Handle::try_current().is_err() {
             panic!("`run_concurrent` must be called from within a Tokio runtime");
         }
+        Self::run_snap_start_hooks(self.options.snap_start, &self.client).await?;
+        self.options
+            .warmup
+            .run(INIT_TYPE.get_or_init(InitializationType::from_env))
+            .await;
 
         if self.concurrency_limit > 1 {
             trace!("Concurrent mode: _X_AMZN_TRACE_ID is not set; use context.xray_trace_id");
-            Self::run_concurrent_inner(self.service, self.config, self.client, self.concurrency_limit).await
+            Self::run_concurrent_inner(
+                self.service,
+                self.config,
+                self.client,
+                self.concurrency_limit,
+                self.options.retry,
+                self.options.stream_body,
+            )
+            .await
         } else {
             debug!(
                 "Concurrent polling disabled (AWS_LAMBDA_MAX_CONCURRENCY unset or <= 1); falling back to sequential polling"
             );
-            let incoming = incoming(&self.client);
-            Self::run_with_incoming(self.service, self.config, incoming).await
+            let incoming = incoming_with_retry(&self.client, self.options.retry);
+            Self::run_with_incoming_until(
+                self.service,
+                self.config,
+                incoming,
+                std::future::pending(),
+                self.options.stream_body,
+            )
+            .await
         }
     }
 
@@ -203,6 +786,8 @@ where
         config: Arc<Config>,
         client: Arc<ApiClient>,
         concurrency_limit: u32,
+        retry: RetryPolicy,
+        stream_body: bool,
     ) -> Result<(), BoxError> {
         let limit = concurrency_limit as usize;
 
@@ -211,9 +796,10 @@ where
         let mut workers: FuturesUnordered<tokio::task::JoinHandle<(tokio::task::Id, Result<(), BoxError>)>> =
             FuturesUnordered::new();
         let spawn_worker = |service: S, config: Arc<Config>, client: Arc<ApiClient>| {
+            let retry = retry.clone();
             tokio::spawn(async move {
                 let task_id = tokio::task::id();
-                let result = concurrent_worker_loop(service, config, client).await;
+                let result = concurrent_worker_loop(service, config, client, retry, stream_body).await;
                 (task_id, result)
             })
         };
@@ -371,22 +957,139 @@ where
                 eprintln!("AWS_LAMBDA_MAX_CONCURRENCY is set to '{raw}', but the concurrency-tokio feature is not enabled; running sequentially");
             }
         }
-        let incoming = incoming(&self.client);
-        Self::run_with_incoming(self.service, self.config, incoming).await
+        Self::run_snap_start_hooks(self.options.snap_start, &self.client).await?;
+        self.options
+            .warmup
+            .run(INIT_TYPE.get_or_init(InitializationType::from_env))
+            .await;
+        let RunOptions {
+            prefetch,
+            retry,
+            stream_body,
+            ..
+        } = self.options;
+        if prefetch {
+            return Self::run_prefetching(self.service, self.config, &self.client, retry, stream_body).await;
+        }
+        let incoming = incoming_with_retry(&self.client, retry);
+        Self::run_with_incoming_until(self.service, self.config, incoming, std::future::pending(), stream_body).await
+    }
+
+    /// Start the runtime like [Runtime::run], and stop polling for events once `shutdown`
+    /// resolves.
+    ///
+    /// The invocation being processed when `shutdown` resolves completes and its response is
+    /// posted before the runtime returns. Invocations are processed sequentially, without
+    /// prefetching the next invocation.
+    ///
+    /// This is useful to embed the runtime in integration tests, local emulators, or extensions
+    /// that need to stop the runtime deterministically:
+    ///
+    /// ```no_run
+    /// use lambda_runtime::{service_fn, Error, LambdaEvent, Runtime};
+    /// use serde_json::Value;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    ///     tokio::spawn(async move {
+    ///         tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+    ///         let _ = stop.send(());
+    ///     });
+    ///     Runtime::new(service_fn(func))
+    ///         .run_until(async move {
+    ///             let _ = stopped.await;
+    ///         })
+    ///         .await
+    /// }
+    ///
+    /// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+    ///     Ok(event.payload)
+    /// }
+    /// ```
+    pub async fn run_until<F>(self, shutdown: F) -> Result<(), BoxError>
+    where
+        F: Future<Output = ()>,
+    {
+        Self::run_snap_start_hooks(self.options.snap_start, &self.client).await?;
+        self.options
+            .warmup
+            .run(INIT_TYPE.get_or_init(InitializationType::from_env))
+            .await;
+        let incoming = incoming_with_retry(&self.client, self.options.retry);
+        Self::run_with_incoming_until(self.service, self.config, incoming, shutdown, self.options.stream_body).await
+    }
+
+    /// Run loop that polls for the next invocation as soon as the response of the current one
+    /// has been sent, see [Runtime::prefetch_next_invocation].
+    pub(crate) async fn run_prefetching(
+        mut service: S,
+        config: Arc<Config>,
+        client: &ApiClient,
+        retry: RetryPolicy,
+        stream_body: bool,
+    ) -> Result<(), BoxError> {
+        let next_event_future = || retry.call("next", || next_event_future(client));
+        let mut next_event = next_event_future().await?;
+        loop {
+            trace!("New event arrived (prefetch loop)");
+            let (sent_tx, sent_rx) = oneshot::channel();
+            next_event.extensions_mut().insert(ResponseSent::new(sent_tx));
+
+            let invocation = process_invocation(&mut service, &config, next_event, true, stream_body);
+            tokio::pin!(invocation);
+            next_event = tokio::select! {
+                biased;
+                result = &mut invocation => {
+                    result?;
+                    next_event_future().await?
+                }
+                _ = sent_rx => {
+                    trace!("Response sent, waiting for next event (prefetch loop)");
+                    let ((), next_event) = tokio::try_join!(invocation, next_event_future())?;
+                    next_event
+                }
+            };
+        }
     }
 
-    /// Internal utility function to start the runtime with a customized incoming stream.
-    /// This implements the core of the [Runtime::run] method.
+    /// Internal utility function to start the runtime with a customized incoming stream, which
+    /// buffers the body of invocations like the core of the [Runtime::run] method.
+    #[cfg(test)]
     pub(crate) async fn run_with_incoming(
+        service: S,
+        config: Arc<Config>,
+        incoming: impl Stream<Item = Result<http::Response<hyper::body::Incoming>, BoxError>> + Send,
+    ) -> Result<(), BoxError> {
+        Self::run_with_incoming_until(service, config, incoming, std::future::pending(), false).await
+    }
+
+    /// Run loop of [Runtime::run_until], which stops polling the incoming stream once `shutdown`
+    /// resolves.
+    async fn run_with_incoming_until(
         mut service: S,
         config: Arc<Config>,
         incoming: impl Stream<Item = Result<http::Response<hyper::body::Incoming>, BoxError>> + Send,
+        shutdown: impl Future<Output = ()>,
+        stream_body: bool,
     ) -> Result<(), BoxError> {
         tokio::pin!(incoming);
-        while let Some(next_event_response) = incoming.next().await {
+        tokio::pin!(shutdown);
+        loop {
+            let next_event_response = tokio::select! {
+                biased;
+                () = &mut shutdown => {
+                    trace!("Shutdown requested, stopping the run loop");
+                    break;
+                }
+                next_event_response = incoming.next() => next_event_response,
+            };
+            let Some(next_event_response) = next_event_response else {
+                break;
+            };
             trace!("New event arrived (run loop)");
             let event = next_event_response?;
-            process_invocation(&mut service, &config, event, true).await?;
+            process_invocation(&mut service, &config, event, true, stream_body).await?;
         }
         Ok(())
     }
@@ -394,25 +1097,53 @@ where
 
 /* ------------------------------------------- UTILS ------------------------------------------- */
 
+/// Options of the services that process every invocation, set with the [RuntimeBuilder].
+#[derive(Clone, Default)]
+struct InvocationOptions {
+    error_report_hook: Option<ErrorReportHook>,
+    observers: Observers,
+    margins: DeadlineMargins,
+    size_limit: Option<ResponseSizeLimit>,
+    short_circuits: Vec<ShortCircuit>,
+    failure_destination: Option<FailureDestination>,
+    retry: RetryPolicy,
+}
+
+impl Debug for InvocationOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InvocationOptions")
+            .field("error_report_hook", &self.error_report_hook.is_some())
+            .field("observers", &self.observers)
+            .field("margins", &self.margins)
+            .field("size_limit", &self.size_limit)
+            .field("short_circuits", &self.short_circuits)
+            .field("failure_destination", &self.failure_destination)
+            .field("retry", &self.retry)
+            .finish()
+    }
+}
+
 #[allow(clippy::type_complexity)]
 fn wrap_handler<'a, F, EventPayload, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError>(
     handler: F,
     client: Arc<ApiClient>,
 ) -> RuntimeApiClientService<
-    RuntimeApiResponseService<
-        CatchPanicService<'a, F>,
-        EventPayload,
-        Response,
-        BufferedResponse,
-        StreamingResponse,
-        StreamItem,
-        StreamError,
+    ResponseSizeService<
+        RuntimeApiResponseService<
+            CatchPanicService<'a, F>,
+            EventPayload,
+            Response,
+            BufferedResponse,
+            StreamingResponse,
+            StreamItem,
+            StreamError,
+        >,
     >,
 >
 where
     F: Service<LambdaEvent<EventPayload>, Response = Response>,
     F::Future: Future<Output = Result<Response, F::Error>>,
-    F::Error: Into<Diagnostic> + Debug,
+    F::Error: IntoDiagnostic + Debug,
     EventPayload: for<'de> Deserialize<'de>,
     Response: IntoFunctionResponse<BufferedResponse, StreamingResponse>,
     BufferedResponse: Serialize,
@@ -420,26 +1151,93 @@ where
     StreamItem: Into<bytes::Bytes> + Send,
     StreamError: Into<BoxError> + Send + Debug,
 {
-    let safe_service = CatchPanicService::new(handler);
-    let response_service = RuntimeApiResponseService::new(safe_service);
-    RuntimeApiClientService::new(response_service, client)
+    wrap_handler_with(handler, client, Json, InvocationOptions::default())
+}
+
+#[allow(clippy::type_complexity)]
+fn wrap_handler_with<'a, F, EventPayload, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError, C>(
+    handler: F,
+    client: Arc<ApiClient>,
+    codec: C,
+    options: InvocationOptions,
+) -> RuntimeApiClientService<
+    ResponseSizeService<
+        RuntimeApiResponseService<
+            CatchPanicService<'a, F>,
+            EventPayload,
+            Response,
+            BufferedResponse,
+            StreamingResponse,
+            StreamItem,
+            StreamError,
+            C,
+        >,
+    >,
+>
+where
+    F: Service<LambdaEvent<EventPayload>, Response = Response>,
+    F::Future: Future<Output = Result<Response, F::Error>>,
+    F::Error: IntoDiagnostic + Debug,
+    C: Deserializer<EventPayload> + Serializer<BufferedResponse> + Clone,
+    Response: IntoFunctionResponse<BufferedResponse, StreamingResponse>,
+    StreamingResponse: Stream<Item = Result<StreamItem, StreamError>> + Unpin + Send + 'static,
+    StreamItem: Into<bytes::Bytes> + Send,
+    StreamError: Into<BoxError> + Send + Debug,
+{
+    let InvocationOptions {
+        error_report_hook,
+        observers,
+        margins,
+        size_limit,
+        short_circuits,
+        failure_destination,
+        retry,
+    } = options;
+    let safe_service = CatchPanicService::with_hook(handler, error_report_hook.clone());
+    let response_service = RuntimeApiResponseService::new(
+        safe_service,
+        codec,
+        observers.clone(),
+        margins,
+        short_circuits.into(),
+        failure_destination,
+    )
+    .with_error_report_hook(error_report_hook);
+    let size_service = ResponseSizeService::new(response_service, size_limit);
+    RuntimeApiClientService::new(size_service, client, observers, retry)
+}
+
+pub(crate) async fn report_init_error(client: &ApiClient, report: ErrorReport) {
+    let result = match (InitErrorRequest { report }).into_req() {
+        Ok(req) => client.call(req).await.map(|_| ()),
+        Err(err) => Err(err),
+    };
+    if let Err(err) = result {
+        error!(error = %err, "initialization error can't be reported to the Runtime API");
+    }
 }
 
+#[cfg(test)]
 fn incoming(
     client: &ApiClient,
+) -> impl Stream<Item = Result<http::Response<hyper::body::Incoming>, BoxError>> + Send + '_ {
+    incoming_with_retry(client, RetryPolicy::none())
+}
+
+fn incoming_with_retry(
+    client: &ApiClient,
+    retry: RetryPolicy,
 ) -> impl Stream<Item = Result<http::Response<hyper::body::Incoming>, BoxError>> + Send + '_ {
     async_stream::stream! {
         loop {
             trace!("Waiting for next event (incoming loop)");
-            let req = NextEventRequest.into_req().expect("Unable to construct request");
-            let res = client.call(req).await;
+            let res = retry.call("next", || next_event_future(client)).await;
             yield res;
         }
     }
 }
 
 /// Creates a future that polls the `/next` endpoint.
-#[cfg(feature = "concurrency-tokio")]
 async fn next_event_future(client: &ApiClient) -> Result<http::Response<hyper::body::Incoming>, BoxError> {
     let req = NextEventRequest.into_req()?;
     client.call(req).await
@@ -457,7 +1255,13 @@ fn concurrency_env_value() -> Option<String> {
 }
 
 #[cfg(feature = "concurrency-tokio")]
-async fn concurrent_worker_loop<S>(mut service: S, config: Arc<Config>, client: Arc<ApiClient>) -> Result<(), BoxError>
+async fn concurrent_worker_loop<S>(
+    mut service: S,
+    config: Arc<Config>,
+    client: Arc<ApiClient>,
+    retry: RetryPolicy,
+    stream_body: bool,
+) -> Result<(), BoxError>
 where
     S: Service<LambdaInvocation, Response = (), Error = BoxError>,
     S::Future: Send,
@@ -465,30 +1269,32 @@ where
     let task_id = tokio::task::id();
     let span = info_span!("worker", task_id = %task_id);
     loop {
-        let event = match next_event_future(client.as_ref()).instrument(span.clone()).await {
-            Ok(event) => event,
-            Err(e) => {
-                warn!(task_id = %task_id, error = %e, "Error polling /next, retrying");
-                continue;
-            }
-        };
+        let event = retry
+            .call("next", || next_event_future(client.as_ref()))
+            .instrument(span.clone())
+            .await?;
 
-        process_invocation(&mut service, &config, event, false)
+        process_invocation(&mut service, &config, event, false, stream_body)
             .instrument(span.clone())
             .await?;
     }
 }
 
+// Invocations processed by this execution environment, shared by all the workers of a runtime.
+static INVOCATION_COUNT: AtomicU64 = AtomicU64::new(0);
+static INIT_TYPE: OnceLock<InitializationType> = OnceLock::new();
+
 async fn process_invocation<S>(
     service: &mut S,
     config: &Arc<Config>,
     event: http::Response<hyper::body::Incoming>,
     set_amzn_trace_env: bool,
+    stream_body: bool,
 ) -> Result<(), BoxError>
 where
     S: Service<LambdaInvocation, Response = (), Error = BoxError>,
 {
-    let (parts, incoming) = event.into_parts();
+    let (mut parts, incoming) = event.into_parts();
 
     #[cfg(debug_assertions)]
     if parts.status == http::StatusCode::NO_CONTENT {
@@ -500,8 +1306,17 @@ where
 
     // Build the invocation such that it can be sent to the service right away
     // when it is ready
-    let body = incoming.collect().await?.to_bytes();
-    let context = Context::new(invoke_request_id(&parts.headers)?, config.clone(), &parts.headers)?;
+    let body = if stream_body {
+        // The codec reads the body from the extensions, as the handler consumes it.
+        parts.extensions.insert(PendingBody::new(incoming));
+        Bytes::new()
+    } else {
+        incoming.collect().await?.to_bytes()
+    };
+    let mut context = Context::new(invoke_request_id(&parts.headers)?, config.clone(), &parts.headers)?;
+    context.invocation_count = INVOCATION_COUNT.fetch_add(1, Ordering::Relaxed) + 1;
+    context.cold_start = context.invocation_count == 1;
+    context.init_type = INIT_TYPE.get_or_init(InitializationType::from_env).clone();
     let invocation = LambdaInvocation { parts, body, context };
 
     if set_amzn_trace_env {
@@ -530,14 +1345,20 @@ fn amzn_trace_env(ctx: &Context) {
 
 #[cfg(test)]
 mod endpoint_tests {
-    use super::{incoming, wrap_handler};
+    use super::{incoming, report_init_error, wrap_handler, wrap_handler_with, InvocationOptions};
     use crate::{
+        codec::{Json, Raw},
+        observer::Observers,
         requests::{EventCompletionRequest, EventErrorRequest, IntoRequest, NextEventRequest},
-        Config, Diagnostic, Error, Runtime,
+        Config, Context, Diagnostic, Error, ErrorReport, InvocationObserver, LambdaEvent, RetryPolicy, Runtime,
+        ShortCircuit,
     };
+    use base64::prelude::*;
     use bytes::Bytes;
     use futures::future::BoxFuture;
-    use http::{HeaderValue, Method, Request, Response, StatusCode};
+    #[cfg(any(all(unix, feature = "unix-socket"), feature = "concurrency-tokio"))]
+    use http::Method;
+    use http::{HeaderValue, Request, Response, StatusCode};
     use http_body_util::{BodyExt, Full};
     use httpmock::prelude::*;
 
@@ -546,18 +1367,19 @@ mod endpoint_tests {
         rt::{tokio::TokioIo, TokioExecutor},
         server::conn::auto::Builder as ServerBuilder,
     };
-    use lambda_runtime_api_client::Client;
-    use std::{
-        convert::Infallible,
-        env,
-        sync::{
-            atomic::{AtomicUsize, Ordering},
-            Arc,
-        },
-        time::Duration,
+    use lambda_runtime_api_client::{
+        connect::{DynConnector, HttpConnector},
+        Client,
     };
-    use tokio::{net::TcpListener, sync::Notify};
+    use serde_json::Value;
+    #[cfg(feature = "concurrency-tokio")]
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::{convert::Infallible, env, sync::Arc, time::Duration};
+    use tokio::net::TcpListener;
+    #[cfg(feature = "concurrency-tokio")]
+    use tokio::sync::Notify;
     use tokio_stream::StreamExt;
+    use tower::util::MapRequestLayer;
 
     #[tokio::test]
     async fn test_next_event() -> Result<(), Error> {
@@ -620,6 +1442,31 @@ mod endpoint_tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_init_error_response() -> Result<(), Error> {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/2018-06-01/runtime/init/error")
+                .header("lambda-runtime-function-error-type", "ConfigError")
+                .json_body(serde_json::json!({
+                    "errorType": "ConfigError",
+                    "errorMessage": "missing TABLE_NAME",
+                }));
+            then.status(202).body("");
+        });
+
+        let base = server.base_url().parse().expect("Invalid mock server Uri");
+        let client = Client::builder().with_endpoint(base).build()?;
+
+        report_init_error(&client, ErrorReport::new("ConfigError", "missing TABLE_NAME")).await;
+        mock.assert_async().await;
+
+        let value = Runtime::init(|| async { Ok::<_, Error>(42) }).await?;
+        assert_eq!(42, value);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_error_response() -> Result<(), Error> {
         let diagnostic = Diagnostic {
@@ -642,7 +1489,7 @@ mod endpoint_tests {
 
         let req = EventErrorRequest {
             request_id: "156cb537-e2d4-11e8-9b34-d36013741fb9",
-            diagnostic,
+            report: diagnostic.into(),
         };
         let req = req.into_req()?;
         let rsp = client.call(req).await?;
@@ -652,6 +1499,116 @@ mod endpoint_tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[cfg(all(unix, feature = "unix-socket"))]
+    async fn unix_socket_endpoint_with_base_path() -> Result<(), Error> {
+        use tokio::net::UnixListener;
+
+        let path = env::temp_dir().join(format!("lambda-runtime-api-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path)?;
+
+        let paths = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let server_paths = paths.clone();
+        let server_handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let service = service_fn(move |req: Request<Incoming>| {
+                server_paths.lock().unwrap().push(req.uri().path().to_string());
+                async move {
+                    let response = match *req.method() {
+                        Method::GET => Response::builder()
+                            .header("lambda-runtime-aws-request-id", "request-1")
+                            .header("lambda-runtime-deadline-ms", "1542409706888")
+                            .body(Full::new(Bytes::from_static(b"{}"))),
+                        _ => Response::builder()
+                            .status(StatusCode::ACCEPTED)
+                            .body(Full::new(Bytes::new())),
+                    };
+                    Ok::<_, Infallible>(response.unwrap())
+                }
+            });
+            let _ = ServerBuilder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(stream), service)
+                .await;
+        });
+
+        let builder = Runtime::builder().unix_socket(&path);
+        assert_eq!(Some(http::Uri::from_static("http://localhost")), builder.endpoint);
+
+        let f = crate::service_fn(|event: LambdaEvent<Value>| async { Ok::<_, Error>(event.payload) });
+        let runtime = builder
+            .endpoint("http://localhost/proxy".parse()?)
+            .config(Config::default())
+            .build(f);
+        let incoming = incoming(&runtime.client).take(1);
+        Runtime::run_with_incoming(runtime.service, runtime.config, incoming).await?;
+
+        server_handle.abort();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(
+            vec![
+                "/proxy/2018-06-01/runtime/invocation/next",
+                "/proxy/2018-06-01/runtime/invocation/request-1/response",
+            ],
+            *paths.lock().unwrap()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_streaming_response_reports_errors_in_trailers() -> Result<(), Error> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let base: http::Uri = format!("http://{}", listener.local_addr()?).parse()?;
+        let (received_tx, received_rx) = tokio::sync::oneshot::channel();
+
+        let server_handle = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let received_tx = Arc::new(std::sync::Mutex::new(Some(received_tx)));
+            let service = service_fn(move |req: Request<Incoming>| {
+                let received_tx = received_tx.clone();
+                async move {
+                    let (parts, body) = req.into_parts();
+                    let collected = body.collect().await.unwrap();
+                    let trailers = collected.trailers().cloned();
+                    if let Some(tx) = received_tx.lock().unwrap().take() {
+                        let _ = tx.send((parts.headers, collected.to_bytes(), trailers));
+                    }
+                    Ok::<_, Infallible>(Response::new(Full::new(Bytes::new())))
+                }
+            });
+            let _ = ServerBuilder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(tcp), service)
+                .await;
+        });
+
+        let stream = tokio_stream::iter(vec![
+            Ok::<_, Error>(Bytes::from_static(b"first chunk")),
+            Err("stream broke".into()),
+            Ok(Bytes::from_static(b"never sent")),
+        ]);
+        let req: EventCompletionRequest<'_, _, (), _, _, _> = EventCompletionRequest::new(
+            "156cb537-e2d4-11e8-9b34-d36013741fb9",
+            crate::StreamResponse::new(stream),
+        );
+        let client = Client::builder().with_endpoint(base).build()?;
+        let rsp = client.call(req.into_req()?).await?;
+        assert_eq!(rsp.status(), StatusCode::OK);
+
+        let (headers, body, trailers) = tokio::time::timeout(Duration::from_secs(2), received_rx).await??;
+        assert_eq!(headers["lambda-runtime-function-response-mode"], "streaming");
+        assert_eq!(headers["transfer-encoding"], "chunked");
+        assert!(body.ends_with(b"\0\0\0\0\0\0\0\0first chunk"));
+
+        let trailers = trailers.expect("missing error trailers");
+        assert_eq!(trailers["lambda-runtime-function-error-type"], "Runtime.StreamError");
+        let error_body = BASE64_STANDARD.decode(trailers["lambda-runtime-function-error-body"].as_bytes())?;
+        let diagnostic: serde_json::Value = serde_json::from_slice(&error_body)?;
+        assert_eq!(diagnostic["errorMessage"], "stream broke");
+
+        server_handle.abort();
+        Ok(())
+    }
+
     #[tokio::test]
     async fn successful_end_to_end_run() -> Result<(), Error> {
         let server = MockServer::start();
@@ -677,7 +1634,10 @@ mod endpoint_tests {
         let client = Client::builder().with_endpoint(base).build()?;
 
         async fn func(event: crate::LambdaEvent<serde_json::Value>) -> Result<serde_json::Value, Error> {
-            let (event, _) = event.into_parts();
+            let (event, context) = event.into_parts();
+            // Other tests may process invocations concurrently, so the exact count is unknown.
+            assert!(context.invocation_count >= 1);
+            assert_eq!(context.invocation_count == 1, context.cold_start);
             Ok(event)
         }
         let f = crate::service_fn(func);
@@ -709,6 +1669,7 @@ mod endpoint_tests {
             config: Arc::new(config),
             service: wrap_handler(f, client),
             concurrency_limit: 1,
+            options: Default::default(),
         };
         let client = &runtime.client;
         let incoming = incoming(client).take(1);
@@ -719,6 +1680,391 @@ mod endpoint_tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn stream_the_body_of_invocations() -> Result<(), Error> {
+        let server = MockServer::start();
+        let request_id = "9d1a3e5b-2d87-4b6f-a1f5-0c4c3b1e7a42";
+
+        let next_request = server.mock(|when, then| {
+            when.method(GET).path("/2018-06-01/runtime/invocation/next");
+            then.status(200)
+                .header("lambda-runtime-aws-request-id", request_id)
+                .header("lambda-runtime-deadline-ms", "1542409706888")
+                .body("first\nsecond\nthird\n");
+        });
+        let next_response = server.mock(|when, then| {
+            when.method(POST)
+                .path(format!("/2018-06-01/runtime/invocation/{request_id}/response"))
+                .body("3");
+            then.status(200).body("");
+        });
+
+        let base = server.base_url().parse().expect("Invalid mock server Uri");
+        let client = Arc::new(Client::builder().with_endpoint(base).build()?);
+
+        async fn count_lines(event: crate::LambdaEvent<crate::EventBody>) -> Result<usize, Error> {
+            let mut lines = tokio::io::AsyncBufReadExt::lines(tokio::io::BufReader::new(event.payload));
+            let mut count = 0;
+            while lines.next_line().await?.is_some() {
+                count += 1;
+            }
+            Ok(count)
+        }
+        let service = wrap_handler_with(
+            crate::service_fn(count_lines),
+            client.clone(),
+            crate::codec::Streamed,
+            Default::default(),
+        );
+
+        let incoming = incoming(&client).take(1);
+        let config = Arc::new(Config::default());
+        Runtime::run_with_incoming_until(service, config, incoming, std::future::pending(), true).await?;
+
+        next_request.assert_async().await;
+        next_response.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn prefetch_next_invocation_while_the_response_is_acknowledged() -> Result<(), Error> {
+        let server = MockServer::start();
+        let request_id = "156cb537-e2d4-11e8-9b34-d36013741fb9";
+
+        let next_request = server.mock(|when, then| {
+            when.method(GET).path("/2018-06-01/runtime/invocation/next");
+            then.status(200)
+                .header("content-type", "application/json")
+                .header("lambda-runtime-aws-request-id", request_id)
+                .header("lambda-runtime-deadline-ms", "1542409706888")
+                .body("{}");
+        });
+        let next_response = server.mock(|when, then| {
+            when.method(POST)
+                .path(format!("/2018-06-01/runtime/invocation/{request_id}/response"))
+                .body("{}");
+            then.status(202).delay(Duration::from_millis(500));
+        });
+
+        let base = server.base_url().parse().expect("Invalid mock server Uri");
+        let client = Arc::new(Client::builder().with_endpoint(base).build()?);
+        let f = crate::service_fn(|event: crate::LambdaEvent<Value>| async { Ok::<_, Error>(event.payload) });
+        let service = wrap_handler(f, client.clone());
+        let run = Runtime::run_prefetching(
+            service,
+            Arc::new(Config::default()),
+            &client,
+            RetryPolicy::none(),
+            false,
+        );
+
+        // The second poll starts while the Runtime API delays the acknowledgement of the first
+        // response, and the second invocation waits for that acknowledgement.
+        tokio::select! {
+            result = run => panic!("runtime stopped: {result:?}"),
+            _ = tokio::time::sleep(Duration::from_millis(250)) => {}
+        }
+        assert_eq!(2, next_request.calls_async().await);
+        assert_eq!(1, next_response.calls_async().await);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn raw_codec_passes_payloads_through() -> Result<(), Error> {
+        let server = MockServer::start();
+        let request_id = "156cb537-e2d4-11e8-9b34-d36013741fb9";
+
+        let next_request = server.mock(|when, then| {
+            when.method(GET).path("/2018-06-01/runtime/invocation/next");
+            then.status(200)
+                .header("content-type", "application/octet-stream")
+                .header("lambda-runtime-aws-request-id", request_id)
+                .header("lambda-runtime-deadline-ms", "1542409706888")
+                .body("hello, world");
+        });
+        let next_response = server.mock(|when, then| {
+            when.method(POST)
+                .path(format!("/2018-06-01/runtime/invocation/{request_id}/response"))
+                .body("dlrow ,olleh");
+            then.status(200).body("");
+        });
+
+        let base = server.base_url().parse().expect("Invalid mock server Uri");
+        let client = Arc::new(Client::builder().with_endpoint(base).build()?);
+
+        async fn func(event: LambdaEvent<Bytes>) -> Result<Vec<u8>, Error> {
+            Ok(event.payload.iter().rev().copied().collect())
+        }
+        let runtime = Runtime {
+            client: client.clone(),
+            config: Arc::new(Config::default()),
+            service: wrap_handler_with(crate::service_fn(func), client, Raw, Default::default()),
+            concurrency_limit: 1,
+            options: Default::default(),
+        };
+        let incoming = incoming(&runtime.client).take(1);
+        Runtime::run_with_incoming(runtime.service, runtime.config, incoming).await?;
+
+        next_request.assert_async().await;
+        next_response.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn custom_connector_opens_the_connections() -> Result<(), Error> {
+        let server = MockServer::start();
+        let request_id = "156cb537-e2d4-11e8-9b34-d36013741fb9";
+
+        let next_request = server.mock(|when, then| {
+            when.method(GET).path("/2018-06-01/runtime/invocation/next");
+            then.status(200)
+                .header("content-type", "application/json")
+                .header("lambda-runtime-aws-request-id", request_id)
+                .header("lambda-runtime-deadline-ms", "1542409706888")
+                .body("{}");
+        });
+        let next_response = server.mock(|when, then| {
+            when.method(POST)
+                .path(format!("/2018-06-01/runtime/invocation/{request_id}/response"))
+                .body("{}");
+            then.status(200).body("");
+        });
+
+        // Records the connections that the client opens.
+        let connections = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = connections.clone();
+        let connector = tower::service_fn(move |uri: http::Uri| {
+            recorded.lock().unwrap().push(uri.clone());
+            let mut connector = HttpConnector::new();
+            async move { tower::Service::call(&mut connector, uri).await }
+        });
+
+        let base = server.base_url().parse().expect("Invalid mock server Uri");
+        let client = Arc::new(
+            Client::builder()
+                .with_connector(DynConnector::new(connector))
+                .with_endpoint(base)
+                .build()?,
+        );
+        let f = crate::service_fn(|event: LambdaEvent<Value>| async { Ok::<_, Error>(event.payload) });
+        let service = wrap_handler(f, client.clone());
+        Runtime::run_with_incoming(service, Arc::new(Config::default()), incoming(&client).take(1)).await?;
+
+        next_request.assert_async().await;
+        next_response.assert_async().await;
+        let connections = connections.lock().unwrap();
+        assert!(!connections.is_empty());
+        assert!(connections.iter().all(|uri| uri.port_u16() == Some(server.port())));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn observers_follow_the_invocation_lifecycle() -> Result<(), Error> {
+        let server = MockServer::start();
+        let request_id = "156cb537-e2d4-11e8-9b34-d36013741fb9";
+
+        server.mock(|when, then| {
+            when.method(GET).path("/2018-06-01/runtime/invocation/next");
+            then.status(200)
+                .header("content-type", "application/json")
+                .header("lambda-runtime-aws-request-id", request_id)
+                .header("lambda-runtime-deadline-ms", "1542409706888")
+                .body(r#"{"fail": true}"#);
+        });
+        let error_response = server.mock(|when, then| {
+            when.method(POST)
+                .path(format!("/2018-06-01/runtime/invocation/{request_id}/error"));
+            then.status(202).body("");
+        });
+
+        #[derive(Default)]
+        struct Recorder(std::sync::Mutex<Vec<String>>);
+
+        impl InvocationObserver for Arc<Recorder> {
+            fn on_event_received(&self, context: &Context) {
+                self.0.lock().unwrap().push(format!("received {}", context.request_id));
+            }
+
+            fn on_handler_complete(&self, _context: &Context, _duration: Duration) {
+                self.0.lock().unwrap().push("complete".into());
+            }
+
+            fn on_response_posted(&self, _context: &Context) {
+                self.0.lock().unwrap().push("posted".into());
+            }
+
+            fn on_error(&self, _context: &Context, error: &ErrorReport) {
+                self.0.lock().unwrap().push(format!("error {}", error.error_type));
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let mut observers = Observers::default();
+        observers.push(recorder.clone());
+
+        let base = server.base_url().parse().expect("Invalid mock server Uri");
+        let client = Arc::new(Client::builder().with_endpoint(base).build()?);
+        let f = crate::service_fn(|_: LambdaEvent<Value>| async {
+            Err::<Value, _>(Diagnostic {
+                error_type: "OutOfStock".into(),
+                error_message: "no more items".into(),
+            })
+        });
+        let service = wrap_handler_with(
+            f,
+            client.clone(),
+            Json,
+            InvocationOptions {
+                observers,
+                ..Default::default()
+            },
+        );
+        Runtime::run_with_incoming(service, Arc::new(Config::default()), incoming(&client).take(1)).await?;
+
+        error_response.assert_async().await;
+        assert_eq!(
+            vec![
+                format!("received {request_id}"),
+                "complete".into(),
+                "error OutOfStock".into(),
+                "posted".into()
+            ],
+            *recorder.0.lock().unwrap()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn short_circuit_skips_the_handler() -> Result<(), Error> {
+        let server = MockServer::start();
+        let request_id = "156cb537-e2d4-11e8-9b34-d36013741fb9";
+
+        server.mock(|when, then| {
+            when.method(GET).path("/2018-06-01/runtime/invocation/next");
+            then.status(200)
+                .header("content-type", "application/json")
+                .header("lambda-runtime-aws-request-id", request_id)
+                .header("lambda-runtime-deadline-ms", "1542409706888")
+                .body(r#"{"warmup":true}"#);
+        });
+        let next_response = server.mock(|when, then| {
+            when.method(POST)
+                .path(format!("/2018-06-01/runtime/invocation/{request_id}/response"))
+                .body(r#"{"warmup":true}"#);
+            then.status(202);
+        });
+
+        // The payload of the ping isn't a valid event for the handler, which is never called.
+        let f = crate::service_fn(|_: LambdaEvent<u32>| async { Err::<u32, _>(Error::from("handler called")) });
+        let runtime = Runtime::builder()
+            .endpoint(server.base_url().parse()?)
+            .config(Config::default())
+            .short_circuit(ShortCircuit::warmup())
+            .build(f);
+        let incoming = incoming(&runtime.client).take(1);
+        Runtime::run_with_incoming(runtime.service, runtime.config, incoming).await?;
+
+        next_response.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn run_until_stops_after_the_current_invocation() -> Result<(), Error> {
+        let server = MockServer::start();
+        let request_id = "156cb537-e2d4-11e8-9b34-d36013741fb9";
+
+        let next_request = server.mock(|when, then| {
+            when.method(GET).path("/2018-06-01/runtime/invocation/next");
+            then.status(200)
+                .header("content-type", "application/json")
+                .header("lambda-runtime-aws-request-id", request_id)
+                .header("lambda-runtime-deadline-ms", "1542409706888")
+                .body("{}");
+        });
+        let next_response = server.mock(|when, then| {
+            when.method(POST)
+                .path(format!("/2018-06-01/runtime/invocation/{request_id}/response"))
+                .body("{}");
+            then.status(202);
+        });
+
+        let base = server.base_url().parse().expect("Invalid mock server Uri");
+        let client = Arc::new(Client::builder().with_endpoint(base).build()?);
+        // The handler requests the shutdown, which lets its invocation complete.
+        let (stop, stopped) = tokio::sync::oneshot::channel();
+        let stop = std::sync::Mutex::new(Some(stop));
+        let f = crate::service_fn(move |event: LambdaEvent<Value>| {
+            if let Some(stop) = stop.lock().unwrap().take() {
+                let _ = stop.send(());
+            }
+            async move { Ok::<_, Error>(event.payload) }
+        });
+        let runtime = Runtime {
+            client: client.clone(),
+            config: Arc::new(Config::default()),
+            service: wrap_handler(f, client),
+            concurrency_limit: 1,
+            options: Default::default(),
+        };
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            runtime.run_until(async move {
+                let _ = stopped.await;
+            }),
+        )
+        .await
+        .expect("runtime didn't stop")?;
+
+        next_request.assert_calls_async(1).await;
+        next_response.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn builder_layers_wrap_the_handler() -> Result<(), Error> {
+        let server = MockServer::start();
+        let request_id = "156cb537-e2d4-11e8-9b34-d36013741fb9";
+
+        let next_request = server.mock(|when, then| {
+            when.method(GET).path("/2018-06-01/runtime/invocation/next");
+            then.status(200)
+                .header("content-type", "application/json")
+                .header("lambda-runtime-aws-request-id", request_id)
+                .header("lambda-runtime-deadline-ms", "1542409706888")
+                .body("{}");
+        });
+        let next_response = server.mock(|when, then| {
+            when.method(POST)
+                .path(format!("/2018-06-01/runtime/invocation/{request_id}/response"))
+                .body(r#"{"outer":{"inner":{}}}"#);
+            then.status(200).body("");
+        });
+
+        fn wrap(key: &'static str) -> MapRequestLayer<impl Fn(LambdaEvent<Value>) -> LambdaEvent<Value> + Clone> {
+            MapRequestLayer::new(move |mut event: LambdaEvent<Value>| {
+                event.payload = serde_json::json!({ key: event.payload });
+                event
+            })
+        }
+        async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+            Ok(event.payload)
+        }
+
+        // The first layer added is the outermost one, so it wraps the payload last.
+        let runtime = Runtime::builder()
+            .layer(wrap("inner"))
+            .layer(wrap("outer"))
+            .endpoint(server.base_url().parse()?)
+            .config(Config::default())
+            .build(crate::service_fn(func));
+        let incoming = incoming(&runtime.client).take(1);
+        Runtime::run_with_incoming(runtime.service, runtime.config, incoming).await?;
+
+        next_request.assert_async().await;
+        next_response.assert_async().await;
+        Ok(())
+    }
+
     async fn run_panicking_handler<F>(func: F) -> Result<(), Error>
     where
         F: FnMut(crate::LambdaEvent<serde_json::Value>) -> BoxFuture<'static, Result<serde_json::Value, Error>>
@@ -764,6 +2110,7 @@ mod endpoint_tests {
             config,
             service: wrap_handler(f, client),
             concurrency_limit: 1,
+            options: Default::default(),
         };
         let client = &runtime.client;
         let incoming = incoming(client).take(1);
@@ -917,6 +2264,7 @@ mod endpoint_tests {
             }),
             service: wrap_handler(handler, client),
             concurrency_limit: 2,
+            options: Default::default(),
         };
 
         let res = tokio::time::timeout(Duration::from_secs(2), runtime.run_concurrent()).await;
@@ -1051,6 +2399,7 @@ mod endpoint_tests {
             }),
             service,
             concurrency_limit: 3,
+            options: Default::default(),
         };
 
         let runtime_handle = tokio::spawn(async move { runtime.run_concurrent().await });
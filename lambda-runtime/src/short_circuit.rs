@@ -0,0 +1,103 @@
+use crate::Context;
+use bytes::Bytes;
+use serde::Deserialize;
+use std::{fmt, sync::Arc};
+
+/// Canned response for synthetic invocations, like warmup pings or health checks, that skips
+/// the deserialization of the payload and the handler.
+///
+/// Register short circuits with
+/// [RuntimeBuilder::short_circuit](crate::RuntimeBuilder::short_circuit). Every invocation is
+/// checked against them, in the order they were registered, before its payload is decoded. The
+/// response of the first one that matches is posted as is, as the serialized response of the
+/// invocation.
+///
+/// ```no_run
+/// use bytes::Bytes;
+/// use lambda_runtime::{service_fn, Error, LambdaEvent, Runtime, ShortCircuit};
+/// use serde_json::Value;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     Runtime::builder()
+///         .short_circuit(ShortCircuit::warmup())
+///         .short_circuit(ShortCircuit::new(|payload, _context| {
+///             (payload == b"\"health\"").then(|| Bytes::from_static(b"\"ok\""))
+///         }))
+///         .build(service_fn(func))
+///         .run()
+///         .await
+/// }
+///
+/// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+///     Ok(event.payload)
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ShortCircuit(Arc<Respond>);
+
+type Respond = dyn Fn(&[u8], &Context) -> Option<Bytes> + Send + Sync;
+
+impl ShortCircuit {
+    /// Payloads larger than this aren't parsed to look for warmup pings, so that regular events
+    /// aren't decoded twice.
+    const WARMUP_MAX_SIZE: usize = 1024;
+
+    /// Create a short circuit that responds with the bytes returned by `respond`, or lets the
+    /// invocation through when it returns `None`.
+    pub fn new<F>(respond: F) -> Self
+    where
+        F: Fn(&[u8], &Context) -> Option<Bytes> + Send + Sync + 'static,
+    {
+        Self(Arc::new(respond))
+    }
+
+    /// Respond `{"warmup":true}` to warmup pings, which are small JSON objects with a `warmup`
+    /// field set to `true`, like `{"warmup":true}`.
+    pub fn warmup() -> Self {
+        #[derive(Deserialize)]
+        struct Ping {
+            #[serde(default)]
+            warmup: bool,
+        }
+
+        Self::new(|payload, _| {
+            if payload.len() > Self::WARMUP_MAX_SIZE || !payload.trim_ascii_start().starts_with(b"{") {
+                return None;
+            }
+            serde_json::from_slice::<Ping>(payload)
+                .is_ok_and(|ping| ping.warmup)
+                .then(|| Bytes::from_static(br#"{"warmup":true}"#))
+        })
+    }
+
+    pub(crate) fn respond(&self, payload: &[u8], context: &Context) -> Option<Bytes> {
+        (self.0)(payload, context)
+    }
+}
+
+impl fmt::Debug for ShortCircuit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ShortCircuit").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn respond_to_warmup_pings() {
+        let warmup = ShortCircuit::warmup();
+        let context = Context::default();
+        let pong = Some(Bytes::from_static(br#"{"warmup":true}"#));
+        assert_eq!(pong, warmup.respond(br#"{"warmup":true}"#, &context));
+        assert_eq!(
+            pong,
+            warmup.respond(br#" { "warmup": true, "source": "scheduler" }"#, &context)
+        );
+        assert_eq!(None, warmup.respond(br#"{"warmup":false}"#, &context));
+        assert_eq!(None, warmup.respond(br#"{"orderId":"42"}"#, &context));
+        assert_eq!(None, warmup.respond(br#"["warmup"]"#, &context));
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    requests::{IntoRequest, RestoreErrorRequest, RestoreNextRequest},
+    runtime::{report_init_error, ApiClient},
+    ErrorReport, IntoDiagnostic,
+};
+use futures::future::BoxFuture;
+use lambda_runtime_api_client::BoxError;
+use std::{fmt, future::Future};
+use tracing::{debug, error};
+
+pub(crate) type Hook = Box<dyn FnOnce() -> BoxFuture<'static, Result<(), ErrorReport>> + Send>;
+
+/// Hooks that run around the SnapStart snapshot of the execution environment.
+#[derive(Default)]
+pub(crate) struct SnapStartHooks {
+    checkpoint: Vec<Hook>,
+    restore: Vec<Hook>,
+}
+
+impl fmt::Debug for SnapStartHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnapStartHooks")
+            .field("checkpoint", &self.checkpoint.len())
+            .field("restore", &self.restore.len())
+            .finish()
+    }
+}
+
+impl SnapStartHooks {
+    pub(crate) fn on_checkpoint<F, Fut, E>(&mut self, hook: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: IntoDiagnostic,
+    {
+        self.checkpoint.push(boxed(hook));
+    }
+
+    pub(crate) fn on_restore<F, Fut, E>(&mut self, hook: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: IntoDiagnostic,
+    {
+        self.restore.push(boxed(hook));
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.checkpoint.is_empty() && self.restore.is_empty()
+    }
+
+    /// Run the checkpoint hooks, wait for the execution environment to be restored from the
+    /// snapshot, and run the restore hooks.
+    ///
+    /// Checkpoint hooks run in the reverse order they were registered, so resources are released
+    /// in the reverse order they were acquired, restore hooks run in the order they were registered.
+    pub(crate) async fn run(self, client: &ApiClient) -> Result<(), BoxError> {
+        for hook in self.checkpoint.into_iter().rev() {
+            if let Err(report) = hook().await {
+                error!(error = %report, "SnapStart checkpoint hook failed");
+                report_init_error(client, report.clone()).await;
+                return Err(report.into());
+            }
+        }
+
+        debug!("Waiting for the execution environment to be restored");
+        let res = client.call(RestoreNextRequest.into_req()?).await?;
+        if !res.status().is_success() {
+            return Err(format!(
+                "unexpected status waiting for the restore of the snapshot: {}",
+                res.status()
+            )
+            .into());
+        }
+
+        for hook in self.restore {
+            if let Err(report) = hook().await {
+                error!(error = %report, "SnapStart restore hook failed");
+                let req = RestoreErrorRequest { report: report.clone() }.into_req()?;
+                if let Err(err) = client.call(req).await {
+                    error!(error = %err, "restore error can't be reported to the Runtime API");
+                }
+                return Err(report.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn boxed<F, Fut, E>(hook: F) -> Hook
+where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+    E: IntoDiagnostic,
+{
+    Box::new(move || {
+        let fut = hook();
+        Box::pin(async move { fut.await.map_err(IntoDiagnostic::into_error_report) })
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Error;
+    use httpmock::prelude::*;
+    use lambda_runtime_api_client::Client;
+    use std::sync::{Arc, Mutex};
+
+    fn client(server: &MockServer) -> ApiClient {
+        let base = server.base_url().parse().expect("Invalid mock server Uri");
+        Client::builder().with_endpoint(base).build().unwrap()
+    }
+
+    #[tokio::test]
+    async fn run_hooks_around_restore() {
+        let server = MockServer::start();
+        let restore_next = server.mock(|when, then| {
+            when.method(GET).path("/2018-06-01/runtime/restore/next");
+            then.status(200).body("");
+        });
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut hooks = SnapStartHooks::default();
+        for name in ["close connections", "flush cache"] {
+            let calls = calls.clone();
+            hooks.on_checkpoint(move || async move {
+                calls.lock().unwrap().push(name);
+                Ok::<_, Error>(())
+            });
+        }
+        let restore_calls = calls.clone();
+        hooks.on_restore(move || async move {
+            restore_calls.lock().unwrap().push("reseed randomness");
+            Ok::<_, Error>(())
+        });
+
+        hooks.run(&client(&server)).await.unwrap();
+        restore_next.assert_async().await;
+        assert_eq!(
+            vec!["flush cache", "close connections", "reseed randomness"],
+            *calls.lock().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn report_restore_hook_errors() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/2018-06-01/runtime/restore/next");
+            then.status(200).body("");
+        });
+        let restore_error = server.mock(|when, then| {
+            when.method(POST)
+                .path("/2018-06-01/runtime/restore/error")
+                .header("lambda-runtime-function-error-type", "CredentialsError")
+                .json_body(serde_json::json!({
+                    "errorType": "CredentialsError",
+                    "errorMessage": "credentials expired",
+                }));
+            then.status(202).body("");
+        });
+
+        let mut hooks = SnapStartHooks::default();
+        hooks.on_restore(|| async { Err(ErrorReport::new("CredentialsError", "credentials expired")) });
+
+        let err = hooks.run(&client(&server)).await.unwrap_err();
+        restore_error.assert_async().await;
+        assert_eq!("CredentialsError: credentials expired", err.to_string());
+    }
+}
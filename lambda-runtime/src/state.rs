@@ -0,0 +1,96 @@
+use crate::{service_fn, LambdaEvent};
+use std::{fmt, ops::Deref, sync::Arc};
+use tower::util::ServiceFn;
+
+/// State shared by all the invocations of a handler, like SDK clients or configuration loaded
+/// once when the function initializes.
+///
+/// Handlers created with [with_state] or [run_with_state](crate::run_with_state) receive a
+/// `State` with every event. Cloning it only clones the [Arc] of the state.
+///
+/// ```no_run
+/// use lambda_runtime::{Error, LambdaEvent, State};
+/// use serde_json::Value;
+///
+/// struct Settings {
+///     table_name: String,
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let settings = Settings {
+///         table_name: std::env::var("TABLE_NAME")?,
+///     };
+///     lambda_runtime::run_with_state(settings, func).await
+/// }
+///
+/// async fn func(event: LambdaEvent<Value>, State(settings): State<Settings>) -> Result<String, Error> {
+///     Ok(format!("{}: {}", settings.table_name, event.payload))
+/// }
+/// ```
+pub struct State<T>(pub Arc<T>);
+
+impl<T> State<T> {
+    /// Wrap the state in an [Arc].
+    pub fn new(state: T) -> Self {
+        Self(Arc::new(state))
+    }
+}
+
+impl<T> Clone for State<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Deref for State<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> From<Arc<T>> for State<T> {
+    fn from(state: Arc<T>) -> Self {
+        Self(state)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for State<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("State").field(&self.0).finish()
+    }
+}
+
+/// Return a new [ServiceFn] with a closure that takes an event and the [State] shared by all
+/// the invocations, to create a [Runtime](crate::Runtime) with a stateful handler.
+pub fn with_state<T, A, F, Fut>(state: Arc<T>, f: F) -> ServiceFn<impl FnMut(LambdaEvent<A>) -> Fut>
+where
+    F: Fn(LambdaEvent<A>, State<T>) -> Fut,
+{
+    let state = State(state);
+    service_fn(move |event: LambdaEvent<A>| f(event, state.clone()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Context, Error};
+    use tower::{Service, ServiceExt};
+
+    #[tokio::test]
+    async fn handlers_share_the_state() {
+        let counter = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut handler = with_state(counter.clone(), |event: LambdaEvent<usize>, state| async move {
+            let State(counter) = state;
+            Ok::<_, Error>(counter.fetch_add(event.payload, std::sync::atomic::Ordering::SeqCst))
+        });
+
+        for payload in [1, 2] {
+            let event = LambdaEvent::new(payload, Context::default());
+            handler.ready().await.unwrap().call(event).await.unwrap();
+        }
+        assert_eq!(3, counter.load(std::sync::atomic::Ordering::SeqCst));
+    }
+}
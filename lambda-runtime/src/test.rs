@@ -0,0 +1,352 @@
+//! Utilities to unit test handlers without the Runtime API.
+//!
+//! [invoke] runs a handler the way the runtime does: it decodes the JSON event, catches the
+//! panics of the handler, and encodes its response or its error, so tests cover the same
+//! serialization and [Diagnostic](crate::Diagnostic) conversions as the deployed function.
+//!
+//! ```
+//! use lambda_runtime::{test::invoke, service_fn, Error, LambdaEvent};
+//! use serde_json::{json, Value};
+//!
+//! async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+//!     match event.payload["name"].as_str() {
+//!         Some(name) => Ok(json!({ "greeting": format!("hello {name}") })),
+//!         None => Err("missing name".into()),
+//!     }
+//! }
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! invoke(service_fn(func), json!({ "name": "ferris" }))
+//!     .await
+//!     .assert_response(json!({ "greeting": "hello ferris" }));
+//! let invocation = invoke(service_fn(func), json!({})).await;
+//! assert_eq!("missing name", invocation.error().error_message);
+//! # });
+//! ```
+use crate::{
+    codec::Json,
+    layers::{CatchPanicService, RuntimeApiResponseService},
+    Config, Context, ErrorReport, IntoDiagnostic, IntoFunctionResponse, LambdaEvent, LambdaInvocation,
+};
+use bytes::Bytes;
+use lambda_runtime_api_client::BoxError;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt::Debug,
+    future::Future,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio_stream::Stream;
+use tower::{Service, ServiceExt};
+
+/// Builder of [Context] values for tests, see [FakeContext::builder].
+#[derive(Debug)]
+pub struct FakeContext;
+
+impl FakeContext {
+    /// Create a builder of a context with a random request id and a deadline 3 seconds from
+    /// now, the default timeout of Lambda functions.
+    pub fn builder() -> FakeContextBuilder {
+        FakeContextBuilder {
+            request_id: format!("{:032x}", rand_id()),
+            timeout: Duration::from_secs(3),
+            invoked_function_arn: "arn:aws:lambda:us-east-1:123456789012:function:test".into(),
+            xray_trace_id: None,
+            tenant_id: None,
+            config: Config {
+                function_name: "test".into(),
+                memory: 128,
+                version: "$LATEST".into(),
+                ..Config::default()
+            },
+            cold_start: true,
+        }
+    }
+}
+
+/// Builder returned by [FakeContext::builder].
+#[derive(Debug)]
+pub struct FakeContextBuilder {
+    request_id: String,
+    timeout: Duration,
+    invoked_function_arn: String,
+    xray_trace_id: Option<String>,
+    tenant_id: Option<String>,
+    config: Config,
+    cold_start: bool,
+}
+
+impl FakeContextBuilder {
+    /// Set the request id of the invocation.
+    pub fn request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = request_id.into();
+        self
+    }
+
+    /// Set the time left before the deadline of the invocation.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the ARN of the invoked function.
+    pub fn invoked_function_arn(mut self, arn: impl Into<String>) -> Self {
+        self.invoked_function_arn = arn.into();
+        self
+    }
+
+    /// Set the X-Ray trace id of the invocation.
+    pub fn xray_trace_id(mut self, trace_id: impl Into<String>) -> Self {
+        self.xray_trace_id = Some(trace_id.into());
+        self
+    }
+
+    /// Set the tenant id of the invocation.
+    pub fn tenant_id(mut self, tenant_id: impl Into<String>) -> Self {
+        self.tenant_id = Some(tenant_id.into());
+        self
+    }
+
+    /// Set the name of the function.
+    pub fn function_name(mut self, function_name: impl Into<String>) -> Self {
+        self.config.function_name = function_name.into();
+        self
+    }
+
+    /// Set the memory of the function, in MB.
+    pub fn memory(mut self, memory: i32) -> Self {
+        self.config.memory = memory;
+        self
+    }
+
+    /// Set whether the invocation is the first one of the execution environment.
+    pub fn cold_start(mut self, cold_start: bool) -> Self {
+        self.cold_start = cold_start;
+        self
+    }
+
+    /// Create the context.
+    pub fn build(self) -> Context {
+        let deadline = SystemTime::now() + self.timeout;
+        Context {
+            request_id: self.request_id,
+            deadline: deadline
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("deadline is after the epoch")
+                .as_millis() as u64,
+            invoked_function_arn: self.invoked_function_arn,
+            xray_trace_id: self.xray_trace_id,
+            tenant_id: self.tenant_id,
+            env_config: Arc::new(self.config),
+            cold_start: self.cold_start,
+            invocation_count: 1,
+            ..Context::default()
+        }
+    }
+}
+
+fn rand_id() -> u128 {
+    use std::hash::{BuildHasher, Hasher};
+    let hasher = || std::collections::hash_map::RandomState::new().build_hasher().finish();
+    (u128::from(hasher()) << 64) | u128::from(hasher())
+}
+
+/// Result of an invocation run by [invoke]: the serialized response of the handler, or the
+/// error reported for the invocation.
+#[derive(Clone, Debug)]
+pub struct Invocation {
+    context: Context,
+    result: Result<Bytes, ErrorReport>,
+}
+
+impl Invocation {
+    /// Context the handler was invoked with.
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Whether the handler returned a response.
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// The serialized response of the handler, or the error reported for the invocation.
+    pub fn result(&self) -> Result<&Bytes, &ErrorReport> {
+        self.result.as_ref()
+    }
+
+    /// Decode the response of the handler.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the invocation failed, or if the response can't be decoded.
+    #[track_caller]
+    pub fn response<T: DeserializeOwned>(&self) -> T {
+        match &self.result {
+            Ok(body) => serde_json::from_slice(body).expect("response can't be decoded"),
+            Err(report) => panic!("invocation failed: {report}"),
+        }
+    }
+
+    /// The error reported for the invocation.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the handler returned a response.
+    #[track_caller]
+    pub fn error(&self) -> &ErrorReport {
+        match &self.result {
+            Ok(body) => panic!("invocation succeeded with {}", String::from_utf8_lossy(body)),
+            Err(report) => report,
+        }
+    }
+
+    /// Assert that the handler returned a response equal to `expected`, compared as JSON.
+    #[track_caller]
+    pub fn assert_response(&self, expected: impl Serialize) {
+        let expected = serde_json::to_value(expected).expect("expected response can't be encoded");
+        assert_eq!(expected, self.response::<serde_json::Value>());
+    }
+
+    /// Assert that the invocation failed with an error of type `error_type`.
+    #[track_caller]
+    pub fn assert_error_type(&self, error_type: &str) {
+        assert_eq!(error_type, self.error().error_type);
+    }
+}
+
+/// Invoke `handler` with `event` encoded as JSON and a [FakeContext].
+pub async fn invoke<F, EventPayload, Response, BufferedResponse, StreamingResponse, StreamItem, StreamError>(
+    handler: F,
+    event: impl Serialize,
+) -> Invocation
+where
+    F: Service<LambdaEvent<EventPayload>, Response = Response>,
+    F::Future: Future<Output = Result<Response, F::Error>>,
+    F::Error: IntoDiagnostic + Debug,
+    EventPayload: DeserializeOwned,
+    Response: IntoFunctionResponse<BufferedResponse, StreamingResponse>,
+    BufferedResponse: Serialize,
+    StreamingResponse: Stream<Item = Result<StreamItem, StreamError>> + Unpin + Send + 'static,
+    StreamItem: Into<Bytes> + Send,
+    StreamError: Into<BoxError> + Send + Debug,
+{
+    invoke_with_context(handler, event, FakeContext::builder().build()).await
+}
+
+/// Invoke `handler` with `event` encoded as JSON and `context`.
+pub async fn invoke_with_context<
+    F,
+    EventPayload,
+    Response,
+    BufferedResponse,
+    StreamingResponse,
+    StreamItem,
+    StreamError,
+>(
+    handler: F,
+    event: impl Serialize,
+    context: Context,
+) -> Invocation
+where
+    F: Service<LambdaEvent<EventPayload>, Response = Response>,
+    F::Future: Future<Output = Result<Response, F::Error>>,
+    F::Error: IntoDiagnostic + Debug,
+    EventPayload: DeserializeOwned,
+    Response: IntoFunctionResponse<BufferedResponse, StreamingResponse>,
+    BufferedResponse: Serialize,
+    StreamingResponse: Stream<Item = Result<StreamItem, StreamError>> + Unpin + Send + 'static,
+    StreamItem: Into<Bytes> + Send,
+    StreamError: Into<BoxError> + Send + Debug,
+{
+    let body = serde_json::to_vec(&event).expect("event can't be encoded").into();
+    let (parts, _) = http::Response::new(()).into_parts();
+    let invocation = LambdaInvocation {
+        parts,
+        body,
+        context: context.clone(),
+    };
+
+    let service = RuntimeApiResponseService::new(
+        CatchPanicService::with_hook(handler, None),
+        Json,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        None,
+    );
+    let request = service
+        .oneshot(invocation)
+        .await
+        .expect("the invocation can't be turned into a Runtime API request");
+    let failed = request.uri().path().ends_with("/error");
+    let body = request
+        .into_body()
+        .collect()
+        .await
+        .expect("the body of the response can't be read")
+        .to_bytes();
+
+    let result = if failed {
+        Err(serde_json::from_slice(&body).expect("the error report can't be decoded"))
+    } else {
+        Ok(body)
+    };
+    Invocation { context, result }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{service_fn, Diagnostic, Error};
+    use serde_json::{json, Value};
+
+    #[tokio::test]
+    async fn invoke_handlers() {
+        async fn double(event: LambdaEvent<u32>) -> Result<u32, Error> {
+            Ok(event.payload * 2)
+        }
+
+        let invocation = invoke(service_fn(double), 21).await;
+        assert!(invocation.is_ok());
+        assert_eq!(42, invocation.response::<u32>());
+        invocation.assert_response(42);
+
+        // The event can't be decoded into the payload of the handler.
+        let invocation = invoke(service_fn(double), "21").await;
+        invocation.assert_error_type("&lambda_runtime::deserializer::DeserializeError");
+    }
+
+    #[tokio::test]
+    async fn report_errors_and_panics() {
+        async fn reject(_: LambdaEvent<Value>) -> Result<Value, Diagnostic> {
+            Err(Diagnostic {
+                error_type: "OrderRejected".into(),
+                error_message: "out of stock".into(),
+            })
+        }
+        let invocation = invoke(service_fn(reject), json!({})).await;
+        invocation.assert_error_type("OrderRejected");
+        assert_eq!("out of stock", invocation.error().error_message);
+
+        async fn explode(_: LambdaEvent<Value>) -> Result<Value, Error> {
+            panic!("boom")
+        }
+        let invocation = invoke(service_fn(explode), json!({})).await;
+        assert_eq!("Lambda panicked: boom", invocation.error().error_message);
+    }
+
+    #[test]
+    fn build_fake_contexts() {
+        let context = FakeContext::builder()
+            .request_id("id")
+            .function_name("orders")
+            .timeout(Duration::from_secs(30))
+            .build();
+        assert_eq!("id", context.request_id);
+        assert_eq!("orders", context.env_config.function_name);
+        let remaining = context.deadline().duration_since(SystemTime::now()).unwrap();
+        assert!(remaining > Duration::from_secs(29));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Error, RefConfig};
+use crate::{BackgroundTasks, Diagnostic, Error, LambdaEnv, RefConfig};
 use base64::prelude::*;
 use bytes::Bytes;
 use http::{header::ToStrError, HeaderMap, HeaderValue, StatusCode};
@@ -6,12 +6,17 @@ use lambda_runtime_api_client::body::Body;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
+    convert::Infallible,
     fmt::Debug,
     time::{Duration, SystemTime},
 };
-use tokio_stream::Stream;
+use tokio_stream::{adapters::Map, Stream, StreamExt};
+use tokio_util::sync::CancellationToken;
 
 /// Client context sent by the AWS Mobile SDK.
+///
+/// The SDKs send it in the `ClientContext` parameter of the `Invoke` API, and Lambda
+/// forwards it to the function in the `lambda-runtime-client-context` header.
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct ClientContext {
     /// Information about the mobile application invoking the function.
@@ -20,17 +25,19 @@ pub struct ClientContext {
     /// Custom properties attached to the mobile event context.
     #[serde(default)]
     pub custom: HashMap<String, String>,
-    /// Environment settings from the mobile client.
-    #[serde(default)]
+    /// Environment settings from the mobile client, like `platform`, `model`, or `locale`.
+    #[serde(default, rename = "env", alias = "environment")]
     pub environment: HashMap<String, String>,
 }
 
 /// AWS Mobile SDK client fields.
+///
+/// The SDKs only send the fields they know about, missing fields are empty.
 #[derive(Serialize, Deserialize, Default, Clone, Debug, Eq, PartialEq)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct ClientApplication {
-    /// The mobile app installation id
-    #[serde(alias = "installation_id")]
+    /// The mobile app installation id, sent as `client_id` by the iOS SDK.
+    #[serde(alias = "installation_id", alias = "client_id")]
     pub installation_id: String,
     /// The app title for the mobile app as registered with AWS' mobile services.
     #[serde(alias = "app_title")]
@@ -85,6 +92,75 @@ pub struct Context {
     /// Includes information such as the function name, memory allocation,
     /// version, and log streams.
     pub env_config: RefConfig,
+    /// Whether this is the first invocation processed by this execution environment.
+    #[serde(default)]
+    pub cold_start: bool,
+    /// Number of invocations processed by this execution environment, including this one.
+    #[serde(default)]
+    pub invocation_count: u64,
+    /// How the execution environment was initialized, from `AWS_LAMBDA_INITIALIZATION_TYPE`.
+    #[serde(default)]
+    pub init_type: InitializationType,
+    #[serde(skip)]
+    pub(crate) handles: InvocationHandles,
+}
+
+/// Handles of an invocation on its [Context], which aren't part of the data of the invocation,
+/// so they don't make two contexts different.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct InvocationHandles {
+    background_tasks: BackgroundTasks,
+    cancellation_token: CancellationToken,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Option<crate::layers::Metrics>,
+}
+
+impl PartialEq for InvocationHandles {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for InvocationHandles {}
+
+/// How the execution environment of the function was initialized.
+///
+/// See <https://docs.aws.amazon.com/lambda/latest/dg/configuration-envvars.html#configuration-envvars-runtime>
+#[non_exhaustive]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InitializationType {
+    /// The environment was initialized for an on-demand invocation.
+    #[default]
+    OnDemand,
+    /// The environment was initialized ahead of time for provisioned concurrency.
+    ProvisionedConcurrency,
+    /// The environment was restored from a SnapStart snapshot.
+    SnapStart,
+    /// An initialization type this version of the runtime doesn't know about.
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl InitializationType {
+    /// Read the initialization type from `AWS_LAMBDA_INITIALIZATION_TYPE`, which is
+    /// `on-demand` when the variable is not set.
+    pub fn from_env() -> Self {
+        std::env::var("AWS_LAMBDA_INITIALIZATION_TYPE")
+            .map(|value| InitializationType::from(value.as_str()))
+            .unwrap_or_default()
+    }
+}
+
+impl From<&str> for InitializationType {
+    fn from(value: &str) -> Self {
+        match value {
+            "on-demand" => InitializationType::OnDemand,
+            "provisioned-concurrency" => InitializationType::ProvisionedConcurrency,
+            "snap-start" => InitializationType::SnapStart,
+            other => InitializationType::Other(other.to_string()),
+        }
+    }
 }
 
 impl Default for Context {
@@ -98,6 +174,10 @@ impl Default for Context {
             identity: None,
             tenant_id: None,
             env_config: std::sync::Arc::new(crate::Config::default()),
+            cold_start: false,
+            invocation_count: 0,
+            init_type: InitializationType::default(),
+            handles: InvocationHandles::default(),
         }
     }
 }
@@ -151,6 +231,10 @@ impl Context {
                 .get("lambda-runtime-aws-tenant-id")
                 .map(|v| String::from_utf8_lossy(v.as_bytes()).to_string()),
             env_config,
+            cold_start: false,
+            invocation_count: 0,
+            init_type: InitializationType::default(),
+            handles: InvocationHandles::default(),
         };
 
         Ok(ctx)
@@ -160,6 +244,113 @@ impl Context {
     pub fn deadline(&self) -> SystemTime {
         SystemTime::UNIX_EPOCH + Duration::from_millis(self.deadline)
     }
+
+    /// Time left until the deadline of the current invocation, which is zero once it passed.
+    ///
+    /// Returns `None` when the invocation has no deadline, like contexts created in tests.
+    pub fn remaining_time(&self) -> Option<Duration> {
+        (self.deadline != 0).then(|| self.deadline().duration_since(SystemTime::now()).unwrap_or_default())
+    }
+
+    /// The configuration of the function, parsed once from the environment variables.
+    ///
+    /// Returns `None` if the environment variables are missing or invalid,
+    /// use [LambdaEnv::current] to get the error.
+    pub fn lambda_env(&self) -> Option<&'static LambdaEnv> {
+        LambdaEnv::current().ok()
+    }
+
+    /// Tasks that the runtime awaits before posting the response of the current invocation.
+    pub fn background_tasks(&self) -> &BackgroundTasks {
+        &self.handles.background_tasks
+    }
+
+    /// Token cancelled shortly before the deadline of the current invocation, so that the
+    /// handler can stop long-running work and return a partial result instead of timing out.
+    ///
+    /// The token is cancelled 500ms before the deadline unless the margin is set with
+    /// [RuntimeBuilder::cancellation_margin](crate::RuntimeBuilder::cancellation_margin). It's
+    /// never cancelled when the invocation has no deadline.
+    ///
+    /// ```no_run
+    /// use lambda_runtime::{Error, LambdaEvent};
+    /// use serde_json::{json, Value};
+    /// use std::time::Duration;
+    ///
+    /// async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
+    ///     let mut processed = 0;
+    ///     while processed < 1_000 {
+    ///         tokio::select! {
+    ///             _ = event.context.cancellation_token().cancelled() => break,
+    ///             _ = tokio::time::sleep(Duration::from_millis(10)) => processed += 1,
+    ///         }
+    ///     }
+    ///     Ok(json!({ "processed": processed }))
+    /// }
+    /// ```
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.handles.cancellation_token
+    }
+
+    /// The X-Ray trace header of the current invocation, parsed into its fields.
+    pub fn xray_trace(&self) -> Option<XRayTraceId> {
+        self.xray_trace_id.as_deref().and_then(|header| header.parse().ok())
+    }
+}
+
+/// X-Ray trace header of an invocation, like
+/// `Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1`.
+///
+/// See <https://docs.aws.amazon.com/xray/latest/devguide/xray-concepts.html#xray-concepts-tracingheader>
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct XRayTraceId {
+    /// The id of the trace, `Root` in the header.
+    pub root: String,
+    /// The id of the segment of the Lambda service, `Parent` in the header.
+    pub parent: Option<String>,
+    /// Whether the trace is sampled, `Sampled` in the header.
+    pub sampled: Option<bool>,
+    /// The other fields of the header, like `Lineage`, in order.
+    pub other: Vec<(String, String)>,
+}
+
+impl std::str::FromStr for XRayTraceId {
+    type Err = Error;
+
+    fn from_str(header: &str) -> Result<Self, Self::Err> {
+        let mut trace = XRayTraceId::default();
+        for field in header.split(';').map(str::trim).filter(|field| !field.is_empty()) {
+            let (key, value) = field
+                .split_once('=')
+                .ok_or_else(|| format!("invalid X-Ray trace header field `{field}`"))?;
+            match key {
+                "Root" => trace.root = value.to_string(),
+                "Parent" => trace.parent = Some(value.to_string()),
+                "Sampled" => trace.sampled = Some(value == "1"),
+                _ => trace.other.push((key.to_string(), value.to_string())),
+            }
+        }
+        if trace.root.is_empty() {
+            return Err(format!("X-Ray trace header `{header}` has no Root").into());
+        }
+        Ok(trace)
+    }
+}
+
+impl std::fmt::Display for XRayTraceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Root={}", self.root)?;
+        if let Some(parent) = &self.parent {
+            write!(f, ";Parent={parent}")?;
+        }
+        if let Some(sampled) = self.sampled {
+            write!(f, ";Sampled={}", u8::from(sampled))?;
+        }
+        for (key, value) in &self.other {
+            write!(f, ";{key}={value}")?;
+        }
+        Ok(())
+    }
 }
 
 /// Extract the invocation request id from the incoming request.
@@ -205,20 +396,47 @@ pub struct MetadataPrelude {
     pub cookies: Vec<String>,
 }
 
-pub trait ToStreamErrorTrailer {
-    /// Convert the hyper error into a stream error trailer.
-    fn to_tailer(&self) -> String;
+/// Trailers that report an error raised in the middle of a streaming response.
+///
+/// The Runtime API reads them after the last chunk of the body.
+/// See <https://docs.aws.amazon.com/lambda/latest/dg/runtimes-custom.html#runtimes-custom-response-streaming>
+pub(crate) trait ToStreamErrorTrailer {
+    /// Convert the error into the `Lambda-Runtime-Function-Error-*` trailers.
+    fn to_trailers(&self) -> HeaderMap;
 }
 
 impl ToStreamErrorTrailer for Error {
-    fn to_tailer(&self) -> String {
-        format!(
-            "Lambda-Runtime-Function-Error-Type: Runtime.StreamError\r\nLambda-Runtime-Function-Error-Body: {}\r\n",
-            BASE64_STANDARD.encode(self.to_string())
-        )
+    fn to_trailers(&self) -> HeaderMap {
+        Diagnostic {
+            error_type: STREAM_ERROR_TYPE.into(),
+            error_message: self.to_string(),
+        }
+        .to_trailers()
     }
 }
 
+impl ToStreamErrorTrailer for Diagnostic {
+    fn to_trailers(&self) -> HeaderMap {
+        let body = serde_json::to_vec(self).unwrap_or_default();
+
+        let mut trailers = HeaderMap::with_capacity(2);
+        trailers.insert(
+            STREAM_ERROR_TYPE_TRAILER,
+            HeaderValue::from_str(&self.error_type).unwrap_or(HeaderValue::from_static(STREAM_ERROR_TYPE)),
+        );
+        // base64 only produces valid header value characters
+        trailers.insert(
+            STREAM_ERROR_BODY_TRAILER,
+            HeaderValue::from_str(&BASE64_STANDARD.encode(body)).expect("base64 is a valid header value"),
+        );
+        trailers
+    }
+}
+
+pub(crate) const STREAM_ERROR_TYPE_TRAILER: &str = "lambda-runtime-function-error-type";
+pub(crate) const STREAM_ERROR_BODY_TRAILER: &str = "lambda-runtime-function-error-body";
+const STREAM_ERROR_TYPE: &str = "Runtime.StreamError";
+
 /// A streaming response that contains the metadata prelude and the stream of bytes that will be
 /// sent to the client.
 #[derive(Debug)]
@@ -229,6 +447,43 @@ pub struct StreamResponse<S> {
     pub stream: S,
 }
 
+impl<S> StreamResponse<S> {
+    /// Create a streaming response with the default metadata prelude.
+    pub fn new(stream: S) -> Self {
+        StreamResponse {
+            metadata_prelude: Default::default(),
+            stream,
+        }
+    }
+
+    /// Set the metadata prelude sent before the stream.
+    pub fn with_metadata_prelude(mut self, metadata_prelude: MetadataPrelude) -> Self {
+        self.metadata_prelude = metadata_prelude;
+        self
+    }
+}
+
+impl<S> StreamResponse<InfallibleStream<S>>
+where
+    S: Stream<Item = Bytes> + Unpin + Send + 'static,
+{
+    /// Create a streaming response from a stream of bytes that can't fail.
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use lambda_runtime::StreamResponse;
+    ///
+    /// let chunks = vec![Bytes::from_static(b"Hello, "), Bytes::from_static(b"world!")];
+    /// let response = StreamResponse::from_bytes(tokio_stream::iter(chunks));
+    /// ```
+    pub fn from_bytes(stream: S) -> Self {
+        StreamResponse::new(stream.map(Ok))
+    }
+}
+
+/// A stream of bytes that can't fail, adapted to the `Result` items of a [`StreamResponse`].
+pub type InfallibleStream<S> = Map<S, fn(Bytes) -> Result<Bytes, Infallible>>;
+
 /// An enum representing the response of a function that can return either a buffered
 /// response of type `B` or a streaming response of type `S`.
 pub enum FunctionResponse<B, S> {
@@ -282,10 +537,7 @@ where
     E: Into<Error> + Send + Debug,
 {
     fn from(value: S) -> Self {
-        StreamResponse {
-            metadata_prelude: Default::default(),
-            stream: value,
-        }
+        StreamResponse::new(value)
     }
 }
 
@@ -295,6 +547,41 @@ mod test {
     use crate::Config;
     use std::sync::Arc;
 
+    #[test]
+    fn xray_trace_id() {
+        let header = "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1;Lineage=a87bd80c:0";
+        let trace: XRayTraceId = header.parse().unwrap();
+        assert_eq!("1-5759e988-bd862e3fe1be46a994272793", trace.root);
+        assert_eq!(Some("53995c3f42cd8ad8"), trace.parent.as_deref());
+        assert_eq!(Some(true), trace.sampled);
+        assert_eq!(vec![("Lineage".to_string(), "a87bd80c:0".to_string())], trace.other);
+        assert_eq!(header, trace.to_string());
+
+        assert!("Parent=53995c3f42cd8ad8".parse::<XRayTraceId>().is_err());
+        assert!("Root".parse::<XRayTraceId>().is_err());
+
+        let context = Context {
+            xray_trace_id: Some(header.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(Some(trace), context.xray_trace());
+    }
+
+    #[test]
+    fn initialization_type() {
+        assert_eq!(InitializationType::OnDemand, InitializationType::from("on-demand"));
+        assert_eq!(InitializationType::SnapStart, InitializationType::from("snap-start"));
+        assert_eq!(
+            InitializationType::Other("future-type".to_string()),
+            InitializationType::from("future-type")
+        );
+
+        let serialized = serde_json::to_string(&InitializationType::ProvisionedConcurrency).unwrap();
+        assert_eq!(r#""provisioned-concurrency""#, serialized);
+        let deserialized: InitializationType = serde_json::from_str(r#""future-type""#).unwrap();
+        assert_eq!(InitializationType::Other("future-type".to_string()), deserialized);
+    }
+
     #[test]
     fn context_with_expected_values_and_types_resolves() {
         let config = Arc::new(Config::default());
@@ -391,6 +678,82 @@ mod test {
         assert_eq!(tried.identity.unwrap(), cognito_identity);
     }
 
+    fn context_from_mobile_sdk_headers(client_context: &'static str, identity: &'static str) -> Context {
+        let config = Arc::new(Config::default());
+        let mut headers = HeaderMap::new();
+        headers.insert("lambda-runtime-aws-request-id", HeaderValue::from_static("my-id"));
+        headers.insert("lambda-runtime-deadline-ms", HeaderValue::from_static("123"));
+        headers.insert(
+            "lambda-runtime-client-context",
+            HeaderValue::from_static(client_context),
+        );
+        headers.insert("lambda-runtime-cognito-identity", HeaderValue::from_static(identity));
+        Context::new("id", config, &headers).unwrap()
+    }
+
+    #[test]
+    fn context_from_android_sdk() {
+        let context = context_from_mobile_sdk_headers(
+            r#"{"client":{"installation_id":"8f9c4b1e-2a6d-4c4b-9f0e-5d3a7b2c1e90","app_title":"Orders","app_version_name":"2.3.1","app_version_code":"231","app_package_name":"com.example.orders"},"custom":{"cart":"3 items"},"env":{"platform":"Android","model":"Pixel 8","make":"Google","platform_version":"14","locale":"en_US"},"services":{"mobile_analytics":{"app_id":"a1b2c3"}}}"#,
+            r#"{"cognitoIdentityId":"us-east-1:1f3c2b4a-9e8d-4c7b-a6f5-0e1d2c3b4a59","cognitoIdentityPoolId":"us-east-1:6a5b4c3d-2e1f-4a0b-9c8d-7e6f5a4b3c21"}"#,
+        );
+
+        let client_context = context.client_context.unwrap();
+        assert_eq!(
+            ClientApplication {
+                installation_id: "8f9c4b1e-2a6d-4c4b-9f0e-5d3a7b2c1e90".into(),
+                app_title: "Orders".into(),
+                app_version_name: "2.3.1".into(),
+                app_version_code: "231".into(),
+                app_package_name: "com.example.orders".into(),
+            },
+            client_context.client
+        );
+        assert_eq!(Some("3 items"), client_context.custom.get("cart").map(String::as_str));
+        assert_eq!(
+            Some("Android"),
+            client_context.environment.get("platform").map(String::as_str)
+        );
+        assert_eq!(
+            Some("en_US"),
+            client_context.environment.get("locale").map(String::as_str)
+        );
+
+        let identity = context.identity.unwrap();
+        assert_eq!("us-east-1:1f3c2b4a-9e8d-4c7b-a6f5-0e1d2c3b4a59", identity.identity_id);
+        assert_eq!(
+            "us-east-1:6a5b4c3d-2e1f-4a0b-9c8d-7e6f5a4b3c21",
+            identity.identity_pool_id
+        );
+    }
+
+    #[test]
+    fn context_from_ios_sdk() {
+        let context = context_from_mobile_sdk_headers(
+            r#"{"client":{"client_id":"C1D2E3F4-A5B6-4C7D-8E9F-0A1B2C3D4E5F","app_title":"Orders","app_version_name":"2.3.1","app_version_code":"231","app_package_name":"com.example.Orders"},"env":{"platform":"iPhoneOS","model":"iPhone","make":"apple","platform_version":"17.4","locale":"en"},"custom":{}}"#,
+            r#"{"cognitoIdentityId":"us-east-1:1f3c2b4a-9e8d-4c7b-a6f5-0e1d2c3b4a59","cognitoIdentityPoolId":"us-east-1:6a5b4c3d-2e1f-4a0b-9c8d-7e6f5a4b3c21"}"#,
+        );
+
+        let client_context = context.client_context.unwrap();
+        assert_eq!(
+            "C1D2E3F4-A5B6-4C7D-8E9F-0A1B2C3D4E5F",
+            client_context.client.installation_id
+        );
+        assert_eq!("com.example.Orders", client_context.client.app_package_name);
+        assert!(client_context.custom.is_empty());
+        assert_eq!(
+            Some("iPhoneOS"),
+            client_context.environment.get("platform").map(String::as_str)
+        );
+    }
+
+    #[test]
+    fn client_context_with_partial_client() {
+        let client_context: ClientContext = serde_json::from_str(r#"{"client":{"app_title":"Orders"}}"#).unwrap();
+        assert_eq!("Orders", client_context.client.app_title);
+        assert!(client_context.client.installation_id.is_empty());
+    }
+
     #[test]
     fn context_with_bad_deadline_type_is_err() {
         let config = Arc::new(Config::default());
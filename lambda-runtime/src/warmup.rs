@@ -0,0 +1,102 @@
+use crate::{
+    snap_start::{boxed, Hook},
+    InitializationType, IntoDiagnostic,
+};
+use std::{fmt, future::Future};
+use tracing::{debug, warn};
+
+/// Hooks that run once before the runtime polls for the first invocation.
+#[derive(Default)]
+pub(crate) struct WarmupHooks {
+    hooks: Vec<Hook>,
+    provisioned: Vec<Hook>,
+}
+
+impl fmt::Debug for WarmupHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WarmupHooks")
+            .field("hooks", &self.hooks.len())
+            .field("provisioned", &self.provisioned.len())
+            .finish()
+    }
+}
+
+impl WarmupHooks {
+    pub(crate) fn push<F, Fut, E>(&mut self, hook: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: IntoDiagnostic,
+    {
+        self.hooks.push(boxed(hook));
+    }
+
+    pub(crate) fn push_provisioned<F, Fut, E>(&mut self, hook: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: IntoDiagnostic,
+    {
+        self.provisioned.push(boxed(hook));
+    }
+
+    /// Run all the hooks concurrently, including the provisioned concurrency hooks when the
+    /// environment was initialized for provisioned concurrency. Warming up is an optimization,
+    /// so failed hooks are only logged and don't stop the runtime.
+    pub(crate) async fn run(self, init_type: &InitializationType) {
+        let mut hooks = self.hooks;
+        if *init_type == InitializationType::ProvisionedConcurrency {
+            hooks.extend(self.provisioned);
+        }
+        if hooks.is_empty() {
+            return;
+        }
+        debug!(hooks = hooks.len(), ?init_type, "Running warmup hooks");
+        let results = futures::future::join_all(hooks.into_iter().map(|hook| hook())).await;
+        for report in results.into_iter().filter_map(Result::err) {
+            warn!(error = %report, "warmup hook failed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Error;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn failed_hooks_dont_stop_the_others() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut hooks = WarmupHooks::default();
+        hooks.push(|| async { Err::<(), _>(Error::from("connection refused")) });
+        let warmed = calls.clone();
+        hooks.push(move || async move {
+            warmed.lock().unwrap().push("pool");
+            Ok::<_, Error>(())
+        });
+
+        hooks.run(&InitializationType::OnDemand).await;
+        assert_eq!(vec!["pool"], *calls.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn provisioned_hooks_only_run_for_provisioned_concurrency() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let hooks = || {
+            let mut hooks = WarmupHooks::default();
+            let warmed = calls.clone();
+            hooks.push_provisioned(move || async move {
+                warmed.lock().unwrap().push("cache");
+                Ok::<_, Error>(())
+            });
+            hooks
+        };
+
+        hooks().run(&InitializationType::OnDemand).await;
+        hooks().run(&InitializationType::SnapStart).await;
+        assert!(calls.lock().unwrap().is_empty());
+        hooks().run(&InitializationType::ProvisionedConcurrency).await;
+        assert_eq!(vec!["cache"], *calls.lock().unwrap());
+    }
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "derive")]
+
+mod with_context {
+    use lambda_runtime::{test::FakeContext, Context, Error};
+    use serde_json::Value;
+
+    #[lambda_runtime::main(catch_panic)]
+    async fn handler(event: Value, context: Context) -> Result<Value, Error> {
+        assert!(!context.request_id.is_empty());
+        Ok(event)
+    }
+
+    #[tokio::test]
+    async fn generate_main_and_keep_the_handler() {
+        let _: fn() -> Result<(), Error> = main;
+        let context = FakeContext::builder().request_id("request").build();
+        let response = handler(Value::from("ping"), context).await.unwrap();
+        assert_eq!(Value::from("ping"), response);
+    }
+}
+
+mod streaming {
+    use lambda_runtime::{Error, EventBody};
+
+    #[lambda_runtime::lambda_handler(streaming)]
+    async fn main(event: EventBody) -> Result<usize, Error> {
+        Ok(event.collect().await?.len())
+    }
+
+    #[tokio::test]
+    async fn rename_a_handler_named_main() {
+        let _: fn() -> Result<(), Error> = main;
+        let len = __lambda_handler(EventBody::from(bytes::Bytes::from_static(b"event")))
+            .await
+            .unwrap();
+        assert_eq!(5, len);
+    }
+}